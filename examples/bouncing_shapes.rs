@@ -0,0 +1,316 @@
+//! Squares bouncing around the window, instrumented with labeled counters,
+//! gauges with units, and histograms of system timings, and a dashboard
+//! that's configured in code.
+//!
+//! Controls:
+//! - Space: spawn more shapes, annotating the plots
+//! - Backspace: despawn half of the shapes
+//! - P: pause or resume all plots
+//! - O: show or hide the overlay
+//! - F1: show or hide the dashboard
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::EguiPlugin;
+use bevy_metrics_dashboard::{
+    core_metrics::names,
+    metrics_util::MetricKind,
+    plots::{
+        CounterPlotConfig, GaugePlotConfig, HistogramDisplay, HistogramPlotConfig,
+        MetricPlotConfig, SpikeDetectorConfig,
+    },
+    registry::{MetricKey, MetricsRegistry},
+    transform::{PlotTransform, UnitConversion},
+    CoreMetricsPlugin, DashboardAnnotation, DashboardPlugin, DashboardWindow, OverlayCorner,
+    OverlayPlugin, RegistryPlugin,
+};
+use metrics::{
+    counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Key, Label,
+    Unit,
+};
+use rand::Rng;
+use std::time::Instant;
+
+const SHAPES: &str = "shapes/count";
+const COVERAGE: &str = "shapes/coverage";
+const SPEED: &str = "shapes/speed";
+const BOUNCES: &str = "shapes/bounces";
+const SPAWNED: &str = "shapes/spawned";
+const SYSTEM_TIME: &str = "systems/duration";
+
+const WALLS: [&str; 4] = ["left", "right", "bottom", "top"];
+const SYSTEMS: [&str; 3] = ["move_shapes", "bounce_off_walls", "measure_shapes"];
+
+/// More shapes than this are marked as a spike.
+const MAX_SHAPES: f64 = 300.0;
+
+const SPAWN_BATCH: usize = 25;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin {
+            enable_multipass_for_primary_context: true,
+        })
+        .add_plugins(RegistryPlugin::default())
+        .add_plugins(CoreMetricsPlugin)
+        .add_plugins(
+            DashboardPlugin::new()
+                .toggle_key(KeyCode::F1)
+                // The game is controlled with the keyboard.
+                .search_hotkeys(false),
+        )
+        .add_plugins(OverlayPlugin::new().toggle_key(KeyCode::KeyO))
+        .add_systems(Startup, (describe_metrics, setup, create_dashboard).chain())
+        .add_systems(
+            Update,
+            (
+                (move_shapes, bounce_off_walls, measure_shapes).chain(),
+                spawn_and_despawn,
+                toggle_pause,
+            ),
+        )
+        .run();
+}
+
+#[derive(Clone, Copy, Component)]
+enum Shape {
+    Small,
+    Large,
+}
+
+impl Shape {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Large => "large",
+        }
+    }
+
+    fn size(self) -> f32 {
+        match self {
+            Self::Small => 16.0,
+            Self::Large => 48.0,
+        }
+    }
+}
+
+#[derive(Component, Deref, DerefMut)]
+struct Velocity(Vec2);
+
+fn describe_metrics() {
+    describe_gauge!(SHAPES, Unit::Count, "Shapes on screen");
+    describe_gauge!(
+        COVERAGE,
+        Unit::Percent,
+        "How much of the window the shapes cover, ignoring overlaps"
+    );
+    describe_histogram!(SPEED, "Speed of each shape, in pixels per second");
+    describe_counter!(BOUNCES, Unit::Count, "Bounces, by wall and shape");
+    describe_counter!(SPAWNED, Unit::Count, "Shapes spawned, by shape");
+    describe_histogram!(SYSTEM_TIME, Unit::Seconds, "How long each system took");
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+    spawn_shapes(&mut commands, 4 * SPAWN_BATCH);
+}
+
+/// Open plots for the interesting metrics, grouped by topic, so the
+/// dashboard doesn't start empty.
+fn create_dashboard(mut commands: Commands, registry: Res<MetricsRegistry>) {
+    let mut window = DashboardWindow::new("Bouncing Shapes");
+    let mut add = |key, unit: Option<Unit>, config, group: &str| {
+        window.add_plot_with_config(&registry, key, unit.map(Into::into), config);
+        let plot = window.plots_mut().last_mut().unwrap();
+        plot.set_group(Some(group.to_owned()));
+    };
+
+    add(
+        key(names::FRAME_TIME, MetricKind::Gauge, &[]),
+        Some(Unit::Milliseconds),
+        MetricPlotConfig::Gauge(GaugePlotConfig {
+            smoothing_weight: 0.5,
+            ..default()
+        }),
+        "Performance",
+    );
+    // Timings are recorded in seconds, but read better in milliseconds.
+    for system in SYSTEMS {
+        add(
+            key(SYSTEM_TIME, MetricKind::Histogram, &[("system", system)]),
+            Some(Unit::Seconds),
+            MetricPlotConfig::Histogram(HistogramPlotConfig {
+                display: HistogramDisplay::FanChart,
+                transform: PlotTransform {
+                    conversion: Some(UnitConversion {
+                        from: Unit::Seconds,
+                        to: Unit::Milliseconds,
+                    }),
+                    ..default()
+                },
+                ..default()
+            }),
+            "Performance",
+        );
+    }
+
+    add(
+        key(SHAPES, MetricKind::Gauge, &[]),
+        Some(Unit::Count),
+        MetricPlotConfig::Gauge(GaugePlotConfig {
+            smoothing_weight: 0.0,
+            spike_detection: Some(SpikeDetectorConfig {
+                absolute_threshold: Some(MAX_SHAPES),
+                ..default()
+            }),
+            ..default()
+        }),
+        "Shapes",
+    );
+    add(
+        key(COVERAGE, MetricKind::Gauge, &[]),
+        Some(Unit::Percent),
+        MetricPlotConfig::Gauge(GaugePlotConfig::dial()),
+        "Shapes",
+    );
+    add(
+        key(SPEED, MetricKind::Histogram, &[]),
+        None,
+        MetricPlotConfig::Histogram(default()),
+        "Shapes",
+    );
+
+    for shape in [Shape::Small, Shape::Large] {
+        for wall in WALLS {
+            add(
+                key(
+                    BOUNCES,
+                    MetricKind::Counter,
+                    &[("wall", wall), ("shape", shape.label())],
+                ),
+                Some(Unit::Count),
+                MetricPlotConfig::Counter(CounterPlotConfig {
+                    derivative: true,
+                    ..default()
+                }),
+                "Bounces",
+            );
+        }
+    }
+
+    // Show the frame time in the overlay too, so it stays visible while the
+    // dashboard is hidden.
+    window.plots_mut()[0].set_overlay(Some(OverlayCorner::TopRight));
+    commands.spawn(window);
+}
+
+fn key(name: &'static str, kind: MetricKind, labels: &[(&'static str, &'static str)]) -> MetricKey {
+    let labels: Vec<_> = labels.iter().map(|&(k, v)| Label::new(k, v)).collect();
+    MetricKey::new(Key::from_parts(name, labels), kind)
+}
+
+fn spawn_shapes(commands: &mut Commands, n: usize) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..n {
+        let shape = if rng.gen_bool(0.7) {
+            Shape::Small
+        } else {
+            Shape::Large
+        };
+        let position = Vec2::new(rng.gen_range(-200.0..200.0), rng.gen_range(-200.0..200.0));
+        let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+        let color = Color::hsl(rng.gen_range(0.0..360.0), 0.7, 0.6);
+        commands.spawn((
+            shape,
+            Velocity(direction * rng.gen_range(50.0..300.0)),
+            Sprite::from_color(color, Vec2::splat(shape.size())),
+            Transform::from_translation(position.extend(0.0)),
+        ));
+        counter!(SPAWNED, "shape" => shape.label()).increment(1);
+    }
+}
+
+fn record_duration(system: &'static str, start: Instant) {
+    histogram!(SYSTEM_TIME, "system" => system).record(start.elapsed().as_secs_f64());
+}
+
+fn move_shapes(time: Res<Time>, mut shapes: Query<(&mut Transform, &Velocity)>) {
+    let start = Instant::now();
+    for (mut transform, velocity) in &mut shapes {
+        transform.translation += (**velocity * time.delta_secs()).extend(0.0);
+    }
+    record_duration("move_shapes", start);
+}
+
+fn bounce_off_walls(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut shapes: Query<(&mut Transform, &mut Velocity, &Shape)>,
+) {
+    let start = Instant::now();
+    for (mut transform, mut velocity, shape) in &mut shapes {
+        let max = (window.size() - Vec2::splat(shape.size())) / 2.0;
+        let position = transform.translation.truncate();
+        let wall = if position.x < -max.x && velocity.x < 0.0 {
+            velocity.x = -velocity.x;
+            WALLS[0]
+        } else if position.x > max.x && velocity.x > 0.0 {
+            velocity.x = -velocity.x;
+            WALLS[1]
+        } else if position.y < -max.y && velocity.y < 0.0 {
+            velocity.y = -velocity.y;
+            WALLS[2]
+        } else if position.y > max.y && velocity.y > 0.0 {
+            velocity.y = -velocity.y;
+            WALLS[3]
+        } else {
+            continue;
+        };
+        let clamped = position.clamp(-max.max(Vec2::ZERO), max.max(Vec2::ZERO));
+        transform.translation = clamped.extend(transform.translation.z);
+        counter!(BOUNCES, "wall" => wall, "shape" => shape.label()).increment(1);
+    }
+    record_duration("bounce_off_walls", start);
+}
+
+fn measure_shapes(
+    window: Single<&Window, With<PrimaryWindow>>,
+    shapes: Query<(&Shape, &Velocity)>,
+) {
+    let start = Instant::now();
+    let mut area = 0.0;
+    for (shape, velocity) in &shapes {
+        area += shape.size().powi(2);
+        histogram!(SPEED).record(velocity.length());
+    }
+    gauge!(SHAPES).set(shapes.iter().len() as f64);
+    gauge!(COVERAGE).set((100.0 * area / window.size().element_product()).min(100.0));
+    record_duration("measure_shapes", start);
+}
+
+fn spawn_and_despawn(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    shapes: Query<Entity, With<Shape>>,
+    mut annotations: EventWriter<DashboardAnnotation>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        spawn_shapes(&mut commands, SPAWN_BATCH);
+        annotations.write(DashboardAnnotation::new(format!("+{SPAWN_BATCH} shapes")));
+    }
+    if keys.just_pressed(KeyCode::Backspace) {
+        for entity in shapes.iter().step_by(2) {
+            commands.entity(entity).despawn();
+        }
+        annotations.write(DashboardAnnotation::new("Despawned half"));
+    }
+}
+
+fn toggle_pause(keys: Res<ButtonInput<KeyCode>>, mut windows: Query<&mut DashboardWindow>) {
+    if !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    for mut window in &mut windows {
+        let paused = !window.config().paused;
+        window.set_paused(paused);
+    }
+}
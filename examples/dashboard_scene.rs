@@ -0,0 +1,85 @@
+//! Saves a dashboard window in a Bevy scene and spawns it in another app,
+//! like an editor would with its project. Run with
+//! `cargo run --example dashboard_scene`.
+
+use bevy::prelude::*;
+use bevy::scene::{ron, serde::SceneDeserializer, DynamicSceneBuilder};
+use bevy_metrics_dashboard::metrics_util::MetricKind;
+use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlotConfig};
+use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+use bevy_metrics_dashboard::{
+    DashboardSceneAppExt, DashboardSceneState, DashboardWindow, RegistryPlugin,
+};
+use metrics::Key;
+use serde::de::DeserializeSeed;
+
+fn main() {
+    let path = std::env::temp_dir().join("dashboard.scn.ron");
+
+    let mut editor = app();
+    let registry = editor.world().resource::<MetricsRegistry>().clone();
+    let mut window = DashboardWindow::new("Metrics");
+    for (name, labels) in [("enemies", vec![]), ("spawns", vec![("team", "red")])] {
+        let key = Key::from_parts(name, labels.as_slice());
+        let key = MetricKey::new(key, MetricKind::Gauge);
+        let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+            window_size: Some(300),
+            ..default()
+        });
+        window.add_plot_with_config(&registry, key, None, config);
+    }
+    window.plots_mut()[1].set_group(Some("Spawning".to_owned()));
+    editor
+        .world_mut()
+        .spawn((window, DashboardSceneState::default()));
+
+    // Bring the states up to date before saving.
+    editor
+        .world_mut()
+        .run_system_cached(DashboardSceneState::capture_all)
+        .unwrap();
+    let world = editor.world_mut();
+    let mut windows = world.query_filtered::<Entity, With<DashboardWindow>>();
+    let entities: Vec<_> = windows.iter(world).collect();
+    let scene = DynamicSceneBuilder::from_world(world)
+        .extract_entities(entities.into_iter())
+        .build();
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let ron = scene.serialize(&type_registry.read()).unwrap();
+    std::fs::write(&path, &ron).unwrap();
+    println!("Saved {}:\n{ron}", path.display());
+
+    let mut game = app();
+    let ron = std::fs::read_to_string(&path).unwrap();
+    let type_registry = game.world().resource::<AppTypeRegistry>().clone();
+    let mut deserializer = ron::de::Deserializer::from_str(&ron).unwrap();
+    let scene = SceneDeserializer {
+        type_registry: &type_registry.read(),
+    }
+    .deserialize(&mut deserializer)
+    .unwrap();
+    scene
+        .write_to_world(game.world_mut(), &mut default())
+        .unwrap();
+    // The plots are restored when the state is added.
+    game.update();
+
+    let world = game.world_mut();
+    for window in world.query::<&DashboardWindow>().iter(world) {
+        println!("Restored window {:?}:", window.title());
+        for plot in window.plots() {
+            println!(
+                "  {} in group {:?}",
+                plot.key().name_with_labels(),
+                plot.group()
+            );
+        }
+    }
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(RegistryPlugin::local(MetricsRegistry::new()))
+        .register_dashboard_scene_types();
+    app
+}
@@ -0,0 +1,38 @@
+//! A dashboard window that starts out in its own OS window.
+//!
+//! Use the "Attach" button, or close the OS window, to move it over the game
+//! window, and "Detach" to move it back.
+
+use bevy::prelude::*;
+use bevy_egui::EguiPlugin;
+use bevy_metrics_dashboard::{
+    CoreMetricsPlugin, DashboardPlugin, DashboardWindow, DetachedDashboard, RegistryPlugin,
+};
+use metrics::{describe_gauge, gauge, Unit};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin {
+            enable_multipass_for_primary_context: true,
+        })
+        .add_plugins(RegistryPlugin::default())
+        .add_plugins(CoreMetricsPlugin)
+        .add_plugins(DashboardPlugin::default())
+        .add_systems(Startup, create_dashboard)
+        .add_systems(Update, update_metrics)
+        .run();
+}
+
+fn create_dashboard(mut commands: Commands) {
+    describe_gauge!("wave", Unit::Count, "A sine wave");
+    commands.spawn(Camera2d);
+    commands.spawn((
+        DashboardWindow::new("Metrics Dashboard"),
+        DetachedDashboard::default(),
+    ));
+}
+
+fn update_metrics(time: Res<Time>) {
+    gauge!("wave").set(time.elapsed_secs_f64().sin());
+}
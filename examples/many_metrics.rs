@@ -13,7 +13,7 @@ fn main() {
             enable_multipass_for_primary_context: true,
         })
         .add_plugins(RegistryPlugin::default())
-        .add_plugins(DashboardPlugin)
+        .add_plugins(DashboardPlugin::default())
         .add_systems(Startup, (describe_metrics, create_dashboard))
         .add_systems(Update, update_metrics)
         .run();
@@ -0,0 +1,49 @@
+//! Runs a headless app against two budgets, one that passes and one that
+//! fails, and exits with a failure code like a CI job would.
+//!
+//! Run with `cargo run --example metric_budget`.
+
+use bevy::prelude::*;
+use bevy_metrics_dashboard::metric_budget::{
+    BudgetWindow, MetricBudget, MetricBudgetPlugin, MetricBudgets,
+};
+use bevy_metrics_dashboard::RegistryPlugin;
+use metrics::{gauge, histogram};
+use rand::Rng;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, RegistryPlugin::default()))
+        .add_plugins(
+            MetricBudgetPlugin::new()
+                // Passes: simulated frames take 8 to 12 ms.
+                .budget(
+                    MetricBudget::new("sim/frame_time_ms")
+                        .p95_below(16.0)
+                        .mean_below(14.0),
+                )
+                // Fails: the entity count keeps growing.
+                .budget(
+                    MetricBudget::new("sim/entities")
+                        .window(BudgetWindow::LastSeconds(0.5))
+                        .max_below(500.0),
+                ),
+        )
+        .add_systems(Update, simulate);
+
+    for _ in 0..300 {
+        app.update();
+    }
+
+    let report = app.world().resource::<MetricBudgets>().evaluate();
+    println!("{report}");
+    report.exit_code()
+}
+
+fn simulate(mut n_entities: Local<u32>) {
+    let mut rng = rand::thread_rng();
+    histogram!("sim/frame_time_ms").record(rng.gen_range(8.0..12.0));
+    *n_entities += 3;
+    gauge!("sim/entities").set(f64::from(*n_entities));
+}
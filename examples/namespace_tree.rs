@@ -1,9 +1,8 @@
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
-use bevy_metrics_dashboard::{DashboardPlugin, DashboardWindow, RegistryPlugin};
-use metrics::{
-    counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit,
-};
+use bevy_metrics_dashboard::namespace::Namespace;
+use bevy_metrics_dashboard::{metric_path, DashboardPlugin, DashboardWindow, RegistryPlugin};
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, Unit};
 use rand::Rng;
 
 fn main() {
@@ -13,30 +12,34 @@ fn main() {
             enable_multipass_for_primary_context: true,
         })
         .add_plugins(RegistryPlugin::default())
-        .add_plugins(DashboardPlugin)
+        .add_plugins(DashboardPlugin::default())
         .add_systems(Startup, (describe_metrics, create_dashboard))
         .add_systems(Update, update_metrics)
         .run();
 }
 
 fn describe_metrics() {
-    describe_counter!("foo::bar::baz", Unit::Count, "Lorem ipsum");
-    describe_counter!("foo::bar::fizz", Unit::Count, "Lorem ipsum");
-    describe_counter!("foo::foo::baz", Unit::Count, "Lorem ipsum");
-    describe_counter!("fizz::bat", Unit::Count, "Lorem ipsum");
+    for name in [
+        metric_path!("foo", "bar", "baz"),
+        metric_path!("foo", "bar", "fizz"),
+        metric_path!("foo", "foo", "baz"),
+        metric_path!("fizz", "bat"),
+    ] {
+        describe_counter!(name, Unit::Count, "Lorem ipsum");
+        describe_gauge!(name, Unit::Count, "Lorem ipsum");
+        describe_histogram!(name, Unit::Count, "Lorem ipsum");
+    }
 
-    describe_gauge!("foo::bar::baz", Unit::Count, "Lorem ipsum");
-    describe_gauge!("foo::bar::fizz", Unit::Count, "Lorem ipsum");
-    describe_gauge!("foo::foo::baz", Unit::Count, "Lorem ipsum");
-    describe_gauge!("fizz::bat", Unit::Count, "Lorem ipsum");
-
-    describe_histogram!("foo::bar::baz", Unit::Count, "Lorem ipsum");
-    describe_histogram!("foo::bar::fizz", Unit::Count, "Lorem ipsum");
-    describe_histogram!("foo::foo::baz", Unit::Count, "Lorem ipsum");
-    describe_histogram!("fizz::bat", Unit::Count, "Lorem ipsum");
-
-    describe_counter!("fizz::partial::collapsed", Unit::Count, "Lorem ipsum");
-    describe_counter!("fully::collapsed::path", Unit::Count, "Lorem ipsum");
+    describe_counter!(
+        metric_path!("fizz", "partial", "collapsed"),
+        Unit::Count,
+        "Lorem ipsum"
+    );
+    describe_counter!(
+        metric_path!("fully", "collapsed", "path"),
+        Unit::Count,
+        "Lorem ipsum"
+    );
 
     // Invalid paths.
     describe_counter!("edge::case::", Unit::Count, "Lorem ipsum");
@@ -52,30 +55,58 @@ fn describe_metrics() {
     describe_counter!("", Unit::Count, "Lorem ipsum");
 }
 
+/// The namespaces metrics are written in, keeping their handles.
+struct Namespaces {
+    foo_bar: Namespace,
+    foo_foo: Namespace,
+    fizz: Namespace,
+    fizz_partial: Namespace,
+}
+
+impl Default for Namespaces {
+    fn default() -> Self {
+        let foo_ns = Namespace::new("foo");
+        Self {
+            foo_bar: foo_ns.child("bar"),
+            foo_foo: foo_ns.child("foo"),
+            fizz: Namespace::new("fizz"),
+            fizz_partial: Namespace::new(metric_path!("fizz", "partial")),
+        }
+    }
+}
+
 fn create_dashboard(mut commands: Commands) {
     commands.spawn(DashboardWindow::new("Dashboard"));
 }
 
-fn update_metrics() {
+fn update_metrics(namespaces: Local<Namespaces>) {
     let mut rng = rand::thread_rng();
+    let Namespaces {
+        foo_bar,
+        foo_foo,
+        fizz,
+        fizz_partial,
+    } = &*namespaces;
 
-    counter!("foo::bar::baz").increment(rng.gen_range(0..10));
-    counter!("foo::bar::fizz").increment(rng.gen_range(0..10));
-    counter!("foo::foo::baz").increment(rng.gen_range(0..10));
-    counter!("fizz::bat").increment(rng.gen_range(0..10));
+    foo_bar.counter("baz").increment(rng.gen_range(0..10));
+    foo_bar.counter("fizz").increment(rng.gen_range(0..10));
+    foo_foo.counter("baz").increment(rng.gen_range(0..10));
+    fizz.counter("bat").increment(rng.gen_range(0..10));
 
-    gauge!("foo::bar::baz").set(rng.gen_range(0.0..10.0));
-    gauge!("foo::bar::fizz").set(rng.gen_range(0.0..10.0));
-    gauge!("foo::foo::baz").set(rng.gen_range(0.0..10.0));
-    gauge!("fizz::bat").set(rng.gen_range(0.0..10.0));
+    foo_bar.gauge("baz").set(rng.gen_range(0.0..10.0));
+    foo_bar.gauge("fizz").set(rng.gen_range(0.0..10.0));
+    foo_foo.gauge("baz").set(rng.gen_range(0.0..10.0));
+    fizz.gauge("bat").set(rng.gen_range(0.0..10.0));
 
-    histogram!("foo::bar::baz").record(rng.gen_range(0.0..10.0));
-    histogram!("foo::bar::fizz").record(rng.gen_range(0.0..10.0));
-    histogram!("foo::foo::baz").record(rng.gen_range(0.0..10.0));
-    histogram!("fizz::bat").record(rng.gen_range(0.0..10.0));
+    foo_bar.histogram("baz").record(rng.gen_range(0.0..10.0));
+    foo_bar.histogram("fizz").record(rng.gen_range(0.0..10.0));
+    foo_foo.histogram("baz").record(rng.gen_range(0.0..10.0));
+    fizz.histogram("bat").record(rng.gen_range(0.0..10.0));
 
-    counter!("fizz::partial::collapsed").increment(rng.gen_range(0..10));
-    counter!("fully::collapsed::path").increment(rng.gen_range(0..10));
+    fizz_partial
+        .counter("collapsed")
+        .increment(rng.gen_range(0..10));
+    counter!(metric_path!("fully", "collapsed", "path")).increment(rng.gen_range(0..10));
 
     // Invalid paths.
     counter!("edge::case::").increment(rng.gen_range(0..10));
@@ -16,7 +16,7 @@ fn main() {
         .add_plugins(RegistryPlugin::default())
         .add_plugins(CoreMetricsPlugin)
         .add_plugins(RenderMetricsPlugin)
-        .add_plugins(DashboardPlugin)
+        .add_plugins(DashboardPlugin::default())
         .add_systems(Startup, (create_dashboard, setup))
         .add_systems(Update, move_camera)
         .run();
@@ -67,6 +67,8 @@ fn setup(mut commands: Commands, assets: Res<AssetServer>) {
 }
 
 fn move_camera(time: Res<Time>, mut camera_query: Query<&mut Transform, With<Camera>>) {
-    let mut camera_transform = camera_query.single_mut();
+    let Ok(mut camera_transform) = camera_query.single_mut() else {
+        return;
+    };
     camera_transform.rotate_z(time.delta_secs() * 0.1);
 }
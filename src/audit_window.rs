@@ -0,0 +1,224 @@
+use crate::egui::{self, Grid, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::registry::{DescriptionKey, HighCardinality, MetricKey, MetricsAudit, MetricsRegistry};
+use bevy::prelude::*;
+use metrics_util::MetricKind;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "bevy_egui")]
+use crate::DashboardConfig;
+
+/// A window that lists descriptions without registered metrics, registered
+/// metrics without descriptions, hot histograms and metrics with too many
+/// label combinations.
+///
+/// See [`MetricsRegistry::audit`]. The first two lists are computed when
+/// the window is first drawn, and again when "Refresh" is clicked. The
+/// [hot histograms](MetricsRegistry::hot_histograms) and [high
+/// cardinality](MetricsRegistry::high_cardinality) metrics are always
+/// current.
+#[derive(Component, Reflect)]
+#[reflect(Component, from_reflect = false)]
+pub struct AuditWindow {
+    title: String,
+    #[reflect(ignore)]
+    id: egui::Id,
+    #[reflect(ignore)]
+    audit: Option<MetricsAudit>,
+}
+
+impl AuditWindow {
+    /// Create a new window.
+    pub fn new(title: impl Into<String>) -> Self {
+        static WINDOW_ID: AtomicU64 = AtomicU64::new(0);
+        let id = WINDOW_ID.fetch_add(1, Ordering::Relaxed);
+        let title = title.into();
+        let id = format!("{title} {id}").into();
+        Self {
+            title,
+            id,
+            audit: None,
+        }
+    }
+
+    /// The window's title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The window's [`egui::Id`].
+    pub fn id(&self) -> &egui::Id {
+        &self.id
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// Bevy system that draws all audit window entities.
+    pub fn draw_all(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        config: Res<DashboardConfig>,
+        localization: Res<DashboardLocalization>,
+        mut ctxts: bevy_egui::EguiContexts,
+        mut windows: Query<(Entity, &mut Self)>,
+    ) {
+        if !config.visible {
+            return;
+        }
+
+        let ctxt = ctxts.ctx_mut();
+        for (entity, mut window) in &mut windows {
+            let mut open = true;
+            localization.scope(ctxt, |ctxt| {
+                config.theme.scope(ctxt, |ctxt| {
+                    egui::Window::new(&window.title)
+                        .id(window.id)
+                        .open(&mut open)
+                        .show(ctxt, |ui| window.draw(&registry, ui));
+                });
+            });
+            if !open {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    /// Draw the widget and accept user input.
+    pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        if ui.button(l10n.get(UiText::Refresh)).clicked() {
+            self.audit = None;
+        }
+        let audit = self.audit.get_or_insert_with(|| registry.audit());
+        let hot = registry.hot_histograms();
+        let high_cardinality = registry.high_cardinality();
+        let evicted = registry.evicted_count();
+        if evicted > 0 {
+            ui.weak(l10n.format(UiText::AuditEvicted, &[("count", &evicted)]))
+                .on_hover_text(l10n.get(UiText::AuditEvictedHint));
+        }
+        if audit.is_empty() && hot.is_empty() && high_cardinality.is_empty() {
+            ui.label(l10n.get(UiText::AuditClean));
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let unregistered: Vec<_> = audit.unregistered.iter().map(|k| (k, None)).collect();
+            audit_section(
+                "unregistered",
+                &l10n.format(UiText::AuditUnregistered, &[("count", &unregistered.len())]),
+                &unregistered,
+                ui,
+            );
+            let undescribed: Vec<_> = audit
+                .undescribed
+                .iter()
+                .map(|(k, series)| (k, Some(*series)))
+                .collect();
+            audit_section(
+                "undescribed",
+                &l10n.format(UiText::AuditUndescribed, &[("count", &undescribed.len())]),
+                &undescribed,
+                ui,
+            );
+            hot_section(&hot, ui);
+            cardinality_section(&high_cardinality, ui);
+        });
+    }
+}
+
+/// A collapsible table of hot histograms.
+fn hot_section(hot: &[(metrics::Key, usize)], ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let heading = l10n.format(UiText::AuditHot, &[("count", &hot.len())]);
+    ui.collapsing(heading, |ui| {
+        if hot.is_empty() {
+            ui.weak(l10n.get(UiText::Nothing));
+            return;
+        }
+        Grid::new("hot").striped(true).show(ui, |ui| {
+            for (key, samples) in hot {
+                let name = MetricKey::new(key.clone(), MetricKind::Histogram).name_with_labels();
+                ui.label(&name);
+                ui.weak(l10n.format(UiText::SamplesPerFrame, &[("samples", samples)]));
+                if ui.small_button(l10n.get(UiText::Copy)).clicked() {
+                    ui.ctx().copy_text(name);
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// A collapsible table of metrics with too many label combinations.
+fn cardinality_section(high: &[HighCardinality], ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let heading = l10n.format(UiText::AuditHighCardinality, &[("count", &high.len())]);
+    ui.collapsing(heading, |ui| {
+        if high.is_empty() {
+            ui.weak(l10n.get(UiText::Nothing));
+            return;
+        }
+        Grid::new("high cardinality").striped(true).show(ui, |ui| {
+            for metric in high {
+                let name = metric.key.name.as_str();
+                let examples: Vec<_> = metric
+                    .examples
+                    .iter()
+                    .map(|key| MetricKey::new(key.clone(), metric.key.kind).name_with_labels())
+                    .collect();
+                ui.label(name).on_hover_text(format!(
+                    "{}\n{}",
+                    l10n.get(UiText::RecentLabelCombinations),
+                    examples.join("\n")
+                ));
+                ui.weak(l10n.get(UiText::kind(metric.key.kind)));
+                ui.weak(l10n.format(
+                    UiText::LabelCombinations,
+                    &[("count", &metric.combinations), ("limit", &metric.limit)],
+                ));
+                if metric.overflowed > 0 {
+                    ui.weak(l10n.format(
+                        UiText::OverflowedRegistrations,
+                        &[("count", &metric.overflowed)],
+                    ))
+                    .on_hover_text(l10n.get(UiText::OverflowedRegistrationsHint));
+                } else {
+                    ui.weak("");
+                }
+                if ui.small_button(l10n.get(UiText::Copy)).clicked() {
+                    ui.ctx().copy_text(name.to_owned());
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// A collapsible table of metric names, each with a copy button.
+fn audit_section(id: &str, heading: &str, keys: &[(&DescriptionKey, Option<usize>)], ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    ui.collapsing(heading, |ui| {
+        if keys.is_empty() {
+            ui.weak(l10n.get(UiText::Nothing));
+            return;
+        }
+        if ui.button(l10n.get(UiText::CopyAll)).clicked() {
+            let names: Vec<_> = keys.iter().map(|(k, _)| k.name.as_str()).collect();
+            ui.ctx().copy_text(names.join("\n"));
+        }
+        Grid::new(id).striped(true).show(ui, |ui| {
+            for (key, series) in keys {
+                ui.label(key.name.as_str());
+                ui.weak(l10n.get(UiText::kind(key.kind)));
+                match series {
+                    Some(n) => ui.weak(l10n.format(UiText::SeriesCount, &[("count", n)])),
+                    None => ui.weak(""),
+                };
+                if ui.small_button(l10n.get(UiText::Copy)).clicked() {
+                    ui.ctx().copy_text(key.name.as_str().to_owned());
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
@@ -0,0 +1,351 @@
+//! Widget for comparing the latest values of several metrics against a budget.
+
+use crate::egui::{
+    self, pos2, vec2, Color32, CornerRadius, DragValue, Rect, Sense, Stroke, StrokeKind, Ui,
+};
+use crate::latest_value::LatestValue;
+use crate::localization::{DashboardLocalization, UiText};
+use crate::registry::{MetricKey, MetricsRegistry};
+use crate::search_bar::{MetricSearchBar, SearchSelection};
+use crate::snapshot::FrameMetricsSnapshot;
+use crate::theme::{DashboardColor, ThemePalette};
+use crate::widget::{DashboardWidget, WidgetContext};
+use bevy::prelude::Reflect;
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
+
+/// Configuration for one [`BudgetBar`].
+#[derive(Clone, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct BudgetBarConfig {
+    /// The metrics stacked in the bar, in order.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "layout", serde(with = "crate::layout::metric_keys"))]
+    pub metrics: Vec<MetricKey>,
+    /// The total budget that the sum of all metrics is compared against.
+    pub budget: f64,
+    /// When the remaining headroom drops below this fraction of the budget,
+    /// the bar uses [`Self::warning_color`].
+    pub warning_headroom: f64,
+    #[allow(missing_docs)]
+    pub ok_color: DashboardColor,
+    #[allow(missing_docs)]
+    pub warning_color: DashboardColor,
+    #[allow(missing_docs)]
+    pub over_budget_color: DashboardColor,
+}
+
+impl Default for BudgetBarConfig {
+    fn default() -> Self {
+        Self {
+            metrics: Vec::new(),
+            budget: 16.6,
+            warning_headroom: 0.2,
+            ok_color: DashboardColor::from_rgb(60, 170, 60),
+            warning_color: DashboardColor::from_rgb(210, 170, 40),
+            over_budget_color: DashboardColor::from_rgb(200, 50, 50),
+        }
+    }
+}
+
+impl BudgetBarConfig {
+    /// The color used for a bar whose metrics sum to `total`.
+    pub fn status_color(&self, total: f64) -> Color32 {
+        let headroom = self.budget - total;
+        if headroom < 0.0 {
+            self.over_budget_color.into()
+        } else if headroom < self.warning_headroom * self.budget {
+            self.warning_color.into()
+        } else {
+            self.ok_color.into()
+        }
+    }
+}
+
+/// A horizontal stacked bar of the latest values of several metrics, drawn
+/// against a budget line.
+///
+/// Counters contribute their increment since the last update, gauges their
+/// current value, and histograms the mean of their samples this frame.
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+pub struct BudgetBar {
+    name: String,
+    config: BudgetBarConfig,
+    #[reflect(ignore)]
+    segments: Vec<LatestValue>,
+    #[reflect(ignore)]
+    search_bar: MetricSearchBar,
+}
+
+impl BudgetBar {
+    /// Create a new budget bar widget.
+    pub fn new(name: impl Into<String>, config: BudgetBarConfig) -> Self {
+        let segments = config
+            .metrics
+            .iter()
+            .cloned()
+            .map(LatestValue::new)
+            .collect();
+        Self {
+            name: name.into(),
+            config,
+            segments,
+            search_bar: Default::default(),
+        }
+    }
+
+    /// Name of the widget.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This widget's configuration.
+    pub fn config(&self) -> &BudgetBarConfig {
+        &self.config
+    }
+
+    /// Sample the latest value of each metric.
+    ///
+    /// This should run in the [`SampleMetricsSet`](crate::SampleMetricsSet),
+    /// like [`MetricPlot::update`](crate::plots::MetricPlot::update).
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        for segment in &mut self.segments {
+            segment.update(snapshot);
+        }
+        self.search_bar.update(snapshot);
+    }
+
+    /// The metrics sampled by [`Self::update`].
+    pub fn consumed_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        self.config
+            .metrics
+            .iter()
+            .chain(self.search_bar.previewed_metrics())
+    }
+
+    /// Draw the widget using `ui`.
+    pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) {
+        let total: f64 = self.segments.iter().filter_map(LatestValue::latest).sum();
+        let status_color = self.config.status_color(total);
+        let l10n = DashboardLocalization::of(ui);
+        ui.colored_label(
+            status_color,
+            format!(
+                "{} / {}",
+                l10n.number(total, 3),
+                l10n.number(self.config.budget, 3)
+            ),
+        );
+
+        self.draw_bar(total, status_color, ui);
+        self.draw_legend(status_color, ui);
+
+        ui.collapsing(l10n.get(UiText::Settings), |ui| {
+            self.configure_ui(registry, ui);
+        });
+    }
+
+    fn draw_bar(&self, total: f64, status_color: Color32, ui: &mut Ui) {
+        const HEIGHT: f32 = 24.0;
+
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(ui.available_width(), HEIGHT), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let palette = ThemePalette::of(ui);
+        painter.rect_stroke(
+            rect,
+            CornerRadius::ZERO,
+            Stroke::new(1.0, palette.weak),
+            StrokeKind::Inside,
+        );
+
+        // Leave some room past the budget line so overflow is visible.
+        let scale_max = 1.1 * self.config.budget.max(total).max(f64::EPSILON);
+        let x_for = |value: f64| rect.left() + (value / scale_max) as f32 * rect.width();
+
+        let mut hovered = None;
+        let mut start = 0.0;
+        for (i, segment) in self.segments.iter().enumerate() {
+            let Some(value) = segment.latest() else {
+                continue;
+            };
+            let end = start + value.max(0.0);
+            let segment_rect = Rect::from_min_max(
+                pos2(x_for(start), rect.top()),
+                pos2(x_for(end), rect.bottom()),
+            );
+            painter.rect_filled(
+                segment_rect,
+                CornerRadius::ZERO,
+                segment_color(status_color, i),
+            );
+            painter.rect_stroke(
+                segment_rect,
+                CornerRadius::ZERO,
+                Stroke::new(1.0, palette.outline),
+                StrokeKind::Inside,
+            );
+            if response
+                .hover_pos()
+                .is_some_and(|p| segment_rect.contains(p))
+            {
+                hovered = Some((segment, value));
+            }
+            start = end;
+        }
+
+        let budget_x = x_for(self.config.budget);
+        painter.line_segment(
+            [pos2(budget_x, rect.top()), pos2(budget_x, rect.bottom())],
+            Stroke::new(2.0, palette.foreground),
+        );
+
+        if let Some((segment, value)) = hovered {
+            let value = DashboardLocalization::of(ui).number(value, 3);
+            response.on_hover_text(format!("{} = {value}", segment.key().key.name()));
+        }
+    }
+
+    fn draw_legend(&self, status_color: Color32, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        for (i, segment) in self.segments.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let (rect, _) = ui.allocate_exact_size(vec2(12.0, 12.0), Sense::hover());
+                let painter = ui.painter_at(rect);
+                if let Some(value) = segment.latest() {
+                    painter.rect_filled(rect, CornerRadius::ZERO, segment_color(status_color, i));
+                    let value = l10n.number(value, 3);
+                    ui.label(format!("{} = {value}", segment.key().title(None, 0)));
+                } else {
+                    draw_hatched(&painter, rect, ThemePalette::of(ui).weak);
+                    let name = segment.key().title(None, 0);
+                    ui.weak(l10n.format(UiText::Missing, &[("name", &name)]));
+                }
+            });
+        }
+    }
+
+    fn configure_ui(&mut self, registry: &MetricsRegistry, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.add(
+            DragValue::new(&mut self.config.budget)
+                .prefix(l10n.get(UiText::BudgetPrefix))
+                .speed(0.1)
+                .range(0.0..=f64::MAX),
+        );
+        ui.add(
+            DragValue::new(&mut self.config.warning_headroom)
+                .prefix(l10n.get(UiText::WarningHeadroomPrefix))
+                .speed(0.01)
+                .range(0.0..=1.0),
+        );
+        ui.horizontal(|ui| {
+            ui.label(l10n.get(UiText::Colors));
+            self.config.ok_color.edit_ui(ui);
+            self.config.warning_color.edit_ui(ui);
+            self.config.over_budget_color.edit_ui(ui);
+        });
+
+        ui.separator();
+
+        let mut remove = None;
+        for (i, key) in self.config.metrics.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button(l10n.get(UiText::Remove)).clicked() {
+                    remove = Some(i);
+                }
+                ui.label(key.title(None, 0));
+            });
+        }
+        if let Some(i) = remove {
+            self.config.metrics.remove(i);
+            self.segments.remove(i);
+        }
+
+        if let Some(selected) = self
+            .search_bar
+            .show(ui, registry)
+            .map(SearchSelection::into_result)
+        {
+            self.config.metrics.push(selected.key.clone());
+            self.segments.push(LatestValue::new(selected.key));
+        }
+    }
+}
+
+/// Alternate the brightness of neighboring segments so they are distinguishable.
+fn segment_color(status_color: Color32, i: usize) -> Color32 {
+    if i.is_multiple_of(2) {
+        status_color
+    } else {
+        status_color.gamma_multiply(0.7)
+    }
+}
+
+/// Placeholder for a metric without a value this frame.
+fn draw_hatched(painter: &egui::Painter, rect: Rect, color: Color32) {
+    let stroke = Stroke::new(1.0, color);
+    painter.rect_stroke(rect, CornerRadius::ZERO, stroke, StrokeKind::Inside);
+    let step = 4.0;
+    let mut offset = 0.0;
+    while offset < rect.width() + rect.height() {
+        painter.line_segment(
+            [
+                pos2(rect.left() + offset, rect.top()),
+                pos2(rect.left() + offset - rect.height(), rect.bottom()),
+            ],
+            stroke,
+        );
+        offset += step;
+    }
+}
+
+impl Default for BudgetBar {
+    fn default() -> Self {
+        Self::new("Budget Bar", BudgetBarConfig::default())
+    }
+}
+
+impl DashboardWidget for BudgetBar {
+    fn id(&self) -> &str {
+        "budget_bar"
+    }
+
+    fn title(&self, _ctx: &WidgetContext) -> String {
+        self.name.clone()
+    }
+
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext) {
+        self.draw(ctx.registry, ui);
+    }
+
+    fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        BudgetBar::update(self, snapshot);
+    }
+
+    fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+        Box::new(BudgetBar::consumed_metrics(self))
+    }
+
+    #[cfg(feature = "layout")]
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        RawValue::from_rust(&self.config).map(Some)
+    }
+
+    #[cfg(feature = "layout")]
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        let name = std::mem::take(&mut self.name);
+        *self = Self::new(name, config.into_rust()?);
+        Ok(())
+    }
+
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+}
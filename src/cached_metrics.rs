@@ -0,0 +1,174 @@
+use crate::registry::{DescriptionKey, MetricDescription, MetricsRegistry};
+use metrics::{CounterFn, GaugeFn, Unit};
+use metrics_util::{storage::AtomicBucket, MetricKind};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Build a key from a name and label pairs, as passed to the registry's
+/// `cached_*` methods.
+pub(crate) fn key_from_parts(name: &str, labels: &[(&str, &str)]) -> metrics::Key {
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|&(k, v)| metrics::Label::new(k.to_owned(), v.to_owned()))
+        .collect();
+    metrics::Key::from_parts(name.to_owned(), labels)
+}
+
+fn describe(
+    registry: &MetricsRegistry,
+    key: &metrics::Key,
+    kind: MetricKind,
+    unit: Option<Unit>,
+    text: &str,
+) {
+    registry.add_description_if_missing(
+        DescriptionKey {
+            name: key.name().to_owned().into(),
+            kind,
+        },
+        MetricDescription::new(unit, text.to_owned().into()),
+    );
+}
+
+/// A handle to one counter in the [`MetricsRegistry`].
+///
+/// Created by [`MetricsRegistry::cached_counter`]. Unlike the `counter!`
+/// macro, writing through this handle doesn't hash and look up the key, so
+/// it's meant to be stored (e.g. in a component or `Local`) and written
+/// every frame. Cloning is cheap.
+#[derive(Clone)]
+pub struct CachedCounter {
+    key: metrics::Key,
+    atomic: Arc<AtomicU64>,
+}
+
+impl CachedCounter {
+    pub(crate) fn new(key: metrics::Key, atomic: Arc<AtomicU64>) -> Self {
+        Self { key, atomic }
+    }
+
+    #[allow(missing_docs)]
+    pub fn key(&self) -> &metrics::Key {
+        &self.key
+    }
+
+    /// Describe the counter, unless it already has a description.
+    pub fn with_description(
+        self,
+        registry: &MetricsRegistry,
+        unit: Option<Unit>,
+        text: &str,
+    ) -> Self {
+        describe(registry, &self.key, MetricKind::Counter, unit, text);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn increment(&self, value: u64) {
+        CounterFn::increment(&*self.atomic, value);
+    }
+
+    /// Set the counter to `value`, if it's larger than the current value.
+    pub fn absolute(&self, value: u64) {
+        CounterFn::absolute(&*self.atomic, value);
+    }
+
+    /// The current value of the counter.
+    pub fn get(&self) -> u64 {
+        self.atomic.load(Ordering::Relaxed)
+    }
+}
+
+/// A handle to one gauge in the [`MetricsRegistry`].
+///
+/// Created by [`MetricsRegistry::cached_gauge`]. See [`CachedCounter`].
+#[derive(Clone)]
+pub struct CachedGauge {
+    key: metrics::Key,
+    atomic: Arc<AtomicU64>,
+}
+
+impl CachedGauge {
+    pub(crate) fn new(key: metrics::Key, atomic: Arc<AtomicU64>) -> Self {
+        Self { key, atomic }
+    }
+
+    #[allow(missing_docs)]
+    pub fn key(&self) -> &metrics::Key {
+        &self.key
+    }
+
+    /// Describe the gauge, unless it already has a description.
+    pub fn with_description(
+        self,
+        registry: &MetricsRegistry,
+        unit: Option<Unit>,
+        text: &str,
+    ) -> Self {
+        describe(registry, &self.key, MetricKind::Gauge, unit, text);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn set(&self, value: f64) {
+        GaugeFn::set(&*self.atomic, value);
+    }
+
+    #[allow(missing_docs)]
+    pub fn increment(&self, value: f64) {
+        GaugeFn::increment(&*self.atomic, value);
+    }
+
+    #[allow(missing_docs)]
+    pub fn decrement(&self, value: f64) {
+        GaugeFn::decrement(&*self.atomic, value);
+    }
+
+    /// The current value of the gauge.
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.atomic.load(Ordering::Relaxed))
+    }
+}
+
+/// A handle to one histogram in the [`MetricsRegistry`].
+///
+/// Created by [`MetricsRegistry::cached_histogram`]. See [`CachedCounter`].
+#[derive(Clone)]
+pub struct CachedHistogram {
+    key: metrics::Key,
+    bucket: Arc<AtomicBucket<f64>>,
+}
+
+impl CachedHistogram {
+    pub(crate) fn new(key: metrics::Key, bucket: Arc<AtomicBucket<f64>>) -> Self {
+        Self { key, bucket }
+    }
+
+    #[allow(missing_docs)]
+    pub fn key(&self) -> &metrics::Key {
+        &self.key
+    }
+
+    /// Describe the histogram, unless it already has a description.
+    pub fn with_description(
+        self,
+        registry: &MetricsRegistry,
+        unit: Option<Unit>,
+        text: &str,
+    ) -> Self {
+        describe(registry, &self.key, MetricKind::Histogram, unit, text);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn record(&self, value: f64) {
+        self.bucket.push(value);
+    }
+
+    /// Record every sample in `values`.
+    pub fn record_many(&self, values: &[f64]) {
+        for &value in values {
+            self.bucket.push(value);
+        }
+    }
+}
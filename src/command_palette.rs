@@ -0,0 +1,430 @@
+use crate::egui::{self, Align2, Context, Key, Modifiers, ScrollArea, TextEdit};
+use crate::{
+    dashboard_window::{CachedPlotConfigs, DashboardConfig, RequestPlot},
+    localization::{DashboardLocalization, UiText},
+    registry::{MetricsRegistry, SearchResult},
+    DashboardWindow,
+};
+use bevy::prelude::*;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use std::sync::Arc;
+
+/// How many metrics the palette lists at most.
+const MAX_PLOT_ENTRIES: usize = 20;
+
+const PALETTE_WIDTH: f32 = 400.0;
+
+/// An action listed in the [`CommandPalette`].
+#[derive(Clone)]
+pub struct DashboardCommand {
+    id: String,
+    label: CommandLabel,
+    action: Arc<dyn Fn(&mut World) + Send + Sync>,
+}
+
+#[derive(Clone)]
+enum CommandLabel {
+    Text(String),
+    Localized(UiText),
+}
+
+impl DashboardCommand {
+    /// Create a command shown as `label`, which runs `action` with exclusive
+    /// access to the world when chosen.
+    ///
+    /// `id` identifies the command in the [`DashboardCommands`], e.g.
+    /// `"dashboard.pause_all"`.
+    pub fn new(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        action: impl Fn(&mut World) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            label: CommandLabel::Text(label.into()),
+            action: Arc::new(action),
+        }
+    }
+
+    /// Create a command that sends a clone of `event` when chosen.
+    pub fn send_event<E: Event + Clone>(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        event: E,
+    ) -> Self {
+        Self::new(id, label, move |world| {
+            world.send_event(event.clone());
+        })
+    }
+
+    /// Like [`Self::new`], with a label translated by the
+    /// [`DashboardLocalization`].
+    pub fn localized(
+        id: impl Into<String>,
+        label: UiText,
+        action: impl Fn(&mut World) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            label: CommandLabel::Localized(label),
+            ..Self::new(id, String::new(), action)
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The label shown in the palette.
+    pub fn label<'a>(&'a self, l10n: &'a DashboardLocalization) -> &'a str {
+        match &self.label {
+            CommandLabel::Text(text) => text,
+            CommandLabel::Localized(key) => l10n.get(*key),
+        }
+    }
+
+    /// Run the command's action.
+    pub fn run(&self, world: &mut World) {
+        (self.action)(world);
+    }
+}
+
+/// The commands listed in the [`CommandPalette`], in the order they were
+/// added.
+///
+/// The `DashboardPlugin` adds commands for the dashboard's own actions, and
+/// the `OverlayPlugin` one for toggling the overlay. Apps can add their own
+/// with [`DashboardCommandsAppExt::add_dashboard_command`], or by changing the
+/// resource at any time.
+#[derive(Clone, Default, Resource)]
+pub struct DashboardCommands {
+    commands: Vec<DashboardCommand>,
+}
+
+impl DashboardCommands {
+    /// Add `command`, replacing any command with the same ID in place.
+    pub fn add(&mut self, command: DashboardCommand) -> &mut Self {
+        match self.commands.iter_mut().find(|c| c.id == command.id) {
+            Some(existing) => *existing = command,
+            None => self.commands.push(command),
+        }
+        self
+    }
+
+    /// Remove the command with `id`, e.g. to hide a built-in command.
+    pub fn remove(&mut self, id: &str) -> Option<DashboardCommand> {
+        let i = self.commands.iter().position(|c| c.id == id)?;
+        Some(self.commands.remove(i))
+    }
+
+    #[allow(missing_docs)]
+    pub fn get(&self, id: &str) -> Option<&DashboardCommand> {
+        self.commands.iter().find(|c| c.id == id)
+    }
+
+    #[allow(missing_docs)]
+    pub fn iter(&self) -> impl Iterator<Item = &DashboardCommand> {
+        self.commands.iter()
+    }
+
+    /// Add the commands of the `DashboardPlugin`.
+    pub(crate) fn add_dashboard_commands(&mut self) {
+        self.add(DashboardCommand::localized(
+            "dashboard.toggle_visible",
+            UiText::ToggleDashboard,
+            |world| {
+                let mut config = world.resource_mut::<DashboardConfig>();
+                config.visible = !config.visible;
+            },
+        ))
+        .add(DashboardCommand::localized(
+            "dashboard.pause_all",
+            UiText::PauseAllWindows,
+            pause_all,
+        ))
+        .add(DashboardCommand::localized(
+            "dashboard.clear_plots",
+            UiText::ClearAllPlots,
+            clear_all_plots,
+        ));
+        #[cfg(feature = "layout")]
+        self.add(DashboardCommand::localized(
+            "dashboard.save_layout",
+            UiText::SaveLayout,
+            |world| {
+                world.send_event(crate::layout::SaveLayout);
+            },
+        ));
+        #[cfg(feature = "report")]
+        self.add(DashboardCommand::localized(
+            "dashboard.export_report",
+            UiText::ExportReport,
+            |world| {
+                world.send_event(crate::report::ExportReport);
+            },
+        ));
+    }
+}
+
+/// Pauses all windows, or resumes them if all are paused.
+fn pause_all(world: &mut World) {
+    let mut windows = world.query::<&mut DashboardWindow>();
+    let pause = windows.iter(world).any(|window| !window.config().paused);
+    for mut window in windows.iter_mut(world) {
+        window.set_paused(pause);
+    }
+}
+
+/// Removes the plots of all windows that aren't
+/// [read-only](DashboardConfig::read_only).
+fn clear_all_plots(world: &mut World) {
+    let read_only = world.resource::<DashboardConfig>().read_only.clone();
+    world.resource_scope(|world, mut cached_configs: Mut<CachedPlotConfigs>| {
+        for mut window in world.query::<&mut DashboardWindow>().iter_mut(world) {
+            window.clear_unlocked_plots(&read_only, &mut cached_configs);
+        }
+    });
+}
+
+/// Shorthand for adding entries to the [`DashboardCommands`].
+pub trait DashboardCommandsAppExt {
+    /// Add `command` to the [`CommandPalette`], replacing any command with the
+    /// same ID.
+    ///
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use bevy_metrics_dashboard::{DashboardCommand, DashboardCommandsAppExt};
+    /// # #[derive(Clone, Event)]
+    /// # struct Respawn;
+    /// # let mut app = App::new();
+    /// app.add_event::<Respawn>()
+    ///     .add_dashboard_command(DashboardCommand::send_event(
+    ///         "game.respawn",
+    ///         "Respawn the player",
+    ///         Respawn,
+    ///     ));
+    /// ```
+    fn add_dashboard_command(&mut self, command: DashboardCommand) -> &mut Self;
+}
+
+impl DashboardCommandsAppExt for App {
+    fn add_dashboard_command(&mut self, command: DashboardCommand) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<DashboardCommands>()
+            .add(command);
+        self
+    }
+}
+
+/// A searchable list of [`DashboardCommands`] and metrics to plot, drawn on
+/// top of the primary window.
+///
+/// Pressing [`DashboardConfig::palette_shortcut`] opens it, even while the
+/// dashboard is hidden. Typing filters the commands with a fuzzy search, and
+/// lists matching metrics as "Plot: name" entries that send a
+/// [`RequestPlot`]. The arrow keys move the selection, Enter runs it and
+/// Escape closes the palette.
+#[derive(Default, Resource)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+    request_focus: bool,
+    /// Recomputed when the query changes.
+    entries: Option<Vec<(String, Entry)>>,
+}
+
+#[derive(Clone)]
+enum Entry {
+    Command(String),
+    Plot(SearchResult),
+}
+
+impl CommandPalette {
+    #[allow(missing_docs)]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open the palette with an empty query.
+    pub fn open(&mut self) {
+        *self = Self {
+            open: true,
+            request_focus: true,
+            ..default()
+        };
+    }
+
+    #[allow(missing_docs)]
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    #[allow(missing_docs)]
+    pub fn toggle(&mut self) {
+        if self.open {
+            self.close();
+        } else {
+            self.open();
+        }
+    }
+
+    /// Bevy system that draws the palette, and runs the chosen entry.
+    pub fn draw(
+        mut palette: ResMut<Self>,
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        dashboard_commands: Res<DashboardCommands>,
+        config: Res<DashboardConfig>,
+        localization: Res<DashboardLocalization>,
+        mut ctxts: bevy_egui::EguiContexts,
+    ) {
+        let ctxt = ctxts.ctx_mut();
+        if config
+            .palette_shortcut
+            .is_some_and(|shortcut| ctxt.input_mut(|i| i.consume_shortcut(&shortcut)))
+        {
+            palette.toggle();
+        }
+        if !palette.open {
+            return;
+        }
+
+        let chosen = localization.scope(ctxt, |ctxt| {
+            config.theme.scope(ctxt, |ctxt| {
+                palette.show(&registry, &dashboard_commands, ctxt)
+            })
+        });
+        match chosen {
+            Some(Entry::Command(id)) => {
+                if let Some(command) = dashboard_commands.get(&id).cloned() {
+                    commands.queue(move |world: &mut World| command.run(world));
+                }
+            }
+            Some(Entry::Plot(result)) => {
+                let unit = result.description.and_then(|d| d.unit);
+                commands.send_event(RequestPlot::new(result.key, unit));
+            }
+            None => {}
+        }
+    }
+
+    /// Returns the entry chosen this frame.
+    fn show(
+        &mut self,
+        registry: &MetricsRegistry,
+        dashboard_commands: &DashboardCommands,
+        ctxt: &Context,
+    ) -> Option<Entry> {
+        // Taken before any window sees them, so the arrow keys don't also
+        // move through other lists.
+        let [up, down, enter, escape] = ctxt.input_mut(|i| {
+            [Key::ArrowUp, Key::ArrowDown, Key::Enter, Key::Escape]
+                .map(|key| i.consume_key(Modifiers::NONE, key))
+        });
+
+        let mut chosen = None;
+        let response = egui::Area::new(egui::Id::new("dashboard-command-palette"))
+            .order(egui::Order::Foreground)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctxt, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(PALETTE_WIDTH);
+                    let l10n = DashboardLocalization::of(ui);
+                    let query = ui.add(
+                        TextEdit::singleline(&mut self.query)
+                            .hint_text(l10n.get(UiText::CommandPaletteHint))
+                            .desired_width(f32::INFINITY),
+                    );
+                    if std::mem::take(&mut self.request_focus) {
+                        query.request_focus();
+                    }
+                    if query.changed() {
+                        self.entries = None;
+                        self.selected = 0;
+                    }
+                    let entries = self.entries.get_or_insert_with(|| {
+                        entries(&self.query, registry, dashboard_commands, &l10n)
+                    });
+                    if entries.is_empty() {
+                        ui.weak(l10n.get(UiText::NoMatchingCommands));
+                        return;
+                    }
+
+                    let n = entries.len();
+                    if down {
+                        self.selected = (self.selected + 1) % n;
+                    }
+                    if up {
+                        self.selected = (self.selected + n - 1) % n;
+                    }
+                    self.selected = self.selected.min(n - 1);
+                    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for (i, (label, entry)) in entries.iter().enumerate() {
+                            let selected = i == self.selected;
+                            let response = ui.selectable_label(selected, label);
+                            if selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() || (selected && enter) {
+                                chosen = Some(entry.clone());
+                            }
+                        }
+                    });
+                });
+            })
+            .response;
+
+        if escape || chosen.is_some() || response.clicked_elsewhere() {
+            self.close();
+        }
+        chosen
+    }
+}
+
+/// The commands and metrics matching `query`, best match first. Metrics are
+/// only listed once something is typed.
+fn entries(
+    query: &str,
+    registry: &MetricsRegistry,
+    dashboard_commands: &DashboardCommands,
+    l10n: &DashboardLocalization,
+) -> Vec<(String, Entry)> {
+    let matcher = SkimMatcherV2::default();
+    let query = query.trim();
+    let mut scored: Vec<_> = dashboard_commands
+        .iter()
+        .filter_map(|command| {
+            let label = command.label(l10n);
+            let score = matcher.fuzzy_match(label, query)?;
+            Some((score, label.to_owned(), Entry::Command(command.id.clone())))
+        })
+        .collect();
+    if !query.is_empty() {
+        // Matched against the whole label, so "plot: fps" finds "fps" too.
+        // Only the listed metrics need their descriptions.
+        let matches = registry.search_iter("");
+        let mut plots: Vec<_> = matches
+            .keys()
+            .iter()
+            .filter_map(|key| {
+                let title = registry.title(key, 0);
+                let label = l10n.format(UiText::PlotMetric, &[("metric", &title)]);
+                let score = matcher.fuzzy_match(&label, query)?;
+                Some((score, label, key))
+            })
+            .collect();
+        plots.sort_by_key(|(score, ..)| std::cmp::Reverse(*score));
+        plots.truncate(MAX_PLOT_ENTRIES);
+        scored.extend(plots.into_iter().map(|(score, label, key)| {
+            let result = registry.get_search_result(key.clone());
+            (score, label, Entry::Plot(result))
+        }));
+    }
+    // Stable, so commands stay in order and ahead of metrics on ties.
+    scored.sort_by_key(|(score, ..)| std::cmp::Reverse(*score));
+    scored
+        .into_iter()
+        .map(|(_, label, entry)| (label, entry))
+        .collect()
+}
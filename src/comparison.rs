@@ -0,0 +1,233 @@
+//! Comparing series that differ only by the value of one label, e.g. the
+//! `run` of a before/after benchmark.
+//!
+//! See [`DashboardWindowConfig::comparison_label`](crate::DashboardWindowConfig::comparison_label).
+
+use crate::dashboard_window::DashboardConfig;
+use crate::display_names::rename_with;
+use crate::egui::{Grid, Ui, Vec2b};
+use crate::egui_plot::{LineStyle, Plot};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::plots::MetricPlot;
+use crate::registry::MetricKey;
+use crate::stats::{mean, percentile};
+use crate::theme::ThemePalette;
+
+/// Series whose keys are equal except for the value of the comparison label.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonGroup {
+    /// The shared key, without the comparison label.
+    pub key: MetricKey,
+    /// The comparison label value and index of each series, in the order they
+    /// were given. The first is the baseline.
+    pub members: Vec<(String, usize)>,
+}
+
+/// Group `keys` that are equal except for the value of `label`.
+///
+/// Only groups with at least two different values are returned, in the
+/// order of their first key. Keys without `label` are never grouped. The
+/// indices in [`ComparisonGroup::members`] refer to the position in `keys`.
+///
+/// ```
+/// # use bevy_metrics_dashboard::comparison::pair_by_label;
+/// # use bevy_metrics_dashboard::registry::MetricKey;
+/// # use metrics::{Key, Label};
+/// # use metrics_util::MetricKind;
+/// let key = |labels: &[(&'static str, &'static str)]| {
+///     let labels = labels.iter().map(|&(k, v)| Label::new(k, v));
+///     MetricKey::new(
+///         Key::from_parts("frame_time", labels.collect::<Vec<_>>()),
+///         MetricKind::Gauge,
+///     )
+/// };
+/// let keys = [
+///     key(&[("run", "before"), ("map", "forest")]),
+///     key(&[("run", "after"), ("map", "forest")]),
+///     // The other labels differ, so this has no partner.
+///     key(&[("run", "after"), ("map", "desert")]),
+///     // Without the label, a key is never grouped.
+///     key(&[("map", "forest")]),
+///     // The label order doesn't matter.
+///     key(&[("map", "forest"), ("run", "baseline")]),
+/// ];
+///
+/// let groups = pair_by_label(&keys, "run");
+/// assert_eq!(groups.len(), 1);
+/// assert_eq!(groups[0].key, key(&[("map", "forest")]));
+/// let members: Vec<_> = groups[0].members.iter().map(|(v, i)| (v.as_str(), *i)).collect();
+/// assert_eq!(members, [("before", 0), ("after", 1), ("baseline", 4)]);
+/// ```
+pub fn pair_by_label<'a>(
+    keys: impl IntoIterator<Item = &'a MetricKey>,
+    label: &str,
+) -> Vec<ComparisonGroup> {
+    let mut groups: Vec<ComparisonGroup> = Vec::new();
+    for (i, key) in keys.into_iter().enumerate() {
+        let Some(value) = key.key.labels().find(|l| l.key() == label) else {
+            continue;
+        };
+        let value = value.value().to_owned();
+        let mut others: Vec<_> = key
+            .key
+            .labels()
+            .filter(|l| l.key() != label)
+            .cloned()
+            .collect();
+        others.sort_by(|a, b| a.key().cmp(b.key()));
+        let shared = MetricKey::new(
+            metrics::Key::from_parts(key.key.name().to_owned(), others),
+            key.kind,
+        );
+
+        match groups.iter_mut().find(|g| g.key == shared) {
+            Some(group) => {
+                if !group.members.iter().any(|(v, _)| *v == value) {
+                    group.members.push((value, i));
+                }
+            }
+            None => groups.push(ComparisonGroup {
+                key: shared,
+                members: vec![(value, i)],
+            }),
+        }
+    }
+    groups.retain(|g| g.members.len() > 1);
+    groups
+}
+
+/// Differences between the values of a series and a baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeltaStats {
+    /// The mean minus the baseline mean.
+    pub mean_diff: f64,
+    /// The 95th percentile minus the baseline's 95th percentile.
+    pub p95_diff: f64,
+    /// [`Self::mean_diff`] as a percentage of the baseline mean, or `None`
+    /// if the baseline mean is zero.
+    pub percent_change: Option<f64>,
+}
+
+impl DeltaStats {
+    /// Compare `values` against `baseline`. Returns `None` if either is
+    /// empty.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::comparison::DeltaStats;
+    /// let stats = DeltaStats::new(&[10.0, 20.0], &[15.0, 30.0]).unwrap();
+    /// assert_eq!(stats.mean_diff, 7.5);
+    /// assert_eq!(stats.p95_diff, 10.0);
+    /// assert_eq!(stats.percent_change, Some(50.0));
+    ///
+    /// // Without a baseline mean, there's no percentage.
+    /// let stats = DeltaStats::new(&[-1.0, 1.0], &[2.0]).unwrap();
+    /// assert_eq!(stats.percent_change, None);
+    ///
+    /// assert_eq!(DeltaStats::new(&[], &[1.0]), None);
+    /// assert_eq!(DeltaStats::new(&[1.0], &[]), None);
+    /// ```
+    pub fn new(baseline: &[f64], values: &[f64]) -> Option<Self> {
+        let baseline_mean = mean(baseline)?;
+        let mean_diff = mean(values)? - baseline_mean;
+        let p95_diff =
+            percentile(&mut values.to_vec(), 95.0)? - percentile(&mut baseline.to_vec(), 95.0)?;
+        Some(Self {
+            mean_diff,
+            p95_diff,
+            percent_change: (baseline_mean != 0.0).then(|| 100.0 * mean_diff / baseline_mean.abs()),
+        })
+    }
+}
+
+/// Paired with the [series colors](ThemePalette::series) of the theme.
+const LINE_STYLES: [LineStyle; 3] = [
+    LineStyle::Solid,
+    LineStyle::Dashed { length: 10.0 },
+    LineStyle::Dotted { spacing: 5.0 },
+];
+
+/// Draw the lines of all plots in `group` in one plot, followed by the delta
+/// of each against the baseline.
+///
+/// Label values are shown with their display names, renamed by the
+/// dashboard's display names and then the display names of each plot. The
+/// plot is drawn in the style of the baseline.
+pub(crate) fn draw_comparison(
+    group: &ComparisonGroup,
+    plots: &mut [MetricPlot],
+    dash_config: &DashboardConfig,
+    ui: &mut Ui,
+) {
+    let names: Vec<_> = group
+        .members
+        .iter()
+        .map(|(value, plot_i)| {
+            let tables = [&dash_config.display_names, plots[*plot_i].display_names()];
+            rename_with(&tables, value).into_owned()
+        })
+        .collect();
+
+    let palette = ThemePalette::of(ui);
+    let l10n = DashboardLocalization::of(ui);
+    let mut lines = Vec::new();
+    for (i, (name, (_, plot_i))) in names.iter().zip(&group.members).enumerate() {
+        let style = LINE_STYLES[i % LINE_STYLES.len()];
+        let color = palette.series[i % palette.series.len()];
+        let plot_lines = plots[*plot_i].overlay_lines(name).unwrap_or_default();
+        lines.extend(plot_lines.into_iter().map(|l| l.style(style).color(color)));
+    }
+
+    let mut plot = Plot::new(("comparison", &group.key))
+        .allow_scroll(false)
+        .view_aspect(2.0)
+        .auto_bounds(Vec2b::new(true, true))
+        .x_axis_label(l10n.get(UiText::FrameAxis));
+    let (_, baseline_i) = &group.members[0];
+    let style = plots[*baseline_i].style(dash_config);
+    if !style.minimal {
+        plot = plot.legend(Default::default());
+    }
+    if let Some(unit) = plots[*baseline_i].displayed_unit() {
+        plot = plot.y_axis_label(unit);
+    }
+    style.show(plot, ui, |plot_ui| {
+        for line in lines {
+            plot_ui.line(line);
+        }
+    });
+
+    let baseline_values = plots[*baseline_i].plotted_values().unwrap_or_default();
+    Grid::new(("comparison-deltas", &group.key))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("");
+            ui.strong(l10n.get(UiText::MeanDiff));
+            ui.strong(l10n.get(UiText::P95Diff));
+            ui.strong(l10n.get(UiText::Change));
+            ui.end_row();
+            let baseline_name = &names[0];
+            for (name, (_, plot_i)) in names.iter().zip(&group.members).skip(1) {
+                ui.label(l10n.format(
+                    UiText::Versus,
+                    &[("name", name), ("baseline", baseline_name)],
+                ));
+                let values = plots[*plot_i].plotted_values().unwrap_or_default();
+                match DeltaStats::new(&baseline_values, &values) {
+                    Some(delta) => {
+                        ui.label(l10n.signed_number(delta.mean_diff, 3));
+                        ui.label(l10n.signed_number(delta.p95_diff, 3));
+                        match delta.percent_change {
+                            Some(percent) => {
+                                ui.label(format!("{}%", l10n.signed_number(percent, 1)))
+                            }
+                            None => ui.weak(l10n.get(UiText::NotAvailable)),
+                        };
+                    }
+                    None => {
+                        ui.weak(l10n.get(UiText::NoData));
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,218 @@
+use crate::cached_metrics::CachedGauge;
+use crate::registry::MetricsRegistry;
+use bevy::{
+    ecs::query::QueryFilter,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use metrics::Unit;
+use std::{marker::PhantomData, sync::Arc};
+
+/// Mirrors a value read from the component `C` into a gauge named `name`,
+/// each frame in [`PostUpdate`].
+///
+/// By default, the gauge follows one entity with `C` that matches the
+/// filter `F`, chosen arbitrarily if there are several. With
+/// [`Self::per_entity`], every matching entity gets its own gauge instead,
+/// labeled by `entity` and, if the entity has a [`Name`], by `name`. Labels
+/// are chosen when an entity is first sampled.
+///
+/// Gauges stop updating when their entity is despawned or stops matching.
+/// They keep their last value, unless [`Self::remove_on_despawn`] is used.
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::ComponentGaugePlugin;
+/// # use metrics::Unit;
+/// #[derive(Component)]
+/// struct Health {
+///     current: u32,
+/// }
+///
+/// #[derive(Component)]
+/// struct Player;
+///
+/// # let mut app = App::new();
+/// app.add_plugins(
+///     ComponentGaugePlugin::<Health, With<Player>>::new("player/health", |h| {
+///         h.current as f64
+///     })
+///     .with_description(Unit::Count, "Hit points of the player"),
+/// );
+/// ```
+pub struct ComponentGaugePlugin<C, F = ()> {
+    name: String,
+    value: Arc<dyn Fn(&C) -> f64 + Send + Sync>,
+    unit: Option<Unit>,
+    description: Option<String>,
+    per_entity: bool,
+    remove_on_despawn: bool,
+    marker: PhantomData<fn() -> F>,
+}
+
+impl<C, F> ComponentGaugePlugin<C, F> {
+    /// Set the gauge `name` to `value` of the matching component.
+    pub fn new(name: impl Into<String>, value: impl Fn(&C) -> f64 + Send + Sync + 'static) -> Self {
+        Self {
+            name: name.into(),
+            value: Arc::new(value),
+            unit: None,
+            description: None,
+            per_entity: false,
+            remove_on_despawn: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Describe the gauge, unless it already has a description.
+    pub fn with_description(
+        mut self,
+        unit: impl Into<Option<Unit>>,
+        text: impl Into<String>,
+    ) -> Self {
+        self.unit = unit.into();
+        self.description = Some(text.into());
+        self
+    }
+
+    /// Add one gauge per matching entity, instead of following one entity.
+    pub fn per_entity(mut self) -> Self {
+        self.per_entity = true;
+        self
+    }
+
+    /// Remove a gauge from the registry once its entity is despawned or stops
+    /// matching.
+    pub fn remove_on_despawn(mut self) -> Self {
+        self.remove_on_despawn = true;
+        self
+    }
+}
+
+impl<C: Component, F: QueryFilter + 'static> Plugin for ComponentGaugePlugin<C, F> {
+    fn build(&self, app: &mut App) {
+        let config = GaugeConfig {
+            name: self.name.clone(),
+            unit: self.unit,
+            description: self.description.clone(),
+            remove_on_despawn: self.remove_on_despawn,
+        };
+        let value = self.value.clone();
+        if self.per_entity {
+            app.add_systems(
+                PostUpdate,
+                move |registry: Res<MetricsRegistry>,
+                      query: Query<(Entity, &C, Option<&Name>), F>,
+                      mut gauges: Local<HashMap<Entity, CachedGauge>>,
+                      mut seen: Local<HashSet<Entity>>| {
+                    seen.clear();
+                    for (entity, component, name) in &query {
+                        seen.insert(entity);
+                        let gauge = gauges.entry(entity).or_insert_with(|| {
+                            let entity = entity.to_string();
+                            let mut labels = vec![("entity", entity.as_str())];
+                            if let Some(name) = name {
+                                labels.push(("name", name.as_str()));
+                            }
+                            config.create(&registry, &labels)
+                        });
+                        gauge.set(value(component));
+                    }
+                    gauges.retain(|entity, gauge| {
+                        let keep = seen.contains(entity);
+                        if !keep {
+                            config.release(&registry, gauge);
+                        }
+                        keep
+                    });
+                },
+            );
+        } else {
+            app.add_systems(
+                PostUpdate,
+                move |registry: Res<MetricsRegistry>,
+                      query: Query<(Entity, &C), F>,
+                      mut followed: Local<Option<(Entity, CachedGauge)>>| {
+                    // Keep following the same entity while it matches.
+                    let current = followed
+                        .as_ref()
+                        .and_then(|(entity, _)| query.get(*entity).ok())
+                        .or_else(|| query.iter().next());
+                    let Some((entity, component)) = current else {
+                        if let Some((_, gauge)) = followed.take() {
+                            config.release(&registry, &gauge);
+                        }
+                        return;
+                    };
+                    let (followed_entity, gauge) =
+                        followed.get_or_insert_with(|| (entity, config.create(&registry, &[])));
+                    *followed_entity = entity;
+                    gauge.set(value(component));
+                },
+            );
+        }
+    }
+
+    fn is_unique(&self) -> bool {
+        // Several gauges may read the same component.
+        false
+    }
+}
+
+/// The parts of a [`ComponentGaugePlugin`] its system needs.
+struct GaugeConfig {
+    name: String,
+    unit: Option<Unit>,
+    description: Option<String>,
+    remove_on_despawn: bool,
+}
+
+impl GaugeConfig {
+    fn create(&self, registry: &MetricsRegistry, labels: &[(&str, &str)]) -> CachedGauge {
+        let gauge = registry.cached_gauge(&self.name, labels);
+        match &self.description {
+            Some(text) => gauge.with_description(registry, self.unit, text),
+            None => gauge,
+        }
+    }
+
+    fn release(&self, registry: &MetricsRegistry, gauge: &CachedGauge) {
+        if self.remove_on_despawn {
+            registry.remove_gauge(gauge.key());
+        }
+    }
+}
+
+/// Shorthand for adding a [`ComponentGaugePlugin`] without a filter or
+/// options.
+pub trait MetricGaugeAppExt {
+    /// Mirror `value` of one entity's `C` component into the gauge `name`.
+    ///
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use bevy_metrics_dashboard::MetricGaugeAppExt;
+    /// # #[derive(Component)]
+    /// # struct Health {
+    /// #     current: u32,
+    /// # }
+    /// # let mut app = App::new();
+    /// app.add_metric_gauge::<Health>("player/health", |h| h.current as f64);
+    /// ```
+    fn add_metric_gauge<C: Component>(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Fn(&C) -> f64 + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl MetricGaugeAppExt for App {
+    fn add_metric_gauge<C: Component>(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Fn(&C) -> f64 + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.add_plugins(ComponentGaugePlugin::<C>::new(name, value))
+    }
+}
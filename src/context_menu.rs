@@ -0,0 +1,95 @@
+use crate::egui::{Response, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::registry::SearchResult;
+
+/// Attach a context menu to `response` for copying and inspecting a metric.
+pub fn metric_context_menu(response: &Response, result: &SearchResult) {
+    metric_context_menu_with(response, result, |_| {});
+}
+
+/// Like [`metric_context_menu`], with more entries added by `add_contents`.
+pub fn metric_context_menu_with(
+    response: &Response,
+    result: &SearchResult,
+    add_contents: impl FnOnce(&mut Ui),
+) {
+    response.context_menu(|ui| {
+        let l10n = DashboardLocalization::of(ui);
+        if ui.button(l10n.get(UiText::CopyMetricName)).clicked() {
+            ui.ctx().copy_text(result.key.key.name().to_owned());
+            ui.close_menu();
+        }
+        if ui.button(l10n.get(UiText::CopyFullKey)).clicked() {
+            ui.ctx().copy_text(result.key.name_with_labels());
+            ui.close_menu();
+        }
+        ui.menu_button(l10n.get(UiText::ShowDescription), |ui| {
+            ui.label(result.detailed_text(None, ui));
+        });
+        let docs_url = result
+            .description
+            .as_ref()
+            .and_then(|d| d.extras.docs_url.as_deref());
+        if let Some(docs_url) = docs_url {
+            open_link_button(docs_url, LinkKind::Docs, ui);
+        }
+        let url = result.description.as_ref().and_then(|d| find_url(&d.text));
+        if let Some(url) = url.filter(|&url| Some(url) != docs_url) {
+            open_link_button(url, LinkKind::Link, ui);
+        }
+        add_contents(ui);
+    });
+}
+
+/// What a link button points to.
+#[derive(Clone, Copy)]
+pub(crate) enum LinkKind {
+    /// The documentation of a metric.
+    Docs,
+    /// A URL found in a description.
+    Link,
+}
+
+/// A button that opens `url`, labeled e.g. "Open Link".
+#[cfg(feature = "open_links")]
+pub(crate) fn open_link_button(url: &str, kind: LinkKind, ui: &mut Ui) {
+    let text = match kind {
+        LinkKind::Docs => UiText::OpenDocs,
+        LinkKind::Link => UiText::OpenLink,
+    };
+    if ui
+        .button(DashboardLocalization::of(ui).get(text))
+        .on_hover_text(url)
+        .clicked()
+    {
+        if let Err(e) = webbrowser::open(url) {
+            bevy::log::warn!("Failed to open {url}: {e}");
+        }
+        ui.close_menu();
+    }
+}
+
+/// A button that copies `url`, labeled e.g. "Copy Link".
+#[cfg(not(feature = "open_links"))]
+pub(crate) fn open_link_button(url: &str, kind: LinkKind, ui: &mut Ui) {
+    let text = match kind {
+        LinkKind::Docs => UiText::CopyDocs,
+        LinkKind::Link => UiText::CopyLink,
+    };
+    if ui
+        .button(DashboardLocalization::of(ui).get(text))
+        .on_hover_text(url)
+        .clicked()
+    {
+        ui.ctx().copy_text(url.to_owned());
+        ui.close_menu();
+    }
+}
+
+/// The first `http://` or `https://` URL in `text`.
+fn find_url(text: &str) -> Option<&str> {
+    text.split_whitespace()
+        .map(|word| word.trim_start_matches(['(', '<', '"', '\'']))
+        .find(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .map(|url| url.trim_end_matches(['.', ',', ';', ':', ')', '>', '"', '\'']))
+}
@@ -0,0 +1,85 @@
+//! Metrics registered by this crate's plugins.
+
+/// Names of the metrics registered by [`CoreMetricsPlugin`](crate::CoreMetricsPlugin),
+/// `RenderMetricsPlugin` and [`SamplingLatencyPlugin`](crate::SamplingLatencyPlugin).
+///
+/// The plugins register and describe their metrics with exactly these names:
+///
+/// ```
+/// # use bevy::{prelude::*, time::TimePlugin};
+/// # use bevy_metrics_dashboard::core_metrics::names::*;
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::{
+/// #     CoreMetricsPlugin, FramePacingConfig, HeadlessDashboardPlugin, RegistryPlugin,
+/// #     SamplingLatencyPlugin,
+/// # };
+/// # use std::collections::BTreeSet;
+/// # use std::time::Duration;
+/// let mut app = App::new();
+/// app.add_plugins((TaskPoolPlugin::default(), TimePlugin))
+///     .add_plugins(RegistryPlugin::new())
+///     .add_plugins((CoreMetricsPlugin, HeadlessDashboardPlugin::new()))
+///     .add_plugins(SamplingLatencyPlugin::new())
+///     // Count every frame as a missed vsync, to register that counter too.
+///     .insert_resource(FramePacingConfig {
+///         vsync_interval: Duration::ZERO,
+///         tolerance: Duration::ZERO,
+///     });
+/// for _ in 0..3 {
+///     app.update();
+/// }
+///
+/// let registry = app.world().resource::<MetricsRegistry>();
+/// let registered: BTreeSet<_> = registry
+///     .all_metrics()
+///     .into_iter()
+///     .map(|result| result.key.key.name().to_owned())
+///     .collect();
+/// let expected: BTreeSet<_> = [
+///     FRAME_TIME,
+///     FRAMES_PER_SECOND,
+///     ENTITIES,
+///     PRESENT_INTERVAL,
+///     MISSED_VSYNCS,
+///     SAMPLING_PROBE,
+///     SAMPLING_LATENCY,
+/// ]
+/// .map(String::from)
+/// .into();
+/// assert_eq!(registered, expected);
+/// // Every description belongs to a registered metric, and vice versa.
+/// assert!(registry.audit().is_empty(), "{:?}", registry.audit());
+/// ```
+pub mod names {
+    /// Gauge and histogram of the frame time delta, in milliseconds.
+    pub const FRAME_TIME: &str = "frame_time";
+    /// Gauge of frames per second.
+    pub const FRAMES_PER_SECOND: &str = "frames_per_second";
+    /// Gauge of the number of entities in the world.
+    pub const ENTITIES: &str = "entities";
+    /// Gauge and histogram of the time between presents, in milliseconds.
+    pub const PRESENT_INTERVAL: &str = "frame_pacing/present_interval";
+    /// Counter of frames that missed the vsync interval.
+    pub const MISSED_VSYNCS: &str = "frame_pacing/missed_vsyncs";
+
+    /// Gauge of the entities visible to a 2D camera, labeled by `entity`.
+    pub const VISIBLE_2D_ENTITIES: &str = "visible_2d_entities";
+    /// Gauge of the entities visible to a 3D camera, labeled by `entity`.
+    pub const VISIBLE_3D_ENTITIES: &str = "visible_3d_entities";
+    /// Histogram of the render schedule's setup phase, in milliseconds.
+    pub const RENDER_SETUP_TIME: &str = "render::setup_time";
+    /// Histogram of the render schedule's queue phase, in milliseconds.
+    pub const RENDER_QUEUE_TIME: &str = "render::queue_time";
+    /// Histogram of the render schedule's prepare phase, in milliseconds.
+    pub const RENDER_PREPARE_TIME: &str = "render::prepare_time";
+    /// Histogram of the render schedule's render phase, in milliseconds.
+    pub const RENDER_RENDER_TIME: &str = "render::render_time";
+    /// Gauge of how many frames old the forwarded render metrics are.
+    pub const RENDER_FRAME_LATENCY: &str = "render::frame_latency";
+
+    /// Gauge of the frame in which the sampling latency probe was written.
+    pub const SAMPLING_PROBE: &str = "dashboard/sampling_probe";
+    /// Gauge of how many frames pass between writing the sampling latency
+    /// probe and sampling it.
+    pub const SAMPLING_LATENCY: &str = "dashboard/sampling_latency";
+}
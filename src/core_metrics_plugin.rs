@@ -1,38 +1,114 @@
+use crate::{core_metrics::names::*, registry::describe_many};
 use bevy::{ecs::entity::Entities, prelude::*};
-use metrics::{describe_gauge, describe_histogram, gauge, histogram, Unit};
+use metrics::{counter, gauge, histogram, KeyName, Unit};
+use metrics_util::MetricKind;
+use std::time::{Duration, Instant};
 
 /// Provides core metrics like frame time, entity count, etc.
+///
+/// Frame pacing metrics are registered under `frame_pacing/`. Bevy doesn't
+/// expose present timestamps, so these are measured at the end of each frame,
+/// which includes any time the main world waits for rendering. Configure the
+/// missed vsync counter with the [`FramePacingConfig`] resource.
 pub struct CoreMetricsPlugin;
 
 impl Plugin for CoreMetricsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, describe_core_metrics)
-            .add_systems(Update, update_core_metrics);
+        app.init_resource::<FramePacingConfig>()
+            .add_systems(Startup, describe_core_metrics)
+            .add_systems(Update, update_core_metrics)
+            .add_systems(Last, update_frame_pacing_metrics);
+    }
+}
+
+/// Configures the `frame_pacing/missed_vsyncs` counter.
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct FramePacingConfig {
+    /// The expected time between presents.
+    pub vsync_interval: Duration,
+    /// How much longer than [`Self::vsync_interval`] a frame may take before
+    /// it counts as a missed vsync.
+    pub tolerance: Duration,
+}
+
+impl Default for FramePacingConfig {
+    fn default() -> Self {
+        Self {
+            vsync_interval: Duration::from_secs(1) / 60,
+            tolerance: Duration::from_millis(2),
+        }
     }
 }
 
 fn describe_core_metrics() {
-    describe_gauge!("frame_time", Unit::Milliseconds, "Frame time delta");
-    describe_histogram!("frame_time", Unit::Milliseconds, "Frame time delta");
-    describe_gauge!(
-        "frames_per_second",
-        Unit::CountPerSecond,
-        "Frames per second"
-    );
-    describe_gauge!(
-        "entities",
-        Unit::Count,
-        "The number of entities in the world"
-    );
+    const PRESENT_INTERVAL_TEXT: &str = "Time between presents (measured at end of frame)";
+    describe_many(&[
+        (
+            KeyName::from_const_str(FRAME_TIME),
+            MetricKind::Gauge,
+            Some(Unit::Milliseconds),
+            "Frame time delta",
+        ),
+        (
+            KeyName::from_const_str(FRAME_TIME),
+            MetricKind::Histogram,
+            Some(Unit::Milliseconds),
+            "Frame time delta",
+        ),
+        (
+            KeyName::from_const_str(FRAMES_PER_SECOND),
+            MetricKind::Gauge,
+            Some(Unit::CountPerSecond),
+            "Frames per second",
+        ),
+        (
+            KeyName::from_const_str(ENTITIES),
+            MetricKind::Gauge,
+            Some(Unit::Count),
+            "The number of entities in the world",
+        ),
+        (
+            KeyName::from_const_str(PRESENT_INTERVAL),
+            MetricKind::Gauge,
+            Some(Unit::Milliseconds),
+            PRESENT_INTERVAL_TEXT,
+        ),
+        (
+            KeyName::from_const_str(PRESENT_INTERVAL),
+            MetricKind::Histogram,
+            Some(Unit::Milliseconds),
+            PRESENT_INTERVAL_TEXT,
+        ),
+        (
+            KeyName::from_const_str(MISSED_VSYNCS),
+            MetricKind::Counter,
+            Some(Unit::Count),
+            "Frames that took longer than the vsync interval plus tolerance (measured at end of frame)",
+        ),
+    ]);
 }
 
 fn update_core_metrics(entities: &Entities, time: Res<Time>) {
-    gauge!("entities").set(entities.len() as f64);
+    gauge!(ENTITIES).set(entities.len() as f64);
 
     let sec = time.delta_secs_f64();
     let ms = 1000.0 * sec;
     let fps = 1.0 / sec;
-    histogram!("frame_time").record(ms);
-    gauge!("frame_time").set(ms);
-    gauge!("frames_per_second").set(fps);
+    histogram!(FRAME_TIME).record(ms);
+    gauge!(FRAME_TIME).set(ms);
+    gauge!(FRAMES_PER_SECOND).set(fps);
+}
+
+fn update_frame_pacing_metrics(config: Res<FramePacingConfig>, mut last: Local<Option<Instant>>) {
+    let now = Instant::now();
+    let Some(last) = last.replace(now) else {
+        return;
+    };
+    let interval = now - last;
+    let ms = 1000.0 * interval.as_secs_f64();
+    histogram!(PRESENT_INTERVAL).record(ms);
+    gauge!(PRESENT_INTERVAL).set(ms);
+    if interval > config.vsync_interval + config.tolerance {
+        counter!(MISSED_VSYNCS).increment(1);
+    }
 }
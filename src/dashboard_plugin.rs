@@ -1,28 +1,358 @@
 use crate::{
-    dashboard_window::{CachedPlotConfigs, RequestPlot},
+    audit_window::AuditWindow,
+    budget_bar::{BudgetBar, BudgetBarConfig},
+    command_palette::{CommandPalette, DashboardCommands},
+    dashboard_window::DashboardConfig,
+    difference_plot::DifferencePlot,
+    display_names::DisplayNames,
+    egui::KeyboardShortcut,
+    focus::{DashboardFocus, UnfocusedBehavior},
+    headless_plugin::add_sampling,
+    leaderboard::Leaderboard,
+    localization::DashboardLocalization,
     namespace_tree::NamespaceTreeWindow,
-    ClearBucketsSystem, DashboardWindow,
+    performance_budget::PerformanceBudget,
+    plot_defaults::PlotDefault,
+    plot_style::PlotStyle,
+    plots::{MetricPlot, MetricPlotConfig},
+    read_only::ReadOnly,
+    registry::UnitSuffixes,
+    scatter_plot::{ScatterPlot, ScatterPlotConfig},
+    search_bar::MetricSearchBar,
+    snapshot_diff::SnapshotDiff,
+    theme::{DashboardColor, DashboardTheme},
+    widget::DashboardWidget,
+    DashboardWindow,
+};
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
 };
-use bevy::prelude::*;
 use bevy_egui::EguiContextPass;
+use std::time::Duration;
+
+#[cfg(feature = "autosave")]
+use crate::layout::LayoutAutosave;
+#[cfg(feature = "layout")]
+use crate::layout::{LayoutFile, SaveLayout};
+#[cfg(feature = "report")]
+use crate::report::{ExportReport, ReportFile};
+
+/// Updates and renders all [`DashboardWindow`], [`NamespaceTreeWindow`] and
+/// [`AuditWindow`] entities.
+///
+/// The initial [`DashboardConfig`] can be set with the builder methods, e.g.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::DashboardPlugin;
+/// DashboardPlugin::new()
+///     .default_history_secs(30.0)
+///     .toggle_key(KeyCode::F3);
+/// ```
+#[derive(Default)]
+pub struct DashboardPlugin {
+    config: DashboardConfig,
+    sample_schedule: Option<InternedScheduleLabel>,
+    localization: Option<DashboardLocalization>,
+    #[cfg(feature = "layout")]
+    layout_file: Option<LayoutFile>,
+    #[cfg(feature = "autosave")]
+    layout_autosave: Option<LayoutAutosave>,
+    #[cfg(feature = "report")]
+    report_file: Option<ReportFile>,
+}
+
+impl DashboardPlugin {
+    /// Create a plugin with the default [`DashboardConfig`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a plugin with a custom [`DashboardConfig`].
+    pub fn with_config(config: DashboardConfig) -> Self {
+        Self {
+            config,
+            ..default()
+        }
+    }
+
+    /// See [`DashboardConfig::default_window_size`].
+    pub fn default_window_size(mut self, size: usize) -> Self {
+        self.config.default_window_size = size;
+        self
+    }
+
+    /// See [`DashboardConfig::default_history_secs`].
+    pub fn default_history_secs(mut self, seconds: f64) -> Self {
+        self.config.default_history_secs = Some(seconds);
+        self
+    }
+
+    /// See [`DashboardConfig::namespace_separator`].
+    pub fn namespace_separator(mut self, separator: impl Into<String>) -> Self {
+        self.config.namespace_separator = separator.into();
+        self
+    }
+
+    /// See [`DashboardConfig::ui_refresh_rate`].
+    pub fn ui_refresh_rate(mut self, hz: f64) -> Self {
+        self.config.ui_refresh_rate = hz;
+        self
+    }
+
+    /// See [`DashboardConfig::performance_budget`].
+    pub fn performance_budget(mut self, budget: PerformanceBudget) -> Self {
+        self.config.performance_budget = Some(budget);
+        self
+    }
 
-/// Updates and renders all [`DashboardWindow`] and [`NamespaceTreeWindow`]
-/// entities.
-pub struct DashboardPlugin;
+    /// See [`DashboardConfig::unfocused_behavior`].
+    pub fn unfocused_behavior(mut self, behavior: UnfocusedBehavior) -> Self {
+        self.config.unfocused_behavior = behavior;
+        self
+    }
+
+    /// See [`DashboardConfig::read_only`].
+    pub fn read_only(mut self, read_only: ReadOnly) -> Self {
+        self.config.read_only = read_only;
+        self
+    }
+
+    /// See [`DashboardConfig::toggle_key`].
+    pub fn toggle_key(mut self, key: KeyCode) -> Self {
+        self.config.toggle_key = Some(key);
+        self
+    }
+
+    /// See [`DashboardConfig::visible`].
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.config.visible = visible;
+        self
+    }
+
+    /// See [`DashboardConfig::display_names`].
+    pub fn display_names(mut self, names: impl Into<DisplayNames>) -> Self {
+        self.config.display_names = names.into();
+        self
+    }
+
+    /// See [`DashboardConfig::plot_style`].
+    pub fn plot_style(mut self, style: PlotStyle) -> Self {
+        self.config.plot_style = style;
+        self
+    }
+
+    /// Add an entry to [`DashboardConfig::plot_defaults`], with a lower
+    /// priority than the entries added before.
+    pub fn plot_default(mut self, pattern: impl Into<String>, config: MetricPlotConfig) -> Self {
+        self.config
+            .plot_defaults
+            .push(PlotDefault::new(pattern, config));
+        self
+    }
+
+    /// See [`DashboardConfig::theme`].
+    pub fn theme(mut self, theme: DashboardTheme) -> Self {
+        self.config.theme = theme;
+        self
+    }
+
+    /// See [`DashboardConfig::search_hotkeys`].
+    pub fn search_hotkeys(mut self, enabled: bool) -> Self {
+        self.config.search_hotkeys = enabled;
+        self
+    }
+
+    /// See [`DashboardConfig::compact_width`].
+    pub fn compact_width(mut self, width: f32) -> Self {
+        self.config.compact_width = width;
+        self
+    }
+
+    /// See [`DashboardConfig::search_debounce`].
+    pub fn search_debounce(mut self, debounce: Duration) -> Self {
+        self.config.search_debounce = debounce;
+        self
+    }
+
+    /// See [`DashboardConfig::open_all_limit`].
+    pub fn open_all_limit(mut self, limit: usize) -> Self {
+        self.config.open_all_limit = limit;
+        self
+    }
+
+    /// See [`DashboardConfig::palette_shortcut`].
+    pub fn palette_shortcut(mut self, shortcut: Option<KeyboardShortcut>) -> Self {
+        self.config.palette_shortcut = shortcut;
+        self
+    }
+
+    /// See [`DashboardConfig::infer_units`].
+    pub fn infer_units(mut self, enabled: bool) -> Self {
+        self.config.infer_units = enabled;
+        self
+    }
+
+    /// See [`DashboardConfig::unit_suffixes`].
+    pub fn unit_suffixes(mut self, suffixes: UnitSuffixes) -> Self {
+        self.config.unit_suffixes = suffixes;
+        self
+    }
+
+    /// See [`DashboardConfig::max_histogram_samples`].
+    pub fn max_histogram_samples(mut self, max: usize) -> Self {
+        self.config.max_histogram_samples = Some(max);
+        self
+    }
+
+    /// Add `W` to the "Add Widget" menu of dashboard windows as `label`, and
+    /// restore it from layouts.
+    ///
+    /// See [`DashboardWidgetTypes::register`](crate::widget::DashboardWidgetTypes::register).
+    pub fn widget<W: DashboardWidget + Default>(mut self, label: impl Into<String>) -> Self {
+        self.config.widget_types.register::<W>(label);
+        self
+    }
+
+    /// Translate the dashboard with `localization`, instead of keeping any
+    /// [`DashboardLocalization`] inserted by the app, or English.
+    pub fn localization(mut self, localization: DashboardLocalization) -> Self {
+        self.localization = Some(localization);
+        self
+    }
+
+    /// Run the [`SampleMetricsSet`](crate::SampleMetricsSet) in `schedule`
+    /// instead of [`Last`].
+    ///
+    /// Histogram samples recorded after the set runs but before the
+    /// [`BucketClearSet`](crate::BucketClearSet) are missed, unless the clearing is moved with
+    /// [`RegistryPlugin::clear_buckets_in`](crate::RegistryPlugin::clear_buckets_in)
+    /// too.
+    pub fn sample_metrics_in(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.sample_schedule = Some(schedule.intern());
+        self
+    }
+
+    #[cfg(feature = "layout")]
+    /// Apply the layout in `file` on startup, and save to it when
+    /// [`SaveLayout`](crate::layout::SaveLayout) is sent.
+    pub fn layout_file(mut self, file: LayoutFile) -> Self {
+        self.layout_file = Some(file);
+        self
+    }
+
+    #[cfg(feature = "report")]
+    /// Write a [`DashboardReport`](crate::report::DashboardReport) to `file`
+    /// when [`ExportReport`] is sent, e.g. from the toolbar.
+    pub fn report_file(mut self, file: ReportFile) -> Self {
+        self.report_file = Some(file);
+        self
+    }
+
+    #[cfg(feature = "autosave")]
+    /// Save the layout periodically to `autosave`, and offer to restore it on
+    /// startup when it's newer than the [`Self::layout_file`].
+    pub fn layout_autosave(mut self, autosave: LayoutAutosave) -> Self {
+        self.layout_autosave = Some(autosave);
+        self
+    }
+}
 
 impl Plugin for DashboardPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_event::<RequestPlot>()
-            .init_resource::<CachedPlotConfigs>()
+        add_sampling(app, self.config.clone(), self.sample_schedule);
+        if let Some(localization) = &self.localization {
+            app.insert_resource(localization.clone());
+        }
+        app.world_mut()
+            .get_resource_or_init::<DashboardCommands>()
+            .add_dashboard_commands();
+        app.register_type::<DashboardConfig>()
+            .register_type::<DashboardWindow>()
+            .register_type::<NamespaceTreeWindow>()
+            .register_type::<AuditWindow>()
+            .register_type::<MetricPlotConfig>()
+            .register_type::<BudgetBarConfig>()
+            .register_type::<ScatterPlotConfig>()
+            .register_type::<DashboardColor>()
+            .register_type::<MetricPlot>()
+            .register_type::<MetricSearchBar>()
+            // Widgets aren't registered through the window, since their
+            // types are erased.
+            .register_type::<BudgetBar>()
+            .register_type::<DifferencePlot>()
+            .register_type::<Leaderboard>()
+            .register_type::<ScatterPlot>()
+            .register_type::<SnapshotDiff>()
+            .init_resource::<CommandPalette>()
             .add_systems(
-                EguiContextPass,
-                (DashboardWindow::draw_all, NamespaceTreeWindow::draw_all),
+                Update,
+                (
+                    DashboardConfig::toggle_visibility,
+                    DashboardFocus::track_windows,
+                ),
             )
-            // Enforce strict ordering:
-            // metrics producers (before Last) --> metrics consumers --> bucket clearing
             .add_systems(
-                Last,
-                DashboardWindow::update_plots_on_all_windows.before(ClearBucketsSystem),
+                EguiContextPass,
+                (
+                    CommandPalette::draw.before(DashboardWindow::draw_all),
+                    DashboardWindow::handle_plot_requests.before(DashboardWindow::draw_all),
+                    DashboardWindow::draw_all,
+                    NamespaceTreeWindow::draw_all,
+                    AuditWindow::draw_all,
+                ),
             );
+
+        #[cfg(feature = "detached_window")]
+        app.add_systems(
+            Update,
+            (
+                crate::DetachedDashboard::close_os_windows,
+                crate::DetachedDashboard::open_os_windows,
+                crate::DetachedDashboard::sync_os_windows,
+            )
+                .chain(),
+        );
+
+        #[cfg(feature = "layout")]
+        {
+            app.add_event::<SaveLayout>();
+            if let Some(file) = &self.layout_file {
+                app.insert_resource(file.clone())
+                    .add_systems(PostStartup, LayoutFile::apply_on_startup)
+                    .add_systems(
+                        Update,
+                        (LayoutFile::apply_on_change, LayoutFile::save_on_request),
+                    );
+            }
+        }
+
+        #[cfg(feature = "report")]
+        {
+            app.add_event::<ExportReport>();
+            if let Some(file) = &self.report_file {
+                app.insert_resource(file.clone())
+                    .add_systems(Update, ReportFile::export_on_request);
+            }
+        }
+
+        #[cfg(feature = "autosave")]
+        if let Some(autosave) = &self.layout_autosave {
+            let check_on_startup = LayoutAutosave::check_on_startup;
+            app.insert_resource(autosave.clone())
+                .add_systems(
+                    PostStartup,
+                    check_on_startup.after(LayoutFile::apply_on_startup),
+                )
+                .add_systems(
+                    Update,
+                    LayoutAutosave::autosave.after(LayoutFile::save_on_request),
+                )
+                .add_systems(
+                    EguiContextPass,
+                    LayoutAutosave::draw_restore_prompt.before(DashboardWindow::draw_all),
+                );
+        }
     }
 }
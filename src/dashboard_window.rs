@@ -1,47 +1,788 @@
-use crate::egui::{self, Ui};
+use crate::egui::{
+    self, collapsing_header::CollapsingState, Color32, Key, KeyboardShortcut, Label, Modifiers,
+    Sense, Ui,
+};
 use crate::{
-    plots::{window_size_slider, MetricPlot, MetricPlotConfig},
-    registry::{MetricKey, MetricsRegistry},
-    search_bar::SearchBar,
+    budget_bar::{BudgetBar, BudgetBarConfig},
+    comparison::{draw_comparison, pair_by_label, ComparisonGroup},
+    context_menu::metric_context_menu_with,
+    core_metrics::names,
+    difference_plot::{DifferencePlot, DifferencePlotConfig},
+    display_names::DisplayNames,
+    focus::{DashboardFocus, UnfocusedBehavior, CATCH_UP_PLOTS_PER_FRAME, UNFOCUSED_REFRESH_RATE},
+    leaderboard::{Leaderboard, LeaderboardConfig},
+    localization::{DashboardLocalization, UiText},
+    namespace_tree::DEFAULT_NAMESPACE_SEPARATOR,
+    overlay::OverlayCorner,
+    performance_budget::{DashboardPerformance, Degradation, PerformanceBudget},
+    plot_defaults::PlotDefaults,
+    plot_style::PlotStyle,
+    plots::{
+        window_size_slider, GaugePlotConfig, MetricPlot, MetricPlotConfig, SamplingMode,
+        DEFAULT_WINDOW_SIZE, MAX_ANNOTATIONS,
+    },
+    read_only::ReadOnly,
+    registry::{MetricKey, MetricUnit, MetricsRegistry, UnitSuffixes},
+    scatter_plot::{ScatterPlot, ScatterPlotConfig},
+    search_bar::{MetricSearchBar, SearchSelection, DEFAULT_SEARCH_DEBOUNCE},
+    snapshot::FrameMetricsSnapshot,
+    snapshot_diff::{SnapshotDiff, SnapshotDiffConfig},
+    theme::{DashboardTheme, ThemePalette},
+    title_summary::{TitleSummary, TitleSummaryConfig},
+    virtual_time::VirtualTimeAdvanced,
+    widget::{DashboardWidget, DashboardWidgetTypes, WidgetContext},
+};
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{
+        utility::NonGenericTypeInfoCell, ApplyError, FieldIter, GetTypeRegistration, ReflectKind,
+        ReflectMut, ReflectOwned, ReflectRef, Struct, StructInfo, TypeInfo, TypeRegistration,
+        Typed,
+    },
 };
-use bevy::{platform::collections::HashMap, prelude::*};
+use float_ord::FloatOrd;
 use metrics::Unit;
+use metrics_util::MetricKind;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "layout")]
+use crate::layout::{AnnotationLayout, PlotLayout, SaveLayout, WidgetLayout, WindowLayout};
 #[cfg(feature = "bevy_egui")]
-use crate::namespace_tree::NamespaceTreeWindow;
+use crate::{audit_window::AuditWindow, namespace_tree::NamespaceTreeWindow};
 
 /// Event used to create a new plot in all [`DashboardWindow`] entities.
+///
+/// Create it with [`Self::new`], since fields may be added:
+///
+/// ```
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlotConfig};
+/// # use bevy_metrics_dashboard::registry::MetricKey;
+/// # use bevy_metrics_dashboard::RequestPlot;
+/// let key = MetricKey::new(metrics::Key::from_name("enemies"), MetricKind::Gauge);
+/// let request = RequestPlot::new(key, None)
+///     .with_config(MetricPlotConfig::Gauge(GaugePlotConfig::default()));
+/// assert!(request.config.is_some());
+/// ```
 #[allow(missing_docs)]
 #[derive(Clone, Event)]
+#[non_exhaustive]
 pub struct RequestPlot {
     pub key: MetricKey,
-    pub unit: Option<Unit>,
+    pub unit: Option<MetricUnit>,
+    /// When `Some`, this is used instead of the cached or default config.
+    pub config: Option<MetricPlotConfig>,
+}
+
+impl RequestPlot {
+    /// Request a plot with the cached or default config.
+    pub fn new(key: MetricKey, unit: Option<MetricUnit>) -> Self {
+        Self {
+            key,
+            unit,
+            config: None,
+        }
+    }
+
+    /// Use `config` instead of the cached or default config.
+    pub fn with_config(mut self, config: MetricPlotConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+}
+
+/// Event used to create plots for several metrics at once in all
+/// [`DashboardWindow`] entities, like the "Open All" button next to the
+/// search results.
+///
+/// The plots use the default config of their kind, not the cached one.
+#[derive(Clone, Event)]
+pub struct OpenPlots {
+    /// The metrics to plot, with their units.
+    pub metrics: Vec<(MetricKey, Option<MetricUnit>)>,
+    /// When `Some`, the plots are put in the [group](MetricPlot::group)
+    /// with this name.
+    pub group: Option<String>,
+}
+
+/// Event used to mark the current time on every time-series plot in all
+/// [`DashboardWindow`] entities, e.g. "level loaded".
+///
+/// Hovering the marker shows the label. Plots and windows keep their latest
+/// [`MAX_ANNOTATIONS`] annotations, and plots added later are marked with
+/// the window's at their first sample. They're saved with the
+/// [layout](crate::layout::WindowLayout::annotations).
+#[derive(Clone, Event)]
+pub struct DashboardAnnotation {
+    /// Text shown when hovering the marker.
+    pub label: String,
+    /// Color of the marker. When `None`, the plot picks a color.
+    pub color: Option<Color32>,
+}
+
+impl DashboardAnnotation {
+    /// Create an annotation with the default color.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            color: None,
+        }
+    }
 }
 
 /// Cache of configs for plots that have been opened and removed.
+///
+/// Dereferences to the configs of [`MetricPlot`]s. [`MetricKey`]s match
+/// regardless of the order of their labels, so a config is found again even
+/// if the metric's labels are added in a different order, e.g. in the next
+/// run:
+///
+/// ```
+/// # use bevy_metrics_dashboard::{CachedPlotConfigs, DashboardWindow};
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlotConfig};
+/// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+/// # use metrics::{Key, Label, Recorder};
+/// let key = |labels: [(&'static str, &'static str); 2]| {
+///     let labels = labels.map(|(k, v)| Label::new(k, v)).to_vec();
+///     MetricKey::new(Key::from_parts("enemies", labels), MetricKind::Gauge)
+/// };
+/// let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+///     window_size: Some(42),
+///     ..Default::default()
+/// });
+/// let mut cached_configs = CachedPlotConfigs::default();
+/// cached_configs.insert(key([("team", "red"), ("zone", "north")]), config.clone());
+///
+/// for labels in [
+///     [("team", "red"), ("zone", "north")],
+///     [("zone", "north"), ("team", "red")],
+/// ] {
+///     let registry = MetricsRegistry::new();
+///     let key = key(labels);
+///     registry.register_gauge(&key.key, &metrics::Metadata::new("", metrics::Level::INFO, None));
+///     let mut window = DashboardWindow::new("Metrics");
+///     window.add_plot(&registry, &cached_configs, &Default::default(), key, None);
+///     assert!(window.plots()[0].clone_config() == config);
+/// }
+/// ```
 #[derive(Default, Deref, DerefMut, Resource)]
-pub struct CachedPlotConfigs(HashMap<MetricKey, MetricPlotConfig>);
+pub struct CachedPlotConfigs {
+    #[deref]
+    plots: HashMap<MetricKey, MetricPlotConfig>,
+    /// Configs of [`ScatterPlot`]s, by their x and y metrics.
+    pub scatter_plots: HashMap<(MetricKey, MetricKey), ScatterPlotConfig>,
+    /// Configs of [`DifferencePlot`]s, by their operands.
+    pub difference_plots: HashMap<(MetricKey, MetricKey), DifferencePlotConfig>,
+}
 
 /// An `egui` window that can search for metrics and plot them.
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct DashboardWindow {
     title: String,
-    search_bar: SearchBar,
+    /// Reflected for inspectors, but not saved in scenes; see
+    /// [`DashboardSceneState`](crate::DashboardSceneState) for plots.
+    #[reflect(skip_serializing, default)]
+    search_bar: MetricSearchBar,
+    #[reflect(skip_serializing, default)]
     plots: Vec<MetricPlot>,
-    config: DashboardConfig,
+    /// Budget bars, scatter plots, difference plots and custom widgets, in
+    /// the order they're drawn.
+    #[reflect(skip_serializing, default)]
+    widgets: Vec<WidgetSlot>,
+    #[reflect(ignore)]
+    next_widget_id: u64,
+    config: DashboardWindowConfig,
+    /// Shows the quick add row even if there are plots.
+    quick_add_open: bool,
+    /// Whether the "Open All" button asks for confirmation.
+    confirm_open_all: bool,
+    #[reflect(ignore)]
+    title_summary: TitleSummary,
+    /// The OS window whose egui context the window is drawn in, instead of
+    /// the primary one. Not reflected, since it's only valid in this run.
+    #[reflect(ignore)]
+    os_window: Option<Entity>,
+    /// Indices of the plots that still need to be refreshed after the app
+    /// was focused again, in reverse order.
+    #[reflect(ignore)]
+    catch_up: Vec<usize>,
+    /// The latest annotations, oldest first, for plots added later.
+    #[reflect(ignore)]
+    annotations: VecDeque<DashboardAnnotation>,
+}
+
+/// A [`DashboardWidget`] with an ID that's unique within its window, so its
+/// collapsing state follows it when it's moved.
+///
+/// Reflected as a struct with the widget's
+/// [state](DashboardWidget::reflect_state) as its `widget` field, or with no
+/// fields for widgets without one.
+#[derive(TypePath)]
+struct WidgetSlot {
+    id: u64,
+    widget: Box<dyn DashboardWidget>,
+}
+
+impl WidgetSlot {
+    const FIELD: &'static str = "widget";
+}
+
+impl PartialReflect for WidgetSlot {
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        Some(Self::type_info())
+    }
+
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Ok(self)
+    }
+
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        let ReflectRef::Struct(value) = value.reflect_ref() else {
+            return Err(ApplyError::MismatchedKinds {
+                from_kind: value.reflect_kind(),
+                to_kind: ReflectKind::Struct,
+            });
+        };
+        match (self.widget.reflect_state_mut(), value.field(Self::FIELD)) {
+            (Some(state), Some(value)) => state.try_apply(value),
+            _ => Ok(()),
+        }
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::Struct(self)
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::Struct(self)
+    }
+
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::Struct(self)
+    }
+}
+
+impl Reflect for WidgetSlot {
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn into_reflect(self: Box<Self>) -> Box<dyn Reflect> {
+        self
+    }
+
+    fn as_reflect(&self) -> &dyn Reflect {
+        self
+    }
+
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect {
+        self
+    }
+
+    fn set(&mut self, value: Box<dyn Reflect>) -> Result<(), Box<dyn Reflect>> {
+        *self = value.take()?;
+        Ok(())
+    }
+}
+
+impl Struct for WidgetSlot {
+    fn field(&self, name: &str) -> Option<&dyn PartialReflect> {
+        (name == Self::FIELD)
+            .then(|| self.widget.reflect_state())
+            .flatten()
+            .map(PartialReflect::as_partial_reflect)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn PartialReflect> {
+        (name == Self::FIELD)
+            .then(|| self.widget.reflect_state_mut())
+            .flatten()
+            .map(PartialReflect::as_partial_reflect_mut)
+    }
+
+    fn field_at(&self, index: usize) -> Option<&dyn PartialReflect> {
+        (index == 0).then(|| self.field(Self::FIELD)).flatten()
+    }
+
+    fn field_at_mut(&mut self, index: usize) -> Option<&mut dyn PartialReflect> {
+        (index == 0).then(|| self.field_mut(Self::FIELD)).flatten()
+    }
+
+    fn name_at(&self, index: usize) -> Option<&str> {
+        (index < self.field_len()).then_some(Self::FIELD)
+    }
+
+    fn field_len(&self) -> usize {
+        usize::from(self.widget.reflect_state().is_some())
+    }
+
+    fn iter_fields(&self) -> FieldIter<'_> {
+        FieldIter::new(self)
+    }
+}
+
+impl Typed for WidgetSlot {
+    /// Has no fields, since they depend on the widget.
+    fn type_info() -> &'static TypeInfo {
+        static CELL: NonGenericTypeInfoCell = NonGenericTypeInfoCell::new();
+        CELL.get_or_set(|| TypeInfo::Struct(StructInfo::new::<Self>(&[])))
+    }
+}
+
+impl GetTypeRegistration for WidgetSlot {
+    fn get_type_registration() -> TypeRegistration {
+        TypeRegistration::of::<Self>()
+    }
+}
+
+/// Widgets can't be created from reflected values.
+impl FromReflect for WidgetSlot {
+    fn from_reflect(_: &dyn PartialReflect) -> Option<Self> {
+        None
+    }
+}
+
+/// A core metric that can be plotted with one click.
+struct QuickAdd {
+    label: UiText,
+    name: &'static str,
+    unit: Unit,
+    smoothing_weight: f64,
+}
+
+const QUICK_ADDS: [QuickAdd; 3] = [
+    QuickAdd {
+        label: UiText::FrameTime,
+        name: names::FRAME_TIME,
+        unit: Unit::Milliseconds,
+        smoothing_weight: 0.5,
+    },
+    QuickAdd {
+        label: UiText::Fps,
+        name: names::FRAMES_PER_SECOND,
+        unit: Unit::CountPerSecond,
+        smoothing_weight: 0.9,
+    },
+    QuickAdd {
+        label: UiText::Entities,
+        name: names::ENTITIES,
+        unit: Unit::Count,
+        smoothing_weight: 0.0,
+    },
+];
+
+/// How many samples quick add plots show.
+const QUICK_ADD_WINDOW_SIZE: usize = 1000;
+
+/// Configuration shared by all dashboard windows.
+///
+/// Inserted by the `DashboardPlugin`, which also offers builder methods for
+/// setting the initial values. Changes made at runtime take effect on the
+/// next frame.
+#[derive(Clone, Reflect, Resource)]
+#[reflect(Resource)]
+pub struct DashboardConfig {
+    /// How many samples are drawn in plots that don't override their window
+    /// size, unless [`Self::default_history_secs`] is set.
+    pub default_window_size: usize,
+    /// When `Some`, counter and gauge plots that don't override their window
+    /// size keep the samples of this many seconds instead, by the
+    /// [time](FrameMetricsSnapshot::elapsed) of the snapshots they were
+    /// sampled from, like a [`TrailingWindow`](crate::plots::TrailingWindow).
+    /// Their memory then grows with the frame rate.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::egui::{self, CentralPanel};
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::plots::{MetricPlot, MetricPlotConfig};
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::{DashboardConfig, DashboardWindowConfig, FrameMetricsSnapshot};
+    /// # use metrics::Key;
+    /// # use std::sync::atomic::Ordering;
+    /// # use std::time::Duration;
+    /// let registry = MetricsRegistry::new();
+    /// let key = MetricKey::new(Key::from_static_name("speed"), MetricKind::Gauge);
+    /// let config = MetricPlotConfig::Gauge(Default::default());
+    /// let mut plot = MetricPlot::new(&registry, "speed", key.clone(), None, config);
+    /// let config = DashboardConfig {
+    ///     default_history_secs: Some(2.0),
+    ///     ..Default::default()
+    /// };
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// let ctx = egui::Context::default();
+    /// for i in 0..10 {
+    ///     let gauge = registry.get_or_create_gauge(&key.key);
+    ///     gauge.store((i as f64).to_bits(), Ordering::Relaxed);
+    ///     snapshot.capture(&registry, [&key]);
+    ///     snapshot.set_elapsed(Some(Duration::from_millis(500 * i)));
+    ///     plot.update(&snapshot);
+    ///     let _ = ctx.run(Default::default(), |ctx| {
+    ///         CentralPanel::default().show(ctx, |ui| {
+    ///             plot.draw(&config, &DashboardWindowConfig::default(), ui);
+    ///         });
+    ///     });
+    /// }
+    /// // The samples from 2.5 to 4.5 seconds.
+    /// assert_eq!(plot.n_samples(), 5);
+    /// ```
+    pub default_history_secs: Option<f64>,
+    /// The delimiter between namespace components in metric names, used by
+    /// [`NamespaceTreeWindow`](crate::NamespaceTreeWindow).
+    pub namespace_separator: String,
+    /// When `Some`, pressing this key toggles [`Self::visible`].
+    pub toggle_key: Option<KeyCode>,
+    /// Whether dashboard windows are drawn. Plots keep updating while hidden.
+    pub visible: bool,
+    /// How many times per second plots are [refreshed](MetricPlot::refresh).
+    ///
+    /// Samples are still collected every frame. Values that aren't positive
+    /// and finite refresh every frame.
+    pub ui_refresh_rate: f64,
+    /// When `Some`, the dashboard skips work while its own cost per frame
+    /// is over this budget. See [`DashboardPerformance`].
+    pub performance_budget: Option<PerformanceBudget>,
+    /// What the dashboard does while the app isn't
+    /// [focused](crate::DashboardFocus).
+    pub unfocused_behavior: UnfocusedBehavior,
+    /// Which plots can't be changed from the UI. See [`ReadOnly`].
+    pub read_only: ReadOnly,
+    /// Display names of label values in all plots, applied before the
+    /// [per-plot names](MetricPlot::display_names).
+    #[reflect(ignore)]
+    pub display_names: DisplayNames,
+    /// The style of all plots that don't
+    /// [override](MetricPlot::style_override) it.
+    pub plot_style: PlotStyle,
+    /// Configs that new plots start with, by metric name pattern, unless
+    /// they were opened before. See [`plot_defaults`](crate::plot_defaults).
+    #[reflect(ignore)]
+    pub plot_defaults: PlotDefaults,
+    /// The colors of all dashboard windows and the overlay.
+    pub theme: DashboardTheme,
+    /// Whether the keyboard can open the search bar of the top-most
+    /// dashboard window: [`SEARCH_SHORTCUT`] and `/` focus it, and typing
+    /// while the pointer is over the window and no widget has keyboard focus
+    /// starts a new search.
+    ///
+    /// Disable this if the app reads keys the dashboard would take, e.g.
+    /// when a game is controlled with the keyboard while the dashboard is
+    /// open.
+    pub search_hotkeys: bool,
+    /// Dashboard windows narrower than this, in logical pixels, switch to a
+    /// compact layout: the toolbar collapses into a menu button, and plot
+    /// titles are truncated, showing the full title on hover.
+    ///
+    /// Set this to zero to always use the full layout.
+    pub compact_width: f32,
+    /// How long the query in a window's search bar must stay unchanged
+    /// before it's searched. See [`MetricSearchBar::set_debounce`].
+    pub search_debounce: Duration,
+    /// How many search results the "Open All" button plots before asking
+    /// for confirmation.
+    pub open_all_limit: usize,
+    /// Whether metrics without a described unit get one inferred from the
+    /// end of their name, like milliseconds for `asset_load_ms`, using
+    /// [`Self::unit_suffixes`].
+    ///
+    /// See [`MetricsRegistry::set_unit_suffixes`].
+    pub infer_units: bool,
+    /// The suffixes units are inferred from, when [`Self::infer_units`] is
+    /// enabled.
+    #[reflect(ignore)]
+    pub unit_suffixes: UnitSuffixes,
+    /// When `Some`, at most this many samples of each histogram are
+    /// captured per frame, bounding the cost of metrics that are recorded
+    /// many times per frame. The other samples are left out of plots and
+    /// widgets.
+    ///
+    /// See [`FrameMetricsSnapshot::set_histogram_cap`].
+    pub max_histogram_samples: Option<usize>,
+    /// When `Some`, pressing this shortcut toggles the `CommandPalette`.
+    #[reflect(ignore)]
+    pub palette_shortcut: Option<KeyboardShortcut>,
+    /// The [`DashboardWidget`]s that windows can create from their "Add
+    /// Widget" menu and from layouts.
+    #[reflect(ignore)]
+    pub widget_types: DashboardWidgetTypes,
+}
+
+/// The default [`DashboardConfig::ui_refresh_rate`].
+pub const DEFAULT_UI_REFRESH_RATE: f64 = 30.0;
+
+/// The default [`DashboardConfig::compact_width`].
+pub const DEFAULT_COMPACT_WIDTH: f32 = 640.0;
+
+/// The default [`DashboardConfig::open_all_limit`].
+pub const DEFAULT_OPEN_ALL_LIMIT: usize = 20;
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            default_window_size: DEFAULT_WINDOW_SIZE,
+            default_history_secs: None,
+            namespace_separator: DEFAULT_NAMESPACE_SEPARATOR.into(),
+            toggle_key: None,
+            visible: true,
+            ui_refresh_rate: DEFAULT_UI_REFRESH_RATE,
+            performance_budget: None,
+            unfocused_behavior: default(),
+            read_only: default(),
+            display_names: default(),
+            plot_style: default(),
+            plot_defaults: default(),
+            theme: default(),
+            search_hotkeys: true,
+            compact_width: DEFAULT_COMPACT_WIDTH,
+            search_debounce: DEFAULT_SEARCH_DEBOUNCE,
+            open_all_limit: DEFAULT_OPEN_ALL_LIMIT,
+            infer_units: true,
+            unit_suffixes: default(),
+            max_histogram_samples: None,
+            palette_shortcut: Some(COMMAND_PALETTE_SHORTCUT),
+            widget_types: default(),
+        }
+    }
+}
+
+impl DashboardConfig {
+    /// Whether a dashboard window with `width` logical pixels of content
+    /// uses the compact layout. See [`Self::compact_width`].
+    ///
+    /// In the compact layout, plots fit in windows as narrow as a small game
+    /// window, however long their titles:
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::egui::{self, CentralPanel, Pos2, RawInput, Rect};
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::{
+    /// #     CachedPlotConfigs, DashboardConfig, DashboardWindow, FrameMetricsSnapshot,
+    /// # };
+    /// # use metrics::{Key, Level, Metadata, Recorder};
+    /// let name = "render.pipelines.shadow_cascades.per_view.per_cascade.queued_draw_calls";
+    /// let key = MetricKey::new(Key::from_static_name(name), MetricKind::Gauge);
+    /// let registry = MetricsRegistry::new();
+    /// registry.register_gauge(&key.key, &Metadata::new("", Level::INFO, None));
+    /// let mut window = DashboardWindow::new("Metrics");
+    /// let mut cached_configs = CachedPlotConfigs::default();
+    /// window.add_plot(&registry, &cached_configs, &Default::default(), key, None);
+    ///
+    /// let snapshot = FrameMetricsSnapshot::default();
+    /// let config = DashboardConfig::default();
+    /// let ctx = egui::Context::default();
+    /// for (width, compact) in [(1280.0, false), (960.0, false), (640.0, true), (480.0, true)] {
+    ///     let input = RawInput {
+    ///         screen_rect: Some(Rect::from_min_size(Pos2::ZERO, [width, 720.0].into())),
+    ///         ..Default::default()
+    ///     };
+    ///     let mut used_width = 0.0;
+    ///     let _ = ctx.run(input, |ctx| {
+    ///         CentralPanel::default().show(ctx, |ui| {
+    ///             assert_eq!(config.is_compact(ui.available_width()), compact);
+    ///             window.draw_plots(&registry, &snapshot, &config, &mut cached_configs, ui);
+    ///             used_width = ui.min_rect().width();
+    ///         });
+    ///     });
+    ///     assert!(used_width <= width, "{used_width} > {width}");
+    /// }
+    /// ```
+    pub fn is_compact(&self, width: f32) -> bool {
+        width < self.compact_width
+    }
+
+    /// Bevy system that toggles [`Self::visible`] when [`Self::toggle_key`]
+    /// is pressed.
+    pub fn toggle_visibility(mut config: ResMut<Self>, keys: Option<Res<ButtonInput<KeyCode>>>) {
+        let (Some(key), Some(keys)) = (config.toggle_key, keys) else {
+            return;
+        };
+        if keys.just_pressed(key) {
+            config.visible = !config.visible;
+        }
+    }
+
+    /// Bevy system that applies [`Self::infer_units`] and
+    /// [`Self::unit_suffixes`] to the registry when they change.
+    pub fn sync_unit_inference(config: Res<Self>, registry: Res<MetricsRegistry>) {
+        if config.is_changed() {
+            registry.set_unit_suffixes(config.infer_units.then(|| config.unit_suffixes.clone()));
+        }
+    }
+
+    /// Bevy system that applies [`Self::max_histogram_samples`] to the
+    /// [`FrameMetricsSnapshot`] when it changes.
+    pub fn sync_histogram_cap(config: Res<Self>, mut snapshot: ResMut<FrameMetricsSnapshot>) {
+        if config.is_changed() {
+            snapshot.set_histogram_cap(config.max_histogram_samples);
+        }
+    }
+
+    fn ui_refresh_period(&self) -> Duration {
+        Duration::try_from_secs_f64(self.ui_refresh_rate.recip()).unwrap_or_default()
+    }
+
+    /// The [`Self::unfocused_behavior`] if the app isn't `focused`.
+    fn behavior(&self, focused: bool) -> UnfocusedBehavior {
+        if focused {
+            UnfocusedBehavior::Full
+        } else {
+            self.unfocused_behavior
+        }
+    }
 }
 
 /// Configuration for a single [`DashboardWindow`].
 ///
 /// Can be edited with [`DashboardWindow::configure_ui`].
-#[derive(Default)]
-pub struct DashboardConfig {
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct DashboardWindowConfig {
     /// Synchronizes the window size of all plots in this window.
     pub global_window_size: Option<usize>,
     /// Pauses all plots.
     pub paused: bool,
+    /// The order in which plots are drawn.
+    ///
+    /// The manual order is kept while an automatic order is active.
+    pub sort: PlotSort,
+    /// When `Some`, core metrics are summarized in the window title.
+    pub title_summary: Option<TitleSummaryConfig>,
+    /// When `Some`, counter and gauge plots whose keys differ only by the
+    /// value of this label are drawn together in one plot, along with their
+    /// differences. See [`pair_by_label`](crate::comparison::pair_by_label).
+    pub comparison_label: Option<String>,
+    /// Shows the search bar as a single button, to leave more room for
+    /// plots. The query and filters are kept.
+    ///
+    /// Pressing [`SEARCH_SHORTCUT`] while the window is on top expands the
+    /// search bar again, unless [`DashboardConfig::search_hotkeys`] is
+    /// disabled.
+    pub search_collapsed: bool,
+    /// The opacity of the window's background, from 0 to 1, e.g. to keep
+    /// the game visible behind it while recording. Plots and text stay
+    /// opaque.
+    pub opacity: f32,
+    /// Hides the title bar. The window can still be moved by dragging its
+    /// background.
+    pub compact_chrome: bool,
+    /// Keeps the OS window above other windows while the dashboard is in
+    /// its own OS window, with the `detached_window` feature. Ignored on
+    /// platforms that don't support it, and while the dashboard is drawn
+    /// over the game.
+    pub always_on_top: bool,
+    /// Plots in this window don't sample in frames where [`Time<Virtual>`]
+    /// is paused, unless they [override](MetricPlot::pause_with_virtual_time)
+    /// it. Budget bars and widgets always follow this one.
+    ///
+    /// This is only a pause flag: samples aren't timestamped with virtual
+    /// time, and the x axis still counts samples. Changing it annotates the
+    /// affected plots.
+    pub pause_with_virtual_time: bool,
+}
+
+impl Default for DashboardWindowConfig {
+    fn default() -> Self {
+        Self {
+            global_window_size: None,
+            paused: false,
+            sort: default(),
+            title_summary: None,
+            comparison_label: None,
+            search_collapsed: false,
+            opacity: 1.0,
+            compact_chrome: false,
+            always_on_top: false,
+            pause_with_virtual_time: false,
+        }
+    }
+}
+
+/// Expands and focuses the search bar of the top-most [`DashboardWindow`].
+///
+/// See [`DashboardConfig::search_hotkeys`].
+pub const SEARCH_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::F);
+
+/// The default [`DashboardConfig::palette_shortcut`].
+pub const COMMAND_PALETTE_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::P);
+
+/// The order of plots in a [`DashboardWindow`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum PlotSort {
+    /// Newest first, unless reordered by dragging plot headers.
+    #[default]
+    Manual,
+    /// By plot name.
+    Alphabetical,
+    /// By [`MetricPlot::latest_value`], descending. Plots without a value
+    /// come last.
+    LatestValue,
+    /// By [`MetricPlot::created`], newest first.
+    RecentlyAdded,
+    /// [Alerting](MetricPlot::is_alerting) plots first, then in manual order.
+    AlertsFirst,
+}
+
+impl PlotSort {
+    const ALL: [Self; 5] = [
+        Self::Manual,
+        Self::Alphabetical,
+        Self::LatestValue,
+        Self::RecentlyAdded,
+        Self::AlertsFirst,
+    ];
+
+    fn label(self) -> UiText {
+        match self {
+            Self::Manual => UiText::SortManual,
+            Self::Alphabetical => UiText::SortAlphabetical,
+            Self::LatestValue => UiText::SortLatestValue,
+            Self::RecentlyAdded => UiText::SortRecentlyAdded,
+            Self::AlertsFirst => UiText::SortAlertsFirst,
+        }
+    }
 }
 
+/// Drag and drop payload for reordering plots.
+struct DraggedPlot(usize);
+
+/// Drag and drop payload for reordering [`DashboardWidget`]s.
+struct DraggedWidget(usize);
+
 impl DashboardWindow {
     /// Create a new dashboard window without any plots.
     pub fn new(title: impl Into<String>) -> Self {
@@ -49,7 +790,15 @@ impl DashboardWindow {
             title: title.into(),
             search_bar: default(),
             plots: default(),
+            widgets: default(),
+            next_widget_id: 0,
             config: default(),
+            quick_add_open: false,
+            confirm_open_all: false,
+            title_summary: default(),
+            os_window: None,
+            catch_up: default(),
+            annotations: default(),
         }
     }
 
@@ -59,147 +808,1649 @@ impl DashboardWindow {
     }
 
     /// This window's configuration.
-    pub fn config(&self) -> &DashboardConfig {
+    pub fn config(&self) -> &DashboardWindowConfig {
         &self.config
     }
 
-    /// Bevy system that calls [`Self::update_plots`] on all window entities.
-    pub fn update_plots_on_all_windows(mut windows: Query<&mut Self>) {
-        for mut window in &mut windows {
-            if !window.config.paused {
-                window.update_plots();
-            }
-        }
+    /// The plots in this window, in the order they were added.
+    pub fn plots(&self) -> &[MetricPlot] {
+        &self.plots
     }
 
-    /// Calls [`MetricPlot::update`] on all plots in this window.
-    pub fn update_plots(&mut self) {
-        for plot in &mut self.plots {
-            plot.update();
+    /// The plots in this window, e.g. to [group](MetricPlot::set_group) or
+    /// [promote](MetricPlot::set_overlay) plots added in code.
+    pub fn plots_mut(&mut self) -> &mut [MetricPlot] {
+        &mut self.plots
+    }
+
+    /// Pause or resume sampling for all plots in this window.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.config.paused = paused;
+    }
+
+    /// See [`DashboardWindowConfig::pause_with_virtual_time`].
+    pub fn set_pause_with_virtual_time(&mut self, pause: bool) {
+        self.config.pause_with_virtual_time = pause;
+    }
+
+    /// Remove all [`MetricPlot`]s from this window, keeping their configs in
+    /// `cached_configs` for when the metrics are plotted again.
+    ///
+    /// Budget bars, scatter plots and difference plots are kept.
+    pub fn clear_plots(&mut self, cached_configs: &mut CachedPlotConfigs) {
+        for plot in self.plots.drain(..) {
+            cached_configs.insert(plot.key().clone(), plot.clone_config());
         }
     }
 
-    #[cfg(feature = "bevy_egui")]
-    /// Bevy system that draws all [`DashboardWindow`] entities into the
-    /// [`bevy_egui::EguiContexts`].
+    /// Like [`Self::clear_plots`], but keeps the plots `read_only` locks.
+    pub fn clear_unlocked_plots(
+        &mut self,
+        read_only: &ReadOnly,
+        cached_configs: &mut CachedPlotConfigs,
+    ) {
+        self.plots.retain(|plot| {
+            let locked = read_only.locks_group(plot.group());
+            if !locked {
+                cached_configs.insert(plot.key().clone(), plot.clone_config());
+            }
+            locked
+        });
+    }
+
+    /// The first plot of a metric named `name`, with any labels.
+    pub fn find_plot(&self, name: &str) -> Option<&MetricPlot> {
+        self.plots.iter().find(|plot| plot.key().key.name() == name)
+    }
+
+    /// The first plot of `key`.
+    pub fn find_plot_by_key(&self, key: &MetricKey) -> Option<&MetricPlot> {
+        self.plots.iter().find(|plot| plot.key() == key)
+    }
+
+    /// Bevy system that calls [`Self::collect_samples`] on all window entities
+    /// every frame, and [`Self::refresh_plots`] at the
+    /// [`DashboardConfig::ui_refresh_rate`].
     ///
-    /// Also handles [`RequestPlot`] events by creating a new plot in each window.
-    pub fn draw_all(
-        mut commands: Commands,
-        registry: Res<MetricsRegistry>,
-        mut cached_configs: ResMut<CachedPlotConfigs>,
-        mut ctxts: bevy_egui::EguiContexts,
-        mut requests: EventReader<RequestPlot>,
-        mut windows: Query<(Entity, &mut Self)>,
+    /// Also handles [`DashboardAnnotation`] events by annotating every plot,
+    /// and annotates plots that started or stopped pausing with virtual
+    /// time.
+    ///
+    /// This runs in the [`SampleMetricsSet`](crate::SampleMetricsSet), after
+    /// the [`FrameMetricsSnapshot`] is captured. The refreshes are reduced
+    /// while the dashboard is [degraded](DashboardPerformance::degradation),
+    /// and while the app isn't [focused](DashboardFocus), depending on the
+    /// [`DashboardConfig::unfocused_behavior`]. When the app is focused
+    /// again, the plots catch up over several frames, refreshing
+    /// [`CATCH_UP_PLOTS_PER_FRAME`] plots per window at a time.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_plots_on_all_windows(
+        snapshot: Res<FrameMetricsSnapshot>,
+        virtual_time: Res<VirtualTimeAdvanced>,
+        config: Res<DashboardConfig>,
+        localization: Res<DashboardLocalization>,
+        time: Res<Time<Real>>,
+        mut focus: ResMut<DashboardFocus>,
+        mut performance: ResMut<DashboardPerformance>,
+        mut since_refresh: Local<Duration>,
+        mut unfocused_behavior: Local<UnfocusedBehavior>,
+        mut annotations: EventReader<DashboardAnnotation>,
+        mut windows: Query<&mut Self>,
     ) {
-        let requests: Vec<_> = requests.read().cloned().collect();
+        let start = Instant::now();
+        let degradation = performance.degradation();
+        let behavior = config.behavior(focus.focused());
+        let mut period = config.ui_refresh_period() * degradation.refresh_rate_divisor();
+        if behavior == UnfocusedBehavior::ReducedRefresh {
+            period = period.max(Duration::from_secs_f64(UNFOCUSED_REFRESH_RATE.recip()));
+        }
+        *since_refresh += time.delta();
+        let mut refresh = *since_refresh >= period && behavior != UnfocusedBehavior::CollectOnly;
+        if refresh {
+            // Don't try to catch up after long frames.
+            *since_refresh = (*since_refresh - period).min(period);
+        }
+        // The behavior that was in effect while the app was unfocused.
+        let catch_up = focus.regained() && *unfocused_behavior != UnfocusedBehavior::Full;
+        if catch_up {
+            *since_refresh = Duration::ZERO;
+            refresh = false;
+        }
+        if !focus.focused() {
+            *unfocused_behavior = behavior;
+        }
+        focus.end_frame();
 
-        let ctxt = ctxts.ctx_mut();
-        for (entity, mut window) in &mut windows {
-            for RequestPlot { key, unit } in requests.iter().cloned() {
-                window.add_plot(&registry, &cached_configs, key, unit);
+        let annotations: Vec<_> = annotations.read().collect();
+        for mut window in &mut windows {
+            window.search_bar.update(&snapshot);
+            window.annotate_virtual_time_changes(&localization);
+            if !window.config.paused {
+                window.collect_samples(&snapshot, &virtual_time);
             }
-
-            let mut open = true;
-            egui::Window::new(&window.title)
-                .open(&mut open)
-                .show(ctxt, |ui| {
-                    ui.horizontal(|ui| {
-                        window.plot_selected_search_result(&registry, &cached_configs, ui);
-                        if ui.button("Browse").clicked() {
-                            commands.spawn(NamespaceTreeWindow::new("Namespace Viewer"));
-                        }
-                    });
-                    ui.collapsing("Global Settings", |ui| {
-                        window.configure_ui(ui);
-                    });
-                    ui.separator();
-                    window.draw_plots(&mut cached_configs, ui);
-                });
-            if !open {
-                commands.entity(entity).despawn();
+            window.update_title_summary(&snapshot, &localization, refresh);
+            if catch_up {
+                window.start_catch_up();
+            }
+            if !window.catch_up.is_empty() {
+                window.catch_up_plots(degradation);
+            } else if refresh {
+                window.refresh_plots_degraded(degradation);
+            }
+            for annotation in &annotations {
+                window.annotate_plots(&annotation.label, annotation.color);
             }
         }
+        performance.add_cost(start.elapsed());
     }
 
-    /// If a result from the [`SearchBar`] is selected, a corresponding
-    /// [`MetricPlot`] will be added to this window.
-    pub fn plot_selected_search_result(
+    fn update_title_summary(
         &mut self,
-        registry: &MetricsRegistry,
-        cached_configs: &CachedPlotConfigs,
-        ui: &mut Ui,
+        snapshot: &FrameMetricsSnapshot,
+        l10n: &DashboardLocalization,
+        refresh: bool,
     ) {
-        let Some(selected) = self.search_bar.draw(registry, ui) else {
+        let Self {
+            title_summary,
+            config,
+            ..
+        } = self;
+        let Some(summary_config) = &config.title_summary else {
             return;
         };
-
-        self.add_plot(
-            registry,
-            cached_configs,
-            selected.key,
-            selected.description.and_then(|d| d.unit),
-        );
+        title_summary.collect(snapshot, summary_config);
+        if refresh {
+            title_summary.refresh(summary_config, l10n);
+        }
     }
 
-    /// Add a new [`MetricPlot`] to this window.
-    pub fn add_plot(
-        &mut self,
-        registry: &MetricsRegistry,
-        cached_configs: &CachedPlotConfigs,
-        key: MetricKey,
-        unit: Option<Unit>,
-    ) {
-        // If we already have this metric, give it a unique name.
-        let n_duplicates = self.plots.iter().filter(|p| p.key() == &key).count();
-
-        let plot_config = cached_configs
-            .get(&key)
-            .cloned()
-            .unwrap_or_else(|| MetricPlotConfig::default_for_kind(key.kind));
-        self.plots.push(MetricPlot::new(
-            registry,
-            key.title(None, n_duplicates),
-            key,
-            unit,
-            plot_config,
-        ));
+    /// The OS window this window is drawn in while it's
+    /// [detached](crate::DetachedDashboard).
+    pub fn os_window(&self) -> Option<Entity> {
+        self.os_window
     }
 
-    /// Draw the plot configuration UI.
-    pub fn configure_ui(&mut self, ui: &mut Ui) {
-        ui.checkbox(&mut self.config.paused, "Pause");
+    #[cfg(feature = "detached_window")]
+    pub(crate) fn set_os_window(&mut self, os_window: Option<Entity>) {
+        self.os_window = os_window;
+    }
 
-        let mut lock_window_size = self.config.global_window_size.is_some();
-        ui.checkbox(&mut lock_window_size, "Link X Axes");
-        if lock_window_size {
-            let window_size = self.config.global_window_size.get_or_insert(500);
-            ui.add(window_size_slider(window_size));
+    /// The title followed by the [`DashboardWindowConfig::title_summary`].
+    pub fn title_with_summary(&self) -> String {
+        let summary = self.title_summary.text();
+        if self.config.title_summary.is_none() || summary.is_empty() {
+            self.title.clone()
         } else {
-            self.config.global_window_size = None;
+            format!("{} — {summary}", self.title)
         }
     }
 
-    /// Draw all [`MetricPlot`]s in this window.
-    pub fn draw_plots(&mut self, cached_configs: &mut CachedPlotConfigs, ui: &mut Ui) {
-        let mut remove_plots = Vec::new();
+    /// The metrics sampled by the widgets in this window, which are captured
+    /// in the [`FrameMetricsSnapshot`].
+    pub fn consumed_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        let title_summary = self
+            .config
+            .title_summary
+            .is_some()
+            .then(|| self.title_summary.keys())
+            .into_iter()
+            .flatten();
+        self.plots
+            .iter()
+            .map(MetricPlot::key)
+            .chain(
+                self.widgets
+                    .iter()
+                    .flat_map(|slot| slot.widget.consumed_metrics()),
+            )
+            .chain(self.search_bar.previewed_metrics())
+            .chain(title_summary)
+    }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for (i, plot) in self.plots.iter_mut().enumerate().rev() {
-                // TODO: avoid string copy here?
-                ui.collapsing(plot.name().to_owned(), |ui| {
-                    if ui.button("Remove").clicked() {
-                        remove_plots.push(i);
-                    }
+    /// Calls [`MetricPlot::update`] and [`DashboardWidget::update`] on all
+    /// widgets in this window that [sample](VirtualTimeAdvanced::samples) in
+    /// this frame.
+    pub fn update_plots(
+        &mut self,
+        snapshot: &FrameMetricsSnapshot,
+        virtual_time: &VirtualTimeAdvanced,
+    ) {
+        self.collect_samples(snapshot, virtual_time);
+        self.refresh_plots();
+    }
+
+    /// Calls [`MetricPlot::collect`] and [`DashboardWidget::update`] on all
+    /// widgets in this window that [sample](VirtualTimeAdvanced::samples) in
+    /// this frame.
+    pub fn collect_samples(
+        &mut self,
+        snapshot: &FrameMetricsSnapshot,
+        virtual_time: &VirtualTimeAdvanced,
+    ) {
+        let window_pause = self.config.pause_with_virtual_time;
+        for plot in &mut self.plots {
+            if virtual_time.samples(plot.pause_with_virtual_time().unwrap_or(window_pause)) {
+                plot.collect(snapshot);
+            }
+        }
+        if !virtual_time.samples(window_pause) {
+            return;
+        }
+        for slot in &mut self.widgets {
+            slot.widget.update(snapshot);
+        }
+    }
+
+    /// Annotate the plots that started or stopped pausing with virtual time
+    /// since they were last checked.
+    fn annotate_virtual_time_changes(&mut self, l10n: &DashboardLocalization) {
+        let window_pause = self.config.pause_with_virtual_time;
+        for plot in &mut self.plots {
+            let pause = plot.pause_with_virtual_time().unwrap_or(window_pause);
+            if plot.track_pause_with_virtual_time(pause) {
+                let text = if pause {
+                    UiText::VirtualTimePaused
+                } else {
+                    UiText::VirtualTimeIgnored
+                };
+                plot.annotate(l10n.get(text), None);
+            }
+        }
+    }
+
+    /// Calls [`MetricPlot::collect_tick`] on all plots in this window,
+    /// unless it's paused.
+    pub fn collect_tick_samples(&mut self, snapshot: &FrameMetricsSnapshot) {
+        if self.config.paused {
+            return;
+        }
+        for plot in &mut self.plots {
+            plot.collect_tick(snapshot);
+        }
+    }
+
+    /// Bevy system that calls [`Self::collect_tick_samples`] on all window
+    /// entities every fixed tick, with a snapshot of the metrics that are
+    /// plotted [per fixed tick](SamplingMode::PerFixedTick).
+    ///
+    /// This runs in the [`SampleMetricsSet`](crate::SampleMetricsSet) of
+    /// [`FixedPostUpdate`].
+    pub fn collect_ticks_on_all_windows(
+        registry: Res<MetricsRegistry>,
+        mut snapshot: Local<FrameMetricsSnapshot>,
+        mut windows: Query<&mut Self>,
+    ) {
+        let keys = windows.iter().flat_map(|window| {
+            window
+                .plots
+                .iter()
+                .filter(|plot| plot.sampling_mode() == SamplingMode::PerFixedTick)
+                .map(MetricPlot::key)
+        });
+        snapshot.capture_values(&registry, keys);
+        for mut window in &mut windows {
+            window.collect_tick_samples(&snapshot);
+        }
+    }
+
+    /// Calls [`MetricPlot::refresh`] on all plots in this window.
+    pub fn refresh_plots(&mut self) {
+        for plot in &mut self.plots {
+            plot.refresh();
+        }
+    }
+
+    /// Like [`Self::refresh_plots`], skipping the work that `degradation`
+    /// skips.
+    fn refresh_plots_degraded(&mut self, degradation: Degradation) {
+        for plot in &mut self.plots {
+            plot.set_downsampling(degradation.downsampling());
+            if degradation.skips_hidden_plots() && !plot.on_screen_since_refresh() {
+                continue;
+            }
+            plot.refresh();
+        }
+    }
+
+    /// Queue all plots to be refreshed by [`Self::catch_up_plots`], those that
+    /// were on screen first.
+    fn start_catch_up(&mut self) {
+        let mut order: Vec<_> = (0..self.plots.len()).collect();
+        order.sort_by_key(|&i| !self.plots[i].on_screen_since_refresh());
+        order.reverse();
+        self.catch_up = order;
+    }
+
+    /// Refresh the next [`CATCH_UP_PLOTS_PER_FRAME`] queued plots.
+    fn catch_up_plots(&mut self, degradation: Degradation) {
+        for _ in 0..CATCH_UP_PLOTS_PER_FRAME {
+            let Some(i) = self.catch_up.pop() else {
+                break;
+            };
+            // Plots may have been removed since the catch-up started.
+            if let Some(plot) = self.plots.get_mut(i) {
+                plot.set_downsampling(degradation.downsampling());
+                plot.refresh();
+            }
+        }
+    }
+
+    /// Calls [`MetricPlot::annotate`] on all plots in this window, and keeps
+    /// the annotation for plots added later.
+    pub fn annotate_plots(&mut self, label: &str, color: Option<Color32>) {
+        for plot in &mut self.plots {
+            plot.annotate(label, color);
+        }
+        if self.annotations.len() == MAX_ANNOTATIONS {
+            self.annotations.pop_front();
+        }
+        self.annotations.push_back(DashboardAnnotation {
+            label: label.to_owned(),
+            color,
+        });
+    }
+
+    /// The latest [`MAX_ANNOTATIONS`] annotations of this window, oldest first.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::DashboardWindow;
+    /// # use metrics::Key;
+    /// let registry = MetricsRegistry::new();
+    /// let mut window = DashboardWindow::new("Window");
+    /// window.annotate_plots("level loaded", None);
+    ///
+    /// // Plots added later are marked too.
+    /// let key = MetricKey::new(Key::from_static_name("enemies"), MetricKind::Gauge);
+    /// window.add_plot(&registry, &Default::default(), &Default::default(), key, None);
+    /// assert_eq!(window.plots()[0].annotation_labels(), [(0, "level loaded".into())]);
+    /// assert_eq!(window.annotations().next().unwrap().label, "level loaded");
+    /// ```
+    pub fn annotations(&self) -> impl Iterator<Item = &DashboardAnnotation> {
+        self.annotations.iter()
+    }
+
+    /// Bevy system that handles [`RequestPlot`] and [`OpenPlots`] events by
+    /// creating new plots in each window.
+    ///
+    /// Events that would add plots the [`DashboardConfig::read_only`] locks
+    /// are ignored.
+    pub fn handle_plot_requests(
+        registry: Res<MetricsRegistry>,
+        dash_config: Res<DashboardConfig>,
+        cached_configs: Res<CachedPlotConfigs>,
+        mut requests: EventReader<RequestPlot>,
+        mut open_plots: EventReader<OpenPlots>,
+        mut windows: Query<&mut Self>,
+    ) {
+        let read_only = &dash_config.read_only;
+        let requests: Vec<_> = requests.read().filter(|_| !read_only.is_all()).collect();
+        let open_plots: Vec<_> = (open_plots.read())
+            .filter(|open| !read_only.locks_group(open.group.as_deref()))
+            .collect();
+        if requests.is_empty() && open_plots.is_empty() {
+            return;
+        }
+        for mut window in &mut windows {
+            for RequestPlot { key, unit, config } in requests.iter().copied().cloned() {
+                if let Some(config) = config {
+                    window.add_plot_with_config(&registry, key, unit, config);
+                } else {
+                    let defaults = &dash_config.plot_defaults;
+                    window.add_plot(&registry, &cached_configs, defaults, key, unit);
+                }
+            }
+            for OpenPlots { metrics, group } in open_plots.iter().copied().cloned() {
+                window.open_plots(&registry, &dash_config.plot_defaults, metrics, group);
+            }
+        }
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// Bevy system that draws all [`DashboardWindow`] entities into the
+    /// [`bevy_egui::EguiContexts`].
+    ///
+    /// Windows are drawn as `egui` windows over the primary window, or
+    /// filling their own OS window while they are
+    /// [detached](crate::DetachedDashboard).
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_all(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        mut config: ResMut<DashboardConfig>,
+        mut cached_configs: ResMut<CachedPlotConfigs>,
+        snapshot: Res<FrameMetricsSnapshot>,
+        localization: Res<DashboardLocalization>,
+        mut ctxts: bevy_egui::EguiContexts,
+        mut windows: Query<(Entity, &mut Self)>,
+        mut performance: ResMut<DashboardPerformance>,
+        focus: Res<DashboardFocus>,
+        #[cfg(feature = "autosave")] autosave: Option<Res<crate::layout::LayoutAutosave>>,
+    ) {
+        let start = Instant::now();
+        #[cfg(feature = "autosave")]
+        let unsaved_layout = autosave.is_some_and(|a| a.has_unsaved_changes());
+        #[cfg(not(feature = "autosave"))]
+        let unsaved_layout = false;
+        let theme = config.theme;
+        let behavior = config.behavior(focus.focused());
+        for (entity, mut window) in &mut windows {
+            if !config.visible || behavior == UnfocusedBehavior::CollectOnly {
+                continue;
+            }
+            let ctxt = match window.os_window {
+                // The context is created the frame after the OS window.
+                Some(os_window) => match ctxts.try_ctx_for_entity_mut(os_window) {
+                    Some(ctxt) => ctxt,
+                    None => continue,
+                },
+                None => ctxts.ctx_mut(),
+            };
+
+            let mut open = true;
+            localization.scope(ctxt, |ctxt| {
+                theme.scope(ctxt, |ctxt| {
+                    let mut frame = egui::Frame::window(&ctxt.style());
+                    frame.fill = frame.fill.gamma_multiply(window.config.opacity);
+                    frame.shadow.color = frame.shadow.color.gamma_multiply(window.config.opacity);
+                    if window.os_window.is_some() {
+                        frame.corner_radius = default();
+                        egui::CentralPanel::default().frame(frame).show(ctxt, |ui| {
+                            egui::ScrollArea::vertical().show(ui, |ui| {
+                                window.draw_contents(
+                                    entity,
+                                    &mut commands,
+                                    &registry,
+                                    &snapshot,
+                                    &mut config,
+                                    &mut cached_configs,
+                                    &performance,
+                                    unsaved_layout,
+                                    ui,
+                                );
+                            });
+                        });
+                        return;
+                    }
+                    // The title can change every frame, so don't derive the ID from it.
+                    let mut egui_window = egui::Window::new(window.title_with_summary())
+                        .id(egui::Id::new(("dashboard-window", entity)))
+                        // Wide toolbars and plots would push the window off screen.
+                        .max_width(ctxt.screen_rect().width())
+                        .frame(frame)
+                        .title_bar(!window.config.compact_chrome);
+                    if !config.read_only.is_all() {
+                        egui_window = egui_window.open(&mut open);
+                    }
+                    egui_window.show(ctxt, |ui| {
+                        window.draw_contents(
+                            entity,
+                            &mut commands,
+                            &registry,
+                            &snapshot,
+                            &mut config,
+                            &mut cached_configs,
+                            &performance,
+                            unsaved_layout,
+                            ui,
+                        );
+                    });
+                })
+            });
+            if !open {
+                commands.entity(entity).despawn();
+            }
+        }
+        performance.add_cost(start.elapsed());
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// The toolbar, settings and widgets of the window.
+    ///
+    /// Below the [`DashboardConfig::compact_width`], the toolbar buttons are
+    /// moved into a menu next to the search bar. While the whole dashboard
+    /// is [read-only](ReadOnly::All), only the pause checkbox of the
+    /// settings is shown, and nothing can be added.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_contents(
+        &mut self,
+        entity: Entity,
+        commands: &mut Commands,
+        registry: &MetricsRegistry,
+        snapshot: &FrameMetricsSnapshot,
+        config: &mut ResMut<DashboardConfig>,
+        cached_configs: &mut CachedPlotConfigs,
+        performance: &DashboardPerformance,
+        unsaved_layout: bool,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        let compact = config.is_compact(ui.available_width());
+        let locked = config.read_only.is_all();
+        ui.horizontal(|ui| {
+            if locked {
+                ui.weak(l10n.get(UiText::ReadOnlyBadge))
+                    .on_hover_text(l10n.get(UiText::ReadOnlyHint));
+            } else {
+                self.plot_selected_search_result(registry, config, cached_configs, ui);
+            }
+            if compact {
+                ui.menu_button(l10n.get(UiText::Tools), |ui| {
+                    self.toolbar_ui(entity, commands, config, unsaved_layout, ui);
+                });
+            } else {
+                self.toolbar_ui(entity, commands, config, unsaved_layout, ui);
+            }
+            performance.badge_ui(config, ui);
+        });
+        if locked {
+            ui.checkbox(&mut self.config.paused, l10n.get(UiText::Pause));
+        } else {
+            self.settings_ui(config, ui);
+        }
+        ui.separator();
+        if !locked {
+            self.quick_add_ui(registry, ui);
+        }
+        self.draw_widgets(registry, snapshot, config, cached_configs, ui);
+        self.draw_plots(registry, snapshot, config, cached_configs, ui);
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// The settings of this window, and those shared by all windows.
+    fn settings_ui(&mut self, config: &mut ResMut<DashboardConfig>, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.collapsing(l10n.get(UiText::GlobalSettings), |ui| {
+            self.configure_ui(ui);
+            ui.collapsing(l10n.get(UiText::PlotStyleAllWindows), |ui| {
+                let style = &mut config.bypass_change_detection().plot_style;
+                if style.configure_ui(ui) {
+                    config.set_changed();
+                }
+            });
+            ui.collapsing(l10n.get(UiText::DisplayNamesAllWindows), |ui| {
+                // Only trigger change detection for edits.
+                let names = &mut config.bypass_change_detection().display_names;
+                if names.configure_ui(ui) {
+                    config.set_changed();
+                }
+            });
+        });
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// The buttons next to the search bar.
+    #[cfg_attr(not(feature = "detached_window"), allow(unused_variables))]
+    fn toolbar_ui(
+        &mut self,
+        entity: Entity,
+        commands: &mut Commands,
+        config: &mut ResMut<DashboardConfig>,
+        unsaved_layout: bool,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        if ui.button(l10n.get(UiText::Browse)).clicked() {
+            commands.spawn(NamespaceTreeWindow::new(
+                l10n.get(UiText::NamespaceViewerTitle),
+            ));
+        }
+        if ui.button(l10n.get(UiText::Audit)).clicked() {
+            commands.spawn(AuditWindow::new(l10n.get(UiText::MetricsAuditTitle)));
+        }
+        if !config.read_only.is_all() {
+            self.edit_toolbar_ui(commands, config, unsaved_layout, ui);
+        }
+        #[cfg(feature = "report")]
+        if ui
+            .button(l10n.get(UiText::ExportReport))
+            .on_hover_text(l10n.get(UiText::ExportReportHint))
+            .clicked()
+        {
+            commands.send_event(crate::report::ExportReport);
+        }
+        #[cfg(feature = "detached_window")]
+        match self.os_window {
+            None => {
+                if ui
+                    .button(l10n.get(UiText::Detach))
+                    .on_hover_text(l10n.get(UiText::DetachHint))
+                    .clicked()
+                {
+                    commands
+                        .entity(entity)
+                        .insert(crate::DetachedDashboard::default());
+                }
+            }
+            Some(_) => {
+                if ui.button(l10n.get(UiText::Attach)).clicked() {
+                    commands.entity(entity).remove::<crate::DetachedDashboard>();
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// The toolbar buttons that change the dashboard, hidden while it's
+    /// [read-only](ReadOnly::All).
+    #[cfg_attr(not(feature = "layout"), allow(unused_variables))]
+    fn edit_toolbar_ui(
+        &mut self,
+        commands: &mut Commands,
+        config: &mut ResMut<DashboardConfig>,
+        unsaved_layout: bool,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        if ui.button(l10n.get(UiText::BudgetBar)).clicked() {
+            self.add_budget_bar(default());
+        }
+        if ui.button(l10n.get(UiText::ScatterPlot)).clicked() {
+            self.add_scatter_plot(default());
+        }
+        if ui.button(l10n.get(UiText::DifferencePlot)).clicked() {
+            self.add_difference_plot(default());
+        }
+        if ui.button(l10n.get(UiText::Leaderboard)).clicked() {
+            self.add_leaderboard(default());
+        }
+        if ui.button(l10n.get(UiText::SnapshotDiff)).clicked() {
+            self.add_snapshot_diff(default());
+        }
+        if config.widget_types.menu_entries().next().is_some() {
+            ui.menu_button(l10n.get(UiText::AddWidget), |ui| {
+                for widget_type in config.widget_types.menu_entries() {
+                    if ui.button(widget_type.label()).clicked() {
+                        self.add_widget(widget_type.create());
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+        self.sort_ui(ui);
+        let overlay = config.plot_style == PlotStyle::overlay();
+        if ui
+            .selectable_label(overlay, l10n.get(UiText::OverlayStyle))
+            .on_hover_text(l10n.get(UiText::OverlayStyleHint))
+            .clicked()
+        {
+            config.plot_style = if overlay {
+                default()
+            } else {
+                PlotStyle::overlay()
+            };
+        }
+        if config.bypass_change_detection().theme.selector_ui(ui) {
+            config.set_changed();
+        }
+        if !self.plots.is_empty() {
+            ui.toggle_value(&mut self.quick_add_open, l10n.get(UiText::QuickAdd));
+        }
+        #[cfg(feature = "layout")]
+        {
+            let mut text = l10n.get(UiText::SaveLayout).to_string();
+            if unsaved_layout {
+                text.push_str(" •");
+            }
+            let mut button = ui.button(text);
+            if unsaved_layout {
+                button = button.on_hover_text(l10n.get(UiText::UnsavedLayoutChanges));
+            }
+            if button.clicked() {
+                commands.send_event(SaveLayout);
+            }
+        }
+    }
+
+    /// If a result from the [`MetricSearchBar`] is selected, a corresponding
+    /// [`MetricPlot`] will be added to this window.
+    ///
+    /// Also handles the [search hotkeys](DashboardConfig::search_hotkeys).
+    /// A search started by typing focuses the search box with the typed text
+    /// as the query, so its results can be browsed with the arrow keys right
+    /// away, like those of a query typed into the box. Escape clears the
+    /// query and gives the keyboard back to the app.
+    pub fn plot_selected_search_result(
+        &mut self,
+        registry: &MetricsRegistry,
+        dash_config: &DashboardConfig,
+        cached_configs: &CachedPlotConfigs,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        let mut shortcut = false;
+        if dash_config.search_hotkeys && ui.ctx().top_layer_id() == Some(ui.layer_id()) {
+            shortcut = ui.input_mut(|i| i.consume_shortcut(&SEARCH_SHORTCUT));
+            if let Some(typed) = take_typed_text(ui) {
+                shortcut = true;
+                if typed != "/" {
+                    self.search_bar.set_query(typed);
+                }
+            }
+        }
+        if self.config.search_collapsed {
+            let expand = ui
+                .button(l10n.get(UiText::SearchButton))
+                .on_hover_text(l10n.format(
+                    UiText::ExpandSearchHint,
+                    &[("shortcut", &ui.ctx().format_shortcut(&SEARCH_SHORTCUT))],
+                ))
+                .clicked();
+            if expand || shortcut {
+                self.config.search_collapsed = false;
+                self.search_bar.request_focus();
+            }
+            return;
+        }
+        if shortcut {
+            self.search_bar.request_focus();
+        }
+        if ui
+            .small_button(l10n.get(UiText::CollapseSearch))
+            .on_hover_text(l10n.get(UiText::CollapseSearchHint))
+            .clicked()
+        {
+            self.config.search_collapsed = true;
+            return;
+        }
+        self.search_bar.set_debounce(dash_config.search_debounce);
+        self.search_bar
+            .set_value_refresh(dash_config.ui_refresh_period());
+        let selected = self.search_bar.show(ui, registry);
+        self.open_all_ui(registry, dash_config, ui);
+        let Some(selected) = selected.map(SearchSelection::into_result) else {
+            return;
+        };
+
+        self.add_plot(
+            registry,
+            cached_configs,
+            &dash_config.plot_defaults,
+            selected.key,
+            selected.description.and_then(|d| d.unit),
+        );
+    }
+
+    /// Draw the "Open All" button for the current search results, which
+    /// asks for confirmation above the [`DashboardConfig::open_all_limit`].
+    fn open_all_ui(
+        &mut self,
+        registry: &MetricsRegistry,
+        dash_config: &DashboardConfig,
+        ui: &mut Ui,
+    ) {
+        let query = self.search_bar.query();
+        if query.is_empty() || !self.search_bar.results_are_current() {
+            self.confirm_open_all = false;
+            return;
+        }
+        let metrics: Vec<_> = self
+            .search_bar
+            .filtered_results(registry)
+            .map(|s| {
+                (
+                    s.key.clone(),
+                    s.description.as_ref().and_then(|d| d.unit.clone()),
+                )
+            })
+            .collect();
+        if metrics.is_empty() {
+            self.confirm_open_all = false;
+            return;
+        }
+
+        let l10n = DashboardLocalization::of(ui);
+        let count = metrics.len();
+        let mut open = false;
+        ui.horizontal(|ui| {
+            if self.confirm_open_all {
+                ui.label(l10n.format(UiText::OpenAllConfirm, &[("count", &count)]));
+                open = ui.button(l10n.get(UiText::Open)).clicked();
+                if open || ui.button(l10n.get(UiText::Cancel)).clicked() {
+                    self.confirm_open_all = false;
+                }
+            } else if ui
+                .button(l10n.format(UiText::OpenAll, &[("count", &count)]))
+                .on_hover_text(l10n.get(UiText::OpenAllHint))
+                .clicked()
+            {
+                open = count <= dash_config.open_all_limit;
+                self.confirm_open_all = !open;
+            }
+        });
+        if open {
+            let group = query.to_owned();
+            self.open_plots(registry, &dash_config.plot_defaults, metrics, Some(group));
+        }
+    }
+
+    /// Add a [`MetricPlot`] with its config from `defaults` for each of
+    /// `metrics`, in `group` if it's `Some`.
+    pub fn open_plots(
+        &mut self,
+        registry: &MetricsRegistry,
+        defaults: &PlotDefaults,
+        metrics: impl IntoIterator<Item = (MetricKey, Option<MetricUnit>)>,
+        group: Option<String>,
+    ) {
+        for (key, unit) in metrics {
+            let config = defaults.config_for(&key);
+            self.add_plot_with_config(registry, key, unit, config);
+            if let Some(plot) = self.plots.last_mut() {
+                plot.set_group(group.clone());
+            }
+        }
+    }
+
+    /// Add a new [`MetricPlot`] to this window, with its config from
+    /// `cached_configs` if it was opened before, or else from `defaults`.
+    pub fn add_plot(
+        &mut self,
+        registry: &MetricsRegistry,
+        cached_configs: &CachedPlotConfigs,
+        defaults: &PlotDefaults,
+        key: MetricKey,
+        unit: Option<MetricUnit>,
+    ) {
+        let plot_config = cached_configs
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| defaults.config_for(&key));
+        self.add_plot_with_config(registry, key, unit, plot_config);
+    }
+
+    /// Add a new [`MetricPlot`] to this window with a specific config.
+    ///
+    /// `plot_config` must match the kind of metric identified by `key`.
+    pub fn add_plot_with_config(
+        &mut self,
+        registry: &MetricsRegistry,
+        key: MetricKey,
+        unit: Option<MetricUnit>,
+        plot_config: MetricPlotConfig,
+    ) {
+        // If we already have this metric, give it a unique name.
+        let n_duplicates = self.plots.iter().filter(|p| p.key() == &key).count();
+
+        let mut plot = MetricPlot::new(
+            registry,
+            registry.title(&key, n_duplicates),
+            key,
+            unit,
+            plot_config,
+        );
+        for annotation in &self.annotations {
+            plot.annotate(&annotation.label, annotation.color);
+        }
+        self.plots.push(plot);
+    }
+
+    /// Draw buttons that plot common core metrics, if they are registered.
+    ///
+    /// Only shown while there are no plots, or after the "Quick Add" toolbar
+    /// button was toggled.
+    pub fn quick_add_ui(&mut self, registry: &MetricsRegistry, ui: &mut Ui) {
+        if !self.plots.is_empty() && !self.quick_add_open {
+            return;
+        }
+        let available: Vec<_> = QUICK_ADDS
+            .iter()
+            .filter(|q| {
+                registry
+                    .get_gauge(&metrics::Key::from_static_name(q.name))
+                    .is_some()
+            })
+            .collect();
+        if available.is_empty() {
+            return;
+        }
+
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            ui.label(l10n.get(UiText::QuickAddPrefix));
+            for quick_add in available {
+                if ui.button(l10n.get(quick_add.label)).clicked() {
+                    self.add_plot_with_config(
+                        registry,
+                        MetricKey::new(
+                            metrics::Key::from_static_name(quick_add.name),
+                            MetricKind::Gauge,
+                        ),
+                        Some(quick_add.unit.into()),
+                        MetricPlotConfig::Gauge(GaugePlotConfig {
+                            smoothing_weight: quick_add.smoothing_weight,
+                            window_size: Some(QUICK_ADD_WINDOW_SIZE),
+                            ..default()
+                        }),
+                    );
+                    self.quick_add_open = false;
+                }
+            }
+        });
+        ui.separator();
+    }
+
+    /// Add a new [`BudgetBar`] to this window, as one of its
+    /// [widgets](Self::widgets).
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::DashboardWindow;
+    /// let mut window = DashboardWindow::new("Metrics");
+    /// window.add_budget_bar(Default::default());
+    /// window.add_scatter_plot(Default::default());
+    /// let ids: Vec<_> = window.widgets().map(|widget| widget.id()).collect();
+    /// assert_eq!(ids, ["budget_bar", "scatter_plot"]);
+    /// ```
+    pub fn add_budget_bar(&mut self, config: BudgetBarConfig) {
+        let name = format!("Budget {}", self.widgets.len());
+        self.add_widget(Box::new(BudgetBar::new(name, config)));
+    }
+
+    /// Add a new [`ScatterPlot`] to this window.
+    ///
+    /// The axes can also be chosen later in the plot's settings.
+    pub fn add_scatter_plot(&mut self, config: ScatterPlotConfig) {
+        let name = format!("Scatter {}", self.widgets.len());
+        self.add_widget(Box::new(ScatterPlot::new(name, config)));
+    }
+
+    /// Add a new [`DifferencePlot`] to this window.
+    ///
+    /// The operands can also be chosen later in the plot's settings.
+    pub fn add_difference_plot(&mut self, config: DifferencePlotConfig) {
+        let name = format!("Difference {}", self.widgets.len());
+        self.add_widget(Box::new(DifferencePlot::new(name, config)));
+    }
+
+    /// Add a new [`Leaderboard`] to this window.
+    ///
+    /// The metrics it ranks can also be chosen later in its settings.
+    pub fn add_leaderboard(&mut self, config: LeaderboardConfig) {
+        let name = format!("Leaderboard {}", self.widgets.len());
+        self.add_widget(Box::new(Leaderboard::new(name, config)));
+    }
+
+    /// Add a new [`SnapshotDiff`] to this window.
+    ///
+    /// Snapshots are captured with the buttons of the widget.
+    pub fn add_snapshot_diff(&mut self, config: SnapshotDiffConfig) {
+        let name = format!("Snapshot Diff {}", self.widgets.len());
+        self.add_widget(Box::new(SnapshotDiff::new(name, config)));
+    }
+
+    /// Add a [`DashboardWidget`] below the other widgets of this window.
+    pub fn add_widget(&mut self, widget: Box<dyn DashboardWidget>) {
+        self.widgets.push(WidgetSlot {
+            id: self.next_widget_id,
+            widget,
+        });
+        self.next_widget_id += 1;
+    }
+
+    /// The budget bars, scatter plots, difference plots and custom widgets in
+    /// this window, in the order they're drawn.
+    pub fn widgets(&self) -> impl Iterator<Item = &dyn DashboardWidget> {
+        self.widgets.iter().map(|slot| &*slot.widget)
+    }
+
+    #[cfg(feature = "layout")]
+    /// Capture the state of this window for saving.
+    pub fn layout(&self) -> WindowLayout {
+        WindowLayout {
+            title: self.title.clone(),
+            config: self.config.clone(),
+            plots: self.plots.iter().map(PlotLayout::from).collect(),
+            widgets: self
+                .widgets
+                .iter()
+                .filter_map(|slot| {
+                    let id = slot.widget.id().to_owned();
+                    match slot.widget.save_config() {
+                        Ok(config) => Some(WidgetLayout { id, config }),
+                        Err(e) => {
+                            error!("Failed to save the config of widget {id:?}: {e}");
+                            None
+                        }
+                    }
+                })
+                .collect(),
+            annotations: self
+                .annotations
+                .iter()
+                .map(AnnotationLayout::from)
+                .collect(),
+            unknown_fields: default(),
+        }
+    }
+
+    #[cfg(feature = "layout")]
+    /// Replace this window's plots and config with those in `layout`.
+    ///
+    /// Plots whose key, unit, and config are unchanged keep their history.
+    /// Other plots are recreated empty. Display names, styles, notes and time
+    /// sources are always replaced. Widgets are matched the same way, and created from
+    /// `widget_types`.
+    pub fn apply_layout(
+        &mut self,
+        registry: &MetricsRegistry,
+        widget_types: &DashboardWidgetTypes,
+        layout: &WindowLayout,
+    ) {
+        self.config = layout.config.clone();
+        self.annotations = layout.annotations.iter().map(Into::into).collect();
+        self.apply_widget_layouts(widget_types, &layout.widgets);
+
+        let mut old_plots: Vec<_> = std::mem::take(&mut self.plots)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for plot_layout in &layout.plots {
+            let reused = old_plots
+                .iter_mut()
+                .find(|p| p.as_ref().is_some_and(|p| plot_layout.matches(p)))
+                .and_then(Option::take);
+            if let Some(plot) = reused {
+                self.plots.push(plot);
+            } else {
+                self.add_plot_with_config(
+                    registry,
+                    plot_layout.key.clone(),
+                    plot_layout.unit.clone(),
+                    plot_layout.config.clone(),
+                );
+            }
+            if let Some(plot) = self.plots.last_mut() {
+                plot_layout.apply_settings(plot);
+            }
+        }
+    }
+
+    #[cfg(feature = "layout")]
+    fn apply_widget_layouts(
+        &mut self,
+        widget_types: &DashboardWidgetTypes,
+        layouts: &[WidgetLayout],
+    ) {
+        let mut old_widgets: Vec<_> = std::mem::take(&mut self.widgets)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for widget_layout in layouts {
+            let Some(widget_type) = widget_types.get(&widget_layout.id) else {
+                warn!("Unknown widget type {:?} in layout", widget_layout.id);
+                continue;
+            };
+            let mut widget = widget_type.create();
+            if let Some(config) = &widget_layout.config {
+                if let Err(e) = widget.load_config(config) {
+                    error!(
+                        "Failed to load the config of widget {:?}: {e}",
+                        widget_layout.id
+                    );
+                }
+            }
+            // Compare saved configs, which don't depend on the formatting of
+            // the file.
+            let config = widget.save_config().ok();
+            let reused = old_widgets
+                .iter_mut()
+                .find(|slot| {
+                    slot.as_ref().is_some_and(|slot| {
+                        slot.widget.id() == widget.id() && slot.widget.save_config().ok() == config
+                    })
+                })
+                .and_then(Option::take);
+            match reused {
+                Some(slot) => self.widgets.push(slot),
+                None => self.add_widget(widget),
+            }
+        }
+    }
+
+    /// Draw the plot configuration UI.
+    pub fn configure_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.checkbox(&mut self.config.paused, l10n.get(UiText::Pause));
+        ui.add(
+            egui::Slider::new(&mut self.config.opacity, 0.1..=1.0).text(l10n.get(UiText::Opacity)),
+        );
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut self.config.compact_chrome,
+                l10n.get(UiText::CompactChrome),
+            )
+            .on_hover_text(l10n.get(UiText::CompactChromeHint));
+            #[cfg(feature = "detached_window")]
+            ui.checkbox(
+                &mut self.config.always_on_top,
+                l10n.get(UiText::AlwaysOnTop),
+            )
+            .on_hover_text(l10n.get(UiText::AlwaysOnTopHint));
+        });
+        ui.checkbox(
+            &mut self.config.pause_with_virtual_time,
+            l10n.get(UiText::PauseWithVirtualTime),
+        )
+        .on_hover_text(l10n.get(UiText::PauseWithVirtualTimeHint));
+
+        let mut lock_window_size = self.config.global_window_size.is_some();
+        ui.checkbox(&mut lock_window_size, l10n.get(UiText::LinkXAxes));
+        if lock_window_size {
+            let window_size = self
+                .config
+                .global_window_size
+                .get_or_insert(DEFAULT_WINDOW_SIZE);
+            ui.add(window_size_slider(&l10n, window_size));
+        } else {
+            self.config.global_window_size = None;
+        }
+
+        let mut compare = self.config.comparison_label.is_some();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut compare, l10n.get(UiText::CompareByLabel));
+            if compare {
+                let label = self.config.comparison_label.get_or_insert_with(default);
+                ui.text_edit_singleline(label);
+            } else {
+                self.config.comparison_label = None;
+            }
+        });
+
+        let mut show_summary = self.config.title_summary.is_some();
+        ui.checkbox(&mut show_summary, l10n.get(UiText::TitleSummary));
+        if !show_summary {
+            self.config.title_summary = None;
+            return;
+        }
+        let summary = self.config.title_summary.get_or_insert_with(default);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut summary.fps, l10n.get(UiText::Fps));
+            ui.checkbox(&mut summary.frame_time, l10n.get(UiText::FrameTime));
+            ui.checkbox(&mut summary.entities, l10n.get(UiText::Entities));
+        });
+        if summary.frame_time {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut summary.percentile)
+                        .prefix(l10n.get(UiText::PercentilePrefix))
+                        .range(0.0..=100.0),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut summary.percentile_window)
+                        .prefix(l10n.get(UiText::WindowPrefix))
+                        .suffix(" s")
+                        .speed(0.1)
+                        .range(0.1..=300.0),
+                );
+            });
+        }
+    }
+
+    /// Draw a selector for [`DashboardWindowConfig::sort`].
+    pub fn sort_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        let sort = l10n.get(self.config.sort.label());
+        egui::ComboBox::from_id_salt("plot-sort")
+            .selected_text(l10n.format(UiText::Sort, &[("sort", &sort)]))
+            .show_ui(ui, |ui| {
+                for sort in PlotSort::ALL {
+                    ui.selectable_value(&mut self.config.sort, sort, l10n.get(sort.label()));
+                }
+            });
+    }
+
+    /// Indices of [`Self::plots`] in the order they are drawn.
+    fn draw_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.plots.len()).rev().collect();
+        let plots = &self.plots;
+        match self.config.sort {
+            PlotSort::Manual => {}
+            PlotSort::Alphabetical => order.sort_by(|&a, &b| plots[a].name().cmp(plots[b].name())),
+            PlotSort::LatestValue => order.sort_by(|&a, &b| {
+                let value = |i: usize| plots[i].latest_value().map(FloatOrd);
+                value(b).cmp(&value(a))
+            }),
+            PlotSort::RecentlyAdded => {
+                order.sort_by_key(|&i| std::cmp::Reverse(plots[i].created()));
+            }
+            PlotSort::AlertsFirst => order.sort_by_key(|&i| !plots[i].is_alerting()),
+        }
+        order
+    }
+
+    /// `order` with the plots of each [group](MetricPlot::group) moved
+    /// together, to where its first plot is.
+    fn group_plots(&self, order: Vec<usize>) -> Vec<usize> {
+        let group = |i: usize| self.plots[i].group();
+        let mut grouped = Vec::with_capacity(order.len());
+        for (n, &i) in order.iter().enumerate() {
+            match group(i) {
+                None => grouped.push(i),
+                Some(name) if !order[..n].iter().any(|&j| group(j) == Some(name)) => {
+                    grouped.extend(order[n..].iter().filter(|&&j| group(j) == Some(name)));
+                }
+                Some(_) => {}
+            }
+        }
+        grouped
+    }
 
-                    plot.draw(&self.config, ui);
+    /// Groups of counter and gauge plots that differ only by the
+    /// [`DashboardWindowConfig::comparison_label`].
+    pub fn comparison_groups(&self) -> Vec<ComparisonGroup> {
+        let Some(label) = self.config.comparison_label.as_deref() else {
+            return Vec::new();
+        };
+        let mut groups = pair_by_label(self.plots.iter().map(MetricPlot::key), label);
+        groups.retain(|group| group.key.kind != MetricKind::Histogram);
+        groups
+    }
+
+    /// Draw all [`MetricPlot`]s in this window.
+    ///
+    /// With [`PlotSort::Manual`], plots can be reordered by dragging the
+    /// handle in their header. Plots in [comparison
+    /// groups](Self::comparison_groups) are drawn together at the top.
+    ///
+    /// Below the [`DashboardConfig::compact_width`], plot titles are
+    /// truncated to fit the header. Plots [locked](DashboardConfig::read_only)
+    /// can't be removed, reordered, regrouped or configured. Plots are drawn
+    /// and closed through their [`DashboardWidget`] implementation, like the
+    /// [other widgets](Self::draw_widgets), and the window adds the headers,
+    /// sorting, grouping and comparisons around them.
+    pub fn draw_plots(
+        &mut self,
+        registry: &MetricsRegistry,
+        snapshot: &FrameMetricsSnapshot,
+        dash_config: &DashboardConfig,
+        cached_configs: &mut CachedPlotConfigs,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        let mut remove_plots = Vec::new();
+        let mut ungroup_plots: Vec<usize> = Vec::new();
+        let mut moved_plot = None;
+        let groups = self.comparison_groups();
+        let mut order = self.draw_order();
+        order.retain(|i| !groups.iter().any(|g| g.members.iter().any(|(_, m)| m == i)));
+        let order = self.group_plots(order);
+        let manual = self.config.sort == PlotSort::Manual;
+        let compact = dash_config.is_compact(ui.available_width());
+        let read_only = &dash_config.read_only;
+        let ctx = WidgetContext {
+            registry,
+            snapshot,
+            style: &dash_config.plot_style,
+            dashboard: dash_config,
+            window: &self.config,
+            cached_configs,
+            requested_plots: default(),
+        };
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for group in &groups {
+                let label = self.config.comparison_label.as_deref().unwrap_or_default();
+                let name = registry.title(&group.key, 0);
+                let title = l10n.format(
+                    UiText::ComparisonTitle,
+                    &[("name", &name), ("label", &label)],
+                );
+                ui.push_id(("comparison", &group.key), |ui| {
+                    ui.collapsing(title, |ui| {
+                        let locked = (group.members.iter())
+                            .any(|&(_, i)| read_only.locks_group(self.plots[i].group()));
+                        if !locked && ui.button(l10n.get(UiText::Remove)).clicked() {
+                            remove_plots.extend(group.members.iter().map(|&(_, i)| i));
+                        }
+
+                        draw_comparison(group, &mut self.plots, dash_config, ui);
+                    });
                 });
             }
+
+            // The plot group being drawn, and whether it's open.
+            let mut current_group: Option<(String, bool)> = None;
+            for &i in &order {
+                let group = self.plots[i].group();
+                if group != current_group.as_ref().map(|(g, _)| g.as_str()) {
+                    current_group = group.map(|group| {
+                        let members: Vec<_> = (order.iter().copied())
+                            .filter(|&j| self.plots[j].group() == Some(group))
+                            .collect();
+                        let locked = read_only.locks_group(Some(group));
+                        let (open, action) = plot_group_header(group, members.len(), locked, ui);
+                        match action {
+                            Some(PlotGroupAction::Ungroup) => ungroup_plots.extend(&members),
+                            Some(PlotGroupAction::Remove) => remove_plots.extend(&members),
+                            None => {}
+                        }
+                        (group.to_owned(), open)
+                    });
+                }
+                if current_group.as_ref().is_some_and(|(_, open)| !open) {
+                    continue;
+                }
+
+                let plot = &mut self.plots[i];
+                let locked = read_only.locks_group(plot.group());
+                let id = ui.make_persistent_id(("metric-plot", plot.key()));
+                let (_, header, _) = CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header(ui, |ui| {
+                        if manual && !locked {
+                            ui.dnd_drag_source(id.with("drag"), DraggedPlot(i), |ui| {
+                                ui.label("☰");
+                            })
+                            .response
+                            .on_hover_text(l10n.get(UiText::DragToReorder));
+                        }
+                        let mut label = Label::new(plot.title(&ctx)).sense(Sense::click());
+                        if compact {
+                            // Truncated labels show the full text on hover.
+                            label = label.truncate();
+                        }
+                        let mut response = ui.add(label);
+                        if plot.key().key.labels().next().is_some() {
+                            response = response.on_hover_text(plot.series_name(dash_config));
+                        }
+                        if plot.key().kind == MetricKind::Histogram {
+                            if let Some(samples) = registry.hot_histogram_samples(&plot.key().key) {
+                                let palette = ThemePalette::of(ui);
+                                ui.colored_label(palette.error, l10n.get(UiText::HotBadge))
+                                    .on_hover_text(
+                                        l10n.format(UiText::HotHistogram, &[("samples", &samples)]),
+                                    );
+                            }
+                        }
+                        if plot.sampling_mode() == SamplingMode::PerFixedTick {
+                            ui.weak(l10n.get(UiText::FixedTickBadge))
+                                .on_hover_text(l10n.get(UiText::FixedTickBadgeHint));
+                        }
+                        let dropped = plot.late_samples_dropped();
+                        if dropped > 0 {
+                            ui.weak(
+                                l10n.format(UiText::LateSamplesDropped, &[("count", &dropped)]),
+                            )
+                            .on_hover_text(l10n.get(UiText::LateSamplesHint));
+                        }
+                        if response.clicked() {
+                            let mut state =
+                                CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                            state.toggle(ui);
+                            state.store(ui.ctx());
+                        }
+                        if response.secondary_clicked() || response.context_menu_opened() {
+                            let result = registry.get_search_result(plot.key().clone());
+                            metric_context_menu_with(&response, &result, |ui| {
+                                copy_markdown_buttons(plot, dash_config, ui);
+                                if !locked {
+                                    ui.separator();
+                                    overlay_menu(plot, ui);
+                                }
+                            });
+                        }
+                    })
+                    .body(|ui| {
+                        if !locked && ui.button(l10n.get(UiText::Remove)).clicked() {
+                            remove_plots.push(i);
+                        }
+
+                        plot.ui(ui, &ctx);
+                    });
+
+                // Locked plots neither move nor take the place of others.
+                if !manual || locked {
+                    continue;
+                }
+                let rect = header.response.rect;
+                if header.response.dnd_hover_payload::<DraggedPlot>().is_some() {
+                    let stroke = ui.visuals().selection.stroke;
+                    ui.painter().hline(rect.x_range(), rect.top(), stroke);
+                }
+                if let Some(dragged) = header.response.dnd_release_payload::<DraggedPlot>() {
+                    moved_plot = Some((dragged.0, i));
+                }
+            }
         });
 
+        for i in ungroup_plots {
+            self.plots[i].set_group(None);
+        }
+        if let Some((from, to)) = moved_plot {
+            let plot = self.plots.remove(from);
+            self.plots.insert(to, plot);
+        }
+        remove_plots.sort_unstable_by(|a, b| b.cmp(a));
         for i in remove_plots {
-            let plot = self.plots.remove(i);
-            cached_configs.insert(plot.key().clone(), plot.clone_config());
+            let mut plot = self.plots.remove(i);
+            plot.on_close(cached_configs);
+        }
+    }
+
+    /// Whether any plot in this window is drawn in `corner` of the overlay.
+    pub fn has_overlay_plots(&self, corner: OverlayCorner) -> bool {
+        self.plots.iter().any(|p| p.overlay() == Some(corner))
+    }
+
+    /// Draw the plots that are promoted to `corner` of the overlay in
+    /// `style`, with their names.
+    pub fn draw_overlay_plots(
+        &mut self,
+        corner: OverlayCorner,
+        dash_config: &DashboardConfig,
+        style: &PlotStyle,
+        ui: &mut Ui,
+    ) {
+        for plot in &mut self.plots {
+            if plot.overlay() != Some(corner) {
+                continue;
+            }
+            ui.push_id(("overlay-plot", plot.key().clone()), |ui| {
+                ui.small(plot.name());
+                plot.draw_overlay(dash_config, &self.config, style, ui);
+            });
+        }
+    }
+
+    /// Draw all [`DashboardWidget`]s in this window, in order. Widgets are
+    /// reordered by dragging their headers, unless the whole dashboard is
+    /// [read-only](ReadOnly::All).
+    ///
+    /// Closed widgets can save their settings to `cached_configs` in
+    /// [`DashboardWidget::on_close`].
+    pub fn draw_widgets(
+        &mut self,
+        registry: &MetricsRegistry,
+        snapshot: &FrameMetricsSnapshot,
+        dash_config: &DashboardConfig,
+        cached_configs: &mut CachedPlotConfigs,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        let ctx = WidgetContext {
+            registry,
+            snapshot,
+            style: &dash_config.plot_style,
+            dashboard: dash_config,
+            window: &self.config,
+            cached_configs,
+            requested_plots: default(),
+        };
+        let mut closed = None;
+        let mut moved = None;
+        let locked = dash_config.read_only.is_all();
+
+        for (i, slot) in self.widgets.iter_mut().enumerate() {
+            let id = ui.make_persistent_id(("dashboard-widget", slot.id));
+            let (_, header, _) = CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| {
+                    if !locked {
+                        ui.dnd_drag_source(id.with("drag"), DraggedWidget(i), |ui| {
+                            ui.label("☰");
+                        })
+                        .response
+                        .on_hover_text(l10n.get(UiText::DragToReorder));
+                    }
+                    let title = Label::new(slot.widget.title(&ctx)).sense(Sense::click());
+                    if ui.add(title).clicked() {
+                        let mut state =
+                            CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                        state.toggle(ui);
+                        state.store(ui.ctx());
+                    }
+                })
+                .body(|ui| {
+                    if !locked && ui.button(l10n.get(UiText::Remove)).clicked() {
+                        closed = Some(i);
+                    }
+
+                    slot.widget.ui(ui, &ctx);
+                });
+
+            if locked {
+                continue;
+            }
+            let rect = header.response.rect;
+            if header
+                .response
+                .dnd_hover_payload::<DraggedWidget>()
+                .is_some()
+            {
+                let stroke = ui.visuals().selection.stroke;
+                ui.painter().hline(rect.x_range(), rect.top(), stroke);
+            }
+            if let Some(dragged) = header.response.dnd_release_payload::<DraggedWidget>() {
+                moved = Some((dragged.0, i));
+            }
+        }
+
+        let requested_plots = ctx.requested_plots.take();
+        if !locked {
+            for (key, unit) in requested_plots {
+                let defaults = &dash_config.plot_defaults;
+                self.add_plot(registry, cached_configs, defaults, key, unit);
+            }
+        }
+
+        if let Some(i) = closed {
+            let mut slot = self.widgets.remove(i);
+            slot.widget.on_close(cached_configs);
+        } else if let Some((from, to)) = moved {
+            let slot = self.widgets.remove(from);
+            self.widgets.insert(to, slot);
         }
     }
 }
+
+/// What to do with the plots of a group, chosen in its header.
+enum PlotGroupAction {
+    Ungroup,
+    Remove,
+}
+
+/// Draw the collapsible header of the plot group named `group`, with
+/// `count` plots. Returns whether the group is open.
+///
+/// A `locked` group has no actions.
+fn plot_group_header(
+    group: &str,
+    count: usize,
+    locked: bool,
+    ui: &mut Ui,
+) -> (bool, Option<PlotGroupAction>) {
+    let l10n = DashboardLocalization::of(ui);
+    let id = ui.make_persistent_id(("plot-group", group));
+    let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, true);
+    let mut action = None;
+    ui.horizontal(|ui| {
+        state.show_toggle_button(ui, egui::collapsing_header::paint_default_icon);
+        let title = l10n.format(
+            UiText::PlotGroupTitle,
+            &[("group", &group), ("count", &count)],
+        );
+        let title = Label::new(egui::RichText::new(title).strong()).sense(Sense::click());
+        if ui.add(title).clicked() {
+            state.toggle(ui);
+        }
+        if locked {
+            ui.weak("🔒")
+                .on_hover_text(l10n.get(UiText::LockedGroupHint));
+            return;
+        }
+        if ui.small_button(l10n.get(UiText::Ungroup)).clicked() {
+            action = Some(PlotGroupAction::Ungroup);
+        }
+        if ui.small_button(l10n.get(UiText::Remove)).clicked() {
+            action = Some(PlotGroupAction::Remove);
+        }
+    });
+    let open = state.is_open();
+    state.store(ui.ctx());
+    (open, action)
+}
+
+/// Context menu entries for promoting `plot` to the overlay.
+/// How many of the latest samples "Copy as Markdown with Samples" includes.
+const MARKDOWN_SAMPLES: usize = 20;
+
+fn copy_markdown_buttons(plot: &MetricPlot, dash_config: &DashboardConfig, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    for (text, samples) in [
+        (UiText::CopyAsMarkdown, 0),
+        (UiText::CopyAsMarkdownWithSamples, MARKDOWN_SAMPLES),
+    ] {
+        if ui.button(l10n.get(text)).clicked() {
+            let markdown = plot.to_markdown(dash_config, samples, l10n.decimal_separator);
+            ui.ctx().copy_text(markdown);
+            ui.close_menu();
+        }
+    }
+}
+
+fn overlay_menu(plot: &mut MetricPlot, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    ui.menu_button(l10n.get(UiText::ShowInOverlay), |ui| {
+        for corner in OverlayCorner::ALL {
+            if ui
+                .radio(plot.overlay() == Some(corner), l10n.get(corner.label()))
+                .clicked()
+            {
+                plot.set_overlay(Some(corner));
+                ui.close_menu();
+            }
+        }
+        if plot.overlay().is_some() && ui.button(l10n.get(UiText::RemoveFromOverlay)).clicked() {
+            plot.set_overlay(None);
+            ui.close_menu();
+        }
+    });
+}
+
+/// Take the text typed this frame, if the pointer is over the window of `ui`
+/// and no widget has keyboard focus, so it's not taken from text fields.
+fn take_typed_text(ui: &mut Ui) -> Option<String> {
+    let hovered = ui
+        .ctx()
+        .pointer_hover_pos()
+        .and_then(|pos| ui.ctx().layer_id_at(pos))
+        == Some(ui.layer_id());
+    if !hovered || ui.memory(|m| m.focused().is_some()) {
+        return None;
+    }
+    ui.input_mut(|i| {
+        let typed: String = i
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if typed.trim().is_empty() {
+            return None;
+        }
+        i.events
+            .retain(|event| !matches!(event, egui::Event::Text(_)));
+        Some(typed)
+    })
+}
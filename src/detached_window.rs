@@ -0,0 +1,149 @@
+use crate::DashboardWindow;
+use bevy::{
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{WindowLevel, WindowRef, WindowResolution},
+};
+
+/// Draws the [`DashboardWindow`] on the same entity in its own OS window,
+/// instead of over the primary window.
+///
+/// Insert this component to detach a dashboard window, e.g. with the
+/// "Detach" button of its toolbar. The OS window and a camera rendering to
+/// it are spawned on the next frame, and the plots move along with all
+/// their state. Removing the component, or closing the OS window, attaches
+/// the dashboard window again.
+///
+/// While detached, the window's
+/// [`always_on_top`](crate::DashboardWindowConfig::always_on_top) option
+/// sets the level of the OS window, where the platform supports it.
+#[derive(Component, Default)]
+pub struct DetachedDashboard {
+    /// The initial size of the OS window, in logical pixels.
+    pub size: Option<Vec2>,
+    spawned: Option<Spawned>,
+}
+
+impl DetachedDashboard {
+    /// The OS window, once it's spawned.
+    pub fn os_window(&self) -> Option<Entity> {
+        self.spawned.as_ref().map(|spawned| spawned.window)
+    }
+}
+
+struct Spawned {
+    window: Entity,
+    camera: Entity,
+}
+
+/// Marks the OS window and camera spawned for the dashboard window on this
+/// entity.
+#[derive(Component)]
+pub(crate) struct OsWindowOf(Entity);
+
+/// The default [`DetachedDashboard::size`].
+const DEFAULT_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+
+impl DetachedDashboard {
+    /// Bevy system that spawns the OS windows of newly detached dashboard
+    /// windows.
+    pub(crate) fn open_os_windows(
+        mut commands: Commands,
+        mut dashboards: Query<(Entity, &mut DashboardWindow, &mut Self)>,
+    ) {
+        for (entity, mut dashboard, mut detached) in &mut dashboards {
+            if detached.spawned.is_some() {
+                continue;
+            }
+            let size = detached.size.unwrap_or(DEFAULT_SIZE);
+            let window = commands
+                .spawn((
+                    Window {
+                        title: dashboard.title_with_summary(),
+                        resolution: WindowResolution::new(size.x, size.y),
+                        window_level: window_level(&dashboard),
+                        // Lets the window opacity show the desktop, where
+                        // supported.
+                        transparent: true,
+                        ..default()
+                    },
+                    OsWindowOf(entity),
+                ))
+                .id();
+            let camera = commands
+                .spawn((
+                    Camera2d,
+                    Camera {
+                        target: RenderTarget::Window(WindowRef::Entity(window)),
+                        clear_color: ClearColorConfig::Custom(Color::NONE),
+                        ..default()
+                    },
+                    OsWindowOf(entity),
+                ))
+                .id();
+            detached.spawned = Some(Spawned { window, camera });
+            dashboard.set_os_window(Some(window));
+        }
+    }
+
+    /// Bevy system that attaches dashboard windows whose OS window was
+    /// closed, and despawns the OS windows of attached or despawned
+    /// dashboard windows.
+    pub(crate) fn close_os_windows(
+        mut commands: Commands,
+        detached: Query<(Entity, &Self)>,
+        mut attached: Query<&mut DashboardWindow, Without<Self>>,
+        os_windows: Query<(), With<Window>>,
+        spawned: Query<(Entity, &OsWindowOf)>,
+    ) {
+        for (entity, detached) in &detached {
+            let Some(spawned) = &detached.spawned else {
+                continue;
+            };
+            if !os_windows.contains(spawned.window) {
+                commands.entity(spawned.camera).try_despawn();
+                commands.entity(entity).remove::<Self>();
+            }
+        }
+        for mut dashboard in &mut attached {
+            if dashboard.os_window().is_some() {
+                dashboard.set_os_window(None);
+            }
+        }
+        for (entity, OsWindowOf(dashboard)) in &spawned {
+            if !detached.contains(*dashboard) {
+                commands.entity(entity).try_despawn();
+            }
+        }
+    }
+
+    /// Bevy system that keeps the titles and levels of OS windows in sync
+    /// with their dashboard windows.
+    pub(crate) fn sync_os_windows(
+        dashboards: Query<(&DashboardWindow, &Self)>,
+        mut windows: Query<&mut Window, With<OsWindowOf>>,
+    ) {
+        for (dashboard, detached) in &dashboards {
+            let Some(Ok(mut window)) = detached.os_window().map(|w| windows.get_mut(w)) else {
+                continue;
+            };
+            let title = dashboard.title_with_summary();
+            if window.title != title {
+                window.title = title;
+            }
+            let level = window_level(dashboard);
+            if window.window_level != level {
+                window.window_level = level;
+            }
+        }
+    }
+}
+
+/// Platforms without window levels ignore them.
+fn window_level(dashboard: &DashboardWindow) -> WindowLevel {
+    if dashboard.config().always_on_top {
+        WindowLevel::AlwaysOnTop
+    } else {
+        WindowLevel::Normal
+    }
+}
@@ -0,0 +1,91 @@
+use crate::egui::{pos2, vec2, Align2, FontId, Pos2, Sense, Shape, Stroke, Ui};
+use crate::localization::DashboardLocalization;
+use crate::number_format::NumberFormat;
+use crate::plots::DialConfig;
+use crate::theme::ThemePalette;
+use std::f32::consts::PI;
+
+/// Draw a semicircular dial with colored zones and a needle at `value`.
+pub fn draw_dial(
+    config: &DialConfig,
+    value: Option<f64>,
+    unit: Option<&str>,
+    numbers: &NumberFormat,
+    ui: &mut Ui,
+) {
+    const ZONE_WIDTH: f32 = 8.0;
+    const N_ARC_POINTS: usize = 32;
+
+    let width = ui.available_width().min(300.0);
+    let (rect, _) = ui.allocate_exact_size(vec2(width, 0.5 * width + 36.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let palette = ThemePalette::of(ui);
+    let l10n = DashboardLocalization::of(ui);
+
+    let radius = 0.5 * width - ZONE_WIDTH;
+    let center = pos2(rect.center().x, rect.top() + radius + ZONE_WIDTH);
+    let range = (config.max - config.min).max(f64::EPSILON);
+    let angle_for = |v: f64| {
+        let t = ((v - config.min) / range).clamp(0.0, 1.0) as f32;
+        PI * (1.0 - t)
+    };
+    let point_at = |angle: f32, r: f32| center + r * vec2(angle.cos(), -angle.sin());
+
+    // Background arc, then zones on top.
+    let arc = |start: f64, end: f64| -> Vec<Pos2> {
+        let (a0, a1) = (angle_for(start), angle_for(end));
+        (0..=N_ARC_POINTS)
+            .map(|i| a0 + (a1 - a0) * i as f32 / N_ARC_POINTS as f32)
+            .map(|a| point_at(a, radius))
+            .collect()
+    };
+    painter.add(Shape::line(
+        arc(config.min, config.max),
+        Stroke::new(ZONE_WIDTH, palette.track),
+    ));
+    let mut zone_start = config.min;
+    for zone in &config.zones {
+        let zone_end = zone.until.min(config.max);
+        if zone_end > zone_start {
+            painter.add(Shape::line(
+                arc(zone_start, zone_end),
+                Stroke::new(ZONE_WIDTH, zone.color),
+            ));
+        }
+        zone_start = zone_start.max(zone_end);
+    }
+
+    let font = FontId::proportional(12.0);
+    painter.text(
+        point_at(PI, radius) + vec2(0.0, ZONE_WIDTH),
+        Align2::CENTER_TOP,
+        numbers.format(config.min, l10n.decimal_separator),
+        font.clone(),
+        palette.weak,
+    );
+    painter.text(
+        point_at(0.0, radius) + vec2(0.0, ZONE_WIDTH),
+        Align2::CENTER_TOP,
+        numbers.format(config.max, l10n.decimal_separator),
+        font,
+        palette.weak,
+    );
+
+    let Some(value) = value else {
+        return;
+    };
+    painter.line_segment(
+        [center, point_at(angle_for(value), radius - ZONE_WIDTH)],
+        Stroke::new(3.0, palette.foreground),
+    );
+    painter.circle_filled(center, 4.0, palette.foreground);
+
+    let text = numbers.format_with_unit(value, unit, l10n.decimal_separator);
+    painter.text(
+        center + vec2(0.0, 8.0),
+        Align2::CENTER_TOP,
+        text,
+        FontId::proportional(18.0),
+        palette.foreground,
+    );
+}
@@ -0,0 +1,363 @@
+//! Widget for plotting two metrics combined into one series, e.g. the
+//! headroom between a budget and a frame time.
+
+use crate::dashboard_window::CachedPlotConfigs;
+use crate::egui::Ui;
+use crate::egui_plot::{Line, Plot, PlotPoints};
+use crate::latest_value::LatestValue;
+use crate::localization::{DashboardLocalization, UiText};
+use crate::plot_style::PlotStyle;
+use crate::plots::{window_size_slider, DEFAULT_WINDOW_SIZE};
+use crate::registry::{MetricKey, MetricsRegistry};
+use crate::ring::Ring;
+use crate::search_bar::{MetricSearchBar, SearchSelection};
+use crate::snapshot::FrameMetricsSnapshot;
+use crate::widget::{DashboardWidget, WidgetContext};
+use bevy::prelude::{default, Reflect};
+use metrics_util::MetricKind;
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
+
+/// How a [`DifferencePlot`] combines its two metrics.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum DifferenceOp {
+    /// `a - b`
+    #[default]
+    Subtract,
+    /// `a / b`. Frames where `b` is zero are shown as a gap.
+    Divide,
+    /// `a + b`
+    Add,
+}
+
+impl DifferenceOp {
+    #[allow(missing_docs)]
+    pub const ALL: [Self; 3] = [Self::Subtract, Self::Divide, Self::Add];
+
+    /// Combine `a` and `b`, or `None` if the result is undefined.
+    pub fn apply(self, a: f64, b: f64) -> Option<f64> {
+        match self {
+            Self::Subtract => Some(a - b),
+            Self::Divide => (b != 0.0).then(|| a / b),
+            Self::Add => Some(a + b),
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Subtract => "−",
+            Self::Divide => "/",
+            Self::Add => "+",
+        }
+    }
+}
+
+/// Configuration for one [`DifferencePlot`].
+#[derive(Clone, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct DifferencePlotConfig {
+    /// The left operand.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "layout", serde(with = "crate::layout::optional_metric_key"))]
+    pub a: Option<MetricKey>,
+    /// The right operand.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "layout", serde(with = "crate::layout::optional_metric_key"))]
+    pub b: Option<MetricKey>,
+    #[allow(missing_docs)]
+    pub op: DifferenceOp,
+    /// How many frames are drawn.
+    pub window_size: usize,
+}
+
+impl Default for DifferencePlotConfig {
+    fn default() -> Self {
+        Self {
+            a: None,
+            b: None,
+            op: default(),
+            window_size: DEFAULT_WINDOW_SIZE,
+        }
+    }
+}
+
+impl DifferencePlotConfig {
+    /// The key of this config in the [`CachedPlotConfigs`], if both operands
+    /// are chosen.
+    pub fn operands(&self) -> Option<(MetricKey, MetricKey)> {
+        Some((self.a.clone()?, self.b.clone()?))
+    }
+}
+
+/// A time series of two metrics combined with a [`DifferenceOp`], sampled
+/// once per frame.
+///
+/// Counters contribute their increment since the last update, i.e. their
+/// rate per frame, and gauges their current value. Histograms can't be
+/// chosen. A frame where either metric has no value, or where the result is
+/// undefined, is shown as a gap.
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+pub struct DifferencePlot {
+    name: String,
+    config: DifferencePlotConfig,
+    #[reflect(ignore)]
+    a: Option<LatestValue>,
+    #[reflect(ignore)]
+    b: Option<LatestValue>,
+    #[reflect(ignore)]
+    values: Ring<Option<f64>>,
+    #[reflect(ignore)]
+    a_search_bar: MetricSearchBar,
+    #[reflect(ignore)]
+    b_search_bar: MetricSearchBar,
+}
+
+impl DifferencePlot {
+    /// Create a new difference plot widget.
+    pub fn new(name: impl Into<String>, config: DifferencePlotConfig) -> Self {
+        Self {
+            name: name.into(),
+            a: config.a.clone().map(LatestValue::new),
+            b: config.b.clone().map(LatestValue::new),
+            values: Ring::new(config.window_size),
+            config,
+            a_search_bar: Default::default(),
+            b_search_bar: Default::default(),
+        }
+    }
+
+    /// Name of the widget.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This widget's configuration.
+    pub fn config(&self) -> &DifferencePlotConfig {
+        &self.config
+    }
+
+    /// The combined value of the last frame, if both metrics had one.
+    pub fn latest_value(&self) -> Option<f64> {
+        self.values.latest().copied().flatten()
+    }
+
+    /// The formula of the plot, like `a − b`, with the titles of the
+    /// operands in `registry`.
+    pub fn formula(&self, registry: &MetricsRegistry) -> Option<String> {
+        let (a, b) = self.config.operands()?;
+        Some(format!(
+            "{} {} {}",
+            registry.title(&a, 0),
+            self.config.op.symbol(),
+            registry.title(&b, 0)
+        ))
+    }
+
+    /// Sample both metrics and push their combined value, or a gap.
+    ///
+    /// This should run in the [`SampleMetricsSet`](crate::SampleMetricsSet),
+    /// like [`MetricPlot::update`](crate::plots::MetricPlot::update). Both
+    /// values are read from the same `snapshot`.
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        self.a_search_bar.update(snapshot);
+        self.b_search_bar.update(snapshot);
+        let (Some(a), Some(b)) = (&mut self.a, &mut self.b) else {
+            return;
+        };
+        a.update(snapshot);
+        b.update(snapshot);
+        let value = match (a.latest(), b.latest()) {
+            (Some(a), Some(b)) => self.config.op.apply(a, b),
+            _ => None,
+        };
+        self.values.push(value);
+    }
+
+    /// The metrics sampled by [`Self::update`].
+    pub fn consumed_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        [&self.config.a, &self.config.b]
+            .into_iter()
+            .flatten()
+            .chain(self.a_search_bar.previewed_metrics())
+            .chain(self.b_search_bar.previewed_metrics())
+    }
+
+    /// Draw the widget using `ui`.
+    ///
+    /// When both operands are newly chosen, settings are restored from
+    /// `cached_configs`.
+    pub fn draw(
+        &mut self,
+        registry: &MetricsRegistry,
+        cached_configs: &CachedPlotConfigs,
+        style: &PlotStyle,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        if self.config.operands().is_none() {
+            ui.weak(l10n.get(UiText::ChooseOperands));
+            ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                self.configure_ui(registry, cached_configs, ui);
+            });
+            return;
+        }
+
+        if let Some(latest) = self.latest_value() {
+            ui.label(l10n.format(UiText::Latest, &[("value", &l10n.number(latest, 3))]));
+        }
+        let plot = Plot::new(&self.name)
+            .allow_scroll(false)
+            .view_aspect(2.0)
+            .x_axis_label(l10n.get(UiText::FrameAxis));
+        let lines = self.lines();
+        style.show(plot, ui, |plot_ui| {
+            for line in lines {
+                plot_ui.line(line);
+            }
+        });
+
+        ui.collapsing(l10n.get(UiText::Settings), |ui| {
+            self.configure_ui(registry, cached_configs, ui);
+        });
+    }
+
+    /// One line per run of frames with values.
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        let mut points = Vec::new();
+        let samples = (self.values.first_index()..).zip(self.values.iter_chronological());
+        for (sample, value) in samples {
+            match value {
+                Some(value) => points.push([sample as f64, *value]),
+                None if !points.is_empty() => {
+                    let points = std::mem::take(&mut points);
+                    lines.push(Line::new(&self.name, PlotPoints::new(points)));
+                }
+                None => {}
+            }
+        }
+        if !points.is_empty() {
+            lines.push(Line::new(&self.name, PlotPoints::new(points)));
+        }
+        lines
+    }
+
+    fn configure_ui(
+        &mut self,
+        registry: &MetricsRegistry,
+        cached_configs: &CachedPlotConfigs,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            for op in DifferenceOp::ALL {
+                ui.selectable_value(&mut self.config.op, op, op.symbol());
+            }
+        });
+        if ui
+            .add(window_size_slider(&l10n, &mut self.config.window_size))
+            .changed()
+        {
+            self.values.set_max_len(self.config.window_size);
+        }
+
+        ui.separator();
+
+        ui.weak(l10n.get(UiText::OperandsHint));
+        let mut changed = false;
+        for (label, key, value, search_bar) in [
+            ("A", &mut self.config.a, &mut self.a, &mut self.a_search_bar),
+            ("B", &mut self.config.b, &mut self.b, &mut self.b_search_bar),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(l10n.format(UiText::Axis, &[("axis", &label)]));
+                match key {
+                    Some(key) => ui.label(registry.title(key, 0)),
+                    None => ui.weak(l10n.get(UiText::Nothing)),
+                };
+            });
+            if let Some(selected) = search_bar
+                .show(ui, registry)
+                .map(SearchSelection::into_result)
+                .filter(|selected| selected.key.kind != MetricKind::Histogram)
+            {
+                *value = Some(LatestValue::new(selected.key.clone()));
+                *key = Some(selected.key);
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+        self.values = Ring::new(self.config.window_size);
+        if let Some(cached) = self
+            .config
+            .operands()
+            .and_then(|operands| cached_configs.difference_plots.get(&operands))
+        {
+            self.config = cached.clone();
+            self.values.set_max_len(self.config.window_size);
+        }
+    }
+}
+
+impl Default for DifferencePlot {
+    fn default() -> Self {
+        Self::new("Difference", DifferencePlotConfig::default())
+    }
+}
+
+impl DashboardWidget for DifferencePlot {
+    fn id(&self) -> &str {
+        "difference_plot"
+    }
+
+    fn title(&self, ctx: &WidgetContext) -> String {
+        self.formula(ctx.registry)
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext) {
+        self.draw(ctx.registry, ctx.cached_configs, ctx.style, ui);
+    }
+
+    fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        DifferencePlot::update(self, snapshot);
+    }
+
+    fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+        Box::new(DifferencePlot::consumed_metrics(self))
+    }
+
+    fn on_close(&mut self, cached_configs: &mut CachedPlotConfigs) {
+        if let Some(operands) = self.config.operands() {
+            cached_configs
+                .difference_plots
+                .insert(operands, self.config.clone());
+        }
+    }
+
+    #[cfg(feature = "layout")]
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        RawValue::from_rust(&self.config).map(Some)
+    }
+
+    #[cfg(feature = "layout")]
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        let name = std::mem::take(&mut self.name);
+        *self = Self::new(name, config.into_rust()?);
+        Ok(())
+    }
+
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+}
@@ -0,0 +1,235 @@
+//! Short display names for long label values, like
+//! `system=bevy_render::renderer::render_system`.
+//!
+//! Display names are only used for drawing. Metric keys, and everything
+//! derived from them, like saved layouts, are unchanged.
+
+use crate::egui::{Grid, TextEdit, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::name_pattern::NamePattern;
+use crate::registry::MetricKey;
+use crate::theme::ThemePalette;
+use bevy::prelude::default;
+use std::borrow::Cow;
+
+/// Maps one label value to a display name.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct DisplayNameRule {
+    /// The label value to match.
+    pub pattern: String,
+    /// If true, [`Self::pattern`] is a regular expression, and the first
+    /// match in the value is replaced with [`Self::display`], which may
+    /// refer to capture groups like `$1`. Otherwise, the whole value must
+    /// equal the pattern.
+    pub regex: bool,
+    #[allow(missing_docs)]
+    pub display: String,
+}
+
+impl DisplayNameRule {
+    /// Replace the label value `pattern` with `display`.
+    pub fn exact(pattern: impl Into<String>, display: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            regex: false,
+            display: display.into(),
+        }
+    }
+
+    /// Replace the first match of the regular expression `pattern` with
+    /// `display`.
+    pub fn regex(pattern: impl Into<String>, display: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            regex: true,
+            display: display.into(),
+        }
+    }
+
+    fn compile(&self) -> Matcher {
+        if !self.regex {
+            return Matcher::Exact;
+        }
+        match NamePattern::regex(self.pattern.as_str()) {
+            Ok(pattern) => Matcher::Regex(pattern),
+            Err(e) => Matcher::Invalid(e.to_string()),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Matcher {
+    Exact,
+    Regex(NamePattern),
+    /// Never matches.
+    Invalid(String),
+}
+
+/// An ordered table of [`DisplayNameRule`]s.
+///
+/// Each label value is renamed by the first rule that matches it. Rules with
+/// invalid regular expressions never match.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(
+    feature = "layout",
+    serde(from = "Vec<DisplayNameRule>", into = "Vec<DisplayNameRule>")
+)]
+pub struct DisplayNames {
+    rules: Vec<DisplayNameRule>,
+    /// One per rule.
+    matchers: Vec<Matcher>,
+}
+
+impl PartialEq for DisplayNames {
+    fn eq(&self, other: &Self) -> bool {
+        self.rules == other.rules
+    }
+}
+
+impl From<Vec<DisplayNameRule>> for DisplayNames {
+    fn from(rules: Vec<DisplayNameRule>) -> Self {
+        let matchers = rules.iter().map(DisplayNameRule::compile).collect();
+        Self { rules, matchers }
+    }
+}
+
+impl From<DisplayNames> for Vec<DisplayNameRule> {
+    fn from(names: DisplayNames) -> Self {
+        names.rules
+    }
+}
+
+impl FromIterator<DisplayNameRule> for DisplayNames {
+    fn from_iter<T: IntoIterator<Item = DisplayNameRule>>(iter: T) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl DisplayNames {
+    #[allow(missing_docs)]
+    pub fn rules(&self) -> &[DisplayNameRule] {
+        &self.rules
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Add a rule with the lowest priority.
+    pub fn push(&mut self, rule: DisplayNameRule) {
+        self.matchers.push(rule.compile());
+        self.rules.push(rule);
+    }
+
+    /// The error of each rule whose regular expression is invalid, by index.
+    pub fn errors(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.matchers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, m)| match m {
+                Matcher::Invalid(e) => Some((i, e.as_str())),
+                _ => None,
+            })
+    }
+
+    /// The display name of the label `value`, or `value` itself if no rule
+    /// matches.
+    pub fn rename<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        for (rule, matcher) in self.rules.iter().zip(&self.matchers) {
+            match matcher {
+                Matcher::Exact if rule.pattern == value => {
+                    return Cow::Owned(rule.display.clone());
+                }
+                Matcher::Regex(pattern) if pattern.matches(value) => {
+                    return Cow::Owned(pattern.replace(value, &rule.display).into_owned());
+                }
+                _ => {}
+            }
+        }
+        Cow::Borrowed(value)
+    }
+
+    /// Draw the table and accept user input. Returns true if any rule
+    /// changed.
+    ///
+    /// Invalid regular expressions are shown below their row.
+    pub fn configure_ui(&mut self, ui: &mut Ui) -> bool {
+        let l10n = DashboardLocalization::of(ui);
+        let mut changed = false;
+        let mut remove = None;
+        Grid::new("display-names").num_columns(4).show(ui, |ui| {
+            for (i, (rule, matcher)) in self.rules.iter_mut().zip(&mut self.matchers).enumerate() {
+                let mut rule_changed = ui
+                    .add(
+                        TextEdit::singleline(&mut rule.pattern)
+                            .hint_text(l10n.get(UiText::LabelValueHint))
+                            .desired_width(160.0),
+                    )
+                    .changed();
+                rule_changed |= ui
+                    .checkbox(&mut rule.regex, l10n.get(UiText::Regex))
+                    .changed();
+                rule_changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut rule.display)
+                            .hint_text(l10n.get(UiText::DisplayNameHint))
+                            .desired_width(100.0),
+                    )
+                    .changed();
+                if ui.small_button(l10n.get(UiText::RemoveRule)).clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+
+                if rule_changed {
+                    *matcher = rule.compile();
+                    changed = true;
+                }
+                if let Matcher::Invalid(e) = matcher {
+                    ui.colored_label(ThemePalette::of(ui).error, e.as_str());
+                    ui.end_row();
+                }
+            }
+        });
+        if ui.button(l10n.get(UiText::AddRule)).clicked() {
+            self.push(default());
+            changed = true;
+        }
+        if let Some(i) = remove {
+            self.rules.remove(i);
+            self.matchers.remove(i);
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// The name of a series, like `name{k1=v1,k2=v2}` with sorted labels, with
+/// each label value renamed by every table in `tables` in turn.
+pub fn series_name(key: &MetricKey, tables: &[&DisplayNames]) -> String {
+    let labels: Vec<_> = key
+        .normalized()
+        .key
+        .labels()
+        .map(|l| format!("{}={}", l.key(), rename_with(tables, l.value())))
+        .collect();
+    if labels.is_empty() {
+        key.key.name().to_owned()
+    } else {
+        format!("{}{{{}}}", key.key.name(), labels.join(","))
+    }
+}
+
+/// Rename `value` by every table in `tables` in turn.
+pub fn rename_with<'a>(tables: &[&DisplayNames], value: &'a str) -> Cow<'a, str> {
+    tables.iter().fold(Cow::Borrowed(value), |value, table| {
+        match table.rename(&value) {
+            Cow::Borrowed(_) => value,
+            Cow::Owned(renamed) => Cow::Owned(renamed),
+        }
+    })
+}
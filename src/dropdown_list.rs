@@ -1,21 +1,57 @@
 use crate::egui::*;
 
-/// Returns the iterator element corresponding to the newly-selected item.
-pub fn dropdown_list<T, I, F, S>(
+/// Returns the iterator element corresponding to the newly-selected item,
+/// and whether it was selected by pressing enter rather than clicking.
+///
+/// While `drop_from_widget` has focus, the up and down arrow keys move the
+/// highlighted item, which is selected by pressing enter. Escape closes the
+/// list, and ctrl+enter selects the highlighted item without closing it. The
+/// list opens when `drop_from_widget` is clicked or changed, e.g. by typing.
+///
+/// `value_text` is called for each item that's visible, and its text, if
+/// any, is shown right-aligned in the item's row. `item_response` is called
+/// with the response of each item, e.g. to attach a context menu.
+pub fn dropdown_list<T, I, F, S, V, R>(
     drop_from_widget: Response,
     ui: &mut Ui,
     id_source: impl std::hash::Hash,
     items: I,
     get_text: F,
-) -> Option<T>
+    mut value_text: V,
+    mut item_response: R,
+) -> Option<(T, bool)>
 where
     I: Iterator<Item = T>,
     F: Fn(&T) -> S,
     S: Into<WidgetText>,
+    V: FnMut(&T) -> Option<String>,
+    R: FnMut(&T, &Response),
 {
     let popup_id = ui.make_persistent_id(id_source);
+    let highlight_id = popup_id.with("highlighted");
 
-    if drop_from_widget.clicked() {
+    // Plain enter makes a single line text edit lose focus.
+    let keyboard = drop_from_widget.has_focus() || drop_from_widget.lost_focus();
+    let (up, down, enter, keep_open) = ui.input(|i| {
+        (
+            keyboard && i.key_pressed(Key::ArrowUp),
+            keyboard && i.key_pressed(Key::ArrowDown),
+            keyboard && i.key_pressed(Key::Enter),
+            i.modifiers.command,
+        )
+    });
+    let mut highlighted = ui.data(|d| d.get_temp::<usize>(highlight_id).unwrap_or(0));
+    if drop_from_widget.changed() {
+        highlighted = 0;
+    }
+    if up {
+        highlighted = highlighted.saturating_sub(1);
+    }
+    if down {
+        highlighted += 1;
+    }
+
+    if drop_from_widget.clicked() || drop_from_widget.changed() || down {
         ui.memory_mut(|m| m.open_popup(popup_id));
     }
 
@@ -26,24 +62,44 @@ where
         &drop_from_widget,
         PopupCloseBehavior::CloseOnClickOutside,
         |ui: &mut Ui| {
-            let select_first = ui.input(|i| i.key_pressed(Key::Enter));
             ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                let mut first = true;
-                for item in items {
+                let mut count = 0usize;
+                for (i, item) in items.enumerate() {
+                    count += 1;
                     let text = get_text(&item);
-
-                    // TODO: implement arrow key browsing, have the one that'd be
-                    // selected by enter highlighted
-                    if ui.selectable_label(false, text.into()).clicked() || (select_first && first)
-                    {
-                        return_val = Some(item);
-                        ui.memory_mut(|m| m.close_popup());
+                    let response = ui
+                        .horizontal(|ui| {
+                            let response = ui.selectable_label(i == highlighted, text.into());
+                            if ui.is_rect_visible(response.rect) {
+                                if let Some(value) = value_text(&item) {
+                                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                        ui.weak(value);
+                                    });
+                                }
+                            }
+                            response
+                        })
+                        .inner;
+                    item_response(&item, &response);
+                    if i == highlighted && (up || down) {
+                        response.scroll_to_me(None);
+                    }
+                    let pressed = enter && i == highlighted;
+                    if response.clicked() || pressed {
+                        return_val = Some((item, pressed));
+                        if !(pressed && keep_open) {
+                            ui.memory_mut(|m| m.close_popup());
+                        }
                     }
-                    first = false;
                 }
+                highlighted = highlighted.min(count.saturating_sub(1));
             });
         },
     );
+    ui.data_mut(|d| d.insert_temp(highlight_id, highlighted));
+    if return_val.is_some() && keep_open {
+        drop_from_widget.request_focus();
+    }
 
     return_val
 }
@@ -0,0 +1,99 @@
+use crate::registry::describe_in_build;
+use crate::type_name::short_type_name;
+use bevy::prelude::*;
+use metrics::{counter, gauge, KeyName, Unit};
+use metrics_util::MetricKind;
+use std::marker::PhantomData;
+
+/// Provides throughput metrics for events of type `E`.
+///
+/// - `events/E/count`: counter of sent events
+/// - `events/E/queued`: gauge of the events currently stored in [`Events<E>`]
+///
+/// Events are read with a separate [`EventReader`], so other readers still
+/// see them. `E` is the type name without its module path, unless
+/// [`Self::full_type_path`] is used.
+///
+/// The plugin can be added before the [`RegistryPlugin`](crate::RegistryPlugin),
+/// its descriptions are replayed when the registry is built:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::registry::{DescriptionKey, MetricsRegistry};
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::{EventMetricsPlugin, RegistryPlugin};
+/// #[derive(Event)]
+/// struct Jump;
+///
+/// let mut app = App::new();
+/// app.add_plugins(EventMetricsPlugin::<Jump>::default())
+///     .add_plugins(RegistryPlugin::local(MetricsRegistry::new()));
+///
+/// let registry = app.world().resource::<MetricsRegistry>();
+/// let key = DescriptionKey {
+///     name: "events/Jump/count".into(),
+///     kind: MetricKind::Counter,
+/// };
+/// assert_eq!(registry.get_description(&key).unwrap().text, "Events sent".into());
+/// ```
+pub struct EventMetricsPlugin<E> {
+    full_type_path: bool,
+    marker: PhantomData<fn() -> E>,
+}
+
+impl<E> Default for EventMetricsPlugin<E> {
+    fn default() -> Self {
+        Self {
+            full_type_path: false,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> EventMetricsPlugin<E> {
+    /// Use the full type path of `E` in metric names, e.g.
+    /// `events/my_game::DamageEvent/count`.
+    pub fn full_type_path(mut self) -> Self {
+        self.full_type_path = true;
+        self
+    }
+}
+
+impl<E: Event> Plugin for EventMetricsPlugin<E> {
+    fn build(&self, app: &mut App) {
+        let type_name = std::any::type_name::<E>();
+        let prefix = if self.full_type_path {
+            format!("events/{type_name}")
+        } else {
+            format!("events/{}", short_type_name(type_name))
+        };
+        let count_name = format!("{prefix}/count");
+        let queued_name = format!("{prefix}/queued");
+
+        describe_in_build(
+            app,
+            &[
+                (
+                    KeyName::from(count_name.clone()),
+                    MetricKind::Counter,
+                    Some(Unit::Count),
+                    "Events sent",
+                ),
+                (
+                    KeyName::from(queued_name.clone()),
+                    MetricKind::Gauge,
+                    Some(Unit::Count),
+                    "Events stored in the event queue",
+                ),
+            ],
+        );
+        // Lets the plugin be added before the event is registered.
+        app.add_event::<E>().add_systems(
+            Last,
+            move |mut reader: EventReader<E>, events: Res<Events<E>>| {
+                counter!(count_name.clone()).increment(reader.read().count() as u64);
+                gauge!(queued_name.clone()).set(events.len() as f64);
+            },
+        );
+    }
+}
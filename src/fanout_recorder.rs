@@ -0,0 +1,201 @@
+use crate::registry::MetricsRegistry;
+use bevy::prelude::Resource;
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::sync::{Arc, RwLock};
+
+type SharedRecorder = Arc<dyn Recorder + Send + Sync>;
+
+/// A recorder that sends every metric to a [`MetricsRegistry`] and to other
+/// recorders, which can be added after it's installed as the global
+/// recorder.
+///
+/// [`RegistryPlugin`](crate::RegistryPlugin) installs one with
+/// [`FallbackBehavior::Fanout`](crate::FallbackBehavior::Fanout), or when
+/// recorders are passed to
+/// [`RegistryPlugin::fanout`](crate::RegistryPlugin::fanout), and inserts it
+/// as a resource. Since the `metrics` crate only allows one global recorder,
+/// an exporter that is set up after the plugin can't install itself, but it
+/// can be added here:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::{FallbackBehavior, FanoutRecorder, RegistryPlugin};
+/// use metrics::{counter, Key};
+/// use std::sync::atomic::Ordering;
+///
+/// let mut app = App::new();
+/// app.add_plugins(RegistryPlugin::new().with_fallback(FallbackBehavior::Fanout));
+///
+/// // Stands in for an exporter's recorder.
+/// let exporter = MetricsRegistry::new();
+/// assert!(metrics::set_global_recorder(exporter.clone()).is_err());
+/// app.world().resource::<FanoutRecorder>().add(exporter.clone());
+///
+/// counter!("hits").increment(2);
+/// let key = Key::from_static_name("hits");
+/// let registry = app.world().resource::<MetricsRegistry>();
+/// assert_eq!(registry.get_counter(&key).unwrap().load(Ordering::Relaxed), 2);
+/// assert_eq!(exporter.get_counter(&key).unwrap().load(Ordering::Relaxed), 2);
+/// ```
+///
+/// Handles registered before a recorder is added don't send to it. The
+/// `metrics` macros register their handle on every call, so this only
+/// matters for handles that are kept, like a
+/// [`CachedCounter`](crate::CachedCounter).
+#[derive(Clone, Resource)]
+pub struct FanoutRecorder {
+    registry: MetricsRegistry,
+    others: Arc<RwLock<Vec<SharedRecorder>>>,
+}
+
+impl FanoutRecorder {
+    /// Send metrics to `registry`, and to no other recorder yet.
+    pub fn new(registry: MetricsRegistry) -> Self {
+        Self {
+            registry,
+            others: Default::default(),
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn registry(&self) -> &MetricsRegistry {
+        &self.registry
+    }
+
+    /// Also send all metrics registered from now on to `recorder`.
+    pub fn add(&self, recorder: impl Recorder + Send + Sync + 'static) {
+        self.others.write().unwrap().push(Arc::new(recorder));
+    }
+
+    pub(crate) fn add_boxed(&self, recorder: Box<dyn Recorder + Send + Sync>) {
+        self.others.write().unwrap().push(Arc::from(recorder));
+    }
+
+    /// How many recorders were [added](Self::add).
+    pub fn len(&self) -> usize {
+        self.others.read().unwrap().len()
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The registry's handle, or one that also writes to the handles of the
+    /// other recorders.
+    fn fan_out<H, F>(&self, ours: H, register: impl Fn(&dyn Recorder) -> H, wrap: F) -> H
+    where
+        F: FnOnce(Vec<H>) -> H,
+    {
+        let others = self.others.read().unwrap();
+        if others.is_empty() {
+            return ours;
+        }
+        let handles = std::iter::once(ours)
+            .chain(others.iter().map(|other| register(&**other)))
+            .collect();
+        wrap(handles)
+    }
+}
+
+impl Recorder for FanoutRecorder {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for other in self.others.read().unwrap().iter() {
+            other.describe_counter(key.clone(), unit, description.clone());
+        }
+        self.registry.describe_counter(key, unit, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for other in self.others.read().unwrap().iter() {
+            other.describe_gauge(key.clone(), unit, description.clone());
+        }
+        self.registry.describe_gauge(key, unit, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for other in self.others.read().unwrap().iter() {
+            other.describe_histogram(key.clone(), unit, description.clone());
+        }
+        self.registry.describe_histogram(key, unit, description);
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        self.fan_out(
+            self.registry.register_counter(key, metadata),
+            |other| other.register_counter(key, metadata),
+            |handles| Counter::from_arc(Arc::new(FanoutHandle(handles))),
+        )
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        self.fan_out(
+            self.registry.register_gauge(key, metadata),
+            |other| other.register_gauge(key, metadata),
+            |handles| Gauge::from_arc(Arc::new(FanoutHandle(handles))),
+        )
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        self.fan_out(
+            self.registry.register_histogram(key, metadata),
+            |other| other.register_histogram(key, metadata),
+            |handles| Histogram::from_arc(Arc::new(FanoutHandle(handles))),
+        )
+    }
+}
+
+/// Writes to the handles of every recorder of a [`FanoutRecorder`].
+struct FanoutHandle<H>(Vec<H>);
+
+impl CounterFn for FanoutHandle<Counter> {
+    fn increment(&self, value: u64) {
+        for counter in &self.0 {
+            counter.increment(value);
+        }
+    }
+
+    fn absolute(&self, value: u64) {
+        for counter in &self.0 {
+            counter.absolute(value);
+        }
+    }
+}
+
+impl GaugeFn for FanoutHandle<Gauge> {
+    fn increment(&self, value: f64) {
+        for gauge in &self.0 {
+            gauge.increment(value);
+        }
+    }
+
+    fn decrement(&self, value: f64) {
+        for gauge in &self.0 {
+            gauge.decrement(value);
+        }
+    }
+
+    fn set(&self, value: f64) {
+        for gauge in &self.0 {
+            gauge.set(value);
+        }
+    }
+}
+
+impl HistogramFn for FanoutHandle<Histogram> {
+    fn record(&self, value: f64) {
+        for histogram in &self.0 {
+            histogram.record(value);
+        }
+    }
+
+    fn record_many(&self, value: f64, count: usize) {
+        for histogram in &self.0 {
+            histogram.record_many(value, count);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+#[cfg(feature = "bevy_egui")]
+use bevy::platform::collections::HashSet;
+
+/// What the dashboard does while none of the app's windows are focused, e.g.
+/// after alt-tabbing out of the game.
+///
+/// See [`DashboardConfig::unfocused_behavior`](crate::DashboardConfig::unfocused_behavior).
+/// Samples are collected in every mode, so plots don't have gaps once the
+/// app is focused again.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Reflect)]
+pub enum UnfocusedBehavior {
+    /// Refresh and draw plots as usual.
+    #[default]
+    Full,
+    /// Refresh plots at [`UNFOCUSED_REFRESH_RATE`], unless the
+    /// [`DashboardConfig::ui_refresh_rate`](crate::DashboardConfig::ui_refresh_rate)
+    /// is lower.
+    ReducedRefresh,
+    /// Neither refresh plots nor draw dashboard windows.
+    CollectOnly,
+}
+
+/// How many times per second plots are refreshed while unfocused with
+/// [`UnfocusedBehavior::ReducedRefresh`].
+pub const UNFOCUSED_REFRESH_RATE: f64 = 1.0;
+
+/// How many plots per window are refreshed each frame while catching up
+/// after the app is focused again.
+///
+/// Plots that were on screen before the app lost focus are refreshed first.
+pub const CATCH_UP_PLOTS_PER_FRAME: usize = 8;
+
+/// Whether any of the app's windows is focused.
+///
+/// Updated from [`WindowFocused`](bevy::window::WindowFocused) events by the
+/// `DashboardPlugin`. Apps without windows are always focused, unless they
+/// call [`Self::set_focused`] themselves.
+#[derive(Debug, Resource)]
+pub struct DashboardFocus {
+    focused: bool,
+    #[cfg(feature = "bevy_egui")]
+    focused_windows: HashSet<Entity>,
+    regained: bool,
+}
+
+impl Default for DashboardFocus {
+    fn default() -> Self {
+        Self {
+            focused: true,
+            #[cfg(feature = "bevy_egui")]
+            focused_windows: default(),
+            regained: false,
+        }
+    }
+}
+
+impl DashboardFocus {
+    #[allow(missing_docs)]
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Whether the app was focused again this frame.
+    pub fn regained(&self) -> bool {
+        self.regained
+    }
+
+    /// Override the focus until the next focus event.
+    pub fn set_focused(&mut self, focused: bool) {
+        self.regained |= focused && !self.focused;
+        self.focused = focused;
+    }
+
+    /// Clear [`Self::regained`] once the frame's plots were updated.
+    pub(crate) fn end_frame(&mut self) {
+        self.regained = false;
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// Bevy system that tracks the focus of every window.
+    ///
+    /// Focus moving between two windows, e.g. to a
+    /// [detached](crate::DetachedDashboard) dashboard, isn't a loss of focus.
+    pub(crate) fn track_windows(
+        mut focus: ResMut<Self>,
+        mut events: EventReader<bevy::window::WindowFocused>,
+    ) {
+        if events.is_empty() {
+            return;
+        }
+        for event in events.read() {
+            if event.focused {
+                focus.focused_windows.insert(event.window);
+            } else {
+                focus.focused_windows.remove(&event.window);
+            }
+        }
+        let focused = !focus.focused_windows.is_empty();
+        focus.set_focused(focused);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    dashboard_window::{
+        CachedPlotConfigs, DashboardAnnotation, DashboardConfig, OpenPlots, RequestPlot,
+    },
+    focus::DashboardFocus,
+    localization::DashboardLocalization,
+    performance_budget::DashboardPerformance,
+    snapshot::{FrameMetricsSnapshot, SampleMetricsSet},
+    virtual_time::VirtualTimeAdvanced,
+    BucketClearSet, DashboardWindow,
+};
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
+
+/// Updates all [`DashboardWindow`] entities without drawing them, e.g. for
+/// performance jobs in CI.
+///
+/// Plots are added with [`RequestPlot`] events or
+/// [`DashboardWindow::add_plot`], and read back with
+/// [`DashboardWindow::find_plot`] and
+/// [`MetricPlot::stats`](crate::plots::MetricPlot::stats). This doesn't
+/// need `bevy_egui` or a window. Use it instead of the
+/// [`DashboardPlugin`](crate::DashboardPlugin), which does the same updates.
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin).
+#[derive(Default)]
+pub struct HeadlessDashboardPlugin {
+    config: DashboardConfig,
+    sample_schedule: Option<InternedScheduleLabel>,
+}
+
+impl HeadlessDashboardPlugin {
+    /// Create a plugin with the default [`DashboardConfig`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a plugin with a custom [`DashboardConfig`].
+    pub fn with_config(config: DashboardConfig) -> Self {
+        Self {
+            config,
+            ..default()
+        }
+    }
+
+    /// See [`DashboardPlugin::sample_metrics_in`](crate::DashboardPlugin::sample_metrics_in).
+    pub fn sample_metrics_in(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.sample_schedule = Some(schedule.intern());
+        self
+    }
+}
+
+impl Plugin for HeadlessDashboardPlugin {
+    fn build(&self, app: &mut App) {
+        add_sampling(app, self.config.clone(), self.sample_schedule);
+        app.add_systems(Update, DashboardWindow::handle_plot_requests);
+    }
+}
+
+/// Add the resources and systems that keep dashboard windows up to date,
+/// without drawing them.
+pub(crate) fn add_sampling(
+    app: &mut App,
+    config: DashboardConfig,
+    sample_schedule: Option<InternedScheduleLabel>,
+) {
+    let sample_schedule = sample_schedule.unwrap_or_else(|| Last.intern());
+    app.add_event::<RequestPlot>()
+        .add_event::<OpenPlots>()
+        .add_event::<DashboardAnnotation>()
+        .insert_resource(config)
+        .init_resource::<DashboardLocalization>()
+        .init_resource::<CachedPlotConfigs>()
+        .init_resource::<FrameMetricsSnapshot>()
+        .init_resource::<VirtualTimeAdvanced>()
+        .init_resource::<DashboardPerformance>()
+        .init_resource::<DashboardFocus>()
+        // Enforce strict ordering:
+        // metrics producers --> snapshot --> metrics consumers --> bucket clearing
+        .configure_sets(sample_schedule, SampleMetricsSet.before(BucketClearSet))
+        .add_systems(
+            sample_schedule,
+            (
+                FrameMetricsSnapshot::capture_system,
+                VirtualTimeAdvanced::update_system,
+                DashboardWindow::update_plots_on_all_windows,
+                DashboardPerformance::update_system,
+            )
+                .chain()
+                .in_set(SampleMetricsSet),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            DashboardWindow::collect_ticks_on_all_windows.in_set(SampleMetricsSet),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                DashboardConfig::sync_unit_inference,
+                DashboardConfig::sync_histogram_cap,
+            ),
+        );
+}
@@ -0,0 +1,175 @@
+use crate::registry::MetricKey;
+use crate::snapshot::{FrameMetricsSnapshot, SampleMetricsSet, SnapshotValue};
+use crate::stats::mean;
+use crate::BucketClearSet;
+use bevy::prelude::*;
+use metrics::{Key, KeyName};
+use metrics_util::MetricKind;
+
+/// Tints a UI node by the value of a metric, e.g. a dot that turns yellow,
+/// then red, as the frame time goes up, without opening a dashboard.
+///
+/// Each frame, the [`MetricIndicatorPlugin`] reads the metric from the
+/// [`FrameMetricsSnapshot`] and writes the [color](Self::color_at) into the
+/// entity's [`BackgroundColor`] and [`TextColor`], whichever it has.
+/// Counters use their total, gauges their value, and histograms the mean of
+/// the samples recorded in the frame.
+///
+/// While the metric isn't registered, the colors are left untouched and
+/// [`Self::missing`] is set.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::MetricIndicator;
+/// fn spawn_indicator(mut commands: Commands) {
+///     commands.spawn((
+///         Node {
+///             width: Val::Px(12.0),
+///             height: Val::Px(12.0),
+///             ..default()
+///         },
+///         BackgroundColor::default(),
+///         MetricIndicator::gauge(
+///             "frame_time_ms",
+///             vec![
+///                 (17.0, Color::srgb(0.2, 0.8, 0.2)),
+///                 (25.0, Color::srgb(0.9, 0.8, 0.1)),
+///                 (33.0, Color::srgb(0.9, 0.2, 0.1)),
+///             ],
+///         ),
+///     ));
+/// }
+/// ```
+#[derive(Clone, Component, Debug)]
+pub struct MetricIndicator {
+    #[allow(missing_docs)]
+    pub metric: MetricKey,
+    /// Values and their colors, in increasing order of value.
+    pub thresholds: Vec<(f64, Color)>,
+    missing: bool,
+}
+
+impl MetricIndicator {
+    #[allow(missing_docs)]
+    pub fn new(metric: MetricKey, thresholds: Vec<(f64, Color)>) -> Self {
+        Self {
+            metric,
+            thresholds,
+            missing: false,
+        }
+    }
+
+    /// Tint by the unlabeled gauge `name`.
+    pub fn gauge(name: impl Into<KeyName>, thresholds: Vec<(f64, Color)>) -> Self {
+        let key = MetricKey::new(Key::from_name(name), MetricKind::Gauge);
+        Self::new(key, thresholds)
+    }
+
+    /// Tint by the unlabeled counter `name`.
+    pub fn counter(name: impl Into<KeyName>, thresholds: Vec<(f64, Color)>) -> Self {
+        let key = MetricKey::new(Key::from_name(name), MetricKind::Counter);
+        Self::new(key, thresholds)
+    }
+
+    /// Whether the metric wasn't registered when it was last sampled.
+    pub fn missing(&self) -> bool {
+        self.missing
+    }
+
+    /// The color of `value`, interpolated between the nearest
+    /// [thresholds](Self::thresholds), or `None` without thresholds.
+    ///
+    /// Colors are mixed in the color space of the lower threshold's color.
+    /// Values outside of the thresholds get the color of the nearest one.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_metrics_dashboard::MetricIndicator;
+    /// let indicator = MetricIndicator::gauge(
+    ///     "queue_length",
+    ///     vec![(10.0, Color::srgb(0.0, 0.0, 0.0)), (20.0, Color::srgb(1.0, 1.0, 1.0))],
+    /// );
+    /// assert_eq!(indicator.color_at(0.0), Some(Color::srgb(0.0, 0.0, 0.0)));
+    /// assert_eq!(indicator.color_at(15.0), Some(Color::srgb(0.5, 0.5, 0.5)));
+    /// assert_eq!(indicator.color_at(30.0), Some(Color::srgb(1.0, 1.0, 1.0)));
+    /// ```
+    pub fn color_at(&self, value: f64) -> Option<Color> {
+        let after = self.thresholds.partition_point(|&(v, _)| v <= value);
+        let &(below, below_color) = match after.checked_sub(1) {
+            Some(i) => &self.thresholds[i],
+            None => return self.thresholds.first().map(|&(_, color)| color),
+        };
+        let Some(&(above, above_color)) = self.thresholds.get(after) else {
+            return Some(below_color);
+        };
+        let t = (value - below) / (above - below);
+        Some(below_color.mix(&above_color, t as f32))
+    }
+
+    /// The value to tint by, if the metric is in the `snapshot`.
+    fn value(&self, snapshot: &FrameMetricsSnapshot) -> Option<Option<f64>> {
+        Some(match snapshot.get(&self.metric)? {
+            SnapshotValue::Counter(total) => Some(*total as f64),
+            SnapshotValue::Gauge(value) => Some(*value),
+            SnapshotValue::Histogram(samples) => mean(samples),
+        })
+    }
+
+    /// Bevy system that tints every [`MetricIndicator`].
+    fn update_system(
+        snapshot: Res<FrameMetricsSnapshot>,
+        mut indicators: Query<(
+            &mut Self,
+            Option<&mut BackgroundColor>,
+            Option<&mut TextColor>,
+        )>,
+    ) {
+        for (mut indicator, background, text) in &mut indicators {
+            let value = indicator.value(&snapshot);
+            let missing = value.is_none();
+            if indicator.missing != missing {
+                indicator.missing = missing;
+            }
+            // Histograms without samples this frame keep their color.
+            let Some(color) = value.flatten().and_then(|v| indicator.color_at(v)) else {
+                continue;
+            };
+            if let Some(mut background) = background {
+                background.set_if_neq(BackgroundColor(color));
+            }
+            if let Some(mut text) = text {
+                text.set_if_neq(TextColor(color));
+            }
+        }
+    }
+}
+
+/// Updates the colors of [`MetricIndicator`]s every frame, after the
+/// [`FrameMetricsSnapshot`] is captured.
+///
+/// The metrics of indicators are captured like those of dashboard windows.
+/// Without a [`DashboardPlugin`](crate::DashboardPlugin) or
+/// [`HeadlessDashboardPlugin`](crate::HeadlessDashboardPlugin), this plugin
+/// captures the snapshot itself, in [`Last`].
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin).
+#[derive(Default)]
+pub struct MetricIndicatorPlugin;
+
+impl Plugin for MetricIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, MetricIndicator::update_system.after(SampleMetricsSet));
+    }
+
+    fn finish(&self, app: &mut App) {
+        if app.world().contains_resource::<FrameMetricsSnapshot>() {
+            return;
+        }
+        app.init_resource::<FrameMetricsSnapshot>()
+            .configure_sets(Last, SampleMetricsSet.before(BucketClearSet))
+            .add_systems(
+                Last,
+                FrameMetricsSnapshot::capture_system.in_set(SampleMetricsSet),
+            );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::registry::MetricKey;
+use crate::snapshot::{FrameMetricsSnapshot, SnapshotValue};
+use crate::stats::mean;
+
+/// Samples one value per frame from any kind of metric.
+///
+/// Counters report their increment since the last update, gauges their
+/// current value, and histograms the mean of their samples this frame.
+pub struct LatestValue {
+    key: MetricKey,
+    last_counter_value: Option<u64>,
+    last_gauge_bits: Option<u64>,
+    latest: Option<f64>,
+    updated: bool,
+}
+
+impl LatestValue {
+    pub fn new(key: MetricKey) -> Self {
+        Self {
+            key,
+            last_counter_value: None,
+            last_gauge_bits: None,
+            latest: None,
+            updated: false,
+        }
+    }
+
+    pub fn key(&self) -> &MetricKey {
+        &self.key
+    }
+
+    /// The value from the last update, if the metric exists and has one.
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    /// Whether the metric got a new value in the last update, like the
+    /// staleness detection of plots counts it: counters when they
+    /// incremented, gauges when their value changed, and histograms when
+    /// they have samples.
+    pub fn updated(&self) -> bool {
+        self.updated
+    }
+
+    /// Sample the value from `snapshot`, which must include [`Self::key`].
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        (self.latest, self.updated) = match snapshot.get(&self.key) {
+            None => (None, false),
+            Some(SnapshotValue::Counter(value)) => {
+                let increment = self
+                    .last_counter_value
+                    .map(|last| value.saturating_sub(last) as f64);
+                self.last_counter_value = Some(*value);
+                (increment, increment.is_some_and(|i| i > 0.0))
+            }
+            Some(SnapshotValue::Gauge(value)) => {
+                let bits = value.to_bits();
+                let updated = self.last_gauge_bits.replace(bits) != Some(bits);
+                (Some(*value), updated)
+            }
+            Some(SnapshotValue::Histogram(samples)) => {
+                let mean = mean(samples);
+                (mean, mean.is_some())
+            }
+        };
+    }
+}
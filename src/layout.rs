@@ -0,0 +1,1613 @@
+//! Saving and loading the plots of all [`DashboardWindow`]s as RON files.
+//!
+//! Layout files have a `version`, which is bumped whenever the format
+//! changes in a way that older versions can't read. Files of older versions
+//! are upgraded when they're loaded, and files of newer versions fail to
+//! load with [`LayoutError::UnsupportedVersion`]:
+//!
+//! ```
+//! # use bevy_metrics_dashboard::layout::{DashboardLayout, LayoutError, LAYOUT_VERSION};
+//! let fixture = |version| {
+//!     let path = format!("{}/fixtures/layouts/v{version}.ron", env!("CARGO_MANIFEST_DIR"));
+//!     DashboardLayout::from_ron(&std::fs::read_to_string(path).unwrap())
+//! };
+//! let current = fixture(LAYOUT_VERSION).unwrap();
+//! assert_eq!(current.version, LAYOUT_VERSION);
+//! assert!(current.windows[0].plots[0].style_override.is_some());
+//! // Fixtures of every version describe the same layout.
+//! for version in 1..LAYOUT_VERSION {
+//!     assert!(fixture(version).unwrap() == current, "version {version}");
+//! }
+//! assert!(DashboardLayout::from_ron(&current.to_ron().unwrap()).unwrap() == current);
+//!
+//! let newer = format!("(version: {}, windows: [])", LAYOUT_VERSION + 1);
+//! assert!(matches!(
+//!     DashboardLayout::from_ron(&newer),
+//!     Err(LayoutError::UnsupportedVersion(v)) if v == LAYOUT_VERSION + 1,
+//! ));
+//! ```
+//!
+//! Fields that this version doesn't know, e.g. settings added by a newer
+//! version without changing the format of the others, are kept in the
+//! [`UnknownFields`] of the layout, its windows and their plots, and written
+//! back when the layout is saved:
+//!
+//! ```
+//! # use bevy_metrics_dashboard::layout::DashboardLayout;
+//! let ron = r#"(
+//!     version: 3,
+//!     windows: [(
+//!         title: "Frame",
+//!         pinned: true,
+//!         plots: [(
+//!             key: (name: "frame_time", kind: Gauge),
+//!             config: Gauge(()),
+//!             baseline: Some(16.6),
+//!         )],
+//!     )],
+//!     accent: "teal",
+//! )"#;
+//! let layout = DashboardLayout::from_ron(ron).unwrap();
+//! let saved = DashboardLayout::from_ron(&layout.to_ron().unwrap()).unwrap();
+//! let window = &saved.windows[0];
+//! assert_eq!(saved.unknown_fields.get("accent").unwrap().get_ron(), r#""teal""#);
+//! assert_eq!(window.unknown_fields.get("pinned").unwrap().get_ron(), "true");
+//! let plot = &window.plots[0];
+//! assert_eq!(plot.unknown_fields.get("baseline").unwrap().get_ron(), "Some(16.6)");
+//! ```
+//!
+//! A [`LayoutFile`] keeps them when it saves the windows, for the windows and
+//! plots that are still there. It still refuses to overwrite a file of a
+//! newer version, whose known fields may have changed.
+
+use crate::{
+    display_names::DisplayNames,
+    overlay::OverlayCorner,
+    plot_style::PlotStyle,
+    plots::{MetricPlot, MetricPlotConfig},
+    registry::{DescriptionExtras, MetricAlias, MetricKey, MetricUnit, MetricsRegistry},
+    theme::{DashboardColor, DashboardTheme},
+    DashboardAnnotation, DashboardConfig, DashboardWindow, DashboardWindowConfig,
+};
+use bevy::{log::error, platform::collections::HashMap, prelude::*};
+use metrics_util::MetricKind;
+use ron::value::RawValue;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+#[cfg(all(feature = "autosave", feature = "bevy_egui"))]
+use crate::localization::{DashboardLocalization, UiText};
+
+/// The version of the layout format that this version of the crate writes.
+///
+/// | Version | Changes                                                   |
+/// |---------|-----------------------------------------------------------|
+/// | 1       | Files without a `version`.                                |
+/// | 2       | Plots' `style` is renamed to [`PlotLayout::style_override`]. |
+/// | 3       | Windows' and plots' `time_source` is replaced by [`PlotLayout::pause_with_virtual_time`]. |
+pub const LAYOUT_VERSION: u32 = 3;
+
+/// The serializable state of all [`DashboardWindow`]s.
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct DashboardLayout {
+    /// The version of the format. Always [`LAYOUT_VERSION`] once loaded,
+    /// since older layouts are upgraded.
+    pub version: u32,
+    #[allow(missing_docs)]
+    #[serde(serialize_with = "unknown_fields::serialize_seq")]
+    pub windows: Vec<WindowLayout>,
+    /// See [`DashboardConfig::display_names`].
+    #[serde(skip_serializing_if = "DisplayNames::is_empty")]
+    pub display_names: DisplayNames,
+    /// See [`DashboardConfig::plot_style`].
+    pub plot_style: PlotStyle,
+    /// See [`DashboardConfig::theme`].
+    pub theme: DashboardTheme,
+    /// See [`MetricsRegistry::aliases`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<AliasLayout>,
+    /// See [`MetricsRegistry::description_extras`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub description_extras: Vec<DescriptionExtrasLayout>,
+    #[allow(missing_docs)]
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// The fields of a layout struct that this version doesn't know, with their
+/// values as they were written, kept by [`DashboardLayout::from_ron`] to be
+/// written back by [`DashboardLayout::to_ron`].
+#[derive(Clone, Default, PartialEq)]
+pub struct UnknownFields(Vec<(String, Box<RawValue>)>);
+
+impl UnknownFields {
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value of the field `name`.
+    pub fn get(&self, name: &str) -> Option<&RawValue> {
+        self.0
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| &**value)
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.0.retain(|(field, _)| field != name);
+    }
+}
+
+/// The [`DescriptionExtras`] of the metric with this name and kind.
+#[allow(missing_docs)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct DescriptionExtrasLayout {
+    pub name: String,
+    #[serde(with = "MetricKindDef")]
+    pub kind: MetricKind,
+    pub extras: DescriptionExtras,
+}
+
+/// The serializable state of one [`MetricAlias`].
+#[allow(missing_docs)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct AliasLayout {
+    /// The name the metric is recorded with.
+    pub original_name: String,
+    pub name: String,
+    #[serde(default, with = "unit", skip_serializing_if = "Option::is_none")]
+    pub unit: Option<MetricUnit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// The serializable state of one [`DashboardWindow`].
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct WindowLayout {
+    /// Windows are matched to their layout by title.
+    pub title: String,
+    #[allow(missing_docs)]
+    pub config: DashboardWindowConfig,
+    /// Plots in the order they were added.
+    #[serde(serialize_with = "unknown_fields::serialize_seq")]
+    pub plots: Vec<PlotLayout>,
+    /// Scatter plots, difference plots and custom widgets, in the order
+    /// they're drawn.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub widgets: Vec<WidgetLayout>,
+    /// See [`DashboardWindow::annotations`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<AnnotationLayout>,
+    #[allow(missing_docs)]
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// The serializable state of one [`DashboardAnnotation`].
+///
+/// ```
+/// # use bevy_metrics_dashboard::layout::DashboardLayout;
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::widget::DashboardWidgetTypes;
+/// # use bevy_metrics_dashboard::DashboardWindow;
+/// let mut window = DashboardWindow::new("Window");
+/// window.annotate_plots("boss spawned", None);
+/// let layout = DashboardLayout {
+///     windows: vec![window.layout()],
+///     ..Default::default()
+/// };
+/// let loaded = DashboardLayout::from_ron(&layout.to_ron().unwrap()).unwrap();
+///
+/// let mut restored = DashboardWindow::new("Window");
+/// let widget_types = DashboardWidgetTypes::default();
+/// restored.apply_layout(&MetricsRegistry::new(), &widget_types, &loaded.windows[0]);
+/// assert_eq!(restored.annotations().next().unwrap().label, "boss spawned");
+/// ```
+#[allow(missing_docs)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct AnnotationLayout {
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<DashboardColor>,
+}
+
+impl From<&DashboardAnnotation> for AnnotationLayout {
+    fn from(annotation: &DashboardAnnotation) -> Self {
+        Self {
+            label: annotation.label.clone(),
+            color: annotation.color.map(Into::into),
+        }
+    }
+}
+
+impl From<&AnnotationLayout> for DashboardAnnotation {
+    fn from(layout: &AnnotationLayout) -> Self {
+        Self {
+            label: layout.label.clone(),
+            color: layout.color.map(Into::into),
+        }
+    }
+}
+
+/// The serializable state of one [`DashboardWidget`](crate::widget::DashboardWidget).
+///
+/// Built-in widgets like budget bars are saved with their settings:
+///
+/// ```
+/// # use bevy_metrics_dashboard::budget_bar::BudgetBarConfig;
+/// # use bevy_metrics_dashboard::layout::DashboardLayout;
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+/// # use bevy_metrics_dashboard::widget::DashboardWidgetTypes;
+/// # use bevy_metrics_dashboard::DashboardWindow;
+/// # use metrics::Key;
+/// let key = |name| MetricKey::new(Key::from_static_name(name), MetricKind::Gauge);
+/// let mut window = DashboardWindow::new("Frame");
+/// window.add_budget_bar(BudgetBarConfig {
+///     metrics: vec![key("update_ms"), key("render_ms")],
+///     budget: 8.3,
+///     ..Default::default()
+/// });
+/// let layout = DashboardLayout {
+///     windows: vec![window.layout()],
+///     ..Default::default()
+/// };
+/// let ron = layout.to_ron().unwrap();
+/// assert!(ron.contains("budget_bar") && ron.contains("render_ms"), "{ron}");
+///
+/// let loaded = DashboardLayout::from_ron(&ron).unwrap();
+/// let mut restored = DashboardWindow::new("Frame");
+/// let widget_types = DashboardWidgetTypes::default();
+/// restored.apply_layout(&MetricsRegistry::new(), &widget_types, &loaded.windows[0]);
+/// assert!(restored.layout().widgets == window.layout().widgets);
+/// ```
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct WidgetLayout {
+    /// The [`DashboardWidget::id`](crate::widget::DashboardWidget::id) of the
+    /// widget's type, which must be in the
+    /// [`DashboardConfig::widget_types`].
+    pub id: String,
+    /// See [`DashboardWidget::save_config`](crate::widget::DashboardWidget::save_config).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Box<RawValue>>,
+}
+
+/// The serializable state of one [`MetricPlot`](crate::plots::MetricPlot).
+#[allow(missing_docs)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct PlotLayout {
+    #[serde(with = "metric_key")]
+    pub key: MetricKey,
+    #[serde(default, with = "unit")]
+    pub unit: Option<MetricUnit>,
+    pub config: MetricPlotConfig,
+    /// See [`MetricPlot::display_names`](crate::plots::MetricPlot::display_names).
+    #[serde(default, skip_serializing_if = "DisplayNames::is_empty")]
+    pub display_names: DisplayNames,
+    /// See [`MetricPlot::style_override`](crate::plots::MetricPlot::style_override).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style_override: Option<PlotStyle>,
+    /// See [`MetricPlot::overlay`](crate::plots::MetricPlot::overlay).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<OverlayCorner>,
+    /// See [`MetricPlot::note`](crate::plots::MetricPlot::note).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// See [`MetricPlot::group`](crate::plots::MetricPlot::group).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// See [`MetricPlot::pause_with_virtual_time`](crate::plots::MetricPlot::pause_with_virtual_time).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pause_with_virtual_time: Option<bool>,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
+}
+
+impl From<&MetricPlot> for PlotLayout {
+    fn from(plot: &MetricPlot) -> Self {
+        Self {
+            key: plot.key().clone(),
+            unit: plot.unit().cloned(),
+            config: plot.clone_config(),
+            display_names: plot.display_names().clone(),
+            style_override: plot.style_override().cloned(),
+            overlay: plot.overlay(),
+            note: plot.note().to_owned(),
+            group: plot.group().map(str::to_owned),
+            pause_with_virtual_time: plot.pause_with_virtual_time(),
+            unknown_fields: default(),
+        }
+    }
+}
+
+impl PlotLayout {
+    /// Whether `plot` can be kept for this layout, keeping its history.
+    pub(crate) fn matches(&self, plot: &MetricPlot) -> bool {
+        plot.key() == &self.key
+            && plot.unit() == self.unit.as_ref()
+            && plot.clone_config() == self.config
+    }
+
+    /// Set everything but the key, unit and config of `plot`.
+    pub(crate) fn apply_settings(&self, plot: &mut MetricPlot) {
+        plot.set_display_names(self.display_names.clone());
+        plot.set_style_override(self.style_override.clone());
+        plot.set_overlay(self.overlay);
+        plot.set_note(self.note.clone());
+        plot.set_group(self.group.clone());
+        plot.set_pause_with_virtual_time(self.pause_with_virtual_time);
+    }
+}
+
+impl Default for DashboardLayout {
+    fn default() -> Self {
+        Self {
+            version: LAYOUT_VERSION,
+            windows: default(),
+            display_names: default(),
+            plot_style: default(),
+            theme: default(),
+            aliases: default(),
+            description_extras: default(),
+            unknown_fields: default(),
+        }
+    }
+}
+
+/// Only the version of a layout file, to choose how to parse the rest.
+#[derive(Deserialize)]
+struct LayoutHeader {
+    /// Files written before layouts were versioned don't have one.
+    #[serde(default = "first_version")]
+    version: u32,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+impl LayoutHeader {
+    /// The version of the layout file at `path`, if it exists and has a
+    /// valid header.
+    fn of_file(path: &Path) -> Option<u32> {
+        let ron = std::fs::read_to_string(path).ok()?;
+        ron::from_str::<Self>(&ron)
+            .ok()
+            .map(|header| header.version)
+    }
+}
+
+impl DashboardLayout {
+    /// Capture the layout of `windows`, the global display names, plot style
+    /// and theme in `config`,
+    /// and the aliases and description extras in `registry`.
+    pub fn from_windows<'a>(
+        registry: &MetricsRegistry,
+        config: &DashboardConfig,
+        windows: impl IntoIterator<Item = &'a DashboardWindow>,
+    ) -> Self {
+        Self {
+            version: LAYOUT_VERSION,
+            windows: windows.into_iter().map(DashboardWindow::layout).collect(),
+            display_names: config.display_names.clone(),
+            plot_style: config.plot_style.clone(),
+            theme: config.theme,
+            aliases: registry
+                .aliases()
+                .into_iter()
+                .map(|(original_name, alias)| AliasLayout {
+                    original_name: original_name.as_str().to_owned(),
+                    name: alias.name,
+                    unit: alias.unit,
+                    description: alias.description,
+                })
+                .collect(),
+            description_extras: registry
+                .description_extras()
+                .into_iter()
+                .map(|(key, extras)| DescriptionExtrasLayout {
+                    name: key.name.as_str().to_owned(),
+                    kind: key.kind,
+                    extras,
+                })
+                .collect(),
+            unknown_fields: default(),
+        }
+    }
+
+    /// Parse a layout from RON, upgrading it if it has an older version.
+    pub fn from_ron(ron: &str) -> Result<Self, LayoutError> {
+        let LayoutHeader { version } = ron::from_str(ron)?;
+        match version {
+            1 => {
+                let mut layout = ron::from_str::<v1::DashboardLayout>(ron)?.upgrade();
+                v2::upgrade(&mut layout, ron)?;
+                Ok(layout)
+            }
+            2 => {
+                let mut layout = ron::from_str(ron)?;
+                unknown_fields::restore(&mut layout, ron)?;
+                v2::upgrade(&mut layout, ron)?;
+                Ok(layout)
+            }
+            LAYOUT_VERSION => {
+                let mut layout = ron::from_str(ron)?;
+                unknown_fields::restore(&mut layout, ron)?;
+                Ok(layout)
+            }
+            _ => Err(LayoutError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Serialize this layout as pretty-printed RON.
+    pub fn to_ron(&self) -> Result<String, LayoutError> {
+        let ron = ron::ser::to_string_pretty(self, default())?;
+        Ok(unknown_fields::append(ron, &self.unknown_fields))
+    }
+
+    /// Copy the [`UnknownFields`] of `earlier`, of its windows with the same
+    /// titles as windows of this layout, and of their plots of the same
+    /// metrics, e.g. to keep those of a loaded layout when saving the
+    /// windows it was applied to.
+    pub fn keep_unknown_fields(&mut self, earlier: &DashboardLayout) {
+        self.unknown_fields = earlier.unknown_fields.clone();
+        for window in &mut self.windows {
+            let Some(earlier) = earlier.windows.iter().find(|w| w.title == window.title) else {
+                continue;
+            };
+            window.unknown_fields = earlier.unknown_fields.clone();
+            for plot in &mut window.plots {
+                if let Some(earlier) = earlier.plots.iter().find(|p| p.key == plot.key) {
+                    plot.unknown_fields = earlier.unknown_fields.clone();
+                }
+            }
+        }
+    }
+
+    /// Apply this layout to the existing `windows`, which are matched by title,
+    /// and replace the global display names, plot style and theme in `config`.
+    ///
+    /// The aliases and description extras are added to `registry`, replacing
+    /// those of the same metrics. Others are kept, e.g. those added by the app
+    /// on startup.
+    ///
+    /// Windows missing from the world are spawned, marked with
+    /// [`SpawnedByLayout`]. Those windows are despawned again when a layout
+    /// without them is applied, while windows spawned by the app are kept,
+    /// and only updated when the layout has a window with their title. See
+    /// [`DashboardWindow::apply_layout`] for how plots are reconciled.
+    ///
+    /// ```
+    /// # use bevy::{ecs::system::RunSystemOnce, prelude::*};
+    /// # use bevy_metrics_dashboard::layout::{DashboardLayout, SpawnedByLayout};
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use bevy_metrics_dashboard::{DashboardConfig, DashboardWindow};
+    /// let mut world = World::new();
+    /// world.insert_resource(MetricsRegistry::new());
+    /// world.init_resource::<DashboardConfig>();
+    /// world.spawn(DashboardWindow::new("Mine"));
+    ///
+    /// let apply = |world: &mut World, ron: &str| {
+    ///     let layout = DashboardLayout::from_ron(ron).unwrap();
+    ///     world
+    ///         .run_system_once(
+    ///             move |mut commands: Commands,
+    ///                   registry: Res<MetricsRegistry>,
+    ///                   mut config: ResMut<DashboardConfig>,
+    ///                   mut windows: Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>| {
+    ///                 layout.apply(&mut commands, &registry, &mut config, &mut windows);
+    ///             },
+    ///         )
+    ///         .unwrap();
+    ///     let mut windows: Vec<_> = world
+    ///         .query::<&DashboardWindow>()
+    ///         .iter(world)
+    ///         .map(|w| {
+    ///             let plots: Vec<_> = w.plots().iter().map(|p| p.key().key.name().to_owned()).collect();
+    ///             (w.title().to_owned(), plots)
+    ///         })
+    ///         .collect();
+    ///     windows.sort();
+    ///     windows
+    /// };
+    ///
+    /// let windows = apply(
+    ///     &mut world,
+    ///     r#"(version: 3, windows: [
+    ///         (title: "Physics", plots: [(key: (name: "bodies", kind: Gauge), config: Gauge(()))]),
+    ///         (title: "Audio"),
+    ///     ])"#,
+    /// );
+    /// assert_eq!(windows, [
+    ///     ("Audio".into(), vec![]),
+    ///     ("Mine".into(), vec![]),
+    ///     ("Physics".into(), vec!["bodies".to_owned()]),
+    /// ]);
+    ///
+    /// // Only the windows spawned by the previous layout are despawned, and
+    /// // the app's window is updated because its title is in the layout.
+    /// let windows = apply(
+    ///     &mut world,
+    ///     r#"(version: 3, windows: [
+    ///         (title: "Mine", plots: [(key: (name: "voices", kind: Gauge), config: Gauge(()))]),
+    ///     ])"#,
+    /// );
+    /// assert_eq!(windows, [("Mine".into(), vec!["voices".to_owned()])]);
+    ///
+    /// // Windows spawned by the app are kept even when they're missing.
+    /// let windows = apply(&mut world, "(version: 3, windows: [])");
+    /// assert_eq!(windows, [("Mine".into(), vec!["voices".to_owned()])]);
+    /// ```
+    pub fn apply(
+        &self,
+        commands: &mut Commands,
+        registry: &MetricsRegistry,
+        config: &mut DashboardConfig,
+        windows: &mut Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>,
+    ) {
+        if config.display_names != self.display_names {
+            config.display_names = self.display_names.clone();
+        }
+        if config.plot_style != self.plot_style {
+            config.plot_style = self.plot_style.clone();
+        }
+        if config.theme != self.theme {
+            config.theme = self.theme;
+        }
+        for alias in &self.aliases {
+            registry.set_alias(
+                alias.original_name.clone(),
+                MetricAlias {
+                    name: alias.name.clone(),
+                    unit: alias.unit.clone(),
+                    description: alias.description.clone(),
+                },
+            );
+        }
+        for extras in &self.description_extras {
+            registry.set_description_extras(
+                extras.name.clone(),
+                extras.kind,
+                extras.extras.clone(),
+            );
+        }
+        let mut layouts: HashMap<&str, &WindowLayout> =
+            self.windows.iter().map(|w| (w.title.as_str(), w)).collect();
+        for (entity, mut window, spawned_by_layout) in windows.iter_mut() {
+            if let Some(layout) = layouts.remove(window.title()) {
+                window.apply_layout(registry, &config.widget_types, layout);
+            } else if spawned_by_layout {
+                commands.entity(entity).despawn();
+            }
+        }
+        // Preserve the order of windows in the file.
+        for layout in &self.windows {
+            if layouts.remove(layout.title.as_str()).is_some() {
+                let mut window = DashboardWindow::new(&layout.title);
+                window.apply_layout(registry, &config.widget_types, layout);
+                commands.spawn((window, SpawnedByLayout));
+            }
+        }
+    }
+}
+
+/// Marks the [`DashboardWindow`]s spawned by [`DashboardLayout::apply`].
+/// See its docs.
+#[derive(Clone, Component, Copy, Debug, Default)]
+pub struct SpawnedByLayout;
+
+/// Error while saving or loading a [`DashboardLayout`].
+#[derive(Debug)]
+pub enum LayoutError {
+    #[allow(missing_docs)]
+    Io(io::Error),
+    #[allow(missing_docs)]
+    Parse(ron::error::SpannedError),
+    #[allow(missing_docs)]
+    Serialize(ron::Error),
+    /// The file has a version that this version of the crate doesn't know,
+    /// e.g. because it was saved by a newer version of the app.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "layout file I/O failed: {e}"),
+            Self::Parse(e) => write!(f, "failed to parse layout: {e}"),
+            Self::Serialize(e) => write!(f, "failed to serialize layout: {e}"),
+            Self::UnsupportedVersion(version) if *version > LAYOUT_VERSION => write!(
+                f,
+                "layout version {version} is newer than the supported version \
+                 {LAYOUT_VERSION}, and can only be loaded or saved by a newer version"
+            ),
+            Self::UnsupportedVersion(version) => write!(f, "unknown layout version {version}"),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl From<io::Error> for LayoutError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for LayoutError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<ron::Error> for LayoutError {
+    fn from(e: ron::Error) -> Self {
+        Self::Serialize(e)
+    }
+}
+
+/// Event used to save the layout of all [`DashboardWindow`]s to the
+/// [`LayoutFile`].
+#[derive(Clone, Event)]
+pub struct SaveLayout;
+
+/// A RON file containing a [`DashboardLayout`].
+///
+/// The layout is applied on startup, and can be saved with [`SaveLayout`].
+/// When watching is enabled, the file is re-applied whenever it changes on
+/// disk. A malformed file logs an error and the current layout is kept.
+///
+/// Saving writes a temporary file next to it first, so a crash while saving
+/// leaves the previous layout intact.
+#[derive(Clone, Resource)]
+pub struct LayoutFile {
+    path: PathBuf,
+    watch_timer: Option<Timer>,
+    last_modified: Option<SystemTime>,
+    last_layout: Option<DashboardLayout>,
+}
+
+impl LayoutFile {
+    /// Use the layout file at `path`, without watching for changes.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            watch_timer: None,
+            last_modified: None,
+            last_layout: None,
+        }
+    }
+
+    /// Check the modification time of the file every `poll_period`, and
+    /// re-apply it when it changes.
+    pub fn watch(mut self, poll_period: Duration) -> Self {
+        self.watch_timer = Some(Timer::new(poll_period, TimerMode::Repeating));
+        self
+    }
+
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    /// The layout that was last loaded from or saved to the file.
+    pub fn last_layout(&self) -> Option<&DashboardLayout> {
+        self.last_layout.as_ref()
+    }
+
+    /// Read and parse the file.
+    pub fn load(&mut self) -> Result<DashboardLayout, LayoutError> {
+        self.last_modified = self.modified();
+        let ron = std::fs::read_to_string(&self.path)?;
+        let layout = DashboardLayout::from_ron(&ron)?;
+        self.last_layout = Some(layout.clone());
+        Ok(layout)
+    }
+
+    /// Write `layout` to the file.
+    ///
+    /// Fails if the file has a newer version, which may have settings that
+    /// would be lost.
+    pub fn save(&mut self, layout: &DashboardLayout) -> Result<(), LayoutError> {
+        write_layout(&self.path, layout)?;
+        // Don't re-apply our own changes.
+        self.last_modified = self.modified();
+        self.last_layout = Some(layout.clone());
+        Ok(())
+    }
+
+    fn load_and_apply(
+        &mut self,
+        commands: &mut Commands,
+        registry: &MetricsRegistry,
+        config: &mut DashboardConfig,
+        windows: &mut Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>,
+    ) {
+        match self.load() {
+            Ok(layout) => layout.apply(commands, registry, config, windows),
+            Err(e) => error!("{}: {e}", self.path.display()),
+        }
+    }
+
+    /// Bevy system that applies the layout file, if it exists.
+    pub fn apply_on_startup(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        mut config: ResMut<DashboardConfig>,
+        mut file: ResMut<Self>,
+        mut windows: Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>,
+    ) {
+        if file.path.exists() {
+            file.load_and_apply(&mut commands, &registry, &mut config, &mut windows);
+        }
+    }
+
+    /// Bevy system that re-applies the layout file when it changes.
+    pub fn apply_on_change(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        mut config: ResMut<DashboardConfig>,
+        time: Res<Time<Real>>,
+        mut file: ResMut<Self>,
+        mut windows: Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>,
+    ) {
+        let Some(timer) = &mut file.watch_timer else {
+            return;
+        };
+        if !timer.tick(time.delta()).just_finished() {
+            return;
+        }
+        let modified = file.modified();
+        if modified.is_some() && modified != file.last_modified {
+            file.load_and_apply(&mut commands, &registry, &mut config, &mut windows);
+        }
+    }
+
+    /// Bevy system that handles [`SaveLayout`] events.
+    ///
+    /// They're ignored while the whole dashboard is
+    /// [read-only](crate::ReadOnly::All).
+    pub fn save_on_request(
+        mut requests: EventReader<SaveLayout>,
+        registry: Res<MetricsRegistry>,
+        config: Res<DashboardConfig>,
+        mut file: ResMut<Self>,
+        windows: Query<&DashboardWindow>,
+    ) {
+        if requests.read().count() == 0 || config.read_only.is_all() {
+            return;
+        }
+        let mut layout = DashboardLayout::from_windows(&registry, &config, windows);
+        if let Some(last_layout) = file.last_layout() {
+            layout.keep_unknown_fields(last_layout);
+        }
+        if let Err(e) = file.save(&layout) {
+            error!("{}: {e}", file.path.display());
+        }
+    }
+}
+
+/// Write `layout` to `path`, unless it has a layout of a newer version.
+fn write_layout(path: &Path, layout: &DashboardLayout) -> Result<(), LayoutError> {
+    if let Some(version) = LayoutHeader::of_file(path).filter(|&v| v > LAYOUT_VERSION) {
+        return Err(LayoutError::UnsupportedVersion(version));
+    }
+    Ok(write_atomically(path, &layout.to_ron()?)?)
+}
+
+/// Write `contents` to a temporary file next to `path`, then rename it over
+/// `path`, so readers never see a partially written file.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp_path, path)
+}
+
+/// Periodically saves the [`DashboardLayout`] to a separate file while it
+/// has changed, and when the app exits, so that a crash doesn't lose the
+/// layout.
+///
+/// Add it with
+/// [`DashboardPlugin::layout_autosave`](crate::DashboardPlugin::layout_autosave).
+/// On startup, if the autosave is newer than the [`LayoutFile`], or there
+/// is no layout file, the dashboard asks whether to restore it. The
+/// toolbar marks the "Save Layout" button while the layout differs from
+/// the one last loaded from or saved to the layout file.
+///
+/// Saves are atomic: the layout is written to a temporary file that is then
+/// renamed over the autosave, so a crash or a failed write leaves the
+/// previous autosave intact.
+///
+/// ```
+/// # use bevy_metrics_dashboard::layout::{DashboardLayout, LayoutAutosave};
+/// # use bevy_metrics_dashboard::DashboardWindow;
+/// let dir = std::env::temp_dir().join(format!("autosave-doctest-{}", std::process::id()));
+/// let mut autosave = LayoutAutosave::new(dir.join("autosave.ron"));
+/// let layout = DashboardLayout {
+///     windows: vec![DashboardWindow::new("Frame").layout()],
+///     ..Default::default()
+/// };
+/// autosave.save(layout.clone()).unwrap();
+/// assert!(autosave.load().unwrap() == layout);
+///
+/// // A crash while writing leaves a partial temporary file, not a partial
+/// // autosave.
+/// let tmp = dir.join("autosave.ron.tmp");
+/// std::fs::write(&tmp, "(version: 3, windows: [(tit").unwrap();
+/// assert!(autosave.load().unwrap() == layout);
+///
+/// // So does a write that fails.
+/// std::fs::remove_file(&tmp).unwrap();
+/// std::fs::create_dir(&tmp).unwrap();
+/// assert!(autosave.save(DashboardLayout::default()).is_err());
+/// assert!(autosave.load().unwrap() == layout);
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[cfg(feature = "autosave")]
+#[derive(Clone, Resource)]
+pub struct LayoutAutosave {
+    path: PathBuf,
+    period: Duration,
+    check_timer: Timer,
+    since_save: Duration,
+    /// The contents of the autosave file.
+    saved: Option<DashboardLayout>,
+    unsaved_changes: bool,
+    pending_restore: Option<DashboardLayout>,
+}
+
+/// The default [`LayoutAutosave::period`].
+#[cfg(feature = "autosave")]
+pub const DEFAULT_AUTOSAVE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How often the layout is compared with the saved ones.
+#[cfg(feature = "autosave")]
+const CHANGE_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+#[cfg(feature = "autosave")]
+impl LayoutAutosave {
+    /// Autosave to the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            period: DEFAULT_AUTOSAVE_PERIOD,
+            check_timer: Timer::new(CHANGE_CHECK_PERIOD, TimerMode::Repeating),
+            since_save: Duration::ZERO,
+            saved: None,
+            unsaved_changes: false,
+            pending_restore: None,
+        }
+    }
+
+    /// Autosave to `dashboard_autosave.ron` in the app's per-user config
+    /// directory, as given by [`ProjectDirs::from`](directories::ProjectDirs::from)
+    /// for these arguments, e.g. `~/.config/barapp` on Linux and
+    /// `~/Library/Application Support/com.Foo-Corp.Bar-App` on macOS for
+    /// `("com", "Foo Corp", "Bar App")`.
+    ///
+    /// Returns `None` if the platform has no config directory, e.g. on the
+    /// web.
+    pub fn in_config_dir(qualifier: &str, organization: &str, application: &str) -> Option<Self> {
+        let dirs = directories::ProjectDirs::from(qualifier, organization, application)?;
+        Some(Self::new(dirs.config_dir().join("dashboard_autosave.ron")))
+    }
+
+    /// Save at most once per `period`, and only when the layout changed.
+    pub fn period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// The path of the autosave file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the layout differs from the one last loaded from or saved to
+    /// the [`LayoutFile`]. Updated about once per second.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved_changes
+    }
+
+    /// The autosaved layout that the dashboard offers to restore, until the
+    /// offer is accepted or discarded.
+    pub fn pending_restore(&self) -> Option<&DashboardLayout> {
+        self.pending_restore.as_ref()
+    }
+
+    /// Apply the [pending](Self::pending_restore) autosaved layout.
+    pub fn restore(
+        &mut self,
+        commands: &mut Commands,
+        registry: &MetricsRegistry,
+        config: &mut DashboardConfig,
+        windows: &mut Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>,
+    ) {
+        if let Some(layout) = self.pending_restore.take() {
+            layout.apply(commands, registry, config, windows);
+        }
+    }
+
+    /// Drop the [pending](Self::pending_restore) autosaved layout, and
+    /// delete the autosave file.
+    pub fn discard(&mut self) {
+        if self.pending_restore.take().is_none() {
+            return;
+        }
+        self.saved = None;
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            error!("{}: {e}", self.path.display());
+        }
+    }
+
+    /// Read and parse the autosave file.
+    pub fn load(&self) -> Result<DashboardLayout, LayoutError> {
+        DashboardLayout::from_ron(&std::fs::read_to_string(&self.path)?)
+    }
+
+    /// Write `layout` to the autosave file, creating its directory, unless
+    /// it's the layout that was last saved.
+    pub fn save(&mut self, layout: DashboardLayout) -> Result<(), LayoutError> {
+        self.since_save = Duration::ZERO;
+        if self.saved.as_ref() == Some(&layout) {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_layout(&self.path, &layout)?;
+        self.saved = Some(layout);
+        Ok(())
+    }
+
+    /// Bevy system that offers to restore the autosave file, if it's newer
+    /// than the [`LayoutFile`]. Runs after the layout file is applied.
+    pub fn check_on_startup(
+        mut autosave: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        config: Res<DashboardConfig>,
+        file: Option<Res<LayoutFile>>,
+        windows: Query<&DashboardWindow>,
+    ) {
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let Some(autosaved) = modified(&autosave.path) else {
+            return;
+        };
+        if file
+            .and_then(|file| modified(&file.path))
+            .is_some_and(|saved| saved >= autosaved)
+        {
+            return;
+        }
+        let layout = match autosave.load() {
+            Ok(layout) => layout,
+            Err(e) => {
+                error!("{}: {e}", autosave.path.display());
+                return;
+            }
+        };
+        autosave.saved = Some(layout.clone());
+        if layout != DashboardLayout::from_windows(&registry, &config, windows) {
+            autosave.pending_restore = Some(layout);
+        }
+    }
+
+    /// Bevy system that saves the layout every [`Self::period`] while it
+    /// changes, and when [`AppExit`] is sent.
+    ///
+    /// Nothing is saved while a restore is [pending](Self::pending_restore),
+    /// to keep the autosave until the offer is answered.
+    pub fn autosave(
+        mut autosave: ResMut<Self>,
+        mut exits: EventReader<AppExit>,
+        registry: Res<MetricsRegistry>,
+        config: Res<DashboardConfig>,
+        time: Res<Time<Real>>,
+        file: Option<Res<LayoutFile>>,
+        windows: Query<&DashboardWindow>,
+    ) {
+        let exiting = exits.read().count() > 0;
+        autosave.since_save += time.delta();
+        if !autosave.check_timer.tick(time.delta()).just_finished() && !exiting {
+            return;
+        }
+        let layout = DashboardLayout::from_windows(&registry, &config, windows);
+        let unsaved = file.is_some_and(|file| file.last_layout() != Some(&layout));
+        if autosave.unsaved_changes != unsaved {
+            autosave.unsaved_changes = unsaved;
+        }
+        if autosave.pending_restore.is_some() {
+            return;
+        }
+        if exiting || autosave.since_save >= autosave.period {
+            if let Err(e) = autosave.save(layout) {
+                error!("{}: {e}", autosave.path.display());
+            }
+        }
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// Bevy system that asks whether to restore the
+    /// [pending](Self::pending_restore) autosaved layout.
+    pub(crate) fn draw_restore_prompt(
+        mut commands: Commands,
+        mut autosave: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        mut config: ResMut<DashboardConfig>,
+        localization: Res<DashboardLocalization>,
+        mut ctxts: bevy_egui::EguiContexts,
+        mut windows: Query<(Entity, &mut DashboardWindow, Has<SpawnedByLayout>)>,
+    ) {
+        use crate::egui;
+
+        if autosave.pending_restore.is_none() {
+            return;
+        }
+        let ctxt = ctxts.ctx_mut();
+        let mut answer = None;
+        localization.scope(ctxt, |ctxt| {
+            config.theme.scope(ctxt, |ctxt| {
+                let l10n = &*localization;
+                egui::Window::new(l10n.get(UiText::RestoreLayoutTitle))
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                    .show(ctxt, |ui| {
+                        ui.label(l10n.get(UiText::RestoreLayoutPrompt));
+                        ui.horizontal(|ui| {
+                            if ui.button(l10n.get(UiText::Restore)).clicked() {
+                                answer = Some(true);
+                            }
+                            if ui.button(l10n.get(UiText::Discard)).clicked() {
+                                answer = Some(false);
+                            }
+                        });
+                    });
+            })
+        });
+        match answer {
+            Some(true) => autosave.restore(&mut commands, &registry, &mut config, &mut windows),
+            Some(false) => autosave.discard(),
+            None => {}
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "metrics_util::MetricKind")]
+pub(crate) enum MetricKindDef {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+mod metric_key {
+    use super::MetricKindDef;
+    use crate::registry::MetricKey;
+    use metrics_util::MetricKind;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize, Serialize)]
+    struct Repr {
+        name: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        labels: Vec<(String, String)>,
+        #[serde(with = "MetricKindDef")]
+        kind: MetricKind,
+    }
+
+    pub fn serialize<S: Serializer>(key: &MetricKey, serializer: S) -> Result<S::Ok, S::Error> {
+        // Sorted, so the file doesn't change when a run adds the labels in a
+        // different order.
+        Repr {
+            name: key.key.name().to_owned(),
+            labels: key
+                .normalized()
+                .key
+                .labels()
+                .map(|l| (l.key().to_owned(), l.value().to_owned()))
+                .collect(),
+            kind: key.kind,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<MetricKey, D::Error> {
+        let Repr { name, labels, kind } = Repr::deserialize(deserializer)?;
+        let labels: Vec<_> = labels
+            .into_iter()
+            .map(|(k, v)| metrics::Label::new(k, v))
+            .collect();
+        Ok(MetricKey::new(metrics::Key::from_parts(name, labels), kind))
+    }
+}
+
+/// Like [`metric_key`], for the optional operands of scatter and difference
+/// plots.
+pub(crate) mod optional_metric_key {
+    use crate::registry::MetricKey;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize, Serialize)]
+    struct Repr(#[serde(with = "super::metric_key")] MetricKey);
+
+    pub fn serialize<S: Serializer>(
+        key: &Option<MetricKey>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        key.clone().map(Repr).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<MetricKey>, D::Error> {
+        Ok(Option::<Repr>::deserialize(deserializer)?.map(|Repr(key)| key))
+    }
+}
+
+/// Like [`metric_key`], for the metrics of budget bars.
+pub(crate) mod metric_keys {
+    use crate::registry::MetricKey;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize, Serialize)]
+    struct Repr(#[serde(with = "super::metric_key")] MetricKey);
+
+    pub fn serialize<S: Serializer>(keys: &[MetricKey], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().cloned().map(Repr))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<MetricKey>, D::Error> {
+        let keys = Vec::<Repr>::deserialize(deserializer)?;
+        Ok(keys.into_iter().map(|Repr(key)| key).collect())
+    }
+}
+
+/// Standard units are written with their `metrics` name, like
+/// `"milliseconds"`. Any other string is a custom unit.
+pub(crate) mod unit {
+    use crate::registry::MetricUnit;
+    use metrics::Unit;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        unit: &Option<MetricUnit>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match unit {
+            Some(MetricUnit::Standard(unit)) => serializer.serialize_some(unit.as_str()),
+            Some(MetricUnit::Custom(unit)) => serializer.serialize_some(unit),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<MetricUnit>, D::Error> {
+        let unit = Option::<String>::deserialize(deserializer)?;
+        Ok(unit.map(|s| match Unit::from_string(&s) {
+            Some(unit) => MetricUnit::Standard(unit),
+            None => MetricUnit::Custom(s),
+        }))
+    }
+}
+
+/// Finds the fields of layout structs that this version doesn't know, and
+/// writes them back.
+///
+/// RON can't deserialize structs with `#[serde(flatten)]` fields from struct
+/// syntax, so layouts are parsed a second time for their unknown fields.
+mod unknown_fields {
+    use super::{DashboardLayout, PlotLayout, UnknownFields, WindowLayout};
+    use ron::value::RawValue;
+    use serde::{
+        de::{self, DeserializeOwned, IgnoredAny, MapAccess, Visitor},
+        forward_to_deserialize_any, ser, Deserialize, Deserializer, Serialize, Serializer,
+    };
+    use std::{fmt, marker::PhantomData};
+
+    pub trait HasUnknownFields {
+        fn unknown_fields(&self) -> &UnknownFields;
+    }
+
+    impl HasUnknownFields for WindowLayout {
+        fn unknown_fields(&self) -> &UnknownFields {
+            &self.unknown_fields
+        }
+    }
+
+    impl HasUnknownFields for PlotLayout {
+        fn unknown_fields(&self) -> &UnknownFields {
+            &self.unknown_fields
+        }
+    }
+
+    /// Set the unknown fields of `layout`, its windows and their plots from
+    /// `ron`, which `layout` was parsed from.
+    pub fn restore(
+        layout: &mut DashboardLayout,
+        ron: &str,
+    ) -> Result<(), ron::error::SpannedError> {
+        let LayoutFields(found) = ron::from_str(ron)?;
+        layout.unknown_fields = found.fields;
+        for (window, WindowFields(found)) in layout.windows.iter_mut().zip(found.nested) {
+            window.unknown_fields = found.fields;
+            for (plot, PlotFields(found)) in window.plots.iter_mut().zip(found.nested) {
+                plot.unknown_fields = found.fields;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `fields` to the end of the struct serialized as `ron`.
+    pub fn append(ron: String, fields: &UnknownFields) -> String {
+        if fields.is_empty() {
+            return ron;
+        }
+        let Some(head) = ron.trim_end().strip_suffix(')') else {
+            return ron;
+        };
+        let pretty = head.contains('\n');
+        let mut ron = head.trim_end().to_owned();
+        for (name, value) in &fields.0 {
+            if !ron.ends_with(['(', ',']) {
+                ron.push(',');
+            }
+            if pretty {
+                ron.push_str(&format!("\n    {name}: {}", value.get_ron()));
+            } else {
+                ron.push_str(&format!("{name}:{}", value.get_ron()));
+            }
+        }
+        ron.push_str(if pretty { ",\n)" } else { ")" });
+        ron
+    }
+
+    /// Serialize `items` with their unknown fields.
+    pub fn serialize_seq<T, S>(items: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: HasUnknownFields + Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(items.iter().map(WithUnknownFields))
+    }
+
+    struct WithUnknownFields<'a, T>(&'a T);
+
+    impl<T: HasUnknownFields + Serialize> Serialize for WithUnknownFields<'_, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let fields = self.0.unknown_fields();
+            if fields.is_empty() {
+                return self.0.serialize(serializer);
+            }
+            let ron = ron::to_string(self.0).map_err(ser::Error::custom)?;
+            RawValue::from_boxed_ron(append(ron, fields).into_boxed_str())
+                .map_err(ser::Error::custom)?
+                .serialize(serializer)
+        }
+    }
+
+    /// The unknown fields of a struct, and of the structs in its field
+    /// that holds nested layouts.
+    struct Found<N> {
+        fields: UnknownFields,
+        nested: Vec<N>,
+    }
+
+    struct LayoutFields(Found<WindowFields>);
+    struct WindowFields(Found<PlotFields>);
+    struct PlotFields(Found<IgnoredAny>);
+
+    impl<'de> Deserialize<'de> for LayoutFields {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            find::<DashboardLayout, _, _>(deserializer, "windows").map(Self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for WindowFields {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            find::<WindowLayout, _, _>(deserializer, "plots").map(Self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PlotFields {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            find::<PlotLayout, _, _>(deserializer, "").map(Self)
+        }
+    }
+
+    /// Find the fields unknown to `T`, and those of the structs in its field
+    /// `nested`.
+    fn find<'de, T, N, D>(deserializer: D, nested: &'static str) -> Result<Found<N>, D::Error>
+    where
+        T: DeserializeOwned,
+        N: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let Err(StructProbe { name, fields }) = T::deserialize(StructProbe::default()) else {
+            unreachable!("layouts are structs");
+        };
+        let visitor = FieldsVisitor {
+            known: fields,
+            nested,
+            marker: PhantomData,
+        };
+        deserializer.deserialize_struct(name, fields, visitor)
+    }
+
+    struct FieldsVisitor<N> {
+        known: &'static [&'static str],
+        nested: &'static str,
+        marker: PhantomData<N>,
+    }
+
+    impl<'de, N: Deserialize<'de>> Visitor<'de> for FieldsVisitor<N> {
+        type Value = Found<N>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a struct")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Found<N>, A::Error> {
+            let mut found = Found {
+                fields: UnknownFields::default(),
+                nested: Vec::new(),
+            };
+            while let Some(name) = map.next_key::<String>()? {
+                if name == self.nested {
+                    found.nested = map.next_value()?;
+                } else if self.known.contains(&name.as_str()) {
+                    map.next_value::<IgnoredAny>()?;
+                } else {
+                    let value = map.next_value::<Box<RawValue>>()?;
+                    found.fields.0.push((name, value.trim_boxed()));
+                }
+            }
+            Ok(found)
+        }
+    }
+
+    /// Fails to deserialize any struct with the struct's name and fields, to
+    /// find the fields that its derived `Deserialize` knows.
+    #[derive(Debug, Default)]
+    struct StructProbe {
+        name: &'static str,
+        fields: &'static [&'static str],
+    }
+
+    impl fmt::Display for StructProbe {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "struct {}", self.name)
+        }
+    }
+
+    impl std::error::Error for StructProbe {}
+
+    impl de::Error for StructProbe {
+        fn custom<T: fmt::Display>(_msg: T) -> Self {
+            Self::default()
+        }
+    }
+
+    impl<'de> Deserializer<'de> for StructProbe {
+        type Error = Self;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self> {
+            Err(self)
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self> {
+            Err(Self { name, fields })
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+}
+
+/// Layouts of version 1, where [`PlotLayout::style_override`] was called
+/// `style`.
+///
+/// Only the structs that changed since are copied here. They're upgraded to
+/// version 2, and then through [`v2`].
+mod v1 {
+    use super::{metric_key, unit, AliasLayout, DescriptionExtrasLayout, WidgetLayout};
+    use crate::{
+        display_names::DisplayNames,
+        overlay::OverlayCorner,
+        plot_style::PlotStyle,
+        plots::MetricPlotConfig,
+        registry::{MetricKey, MetricUnit},
+        theme::DashboardTheme,
+        DashboardWindowConfig,
+    };
+    use bevy::prelude::default;
+    use serde::Deserialize;
+
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    pub struct DashboardLayout {
+        windows: Vec<WindowLayout>,
+        display_names: DisplayNames,
+        plot_style: PlotStyle,
+        theme: DashboardTheme,
+        aliases: Vec<AliasLayout>,
+        description_extras: Vec<DescriptionExtrasLayout>,
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct WindowLayout {
+        title: String,
+        config: DashboardWindowConfig,
+        plots: Vec<PlotLayout>,
+        widgets: Vec<WidgetLayout>,
+    }
+
+    #[derive(Deserialize)]
+    struct PlotLayout {
+        #[serde(with = "metric_key")]
+        key: MetricKey,
+        #[serde(default, with = "unit")]
+        unit: Option<MetricUnit>,
+        config: MetricPlotConfig,
+        #[serde(default)]
+        display_names: DisplayNames,
+        #[serde(default)]
+        style: Option<PlotStyle>,
+        #[serde(default)]
+        overlay: Option<OverlayCorner>,
+        #[serde(default)]
+        note: String,
+        #[serde(default)]
+        group: Option<String>,
+    }
+
+    impl DashboardLayout {
+        /// Upgrade to version 2.
+        pub fn upgrade(self) -> super::DashboardLayout {
+            super::DashboardLayout {
+                version: 2,
+                windows: self
+                    .windows
+                    .into_iter()
+                    .map(WindowLayout::upgrade)
+                    .collect(),
+                display_names: self.display_names,
+                plot_style: self.plot_style,
+                theme: self.theme,
+                aliases: self.aliases,
+                description_extras: self.description_extras,
+                unknown_fields: default(),
+            }
+        }
+    }
+
+    impl WindowLayout {
+        fn upgrade(self) -> super::WindowLayout {
+            super::WindowLayout {
+                title: self.title,
+                config: self.config,
+                plots: self.plots.into_iter().map(PlotLayout::upgrade).collect(),
+                widgets: self.widgets,
+                annotations: Vec::new(),
+                unknown_fields: default(),
+            }
+        }
+    }
+
+    impl PlotLayout {
+        fn upgrade(self) -> super::PlotLayout {
+            super::PlotLayout {
+                key: self.key,
+                unit: self.unit,
+                config: self.config,
+                display_names: self.display_names,
+                style_override: self.style,
+                overlay: self.overlay,
+                note: self.note,
+                group: self.group,
+                pause_with_virtual_time: None,
+                unknown_fields: default(),
+            }
+        }
+    }
+}
+
+/// Layouts of version 2, where windows and plots had a `time_source`
+/// instead of [`PlotLayout::pause_with_virtual_time`].
+///
+/// Everything else is parsed as the current version, so only the time
+/// sources are read here, and patched into the parsed layout.
+mod v2 {
+    use serde::Deserialize;
+
+    /// The clocks that plots could sample with. Fixed time advances with
+    /// virtual time, so everything but real time pauses with it.
+    #[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+    enum TimeSource {
+        #[default]
+        Real,
+        Virtual,
+        Fixed,
+    }
+
+    impl TimeSource {
+        fn pauses_with_virtual_time(self) -> bool {
+            self != Self::Real
+        }
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct DashboardLayout {
+        windows: Vec<WindowLayout>,
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct WindowLayout {
+        config: DashboardWindowConfig,
+        plots: Vec<PlotLayout>,
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct DashboardWindowConfig {
+        time_source: TimeSource,
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct PlotLayout {
+        time_source: Option<TimeSource>,
+    }
+
+    /// Upgrade `layout`, parsed from `ron`, to version 3.
+    pub fn upgrade(
+        layout: &mut super::DashboardLayout,
+        ron: &str,
+    ) -> Result<(), ron::error::SpannedError> {
+        let DashboardLayout { windows } = ron::from_str(ron)?;
+        for (window, old) in layout.windows.iter_mut().zip(windows) {
+            window.config.pause_with_virtual_time =
+                old.config.time_source.pauses_with_virtual_time();
+            for (plot, old) in window.plots.iter_mut().zip(old.plots) {
+                plot.pause_with_virtual_time =
+                    old.time_source.map(TimeSource::pauses_with_virtual_time);
+                plot.unknown_fields.remove("time_source");
+            }
+        }
+        layout.version = 3;
+        Ok(())
+    }
+}
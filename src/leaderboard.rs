@@ -0,0 +1,467 @@
+//! Widget listing the metrics with the largest values among those sharing a
+//! name prefix, e.g. the AI behaviors that take the most time.
+
+use crate::egui::{DragValue, Grid, ProgressBar, TextEdit, Ui};
+use crate::latest_value::LatestValue;
+use crate::localization::{DashboardLocalization, UiText};
+use crate::number_format::NumberFormat;
+use crate::registry::{MetricKey, MetricUnit, MetricsRegistry, RegistryGeneration};
+use crate::snapshot::FrameMetricsSnapshot;
+use crate::widget::{DashboardWidget, WidgetContext};
+use bevy::prelude::{default, Reflect};
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
+use std::collections::VecDeque;
+
+/// What a [`Leaderboard`] ranks its metrics by.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum LeaderboardSort {
+    /// The value of the last frame.
+    #[default]
+    Latest,
+    /// The mean of the values of the last [`LeaderboardConfig::window`]
+    /// frames.
+    WindowMean,
+}
+
+impl LeaderboardSort {
+    #[allow(missing_docs)]
+    pub const ALL: [Self; 2] = [Self::Latest, Self::WindowMean];
+
+    fn label(self) -> UiText {
+        match self {
+            Self::Latest => UiText::SortLatest,
+            Self::WindowMean => UiText::SortWindowMean,
+        }
+    }
+}
+
+/// Configuration for one [`Leaderboard`].
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct LeaderboardConfig {
+    /// Only metrics whose names start with this are listed.
+    pub prefix: String,
+    /// When `Some`, only metrics with this label are listed, named by its
+    /// value, e.g. `behavior` to rank one metric per AI behavior.
+    pub label: Option<String>,
+    /// How many metrics are listed.
+    pub count: usize,
+    #[allow(missing_docs)]
+    pub sort: LeaderboardSort,
+    /// How many frames [`LeaderboardSort::WindowMean`] averages.
+    pub window: usize,
+}
+
+impl Default for LeaderboardConfig {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            label: None,
+            count: 10,
+            sort: default(),
+            window: 60,
+        }
+    }
+}
+
+impl LeaderboardConfig {
+    /// Whether the metric `key` is ranked.
+    pub fn matches(&self, key: &MetricKey) -> bool {
+        key.key.name().starts_with(&self.prefix)
+            && self
+                .label
+                .as_ref()
+                .is_none_or(|label| key.key.labels().any(|l| l.key() == label))
+    }
+}
+
+/// How much a metric's value must exceed that of the metric ranked above it
+/// to take its place, relative to the larger of both. Keeps metrics with
+/// similar values from swapping places every frame.
+const HYSTERESIS: f64 = 0.05;
+
+/// One metric ranked by a [`Leaderboard`].
+struct Entry {
+    value: LatestValue,
+    unit: Option<MetricUnit>,
+    /// The latest values, for [`LeaderboardSort::WindowMean`].
+    recent: VecDeque<f64>,
+}
+
+impl Entry {
+    fn score(&self, sort: LeaderboardSort) -> Option<f64> {
+        match sort {
+            LeaderboardSort::Latest => self.value.latest(),
+            LeaderboardSort::WindowMean => (!self.recent.is_empty())
+                .then(|| self.recent.iter().sum::<f64>() / self.recent.len() as f64),
+        }
+    }
+}
+
+/// Lists the metrics whose names start with a prefix, ranked by their
+/// latest value or by their mean over a window of frames, with bars
+/// proportional to the largest value. Clicking a metric plots it.
+///
+/// Counters are ranked by their increment per frame, gauges by their value,
+/// and histograms by the mean of their samples in a frame. Metrics without a
+/// value are listed last.
+///
+/// Metrics only change places when one exceeds the other by a few percent,
+/// so that similar values don't swap places every frame.
+///
+/// ```
+/// # use bevy_metrics_dashboard::FrameMetricsSnapshot;
+/// # use bevy_metrics_dashboard::leaderboard::{Leaderboard, LeaderboardConfig};
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use metrics::Key;
+/// # use std::sync::atomic::Ordering;
+/// let registry = MetricsRegistry::new();
+/// let mut leaderboard = Leaderboard::new(
+///     "AI",
+///     LeaderboardConfig {
+///         prefix: "ai::".into(),
+///         count: 2,
+///         ..Default::default()
+///     },
+/// );
+/// let mut snapshot = FrameMetricsSnapshot::default();
+/// let mut frame = |leaderboard: &mut Leaderboard, values: [(&'static str, f64); 4]| {
+///     for (name, value) in values {
+///         let gauge = registry.get_or_create_gauge(&Key::from_static_name(name));
+///         gauge.store(value.to_bits(), Ordering::Relaxed);
+///     }
+///     leaderboard.refresh_metrics(&registry);
+///     snapshot.capture(&registry, leaderboard.consumed_metrics());
+///     leaderboard.update(&snapshot);
+///     leaderboard
+///         .top()
+///         .into_iter()
+///         .map(|(key, value)| (key.key.name().to_owned(), value))
+///         .collect::<Vec<_>>()
+/// };
+///
+/// let values = [("ai::idle", 1.0), ("ai::chase", 2.0), ("ai::flee", 3.0), ("physics", 9.0)];
+/// let top = frame(&mut leaderboard, values);
+/// assert_eq!(top, [("ai::flee".into(), 3.0), ("ai::chase".into(), 2.0)]);
+///
+/// // Too close to overtake.
+/// let values = [("ai::idle", 1.0), ("ai::chase", 3.1), ("ai::flee", 3.0), ("physics", 9.0)];
+/// let top = frame(&mut leaderboard, values);
+/// assert_eq!(top, [("ai::flee".into(), 3.0), ("ai::chase".into(), 3.1)]);
+/// ```
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+pub struct Leaderboard {
+    name: String,
+    config: LeaderboardConfig,
+    /// In ranked order.
+    #[reflect(ignore)]
+    entries: Vec<Entry>,
+    /// The registry generation and config the entries were found with, so
+    /// they're found again when the config is changed through reflection.
+    #[reflect(ignore)]
+    generation: Option<(RegistryGeneration, LeaderboardConfig)>,
+}
+
+impl Leaderboard {
+    /// Create a new leaderboard widget.
+    pub fn new(name: impl Into<String>, config: LeaderboardConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            entries: Vec::new(),
+            generation: None,
+        }
+    }
+
+    /// Name of the widget.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This widget's configuration.
+    pub fn config(&self) -> &LeaderboardConfig {
+        &self.config
+    }
+
+    /// Find the metrics in `registry` that [match](LeaderboardConfig::matches)
+    /// the config, if any metric was registered or removed, or the config
+    /// changed since the last call.
+    ///
+    /// Metrics that were already ranked keep their place and values, and
+    /// removed metrics, e.g. [evicted](crate::retention) ones, are dropped.
+    /// Called when the widget is drawn.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::FrameMetricsSnapshot;
+    /// # use bevy_metrics_dashboard::leaderboard::{Leaderboard, LeaderboardConfig};
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use bevy_metrics_dashboard::retention::RetentionPolicy;
+    /// # use metrics::Key;
+    /// # use std::{sync::atomic::Ordering, time::Duration};
+    /// let registry = MetricsRegistry::new();
+    /// registry.set_retention_policy(Some(RetentionPolicy::new(Duration::ZERO)));
+    /// let chase = registry.get_or_create_gauge(&Key::from_static_name("ai::chase"));
+    /// chase.store(2.0f64.to_bits(), Ordering::Relaxed);
+    ///
+    /// let config = LeaderboardConfig {
+    ///     prefix: "ai::".into(),
+    ///     ..Default::default()
+    /// };
+    /// let mut leaderboard = Leaderboard::new("AI", config);
+    /// leaderboard.refresh_metrics(&registry);
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// snapshot.capture(&registry, leaderboard.consumed_metrics());
+    /// leaderboard.update(&snapshot);
+    /// assert_eq!(leaderboard.top().len(), 1);
+    ///
+    /// registry.sample_activity();
+    /// assert_eq!(registry.evict_idle(|_| false).len(), 1);
+    /// leaderboard.refresh_metrics(&registry);
+    /// assert!(leaderboard.top().is_empty());
+    /// ```
+    pub fn refresh_metrics(&mut self, registry: &MetricsRegistry) {
+        let generation = registry.generation();
+        if self
+            .generation
+            .as_ref()
+            .is_some_and(|(g, config)| *g == generation && *config == self.config)
+        {
+            return;
+        }
+        self.generation = Some((generation, self.config.clone()));
+
+        let mut found: Vec<_> = registry
+            .search_iter("")
+            .filter(|result| self.config.matches(&result.key))
+            .collect();
+        self.entries.retain(|entry| {
+            let position = found.iter().position(|r| &r.key == entry.value.key());
+            position.map(|i| found.swap_remove(i)).is_some()
+        });
+        found.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        self.entries.extend(found.into_iter().map(|result| Entry {
+            unit: result.description.and_then(|d| d.unit),
+            value: LatestValue::new(result.key),
+            recent: VecDeque::new(),
+        }));
+    }
+
+    /// Sample every ranked metric and update the ranking.
+    ///
+    /// This should run in the [`SampleMetricsSet`](crate::SampleMetricsSet),
+    /// like [`MetricPlot::update`](crate::plots::MetricPlot::update).
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        let window = self.config.window.max(1);
+        for entry in &mut self.entries {
+            entry.value.update(snapshot);
+            if let Some(value) = entry.value.latest() {
+                entry.recent.push_back(value);
+            }
+            while entry.recent.len() > window {
+                entry.recent.pop_front();
+            }
+        }
+        self.rank();
+    }
+
+    /// Move metrics up past those they exceed by the [`HYSTERESIS`].
+    fn rank(&mut self) {
+        let sort = self.config.sort;
+        let overtakes = |below: Option<f64>, above: Option<f64>| match (below, above) {
+            (Some(_), None) => true,
+            (Some(below), Some(above)) => below - above > HYSTERESIS * below.abs().max(above.abs()),
+            _ => false,
+        };
+        for _ in 0..self.entries.len() {
+            let mut swapped = false;
+            for i in 1..self.entries.len() {
+                let below = self.entries[i].score(sort);
+                let above = self.entries[i - 1].score(sort);
+                if overtakes(below, above) {
+                    self.entries.swap(i - 1, i);
+                    swapped = true;
+                }
+            }
+            if !swapped {
+                break;
+            }
+        }
+    }
+
+    /// The [`LeaderboardConfig::count`] highest ranked metrics that have a
+    /// value, with the value they're ranked by.
+    pub fn top(&self) -> Vec<(&MetricKey, f64)> {
+        self.top_entries()
+            .map(|(entry, score)| (entry.value.key(), score))
+            .collect()
+    }
+
+    fn top_entries(&self) -> impl Iterator<Item = (&Entry, f64)> {
+        self.entries
+            .iter()
+            .take(self.config.count)
+            .filter_map(|entry| Some((entry, entry.score(self.config.sort)?)))
+    }
+
+    /// The metrics sampled by [`Self::update`].
+    pub fn consumed_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        self.entries.iter().map(|entry| entry.value.key())
+    }
+
+    /// The name of a metric in the list.
+    fn row_title(&self, key: &MetricKey) -> String {
+        if let Some(label) = &self.config.label {
+            if let Some(label) = key.key.labels().find(|l| l.key() == label) {
+                return label.value().to_owned();
+            }
+        }
+        let title = key.name_with_labels();
+        match title.strip_prefix(&self.config.prefix) {
+            Some(rest) if !rest.is_empty() => rest.to_owned(),
+            _ => title,
+        }
+    }
+
+    /// Draw the widget using `ui`. Returns the metric that was clicked, to
+    /// be plotted, with its unit.
+    pub fn draw(
+        &mut self,
+        registry: &MetricsRegistry,
+        numbers: &NumberFormat,
+        ui: &mut Ui,
+    ) -> Option<(MetricKey, Option<MetricUnit>)> {
+        let l10n = DashboardLocalization::of(ui);
+        self.refresh_metrics(registry);
+
+        let mut clicked = None;
+        let top: Vec<_> = self.top_entries().collect();
+        if top.is_empty() {
+            ui.weak(l10n.get(UiText::NoMatchingMetrics));
+        }
+        let max = top.iter().map(|(_, score)| score.abs()).fold(0.0, f64::max);
+        Grid::new(&self.name).num_columns(2).show(ui, |ui| {
+            for (entry, score) in top {
+                let key = entry.value.key();
+                if ui
+                    .link(self.row_title(key))
+                    .on_hover_text(l10n.get(UiText::ClickToPlot))
+                    .clicked()
+                {
+                    clicked = Some((key.clone(), entry.unit.clone()));
+                }
+                let fraction = if max > 0.0 { score.abs() / max } else { 0.0 };
+                let unit = entry.unit.as_ref().map(MetricUnit::as_str);
+                let text = numbers.format_with_unit(score, unit, l10n.decimal_separator);
+                ui.add(ProgressBar::new(fraction as f32).text(text));
+                ui.end_row();
+            }
+        });
+
+        ui.collapsing(l10n.get(UiText::Settings), |ui| self.configure_ui(ui));
+        clicked
+    }
+
+    fn configure_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(l10n.get(UiText::NamePrefix));
+            changed |= ui
+                .add(TextEdit::singleline(&mut self.config.prefix))
+                .changed();
+        });
+        ui.horizontal(|ui| {
+            let mut split = self.config.label.is_some();
+            changed |= ui
+                .checkbox(&mut split, l10n.get(UiText::SplitByLabel))
+                .changed();
+            if !split {
+                self.config.label = None;
+                return;
+            }
+            let label = self.config.label.get_or_insert_with(String::new);
+            changed |= ui.add(TextEdit::singleline(label)).changed();
+        });
+        if changed {
+            self.generation = None;
+            self.entries.clear();
+        }
+
+        ui.add(
+            DragValue::new(&mut self.config.count)
+                .prefix(l10n.get(UiText::TopPrefix))
+                .range(1..=100),
+        );
+        ui.horizontal(|ui| {
+            ui.label(l10n.get(UiText::SortBy));
+            for sort in LeaderboardSort::ALL {
+                ui.selectable_value(&mut self.config.sort, sort, l10n.get(sort.label()));
+            }
+        });
+        if self.config.sort == LeaderboardSort::WindowMean {
+            ui.add(
+                DragValue::new(&mut self.config.window)
+                    .prefix(l10n.get(UiText::FramesPrefix))
+                    .range(1..=10_000),
+            );
+        }
+    }
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self::new("Leaderboard", LeaderboardConfig::default())
+    }
+}
+
+impl DashboardWidget for Leaderboard {
+    fn id(&self) -> &str {
+        "leaderboard"
+    }
+
+    fn title(&self, _ctx: &WidgetContext) -> String {
+        if self.config.prefix.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, self.config.prefix)
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext) {
+        if let Some((key, unit)) = self.draw(ctx.registry, &ctx.style.numbers, ui) {
+            ctx.request_plot(key, unit);
+        }
+    }
+
+    fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        Leaderboard::update(self, snapshot);
+    }
+
+    fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+        Box::new(Leaderboard::consumed_metrics(self))
+    }
+
+    #[cfg(feature = "layout")]
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        RawValue::from_rust(&self.config).map(Some)
+    }
+
+    #[cfg(feature = "layout")]
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        let name = std::mem::take(&mut self.name);
+        *self = Self::new(name, config.into_rust()?);
+        Ok(())
+    }
+
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+}
@@ -1,29 +1,139 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+mod audit_window;
+pub mod budget_bar;
+mod cached_metrics;
+#[cfg(feature = "bevy_egui")]
+mod command_palette;
+pub mod comparison;
+mod component_gauge_plugin;
+mod context_menu;
+pub mod core_metrics;
 mod core_metrics_plugin;
 #[cfg(feature = "bevy_egui")]
 mod dashboard_plugin;
 mod dashboard_window;
+#[cfg(feature = "detached_window")]
+mod detached_window;
+mod dial;
+pub mod difference_plot;
+pub mod display_names;
 mod dropdown_list;
+mod event_metrics_plugin;
+mod fanout_recorder;
+mod focus;
+mod headless_plugin;
+#[cfg(feature = "ui_indicator")]
+mod indicator;
+mod latest_value;
+#[cfg(feature = "layout")]
+pub mod layout;
+pub mod leaderboard;
+pub mod localization;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+pub mod markdown;
+pub mod metric_budget;
+mod name_pattern;
+pub mod namespace;
 mod namespace_tree;
+pub mod number_format;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+mod overlay;
+mod performance_budget;
+pub mod plot_defaults;
+pub mod plot_style;
 pub mod plots;
+mod preview;
+mod read_only;
 pub mod registry;
 mod registry_plugin;
+#[cfg(feature = "report")]
+pub mod report;
+pub mod retention;
 mod ring;
+mod sampling_latency;
+pub mod scatter_plot;
+mod scene;
+mod scoped_recorder;
 mod search_bar;
+mod snapshot;
+pub mod snapshot_diff;
+mod spike_detector;
+#[cfg(feature = "state_metrics")]
+mod state_metrics_plugin;
+mod stats;
+#[cfg(feature = "statsd")]
+pub mod statsd;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+pub mod theme;
+mod title_summary;
+pub mod transform;
+mod type_name;
+mod virtual_time;
+pub mod widget;
 
+#[cfg(feature = "render_metrics")]
+mod render_forwarding;
 #[cfg(feature = "render_metrics")]
 mod render_metrics_plugin;
 
-pub use core_metrics_plugin::CoreMetricsPlugin;
+pub use audit_window::AuditWindow;
+pub use cached_metrics::{CachedCounter, CachedGauge, CachedHistogram};
+#[cfg(feature = "bevy_egui")]
+pub use command_palette::{
+    CommandPalette, DashboardCommand, DashboardCommands, DashboardCommandsAppExt,
+};
+pub use component_gauge_plugin::{ComponentGaugePlugin, MetricGaugeAppExt};
+pub use core_metrics_plugin::{CoreMetricsPlugin, FramePacingConfig};
 #[cfg(feature = "bevy_egui")]
 pub use dashboard_plugin::DashboardPlugin;
-pub use dashboard_window::{CachedPlotConfigs, DashboardConfig, DashboardWindow, RequestPlot};
-pub use namespace_tree::NamespaceTreeWindow;
-pub use registry_plugin::{ClearBucketsSystem, RegistryPlugin};
-pub use search_bar::SearchBar;
+pub use dashboard_window::{
+    CachedPlotConfigs, DashboardAnnotation, DashboardConfig, DashboardWindow,
+    DashboardWindowConfig, OpenPlots, PlotSort, RequestPlot, COMMAND_PALETTE_SHORTCUT,
+    DEFAULT_COMPACT_WIDTH, DEFAULT_OPEN_ALL_LIMIT, DEFAULT_UI_REFRESH_RATE, SEARCH_SHORTCUT,
+};
+#[cfg(feature = "detached_window")]
+pub use detached_window::DetachedDashboard;
+pub use event_metrics_plugin::EventMetricsPlugin;
+pub use fanout_recorder::FanoutRecorder;
+pub use focus::{
+    DashboardFocus, UnfocusedBehavior, CATCH_UP_PLOTS_PER_FRAME, UNFOCUSED_REFRESH_RATE,
+};
+pub use headless_plugin::HeadlessDashboardPlugin;
+#[cfg(feature = "ui_indicator")]
+pub use indicator::{MetricIndicator, MetricIndicatorPlugin};
+pub use name_pattern::NamePattern;
+pub use namespace_tree::{NamespaceTreeWindow, DEFAULT_NAMESPACE_SEPARATOR};
+#[cfg(feature = "bevy_egui")]
+pub use overlay::OverlayPlugin;
+pub use overlay::{OverlayConfig, OverlayCorner};
+pub use performance_budget::{
+    DashboardPerformance, Degradation, PerformanceBudget, DOWNSAMPLING_CHUNK,
+};
+pub use read_only::ReadOnly;
+#[allow(deprecated)]
+pub use registry_plugin::{BucketClearSet, ClearBucketsSystem, FallbackBehavior, RegistryPlugin};
+pub use sampling_latency::{
+    validate_sampling_latency, SamplingLatency, SamplingLatencyError, SamplingLatencyPlugin,
+};
+pub use scene::{DashboardSceneAppExt, DashboardSceneState, ScenePlot};
+pub use scoped_recorder::{with_scoped_recorder, ScopedRecorderGuard};
+pub use search_bar::{MetricSearchBar, SearchSelection, DEFAULT_SEARCH_DEBOUNCE};
+pub use snapshot::{
+    FrameMetricsSnapshot, MetricDiff, MetricPresence, RegistrySnapshot, SampleMetricsSet,
+    SnapshotValue,
+};
+#[cfg(feature = "state_metrics")]
+pub use state_metrics_plugin::StateMetricsPlugin;
+pub use title_summary::TitleSummaryConfig;
+pub use virtual_time::VirtualTimeAdvanced;
 
+#[cfg(feature = "render_metrics")]
+pub use render_forwarding::RenderMetricsForwardingPlugin;
 #[cfg(feature = "render_metrics")]
 pub use render_metrics_plugin::RenderMetricsPlugin;
 
@@ -0,0 +1,532 @@
+//! Translations of the strings shown by the dashboard.
+//!
+//! Every user-visible string is named by a [`UiText`] key. The
+//! [`DashboardLocalization`] resource maps keys to translations, falling back
+//! to the English text of the key.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::localization::{DashboardLocalization, UiText};
+//! # let mut app = App::new();
+//! app.insert_resource(
+//!     DashboardLocalization::new()
+//!         .with_translation(UiText::Pause, "Pausieren")
+//!         .with_translation(UiText::Latest, "aktuell = {value}")
+//!         .with_decimal_separator(','),
+//! );
+//! ```
+//!
+//! Some texts have placeholders in braces, like `{value}`, which are replaced
+//! by [`DashboardLocalization::format`]. Translations should keep them, in any
+//! order. Metric names, label values and unit symbols aren't translated.
+
+use crate::egui::{self, Context, Ui};
+use bevy::{platform::collections::HashMap, prelude::*};
+use metrics_util::MetricKind;
+use std::{fmt::Display, sync::Arc};
+
+macro_rules! ui_texts {
+    ($($(#[$attr:meta])* $key:ident => $english:literal,)*) => {
+        /// The key of a string shown by the dashboard. The documentation of
+        /// each key is its English text.
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Reflect)]
+        #[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+        pub enum UiText {
+            $(
+                #[doc = concat!("`", $english, "`")]
+                $(#[$attr])*
+                $key,
+            )*
+        }
+
+        impl UiText {
+            /// Every key, e.g. for exporting a table to translate.
+            pub const ALL: &[Self] = &[$(Self::$key,)*];
+
+            /// The text shown when there is no translation.
+            pub fn english(self) -> &'static str {
+                match self {
+                    $(Self::$key => $english,)*
+                }
+            }
+        }
+    };
+}
+
+ui_texts! {
+    // Common
+    Settings => "Settings",
+    Remove => "Remove",
+    Refresh => "Refresh",
+    Copy => "Copy",
+    Nothing => "none",
+    WindowPrefix => "Window: ",
+    MinPrefix => "Min: ",
+    MaxPrefix => "Max: ",
+    BucketsPrefix => "Buckets: ",
+    Counter => "counter",
+    Gauge => "gauge",
+    Histogram => "histogram",
+
+    // Dashboard window
+    Tools => "Tools",
+    Browse => "Browse",
+    NamespaceViewerTitle => "Namespace Viewer",
+    Audit => "Audit",
+    MetricsAuditTitle => "Metrics Audit",
+    BudgetBar => "Budget Bar",
+    ScatterPlot => "Scatter Plot",
+    DifferencePlot => "Difference Plot",
+    Leaderboard => "Leaderboard",
+    SnapshotDiff => "Snapshot Diff",
+    AddWidget => "Add Widget",
+    OverlayStyle => "Overlay Style",
+    OverlayStyleHint => "Only draw the lines and latest values of all plots",
+    QuickAdd => "Quick Add",
+    QuickAddPrefix => "Quick Add:",
+    SaveLayout => "Save Layout",
+    UnsavedLayoutChanges => "The layout has changed since it was last saved",
+    ExportReport => "Export Report",
+    ExportReportHint => "Write an HTML report of all plots to the report file",
+    RestoreLayoutTitle => "Restore Layout",
+    RestoreLayoutPrompt => "An autosaved layout is newer than the saved one. Restore it?",
+    Restore => "Restore",
+    Discard => "Discard",
+    Detach => "Detach",
+    DetachHint => "Move this window to its own OS window",
+    Attach => "Attach",
+    GlobalSettings => "Global Settings",
+    PlotStyleAllWindows => "Plot Style (All Windows)",
+    DisplayNamesAllWindows => "Display Names (All Windows)",
+    Pause => "Pause",
+    LinkXAxes => "Link X Axes",
+    Opacity => "Opacity",
+    CompactChrome => "Compact Chrome",
+    CompactChromeHint => "Hide the title bar. Drag the window by its background",
+    AlwaysOnTop => "Always On Top",
+    AlwaysOnTopHint => "Keep the dashboard's own OS window above other windows, where the platform supports it",
+    CompareByLabel => "Compare By Label",
+    TitleSummary => "Title Summary",
+    Fps => "FPS",
+    FrameTime => "Frame Time",
+    Entities => "Entities",
+    PercentilePrefix => "Percentile: ",
+    Sort => "Sort: {sort}",
+    SortManual => "Manual",
+    SortAlphabetical => "Alphabetical",
+    SortLatestValue => "Latest Value",
+    SortRecentlyAdded => "Recently Added",
+    SortAlertsFirst => "Alerts First",
+    ComparisonTitle => "{name} by {label}",
+    PlotGroupTitle => "{group} ({count})",
+    Ungroup => "Ungroup",
+    ReadOnlyBadge => "🔒 Read-Only",
+    ReadOnlyHint => "This dashboard is read-only. Plots can still be panned, zoomed, paused and exported",
+    LockedGroupHint => "The plots in this group are read-only",
+    OpenAll => "Open All ({count})",
+    OpenAllHint => "Plot every result with default settings, in a group named after the query",
+    OpenAllConfirm => "Open {count} plots?",
+    Open => "Open",
+    Cancel => "Cancel",
+    DragToReorder => "Drag to reorder",
+    ShowInOverlay => "Show in Overlay",
+    RemoveFromOverlay => "Remove from Overlay",
+    TopLeft => "Top Left",
+    TopRight => "Top Right",
+    BottomLeft => "Bottom Left",
+    BottomRight => "Bottom Right",
+    Theme => "Theme: {theme}",
+    ThemeInherit => "Inherit",
+    ThemeDark => "Dark",
+    ThemeLight => "Light",
+    ThemeHighContrast => "High Contrast",
+    PauseWithVirtualTime => "Pause With Virtual Time",
+    PauseWithVirtualTimeHint => "Plots don't sample in frames where virtual time is paused, e.g. while the game is paused. The x axis still counts samples, so scaling virtual time doesn't stretch plots.",
+    VirtualTime => "Virtual Time: {mode}",
+    VirtualTimeDefault => "Window Default",
+    VirtualTimePause => "Pause",
+    VirtualTimeIgnore => "Ignore",
+    VirtualTimePaused => "Pausing with virtual time",
+    VirtualTimeIgnored => "Ignoring virtual time",
+
+    // Title summary
+    FpsSummary => "{fps} fps",
+    FrameTimeSummary => "p{percentile} {frame_time} ms",
+    EntitiesSummary => "{entities} entities",
+
+    // Plots
+    Latest => "latest = {value}",
+    Stale => "{text} stale ({seconds} s)",
+    FrameAxis => "frame",
+    FixedTickAxis => "fixed tick",
+    SamplePerFixedTick => "Sample Per Fixed Tick",
+    SamplePerFixedTickHint => "Take one sample per fixed timestep tick instead of per frame, for metrics recorded in FixedUpdate",
+    FixedTickBadge => "tick",
+    FixedTickBadgeHint => "Sampled once per fixed timestep tick",
+
+    // Performance budget
+    DegradedBadge => "⏬ Degraded",
+    DegradedHint => "The dashboard takes {cost} ms per frame, over its budget of {budget} ms. To save time, it:",
+    DegradedSkipHidden => "• skips refreshing plots that aren't on screen",
+    DegradedRefreshRate => "• refreshes plots {rate} times per second",
+    DegradedDownsampled => "• draws two points for every {samples} samples",
+    RefreshAxis => "refresh",
+    CountAxis => "count",
+    Derivative => "Derivative",
+    BufferWhileHidden => "Buffer While Hidden",
+    StaleRateAsZero => "Stale Rate as Zero",
+    StaleDetection => "Stale Detection",
+    IntervalTimesPrefix => "Interval × ",
+    OverrideWindowSize => "Override Window Size",
+    WindowSize => "Window Size",
+    SmoothingWeight => "Smoothing Weight",
+    Line => "Line",
+    Dial => "Dial",
+    Interpolation => "Interpolation:",
+    Auto => "Auto",
+    InterpolationAutoHint => "Steps after each sample for counter rates and counts, straight lines otherwise",
+    Linear => "Linear",
+    StepBefore => "Step Before",
+    StepAfter => "Step After",
+    ShowEnvelope => "Show Intra-Frame Envelope",
+    ShowStdDev => "Show Standard Deviation",
+    ShowStdDevHint => "Shade one standard deviation around the smoothed line, weighted like the smoothing",
+    CompareWithEarlier => "Compare with Earlier Samples",
+    CompareWithEarlierHint => "Draw a dashed ghost of the line shifted by a number of seconds. The plot's history must be longer than the offset",
+    SecondsEarlierPrefix => "Seconds Earlier: ",
+    ShowMeanDelta => "Show Mean Difference",
+    GhostSeries => "{series} ({offset} s earlier)",
+    GhostSummary => "Compared with {offset} s earlier over the last {samples} samples",
+    GhostNotEnoughHistory => "Not enough history to compare with {offset} s earlier",
+    GhostMeanDelta => "mean Δ {delta} ({percent})",
+    Distribution => "Distribution",
+    LimitSamples => "Limit Samples",
+    Buckets => "Buckets",
+    FanChart => "Fan Chart",
+    SlidingWindow => "Sliding Window",
+    TrailingPercentiles => "Trailing Percentiles",
+    TrailingPercentilesHint => "Compute percentiles over the samples of the last frames, instead of each refresh",
+    FramesPrefix => "Frames: ",
+    SecondsPrefix => "Seconds: ",
+    SamplesPerFramePrefix => "Samples per Frame: ",
+    TrailingSummary => "Last {seconds} s: p50 {p50} · p95 {p95} · p99 {p99}",
+    CaptureOutliers => "Capture Outliers",
+    Outliers => "Outliers",
+    Outlier => "{value}, {frames} frames ago",
+    NearAnnotation => "near \"{label}\"",
+    UntilPrefix => "Until: ",
+    AddZone => "Add Zone",
+    FromPercentilePrefix => "From: p",
+    ToPercentilePrefix => "To: p",
+    AddBand => "Add Band",
+    Median => "Median",
+    SpikeDetection => "Spike Detection",
+    AbsoluteThreshold => "Absolute Threshold",
+    MedianTimesPrefix => "Median × ",
+    NoSpikes => "No spikes detected",
+    Spikes => "Spikes",
+    Spike => "frame {sample}: {value}",
+    DisplayNames => "Display Names",
+    Style => "Style",
+    OverrideDashboardStyle => "Override Dashboard Style",
+    Note => "Note",
+    Notes => "Notes",
+    SampleNoteHint => "Ctrl-click the plot to attach a note to a frame",
+    NoteAtFrame => "Frame {frame}:",
+
+    // Plot style
+    Minimal => "Minimal",
+    Grid => "Grid",
+    Axes => "Axes",
+    AxisLabels => "Axis Labels",
+    BackgroundOpacity => "Background Opacity",
+    Decimals => "Decimals",
+    SignificantDigits => "Significant Digits",
+    ThousandsSeparator => "Thousands Separator",
+    ScientificAbove => "Scientific Above",
+
+    // Transforms
+    Transform => "Transform",
+    Scale => "Scale",
+    Offset => "Offset",
+    AbsoluteValue => "Absolute Value",
+    Clamp => "Clamp",
+    Reciprocal => "Reciprocal",
+    MoveUp => "⏶",
+    AddStep => "Add Step",
+    DisplayUnit => "Unit: {unit}",
+    OverrideUnit => "Override Unit",
+    TransformPreview => "{raw} → {value}",
+
+    // Display names
+    LabelValueHint => "label value",
+    Regex => "Regex",
+    DisplayNameHint => "display name",
+    RemoveRule => "x",
+    AddRule => "Add Rule",
+
+    // Budget bars
+    BudgetPrefix => "Budget: ",
+    WarningHeadroomPrefix => "Warning Headroom: ",
+    Colors => "Colors:",
+    Missing => "{name} (missing)",
+
+    // Comparisons
+    MeanDiff => "Mean Diff",
+    P95Diff => "P95 Diff",
+    Change => "Change",
+    Versus => "{name} vs {baseline}",
+    NotAvailable => "n/a",
+    NoData => "no data",
+
+    // Scatter plots
+    ChooseAxes => "Choose a metric for each axis in the settings.",
+    RetentionPrefix => "Retention: ",
+    FadePrefix => "Fade: ",
+    TrendLine => "Trend Line",
+    Axis => "{axis}:",
+
+    // Difference plots
+    ChooseOperands => "Choose a metric for each operand in the settings.",
+    OperandsHint => "Counters are combined as their rate per frame. Histograms can't be chosen.",
+
+    // Leaderboards
+    NoMatchingMetrics => "No metrics with a value match the prefix.",
+    ClickToPlot => "Click to plot",
+    NamePrefix => "Name Prefix:",
+    SplitByLabel => "Split by Label",
+    TopPrefix => "Top: ",
+    SortBy => "Sort by:",
+    SortLatest => "Latest",
+    SortWindowMean => "Window Mean",
+
+    // Snapshot diffs
+    CaptureA => "Capture A",
+    CaptureB => "Capture B",
+    FilterPrefix => "Filter:",
+    CaptureBothSnapshots => "Capture snapshots A and B to compare every metric.",
+    NoMatchingRows => "No metrics match the filter.",
+    ClickToSort => "Click to sort",
+    ColumnName => "Name",
+    SnapshotA => "A",
+    SnapshotB => "B",
+    ColumnDelta => "Δ",
+    ColumnPercent => "Δ %",
+    OnlyInA => "only in A",
+    OnlyInB => "only in B",
+
+    // Search
+    SearchButton => "🔍",
+    ExpandSearchHint => "Search ({shortcut})",
+    CollapseSearch => "⏴",
+    CollapseSearchHint => "Hide the search bar",
+    SearchPrefix => "Search:",
+    MetricNameHint => "metric name",
+    OnlyActive => "Only Active",
+    AnyKind => "any kind",
+    AnyUnit => "any unit",
+    UpdatedAgo => "Updated {seconds} s ago",
+    NotUpdated => "Not updated since it was registered",
+    SampleCount => "{count} samples",
+    CollectingSamples => "Collecting samples...",
+    AliasOf => "alias of {name}",
+    InferredUnit => "(inferred)",
+    Deprecated => "(deprecated)",
+    Owner => "owner: {owner}",
+
+    // Context menus
+    CopyMetricName => "Copy Metric Name",
+    CopyFullKey => "Copy Full Key",
+    CopyAsMarkdown => "Copy as Markdown",
+    CopyAsMarkdownWithSamples => "Copy as Markdown with Samples",
+    ShowDescription => "Show Description",
+    OpenDocs => "Open Docs",
+    CopyDocs => "Copy Docs",
+    OpenLink => "Open Link",
+    CopyLink => "Copy Link",
+
+    // Namespace tree
+    Plot => "Plot",
+
+    // Audit
+    AuditClean => "Every registered metric is described, and every description is used.",
+    AuditUnregistered => "Described but never registered ({count})",
+    AuditUndescribed => "Registered without a description ({count})",
+    AuditEvicted => "{count} idle metrics evicted",
+    AuditEvictedHint => "Removed by the retention policy after going without updates. They're registered again when recorded",
+    CopyAll => "Copy All",
+    SeriesCount => "{count} series",
+    AuditHot => "Hot histograms ({count})",
+    SamplesPerFrame => "{samples} samples/frame",
+    AuditHighCardinality => "High label cardinality ({count})",
+    LabelCombinations => "{count}/{limit} label combinations",
+    OverflowedRegistrations => "{count} overflowed",
+    OverflowedRegistrationsHint => "New label combinations recorded with label=\"<overflow>\" instead",
+    RecentLabelCombinations => "Recent label combinations:",
+    HotBadge => "⚠",
+    HotHistogram => "Records {samples} samples per frame, which is expensive to buffer",
+    LateSamplesDropped => "{count} late samples dropped",
+    LateSamplesHint => "Values recorded for frames that were no longer in the plot's window",
+
+    // Command palette
+    CommandPaletteHint => "Type a command, or a metric to plot",
+    NoMatchingCommands => "No matching commands",
+    PlotMetric => "Plot: {metric}",
+    PauseAllWindows => "Pause or resume all windows",
+    ClearAllPlots => "Clear all plots",
+    ToggleDashboard => "Show or hide the dashboard",
+    ToggleOverlay => "Show or hide the overlay",
+}
+
+impl UiText {
+    /// The name of a metric kind.
+    pub fn kind(kind: MetricKind) -> Self {
+        match kind {
+            MetricKind::Counter => Self::Counter,
+            MetricKind::Gauge => Self::Gauge,
+            MetricKind::Histogram => Self::Histogram,
+        }
+    }
+}
+
+/// Translations of the dashboard's [`UiText`], with English defaults, and the
+/// formatting of numbers.
+///
+/// Inserted by the [`DashboardPlugin`](crate::DashboardPlugin), unless the
+/// app inserts its own. Changes take effect on the next frame. See the
+/// [module docs](self) for an example.
+#[derive(Clone, Resource)]
+pub struct DashboardLocalization {
+    translations: Arc<HashMap<UiText, String>>,
+    /// Separates the integer and fractional digits of numbers formatted by
+    /// [`Self::number`].
+    pub decimal_separator: char,
+}
+
+impl Default for DashboardLocalization {
+    fn default() -> Self {
+        Self {
+            translations: default(),
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl DashboardLocalization {
+    /// English, with a `.` decimal separator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(missing_docs)]
+    pub fn with_translation(mut self, key: UiText, text: impl Into<String>) -> Self {
+        self.set(key, text);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Translate `key` as `text`.
+    pub fn set(&mut self, key: UiText, text: impl Into<String>) {
+        Arc::make_mut(&mut self.translations).insert(key, text.into());
+    }
+
+    /// The translation of `key`, or its English text.
+    pub fn get(&self, key: UiText) -> &str {
+        self.translations
+            .get(&key)
+            .map_or(key.english(), String::as_str)
+    }
+
+    /// The keys without a translation, which are shown in English.
+    ///
+    /// Use this to check that a table covers every key, e.g. in a test:
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::localization::{DashboardLocalization, UiText};
+    /// # use std::collections::HashSet;
+    /// // Every key has an English text, which is the default locale.
+    /// let english = DashboardLocalization::new();
+    /// assert!(UiText::ALL.iter().all(|key| !english.get(*key).is_empty()));
+    /// let keys: HashSet<_> = UiText::ALL.iter().collect();
+    /// assert_eq!(keys.len(), UiText::ALL.len());
+    /// assert_eq!(english.untranslated().count(), UiText::ALL.len());
+    ///
+    /// let mut german = DashboardLocalization::new().with_translation(UiText::Pause, "Pausieren");
+    /// assert!(german.untranslated().any(|key| key == UiText::LinkXAxes));
+    /// for key in UiText::ALL {
+    ///     german.set(*key, format!("[de] {}", key.english()));
+    /// }
+    /// assert_eq!(german.untranslated().count(), 0);
+    /// ```
+    pub fn untranslated(&self) -> impl Iterator<Item = UiText> + '_ {
+        UiText::ALL
+            .iter()
+            .copied()
+            .filter(|key| !self.translations.contains_key(key))
+    }
+
+    /// The translation of `key`, with each `{name}` placeholder replaced by
+    /// the value paired with `name` in `args`.
+    pub fn format(&self, key: UiText, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_owned();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    /// Format `value` with `precision` fractional digits and the
+    /// [`Self::decimal_separator`].
+    pub fn number(&self, value: f64, precision: usize) -> String {
+        self.localize_number(format!("{value:.precision$}"))
+    }
+
+    /// Like [`Self::number`], always with a sign.
+    pub fn signed_number(&self, value: f64, precision: usize) -> String {
+        self.localize_number(format!("{value:+.precision$}"))
+    }
+
+    fn localize_number(&self, text: String) -> String {
+        if self.decimal_separator == '.' {
+            text
+        } else {
+            text.replace('.', self.decimal_separator.encode_utf8(&mut [0; 4]))
+        }
+    }
+
+    /// Draw the windows and areas in `add_contents` with this localization.
+    ///
+    /// Widgets find it with [`Self::of`].
+    pub fn scope<R>(&self, ctx: &Context, add_contents: impl FnOnce(&Context) -> R) -> R {
+        let outer = ctx.data_mut(|d| {
+            let outer = d.get_temp::<Self>(localization_id());
+            d.insert_temp(localization_id(), self.clone());
+            outer
+        });
+
+        let inner = add_contents(ctx);
+
+        ctx.data_mut(|d| match outer {
+            Some(outer) => d.insert_temp(localization_id(), outer),
+            None => d.remove::<Self>(localization_id()),
+        });
+        inner
+    }
+
+    /// The localization that `ui` is drawn with, or English outside of
+    /// [`Self::scope`].
+    pub fn of(ui: &Ui) -> Self {
+        ui.ctx()
+            .data(|d| d.get_temp(localization_id()))
+            .unwrap_or_default()
+    }
+}
+
+fn localization_id() -> egui::Id {
+    egui::Id::new("bevy_metrics_dashboard_localization")
+}
@@ -0,0 +1,305 @@
+//! Metric descriptions loaded from a RON manifest file, as a single source
+//! of truth instead of `describe_*` calls spread across crates.
+//!
+//! A manifest is a list of entries:
+//!
+//! ```
+//! # use bevy_metrics_dashboard::manifest::MetricManifest;
+//! # use bevy_metrics_dashboard::registry::{DescriptionKey, ManifestPolicy, MetricsRegistry};
+//! # use bevy_metrics_dashboard::metrics_util::MetricKind;
+//! let manifest = MetricManifest::from_ron(r#"[
+//!     (
+//!         name: "physics.step_time",
+//!         kind: Histogram,
+//!         unit: "seconds",
+//!         description: "Time spent in one physics step",
+//!         docs_url: "https://example.com/physics",
+//!     ),
+//!     (name: "physics.bodies", kind: Gauge, description: "Rigid bodies"),
+//! ]"#)
+//! .unwrap();
+//!
+//! let registry = MetricsRegistry::new();
+//! manifest.apply(&registry, ManifestPolicy::CodeWins);
+//! let key = DescriptionKey {
+//!     name: "physics.bodies".into(),
+//!     kind: MetricKind::Gauge,
+//! };
+//! assert_eq!(&*registry.get_description(&key).unwrap().text, "Rigid bodies");
+//!
+//! let error = MetricManifest::from_ron(r#"[
+//!     (name: "physics.bodies", kind: Gauge),
+//!     (name: "physics.contacts", kind: Gage),
+//! ]"#)
+//! .unwrap_err();
+//! assert!(error.to_string().contains(r#"entry 2 ("physics.contacts")"#));
+//! ```
+//!
+//! Load the file on startup with
+//! [`RegistryPlugin::manifest`](crate::RegistryPlugin::manifest). Units are
+//! written like in [layouts](crate::layout): standard units with their
+//! `metrics` name, like `"milliseconds"`, and any other string as a custom
+//! unit.
+
+use crate::{
+    layout::{unit, MetricKindDef},
+    metric_kind_str,
+    registry::{
+        DescriptionExtras, DescriptionKey, ManifestPolicy, MetricDescription, MetricUnit,
+        MetricsRegistry,
+    },
+};
+use bevy::platform::collections::HashSet;
+use metrics_util::MetricKind;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+use std::{fmt, io, path::Path};
+
+/// The contents of a manifest file. See the [module docs](self).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct MetricManifest {
+    #[allow(missing_docs)]
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// The description of one metric in a [`MetricManifest`].
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    #[serde(with = "MetricKindDef")]
+    pub kind: MetricKind,
+    #[serde(default, with = "unit", skip_serializing_if = "Option::is_none")]
+    pub unit: Option<MetricUnit>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs_url: Option<String>,
+}
+
+impl MetricManifest {
+    /// Read and parse the manifest at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ManifestError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse a manifest from RON.
+    ///
+    /// Optional fields can be written without `Some(..)`. Each metric can only be described once.
+    pub fn from_ron(ron: &str) -> Result<Self, ManifestError> {
+        let options = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+        let manifest: Self = options.from_str(ron).map_err(|e| {
+            let (line, column) = (e.position.line, e.position.col);
+            let entry = entry_at(ron, offset_of(ron, line, column));
+            ManifestError::Parse {
+                line,
+                column,
+                entry: entry.map(|i| i + 1),
+                name: entry.and_then(|i| entry_name(ron, i)),
+                message: e.code.to_string(),
+                context: context(ron, line, column),
+            }
+        })?;
+
+        let mut seen = HashSet::new();
+        for (i, entry) in manifest.entries.iter().enumerate() {
+            if !seen.insert((entry.name.as_str(), entry.kind)) {
+                let line = entry_starts(ron)
+                    .get(i)
+                    .map_or(1, |&start| line_of(ron, start));
+                return Err(ManifestError::Duplicate {
+                    line,
+                    entry: i + 1,
+                    name: entry.name.clone(),
+                    kind: entry.kind,
+                    context: context(ron, line, 0),
+                });
+            }
+        }
+        drop(seen);
+        Ok(manifest)
+    }
+
+    /// Describe the manifest's metrics in `registry`, replacing any manifest
+    /// applied before. See [`MetricsRegistry::set_manifest_descriptions`].
+    pub fn apply(&self, registry: &MetricsRegistry, policy: ManifestPolicy) {
+        let descriptions = self.entries.iter().map(|entry| {
+            let key = DescriptionKey {
+                name: entry.name.clone().into(),
+                kind: entry.kind,
+            };
+            let description = MetricDescription {
+                unit: entry.unit.clone(),
+                unit_inferred: false,
+                text: entry.description.clone().into(),
+                deprecated: false,
+                extras: DescriptionExtras {
+                    docs_url: entry.docs_url.clone(),
+                    owner: None,
+                },
+            };
+            (key, description)
+        });
+        registry.set_manifest_descriptions(descriptions, policy);
+    }
+}
+
+/// Error while loading a [`MetricManifest`].
+///
+/// Lines and columns start at 1. The displayed error includes the offending
+/// lines of the file.
+#[derive(Debug)]
+pub enum ManifestError {
+    #[allow(missing_docs)]
+    Io(io::Error),
+    /// The file isn't valid RON, or an entry is malformed.
+    #[allow(missing_docs)]
+    Parse {
+        line: usize,
+        column: usize,
+        /// The number of the entry containing the error, if it's inside one.
+        entry: Option<usize>,
+        /// The name of that entry, if it has one.
+        name: Option<String>,
+        message: String,
+        context: String,
+    },
+    /// An entry describes the same metric as an earlier one.
+    #[allow(missing_docs)]
+    Duplicate {
+        line: usize,
+        entry: usize,
+        name: String,
+        kind: MetricKind,
+        context: String,
+    },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "manifest file I/O failed: {e}"),
+            Self::Parse {
+                line,
+                column,
+                entry,
+                name,
+                message,
+                context,
+            } => {
+                write!(f, "{line}:{column}: ")?;
+                match (entry, name) {
+                    (Some(entry), Some(name)) => write!(f, "in entry {entry} ({name:?}): ")?,
+                    (Some(entry), None) => write!(f, "in entry {entry}: ")?,
+                    _ => {}
+                }
+                write!(f, "{message}\n{context}")
+            }
+            Self::Duplicate {
+                line,
+                entry,
+                name,
+                kind,
+                context,
+            } => write!(
+                f,
+                "{line}: entry {entry} describes {} {name:?} again\n{context}",
+                metric_kind_str(*kind),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl From<io::Error> for ManifestError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The byte offsets of the opening parentheses of the top-level list's
+/// entries, skipping strings and comments.
+fn entry_starts(ron: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut depth = 0_usize;
+    let mut chars = ron.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.next_if(|&(_, c)| c == '/').is_some() => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '(' | '[' | '{' => {
+                if depth == 1 && c == '(' {
+                    starts.push(i);
+                }
+                depth += 1;
+            }
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    starts
+}
+
+/// The index of the entry that starts last before `offset`.
+fn entry_at(ron: &str, offset: usize) -> Option<usize> {
+    entry_starts(ron).iter().rposition(|&start| start <= offset)
+}
+
+/// The `name` field of entry `index`, if it's a plain string.
+fn entry_name(ron: &str, index: usize) -> Option<String> {
+    let starts = entry_starts(ron);
+    let start = *starts.get(index)?;
+    let end = starts.get(index + 1).copied().unwrap_or(ron.len());
+    let entry = &ron[start..end];
+    let rest = entry[entry.find("name")? + "name".len()..].trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start().strip_prefix('"')?;
+    Some(rest[..rest.find('"')?].to_owned())
+}
+
+fn offset_of(ron: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = ron
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    line_start + column.saturating_sub(1)
+}
+
+fn line_of(ron: &str, offset: usize) -> usize {
+    ron[..offset].matches('\n').count() + 1
+}
+
+/// The line before `line` and `line` itself, numbered, with a caret under
+/// `column` unless it's 0.
+fn context(ron: &str, line: usize, column: usize) -> String {
+    let first = line.saturating_sub(1).max(1);
+    let width = line.to_string().len();
+    let mut context = String::new();
+    for (number, text) in ron.lines().enumerate().map(|(i, t)| (i + 1, t)) {
+        if number < first {
+            continue;
+        }
+        if number > line {
+            break;
+        }
+        context.push_str(&format!("{number:>width$} | {text}\n"));
+    }
+    if column > 0 {
+        context.push_str(&format!("{:>width$} | {:>column$}\n", "", "^"));
+    }
+    context
+}
@@ -0,0 +1,187 @@
+//! Markdown tables of a plot's statistics, buckets and latest samples, for
+//! pasting into issue trackers.
+//!
+//! Plots copy them from the context menu of their header, with
+//! [`MetricPlot::to_markdown`](crate::plots::MetricPlot::to_markdown).
+//! Label values and other text are [escaped](escape), so they can't break
+//! the tables.
+//!
+//! ```
+//! # use bevy_metrics_dashboard::markdown;
+//! # use bevy_metrics_dashboard::number_format::NumberFormat;
+//! let numbers = NumberFormat::default();
+//! assert_eq!(
+//!     markdown::samples_table(&[(7, 1.5), (8, 2.0)], Some("ms"), &numbers, '.'),
+//!     "| Sample | Value (ms) |\n\
+//!      |---:|---:|\n\
+//!      | 7 | 1.500 |\n\
+//!      | 8 | 2.000 |\n"
+//! );
+//! assert_eq!(
+//!     markdown::bucket_table(&[1.0, 2.0], &[3, 0, 1], &numbers, '.'),
+//!     "| Bucket | Count |\n\
+//!      |---|---:|\n\
+//!      | ≤ 1.000 | 3 |\n\
+//!      | 1.000 – 2.000 | 0 |\n\
+//!      | > 2.000 | 1 |\n"
+//! );
+//! ```
+
+use crate::number_format::NumberFormat;
+use crate::plots::PlotStats;
+use std::fmt::Write;
+
+/// How a column's cells are aligned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Align {
+    #[allow(missing_docs)]
+    Left,
+    /// For numbers.
+    Right,
+}
+
+/// Escape `text` for a table cell: pipes and backslashes are escaped, and
+/// line breaks become spaces.
+///
+/// ```
+/// # use bevy_metrics_dashboard::markdown::escape;
+/// assert_eq!(escape(r"a|b\c"), r"a\|b\\c");
+/// assert_eq!(escape("two\nlines"), "two lines");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '|' => escaped.push_str("\\|"),
+            '\\' => escaped.push_str("\\\\"),
+            '\r' => {}
+            '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A table with a `header` row, one column per entry of `columns`. Cells
+/// are [escaped](escape).
+///
+/// ```
+/// # use bevy_metrics_dashboard::markdown::{table, Align};
+/// let rows = [vec!["team=a|b".to_owned(), "3".to_owned()]];
+/// assert_eq!(
+///     table(&[("Labels", Align::Left), ("Count", Align::Right)], &rows),
+///     "| Labels | Count |\n|---|---:|\n| team=a\\|b | 3 |\n"
+/// );
+/// ```
+pub fn table(columns: &[(&str, Align)], rows: &[Vec<String>]) -> String {
+    let mut markdown = String::new();
+    row(&mut markdown, columns.iter().map(|(header, _)| *header));
+    markdown.push('|');
+    for (_, align) in columns {
+        markdown.push_str(match align {
+            Align::Left => "---|",
+            Align::Right => "---:|",
+        });
+    }
+    markdown.push('\n');
+    for cells in rows {
+        row(&mut markdown, cells.iter().map(String::as_str));
+    }
+    markdown
+}
+
+fn row<'a>(markdown: &mut String, cells: impl Iterator<Item = &'a str>) {
+    markdown.push('|');
+    for cell in cells {
+        let _ = write!(markdown, " {} |", escape(cell));
+    }
+    markdown.push('\n');
+}
+
+/// One row with the number of values in the window, and their minimum,
+/// mean, 95th percentile and maximum.
+///
+/// ```
+/// # use bevy_metrics_dashboard::markdown::stats_table;
+/// # use bevy_metrics_dashboard::number_format::NumberFormat;
+/// # use bevy_metrics_dashboard::plots::PlotStats;
+/// let stats = PlotStats::new((1..=20).map(f64::from).collect()).unwrap();
+/// assert_eq!(
+///     stats_table(&stats, Some("ms"), &NumberFormat::default(), ','),
+///     "| Window | Min | Mean | p95 | Max | Unit |\n\
+///      |---:|---:|---:|---:|---:|---|\n\
+///      | 20 | 1,000 | 10,500 | 19,000 | 20,000 | ms |\n"
+/// );
+/// ```
+pub fn stats_table(
+    stats: &PlotStats,
+    unit: Option<&str>,
+    numbers: &NumberFormat,
+    decimal_separator: char,
+) -> String {
+    let number = |value| numbers.format(value, decimal_separator);
+    table(
+        &[
+            ("Window", Align::Right),
+            ("Min", Align::Right),
+            ("Mean", Align::Right),
+            ("p95", Align::Right),
+            ("Max", Align::Right),
+            ("Unit", Align::Left),
+        ],
+        &[vec![
+            stats.n_samples().to_string(),
+            number(stats.min()),
+            number(stats.mean()),
+            number(stats.percentile(95.0)),
+            number(stats.max()),
+            unit.unwrap_or_default().to_owned(),
+        ]],
+    )
+}
+
+/// One row per histogram bucket between `bounds`, with its count. A value
+/// on a bound is counted in the lower bucket.
+pub fn bucket_table(
+    bounds: &[f64],
+    counts: &[u32],
+    numbers: &NumberFormat,
+    decimal_separator: char,
+) -> String {
+    let number = |value| numbers.format(value, decimal_separator);
+    let rows: Vec<_> = counts
+        .iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let bucket = match (i.checked_sub(1).map(|i| bounds[i]), bounds.get(i)) {
+                (None, Some(&upper)) => format!("≤ {}", number(upper)),
+                (Some(lower), Some(&upper)) => format!("{} – {}", number(lower), number(upper)),
+                (Some(lower), None) => format!("> {}", number(lower)),
+                (None, None) => "all".to_owned(),
+            };
+            vec![bucket, count.to_string()]
+        })
+        .collect();
+    table(&[("Bucket", Align::Left), ("Count", Align::Right)], &rows)
+}
+
+/// One row per `(sample, value)`.
+pub fn samples_table(
+    samples: &[(u64, f64)],
+    unit: Option<&str>,
+    numbers: &NumberFormat,
+    decimal_separator: char,
+) -> String {
+    let value_header = match unit {
+        Some(unit) => format!("Value ({unit})"),
+        None => "Value".to_owned(),
+    };
+    let rows: Vec<_> = samples
+        .iter()
+        .map(|&(sample, value)| vec![sample.to_string(), numbers.format(value, decimal_separator)])
+        .collect();
+    table(
+        &[("Sample", Align::Right), (&value_header, Align::Right)],
+        &rows,
+    )
+}
@@ -0,0 +1,425 @@
+//! Performance budgets for tests and CI jobs.
+//!
+//! Declare budgets with [`MetricBudget`], add them with the
+//! [`MetricBudgetPlugin`], run the app, and then call
+//! [`MetricBudgets::evaluate`] to get a [`BudgetReport`].
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! use bevy_metrics_dashboard::metric_budget::{MetricBudget, MetricBudgetPlugin, MetricBudgets};
+//! use bevy_metrics_dashboard::registry::MetricsRegistry;
+//! use bevy_metrics_dashboard::RegistryPlugin;
+//! # use std::sync::atomic::Ordering;
+//!
+//! let registry = MetricsRegistry::new();
+//! let mut app = App::new();
+//! app.add_plugins((MinimalPlugins, RegistryPlugin::local(registry.clone())));
+//! app.add_plugins(MetricBudgetPlugin::new().budget(
+//!     MetricBudget::new("frame_time_ms")
+//!         .mean_below(14.0)
+//!         .max_below(16.0),
+//! ));
+//! // Frames of 10 ms, with a spike of 30 ms every 10 frames.
+//! let frame_time = registry.get_or_create_gauge(&metrics::Key::from_static_name("frame_time_ms"));
+//! for frame in 0..100 {
+//!     let ms: f64 = if frame % 10 == 9 { 30.0 } else { 10.0 };
+//!     frame_time.store(ms.to_bits(), Ordering::Relaxed);
+//!     app.update();
+//! }
+//! let report = app.world().resource::<MetricBudgets>().evaluate();
+//! println!("{report}");
+//!
+//! let [mean, max] = &report.results[..] else {
+//!     panic!("one result per check");
+//! };
+//! assert_eq!(mean.measured, Some(12.0));
+//! assert!(mean.passed());
+//! assert_eq!(max.measured, Some(30.0));
+//! assert!(!max.passed());
+//! assert!(!report.passed());
+//! assert_eq!(report.failures().count(), 1);
+//! ```
+
+use crate::cached_metrics::key_from_parts;
+use crate::registry::{HistogramSubscription, MetricsRegistry};
+use crate::stats::{mean, percentile};
+use crate::{metric_kind_str, BucketClearSet};
+use bevy::prelude::*;
+use metrics_util::MetricKind;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::{fmt, process::ExitCode, time::Duration};
+
+/// Which samples a [`MetricBudget`] is evaluated over.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BudgetWindow {
+    /// Every sample since the budget was added.
+    #[default]
+    WholeRun,
+    /// The samples of the last this many seconds of the run.
+    LastSeconds(f64),
+}
+
+/// A statistic of a metric that must stay below a limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BudgetCheck {
+    /// The `p`th percentile, from 0 to 100, by the nearest-rank method.
+    Percentile(f64),
+    #[allow(missing_docs)]
+    Mean,
+    #[allow(missing_docs)]
+    Max,
+}
+
+impl fmt::Display for BudgetCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percentile(p) => write!(f, "p{p}"),
+            Self::Mean => f.write_str("mean"),
+            Self::Max => f.write_str("max"),
+        }
+    }
+}
+
+/// Limits on the samples of one gauge or histogram.
+///
+/// Gauges are sampled once per frame. Histograms contribute every recorded
+/// sample, read through a [`HistogramSubscription`], so other consumers
+/// like plots still see them.
+pub struct MetricBudget {
+    key: metrics::Key,
+    kind: Option<MetricKind>,
+    window: BudgetWindow,
+    checks: Vec<(BudgetCheck, f64)>,
+    source: Option<BudgetSource>,
+    /// Samples by the time they were taken, since startup.
+    samples: VecDeque<(Duration, f64)>,
+}
+
+enum BudgetSource {
+    Gauge(Arc<AtomicU64>),
+    Histogram {
+        subscription: HistogramSubscription,
+        /// The samples that were read last, so they aren't read twice.
+        last: Option<Arc<[f64]>>,
+    },
+}
+
+impl MetricBudget {
+    /// A budget for the metric `name` without labels.
+    ///
+    /// The metric is a histogram if one with this name is registered, and
+    /// otherwise a gauge, unless [`Self::kind`] is used.
+    pub fn new(name: &str) -> Self {
+        Self::with_labels(name, &[])
+    }
+
+    /// A budget for the metric `name` with `labels`.
+    pub fn with_labels(name: &str, labels: &[(&str, &str)]) -> Self {
+        Self {
+            key: key_from_parts(name, labels),
+            kind: None,
+            window: default(),
+            checks: Vec::new(),
+            source: None,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Read the metric as a gauge or histogram, even before it's registered.
+    ///
+    /// Counters are not supported, and never have samples.
+    pub fn kind(mut self, kind: MetricKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn window(mut self, window: BudgetWindow) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Require the [`BudgetCheck`] to stay below `limit`.
+    pub fn check_below(mut self, check: BudgetCheck, limit: f64) -> Self {
+        self.checks.push((check, limit));
+        self
+    }
+
+    /// Require the `p`th percentile to stay below `limit`.
+    pub fn percentile_below(self, p: f64, limit: f64) -> Self {
+        self.check_below(BudgetCheck::Percentile(p), limit)
+    }
+
+    /// Require the 95th percentile to stay below `limit`.
+    pub fn p95_below(self, limit: f64) -> Self {
+        self.percentile_below(95.0, limit)
+    }
+
+    /// Require the 99th percentile to stay below `limit`.
+    pub fn p99_below(self, limit: f64) -> Self {
+        self.percentile_below(99.0, limit)
+    }
+
+    #[allow(missing_docs)]
+    pub fn mean_below(self, limit: f64) -> Self {
+        self.check_below(BudgetCheck::Mean, limit)
+    }
+
+    #[allow(missing_docs)]
+    pub fn max_below(self, limit: f64) -> Self {
+        self.check_below(BudgetCheck::Max, limit)
+    }
+
+    #[allow(missing_docs)]
+    pub fn key(&self) -> &metrics::Key {
+        &self.key
+    }
+
+    /// The number of samples the budget would be evaluated over now.
+    pub fn n_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Take the samples recorded since the last call.
+    fn sample(&mut self, registry: &MetricsRegistry, now: Duration) {
+        if self.source.is_none() {
+            self.source = self.find_source(registry);
+        }
+        match &mut self.source {
+            Some(BudgetSource::Gauge(atomic)) => {
+                let value = f64::from_bits(atomic.load(Ordering::Relaxed));
+                self.samples.push_back((now, value));
+            }
+            Some(BudgetSource::Histogram { subscription, last }) => {
+                let samples = subscription.samples();
+                if last
+                    .as_ref()
+                    .is_some_and(|last| Arc::ptr_eq(last, &samples))
+                {
+                    return;
+                }
+                self.samples.extend(samples.iter().map(|&v| (now, v)));
+                *last = Some(samples);
+            }
+            None => return,
+        }
+
+        if let BudgetWindow::LastSeconds(seconds) = self.window {
+            let window = Duration::try_from_secs_f64(seconds).unwrap_or_default();
+            while self
+                .samples
+                .front()
+                .is_some_and(|&(time, _)| now.saturating_sub(time) > window)
+            {
+                self.samples.pop_front();
+            }
+        }
+    }
+
+    fn find_source(&self, registry: &MetricsRegistry) -> Option<BudgetSource> {
+        let kind = self.kind.or_else(|| {
+            if registry.get_histogram(&self.key).is_some() {
+                Some(MetricKind::Histogram)
+            } else if registry.get_gauge(&self.key).is_some() {
+                Some(MetricKind::Gauge)
+            } else {
+                None
+            }
+        })?;
+        match kind {
+            MetricKind::Gauge => Some(BudgetSource::Gauge(registry.get_or_create_gauge(&self.key))),
+            MetricKind::Histogram => Some(BudgetSource::Histogram {
+                subscription: registry.subscribe_histogram(self.key.clone()),
+                last: None,
+            }),
+            MetricKind::Counter => None,
+        }
+    }
+
+    fn evaluate(&self) -> Vec<BudgetResult> {
+        let mut values: Vec<f64> = self.samples.iter().map(|&(_, v)| v).collect();
+        let kind = match &self.source {
+            Some(BudgetSource::Gauge(_)) => Some(MetricKind::Gauge),
+            Some(BudgetSource::Histogram { .. }) => Some(MetricKind::Histogram),
+            None => None,
+        };
+        self.checks
+            .iter()
+            .map(|&(check, limit)| {
+                let measured = match check {
+                    BudgetCheck::Percentile(p) => percentile(&mut values, p),
+                    BudgetCheck::Mean => mean(&values),
+                    BudgetCheck::Max => values.iter().copied().reduce(f64::max),
+                };
+                BudgetResult {
+                    key: self.key.clone(),
+                    kind,
+                    check,
+                    limit,
+                    measured,
+                    n_samples: values.len(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The outcome of one [`BudgetCheck`] of a [`MetricBudget`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetResult {
+    #[allow(missing_docs)]
+    pub key: metrics::Key,
+    /// The kind the metric was read as, or `None` if it was never found.
+    pub kind: Option<MetricKind>,
+    #[allow(missing_docs)]
+    pub check: BudgetCheck,
+    #[allow(missing_docs)]
+    pub limit: f64,
+    /// The value of the statistic, or `None` if there were no samples.
+    pub measured: Option<f64>,
+    #[allow(missing_docs)]
+    pub n_samples: usize,
+}
+
+impl BudgetResult {
+    /// Whether the measured value is below the limit. Budgets without
+    /// samples fail.
+    pub fn passed(&self) -> bool {
+        self.measured.is_some_and(|measured| measured < self.limit)
+    }
+}
+
+impl fmt::Display for BudgetResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.passed() { "PASS" } else { "FAIL" };
+        let kind = self.kind.map_or("missing", metric_kind_str);
+        write!(f, "{status} {} ({kind}) {} ", self.key.name(), self.check)?;
+        match self.measured {
+            Some(measured) => write!(f, "{measured:.3}")?,
+            None => f.write_str("no samples")?,
+        }
+        write!(f, " < {} over {} samples", self.limit, self.n_samples)
+    }
+}
+
+/// The results of [`MetricBudgets::evaluate`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BudgetReport {
+    /// One per check of each budget, in the order they were added.
+    pub results: Vec<BudgetResult>,
+}
+
+impl BudgetReport {
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(BudgetResult::passed)
+    }
+
+    /// The checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &BudgetResult> {
+        self.results.iter().filter(|r| !r.passed())
+    }
+
+    /// [`ExitCode::SUCCESS`] if every check passed, and [`ExitCode::FAILURE`]
+    /// otherwise.
+    pub fn exit_code(&self) -> ExitCode {
+        if self.passed() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl fmt::Display for BudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{result}")?;
+        }
+        let n_failed = self.failures().count();
+        write!(
+            f,
+            "{} of {} budget checks passed",
+            self.results.len() - n_failed,
+            self.results.len()
+        )
+    }
+}
+
+/// All budgets sampled by the [`MetricBudgetPlugin`].
+#[derive(Default, Resource)]
+pub struct MetricBudgets {
+    budgets: Vec<MetricBudget>,
+}
+
+impl MetricBudgets {
+    #[allow(missing_docs)]
+    pub fn add(&mut self, budget: MetricBudget) {
+        self.budgets.push(budget);
+    }
+
+    #[allow(missing_docs)]
+    pub fn budgets(&self) -> &[MetricBudget] {
+        &self.budgets
+    }
+
+    /// Check every budget against the samples taken so far.
+    pub fn evaluate(&self) -> BudgetReport {
+        BudgetReport {
+            results: self
+                .budgets
+                .iter()
+                .flat_map(MetricBudget::evaluate)
+                .collect(),
+        }
+    }
+
+    /// Bevy system that samples every budget's metric.
+    ///
+    /// This runs in [`Last`] after the [`BucketClearSet`], when the
+    /// samples of subscribed histograms are published.
+    pub fn sample_system(
+        registry: Res<MetricsRegistry>,
+        time: Res<Time<Real>>,
+        mut budgets: ResMut<Self>,
+    ) {
+        let now = time.elapsed();
+        for budget in &mut budgets.budgets {
+            budget.sample(&registry, now);
+        }
+    }
+}
+
+/// Samples [`MetricBudget`]s every frame into the [`MetricBudgets`]
+/// resource.
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin) and the
+/// [`TimePlugin`](bevy::time::TimePlugin).
+#[derive(Default)]
+pub struct MetricBudgetPlugin {
+    budgets: Mutex<Vec<MetricBudget>>,
+}
+
+impl MetricBudgetPlugin {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a budget. More can be added to the [`MetricBudgets`] resource
+    /// later.
+    pub fn budget(self, budget: MetricBudget) -> Self {
+        self.budgets.lock().unwrap().push(budget);
+        self
+    }
+}
+
+impl Plugin for MetricBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        let budgets = std::mem::take(&mut *self.budgets.lock().unwrap());
+        app.insert_resource(MetricBudgets { budgets })
+            .add_systems(Last, MetricBudgets::sample_system.after(BucketClearSet));
+    }
+}
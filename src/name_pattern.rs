@@ -0,0 +1,87 @@
+use regex::Regex;
+use std::borrow::Cow;
+
+/// A glob matched against the whole metric name, ignoring labels. `*`
+/// matches any number of characters, including namespace separators, and
+/// `?` matches one.
+///
+/// Used by [`PlotDefaults`](crate::plot_defaults::PlotDefaults) and
+/// [`RetentionPolicy::protect`](crate::retention::RetentionPolicy::protect).
+/// [`DisplayNames`](crate::display_names::DisplayNames) compile their
+/// regular expressions into it too.
+///
+/// ```
+/// # use bevy_metrics_dashboard::NamePattern;
+/// let pattern = NamePattern::new("frame_time*");
+/// assert!(pattern.matches("frame_time_ms"));
+/// assert!(!pattern.matches("render::frame_time"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct NamePattern {
+    source: String,
+    regex: Regex,
+    glob: bool,
+}
+
+impl NamePattern {
+    #[allow(missing_docs)]
+    pub fn new(glob: impl Into<String>) -> Self {
+        let source = glob.into();
+        let mut regex = String::from("^");
+        let mut buf = [0; 4];
+        for c in source.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(c.encode_utf8(&mut buf))),
+            }
+        }
+        regex.push('$');
+        let regex = Regex::new(&regex).expect("escaped glob is a valid regex");
+        Self {
+            source,
+            regex,
+            glob: true,
+        }
+    }
+
+    /// A pattern that matches wherever the regular expression `pattern`
+    /// matches, rather than a glob.
+    pub(crate) fn regex(pattern: impl Into<String>) -> Result<Self, regex::Error> {
+        let source = pattern.into();
+        let regex = Regex::new(&source)?;
+        Ok(Self {
+            source,
+            regex,
+            glob: false,
+        })
+    }
+
+    /// The glob or regular expression this pattern was created from.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether `name` matches the pattern.
+    pub fn matches(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+
+    /// Replace the first match in `value` with `replacement`, which may refer
+    /// to capture groups like `$1`.
+    pub(crate) fn replace<'a>(&self, value: &'a str, replacement: &str) -> Cow<'a, str> {
+        self.regex.replace(value, replacement)
+    }
+}
+
+impl PartialEq for NamePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.glob == other.glob && self.source == other.source
+    }
+}
+
+impl From<&str> for NamePattern {
+    fn from(glob: &str) -> Self {
+        Self::new(glob)
+    }
+}
@@ -0,0 +1,165 @@
+//! Helpers for naming metrics hierarchically, so that the
+//! [`NamespaceTreeWindow`](crate::NamespaceTreeWindow) can group them.
+//!
+//! [`metric_path!`](crate::metric_path) joins literal components at compile
+//! time, and a [`Namespace`] prefixes names given at runtime and caches the
+//! handles of its metrics. Both use the [`DEFAULT_NAMESPACE_SEPARATOR`],
+//! which is also the default [`DashboardConfig::namespace_separator`](crate::DashboardConfig::namespace_separator).
+//!
+//! ```
+//! # use bevy_metrics_dashboard::metric_path;
+//! # use bevy_metrics_dashboard::namespace::Namespace;
+//! let ai = Namespace::new(metric_path!("game", "ai"));
+//! assert_eq!(ai.name("requests"), "game::ai::requests");
+//! assert_eq!(ai.child("pathfinding").path(), "game::ai::pathfinding");
+//! assert_eq!(
+//!     metric_path!("game", "ai", "pathfinding", "requests"),
+//!     "game::ai::pathfinding::requests"
+//! );
+//! ```
+
+use crate::DEFAULT_NAMESPACE_SEPARATOR;
+use bevy::platform::collections::HashMap;
+use metrics::{Counter, Gauge, Histogram};
+use std::sync::Mutex;
+
+/// Expands to the [`DEFAULT_NAMESPACE_SEPARATOR`] as a literal, for
+/// [`metric_path!`](crate::metric_path).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __default_namespace_separator {
+    () => {
+        "::"
+    };
+}
+
+/// Join string literals with the [`DEFAULT_NAMESPACE_SEPARATOR`](crate::DEFAULT_NAMESPACE_SEPARATOR)
+/// into a `&'static str` metric name.
+///
+/// Names built at runtime, or with another separator, can use a
+/// [`Namespace`](crate::namespace::Namespace) instead.
+///
+/// ```
+/// # use bevy_metrics_dashboard::metric_path;
+/// metrics::counter!(metric_path!("game", "ai", "requests")).increment(1);
+/// assert_eq!(metric_path!("game"), "game");
+/// ```
+#[macro_export]
+macro_rules! metric_path {
+    ($first:literal $(, $rest:literal)* $(,)?) => {
+        concat!($first $(, $crate::__default_namespace_separator!(), $rest)*)
+    };
+}
+
+/// A prefix for metric names, e.g. `game::ai`, with the handles of the
+/// metrics created under it.
+///
+/// Handles are created with the `metrics` macros, so they're registered with
+/// the global recorder, and cached by name. Cloning a handle is cheap, but
+/// looking it up still hashes the name, so store handles written every frame.
+///
+/// ```
+/// # use bevy_metrics_dashboard::namespace::Namespace;
+/// let pathfinding = Namespace::new("game::ai").child("pathfinding");
+/// pathfinding.counter("requests").increment(1);
+/// pathfinding.histogram("search_ms").record(0.4);
+/// assert_eq!(pathfinding.name("requests"), "game::ai::pathfinding::requests");
+///
+/// // Match a custom `DashboardConfig::namespace_separator`.
+/// let ai = Namespace::with_separator("game/ai", "/");
+/// assert_eq!(ai.name("requests"), "game/ai/requests");
+/// ```
+#[derive(Debug)]
+pub struct Namespace {
+    path: String,
+    separator: String,
+    counters: Mutex<HashMap<String, Counter>>,
+    gauges: Mutex<HashMap<String, Gauge>>,
+    histograms: Mutex<HashMap<String, Histogram>>,
+}
+
+impl Namespace {
+    /// A namespace with the [`DEFAULT_NAMESPACE_SEPARATOR`].
+    pub fn new(path: impl Into<String>) -> Self {
+        Self::with_separator(path, DEFAULT_NAMESPACE_SEPARATOR)
+    }
+
+    /// A namespace whose components are joined with `separator`, which
+    /// should match the [`DashboardConfig::namespace_separator`](crate::DashboardConfig::namespace_separator).
+    pub fn with_separator(path: impl Into<String>, separator: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            separator: separator.into(),
+            counters: default_handles(),
+            gauges: default_handles(),
+            histograms: default_handles(),
+        }
+    }
+
+    /// The prefix of names in this namespace, without a trailing separator.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    #[allow(missing_docs)]
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    /// The full name of the metric `name` in this namespace. In an empty
+    /// namespace, this is `name` itself.
+    pub fn name(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}{}{name}", self.path, self.separator)
+        }
+    }
+
+    /// The namespace `name` nested in this one, with the same separator.
+    pub fn child(&self, name: &str) -> Self {
+        Self::with_separator(self.name(name), self.separator.clone())
+    }
+
+    /// The counter `name` in this namespace, created on first use.
+    pub fn counter(&self, name: &str) -> Counter {
+        cached(&self.counters, name, || metrics::counter!(self.name(name)))
+    }
+
+    /// Like [`Self::counter`], for a gauge.
+    pub fn gauge(&self, name: &str) -> Gauge {
+        cached(&self.gauges, name, || metrics::gauge!(self.name(name)))
+    }
+
+    /// Like [`Self::counter`], for a histogram.
+    pub fn histogram(&self, name: &str) -> Histogram {
+        cached(&self.histograms, name, || {
+            metrics::histogram!(self.name(name))
+        })
+    }
+}
+
+impl Clone for Namespace {
+    /// Clones the path and separator. Handles are cached again by the clone.
+    fn clone(&self) -> Self {
+        Self::with_separator(self.path.clone(), self.separator.clone())
+    }
+}
+
+fn default_handles<T>() -> Mutex<HashMap<String, T>> {
+    Mutex::new(HashMap::default())
+}
+
+fn cached<T: Clone>(
+    handles: &Mutex<HashMap<String, T>>,
+    name: &str,
+    create: impl FnOnce() -> T,
+) -> T {
+    let mut handles = handles.lock().unwrap();
+    if let Some(handle) = handles.get(name) {
+        return handle.clone();
+    }
+    let handle = create();
+    handles.insert(name.to_owned(), handle.clone());
+    handle
+}
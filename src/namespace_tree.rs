@@ -1,29 +1,45 @@
+use crate::context_menu::metric_context_menu;
 use crate::egui::{self, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::plots::{GaugePlotConfig, MetricPlotConfig};
 use crate::registry::{MetricsRegistry, SearchResult};
 use bevy::{
     prelude::*,
     tasks::{block_on, AsyncComputeTaskPool, Task},
 };
+use metrics_util::MetricKind;
 use std::{
     sync::atomic::{AtomicU64, Ordering},
     time::{Duration, Instant},
 };
 
 #[cfg(feature = "bevy_egui")]
-use crate::RequestPlot;
+use crate::{DashboardConfig, RequestPlot};
+
+/// The default delimiter between namespace components in metric names.
+///
+/// [`metric_path!`](crate::metric_path) and [`Namespace`](crate::namespace::Namespace)
+/// name metrics with it.
+pub const DEFAULT_NAMESPACE_SEPARATOR: &str = crate::__default_namespace_separator!();
 
 /// A widget that shows all metrics metadata in a tree, grouped by namespace.
 ///
 /// For example, a metric with name "foo::bar::baz" would be found by expanding
 /// "foo", then "bar", then "baz".
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component, from_reflect = false)]
 pub struct NamespaceTreeWindow {
     title: String,
+    #[reflect(ignore)]
     id: egui::Id,
+    separator: String,
     refresh_period: Duration,
     is_new: bool,
+    #[reflect(ignore)]
     last_refresh_time: Instant,
+    #[reflect(ignore)]
     refresh_task: Option<Task<Vec<NamespaceNode>>>,
+    #[reflect(ignore)]
     roots: Vec<NamespaceNode>,
 }
 
@@ -37,6 +53,7 @@ impl NamespaceTreeWindow {
         Self {
             title,
             id,
+            separator: DEFAULT_NAMESPACE_SEPARATOR.into(),
             refresh_period: Duration::from_secs(5),
             is_new: true,
             last_refresh_time: Instant::now(),
@@ -55,6 +72,22 @@ impl NamespaceTreeWindow {
         &self.id
     }
 
+    /// Set the delimiter between namespace components.
+    ///
+    /// An empty separator is replaced by [`DEFAULT_NAMESPACE_SEPARATOR`]. The
+    /// tree is rebuilt if the separator changed.
+    pub fn set_separator(&mut self, separator: &str) {
+        let separator = if separator.is_empty() {
+            DEFAULT_NAMESPACE_SEPARATOR
+        } else {
+            separator
+        };
+        if self.separator != separator {
+            self.separator = separator.into();
+            self.is_new = true;
+        }
+    }
+
     /// Set the time between updates of the tree.
     pub fn set_refresh_period(&mut self, period: Duration) {
         self.refresh_period = period;
@@ -65,24 +98,36 @@ impl NamespaceTreeWindow {
     pub fn draw_all(
         mut commands: Commands,
         registry: Res<MetricsRegistry>,
+        config: Res<DashboardConfig>,
+        localization: Res<DashboardLocalization>,
         mut ctxts: bevy_egui::EguiContexts,
         mut requests: EventWriter<RequestPlot>,
         mut windows: Query<(Entity, &mut Self)>,
     ) {
+        if !config.visible {
+            return;
+        }
+
         let ctxt = ctxts.ctx_mut();
         for (entity, mut window) in &mut windows {
+            window.set_separator(&config.namespace_separator);
+
             let mut open = true;
-            egui::Window::new(&window.title)
-                .id(window.id)
-                .open(&mut open)
-                .show(ctxt, |ui| {
-                    if let Some(result) = window.draw(&registry, ui) {
-                        requests.write(crate::RequestPlot {
-                            key: result.key,
-                            unit: result.description.and_then(|d| d.unit),
+            localization.scope(ctxt, |ctxt| {
+                config.theme.scope(ctxt, |ctxt| {
+                    egui::Window::new(&window.title)
+                        .id(window.id)
+                        .open(&mut open)
+                        .show(ctxt, |ui| {
+                            if let Some((result, config)) = window.draw_with_config(&registry, ui) {
+                                let unit = result.description.and_then(|d| d.unit);
+                                let mut request = crate::RequestPlot::new(result.key, unit);
+                                request.config = config;
+                                requests.write(request);
+                            }
                         });
-                    }
-                });
+                })
+            });
             if !open {
                 commands.entity(entity).despawn();
             }
@@ -93,12 +138,24 @@ impl NamespaceTreeWindow {
     ///
     /// If the user selects a metric, it will be returned.
     pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) -> Option<SearchResult> {
+        self.draw_with_config(registry, ui)
+            .map(|(result, _)| result)
+    }
+
+    /// Like [`Self::draw`], but also returns the plot config if the user
+    /// chose a specific way of plotting the metric, e.g. as a dial.
+    pub fn draw_with_config(
+        &mut self,
+        registry: &MetricsRegistry,
+        ui: &mut Ui,
+    ) -> Option<(SearchResult, Option<MetricPlotConfig>)> {
         if self.is_new || self.last_refresh_time.elapsed() > self.refresh_period {
             self.is_new = false;
             let task_registry = registry.clone();
+            let separator = self.separator.clone();
             self.refresh_task = Some(AsyncComputeTaskPool::get().spawn(async move {
                 let mut results = task_registry.all_metrics();
-                NamespaceNode::tree_from_results(&mut results)
+                NamespaceNode::tree_from_results(&mut results, &separator)
             }));
             self.last_refresh_time = Instant::now();
         }
@@ -119,7 +176,11 @@ impl NamespaceTreeWindow {
         selected
     }
 
-    fn draw_recursive(nodes: &[NamespaceNode], selected: &mut Option<SearchResult>, ui: &mut Ui) {
+    fn draw_recursive(
+        nodes: &[NamespaceNode],
+        selected: &mut Option<(SearchResult, Option<MetricPlotConfig>)>,
+        ui: &mut Ui,
+    ) {
         for node in nodes {
             match node {
                 NamespaceNode::Namespace {
@@ -135,10 +196,21 @@ impl NamespaceTreeWindow {
                     result,
                 } => {
                     ui.horizontal(|ui| {
-                        if ui.button("Plot").clicked() {
-                            *selected = Some(result.clone());
+                        let l10n = DashboardLocalization::of(ui);
+                        if ui.button(l10n.get(UiText::Plot)).clicked() {
+                            *selected = Some((result.clone(), None));
+                        }
+                        if result.key.kind == MetricKind::Gauge
+                            && ui.button(l10n.get(UiText::Dial)).clicked()
+                        {
+                            let config = MetricPlotConfig::Gauge(GaugePlotConfig::dial());
+                            *selected = Some((result.clone(), Some(config)));
                         }
-                        ui.label(result.detailed_text(Some(display_path)));
+                        let response = ui.add(
+                            egui::Label::new(result.detailed_text(Some(display_path), ui))
+                                .sense(egui::Sense::click()),
+                        );
+                        metric_context_menu(&response, result);
                     });
                 }
             }
@@ -158,16 +230,18 @@ enum NamespaceNode {
 }
 
 impl NamespaceNode {
-    fn tree_from_results(results: &mut [SearchResult]) -> Vec<Self> {
-        results.sort_unstable_by(|r1, r2| r1.key.key.name().cmp(r2.key.key.name()));
-        Self::tree_from_sorted_results_recursive(results, 0)
+    fn tree_from_results(results: &mut [SearchResult], separator: &str) -> Vec<Self> {
+        results.sort_unstable_by(|r1, r2| r1.name().cmp(r2.name()));
+        Self::tree_from_sorted_results_recursive(results, separator, 0)
     }
 
     fn tree_from_sorted_results_recursive(
         mut results: &[SearchResult],
+        separator: &str,
         path_start: usize,
     ) -> Vec<Self> {
-        const DELIM: &str = "::";
+        // Paths that start or end with any part of the separator are invalid.
+        let separator_char = separator.chars().next().unwrap();
 
         // Having sorted results allows us to create a subtree from a range
         // of results.
@@ -176,44 +250,41 @@ impl NamespaceNode {
         // tactic of cloning results into the tree instead of moving them.
         let mut nodes = Vec::new();
         while let Some(first_result) = results.first() {
-            let first_path = &first_result.key.key.name()[path_start..];
-            if first_path.starts_with(':') {
+            let first_path = &first_result.name()[path_start..];
+            if first_path.starts_with(separator_char) {
                 // Skip invalid path.
                 results = &results[1..];
                 continue;
             }
 
-            if let Some((group_name, _)) =
-                first_result.key.key.name()[path_start..].split_once(DELIM)
-            {
+            if let Some((group_name, _)) = first_result.name()[path_start..].split_once(separator) {
                 // Split a group off the front of the results.
                 let group_end = results
                     .iter()
-                    .position(|r| !r.key.key.name()[path_start..].starts_with(group_name))
+                    .position(|r| !r.name()[path_start..].starts_with(group_name))
                     .unwrap_or(results.len());
                 let (group, rem) = results.split_at(group_end);
 
                 // Recurse and create node from children.
-                let new_path_start = path_start + group_name.len() + DELIM.len();
-                let children = Self::tree_from_sorted_results_recursive(group, new_path_start);
-                if let Some(node) = Self::create_parent_node(group_name, children) {
+                let new_path_start = path_start + group_name.len() + separator.len();
+                let children =
+                    Self::tree_from_sorted_results_recursive(group, separator, new_path_start);
+                if let Some(node) = Self::create_parent_node(group_name, children, separator) {
                     nodes.push(node);
                 }
                 results = rem;
             } else {
                 // No delimiter. This result is a leaf.
                 let (leaf_result, rem) = results.split_first().unwrap();
-                let leaf_name = leaf_result.key.key.name();
-                let is_invalid_path = leaf_name.is_empty() || leaf_name.ends_with(':');
+                let leaf_name = leaf_result.name();
+                let is_invalid_path = leaf_name.is_empty() || leaf_name.ends_with(separator_char);
                 if !is_invalid_path {
                     // Only display last component of path.
                     let display_path = leaf_result
-                        .key
-                        .key
                         .name()
-                        .rsplit_once(':')
+                        .rsplit_once(separator_char)
                         .map(|(_, end)| end)
-                        .unwrap_or(leaf_result.key.key.name());
+                        .unwrap_or(leaf_result.name());
                     nodes.push(Self::Metric {
                         display_path: display_path.into(),
                         result: leaf_result.clone(),
@@ -225,7 +296,7 @@ impl NamespaceNode {
         nodes
     }
 
-    fn create_parent_node(group_name: &str, children: Vec<Self>) -> Option<Self> {
+    fn create_parent_node(group_name: &str, children: Vec<Self>, separator: &str) -> Option<Self> {
         match children.len() {
             0 => None,
             1 => {
@@ -234,14 +305,14 @@ impl NamespaceNode {
                         display_path: path_component,
                         children,
                     } => Self::Namespace {
-                        display_path: format!("{group_name}::{path_component}"),
+                        display_path: format!("{group_name}{separator}{path_component}"),
                         children,
                     },
                     Self::Metric {
                         display_path,
                         result,
                     } => Self::Metric {
-                        display_path: format!("{group_name}::{display_path}"),
+                        display_path: format!("{group_name}{separator}{display_path}"),
                         result,
                     },
                 };
@@ -0,0 +1,267 @@
+//! How values are formatted in plots: their latest values, axis ticks,
+//! hover labels and statistics.
+//!
+//! See [`PlotStyle::numbers`](crate::plot_style::PlotStyle::numbers).
+
+use crate::egui::{ComboBox, DragValue, Ui};
+use crate::egui_plot::{GridMark, PlotPoint};
+use crate::localization::{DashboardLocalization, UiText};
+use bevy::prelude::Reflect;
+use std::ops::RangeInclusive;
+
+/// How values are formatted, before their unit is appended.
+///
+/// ```
+/// # use bevy_metrics_dashboard::number_format::{NumberFormat, Precision};
+/// let format = NumberFormat {
+///     precision: Precision::SignificantDigits(3),
+///     thousands_separator: Some(','),
+///     scientific_threshold: Some(1e6),
+/// };
+/// assert_eq!(format.format(0.016666666, '.'), "0.0167");
+/// assert_eq!(format.format(12345.678, '.'), "12,300");
+/// assert_eq!(format.format(1.2e-7, '.'), "1.20e-7");
+/// assert_eq!(format.format(-98765432.0, ','), "-9,88e7");
+///
+/// // The default matches how values were formatted before it existed.
+/// let default = NumberFormat::default();
+/// assert_eq!(default.format(0.016666666, '.'), "0.017");
+/// assert_eq!(default.format(1234567.0, '.'), "1234567.000");
+/// ```
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct NumberFormat {
+    #[allow(missing_docs)]
+    pub precision: Precision,
+    /// When `Some`, groups the digits before the decimal separator in
+    /// thousands with this character.
+    pub thousands_separator: Option<char>,
+    /// When `Some`, values at least this large, or smaller than its
+    /// reciprocal, are shown in scientific notation, like `1.20e-5`. Zero
+    /// never is.
+    pub scientific_threshold: Option<f64>,
+}
+
+/// How many digits a [`NumberFormat`] shows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum Precision {
+    /// A fixed number of digits after the decimal separator.
+    Decimals(usize),
+    /// This many digits from the first non-zero one, rounding larger values
+    /// to tens, hundreds and so on.
+    SignificantDigits(usize),
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            precision: Precision::Decimals(3),
+            thousands_separator: None,
+            scientific_threshold: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Format `value` with `decimal_separator`, usually the
+    /// [`DashboardLocalization::decimal_separator`].
+    pub fn format(&self, value: f64, decimal_separator: char) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        if self.is_scientific(value) {
+            let decimals = match self.precision {
+                Precision::Decimals(n) => n,
+                Precision::SignificantDigits(n) => n.saturating_sub(1),
+            };
+            let text = format!("{value:.decimals$e}");
+            return text.replace('.', decimal_separator.encode_utf8(&mut [0; 4]));
+        }
+        let decimals = match self.precision {
+            Precision::Decimals(n) => n as i32,
+            Precision::SignificantDigits(n) if value == 0.0 => n.saturating_sub(1) as i32,
+            Precision::SignificantDigits(n) => {
+                n.max(1) as i32 - 1 - value.abs().log10().floor() as i32
+            }
+        };
+        self.fixed(value, decimals, decimal_separator)
+    }
+
+    /// Like [`Self::format`], followed by `unit`.
+    pub fn format_with_unit(
+        &self,
+        value: f64,
+        unit: Option<&str>,
+        decimal_separator: char,
+    ) -> String {
+        let text = self.format(value, decimal_separator);
+        match unit {
+            Some(unit) => format!("{text} {unit}"),
+            None => text,
+        }
+    }
+
+    /// Format an axis tick at `mark`, with as many decimals as the distance
+    /// between ticks needs, instead of [`Self::precision`]. The thousands
+    /// separator and scientific notation still apply.
+    pub fn format_tick(&self, mark: GridMark, decimal_separator: char) -> String {
+        if self.is_scientific(mark.value) {
+            return self.format(mark.value, decimal_separator);
+        }
+        let decimals = (-mark.step_size.log10().round()).max(0.0) as i32;
+        self.fixed(mark.value, decimals, decimal_separator)
+    }
+
+    /// A formatter for the ticks of the axis that shows values.
+    pub fn axis_formatter(
+        &self,
+        decimal_separator: char,
+    ) -> impl Fn(GridMark, &RangeInclusive<f64>) -> String {
+        let format = self.clone();
+        move |mark, _| format.format_tick(mark, decimal_separator)
+    }
+
+    /// A formatter for the label shown when hovering a time series, with
+    /// the sample as an integer and the value formatted.
+    pub fn hover_formatter(&self, decimal_separator: char) -> impl Fn(&str, &PlotPoint) -> String {
+        let format = self.clone();
+        move |name, point| {
+            let y = format.format(point.y, decimal_separator);
+            let x = point.x.round();
+            if name.is_empty() {
+                format!("x = {x}\ny = {y}")
+            } else {
+                format!("{name}\nx = {x}\ny = {y}")
+            }
+        }
+    }
+
+    fn is_scientific(&self, value: f64) -> bool {
+        self.scientific_threshold.is_some_and(|threshold| {
+            let magnitude = value.abs();
+            magnitude != 0.0 && (magnitude >= threshold || magnitude < threshold.recip())
+        })
+    }
+
+    /// `value` with `decimals` digits after the separator, or rounded to a
+    /// power of ten if negative.
+    fn fixed(&self, value: f64, decimals: i32, decimal_separator: char) -> String {
+        let text = if decimals >= 0 {
+            format!("{value:.0$}", decimals as usize)
+        } else {
+            let scale = 10f64.powi(-decimals);
+            format!("{:.0}", (value / scale).round() * scale)
+        };
+        let (integer, fraction) = match text.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (text.as_str(), None),
+        };
+        let mut result = match self.thousands_separator {
+            Some(separator) => group_thousands(integer, separator),
+            None => integer.to_owned(),
+        };
+        if let Some(fraction) = fraction {
+            result.push(decimal_separator);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Draw the settings and accept user input. Returns true if any changed.
+    pub fn configure_ui(&mut self, ui: &mut Ui) -> bool {
+        let l10n = DashboardLocalization::of(ui);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            let (label, mut digits) = match self.precision {
+                Precision::Decimals(n) => (UiText::Decimals, n),
+                Precision::SignificantDigits(n) => (UiText::SignificantDigits, n),
+            };
+            ComboBox::from_id_salt(ui.id().with("number-precision"))
+                .selected_text(l10n.get(label))
+                .show_ui(ui, |ui| {
+                    for (precision, label) in [
+                        (Precision::Decimals(digits), UiText::Decimals),
+                        (
+                            Precision::SignificantDigits(digits.max(1)),
+                            UiText::SignificantDigits,
+                        ),
+                    ] {
+                        changed |= ui
+                            .selectable_value(&mut self.precision, precision, l10n.get(label))
+                            .changed();
+                    }
+                });
+            let min = match self.precision {
+                Precision::Decimals(_) => 0,
+                Precision::SignificantDigits(_) => 1,
+            };
+            if ui
+                .add(DragValue::new(&mut digits).range(min..=12))
+                .changed()
+            {
+                changed = true;
+                self.precision = match self.precision {
+                    Precision::Decimals(_) => Precision::Decimals(digits),
+                    Precision::SignificantDigits(_) => Precision::SignificantDigits(digits),
+                };
+            }
+
+            let mut grouped = self.thousands_separator.is_some();
+            if ui
+                .checkbox(&mut grouped, l10n.get(UiText::ThousandsSeparator))
+                .changed()
+            {
+                changed = true;
+                // Whichever of `,` and `.` isn't the decimal separator.
+                let separator = if l10n.decimal_separator == ',' {
+                    '.'
+                } else {
+                    ','
+                };
+                self.thousands_separator = grouped.then_some(separator);
+            }
+
+            let mut scientific = self.scientific_threshold.is_some();
+            if ui
+                .checkbox(&mut scientific, l10n.get(UiText::ScientificAbove))
+                .changed()
+            {
+                changed = true;
+                self.scientific_threshold = scientific.then_some(DEFAULT_SCIENTIFIC_THRESHOLD);
+            }
+            if let Some(threshold) = &mut self.scientific_threshold {
+                changed |= ui
+                    .add(
+                        DragValue::new(threshold)
+                            .range(1.0..=f64::MAX)
+                            .custom_formatter(|v, _| format!("{v:e}")),
+                    )
+                    .changed();
+            }
+        });
+        changed
+    }
+}
+
+/// The threshold chosen when enabling
+/// [`NumberFormat::scientific_threshold`] in the UI.
+pub const DEFAULT_SCIENTIFIC_THRESHOLD: f64 = 1e6;
+
+/// `integer`, which may start with a minus sign, with `separator` between
+/// each group of three digits.
+fn group_thousands(integer: &str, separator: char) -> String {
+    let (sign, digits) = match integer.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", integer),
+    };
+    let mut result = String::from(sign);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(separator);
+        }
+        result.push(digit);
+    }
+    result
+}
@@ -0,0 +1,674 @@
+//! Export metrics to an OpenTelemetry collector over OTLP/HTTP.
+//!
+//! See [`OtlpExporterPlugin`].
+
+use crate::registry::{
+    DescriptionKey, HistogramSubscription, MetricDescription, MetricUnit, MetricsRegistry,
+};
+use crate::BucketClearSet;
+use bevy::{platform::collections::HashMap, prelude::*};
+use metrics::Unit;
+use metrics_util::MetricKind;
+use opentelemetry_proto::tonic::{
+    collector::metrics::v1::ExportMetricsServiceRequest,
+    common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue},
+    metrics::v1::{
+        metric, number_data_point, AggregationTemporality, Gauge, Histogram, HistogramDataPoint,
+        Metric, NumberDataPoint, ResourceMetrics, ScopeMetrics, Sum,
+    },
+    resource::v1::Resource,
+};
+use prost::Message;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub use opentelemetry_proto;
+
+/// The default bounds of exported histogram buckets, as recommended by the
+/// OpenTelemetry SDK specification.
+pub const DEFAULT_HISTOGRAM_BOUNDS: [f64; 15] = [
+    0.0, 5.0, 10.0, 25.0, 50.0, 75.0, 100.0, 250.0, 500.0, 750.0, 1000.0, 2500.0, 5000.0, 7500.0,
+    10000.0,
+];
+
+/// The [UCUM](https://ucum.org/ucum) code of `unit`, as used by OpenTelemetry.
+///
+/// ```
+/// # use bevy_metrics_dashboard::otlp::ucum_unit;
+/// # use metrics::Unit;
+/// assert_eq!(ucum_unit(Unit::Count), "1");
+/// assert_eq!(ucum_unit(Unit::Microseconds), "us");
+/// assert_eq!(ucum_unit(Unit::Mebibytes), "MiBy");
+/// assert_eq!(ucum_unit(Unit::KilobitsPerSecond), "kbit/s");
+/// assert_eq!(ucum_unit(Unit::CountPerSecond), "1/s");
+/// ```
+pub fn ucum_unit(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Count => "1",
+        Unit::Percent => "%",
+        Unit::Seconds => "s",
+        Unit::Milliseconds => "ms",
+        Unit::Microseconds => "us",
+        Unit::Nanoseconds => "ns",
+        Unit::Tebibytes => "TiBy",
+        Unit::Gibibytes => "GiBy",
+        Unit::Mebibytes => "MiBy",
+        Unit::Kibibytes => "KiBy",
+        Unit::Bytes => "By",
+        Unit::TerabitsPerSecond => "Tbit/s",
+        Unit::GigabitsPerSecond => "Gbit/s",
+        Unit::MegabitsPerSecond => "Mbit/s",
+        Unit::KilobitsPerSecond => "kbit/s",
+        Unit::BitsPerSecond => "bit/s",
+        Unit::CountPerSecond => "1/s",
+    }
+}
+
+/// The UCUM code of `unit`. Custom units become annotations, like
+/// `{entities}`.
+fn metric_unit_code(unit: &MetricUnit) -> String {
+    match unit {
+        MetricUnit::Standard(unit) => ucum_unit(*unit).to_owned(),
+        MetricUnit::Custom(unit) => format!("{{{unit}}}"),
+    }
+}
+
+/// Periodically pushes every metric in the [`MetricsRegistry`] to an
+/// OpenTelemetry collector, using OTLP/HTTP with protobuf encoding.
+///
+/// - counters are exported as cumulative monotonic sums
+/// - gauges are exported as gauges
+/// - histograms are exported as cumulative explicit-bucket histograms, with
+///   the bounds set by [`Self::histogram_bounds`]
+///
+/// Histogram samples are read every frame through
+/// [`HistogramSubscription`]s, so plots still see them. The main thread only
+/// hands the sample buffers to a background thread, which counts them,
+/// encodes the requests and sends them. While the collector can't be
+/// reached, the thread waits between attempts, doubling the wait up to
+/// [`Self::max_backoff`]; exports that come due meanwhile are skipped,
+/// which loses nothing since all values are cumulative.
+///
+/// Both `http://` and `https://` endpoints are supported, the latter with
+/// the Mozilla root certificates. gRPC is not supported, so the collector
+/// needs its OTLP/HTTP receiver enabled. See [`OtlpEncoder`] for the
+/// payload.
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::otlp::OtlpExporterPlugin;
+/// # use std::time::Duration;
+/// # let mut app = App::new();
+/// app.add_plugins(
+///     OtlpExporterPlugin::new("https://otlp.example.com/v1/metrics")
+///         .service_name("my_game")
+///         .service_version(env!("CARGO_PKG_VERSION"))
+///         .header("Authorization", "Bearer my-token")
+///         .interval(Duration::from_secs(10)),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct OtlpExporterPlugin {
+    config: OtlpConfig,
+}
+
+/// Configuration of the [`OtlpExporterPlugin`].
+#[derive(Clone, Debug)]
+pub struct OtlpConfig {
+    /// The URL that metrics are posted to, like
+    /// `http://localhost:4318/v1/metrics`.
+    pub endpoint: String,
+    /// Resource attributes, like `service.name`.
+    pub resource_attributes: Vec<(String, String)>,
+    /// Extra HTTP headers, e.g. for authentication.
+    pub headers: Vec<(String, String)>,
+    /// How often metrics are exported.
+    pub interval: Duration,
+    /// How long to wait for the collector to connect and respond.
+    pub timeout: Duration,
+    /// The longest wait between attempts after failed exports.
+    pub max_backoff: Duration,
+    /// The upper bounds of exported histogram buckets, in increasing order.
+    pub histogram_bounds: Vec<f64>,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:4318/v1/metrics".into(),
+            resource_attributes: Vec::new(),
+            headers: Vec::new(),
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+            histogram_bounds: DEFAULT_HISTOGRAM_BOUNDS.to_vec(),
+        }
+    }
+}
+
+impl OtlpExporterPlugin {
+    /// Export to `endpoint`, a URL like `http://localhost:4318/v1/metrics`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self::with_config(OtlpConfig {
+            endpoint: endpoint.into(),
+            ..default()
+        })
+    }
+
+    #[allow(missing_docs)]
+    pub fn with_config(config: OtlpConfig) -> Self {
+        Self { config }
+    }
+
+    /// Set the `service.name` resource attribute.
+    pub fn service_name(self, name: impl Into<String>) -> Self {
+        self.resource_attribute("service.name", name)
+    }
+
+    /// Set the `service.version` resource attribute.
+    pub fn service_version(self, version: impl Into<String>) -> Self {
+        self.resource_attribute("service.version", version)
+    }
+
+    /// Set a resource attribute, replacing any previous value.
+    pub fn resource_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        self.config.resource_attributes.retain(|(k, _)| *k != key);
+        self.config.resource_attributes.push((key, value.into()));
+        self
+    }
+
+    /// Send an HTTP header with every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// See [`OtlpConfig::interval`].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.config.interval = interval;
+        self
+    }
+
+    /// See [`OtlpConfig::timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    /// See [`OtlpConfig::max_backoff`].
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.config.max_backoff = max_backoff;
+        self
+    }
+
+    /// See [`OtlpConfig::histogram_bounds`].
+    pub fn histogram_bounds(mut self, bounds: impl Into<Vec<f64>>) -> Self {
+        self.config.histogram_bounds = bounds.into();
+        self
+    }
+}
+
+impl Plugin for OtlpExporterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            OtlpExporter::export_system
+                .after(BucketClearSet)
+                .run_if(resource_exists::<OtlpExporter>),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        match self.config.endpoint.parse::<ureq::http::Uri>() {
+            Ok(uri) if matches!(uri.scheme_str(), Some("http" | "https")) => {}
+            _ => {
+                error!(
+                    "OTLP export disabled: expected an http:// or https:// endpoint, got {:?}",
+                    self.config.endpoint
+                );
+                return;
+            }
+        }
+        let Some(registry) = app.world().get_resource::<MetricsRegistry>().cloned() else {
+            error!("OTLP export disabled: the OtlpExporterPlugin requires the RegistryPlugin");
+            return;
+        };
+        let (sender, receiver) = mpsc::channel();
+        let config = self.config.clone();
+        let spawned = std::thread::Builder::new()
+            .name("otlp-exporter".into())
+            .spawn(move || export_thread(receiver, registry, config));
+        if let Err(e) = spawned {
+            error!("OTLP export disabled: failed to spawn thread: {e}");
+            return;
+        }
+        app.insert_resource(OtlpExporter {
+            histograms: HistogramCollector::default(),
+            interval: self.config.interval,
+            sender,
+            last_export: None,
+        });
+    }
+}
+
+/// The state of the [`OtlpExporterPlugin`] on the main thread.
+#[derive(Resource)]
+pub struct OtlpExporter {
+    histograms: HistogramCollector,
+    interval: Duration,
+    sender: Sender<ExportMessage>,
+    last_export: Option<Instant>,
+}
+
+/// Sent from the [`OtlpExporter`] to the exporter thread.
+enum ExportMessage {
+    /// The histogram samples of a frame.
+    Samples(Vec<(metrics::Key, Arc<[f64]>)>),
+    /// Export all metrics, unless the thread is backing off.
+    Export,
+}
+
+impl OtlpExporter {
+    /// Bevy system that hands the histogram samples of this frame to the
+    /// exporter thread, and asks it to export every
+    /// [`OtlpConfig::interval`].
+    ///
+    /// This runs in [`Last`] after the [`BucketClearSet`], when the
+    /// samples of subscribed histograms are published.
+    pub fn export_system(registry: Res<MetricsRegistry>, mut exporter: ResMut<Self>) {
+        let samples = exporter.histograms.collect(&registry);
+        let mut messages = Vec::with_capacity(2);
+        if !samples.is_empty() {
+            messages.push(ExportMessage::Samples(samples));
+        }
+        let now = Instant::now();
+        if exporter
+            .last_export
+            .is_none_or(|last| now - last >= exporter.interval)
+        {
+            exporter.last_export = Some(now);
+            messages.push(ExportMessage::Export);
+        }
+        for message in messages {
+            if exporter.sender.send(message).is_err() {
+                warn_once!("OTLP exporter thread stopped");
+            }
+        }
+    }
+}
+
+/// Subscriptions to every histogram of a [`MetricsRegistry`], to hand their
+/// samples to an [`OtlpEncoder`].
+///
+/// Collecting only clones the buffers of the samples, so it's cheap enough
+/// for the main thread. See [`OtlpEncoder`] for an example.
+#[derive(Default)]
+pub struct HistogramCollector {
+    subscriptions: HashMap<metrics::Key, Subscribed>,
+    /// The [histogram generation](crate::registry::RegistryGeneration::histograms)
+    /// the subscriptions were last updated at.
+    generation: Option<u64>,
+}
+
+struct Subscribed {
+    subscription: HistogramSubscription,
+    /// The samples that were read last, so they aren't collected twice.
+    last: Option<Arc<[f64]>>,
+}
+
+impl HistogramCollector {
+    /// Subscribe to new histograms, forget removed ones, and return the
+    /// samples published since the last call.
+    ///
+    /// Call this after the atomic buckets are cleared, every time.
+    pub fn collect(&mut self, registry: &MetricsRegistry) -> Vec<(metrics::Key, Arc<[f64]>)> {
+        let generation = registry.generation().histograms;
+        if self.generation != Some(generation) {
+            self.generation = Some(generation);
+            // E.g. evicted by the retention policy.
+            self.subscriptions
+                .retain(|key, _| registry.get_histogram(key).is_some());
+            registry.storage().visit_histograms(|key, _| {
+                if !self.subscriptions.contains_key(key) {
+                    let subscribed = Subscribed {
+                        subscription: registry.subscribe_histogram(key.clone()),
+                        last: None,
+                    };
+                    self.subscriptions.insert(key.clone(), subscribed);
+                }
+            });
+        }
+        let mut collected = Vec::new();
+        for (key, Subscribed { subscription, last }) in &mut self.subscriptions {
+            let samples = subscription.samples();
+            if last.as_ref().is_some_and(|l| Arc::ptr_eq(l, &samples)) {
+                continue;
+            }
+            *last = Some(samples.clone());
+            if !samples.is_empty() {
+                collected.push((key.clone(), samples));
+            }
+        }
+        collected
+    }
+}
+
+/// Encodes the metrics of a [`MetricsRegistry`] as an OTLP
+/// `ExportMetricsServiceRequest`, like the [`OtlpExporterPlugin`] sends.
+///
+/// Histograms are exported with cumulative buckets, so the encoder counts
+/// every sample handed to [`Self::record_histogram`], e.g. by a
+/// [`HistogramCollector`].
+///
+/// ```
+/// # use bevy_metrics_dashboard::otlp::{HistogramCollector, OtlpConfig, OtlpEncoder};
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// use bevy_metrics_dashboard::otlp::opentelemetry_proto::tonic::metrics::v1::{
+///     metric::Data, number_data_point::Value,
+/// };
+/// use metrics::{Key, Level, Metadata, Recorder};
+/// use std::sync::atomic::Ordering;
+///
+/// let registry = MetricsRegistry::new();
+/// let spawned = Key::from_parts("enemies_spawned", &[("team", "red")]);
+/// registry.get_or_create_counter(&spawned).fetch_add(3, Ordering::Relaxed);
+/// let queued = Key::from_static_name("jobs_queued");
+/// registry.get_or_create_gauge(&queued).store(2.5f64.to_bits(), Ordering::Relaxed);
+///
+/// let mut encoder = OtlpEncoder::new(OtlpConfig {
+///     histogram_bounds: vec![1.0, 10.0],
+///     resource_attributes: vec![("service.name".into(), "my_game".into())],
+///     ..Default::default()
+/// });
+/// let mut collector = HistogramCollector::default();
+/// let metadata = Metadata::new(module_path!(), Level::INFO, None);
+/// let latency = registry.register_histogram(&Key::from_static_name("latency"), &metadata);
+/// // Subscribes to the histogram, then collects the samples of the frame.
+/// collector.collect(&registry);
+/// latency.record(0.5);
+/// latency.record(5.0);
+/// latency.record(20.0);
+/// registry.clear_atomic_buckets();
+/// for (key, samples) in collector.collect(&registry) {
+///     encoder.record_histogram(&key, &samples);
+/// }
+///
+/// let request = encoder.request(&registry);
+/// let resource = &request.resource_metrics[0];
+/// let attribute = &resource.resource.as_ref().unwrap().attributes[0];
+/// assert_eq!(attribute.key, "service.name");
+/// let metrics = &resource.scope_metrics[0].metrics;
+/// let data = |name: &str| {
+///     let metric = metrics.iter().find(|m| m.name == name).unwrap();
+///     metric.data.clone().unwrap()
+/// };
+///
+/// let Data::Sum(sum) = data("enemies_spawned") else { panic!() };
+/// assert!(sum.is_monotonic);
+/// assert_eq!(sum.data_points[0].attributes[0].key, "team");
+/// assert_eq!(sum.data_points[0].value, Some(Value::AsInt(3)));
+/// let Data::Gauge(gauge) = data("jobs_queued") else { panic!() };
+/// assert_eq!(gauge.data_points[0].value, Some(Value::AsDouble(2.5)));
+/// let Data::Histogram(histogram) = data("latency") else { panic!() };
+/// let point = &histogram.data_points[0];
+/// assert_eq!((point.count, point.sum), (3, Some(25.5)));
+/// assert_eq!(point.bucket_counts, [1, 1, 1]);
+/// assert_eq!(point.explicit_bounds, [1.0, 10.0]);
+/// assert_eq!((point.min, point.max), (Some(0.5), Some(20.0)));
+///
+/// // The body of the HTTP request.
+/// assert!(!encoder.encode(&registry).is_empty());
+/// ```
+pub struct OtlpEncoder {
+    config: OtlpConfig,
+    start_time: u64,
+    histograms: HashMap<metrics::Key, HistogramState>,
+}
+
+/// The cumulative buckets of one exported histogram.
+struct HistogramState {
+    /// One more than the number of bounds.
+    bucket_counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl OtlpEncoder {
+    #[allow(missing_docs)]
+    pub fn new(config: OtlpConfig) -> Self {
+        Self {
+            config,
+            start_time: unix_nanos(),
+            histograms: HashMap::default(),
+        }
+    }
+
+    /// Count `samples` of the histogram `key` into its cumulative buckets.
+    pub fn record_histogram(&mut self, key: &metrics::Key, samples: &[f64]) {
+        let n_buckets = self.config.histogram_bounds.len() + 1;
+        let state = self
+            .histograms
+            .entry(key.clone())
+            .or_insert_with(|| HistogramState {
+                bucket_counts: vec![0; n_buckets],
+                count: 0,
+                sum: 0.0,
+                min: f64::INFINITY,
+                max: f64::NEG_INFINITY,
+            });
+        for &value in samples.iter().filter(|v| v.is_finite()) {
+            // Buckets include their upper bound.
+            let bucket = self
+                .config
+                .histogram_bounds
+                .partition_point(|&bound| bound < value);
+            state.bucket_counts[bucket] += 1;
+            state.count += 1;
+            state.sum += value;
+            state.min = state.min.min(value);
+            state.max = state.max.max(value);
+        }
+    }
+
+    /// An `ExportMetricsServiceRequest` with the current value of every
+    /// metric. Histograms that were removed from the registry, e.g. by the
+    /// [retention policy](crate::retention), are forgotten.
+    pub fn request(&mut self, registry: &MetricsRegistry) -> ExportMetricsServiceRequest {
+        self.histograms
+            .retain(|key, _| registry.get_histogram(key).is_some());
+
+        let time = unix_nanos();
+        let mut metrics = Vec::new();
+        let storage = registry.storage();
+        storage.visit_counters(|key, counter| {
+            let point = NumberDataPoint {
+                attributes: key_attributes(key),
+                start_time_unix_nano: self.start_time,
+                time_unix_nano: time,
+                value: Some(number_data_point::Value::AsInt(
+                    counter.load(Ordering::Relaxed) as i64,
+                )),
+                ..default()
+            };
+            let data = metric::Data::Sum(Sum {
+                data_points: vec![point],
+                aggregation_temporality: AggregationTemporality::Cumulative.into(),
+                is_monotonic: true,
+            });
+            metrics.push(encode_metric(registry, key, MetricKind::Counter, data));
+        });
+        storage.visit_gauges(|key, gauge| {
+            let value = f64::from_bits(gauge.load(Ordering::Relaxed));
+            if !value.is_finite() {
+                return;
+            }
+            let point = NumberDataPoint {
+                attributes: key_attributes(key),
+                time_unix_nano: time,
+                value: Some(number_data_point::Value::AsDouble(value)),
+                ..default()
+            };
+            let data = metric::Data::Gauge(Gauge {
+                data_points: vec![point],
+            });
+            metrics.push(encode_metric(registry, key, MetricKind::Gauge, data));
+        });
+        for (key, state) in &self.histograms {
+            let point = HistogramDataPoint {
+                attributes: key_attributes(key),
+                start_time_unix_nano: self.start_time,
+                time_unix_nano: time,
+                count: state.count,
+                sum: Some(state.sum),
+                bucket_counts: state.bucket_counts.clone(),
+                explicit_bounds: self.config.histogram_bounds.clone(),
+                min: (state.count > 0).then_some(state.min),
+                max: (state.count > 0).then_some(state.max),
+                ..default()
+            };
+            let data = metric::Data::Histogram(Histogram {
+                data_points: vec![point],
+                aggregation_temporality: AggregationTemporality::Cumulative.into(),
+            });
+            metrics.push(encode_metric(registry, key, MetricKind::Histogram, data));
+        }
+
+        let resource = Resource {
+            attributes: self
+                .config
+                .resource_attributes
+                .iter()
+                .map(|(k, v)| attribute(k, v))
+                .collect(),
+            ..default()
+        };
+        let scope = InstrumentationScope {
+            name: env!("CARGO_PKG_NAME").into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            ..default()
+        };
+        ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(resource),
+                scope_metrics: vec![ScopeMetrics {
+                    scope: Some(scope),
+                    metrics,
+                    ..default()
+                }],
+                ..default()
+            }],
+        }
+    }
+
+    /// The [request](Self::request) in the protobuf encoding.
+    pub fn encode(&mut self, registry: &MetricsRegistry) -> Vec<u8> {
+        self.request(registry).encode_to_vec()
+    }
+}
+
+/// One `Metric` with its name, description, unit, and `data`.
+fn encode_metric(
+    registry: &MetricsRegistry,
+    key: &metrics::Key,
+    kind: MetricKind,
+    data: metric::Data,
+) -> Metric {
+    let description = registry.get_description(&DescriptionKey {
+        name: key.name().to_owned().into(),
+        kind,
+    });
+    let (text, unit) = match &description {
+        Some(MetricDescription { text, unit, .. }) => {
+            (text.to_string(), unit.as_ref().map(metric_unit_code))
+        }
+        None => (String::new(), None),
+    };
+    Metric {
+        name: key.name().to_owned(),
+        description: text,
+        unit: unit.unwrap_or_default(),
+        data: Some(data),
+        ..default()
+    }
+}
+
+fn key_attributes(key: &metrics::Key) -> Vec<KeyValue> {
+    key.labels()
+        .map(|label| attribute(label.key(), label.value()))
+        .collect()
+}
+
+fn attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_owned(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_owned())),
+        }),
+        ..default()
+    }
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Runs on the exporter thread until the [`OtlpExporter`] is dropped.
+fn export_thread(receiver: Receiver<ExportMessage>, registry: MetricsRegistry, config: OtlpConfig) {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(config.timeout))
+        .build()
+        .into();
+    let mut encoder = OtlpEncoder::new(config.clone());
+    let mut backoff = Duration::ZERO;
+    let mut retry_at = None;
+    while let Ok(message) = receiver.recv() {
+        // Catch up on everything sent while the last request was pending.
+        let mut export = false;
+        for message in std::iter::once(message).chain(receiver.try_iter()) {
+            match message {
+                ExportMessage::Samples(samples) => {
+                    for (key, samples) in samples {
+                        encoder.record_histogram(&key, &samples);
+                    }
+                }
+                ExportMessage::Export => export = true,
+            }
+        }
+        if !export || retry_at.is_some_and(|at| Instant::now() < at) {
+            continue;
+        }
+        match post(&agent, &encoder.encode(&registry), &config) {
+            Ok(()) => {
+                backoff = Duration::ZERO;
+                retry_at = None;
+            }
+            Err(e) => {
+                backoff = (backoff * 2)
+                    .max(Duration::from_secs(1))
+                    .min(config.max_backoff);
+                warn!("OTLP export failed, retrying in {backoff:?}: {e}");
+                retry_at = Some(Instant::now() + backoff);
+            }
+        }
+    }
+}
+
+fn post(agent: &ureq::Agent, body: &[u8], config: &OtlpConfig) -> Result<(), ureq::Error> {
+    let mut request = agent
+        .post(&config.endpoint)
+        .header("Content-Type", "application/x-protobuf");
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    request.send(body)?;
+    Ok(())
+}
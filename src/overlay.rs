@@ -0,0 +1,201 @@
+use crate::egui::Align2;
+use crate::localization::UiText;
+use crate::plot_style::PlotStyle;
+use bevy::prelude::*;
+
+#[cfg(feature = "bevy_egui")]
+use crate::{
+    command_palette::{DashboardCommand, DashboardCommandsAppExt},
+    egui::{self, Area, Order},
+    localization::DashboardLocalization,
+    DashboardConfig, DashboardWindow,
+};
+
+/// A corner of the screen where the [`OverlayPlugin`] draws plots.
+///
+/// Plots are promoted to the overlay from their context menu, or with
+/// [`MetricPlot::set_overlay`](crate::plots::MetricPlot::set_overlay).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum OverlayCorner {
+    #[allow(missing_docs)]
+    TopLeft,
+    #[allow(missing_docs)]
+    #[default]
+    TopRight,
+    #[allow(missing_docs)]
+    BottomLeft,
+    #[allow(missing_docs)]
+    BottomRight,
+}
+
+impl OverlayCorner {
+    #[allow(missing_docs)]
+    pub const ALL: [Self; 4] = [
+        Self::TopLeft,
+        Self::TopRight,
+        Self::BottomLeft,
+        Self::BottomRight,
+    ];
+
+    #[allow(missing_docs)]
+    pub fn label(self) -> UiText {
+        match self {
+            Self::TopLeft => UiText::TopLeft,
+            Self::TopRight => UiText::TopRight,
+            Self::BottomLeft => UiText::BottomLeft,
+            Self::BottomRight => UiText::BottomRight,
+        }
+    }
+
+    /// The anchor of this corner's [`egui::Area`].
+    pub fn align(self) -> Align2 {
+        match self {
+            Self::TopLeft => Align2::LEFT_TOP,
+            Self::TopRight => Align2::RIGHT_TOP,
+            Self::BottomLeft => Align2::LEFT_BOTTOM,
+            Self::BottomRight => Align2::RIGHT_BOTTOM,
+        }
+    }
+
+    /// The offset of this corner's area from the screen edges, moved inwards
+    /// by `margin`.
+    #[cfg(feature = "bevy_egui")]
+    fn offset(self, margin: f32) -> [f32; 2] {
+        let sign = self.align().to_sign();
+        [-sign.x * margin, -sign.y * margin]
+    }
+}
+
+/// Configuration of the [`OverlayPlugin`].
+#[derive(Clone, Reflect, Resource)]
+#[reflect(Resource)]
+pub struct OverlayConfig {
+    /// Whether the overlay is drawn, independent of
+    /// [`DashboardConfig::visible`](crate::DashboardConfig::visible).
+    pub visible: bool,
+    /// When `Some`, pressing this key toggles [`Self::visible`].
+    pub toggle_key: Option<KeyCode>,
+    /// The style of all plots in the overlay.
+    pub style: PlotStyle,
+    /// The width of each plot, in points.
+    pub width: f32,
+    /// The distance between the plots and the screen edges, in points.
+    pub margin: f32,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            toggle_key: None,
+            style: PlotStyle::overlay(),
+            width: 240.0,
+            margin: 8.0,
+        }
+    }
+}
+
+impl OverlayConfig {
+    /// Bevy system that toggles [`Self::visible`] when [`Self::toggle_key`]
+    /// is pressed.
+    pub fn toggle_visibility(mut config: ResMut<Self>, keys: Option<Res<ButtonInput<KeyCode>>>) {
+        let (Some(key), Some(keys)) = (config.toggle_key, keys) else {
+            return;
+        };
+        if keys.just_pressed(key) {
+            config.visible = !config.visible;
+        }
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// Bevy system that draws the promoted plots of all [`DashboardWindow`]s
+    /// in their corners of the screen.
+    ///
+    /// The overlay has no window chrome, and it doesn't take any input, so
+    /// clicks go through to the game.
+    pub fn draw_all(
+        config: Res<Self>,
+        dash_config: Res<DashboardConfig>,
+        localization: Res<DashboardLocalization>,
+        mut ctxts: bevy_egui::EguiContexts,
+        mut windows: Query<&mut DashboardWindow>,
+    ) {
+        if !config.visible {
+            return;
+        }
+
+        let ctxt = ctxts.ctx_mut();
+        for corner in OverlayCorner::ALL {
+            if !windows.iter().any(|w| w.has_overlay_plots(corner)) {
+                continue;
+            }
+            localization.scope(ctxt, |ctxt| {
+                dash_config.theme.scope(ctxt, |ctxt| {
+                    Area::new(egui::Id::new(("metrics-overlay", corner)))
+                        .anchor(corner.align(), corner.offset(config.margin))
+                        .order(Order::Foreground)
+                        .interactable(false)
+                        .show(ctxt, |ui| {
+                            ui.set_width(config.width);
+                            for mut window in &mut windows {
+                                window.draw_overlay_plots(corner, &dash_config, &config.style, ui);
+                            }
+                        });
+                })
+            });
+        }
+    }
+}
+
+/// Draws plots promoted to the overlay directly over the game, in the
+/// [`OverlayCorner`] chosen for each plot.
+///
+/// Requires the [`DashboardPlugin`](crate::DashboardPlugin). The overlay is
+/// configured by the [`OverlayConfig`] resource, and it is shown even while
+/// the dashboard windows are hidden.
+#[cfg(feature = "bevy_egui")]
+#[derive(Default)]
+pub struct OverlayPlugin {
+    config: OverlayConfig,
+}
+
+#[cfg(feature = "bevy_egui")]
+impl OverlayPlugin {
+    /// Create a plugin with the default [`OverlayConfig`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a plugin with a custom [`OverlayConfig`].
+    pub fn with_config(config: OverlayConfig) -> Self {
+        Self { config }
+    }
+
+    /// See [`OverlayConfig::toggle_key`].
+    pub fn toggle_key(mut self, key: KeyCode) -> Self {
+        self.config.toggle_key = Some(key);
+        self
+    }
+}
+
+#[cfg(feature = "bevy_egui")]
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<OverlayConfig>()
+            .insert_resource(self.config.clone())
+            .add_dashboard_command(DashboardCommand::localized(
+                "overlay.toggle_visible",
+                UiText::ToggleOverlay,
+                |world| {
+                    let mut config = world.resource_mut::<OverlayConfig>();
+                    config.visible = !config.visible;
+                },
+            ))
+            .add_systems(Update, OverlayConfig::toggle_visibility)
+            .add_systems(
+                bevy_egui::EguiContextPass,
+                OverlayConfig::draw_all.after(DashboardWindow::draw_all),
+            );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::dashboard_window::DashboardConfig;
+use bevy::prelude::*;
+use std::time::Duration;
+
+#[cfg(feature = "bevy_egui")]
+use crate::{
+    egui::Ui,
+    localization::{DashboardLocalization, UiText},
+    theme::ThemePalette,
+};
+
+/// A limit on the time the dashboard itself spends per frame, sampling and
+/// drawing, above which it does less work to stay out of the game's way.
+///
+/// See [`DashboardConfig::performance_budget`]. When the smoothed
+/// [cost](DashboardPerformance::cost) stays over [`Self::budget`] for
+/// [`Self::frames`] frames, the dashboard [degrades](Degradation) by one
+/// level. It recovers by one level once the cost it expects without that
+/// degradation, based on how much the degradation saved, stays under
+/// [`Self::recover_fraction`] of the budget for as long. This keeps it from
+/// switching back and forth when a degradation brings the cost just under
+/// the budget.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct PerformanceBudget {
+    #[allow(missing_docs)]
+    pub budget: Duration,
+    /// The fraction of [`Self::budget`] that the expected cost must stay
+    /// under before a degradation is reversed.
+    pub recover_fraction: f64,
+    /// How many consecutive frames the cost must be over or under its
+    /// threshold before the degradation changes.
+    pub frames: u32,
+}
+
+impl PerformanceBudget {
+    /// A budget of `budget` per frame with the default thresholds.
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            recover_fraction: 0.8,
+            frames: 30,
+        }
+    }
+
+    /// See [`Self::recover_fraction`].
+    pub fn with_recover_fraction(mut self, fraction: f64) -> Self {
+        self.recover_fraction = fraction;
+        self
+    }
+
+    /// See [`Self::frames`].
+    pub fn with_frames(mut self, frames: u32) -> Self {
+        self.frames = frames;
+        self
+    }
+}
+
+/// The work the dashboard skips to stay within its [`PerformanceBudget`].
+///
+/// Each level includes the ones before it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, Reflect)]
+pub enum Degradation {
+    /// Nothing is skipped.
+    #[default]
+    None,
+    /// Plots that weren't on screen since they were last refreshed, e.g.
+    /// because they're collapsed or scrolled out of view, aren't refreshed.
+    /// They still collect samples.
+    SkipHiddenPlots,
+    /// Plots are refreshed at a third of the
+    /// [`DashboardConfig::ui_refresh_rate`].
+    ReducedRefreshRate,
+    /// Lines are drawn with the lowest and highest value of every
+    /// [`DOWNSAMPLING_CHUNK`] samples, instead of every sample.
+    Downsampled,
+}
+
+/// How many samples [`Degradation::Downsampled`] lines draw two points for.
+pub const DOWNSAMPLING_CHUNK: usize = 8;
+
+impl Degradation {
+    const LEVELS: [Self; 4] = [
+        Self::None,
+        Self::SkipHiddenPlots,
+        Self::ReducedRefreshRate,
+        Self::Downsampled,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    #[allow(missing_docs)]
+    pub fn skips_hidden_plots(self) -> bool {
+        self >= Self::SkipHiddenPlots
+    }
+
+    /// What the [`DashboardConfig::ui_refresh_rate`] is divided by.
+    pub fn refresh_rate_divisor(self) -> u32 {
+        if self >= Self::ReducedRefreshRate {
+            3
+        } else {
+            1
+        }
+    }
+
+    /// How many samples are drawn as two points, or 1 to draw every sample.
+    pub fn downsampling(self) -> usize {
+        if self >= Self::Downsampled {
+            DOWNSAMPLING_CHUNK
+        } else {
+            1
+        }
+    }
+}
+
+/// The dashboard's own cost per frame, and how it's [degraded](Degradation)
+/// to stay within the [`DashboardConfig::performance_budget`].
+///
+/// The cost is the time spent capturing the snapshot, collecting samples,
+/// refreshing plots and drawing dashboard windows.
+#[derive(Debug, Default, Resource)]
+pub struct DashboardPerformance {
+    frame_cost: Duration,
+    cost: Duration,
+    degradation: Degradation,
+    /// What the cost was when each degradation level was entered, and what
+    /// it settled at after [`PerformanceBudget::frames`] frames.
+    history: [(Duration, Option<Duration>); Degradation::LEVELS.len()],
+    frames_at_level: u32,
+    frames_over: u32,
+    frames_under: u32,
+}
+
+impl DashboardPerformance {
+    /// The dashboard's cost per frame, smoothed over recent frames.
+    pub fn cost(&self) -> Duration {
+        self.cost
+    }
+
+    /// The current degradation. [`Degradation::None`] without a
+    /// [`PerformanceBudget`].
+    pub fn degradation(&self) -> Degradation {
+        self.degradation
+    }
+
+    /// Count `cost` towards the current frame.
+    pub(crate) fn add_cost(&mut self, cost: Duration) {
+        self.frame_cost += cost;
+    }
+
+    /// Bevy system that updates the [cost](Self::cost) with the current
+    /// frame's and adjusts the [degradation](Self::degradation).
+    pub(crate) fn update_system(mut performance: ResMut<Self>, config: Res<DashboardConfig>) {
+        let frame_cost = std::mem::take(&mut performance.frame_cost);
+        performance.cost = performance.cost.mul_f64(0.9) + frame_cost.mul_f64(0.1);
+        match &config.performance_budget {
+            Some(budget) => performance.adjust(budget),
+            None => performance.set_degradation(Degradation::None),
+        }
+    }
+
+    fn adjust(&mut self, budget: &PerformanceBudget) {
+        let level = self.degradation.index();
+        self.frames_at_level = self.frames_at_level.saturating_add(1);
+        if self.frames_at_level == budget.frames && level > 0 {
+            self.history[level].1 = Some(self.cost);
+        }
+
+        self.frames_over = if self.cost > budget.budget {
+            self.frames_over + 1
+        } else {
+            0
+        };
+        // The cost saved by the current level, as measured when entering it.
+        let expected = match self.history[level] {
+            (entered, Some(settled)) => Some(self.cost + entered.saturating_sub(settled)),
+            _ => None,
+        };
+        let recovered =
+            expected.is_some_and(|cost| cost < budget.budget.mul_f64(budget.recover_fraction));
+        self.frames_under = if recovered { self.frames_under + 1 } else { 0 };
+
+        if self.frames_over >= budget.frames && level + 1 < Degradation::LEVELS.len() {
+            self.history[level + 1] = (self.cost, None);
+            self.set_degradation(Degradation::LEVELS[level + 1]);
+        } else if self.frames_under >= budget.frames && level > 0 {
+            self.set_degradation(Degradation::LEVELS[level - 1]);
+        }
+    }
+
+    fn set_degradation(&mut self, degradation: Degradation) {
+        if self.degradation != degradation {
+            self.degradation = degradation;
+            self.frames_at_level = 0;
+            self.frames_over = 0;
+            self.frames_under = 0;
+        }
+    }
+
+    #[cfg(feature = "bevy_egui")]
+    /// A badge explaining what was reduced, if anything.
+    pub(crate) fn badge_ui(&self, config: &DashboardConfig, ui: &mut Ui) {
+        let (Some(budget), true) = (
+            &config.performance_budget,
+            self.degradation != Degradation::None,
+        ) else {
+            return;
+        };
+        let l10n = DashboardLocalization::of(ui);
+        let ms = |d: Duration| l10n.number(d.as_secs_f64() * 1000.0, 1);
+        let mut hint = l10n.format(
+            UiText::DegradedHint,
+            &[("cost", &ms(self.cost)), ("budget", &ms(budget.budget))],
+        );
+        if self.degradation.skips_hidden_plots() {
+            hint.push('\n');
+            hint.push_str(l10n.get(UiText::DegradedSkipHidden));
+        }
+        if self.degradation.refresh_rate_divisor() > 1 {
+            let rate = config.ui_refresh_rate / f64::from(self.degradation.refresh_rate_divisor());
+            hint.push('\n');
+            hint.push_str(&l10n.format(
+                UiText::DegradedRefreshRate,
+                &[("rate", &l10n.number(rate, 0))],
+            ));
+        }
+        if self.degradation.downsampling() > 1 {
+            hint.push('\n');
+            hint.push_str(&l10n.format(
+                UiText::DegradedDownsampled,
+                &[("samples", &self.degradation.downsampling())],
+            ));
+        }
+        let palette = ThemePalette::of(ui);
+        ui.colored_label(palette.error, l10n.get(UiText::DegradedBadge))
+            .on_hover_text(hint);
+    }
+}
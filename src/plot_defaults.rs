@@ -0,0 +1,156 @@
+//! Configs that new plots of matching metrics start with, like a spike
+//! threshold for the frame time or a unit conversion for memory usage.
+//!
+//! A plot's config comes from, in order of precedence:
+//! 1. the config it had when it was last closed, kept in the
+//!    [`CachedPlotConfigs`](crate::CachedPlotConfigs), or the config in a
+//!    loaded layout,
+//! 2. the first [`PlotDefault`] whose pattern matches the metric's name and
+//!    whose config is for the metric's kind,
+//! 3. [`MetricPlotConfig::default_for_kind`].
+//!
+//! ```
+//! # use bevy_metrics_dashboard::{CachedPlotConfigs, DashboardWindow};
+//! # use bevy_metrics_dashboard::metrics_util::MetricKind;
+//! # use bevy_metrics_dashboard::plot_defaults::{PlotDefault, PlotDefaults};
+//! # use bevy_metrics_dashboard::plots::{CounterPlotConfig, GaugePlotConfig, MetricPlotConfig};
+//! # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+//! # use metrics::Key;
+//! let gauge = |window_size| {
+//!     MetricPlotConfig::Gauge(GaugePlotConfig {
+//!         window_size: Some(window_size),
+//!         ..Default::default()
+//!     })
+//! };
+//! let defaults = PlotDefaults::from(vec![
+//!     PlotDefault::new("frame_time*", gauge(100)),
+//!     PlotDefault::new("*", gauge(200)),
+//! ]);
+//! let mut cached_configs = CachedPlotConfigs::default();
+//! let registry = MetricsRegistry::new();
+//! let open = |name: &'static str, kind, cached_configs: &CachedPlotConfigs| {
+//!     let key = MetricKey::new(Key::from_static_name(name), kind);
+//!     let mut window = DashboardWindow::new("Metrics");
+//!     window.add_plot(&registry, cached_configs, &defaults, key, None);
+//!     window.plots()[0].clone_config()
+//! };
+//!
+//! // The first matching pattern wins.
+//! assert!(open("frame_time_ms", MetricKind::Gauge, &cached_configs) == gauge(100));
+//! assert!(open("entities", MetricKind::Gauge, &cached_configs) == gauge(200));
+//! // Defaults for other kinds are skipped.
+//! assert!(
+//!     open("frames", MetricKind::Counter, &cached_configs)
+//!         == MetricPlotConfig::Counter(CounterPlotConfig::default())
+//! );
+//! // Configs the user changed take precedence.
+//! let key = MetricKey::new(Key::from_static_name("frame_time_ms"), MetricKind::Gauge);
+//! cached_configs.insert(key, gauge(300));
+//! assert!(open("frame_time_ms", MetricKind::Gauge, &cached_configs) == gauge(300));
+//! ```
+
+use crate::name_pattern::NamePattern;
+use crate::plots::MetricPlotConfig;
+use crate::registry::MetricKey;
+
+/// The config new plots of metrics whose name matches [`Self::pattern`]
+/// start with.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub struct PlotDefault {
+    /// A glob matched against the whole metric name, ignoring labels. `*`
+    /// matches any number of characters, including namespace separators,
+    /// and `?` matches one.
+    pub pattern: String,
+    /// Only used for metrics of the same kind.
+    pub config: MetricPlotConfig,
+}
+
+impl PlotDefault {
+    #[allow(missing_docs)]
+    pub fn new(pattern: impl Into<String>, config: MetricPlotConfig) -> Self {
+        Self {
+            pattern: pattern.into(),
+            config,
+        }
+    }
+
+    fn compile(&self) -> NamePattern {
+        NamePattern::new(self.pattern.as_str())
+    }
+}
+
+/// An ordered table of [`PlotDefault`]s. See the [module docs](self).
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(
+    feature = "layout",
+    serde(from = "Vec<PlotDefault>", into = "Vec<PlotDefault>")
+)]
+pub struct PlotDefaults {
+    entries: Vec<PlotDefault>,
+    /// One per entry.
+    matchers: Vec<NamePattern>,
+}
+
+impl PartialEq for PlotDefaults {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl From<Vec<PlotDefault>> for PlotDefaults {
+    fn from(entries: Vec<PlotDefault>) -> Self {
+        let matchers = entries.iter().map(PlotDefault::compile).collect();
+        Self { entries, matchers }
+    }
+}
+
+impl From<PlotDefaults> for Vec<PlotDefault> {
+    fn from(defaults: PlotDefaults) -> Self {
+        defaults.entries
+    }
+}
+
+impl FromIterator<PlotDefault> for PlotDefaults {
+    fn from_iter<T: IntoIterator<Item = PlotDefault>>(iter: T) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl PlotDefaults {
+    #[allow(missing_docs)]
+    pub fn entries(&self) -> &[PlotDefault] {
+        &self.entries
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add an entry with the lowest priority.
+    pub fn push(&mut self, entry: PlotDefault) {
+        self.matchers.push(entry.compile());
+        self.entries.push(entry);
+    }
+
+    /// The config of the first entry that matches `key`, if any.
+    pub fn get(&self, key: &MetricKey) -> Option<&MetricPlotConfig> {
+        self.entries
+            .iter()
+            .zip(&self.matchers)
+            .find(|(entry, matcher)| {
+                entry.config.kind() == key.kind && matcher.matches(key.key.name())
+            })
+            .map(|(entry, _)| &entry.config)
+    }
+
+    /// The config of the first entry that matches `key`, or the default of
+    /// its kind.
+    pub fn config_for(&self, key: &MetricKey) -> MetricPlotConfig {
+        self.get(key)
+            .cloned()
+            .unwrap_or_else(|| MetricPlotConfig::default_for_kind(key.kind))
+    }
+}
@@ -0,0 +1,132 @@
+//! How plots are drawn, independent of the metric they show.
+//!
+//! See [`DashboardConfig::plot_style`](crate::DashboardConfig::plot_style).
+
+use crate::egui::{Slider, Ui};
+use crate::egui_plot::{Plot, PlotResponse, PlotUi};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::number_format::NumberFormat;
+use bevy::prelude::Reflect;
+
+/// Styling applied to every plot, e.g. to make the dashboard less noisy in
+/// recorded videos.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct PlotStyle {
+    #[allow(missing_docs)]
+    pub show_grid: bool,
+    /// Show the axes with their tick labels.
+    pub show_axes: bool,
+    /// Show the names of the axes, like the unit.
+    pub show_axis_labels: bool,
+    /// The opacity of the plot background, from 0 to 1.
+    pub background_opacity: f32,
+    /// Only draw the lines and the latest value. Hides the grid, axes,
+    /// background, legend and the coordinates on hover, regardless of the
+    /// other settings.
+    pub minimal: bool,
+    /// How values are formatted in latest values, axis ticks, hover labels
+    /// and statistics. Plots in the overlay keep the format of their own
+    /// style.
+    pub numbers: NumberFormat,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            show_grid: true,
+            show_axes: true,
+            show_axis_labels: true,
+            background_opacity: 1.0,
+            minimal: false,
+            numbers: NumberFormat::default(),
+        }
+    }
+}
+
+impl PlotStyle {
+    /// The minimal style, for plots shown on top of gameplay.
+    pub fn overlay() -> Self {
+        Self {
+            show_grid: false,
+            show_axes: false,
+            show_axis_labels: false,
+            background_opacity: 0.0,
+            minimal: true,
+            numbers: NumberFormat::default(),
+        }
+    }
+
+    /// Apply the options of this style that are part of the [`Plot`] builder.
+    ///
+    /// The background opacity is applied by [`Self::scope`].
+    pub fn apply<'a>(&self, plot: Plot<'a>) -> Plot<'a> {
+        let show_grid = self.show_grid && !self.minimal;
+        let show_axes = self.show_axes && !self.minimal;
+        let mut plot = plot
+            .show_grid(show_grid)
+            .show_axes(show_axes)
+            .show_background(self.background_opacity > 0.0 && !self.minimal);
+        if !self.show_axis_labels || self.minimal {
+            plot = plot.x_axis_label("").y_axis_label("");
+        }
+        if self.minimal {
+            plot = plot.show_x(false).show_y(false);
+        }
+        plot
+    }
+
+    /// Draw the plots in `add_contents` with the background opacity of this
+    /// style.
+    pub fn scope<R>(&self, ui: &mut Ui, add_contents: impl FnOnce(&mut Ui) -> R) -> R {
+        ui.scope(|ui| {
+            let visuals = ui.visuals_mut();
+            visuals.extreme_bg_color = visuals
+                .extreme_bg_color
+                .gamma_multiply(self.background_opacity.clamp(0.0, 1.0));
+            add_contents(ui)
+        })
+        .inner
+    }
+
+    /// Show `plot` with this style.
+    pub fn show<'a, R>(
+        &self,
+        plot: Plot<'a>,
+        ui: &mut Ui,
+        build_fn: impl FnOnce(&mut PlotUi<'a>) -> R + 'a,
+    ) -> PlotResponse<R> {
+        self.scope(ui, |ui| self.apply(plot).show(ui, build_fn))
+    }
+
+    /// Draw the settings and accept user input. Returns true if any changed.
+    pub fn configure_ui(&mut self, ui: &mut Ui) -> bool {
+        let l10n = DashboardLocalization::of(ui);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(&mut self.minimal, l10n.get(UiText::Minimal))
+                .changed();
+            ui.add_enabled_ui(!self.minimal, |ui| {
+                changed |= ui
+                    .checkbox(&mut self.show_grid, l10n.get(UiText::Grid))
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.show_axes, l10n.get(UiText::Axes))
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.show_axis_labels, l10n.get(UiText::AxisLabels))
+                    .changed();
+                changed |= ui
+                    .add(
+                        Slider::new(&mut self.background_opacity, 0.0..=1.0)
+                            .text(l10n.get(UiText::BackgroundOpacity)),
+                    )
+                    .changed();
+            });
+        });
+        changed |= self.numbers.configure_ui(ui);
+        changed
+    }
+}
@@ -1,18 +1,48 @@
 //! Widgets for plotting metrics.
 
-use crate::dashboard_window::DashboardConfig;
-use crate::egui::{Color32, DragValue, Slider, Ui, Vec2b};
-use crate::egui_plot::{Bar, BarChart, Line, Plot, PlotPoint, PlotPoints};
-use crate::registry::{MetricKey, MetricsRegistry};
+use crate::context_menu::{open_link_button, LinkKind};
+use crate::dashboard_window::{CachedPlotConfigs, DashboardConfig, DashboardWindowConfig};
+use crate::dial::draw_dial;
+use crate::display_names::{series_name, DisplayNames};
+use crate::egui::{self, vec2, Color32, DragValue, Rect, Slider, Stroke, Ui, Vec2b};
+use crate::egui_plot::{
+    Bar, BarChart, Line, LineStyle, MarkerShape, Plot, PlotBounds, PlotPoint, PlotPoints, PlotUi,
+    Points, Polygon, VLine,
+};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::markdown;
+use crate::metric_kind_str;
+use crate::number_format::{NumberFormat, Precision};
+use crate::overlay::OverlayCorner;
+use crate::plot_style::PlotStyle;
+use crate::registry::{
+    DescriptionKey, FrameId, FrameSample, MetricKey, MetricUnit, MetricsRegistry,
+};
 use crate::ring::Ring;
-use crate::unit_str;
-use bevy::prelude::default;
+use crate::snapshot::{FrameMetricsSnapshot, SnapshotValue};
+use crate::spike_detector::SpikeDetector;
+use crate::stats::{mean, percentile, percentile_of_sorted};
+use crate::theme::{DashboardColor, ThemePalette};
+use crate::transform::PlotTransform;
+use crate::widget::{DashboardWidget, WidgetContext};
+use bevy::prelude::{default, Reflect};
+use bevy::reflect::{FromReflect, PartialReflect};
 use float_ord::FloatOrd;
-use metrics::atomics::AtomicU64;
 use metrics::Unit;
-use metrics_util::{storage::AtomicBucket, MetricKind};
+use metrics_util::MetricKind;
 use smallvec::{smallvec, SmallVec};
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "layout")]
+use crate::layout::PlotLayout;
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
 
 // BUG: If you have a constant metric, it results in a perfectly horizontal line
 // plot. Such a plot seems invisible because the axes auto-fit with a height
@@ -20,9 +50,19 @@ use std::sync::{atomic::Ordering, Arc};
 //
 // https://github.com/emilk/egui/issues/3970
 
+/// The maximum number of annotations retained by each [`MetricPlot`].
+///
+/// When more annotations are added, the oldest are discarded.
+pub const MAX_ANNOTATIONS: usize = 64;
+
+const MAX_SPIKES: usize = 64;
+
+pub(crate) const DEFAULT_WINDOW_SIZE: usize = 500;
+
 /// Configuration for one [`MetricPlot`].
 #[allow(missing_docs)]
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
 pub enum MetricPlotConfig {
     Counter(CounterPlotConfig),
     Gauge(GaugePlotConfig),
@@ -38,49 +78,489 @@ impl MetricPlotConfig {
             MetricKind::Histogram => MetricPlotConfig::Histogram(default()),
         }
     }
+
+    /// The kind of metric this config plots.
+    pub fn kind(&self) -> MetricKind {
+        match self {
+            MetricPlotConfig::Counter(_) => MetricKind::Counter,
+            MetricPlotConfig::Gauge(_) => MetricKind::Gauge,
+            MetricPlotConfig::Histogram(_) => MetricKind::Histogram,
+        }
+    }
 }
 
 /// Configuration for a [`MetricPlot`] of [`MetricKind::Counter`].
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
 pub struct CounterPlotConfig {
     /// How many samples are drawn in one plot.
-    pub window_size: usize,
+    ///
+    /// When `None`, uses [`DashboardConfig::default_history_secs`] or
+    /// [`DashboardConfig::default_window_size`].
+    pub window_size: Option<usize>,
     /// If true, plots the time derivative.
     pub derivative: bool,
+    /// How the line connects samples.
+    ///
+    /// When `None`, steps after each sample when plotting the
+    /// [derivative](Self::derivative) or a metric whose unit is
+    /// [`Unit::Count`], and draws straight lines otherwise.
+    pub interpolation: Option<LineInterpolation>,
+    /// When `Some`, marks increments that spike above the recent trend.
+    pub spike_detection: Option<SpikeDetectorConfig>,
+    /// If false, no samples are collected while the plot isn't drawn, and
+    /// the hidden period is shown as a gap.
+    pub buffer_while_hidden: bool,
+    /// When `Some`, periods without increments are shown as a gap.
+    pub staleness: Option<StalenessConfig>,
+    /// When plotting the [derivative](Self::derivative), show stale periods
+    /// as a rate of zero instead of a gap.
+    pub stale_rate_as_zero: bool,
+    /// Applied to the plotted values, i.e. the total or the rate, and to the
+    /// increments checked for spikes.
+    pub transform: PlotTransform,
+    /// When samples are taken.
+    pub sampling: SamplingMode,
+    /// When `Some`, draws a ghost of the line shifted by a number of seconds.
+    pub ghost: Option<GhostConfig>,
 }
 
 impl Default for CounterPlotConfig {
     fn default() -> Self {
         Self {
-            window_size: 500,
+            window_size: None,
             derivative: false,
+            interpolation: None,
+            spike_detection: None,
+            buffer_while_hidden: true,
+            staleness: None,
+            stale_rate_as_zero: true,
+            transform: default(),
+            sampling: default(),
+            ghost: None,
         }
     }
 }
 
 /// Configuration for a [`MetricPlot`] of [`MetricKind::Gauge`].
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
 pub struct GaugePlotConfig {
     /// A weight in `0.0..=1.0` used for exponential smoothing.
     pub smoothing_weight: f64,
     /// How many samples are drawn in one plot.
-    pub window_size: usize,
+    ///
+    /// When `None`, uses [`DashboardConfig::default_history_secs`] or
+    /// [`DashboardConfig::default_window_size`].
+    pub window_size: Option<usize>,
     /// If true, plots the time derivative.
     pub derivative: bool,
+    /// When `Some`, marks samples that spike above the recent trend.
+    ///
+    /// Detection uses raw samples, before smoothing.
+    pub spike_detection: Option<SpikeDetectorConfig>,
+    /// How the gauge is drawn.
+    pub display: GaugeDisplay,
+    /// How the line connects samples, when [`Self::display`] is
+    /// [`GaugeDisplay::Line`].
+    ///
+    /// When `None`, steps after each sample for metrics whose unit is
+    /// [`Unit::Count`], like entity counts or queue lengths, and draws
+    /// straight lines otherwise.
+    pub interpolation: Option<LineInterpolation>,
+    /// Used when [`Self::display`] is [`GaugeDisplay::Dial`].
+    pub dial: DialConfig,
+    /// Used when [`Self::display`] is [`GaugeDisplay::Distribution`].
+    pub distribution: DistributionConfig,
+    /// If false, no samples are collected while the plot isn't drawn, and
+    /// the hidden period is shown as a gap.
+    pub buffer_while_hidden: bool,
+    /// When `Some`, periods where the gauge isn't updated are shown as a gap.
+    pub staleness: Option<StalenessConfig>,
+    /// Applied to the smoothed values, and to the raw samples checked for
+    /// spikes.
+    pub transform: PlotTransform,
+    /// When samples are taken.
+    pub sampling: SamplingMode,
+    /// Shade the lowest and highest values of each frame around the line,
+    /// for gauges whose [envelope is tracked](crate::registry::MetricsRegistry::track_gauge_envelope).
+    ///
+    /// Not drawn for the derivative.
+    pub show_envelope: bool,
+    /// Shade one standard deviation of the raw values around the smoothed
+    /// line, weighted like the [smoothing](Self::smoothing_weight). See
+    /// [`Smoother`].
+    ///
+    /// Not drawn for the derivative.
+    pub show_std_dev: bool,
+    /// When `Some`, draws a ghost of the line shifted by a number of seconds,
+    /// when [`Self::display`] is [`GaugeDisplay::Line`].
+    pub ghost: Option<GhostConfig>,
 }
 
 impl Default for GaugePlotConfig {
     fn default() -> Self {
         Self {
             smoothing_weight: 0.8,
-            window_size: 500,
+            window_size: None,
             derivative: false,
+            spike_detection: None,
+            display: default(),
+            interpolation: None,
+            dial: default(),
+            distribution: default(),
+            buffer_while_hidden: true,
+            staleness: None,
+            transform: default(),
+            sampling: default(),
+            show_envelope: true,
+            show_std_dev: false,
+            ghost: None,
+        }
+    }
+}
+
+impl GaugePlotConfig {
+    /// Default configuration for drawing a dial instead of a line plot.
+    pub fn dial() -> Self {
+        Self {
+            display: GaugeDisplay::Dial,
+            ..default()
+        }
+    }
+}
+
+/// How a gauge plot is drawn.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum GaugeDisplay {
+    /// A line plot of recent values.
+    #[default]
+    Line,
+    /// A radial dial with a needle at the latest value.
+    Dial,
+    /// A bar chart of how recent values are distributed.
+    Distribution,
+}
+
+/// When a counter or gauge plot takes its samples.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum SamplingMode {
+    /// Once per rendered frame, except while virtual time is paused for
+    /// plots that [pause with it](MetricPlot::pause_with_virtual_time).
+    #[default]
+    PerFrame,
+    /// Once per tick of the fixed timestep, in [`FixedPostUpdate`], e.g. for
+    /// physics metrics recorded in [`FixedUpdate`]. Sampling per frame would
+    /// repeat or skip their values, depending on how many ticks ran in each
+    /// frame.
+    ///
+    /// The x axis counts ticks, and the plot's time source is ignored.
+    /// Values [recorded for earlier frames](MetricsRegistry::record_for_frame)
+    /// aren't inserted.
+    ///
+    /// [`FixedPostUpdate`]: bevy::app::FixedPostUpdate
+    /// [`FixedUpdate`]: bevy::app::FixedUpdate
+    PerFixedTick,
+}
+
+/// How the line of a counter or gauge plot connects consecutive samples.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum LineInterpolation {
+    /// A straight line from each sample to the next.
+    #[default]
+    Linear,
+    /// Each value starts at the previous sample, i.e. the line jumps right
+    /// after a sample.
+    StepBefore,
+    /// Each value holds until the next sample, i.e. the line jumps right
+    /// before it. Suits values that change discretely: a queue length of 3
+    /// and then 10 never passed through 6.5.
+    StepAfter,
+}
+
+impl LineInterpolation {
+    #[allow(missing_docs)]
+    pub const ALL: [Self; 3] = [Self::Linear, Self::StepBefore, Self::StepAfter];
+
+    #[allow(missing_docs)]
+    pub fn label(self) -> UiText {
+        match self {
+            Self::Linear => UiText::Linear,
+            Self::StepBefore => UiText::StepBefore,
+            Self::StepAfter => UiText::StepAfter,
+        }
+    }
+
+    /// The points of a line through `samples`, with a corner inserted
+    /// between each pair of steps.
+    pub fn points(self, samples: &[PlotPoint]) -> Vec<PlotPoint> {
+        if self == Self::Linear || samples.len() < 2 {
+            return samples.to_vec();
+        }
+        let mut points = Vec::with_capacity(2 * samples.len() - 1);
+        points.push(samples[0]);
+        for pair in samples.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let corner = match self {
+                Self::StepAfter => PlotPoint::new(b.x, a.y),
+                _ => PlotPoint::new(a.x, b.y),
+            };
+            points.extend([corner, b]);
+        }
+        points
+    }
+
+    /// The interpolation of a plot without an explicit one.
+    fn auto(steps: bool) -> Self {
+        if steps {
+            Self::StepAfter
+        } else {
+            Self::Linear
+        }
+    }
+}
+
+/// Configuration for drawing a gauge's recent values as a bar chart.
+///
+/// The buckets evenly divide the range between the smallest and largest
+/// binned value. Infinite and NaN values are left out, and nothing is drawn
+/// until a finite value is sampled:
+///
+/// ```
+/// # use bevy_metrics_dashboard::egui::{self, CentralPanel};
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::plots::{GaugeDisplay, GaugePlotConfig, MetricPlot, MetricPlotConfig};
+/// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+/// # use bevy_metrics_dashboard::{DashboardConfig, DashboardWindowConfig, FrameMetricsSnapshot};
+/// # use metrics::Key;
+/// # use std::sync::atomic::Ordering;
+/// let registry = MetricsRegistry::new();
+/// let key = MetricKey::new(Key::from_static_name("ratio"), MetricKind::Gauge);
+/// let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+///     display: GaugeDisplay::Distribution,
+///     ..Default::default()
+/// });
+/// let mut plot = MetricPlot::new(&registry, "ratio", key.clone(), None, config);
+/// let mut snapshot = FrameMetricsSnapshot::default();
+/// let ctx = egui::Context::default();
+/// for value in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 0.5] {
+///     let gauge = registry.get_or_create_gauge(&key.key);
+///     gauge.store(value.to_bits(), Ordering::Relaxed);
+///     snapshot.capture(&registry, [&key]);
+///     plot.update(&snapshot);
+///     let _ = ctx.run(Default::default(), |ctx| {
+///         CentralPanel::default().show(ctx, |ui| {
+///             plot.draw(&DashboardConfig::default(), &DashboardWindowConfig::default(), ui);
+///         });
+///     });
+/// }
+/// ```
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct DistributionConfig {
+    #[allow(missing_docs)]
+    pub n_buckets: usize,
+    /// How many of the latest samples are binned.
+    ///
+    /// When `None`, bins the plot's whole history.
+    pub window_size: Option<usize>,
+}
+
+impl Default for DistributionConfig {
+    fn default() -> Self {
+        Self {
+            n_buckets: 20,
+            window_size: None,
+        }
+    }
+}
+
+/// Configuration for drawing a gauge as a dial.
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct DialConfig {
+    #[allow(missing_docs)]
+    pub min: f64,
+    #[allow(missing_docs)]
+    pub max: f64,
+    /// Colored ranges of the dial, sorted by [`DialZone::until`].
+    ///
+    /// Each zone starts where the previous one ended, beginning at
+    /// [`Self::min`].
+    pub zones: Vec<DialZone>,
+}
+
+/// A colored range of a dial.
+#[allow(missing_docs)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub struct DialZone {
+    pub until: f64,
+    pub color: DashboardColor,
+}
+
+impl Default for DialConfig {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 100.0,
+            zones: vec![
+                DialZone {
+                    until: 60.0,
+                    color: DashboardColor::from_rgb(60, 170, 60),
+                },
+                DialZone {
+                    until: 85.0,
+                    color: DashboardColor::from_rgb(210, 170, 40),
+                },
+                DialZone {
+                    until: 100.0,
+                    color: DashboardColor::from_rgb(200, 50, 50),
+                },
+            ],
+        }
+    }
+}
+
+impl DialConfig {
+    fn configure_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut self.min)
+                    .prefix(l10n.get(UiText::MinPrefix))
+                    .speed(0.1),
+            );
+            self.max = self.max.max(self.min + 0.001);
+            ui.add(
+                DragValue::new(&mut self.max)
+                    .prefix(l10n.get(UiText::MaxPrefix))
+                    .speed(0.1),
+            );
+            self.min = self.min.min(self.max - 0.001);
+        });
+
+        let mut remove = None;
+        for (i, zone) in self.zones.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut zone.until)
+                        .prefix(l10n.get(UiText::UntilPrefix))
+                        .speed(0.1),
+                );
+                zone.color.edit_ui(ui);
+                if ui.button(l10n.get(UiText::Remove)).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.zones.remove(i);
+        }
+        if ui.button(l10n.get(UiText::AddZone)).clicked() {
+            let until = self
+                .zones
+                .last()
+                .map_or(self.max, |z| z.until.max(self.max));
+            self.zones.push(DialZone {
+                until,
+                color: Color32::GRAY.into(),
+            });
+        }
+        self.zones.sort_by_key(|z| FloatOrd(z.until));
+    }
+}
+
+/// Configuration for detecting spikes in a time-series plot.
+///
+/// A sample is a spike when it exceeds the threshold. After a spike, the
+/// detector does not fire again until a sample falls back below the threshold.
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct SpikeDetectorConfig {
+    /// How many recent samples are used to compute the rolling median.
+    pub median_window: usize,
+    /// The threshold is the rolling median multiplied by this factor.
+    pub median_multiple: f64,
+    /// When `Some`, this threshold is used instead of the rolling median.
+    pub absolute_threshold: Option<f64>,
+}
+
+impl Default for SpikeDetectorConfig {
+    fn default() -> Self {
+        Self {
+            median_window: 31,
+            median_multiple: 3.0,
+            absolute_threshold: None,
+        }
+    }
+}
+
+/// Configuration for detecting metrics that stopped updating.
+///
+/// A metric is stale when no new value arrived for [`Self::threshold`] times
+/// the typical number of frames between new values. Gauges only count as
+/// updated when their value changes, so a gauge that is repeatedly set to the
+/// same value also becomes stale.
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct StalenessConfig {
+    #[allow(missing_docs)]
+    pub threshold: f64,
+}
+
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self { threshold: 5.0 }
+    }
+}
+
+/// Configuration for drawing a ghost of a time series: a dimmed, dashed copy
+/// of the line shifted forward by [`Self::seconds`], to compare the latest
+/// samples with earlier ones at a glance.
+///
+/// The ghost is drawn from the samples already in the plot's buffer, so it
+/// only spans the plot's history minus the offset, and nothing when the
+/// buffer doesn't reach that far back. See [`MetricPlot::ghost_comparison`].
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct GhostConfig {
+    /// How many seconds the ghost lags behind the line, e.g. 60 to compare
+    /// with a minute earlier.
+    ///
+    /// The x axis counts samples, so at each refresh this is mapped to the
+    /// samples taken since, by the
+    /// [time](FrameMetricsSnapshot::elapsed) of the snapshots they were
+    /// taken from, like a [`TrailingWindow`].
+    pub seconds: f64,
+    /// Show the difference between the means of the line and of the ghost,
+    /// over the samples where both are drawn, below the plot.
+    pub show_mean_delta: bool,
+}
+
+impl Default for GhostConfig {
+    fn default() -> Self {
+        Self {
+            seconds: 4.0,
+            show_mean_delta: true,
         }
     }
 }
 
 /// Configuration for a [`MetricPlot`] of [`MetricKind::Histogram`].
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
 pub struct HistogramPlotConfig {
     /// When `Some`, the bar chart is derived from a sliding window of
     /// data. Otherwise, the bar chart retains all data until it is reset or
@@ -88,19 +568,194 @@ pub struct HistogramPlotConfig {
     pub window_size: Option<usize>,
     #[allow(missing_docs)]
     pub buckets: BucketConfig,
+    /// If false, no samples are collected while the plot isn't drawn.
+    pub buffer_while_hidden: bool,
+    /// How the histogram is drawn.
+    pub display: HistogramDisplay,
+    /// Used when [`Self::display`] is [`HistogramDisplay::FanChart`].
+    pub fan_chart: FanChartConfig,
+    /// Applied to each sample. The bucket bounds are in transformed units.
+    pub transform: PlotTransform,
+    /// When `Some`, percentiles are computed over the samples of the last
+    /// few frames, instead of only those of the latest refresh.
+    pub trailing_window: Option<TrailingWindow>,
+    /// When `Some`, up to this many of the largest samples in the sliding
+    /// window are listed below the plot, with the frame they were collected
+    /// in. Without a sliding window, the largest since the plot was created.
+    pub outliers: Option<usize>,
 }
 
+/// The default number of [`HistogramPlotConfig::outliers`] when they're
+/// enabled in the settings.
+pub const DEFAULT_OUTLIERS: usize = 5;
+
 impl Default for HistogramPlotConfig {
     fn default() -> Self {
         Self {
-            window_size: Some(500),
+            window_size: Some(DEFAULT_WINDOW_SIZE),
             buckets: default(),
+            buffer_while_hidden: true,
+            display: default(),
+            fan_chart: default(),
+            transform: default(),
+            trailing_window: None,
+            outliers: None,
+        }
+    }
+}
+
+/// How a histogram plot is drawn.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum HistogramDisplay {
+    /// A bar chart of the bucket counts.
+    #[default]
+    Buckets,
+    /// Percentile bands of the samples in each refresh, over time.
+    FanChart,
+}
+
+/// Configuration for drawing a histogram as a fan chart.
+///
+/// Every [refresh](MetricPlot::refresh), the percentiles of the samples
+/// collected since the last refresh are added to the chart. Refreshes
+/// without samples break the bands.
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct FanChartConfig {
+    /// The lower and upper percentile of each band, in `0.0..=100.0`.
+    ///
+    /// Wider bands are drawn with a lower alpha.
+    pub bands: Vec<[f64; 2]>,
+    /// If true, draws the median as a line on top of the bands.
+    pub median: bool,
+    /// The color of the median line, from which the band colors are derived.
+    pub color: DashboardColor,
+    /// How many refreshes are drawn.
+    pub window_size: usize,
+}
+
+impl Default for FanChartConfig {
+    fn default() -> Self {
+        Self {
+            bands: vec![[5.0, 95.0], [25.0, 75.0]],
+            median: true,
+            color: Color32::LIGHT_BLUE.into(),
+            window_size: DEFAULT_WINDOW_SIZE,
+        }
+    }
+}
+
+impl FanChartConfig {
+    fn configure_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        let mut remove = None;
+        for (i, [lower, upper]) in self.bands.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(lower)
+                        .prefix(l10n.get(UiText::FromPercentilePrefix))
+                        .speed(0.5)
+                        .range(0.0..=100.0),
+                );
+                *upper = upper.max(*lower);
+                ui.add(
+                    DragValue::new(upper)
+                        .prefix(l10n.get(UiText::ToPercentilePrefix))
+                        .speed(0.5)
+                        .range(0.0..=100.0),
+                );
+                *lower = lower.min(*upper);
+                if ui.button(l10n.get(UiText::Remove)).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.bands.remove(i);
+        }
+        if ui.button(l10n.get(UiText::AddBand)).clicked() {
+            self.bands.push([10.0, 90.0]);
+        }
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.median, l10n.get(UiText::Median));
+            self.color.edit_ui(ui);
+        });
+        ui.add(window_size_slider(&l10n, &mut self.window_size));
+    }
+
+    /// The fill color of each band, with wider bands more transparent.
+    fn band_colors(&self) -> Vec<Color32> {
+        let mut by_width: Vec<_> = (0..self.bands.len()).collect();
+        by_width.sort_by_key(|&i| {
+            let [lower, upper] = self.bands[i];
+            FloatOrd(lower - upper)
+        });
+        let mut colors = vec![Color32::TRANSPARENT; self.bands.len()];
+        for (rank, i) in by_width.into_iter().enumerate() {
+            let alpha = 0.6 * (rank + 1) as f32 / (self.bands.len() + 1) as f32;
+            colors[i] = Color32::from(self.color).gamma_multiply(alpha);
+        }
+        colors
+    }
+}
+
+/// The frames that a histogram's percentiles are computed over, e.g. for
+/// the p99 of the last 5 seconds instead of the noisy p99 of one refresh.
+///
+/// The plot shows the p50, p95 and p99 of the window, fan charts draw the
+/// percentiles of the window at each refresh, and
+/// [`MetricPlot::trailing_stats`] returns its statistics.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct TrailingWindow {
+    /// How many seconds of the latest frames are kept, by the
+    /// [time](FrameMetricsSnapshot::elapsed) of the snapshots they were
+    /// sampled from.
+    pub seconds: f64,
+    /// Frames with more samples keep this many, each standing for an equal
+    /// share of the frame's samples, from lowest to highest. At most
+    /// `max_samples_per_frame` samples are kept for each frame with samples
+    /// in the window, so memory grows with the frame rate.
+    ///
+    /// This is an approximation: the extremes of such frames are dropped.
+    pub max_samples_per_frame: usize,
+}
+
+impl Default for TrailingWindow {
+    fn default() -> Self {
+        Self {
+            seconds: 5.0,
+            max_samples_per_frame: 64,
         }
     }
 }
 
+impl TrailingWindow {
+    fn configure_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut self.seconds)
+                    .prefix(l10n.get(UiText::SecondsPrefix))
+                    .speed(0.1)
+                    .range(0.1..=600.0),
+            );
+            ui.add(
+                DragValue::new(&mut self.max_samples_per_frame)
+                    .prefix(l10n.get(UiText::SamplesPerFramePrefix))
+                    .range(1..=10_000),
+            );
+        });
+    }
+}
+
 /// Configuration of the buckets in a histogram.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
 pub struct BucketConfig {
     /// Sorted list of boundaries between contiguous bucket ranges.
     ///
@@ -117,7 +772,9 @@ pub type CountsVec = SmallVec<[u32; 16]>;
 
 /// A uniformly distributed set of buckets.
 #[allow(missing_docs)]
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
 pub struct BucketRange {
     pub n_buckets: usize,
     pub min: f64,
@@ -180,357 +837,2889 @@ impl Default for BucketConfig {
     }
 }
 
-/// A plot for any [`MetricKind`], rendering with [`egui_plot`].
-pub struct MetricPlot {
-    name: String,
-    key: MetricKey,
-    unit: Option<Unit>,
-    data: MetricPlotData,
+/// A labeled vertical marker on the time axis of a plot.
+#[derive(Clone, Default)]
+struct Annotation {
+    label: String,
+    color: Option<Color32>,
+    /// Index of the sample being marked.
+    sample: u64,
 }
 
-#[allow(clippy::large_enum_variant)]
-enum MetricPlotData {
-    Counter(CounterData),
-    Gauge(GaugeData),
-    Histogram(HistogramData),
+/// A note attached to one sample of a time series, e.g. "spike caused by
+/// the save system".
+#[derive(Clone, Default)]
+struct SampleNote {
+    /// Index of the sample being noted.
+    sample: u64,
+    text: String,
 }
 
-struct CounterData {
-    source: Arc<AtomicU64>,
-    ring: Ring<u64>,
-    config: CounterPlotConfig,
+/// A sample found by the [`SpikeDetector`].
+#[derive(Clone, Default)]
+struct Spike {
+    sample: u64,
+    value: f64,
 }
 
-impl CounterData {
-    fn new(config: CounterPlotConfig, source: Arc<AtomicU64>) -> Self {
-        let CounterPlotConfig { window_size, .. } = config;
-        Self {
-            source,
-            ring: Ring::new(window_size),
-            config,
-        }
-    }
+/// The frames in which the samples of a time series were collected, to move
+/// values [recorded for earlier frames](MetricsRegistry::record_for_frame) to
+/// their samples.
+#[derive(Default)]
+struct FrameAttribution {
+    /// The frame and sample where each run of consecutive frames starts,
+    /// oldest first. Runs break when samples aren't collected, e.g. while
+    /// the window is paused.
+    runs: VecDeque<(u64, u64)>,
+    /// The frame and sample of the latest sample.
+    latest: Option<(u64, u64)>,
+    /// Values for frames that weren't sampled, or are no longer retained.
+    dropped: u64,
+}
 
-    fn handle_global_config(&mut self, dash_config: &DashboardConfig) {
-        if let Some(window_size) = dash_config.global_window_size {
-            self.config.window_size = window_size;
-            self.ring.set_max_len(self.config.window_size);
+impl FrameAttribution {
+    /// Note that `sample` was collected in `frame`, forgetting runs that
+    /// ended before `first_retained`.
+    fn observe(&mut self, frame: u64, sample: u64, first_retained: u64) {
+        let continues = self
+            .latest
+            .is_some_and(|(f, s)| frame == f + 1 && sample == s + 1);
+        if !continues {
+            self.runs.push_back((frame, sample));
         }
-    }
-
-    fn configure_ui(&mut self, enable_window_size: bool, ui: &mut Ui) {
-        ui.checkbox(&mut self.config.derivative, "Derivative");
-
-        if enable_window_size
-            && ui
-                .add(window_size_slider(&mut self.config.window_size))
-                .changed()
-        {
-            self.ring.set_max_len(self.config.window_size);
+        self.latest = Some((frame, sample));
+        while self.runs.get(1).is_some_and(|&(_, s)| s <= first_retained) {
+            self.runs.pop_front();
         }
     }
 
-    fn update(&mut self) {
-        let value = self.source.load(Ordering::Relaxed);
-        self.ring.push(value);
+    /// The retained sample collected in `frame`, if it's older than the
+    /// latest one. Values for older frames without one are counted as
+    /// dropped.
+    fn sample(&mut self, frame: u64, first_retained: u64) -> Option<u64> {
+        let (latest_frame, latest_sample) = self.latest?;
+        if frame >= latest_frame {
+            // Already included in the latest sample.
+            return None;
+        }
+        let run = self.runs.partition_point(|&(f, _)| f <= frame);
+        let sample = run.checked_sub(1).and_then(|run| {
+            let (run_frame, run_sample) = self.runs[run];
+            let sample = run_sample + (frame - run_frame);
+            let run_end = self.runs.get(run + 1).map_or(latest_sample, |&(_, s)| s);
+            (sample < run_end && sample >= first_retained).then_some(sample)
+        });
+        if sample.is_none() {
+            self.dropped += 1;
+        }
+        sample
     }
 }
 
-struct GaugeData {
-    source: Arc<AtomicU64>,
-    smoother: Smoother,
-    ring: Ring<f64>,
-    config: GaugePlotConfig,
+/// Markers drawn over a time-series plot.
+struct TimeSeriesMarkers {
+    annotations: Ring<Annotation>,
+    spikes: Ring<Spike>,
+    spike_detector: Option<SpikeDetector>,
+    /// Kept while their samples are evicted, but only drawn while retained.
+    notes: Vec<SampleNote>,
+    /// When `Some`, the note at this sample was just added and its text
+    /// edit gets the keyboard focus.
+    new_note: Option<u64>,
+    /// When `Some`, the plot will be panned to center on this sample.
+    pan_to: Option<u64>,
 }
 
-impl GaugeData {
-    fn new(config: GaugePlotConfig, source: Arc<AtomicU64>) -> Self {
-        let GaugePlotConfig {
-            window_size,
-            smoothing_weight,
-            ..
-        } = config;
+impl TimeSeriesMarkers {
+    fn new(spike_detection: Option<SpikeDetectorConfig>) -> Self {
         Self {
-            source,
-            smoother: Smoother::new(smoothing_weight),
-            ring: Ring::new(window_size),
-            config,
+            annotations: Ring::new(MAX_ANNOTATIONS),
+            spikes: Ring::new(MAX_SPIKES),
+            spike_detector: spike_detection.map(SpikeDetector::new),
+            notes: Vec::new(),
+            new_note: None,
+            pan_to: None,
         }
     }
 
-    fn handle_global_config(&mut self, dash_config: &DashboardConfig) {
-        if let Some(window_size) = dash_config.global_window_size {
-            self.config.window_size = window_size;
-            self.ring.set_max_len(self.config.window_size);
+    /// Add an empty note at `sample`, unless it already has one.
+    fn add_note(&mut self, sample: u64) {
+        if !self.notes.iter().any(|n| n.sample == sample) {
+            self.notes.push(SampleNote {
+                sample,
+                text: String::new(),
+            });
+            self.notes.sort_by_key(|n| n.sample);
+        }
+        self.new_note = Some(sample);
+    }
+
+    /// List the sample notes, to edit, remove or pan to them.
+    fn notes_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        let new_note = self.new_note.take();
+        let mut remove = None;
+        for (i, note) in self.notes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .link(l10n.format(UiText::NoteAtFrame, &[("frame", &note.sample)]))
+                    .clicked()
+                {
+                    self.pan_to = Some(note.sample);
+                }
+                let response = ui.text_edit_singleline(&mut note.text);
+                if new_note == Some(note.sample) {
+                    response.request_focus();
+                }
+                if ui.small_button(l10n.get(UiText::Remove)).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.notes.remove(i);
         }
     }
 
-    fn configure_ui(&mut self, enable_window_size: bool, ui: &mut Ui) {
-        ui.checkbox(&mut self.config.derivative, "Derivative");
+    fn annotate(&mut self, label: String, color: Option<Color32>, sample: u64) {
+        self.annotations.push(Annotation {
+            label,
+            color,
+            sample,
+        });
+    }
 
-        if enable_window_size
-            && ui
-                .add(window_size_slider(&mut self.config.window_size))
-                .changed()
-        {
-            self.ring.set_max_len(self.config.window_size);
+    fn detect_spike(&mut self, sample: u64, value: f64) {
+        if let Some(detector) = &mut self.spike_detector {
+            if detector.add(value) {
+                self.spikes.push(Spike { sample, value });
+            }
         }
+    }
 
-        ui.add(Slider::new(&mut self.config.smoothing_weight, 0.0..=1.0).text("Smoothing Weight"));
-        self.smoother.weight = self.config.smoothing_weight;
+    fn is_firing(&self) -> bool {
+        self.spike_detector
+            .as_ref()
+            .is_some_and(SpikeDetector::is_firing)
     }
 
-    fn update(&mut self) {
-        let value = f64::from_bits(self.source.load(Ordering::Relaxed));
-        self.smoother.add(value);
-        self.ring.push(self.smoother.smoothed_value());
+    fn configure_ui(&mut self, config: &mut Option<SpikeDetectorConfig>, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        let mut enabled = config.is_some();
+        ui.checkbox(&mut enabled, l10n.get(UiText::SpikeDetection));
+        if !enabled {
+            *config = None;
+            self.spike_detector = None;
+            return;
+        }
+
+        let config = config.get_or_insert_with(default);
+        let mut use_absolute = config.absolute_threshold.is_some();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut use_absolute, l10n.get(UiText::AbsoluteThreshold));
+            if use_absolute {
+                let threshold = config.absolute_threshold.get_or_insert(0.0);
+                ui.add(DragValue::new(threshold).speed(0.1));
+            } else {
+                config.absolute_threshold = None;
+                ui.add(
+                    DragValue::new(&mut config.median_multiple)
+                        .prefix(l10n.get(UiText::MedianTimesPrefix))
+                        .speed(0.05)
+                        .range(1.0..=f64::MAX),
+                );
+                ui.add(
+                    DragValue::new(&mut config.median_window)
+                        .prefix(l10n.get(UiText::WindowPrefix))
+                        .speed(0.2)
+                        .range(1..=1000),
+                );
+            }
+        });
+        match &mut self.spike_detector {
+            Some(detector) => detector.set_config(config.clone()),
+            None => self.spike_detector = Some(SpikeDetector::new(config.clone())),
+        }
+
+        if self.spikes.latest().is_none() {
+            ui.label(l10n.get(UiText::NoSpikes));
+            return;
+        }
+        ui.collapsing(l10n.get(UiText::Spikes), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(100.0)
+                .show(ui, |ui| {
+                    for spike in self.spikes.iter_chronological() {
+                        let text = l10n.format(
+                            UiText::Spike,
+                            &[
+                                ("sample", &spike.sample),
+                                ("value", &l10n.number(spike.value, 3)),
+                            ],
+                        );
+                        if ui.selectable_label(false, text).clicked() {
+                            self.pan_to = Some(spike.sample);
+                        }
+                    }
+                });
+        });
     }
 }
 
-struct HistogramData {
-    source: Arc<AtomicBucket<f64>>,
-    ring: Option<Ring<f64>>,
-    bucket_counts: CountsVec,
-    config: HistogramPlotConfig,
+/// A plot for any [`MetricKind`], rendering with [`egui_plot`].
+///
+/// Its name, config and display options are reflected, e.g. to edit them in
+/// an inspector. Sample buffers aren't, so a plot can't be created from a
+/// reflected value; save it as a [`ScenePlot`](crate::ScenePlot) instead.
+///
+/// ```
+/// # use bevy::reflect::GetPath;
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlot, MetricPlotConfig};
+/// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+/// # use bevy_metrics_dashboard::theme::DashboardColor;
+/// # use metrics::Key;
+/// let registry = MetricsRegistry::new();
+/// let key = MetricKey::new(Key::from_static_name("enemies"), MetricKind::Gauge);
+/// let config = MetricPlotConfig::Gauge(GaugePlotConfig::default());
+/// let mut plot = MetricPlot::new(&registry, "enemies", key, None, config);
+///
+/// *plot.path_mut::<f64>("data.0.config.smoothing_weight").unwrap() = 0.25;
+/// let blue = DashboardColor::from_rgb(0, 0, 255);
+/// *plot.path_mut("data.0.config.dial.zones[0].color").unwrap() = blue;
+///
+/// let MetricPlotConfig::Gauge(config) = plot.clone_config() else {
+///     unreachable!()
+/// };
+/// assert_eq!(config.smoothing_weight, 0.25);
+/// assert_eq!(config.dial.zones[0].color, blue);
+/// ```
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+pub struct MetricPlot {
+    name: String,
+    #[reflect(ignore)]
+    key: MetricKey,
+    #[reflect(ignore)]
+    unit: Option<MetricUnit>,
+    data: MetricPlotData,
+    #[reflect(ignore)]
+    display_names: DisplayNames,
+    style_override: Option<PlotStyle>,
+    overlay: Option<OverlayCorner>,
+    docs_url: Option<String>,
+    note: String,
+    group: Option<String>,
+    pause_with_virtual_time: Option<bool>,
+    /// Whether the last sample paused with virtual time, for annotating
+    /// changes.
+    #[reflect(ignore)]
+    sampled_pause_with_virtual_time: Option<bool>,
+    /// Whether the plot was drawn since the last [`Self::collect`].
+    #[reflect(ignore)]
+    drawn: bool,
+    /// Whether the plot was drawn in the previous frame, for ticks that run
+    /// before it's drawn again.
+    #[reflect(ignore)]
+    drawn_last_frame: bool,
+    /// Whether any part of the plot was drawn on screen since the last
+    /// [`Self::refresh`].
+    #[reflect(ignore)]
+    on_screen: bool,
+    /// The width available when the plot was last drawn, and the height it
+    /// took, for the placeholder drawn while it's scrolled out of view.
+    #[reflect(ignore)]
+    drawn_size: Option<(f32, f32)>,
+    #[reflect(ignore)]
+    created: Instant,
+    /// Counts the calls to [`Self::collect`] and [`Self::collect_tick`] that
+    /// found the metric.
+    #[reflect(ignore)]
+    revision: u64,
+    /// The revision, config and downsampling of the last [`Self::refresh`],
+    /// to skip it when none of them changed.
+    #[reflect(ignore)]
+    refreshed: Option<(u64, MetricPlotConfig, usize)>,
 }
 
-impl HistogramData {
-    fn new(config: HistogramPlotConfig, source: Arc<AtomicBucket<f64>>) -> Self {
-        let n_buckets = config.buckets.bounds.len() + 1;
+#[allow(clippy::large_enum_variant)]
+#[derive(Reflect)]
+enum MetricPlotData {
+    Counter(CounterData),
+    Gauge(GaugeData),
+    Histogram(HistogramData),
+}
 
-        Self {
-            source,
-            ring: None,
-            bucket_counts: smallvec![0; n_buckets],
-            config,
+/// Plots can't be created from reflected values, but reflecting them in
+/// lists and enums requires [`FromReflect`].
+macro_rules! impl_no_from_reflect {
+    ($($ty:ty),*) => {$(
+        impl FromReflect for $ty {
+            fn from_reflect(_: &dyn PartialReflect) -> Option<Self> {
+                None
+            }
         }
+    )*};
+}
+
+impl_no_from_reflect!(MetricPlot, CounterData, GaugeData, HistogramData);
+
+/// Ranges of samples without data, in push order.
+///
+/// The ring still holds placeholder values for these samples, so the x axis
+/// keeps counting frames.
+#[derive(Default)]
+struct Gaps {
+    /// Samples that weren't collected, e.g. while the plot was hidden.
+    ranges: VecDeque<Range<u64>>,
+    /// Samples after the metric went stale, kept apart so they can be
+    /// forgotten when staleness detection is turned off.
+    stale: VecDeque<Range<u64>>,
+}
+
+impl Gaps {
+    /// Mark `sample` as missing. Samples must be marked in push order.
+    fn push(&mut self, sample: u64) {
+        Self::merge_into(&mut self.ranges, sample..sample + 1);
     }
 
-    fn configure_ui(&mut self, ui: &mut Ui) {
-        let mut update = false;
-        ui.horizontal(|ui| {
-            update |= ui
-                .add(
-                    DragValue::new(&mut self.config.buckets.range_input.n_buckets)
-                        .prefix("Buckets: ")
-                        .speed(0.1),
-                )
-                .changed();
-            update |= ui
-                .add(
-                    DragValue::new(&mut self.config.buckets.range_input.min)
-                        .prefix("Min: ")
-                        .speed(0.1),
-                )
-                .changed();
-            self.config.buckets.range_input.clamp_max();
-            update |= ui
-                .add(
-                    DragValue::new(&mut self.config.buckets.range_input.max)
-                        .prefix("Max: ")
-                        .speed(0.1),
+    /// Mark `samples` as stale, merging with the latest stale gaps if they
+    /// overlap.
+    fn push_stale(&mut self, samples: Range<u64>) {
+        Self::merge_into(&mut self.stale, samples);
+    }
+
+    fn merge_into(ranges: &mut VecDeque<Range<u64>>, mut samples: Range<u64>) {
+        while let Some(last) = ranges.back() {
+            if last.end < samples.start {
+                break;
+            }
+            samples.start = samples.start.min(last.start);
+            samples.end = samples.end.max(last.end);
+            ranges.pop_back();
+        }
+        ranges.push_back(samples);
+    }
+
+    /// Forget the stale gaps.
+    fn clear_stale(&mut self) {
+        self.stale.clear();
+    }
+
+    fn contains(&self, sample: u64) -> bool {
+        self.ranges
+            .iter()
+            .chain(&self.stale)
+            .any(|gap| gap.contains(&sample))
+    }
+
+    /// All gaps in order, with overlapping ones merged.
+    fn merged(&self) -> VecDeque<Range<u64>> {
+        let mut all: Vec<_> = self.ranges.iter().chain(&self.stale).cloned().collect();
+        all.sort_by_key(|gap| gap.start);
+        let mut merged = VecDeque::with_capacity(all.len());
+        for gap in all {
+            Self::merge_into(&mut merged, gap);
+        }
+        merged
+    }
+
+    /// Forget gaps that ended before `first_sample`.
+    fn prune(&mut self, first_sample: u64) {
+        for ranges in [&mut self.ranges, &mut self.stale] {
+            while ranges.front().is_some_and(|gap| gap.end <= first_sample) {
+                ranges.pop_front();
+            }
+        }
+    }
+}
+
+/// Detects when a metric stops receiving new values.
+#[derive(Default)]
+struct StalenessTracker {
+    /// The sample and time of the latest new value.
+    last_update: Option<(u64, Instant)>,
+    /// Moving average of the number of samples between new values.
+    typical_interval: f64,
+    stale: bool,
+}
+
+impl StalenessTracker {
+    /// Observe `sample`, which has a new value if `updated`.
+    ///
+    /// Returns the samples since the last update if the metric is stale.
+    fn observe(
+        &mut self,
+        sample: u64,
+        updated: bool,
+        config: &StalenessConfig,
+    ) -> Option<Range<u64>> {
+        if updated {
+            if let (Some((last, _)), false) = (self.last_update, self.stale) {
+                let interval = (sample - last) as f64;
+                self.typical_interval = if self.typical_interval == 0.0 {
+                    interval
+                } else {
+                    0.9 * self.typical_interval + 0.1 * interval
+                };
+            }
+            self.last_update = Some((sample, Instant::now()));
+            self.stale = false;
+            return None;
+        }
+
+        let (last, _) = self.last_update?;
+        let waited = (sample - last) as f64;
+        self.stale = waited > config.threshold * self.typical_interval.max(1.0);
+        self.stale.then_some(last + 1..sample + 1)
+    }
+
+    /// Forget the metric's updates and stale gaps, e.g. when staleness
+    /// detection is turned off.
+    fn reset(&mut self, gaps: &mut Gaps) {
+        *self = default();
+        gaps.clear_stale();
+    }
+
+    /// Don't count a sample that was skipped while hidden.
+    fn skip(&mut self) {
+        if let Some((last, _)) = &mut self.last_update {
+            *last += 1;
+        }
+    }
+
+    fn stale_for(&self) -> Option<Duration> {
+        let (_, time) = self.last_update?;
+        self.stale.then(|| time.elapsed())
+    }
+}
+
+/// Plot points computed by [`MetricPlot::refresh`], so drawing doesn't rebuild
+/// them every frame.
+#[derive(Default)]
+struct CachedPoints {
+    first_sample: u64,
+    points: Vec<PlotPoint>,
+    /// Ranges of [`Self::points`] that are drawn as connected lines.
+    segments: Vec<Range<usize>>,
+    /// The x ranges of gaps between segments.
+    gaps: Vec<Range<f64>>,
+    interpolation: LineInterpolation,
+    /// See [`MetricPlot::set_downsampling`].
+    downsampling: usize,
+    /// How many samples the [ghost](GhostConfig) lags behind the line, or
+    /// `None` without a ghost or enough history for it.
+    ghost_offset: Option<usize>,
+}
+
+impl CachedPoints {
+    fn refresh<T>(
+        &mut self,
+        ring: &Ring<T>,
+        gaps: &mut Gaps,
+        derivative: bool,
+        transform: &PlotTransform,
+        interpolation: LineInterpolation,
+    ) where
+        T: Clone + Default + num_traits::NumCast,
+    {
+        self.first_sample = ring.first_index();
+        self.interpolation = interpolation;
+        self.points = ring.make_plot_points();
+        if derivative {
+            self::derivative(&mut self.points);
+        }
+        if !transform.is_identity() {
+            for point in &mut self.points {
+                point.y = transform.apply(point.y);
+            }
+        }
+
+        gaps.prune(self.first_sample);
+        self.segments.clear();
+        self.gaps.clear();
+        let n_points = self.points.len();
+        let mut segment_start = 0;
+        for gap in &gaps.merged() {
+            let gap_start = ((gap.start.saturating_sub(self.first_sample)) as usize).min(n_points);
+            let gap_end = ((gap.end - self.first_sample) as usize).min(n_points);
+            if gap_start > segment_start {
+                self.segments.push(segment_start..gap_start);
+            }
+            // Shade from the last sample before the gap to the first one
+            // after it.
+            let shade_start = gap.start.max(self.first_sample + 1) - 1;
+            self.gaps.push(shade_start as f64..gap.end as f64);
+            segment_start = gap_end;
+        }
+        if segment_start < n_points {
+            self.segments.push(segment_start..n_points);
+        }
+    }
+
+    /// The values of the points in [`Self::segments`].
+    fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.segments
+            .iter()
+            .flat_map(|segment| &self.points[segment.clone()])
+            .map(|p| p.y)
+    }
+
+    /// The sample and value of the points in [`Self::segments`].
+    fn samples(&self) -> impl Iterator<Item = (u64, f64)> + '_ {
+        self.segments
+            .iter()
+            .flat_map(|segment| &self.points[segment.clone()])
+            .map(|p| (p.x as u64, p.y))
+    }
+
+    fn lines(&self, name: &str) -> Vec<Line<'static>> {
+        self.segments
+            .iter()
+            .map(|segment| {
+                Line::new(
+                    name,
+                    PlotPoints::Owned(self.interpolation.points(&downsample(
+                        &self.points[segment.clone()],
+                        self.downsampling,
+                    ))),
                 )
-                .changed();
-            self.config.buckets.range_input.clamp_min();
+            })
+            .collect()
+    }
+
+    /// The lines of [`Self::lines`] shifted forward by [`Self::ghost_offset`]
+    /// samples, up to the latest point. See [`GhostConfig`].
+    fn ghost_lines(&self, name: &str, color: Color32) -> Vec<Line<'static>> {
+        let (Some(last), Some(offset)) = (self.points.last().map(|p| p.x), self.ghost_offset)
+        else {
+            return Vec::new();
+        };
+        let offset = offset as f64;
+        self.segments
+            .iter()
+            .filter_map(|segment| {
+                let shifted: Vec<_> = self.points[segment.clone()]
+                    .iter()
+                    .map(|p| PlotPoint::new(p.x + offset, p.y))
+                    .take_while(|p| p.x <= last)
+                    .collect();
+                (!shifted.is_empty()).then(|| {
+                    Line::new(
+                        name,
+                        PlotPoints::Owned(
+                            self.interpolation
+                                .points(&downsample(&shifted, self.downsampling)),
+                        ),
+                    )
+                    .color(color)
+                    .style(LineStyle::dashed_loose())
+                })
+            })
+            .collect()
+    }
+
+    /// Compare the points drawn where [`Self::ghost_lines`] are with the
+    /// ghost, or `None` if the points don't span more than
+    /// [`Self::ghost_offset`] samples.
+    fn ghost_comparison(&self) -> Option<GhostComparison> {
+        let first = self.points.first()?.x;
+        let last = self.points.last()?.x;
+        let offset = self.ghost_offset? as f64;
+        if last - first < offset {
+            return None;
+        }
+        let values = |range: Range<f64>| -> Vec<f64> {
+            self.segments
+                .iter()
+                .flat_map(|segment| &self.points[segment.clone()])
+                .filter(|p| range.contains(&p.x))
+                .map(|p| p.y)
+                .collect()
+        };
+        let current = values(first + offset..last + 1.0);
+        let ghost = values(first..last - offset + 1.0);
+        Some(GhostComparison {
+            samples: (last - first - offset) as usize + 1,
+            mean: mean(&current)?,
+            ghost_mean: mean(&ghost)?,
+        })
+    }
+}
+
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+struct CounterData {
+    #[reflect(ignore)]
+    ring: Ring<u64>,
+    #[reflect(ignore)]
+    times: SampleTimes,
+    /// The [`DashboardConfig::default_history_secs`] that the window size
+    /// follows, if it isn't a number of samples.
+    #[reflect(ignore)]
+    history_secs: Option<f64>,
+    #[reflect(ignore)]
+    frames: FrameAttribution,
+    #[reflect(ignore)]
+    gaps: Gaps,
+    #[reflect(ignore)]
+    staleness: StalenessTracker,
+    #[reflect(ignore)]
+    cached: CachedPoints,
+    #[reflect(ignore)]
+    markers: TimeSeriesMarkers,
+    config: CounterPlotConfig,
+}
+
+impl CounterData {
+    fn new(config: CounterPlotConfig) -> Self {
+        let CounterPlotConfig { window_size, .. } = config;
+        let window_size = window_size.unwrap_or(DEFAULT_WINDOW_SIZE);
+        Self {
+            ring: Ring::new(window_size),
+            times: SampleTimes::new(window_size),
+            history_secs: None,
+            frames: default(),
+            gaps: default(),
+            staleness: default(),
+            cached: default(),
+            markers: TimeSeriesMarkers::new(config.spike_detection.clone()),
+            config,
+        }
+    }
+
+    /// Count each increment recorded for an earlier frame from that frame on.
+    ///
+    /// The counter was incremented when the value was recorded, so the
+    /// latest sample already includes it.
+    fn attribute(&mut self, frame: FrameId, samples: &[FrameSample]) {
+        let latest = self.ring.n_pushed().saturating_sub(1);
+        self.frames
+            .observe(frame.0, latest, self.ring.first_index());
+        for late in samples {
+            let Some(sample) = self.frames.sample(late.frame.0, self.ring.first_index()) else {
+                continue;
+            };
+            for sample in sample..latest {
+                if let Some(total) = self.ring.get_mut(sample) {
+                    *total += late.value as u64;
+                }
+            }
+        }
+    }
+
+    fn handle_global_config(
+        &mut self,
+        dash_config: &DashboardConfig,
+        window_config: &DashboardWindowConfig,
+    ) {
+        match resolve_window_size(dash_config, window_config, self.config.window_size) {
+            WindowSize::Samples(window_size) => {
+                self.history_secs = None;
+                self.set_window_size(window_size);
+            }
+            WindowSize::Seconds(seconds) => self.history_secs = Some(seconds),
+        }
+    }
+
+    fn set_window_size(&mut self, window_size: usize) {
+        if window_size != self.ring.max_len() {
+            self.ring.set_max_len(window_size);
+            self.times.0.set_max_len(window_size);
+        }
+    }
+
+    fn configure_ui(&mut self, enable_window_size: bool, unit: Option<&MetricUnit>, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.checkbox(&mut self.config.derivative, l10n.get(UiText::Derivative));
+        interpolation_ui(&mut self.config.interpolation, ui);
+        sampling_mode_ui(&mut self.config.sampling, ui);
+        ui.checkbox(
+            &mut self.config.buffer_while_hidden,
+            l10n.get(UiText::BufferWhileHidden),
+        );
+        staleness_ui(&mut self.config.staleness, ui);
+        if self.config.derivative && self.config.staleness.is_some() {
+            ui.checkbox(
+                &mut self.config.stale_rate_as_zero,
+                l10n.get(UiText::StaleRateAsZero),
+            );
+        }
+
+        if enable_window_size {
+            window_size_override_ui(&mut self.config.window_size, self.ring.max_len(), ui);
+        }
+        ghost_ui(&mut self.config.ghost, ui);
+
+        let latest = self.ring.latest().map(|&v| v as f64);
+        ui.collapsing(l10n.get(UiText::Transform), |ui| {
+            self.config.transform.configure_ui(unit, latest, ui);
         });
-        if update {
-            self.update_bounds_from_input();
+
+        ui.separator();
+        self.markers
+            .configure_ui(&mut self.config.spike_detection, ui);
+    }
+
+    fn collect(&mut self, visible: bool, time: Duration, value: u64) {
+        if let Some(seconds) = self.history_secs {
+            self.set_window_size(self.times.window_size(time, seconds));
+        }
+        self.times.0.push(time);
+        let sample = self.ring.n_pushed();
+        if !visible && !self.config.buffer_while_hidden {
+            // Keep the total so the first increment after the gap is right.
+            self.gaps.push(sample);
+            self.staleness.skip();
+            self.ring.push(value);
+            return;
         }
+        let previous = self.ring.latest().copied();
+        let increment = value.saturating_sub(previous.unwrap_or(value));
+        self.markers
+            .detect_spike(sample, self.config.transform.apply(increment as f64));
+        self.ring.push(value);
+
+        if let Some(config) = &self.config.staleness {
+            let stale = self
+                .staleness
+                .observe(sample, previous != Some(value), config);
+            // No increments genuinely means a rate of zero.
+            let show_zero = self.config.derivative && self.config.stale_rate_as_zero;
+            if let (Some(stale), false) = (stale, show_zero) {
+                self.gaps.push_stale(stale);
+            }
+        } else {
+            self.staleness.reset(&mut self.gaps);
+        }
+    }
+
+    /// `counts` is whether the metric's unit is [`Unit::Count`].
+    fn refresh(&mut self, counts: bool) {
+        let interpolation = self
+            .config
+            .interpolation
+            .unwrap_or_else(|| LineInterpolation::auto(self.config.derivative || counts));
+        self.cached.refresh(
+            &self.ring,
+            &mut self.gaps,
+            self.config.derivative,
+            &self.config.transform,
+            interpolation,
+        );
+        self.cached.ghost_offset = self.times.ghost_offset(self.config.ghost.as_ref());
+    }
+}
+
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+struct GaugeData {
+    #[reflect(ignore)]
+    smoother: Smoother,
+    #[reflect(ignore)]
+    ring: Ring<f64>,
+    #[reflect(ignore)]
+    times: SampleTimes,
+    /// The [`DashboardConfig::default_history_secs`] that the window size
+    /// follows, if it isn't a number of samples.
+    #[reflect(ignore)]
+    history_secs: Option<f64>,
+    #[reflect(ignore)]
+    frames: FrameAttribution,
+    /// The latest raw value, used to detect updates.
+    #[reflect(ignore)]
+    last_bits: Option<u64>,
+    #[reflect(ignore)]
+    gaps: Gaps,
+    #[reflect(ignore)]
+    staleness: StalenessTracker,
+    #[reflect(ignore)]
+    cached: CachedPoints,
+    /// The lowest and highest raw values in each sample, indexed like
+    /// [`Self::ring`]. Created when the first envelope is collected.
+    #[reflect(ignore)]
+    envelope: Option<Ring<(f64, f64)>>,
+    /// `[x, lower, upper]` runs of [`Self::envelope`] drawn as bands.
+    #[reflect(ignore)]
+    cached_envelope: Vec<Vec<[f64; 3]>>,
+    /// The standard deviation around each smoothed value, indexed like
+    /// [`Self::ring`], while [`GaugePlotConfig::show_std_dev`] is enabled.
+    #[reflect(ignore)]
+    std_dev: Option<Ring<f64>>,
+    /// `[x, lower, upper]` runs of [`Self::std_dev`] drawn as bands.
+    #[reflect(ignore)]
+    cached_std_dev: Vec<Vec<[f64; 3]>>,
+    #[reflect(ignore)]
+    distribution: Distribution,
+    #[reflect(ignore)]
+    markers: TimeSeriesMarkers,
+    config: GaugePlotConfig,
+}
+
+/// Recent values of a time series, binned into evenly sized buckets.
+#[derive(Default)]
+struct Distribution {
+    bounds: BoundsVec,
+    counts: CountsVec,
+}
+
+impl Distribution {
+    /// Bin the finite `values`. Without any, nothing is drawn.
+    fn refresh(&mut self, values: &[f64], n_buckets: usize) {
+        self.bounds.clear();
+        self.counts.clear();
+        let mut finite = values.iter().copied().filter(|v| v.is_finite());
+        let Some(first) = finite.next() else {
+            return;
+        };
+        if n_buckets == 0 {
+            return;
+        }
+        let (min, max) = finite.fold((first, first), |(min, max), v| (min.min(v), max.max(v)));
+        // Values on a bound are counted in the lower bucket, so move the
+        // lowest bound below the minimum.
+        let mut range = BucketRange {
+            n_buckets,
+            min: min - f64::EPSILON * min.abs().max(1.0),
+            max,
+        };
+        range.clamp_max();
+        self.bounds = range.get_bounds();
+        self.counts = smallvec![0; self.bounds.len() + 1];
+        for &value in values.iter().filter(|v| v.is_finite()) {
+            add_value_to_bucket(&self.bounds, value, &mut self.counts);
+        }
+    }
+}
+
+impl GaugeData {
+    fn new(config: GaugePlotConfig) -> Self {
+        let GaugePlotConfig {
+            window_size,
+            smoothing_weight,
+            ..
+        } = config;
+        let window_size = window_size.unwrap_or(DEFAULT_WINDOW_SIZE);
+        Self {
+            smoother: Smoother::new(smoothing_weight),
+            last_bits: None,
+            ring: Ring::new(window_size),
+            times: SampleTimes::new(window_size),
+            history_secs: None,
+            frames: default(),
+            gaps: default(),
+            staleness: default(),
+            cached: default(),
+            envelope: None,
+            cached_envelope: Vec::new(),
+            std_dev: None,
+            cached_std_dev: Vec::new(),
+            distribution: default(),
+            markers: TimeSeriesMarkers::new(config.spike_detection.clone()),
+            config,
+        }
+    }
+
+    fn handle_global_config(
+        &mut self,
+        dash_config: &DashboardConfig,
+        window_config: &DashboardWindowConfig,
+    ) {
+        match resolve_window_size(dash_config, window_config, self.config.window_size) {
+            WindowSize::Samples(window_size) => {
+                self.history_secs = None;
+                self.set_window_size(window_size);
+            }
+            WindowSize::Seconds(seconds) => self.history_secs = Some(seconds),
+        }
+    }
+
+    fn set_window_size(&mut self, window_size: usize) {
+        if window_size != self.ring.max_len() {
+            self.ring.set_max_len(window_size);
+            self.times.0.set_max_len(window_size);
+            if let Some(envelope) = &mut self.envelope {
+                envelope.set_max_len(window_size);
+            }
+            if let Some(std_dev) = &mut self.std_dev {
+                std_dev.set_max_len(window_size);
+            }
+        }
+    }
 
+    fn configure_ui(&mut self, enable_window_size: bool, unit: Option<&MetricUnit>, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            ui.selectable_value(
+                &mut self.config.display,
+                GaugeDisplay::Line,
+                l10n.get(UiText::Line),
+            );
+            ui.selectable_value(
+                &mut self.config.display,
+                GaugeDisplay::Dial,
+                l10n.get(UiText::Dial),
+            );
+            ui.selectable_value(
+                &mut self.config.display,
+                GaugeDisplay::Distribution,
+                l10n.get(UiText::Distribution),
+            );
+        });
+        match self.config.display {
+            GaugeDisplay::Line => {
+                interpolation_ui(&mut self.config.interpolation, ui);
+                if self.envelope.is_some() {
+                    ui.checkbox(
+                        &mut self.config.show_envelope,
+                        l10n.get(UiText::ShowEnvelope),
+                    );
+                }
+            }
+            GaugeDisplay::Dial => self.config.dial.configure_ui(ui),
+            GaugeDisplay::Distribution => {
+                let config = &mut self.config.distribution;
+                ui.add(
+                    DragValue::new(&mut config.n_buckets)
+                        .prefix(l10n.get(UiText::BucketsPrefix))
+                        .speed(0.1)
+                        .range(1..=200),
+                );
+                let mut limit = config.window_size.is_some();
+                ui.checkbox(&mut limit, l10n.get(UiText::LimitSamples));
+                if limit {
+                    ui.add(window_size_slider(
+                        &l10n,
+                        config.window_size.get_or_insert(self.ring.max_len()),
+                    ));
+                } else {
+                    config.window_size = None;
+                }
+            }
+        }
         ui.separator();
 
-        let mut use_sliding_window = self.config.window_size.is_some();
-        if ui
-            .checkbox(&mut use_sliding_window, "Sliding Window")
-            .changed()
-            && !use_sliding_window
-        {
-            self.config.window_size = None;
-            self.ring = None;
-            self.bucket_counts.fill(0);
+        ui.checkbox(&mut self.config.derivative, l10n.get(UiText::Derivative));
+        sampling_mode_ui(&mut self.config.sampling, ui);
+        ui.checkbox(
+            &mut self.config.buffer_while_hidden,
+            l10n.get(UiText::BufferWhileHidden),
+        );
+        staleness_ui(&mut self.config.staleness, ui);
+
+        if enable_window_size {
+            window_size_override_ui(&mut self.config.window_size, self.ring.max_len(), ui);
         }
-        if use_sliding_window {
-            let window_size = self.config.window_size.get_or_insert(500);
-            if ui.add(window_size_slider(window_size)).changed() {
-                self.ring = Some(Ring::new(*window_size));
+
+        ui.add(
+            Slider::new(&mut self.config.smoothing_weight, 0.0..=1.0)
+                .text(l10n.get(UiText::SmoothingWeight)),
+        );
+        self.smoother.set_weight(self.config.smoothing_weight);
+        if self.config.display == GaugeDisplay::Line {
+            ui.checkbox(&mut self.config.show_std_dev, l10n.get(UiText::ShowStdDev))
+                .on_hover_text(l10n.get(UiText::ShowStdDevHint));
+            ghost_ui(&mut self.config.ghost, ui);
+        }
+
+        let latest = self.ring.latest().copied();
+        ui.collapsing(l10n.get(UiText::Transform), |ui| {
+            self.config.transform.configure_ui(unit, latest, ui);
+        });
+
+        ui.separator();
+        self.markers
+            .configure_ui(&mut self.config.spike_detection, ui);
+    }
+
+    /// Replace the samples of earlier frames with the values recorded for
+    /// them. Late values aren't smoothed.
+    fn attribute(&mut self, frame: FrameId, samples: &[FrameSample]) {
+        let latest = self.ring.n_pushed().saturating_sub(1);
+        self.frames
+            .observe(frame.0, latest, self.ring.first_index());
+        for late in samples {
+            let Some(sample) = self.frames.sample(late.frame.0, self.ring.first_index()) else {
+                continue;
+            };
+            if let Some(value) = self.ring.get_mut(sample) {
+                *value = late.value;
+            }
+        }
+    }
+
+    /// `envelope` is the lowest and highest value since the previous sample,
+    /// if the gauge's envelope is tracked.
+    fn collect(&mut self, visible: bool, time: Duration, value: f64, envelope: Option<(f64, f64)>) {
+        if let Some(seconds) = self.history_secs {
+            self.set_window_size(self.times.window_size(time, seconds));
+        }
+        self.times.0.push(time);
+        let sample = self.ring.n_pushed();
+        if let Some(envelope) = envelope {
+            self.envelope
+                .get_or_insert_with(|| Ring::starting_at(self.ring.max_len(), sample))
+                .push(envelope);
+        } else if let Some(ring) = &mut self.envelope {
+            ring.push((value, value));
+        }
+        if !visible && !self.config.buffer_while_hidden {
+            self.gaps.push(sample);
+            self.staleness.skip();
+            let placeholder = self.ring.latest().copied().unwrap_or_default();
+            self.ring.push(placeholder);
+            if let Some(std_dev) = &mut self.std_dev {
+                std_dev.push(0.0);
+            }
+            return;
+        }
+        let bits = value.to_bits();
+        self.markers
+            .detect_spike(sample, self.config.transform.apply(value));
+        self.smoother.add(value);
+        self.ring
+            .push(self.smoother.smoothed_value().unwrap_or(value));
+        if self.config.show_std_dev {
+            self.std_dev
+                .get_or_insert_with(|| Ring::starting_at(self.ring.max_len(), sample))
+                .push(self.smoother.std_dev());
+        } else {
+            self.std_dev = None;
+        }
+
+        let updated = self.last_bits.replace(bits) != Some(bits);
+        if let Some(config) = &self.config.staleness {
+            if let Some(stale) = self.staleness.observe(sample, updated, config) {
+                self.gaps.push_stale(stale);
             }
+        } else {
+            self.staleness.reset(&mut self.gaps);
+        }
+    }
+
+    /// `counts` is whether the metric's unit is [`Unit::Count`].
+    fn refresh(&mut self, counts: bool) {
+        let interpolation = self
+            .config
+            .interpolation
+            .unwrap_or_else(|| LineInterpolation::auto(counts));
+        self.cached.refresh(
+            &self.ring,
+            &mut self.gaps,
+            self.config.derivative,
+            &self.config.transform,
+            interpolation,
+        );
+        self.cached.ghost_offset = self.times.ghost_offset(self.config.ghost.as_ref());
+        self.refresh_envelope();
+        self.refresh_std_dev();
+        if self.config.display == GaugeDisplay::Distribution {
+            let DistributionConfig {
+                n_buckets,
+                window_size,
+            } = self.config.distribution;
+            let n_samples = (self.ring.n_pushed() - self.ring.first_index()) as usize;
+            let skip = window_size.map_or(0, |w| n_samples.saturating_sub(w));
+            let values: Vec<f64> = (self.ring.first_index()..)
+                .zip(self.ring.iter_chronological())
+                .skip(skip)
+                .filter(|&(sample, _)| !self.gaps.contains(sample))
+                .map(|(_, &value)| self.config.transform.apply(value))
+                .collect();
+            self.distribution.refresh(&values, n_buckets);
+        }
+    }
+
+    /// Split the envelope of the drawn segments into runs of samples that
+    /// have one, downsampled like the line.
+    fn refresh_envelope(&mut self) {
+        self.cached_envelope.clear();
+        let Some(envelope) = &self.envelope else {
+            return;
+        };
+        if !self.config.show_envelope
+            || self.config.derivative
+            || self.config.display != GaugeDisplay::Line
+        {
+            return;
+        }
+        self.cached_envelope = band_runs(&self.cached, &self.config.transform, |sample| {
+            envelope.get(sample).copied()
+        });
+    }
+
+    /// Like [`Self::refresh_envelope`], for one standard deviation around
+    /// the smoothed values.
+    fn refresh_std_dev(&mut self) {
+        self.cached_std_dev.clear();
+        let Some(std_dev) = &self.std_dev else {
+            return;
+        };
+        if self.config.derivative || self.config.display != GaugeDisplay::Line {
+            return;
+        }
+        let ring = &self.ring;
+        self.cached_std_dev = band_runs(&self.cached, &self.config.transform, |sample| {
+            let (&value, &std_dev) = (ring.get(sample)?, std_dev.get(sample)?);
+            Some((value - std_dev, value + std_dev))
+        });
+    }
+
+    fn envelope_polygons(&self, name: &str, color: Color32) -> Vec<Polygon<'static>> {
+        self.cached_envelope
+            .iter()
+            .flat_map(|run| band_polygons(name, run, color))
+            .collect()
+    }
+
+    fn std_dev_polygons(&self, name: &str, color: Color32) -> Vec<Polygon<'static>> {
+        self.cached_std_dev
+            .iter()
+            .flat_map(|run| band_polygons(name, run, color))
+            .collect()
+    }
+}
+
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+struct HistogramData {
+    #[reflect(ignore)]
+    ring: Option<Ring<f64>>,
+    /// Samples collected since the last refresh, when not using a sliding
+    /// window.
+    #[reflect(ignore)]
+    pending: Vec<f64>,
+    /// The latest raw sample, for previewing the transform.
+    #[reflect(ignore)]
+    last_sample: Option<f64>,
+    #[reflect(ignore)]
+    bucket_counts: CountsVec,
+    #[reflect(ignore)]
+    fan: FanSeries,
+    #[reflect(ignore)]
+    trailing: TrailingSamples,
+    #[reflect(ignore)]
+    outliers: Outliers,
+    /// How many frames samples were collected in.
+    #[reflect(ignore)]
+    frames_collected: u64,
+    /// The frame and label of recent annotations, to show with the outliers
+    /// collected near them.
+    #[reflect(ignore)]
+    annotations: Ring<(u64, String)>,
+    /// When `Some`, the fan chart will be panned to center on this refresh.
+    #[reflect(ignore)]
+    pan_to: Option<u64>,
+    config: HistogramPlotConfig,
+}
+
+/// When each sample of a counter or gauge plot was taken, indexed like its
+/// ring, to measure its [history](DashboardConfig::default_history_secs) and
+/// [ghost](GhostConfig::seconds) in seconds.
+///
+/// Like a [`TrailingWindow`], samples are timed by the
+/// [time](FrameMetricsSnapshot::elapsed) of the snapshots they were taken
+/// from.
+struct SampleTimes(Ring<Duration>);
+
+impl SampleTimes {
+    fn new(window_size: usize) -> Self {
+        Self(Ring::new(window_size))
+    }
+
+    /// How many of the latest samples were taken less than `seconds` before
+    /// `time`, or `None` if all of them were.
+    fn count_within(&self, time: Duration, seconds: f64) -> Option<usize> {
+        let length = Duration::try_from_secs_f64(seconds).unwrap_or_default();
+        self.0
+            .iter_chronological()
+            .rev()
+            .position(|&t| time.saturating_sub(t) >= length)
+    }
+
+    /// The window size that keeps `seconds` of samples when another one is
+    /// taken at `time`: the samples taken less than `seconds` before it,
+    /// the latest one taken earlier, so that the plot spans all of them,
+    /// and the new one.
+    fn window_size(&self, time: Duration, seconds: f64) -> usize {
+        self.count_within(time, seconds)
+            .map_or(self.0.len(), |n| n + 1)
+            + 1
+    }
+
+    /// How many samples before the latest one the sample `ghost` compares
+    /// it with was taken, i.e. the latest one taken at least
+    /// [`GhostConfig::seconds`] earlier.
+    fn ghost_offset(&self, ghost: Option<&GhostConfig>) -> Option<usize> {
+        self.count_within(*self.0.latest()?, ghost?.seconds)
+    }
+}
+
+/// The samples of the latest frames, for a [`TrailingWindow`].
+#[derive(Default)]
+struct TrailingSamples {
+    /// One entry per frame with samples, oldest first, with its time, the
+    /// kept samples and how many samples each of them stands for.
+    frames: VecDeque<(Duration, Vec<f64>, f64)>,
+}
+
+impl TrailingSamples {
+    /// Add the `samples` of the frame at `time`, and drop the frames that
+    /// are more than the window's seconds older.
+    fn push(&mut self, window: &TrailingWindow, time: Duration, samples: &[f64]) {
+        let length = Duration::try_from_secs_f64(window.seconds).unwrap_or_default();
+        let mut kept = Vec::new();
+        while self
+            .frames
+            .front()
+            .is_some_and(|&(t, ..)| time.saturating_sub(t) >= length)
+        {
+            // Reuse the oldest frame's allocation.
+            kept = self
+                .frames
+                .pop_front()
+                .map(|(_, kept, _)| kept)
+                .unwrap_or_default();
+        }
+        if samples.is_empty() {
+            return;
+        }
+        kept.clear();
+        kept.extend_from_slice(samples);
+
+        let max = window.max_samples_per_frame.max(1);
+        let n = kept.len();
+        let mut weight = 1.0;
+        if n > max {
+            kept.sort_unstable_by_key(|&v| FloatOrd(v));
+            for i in 0..max {
+                // The middle of the `i`th share. It's never below `i`, so
+                // it's read before being overwritten.
+                let rank = (2 * i + 1) * n / (2 * max);
+                kept[i] = kept[rank];
+            }
+            kept.truncate(max);
+            weight = n as f64 / max as f64;
+        }
+        self.frames.push_back((time, kept, weight));
+    }
+
+    fn stats(&self, transform: &PlotTransform) -> Option<PlotStats> {
+        let values = self
+            .frames
+            .iter()
+            .flat_map(|(_, kept, weight)| kept.iter().map(|&v| (transform.apply(v), *weight)));
+        PlotStats::weighted(values.collect())
+    }
+}
+
+/// One of the largest samples of a histogram. Ordered by value.
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+struct Outlier {
+    value: FloatOrd<f64>,
+    /// The index of the sample among all collected samples.
+    sample: u64,
+    /// The index of the frame it was collected in.
+    frame: u64,
+    /// The index of the fan chart refresh that includes it.
+    refresh: u64,
+}
+
+/// The largest samples of a histogram, for
+/// [`HistogramPlotConfig::outliers`].
+#[derive(Default)]
+struct Outliers {
+    /// The smallest outlier is on top, to be replaced by larger samples.
+    heap: BinaryHeap<Reverse<Outlier>>,
+    /// The first sample, frame and refresh of each collected frame, oldest
+    /// first, to find the frames of retained samples when the heap is
+    /// rebuilt.
+    frames: VecDeque<(u64, u64, u64)>,
+    /// The index of the next sample without a sliding window.
+    next_sample: u64,
+}
+
+impl Outliers {
+    fn clear(&mut self) {
+        self.heap.clear();
+        self.frames.clear();
+    }
+
+    /// Note that the samples from `first_sample` on were collected in
+    /// `frame`, and are included in `refresh`.
+    fn start_frame(&mut self, first_sample: u64, frame: u64, refresh: u64) {
+        self.frames.push_back((first_sample, frame, refresh));
+    }
+
+    /// Keep `value` if it's among the `max_len` largest samples. This is
+    /// `O(log max_len)`.
+    fn push(&mut self, max_len: usize, value: f64, sample: u64) {
+        let Some(&(_, frame, refresh)) = self.frames.back() else {
+            return;
+        };
+        let outlier = Outlier {
+            value: FloatOrd(value),
+            sample,
+            frame,
+            refresh,
+        };
+        if self.heap.len() < max_len {
+            self.heap.push(Reverse(outlier));
+        } else if self.heap.peek().is_some_and(|Reverse(min)| outlier > *min) {
+            self.heap.pop();
+            self.heap.push(Reverse(outlier));
+        }
+    }
+
+    /// Drop outliers that left the sliding window `ring`, replacing them
+    /// with the largest samples still in it.
+    ///
+    /// The heap is only rebuilt when an outlier is dropped, which happens
+    /// about `max_len` times per window.
+    fn retain_window(&mut self, max_len: usize, ring: &Ring<f64>) {
+        let first = ring.first_index();
+        while self.frames.get(1).is_some_and(|&(s, ..)| s <= first) {
+            self.frames.pop_front();
+        }
+        let evicted = self.heap.iter().any(|Reverse(o)| o.sample < first);
+        if !evicted && self.heap.len() <= max_len {
+            return;
+        }
+        let frames = std::mem::take(&mut self.frames);
+        self.heap.clear();
+        for (sample, &value) in (first..).zip(ring.iter_chronological()) {
+            let run = frames.partition_point(|&(s, ..)| s <= sample);
+            let Some(&(_, frame, refresh)) = run.checked_sub(1).and_then(|run| frames.get(run))
+            else {
+                continue;
+            };
+            let outlier = Outlier {
+                value: FloatOrd(value),
+                sample,
+                frame,
+                refresh,
+            };
+            self.heap.push(Reverse(outlier));
+            if self.heap.len() > max_len {
+                self.heap.pop();
+            }
+        }
+        self.frames = frames;
+    }
+
+    /// Drop the smallest outliers beyond `max_len`, without a sliding window.
+    fn truncate(&mut self, max_len: usize) {
+        while self.heap.len() > max_len {
+            self.heap.pop();
+        }
+        // Without a sliding window, only the frames of the outliers matter.
+        let oldest = self.heap.iter().map(|Reverse(o)| o.sample).min();
+        while self
+            .frames
+            .get(1)
+            .is_some_and(|&(s, ..)| oldest.is_none_or(|oldest| s <= oldest))
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    /// The outliers, largest first.
+    fn sorted(&self) -> Vec<Outlier> {
+        let mut outliers: Vec<_> = self.heap.iter().map(|Reverse(o)| *o).collect();
+        outliers.sort_unstable_by(|a, b| b.cmp(a));
+        outliers
+    }
+}
+
+/// The band percentiles followed by the median.
+type PercentilesVec = SmallVec<[f64; 8]>;
+
+/// Percentiles of the samples in each refresh, for the fan chart.
+struct FanSeries {
+    /// Samples collected since the last refresh.
+    samples: Vec<f64>,
+    /// One entry per refresh. Empty for refreshes without samples.
+    ring: Ring<PercentilesVec>,
+}
+
+impl FanSeries {
+    /// Add the percentiles of the samples since the last refresh, or of the
+    /// `trailing` window's samples if there is one.
+    fn refresh(
+        &mut self,
+        config: &FanChartConfig,
+        transform: &PlotTransform,
+        trailing: Option<Option<PlotStats>>,
+    ) {
+        if self.ring.max_len() != config.window_size {
+            self.ring.set_max_len(config.window_size);
+        }
+        let mut values = PercentilesVec::new();
+        let median = config.median.then_some(50.0);
+        let ps = config.bands.iter().flatten().copied().chain(median);
+        if let Some(stats) = trailing {
+            // An empty window gives no values, which breaks the bands.
+            if let Some(stats) = stats {
+                values.extend(ps.map(|p| stats.percentile(p)));
+            }
+            self.ring.push(values);
+            self.samples.clear();
+            return;
+        }
+        let samples = &mut self.samples;
+        if !transform.is_identity() {
+            for sample in samples.iter_mut() {
+                *sample = transform.apply(*sample);
+            }
+        }
+        for p in ps {
+            // Empty samples give no values, which breaks the bands.
+            values.extend(percentile(samples, p));
+        }
+        self.ring.push(values);
+        self.samples.clear();
+    }
+
+    /// Polygons for the bands and lines for the median.
+    ///
+    /// Entries computed with a different config (before it changed) are
+    /// treated like refreshes without samples.
+    fn items(
+        &self,
+        name: &str,
+        config: &FanChartConfig,
+    ) -> (Vec<Polygon<'static>>, Vec<Line<'static>>) {
+        let n_values = 2 * config.bands.len() + usize::from(config.median);
+        let first = self.ring.first_index();
+        let entries: Vec<_> = self.ring.iter_chronological().collect();
+        // Split into runs of refreshes with samples.
+        let segments: Vec<_> = entries
+            .split(|values| values.len() != n_values)
+            .scan(0, |start, run| {
+                let segment = (*start, run);
+                *start += run.len() + 1;
+                Some(segment)
+            })
+            .filter(|(_, run)| !run.is_empty())
+            .collect();
+
+        let mut polygons = Vec::new();
+        for (band, color) in config.band_colors().into_iter().enumerate() {
+            for &(start, run) in &segments {
+                let points: Vec<_> = run
+                    .iter()
+                    .enumerate()
+                    .map(|(i, values)| {
+                        let x = (first + (start + i) as u64) as f64;
+                        [x, values[2 * band], values[2 * band + 1]]
+                    })
+                    .collect();
+                polygons.extend(band_polygons(name, &points, color));
+            }
+        }
+        let mut lines = Vec::new();
+        if config.median {
+            for &(start, run) in &segments {
+                let points: Vec<_> = run
+                    .iter()
+                    .enumerate()
+                    .map(|(i, values)| [(first + (start + i) as u64) as f64, values[n_values - 1]])
+                    .collect();
+                lines.push(Line::new(name, PlotPoints::new(points)).color(config.color));
+            }
+        }
+        (polygons, lines)
+    }
+}
+
+/// Fill the area between the bounds of `points`, which are
+/// `[x, lower, upper]` in increasing x.
+///
+/// The outline of the band is the upper path followed by the reversed lower
+/// path. `egui` only fills convex polygons, so the outline is filled with one
+/// trapezoid per pair of neighboring points.
+fn band_polygons(name: &str, points: &[[f64; 3]], color: Color32) -> Vec<Polygon<'static>> {
+    let n = points.len();
+    let outline: Vec<[f64; 2]> = points
+        .iter()
+        .map(|&[x, _, upper]| [x, upper])
+        .chain(points.iter().rev().map(|&[x, lower, _]| [x, lower]))
+        .collect();
+    (1..n)
+        .map(|i| {
+            let quad = vec![
+                outline[i - 1],
+                outline[i],
+                outline[2 * n - 1 - i],
+                outline[2 * n - i],
+            ];
+            Polygon::new(name, PlotPoints::new(quad))
+                .fill_color(color)
+                .stroke(Stroke::NONE)
+                .allow_hover(false)
+        })
+        .collect()
+}
+
+impl HistogramData {
+    fn new(config: HistogramPlotConfig) -> Self {
+        let n_buckets = config.buckets.bounds.len() + 1;
+
+        Self {
+            ring: None,
+            pending: Vec::new(),
+            last_sample: None,
+            bucket_counts: smallvec![0; n_buckets],
+            fan: FanSeries {
+                samples: Vec::new(),
+                ring: Ring::new(config.fan_chart.window_size),
+            },
+            trailing: default(),
+            outliers: default(),
+            frames_collected: 0,
+            annotations: Ring::new(MAX_ANNOTATIONS),
+            pan_to: None,
+            config,
+        }
+    }
+
+    fn configure_ui(&mut self, unit: Option<&MetricUnit>, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            ui.selectable_value(
+                &mut self.config.display,
+                HistogramDisplay::Buckets,
+                l10n.get(UiText::Buckets),
+            );
+            ui.selectable_value(
+                &mut self.config.display,
+                HistogramDisplay::FanChart,
+                l10n.get(UiText::FanChart),
+            );
+        });
+        ui.collapsing(l10n.get(UiText::Transform), |ui| {
+            let transform = self.config.transform.clone();
+            self.config
+                .transform
+                .configure_ui(unit, self.last_sample, ui);
+            // Counts without a sliding window can't be recomputed.
+            if self.config.transform != transform && self.ring.is_none() {
+                self.bucket_counts.fill(0);
+            }
+        });
+        let mut trailing = self.config.trailing_window.is_some();
+        if ui
+            .checkbox(&mut trailing, l10n.get(UiText::TrailingPercentiles))
+            .on_hover_text(l10n.get(UiText::TrailingPercentilesHint))
+            .changed()
+            && !trailing
+        {
+            self.config.trailing_window = None;
+            self.trailing.frames.clear();
+        }
+        if trailing {
+            self.config
+                .trailing_window
+                .get_or_insert_with(default)
+                .configure_ui(ui);
+        }
+        ui.horizontal(|ui| {
+            let mut outliers = self.config.outliers.is_some();
+            if ui
+                .checkbox(&mut outliers, l10n.get(UiText::CaptureOutliers))
+                .changed()
+            {
+                self.config.outliers = outliers.then_some(DEFAULT_OUTLIERS);
+                self.outliers.clear();
+            }
+            if let Some(max_len) = &mut self.config.outliers {
+                ui.add(DragValue::new(max_len).range(1..=100));
+            }
+        });
+        if self.config.display == HistogramDisplay::FanChart {
+            self.config.fan_chart.configure_ui(ui);
+            ui.separator();
+            ui.checkbox(
+                &mut self.config.buffer_while_hidden,
+                l10n.get(UiText::BufferWhileHidden),
+            );
+            return;
+        }
+
+        let mut update = false;
+        ui.horizontal(|ui| {
+            update |= ui
+                .add(
+                    DragValue::new(&mut self.config.buckets.range_input.n_buckets)
+                        .prefix(l10n.get(UiText::BucketsPrefix))
+                        .speed(0.1),
+                )
+                .changed();
+            update |= ui
+                .add(
+                    DragValue::new(&mut self.config.buckets.range_input.min)
+                        .prefix(l10n.get(UiText::MinPrefix))
+                        .speed(0.1),
+                )
+                .changed();
+            self.config.buckets.range_input.clamp_max();
+            update |= ui
+                .add(
+                    DragValue::new(&mut self.config.buckets.range_input.max)
+                        .prefix(l10n.get(UiText::MaxPrefix))
+                        .speed(0.1),
+                )
+                .changed();
+            self.config.buckets.range_input.clamp_min();
+        });
+        if update {
+            self.update_bounds_from_input();
+        }
+
+        ui.separator();
+
+        ui.checkbox(
+            &mut self.config.buffer_while_hidden,
+            l10n.get(UiText::BufferWhileHidden),
+        );
+
+        let mut use_sliding_window = self.config.window_size.is_some();
+        if ui
+            .checkbox(&mut use_sliding_window, l10n.get(UiText::SlidingWindow))
+            .changed()
+            && !use_sliding_window
+        {
+            self.config.window_size = None;
+            self.ring = None;
+            self.bucket_counts.fill(0);
+            self.outliers.clear();
+        }
+        if use_sliding_window {
+            let window_size = self.config.window_size.get_or_insert(DEFAULT_WINDOW_SIZE);
+            if ui.add(window_size_slider(&l10n, window_size)).changed() {
+                self.ring = Some(Ring::new(*window_size));
+                self.outliers.clear();
+            }
+        }
+    }
+
+    fn update_bounds_from_input(&mut self) {
+        let Some(new_bounds) = self.config.buckets.get_bounds() else {
+            return;
+        };
+
+        self.config.buckets.bounds = new_bounds;
+
+        self.bucket_counts
+            .resize(self.config.buckets.bounds.len() + 1, 0);
+        self.bucket_counts.fill(0);
+    }
+
+    fn make_bar_chart(&self, name: &str, overflow_color: Color32) -> BarChart {
+        make_bar_chart(
+            name,
+            &self.config.buckets.bounds,
+            &self.bucket_counts,
+            overflow_color,
+        )
+    }
+
+    /// `time` is when `samples` were captured, for the trailing window.
+    fn collect(&mut self, visible: bool, time: Duration, samples: &[f64]) {
+        if !visible && !self.config.buffer_while_hidden {
+            return;
+        }
+        if let Some(&last) = samples.last() {
+            self.last_sample = Some(last);
+        }
+        if let Some(window) = &self.config.trailing_window {
+            self.trailing.push(window, time, samples);
+        }
+        if self.config.display == HistogramDisplay::FanChart {
+            self.fan.samples.extend_from_slice(samples);
+        }
+        let frame = self.frames_collected;
+        self.frames_collected += 1;
+        let refresh = self.fan.ring.n_pushed();
+        // Frames without samples don't need to be tracked.
+        let outliers = self.config.outliers.filter(|_| !samples.is_empty());
+        if let Some(window_size) = self.config.window_size {
+            // We only need to take the latest values up to the ring's
+            // capacity. The histogram doesn't actually care about the order
+            // of elements, it just needs to always have the most recent
+            // N elements.
+            let ring = self.ring.get_or_insert_with(|| Ring::new(window_size));
+            if outliers.is_some() {
+                self.outliers.start_frame(ring.n_pushed(), frame, refresh);
+            }
+            for &value in samples.iter().rev().take(ring.max_len()) {
+                if let Some(max_len) = outliers {
+                    self.outliers.push(max_len, value, ring.n_pushed());
+                }
+                ring.push(value);
+            }
+            if let Some(max_len) = outliers {
+                self.outliers.retain_window(max_len, ring);
+            }
+        } else {
+            self.pending.extend_from_slice(samples);
+            if let Some(max_len) = outliers {
+                let first_sample = self.outliers.next_sample;
+                self.outliers.start_frame(first_sample, frame, refresh);
+                for &value in samples {
+                    let sample = self.outliers.next_sample;
+                    self.outliers.next_sample += 1;
+                    self.outliers.push(max_len, value, sample);
+                }
+                self.outliers.truncate(max_len);
+            }
+        }
+    }
+
+    /// Mark the latest collected frame with `label`, for the outliers
+    /// collected near it.
+    fn annotate(&mut self, label: String) {
+        let frame = self.frames_collected.saturating_sub(1);
+        self.annotations.push((frame, label));
+    }
+
+    /// The label of the annotation closest to `frame`, if there is one.
+    fn nearest_annotation(&self, frame: u64) -> Option<&str> {
+        self.annotations
+            .iter_chronological()
+            .min_by_key(|(f, _)| f.abs_diff(frame))
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// List the [outliers](HistogramPlotConfig::outliers), largest first.
+    /// In a fan chart, clicking one pans the chart to it.
+    fn outliers_ui(&mut self, numbers: &NumberFormat, ui: &mut Ui) {
+        if self.config.outliers.is_none() || self.outliers.heap.is_empty() {
+            return;
+        }
+        let l10n = DashboardLocalization::of(ui);
+        let fan_chart = self.config.display == HistogramDisplay::FanChart;
+        ui.collapsing(l10n.get(UiText::Outliers), |ui| {
+            for outlier in self.outliers.sorted() {
+                let value = self.config.transform.apply(outlier.value.0);
+                let ago = self.frames_collected - 1 - outlier.frame;
+                let mut text = l10n.format(
+                    UiText::Outlier,
+                    &[
+                        ("value", &numbers.format(value, l10n.decimal_separator)),
+                        ("frames", &ago),
+                    ],
+                );
+                if let Some(label) = self.nearest_annotation(outlier.frame) {
+                    text.push_str(" · ");
+                    text.push_str(&l10n.format(UiText::NearAnnotation, &[("label", &label)]));
+                }
+                if !fan_chart {
+                    ui.label(text);
+                } else if ui.selectable_label(false, text).clicked() {
+                    self.pan_to = Some(outlier.refresh);
+                }
+            }
+        });
+    }
+
+    fn refresh(&mut self) {
+        if let Some(ring) = &self.ring {
+            // We are only counting within a sliding window, so clear
+            // counts first.
+            self.bucket_counts.fill(0);
+            for &value in ring.iter_chronological() {
+                let value = self.config.transform.apply(value);
+                add_value_to_bucket(&self.config.buckets.bounds, value, &mut self.bucket_counts);
+            }
+        }
+        // Keep adding to the existing buckets.
+        for value in self.pending.drain(..) {
+            let value = self.config.transform.apply(value);
+            add_value_to_bucket(&self.config.buckets.bounds, value, &mut self.bucket_counts);
+        }
+        if self.config.display == HistogramDisplay::FanChart {
+            let trailing = self.config.trailing_window.map(|_| self.trailing_stats());
+            self.fan
+                .refresh(&self.config.fan_chart, &self.config.transform, trailing);
+        }
+    }
+
+    fn trailing_stats(&self) -> Option<PlotStats> {
+        self.trailing.stats(&self.config.transform)
+    }
+
+    /// The p50, p95 and p99 of the trailing window, if there is one.
+    fn trailing_summary(
+        &self,
+        numbers: &NumberFormat,
+        l10n: &DashboardLocalization,
+    ) -> Option<String> {
+        let window = self.config.trailing_window.as_ref()?;
+        let stats = self.trailing_stats();
+        let p = |p| {
+            stats.as_ref().map_or_else(
+                || "-".to_owned(),
+                |s| numbers.format(s.percentile(p), l10n.decimal_separator),
+            )
+        };
+        Some(l10n.format(
+            UiText::TrailingSummary,
+            &[
+                ("seconds", &l10n.number(window.seconds, 1)),
+                ("p50", &p(50.0)),
+                ("p95", &p(95.0)),
+                ("p99", &p(99.0)),
+            ],
+        ))
+    }
+}
+
+/// The latest samples of a plot compared with its [ghost](GhostConfig). See
+/// [`MetricPlot::ghost_comparison`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GhostComparison {
+    /// How many samples the ghost spans, i.e. how many of the latest samples
+    /// are compared.
+    pub samples: usize,
+    /// The mean of the line over the compared samples.
+    pub mean: f64,
+    /// The mean of the ghost.
+    pub ghost_mean: f64,
+}
+
+impl GhostComparison {
+    /// How much larger the mean of the line is than that of the ghost.
+    pub fn mean_delta(&self) -> f64 {
+        self.mean - self.ghost_mean
+    }
+}
+
+/// Summary statistics of a plot's buffer. See [`MetricPlot::stats`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotStats {
+    /// Never empty.
+    sorted: Vec<f64>,
+    /// The running total of the weights of [`Self::sorted`], or empty if
+    /// every value stands for one sample.
+    cumulative_weights: Vec<f64>,
+}
+
+impl PlotStats {
+    /// Statistics of `values`, or `None` if there are none.
+    pub fn new(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable_by_key(|&v| FloatOrd(v));
+        Some(Self {
+            sorted: values,
+            cumulative_weights: Vec::new(),
+        })
+    }
+
+    /// Statistics of values that each stand for `weight` samples.
+    fn weighted(mut values: Vec<(f64, f64)>) -> Option<Self> {
+        if values.iter().all(|&(_, weight)| weight == 1.0) {
+            return Self::new(values.into_iter().map(|(v, _)| v).collect());
+        }
+        values.sort_unstable_by_key(|&(v, _)| FloatOrd(v));
+        let cumulative_weights = values
+            .iter()
+            .scan(0.0, |total, &(_, weight)| {
+                *total += weight;
+                Some(*total)
+            })
+            .collect();
+        Some(Self {
+            sorted: values.into_iter().map(|(v, _)| v).collect(),
+            cumulative_weights,
+        })
+    }
+
+    /// The number of samples, including those that subsampled values stand
+    /// for.
+    pub fn n_samples(&self) -> usize {
+        match self.cumulative_weights.last() {
+            Some(total) => total.round() as usize,
+            None => self.sorted.len(),
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn min(&self) -> f64 {
+        self.sorted[0]
+    }
+
+    #[allow(missing_docs)]
+    pub fn max(&self) -> f64 {
+        self.sorted[self.sorted.len() - 1]
+    }
+
+    #[allow(missing_docs)]
+    pub fn mean(&self) -> f64 {
+        let Some(&total) = self.cumulative_weights.last() else {
+            return mean(&self.sorted).unwrap_or_default();
+        };
+        let mut previous = 0.0;
+        let sum: f64 = (self.sorted.iter().zip(&self.cumulative_weights))
+            .map(|(&v, &cumulative)| {
+                v * (cumulative - std::mem::replace(&mut previous, cumulative))
+            })
+            .sum();
+        sum / total
+    }
+
+    /// The `p`th percentile, from 0 to 100, by the nearest-rank method.
+    pub fn percentile(&self, p: f64) -> f64 {
+        let Some(&total) = self.cumulative_weights.last() else {
+            return percentile_of_sorted(&self.sorted, p).unwrap_or_default();
+        };
+        let rank = p.clamp(0.0, 100.0) / 100.0 * total;
+        let i = self.cumulative_weights.partition_point(|&c| c < rank);
+        self.sorted[i.min(self.sorted.len() - 1)]
+    }
+}
+
+impl MetricPlot {
+    /// Create a new plot from the metric identified by `key`.
+    ///
+    /// If the metric does not exist in `registry`, it will be created.
+    /// Samples are read from the [`FrameMetricsSnapshot`]. The settings link
+    /// to the metric's [docs](crate::registry::DescriptionExtras::docs_url),
+    /// as described at this point.
+    pub fn new(
+        registry: &MetricsRegistry,
+        name: impl Into<String>,
+        key: MetricKey,
+        unit: Option<MetricUnit>,
+        config: MetricPlotConfig,
+    ) -> Self {
+        match config {
+            MetricPlotConfig::Counter(_) => drop(registry.get_or_create_counter(&key.key)),
+            MetricPlotConfig::Gauge(_) => drop(registry.get_or_create_gauge(&key.key)),
+            MetricPlotConfig::Histogram(_) => drop(registry.get_or_create_histogram(&key.key)),
+        }
+        let docs_url = registry
+            .get_description(&DescriptionKey::from(&key))
+            .and_then(|d| d.extras.docs_url);
+        Self::from_parts(name, key, unit, config, docs_url)
+    }
+
+    /// A new plot, without checking the registry.
+    fn from_parts(
+        name: impl Into<String>,
+        key: MetricKey,
+        unit: Option<MetricUnit>,
+        config: MetricPlotConfig,
+        docs_url: Option<String>,
+    ) -> Self {
+        let data = match config {
+            MetricPlotConfig::Counter(config) => MetricPlotData::Counter(CounterData::new(config)),
+            MetricPlotConfig::Gauge(config) => MetricPlotData::Gauge(GaugeData::new(config)),
+            MetricPlotConfig::Histogram(bar_config) => {
+                MetricPlotData::Histogram(HistogramData::new(bar_config))
+            }
+        };
+        Self {
+            name: name.into(),
+            key: key.to_retained(),
+            unit,
+            data,
+            display_names: default(),
+            style_override: None,
+            overlay: None,
+            docs_url,
+            note: String::new(),
+            group: None,
+            pause_with_virtual_time: None,
+            sampled_pause_with_virtual_time: None,
+            drawn: false,
+            drawn_last_frame: false,
+            on_screen: false,
+            drawn_size: None,
+            created: Instant::now(),
+            revision: 0,
+            refreshed: None,
+        }
+    }
+
+    /// Name of the plot.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The key of the metric being plotted.
+    pub fn key(&self) -> &MetricKey {
+        &self.key
+    }
+
+    /// The unit of the metric being plotted.
+    pub fn unit(&self) -> Option<&MetricUnit> {
+        self.unit.as_ref()
+    }
+
+    /// The unit of the plotted values, after the plot's
+    /// [transform](PlotTransform::displayed_unit).
+    pub fn displayed_unit(&self) -> Option<String> {
+        let transform = match &self.data {
+            MetricPlotData::Counter(data) => &data.config.transform,
+            MetricPlotData::Gauge(data) => &data.config.transform,
+            MetricPlotData::Histogram(data) => &data.config.transform,
+        };
+        transform.displayed_unit(self.unit.as_ref())
+    }
+
+    /// Display names of this plot's label values, applied after
+    /// [`DashboardConfig::display_names`].
+    pub fn display_names(&self) -> &DisplayNames {
+        &self.display_names
+    }
+
+    #[allow(missing_docs)]
+    pub fn set_display_names(&mut self, names: impl Into<DisplayNames>) {
+        self.display_names = names.into();
+    }
+
+    /// The style of this plot, replacing [`DashboardConfig::plot_style`].
+    pub fn style_override(&self) -> Option<&PlotStyle> {
+        self.style_override.as_ref()
+    }
+
+    #[allow(missing_docs)]
+    pub fn set_style_override(&mut self, style: Option<PlotStyle>) {
+        self.style_override = style;
+    }
+
+    /// Where the [`OverlayPlugin`](crate::OverlayPlugin) draws this plot, if
+    /// it was promoted to the overlay.
+    pub fn overlay(&self) -> Option<OverlayCorner> {
+        self.overlay
+    }
+
+    #[allow(missing_docs)]
+    pub fn set_overlay(&mut self, corner: Option<OverlayCorner>) {
+        self.overlay = corner;
+    }
+
+    /// A free-text note about this plot, shown under it and edited in its
+    /// settings. Notes attached to single samples by ctrl-clicking the plot
+    /// are separate.
+    pub fn note(&self) -> &str {
+        &self.note
+    }
+
+    #[allow(missing_docs)]
+    pub fn set_note(&mut self, note: impl Into<String>) {
+        self.note = note.into();
+    }
+
+    /// The name of the group this plot is drawn in, together with the other
+    /// plots of the group under a collapsible header.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    #[allow(missing_docs)]
+    pub fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
+
+    /// When this plot takes samples. Histograms always sample per frame.
+    pub fn sampling_mode(&self) -> SamplingMode {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.config.sampling,
+            MetricPlotData::Gauge(data) => data.config.sampling,
+            MetricPlotData::Histogram(_) => SamplingMode::PerFrame,
+        }
+    }
+
+    /// Whether this plot stops sampling while virtual time is paused,
+    /// replacing [`DashboardWindowConfig::pause_with_virtual_time`]. It
+    /// doesn't change the x axis or rates, which still count samples.
+    pub fn pause_with_virtual_time(&self) -> Option<bool> {
+        self.pause_with_virtual_time
+    }
+
+    #[allow(missing_docs)]
+    pub fn set_pause_with_virtual_time(&mut self, pause: Option<bool>) {
+        self.pause_with_virtual_time = pause;
+    }
+
+    /// Record whether this plot now pauses with virtual time. Returns true
+    /// if it did otherwise before.
+    pub(crate) fn track_pause_with_virtual_time(&mut self, pause: bool) -> bool {
+        self.sampled_pause_with_virtual_time
+            .replace(pause)
+            .is_some_and(|old| old != pause)
+    }
+
+    /// The style this plot is drawn with.
+    pub fn style<'a>(&'a self, dash_config: &'a DashboardConfig) -> &'a PlotStyle {
+        self.style_override
+            .as_ref()
+            .unwrap_or(&dash_config.plot_style)
+    }
+
+    /// The name of this plot's series in legends and tooltips, with label
+    /// values renamed by `dash_config` and this plot's display names.
+    pub fn series_name(&self, dash_config: &DashboardConfig) -> String {
+        series_name(
+            &self.key,
+            &[&dash_config.display_names, &self.display_names],
+        )
+    }
+
+    /// The most recently plotted value.
+    ///
+    /// For counters this is the total count, and for gauges the smoothed
+    /// value, both after the plot's [`PlotTransform`]. Histograms have no
+    /// single latest value, so this is `None`.
+    pub fn latest_value(&self) -> Option<f64> {
+        match &self.data {
+            MetricPlotData::Counter(data) => data
+                .ring
+                .latest()
+                .map(|&v| data.config.transform.apply(v as f64)),
+            MetricPlotData::Gauge(data) => {
+                data.ring.latest().map(|&v| data.config.transform.apply(v))
+            }
+            MetricPlotData::Histogram(_) => None,
+        }
+    }
+
+    /// Statistics of the values in this plot's buffer, as of the last
+    /// [refresh](Self::refresh).
+    ///
+    /// Counters and gauges use the values drawn in the time series, without
+    /// gaps, e.g. the rate for derivative plots. Histograms use the samples
+    /// in their sliding window, and have no statistics without one. Values
+    /// are [transformed](PlotTransform) like the plotted ones. Returns
+    /// `None` if there are no values.
+    pub fn stats(&self) -> Option<PlotStats> {
+        let values = match &self.data {
+            MetricPlotData::Counter(data) => data.cached.values().collect(),
+            MetricPlotData::Gauge(data) => data.cached.values().collect(),
+            MetricPlotData::Histogram(data) => data
+                .ring
+                .as_ref()?
+                .iter_chronological()
+                .map(|&v| data.config.transform.apply(v))
+                .collect(),
+        };
+        PlotStats::new(values)
+    }
+
+    /// Statistics of the histogram samples in the plot's
+    /// [`TrailingWindow`], transformed like [`Self::stats`].
+    ///
+    /// Returns `None` for other plots, without a trailing window, or if the
+    /// window has no samples. Weighted for frames that had more samples than
+    /// [`TrailingWindow::max_samples_per_frame`].
+    pub fn trailing_stats(&self) -> Option<PlotStats> {
+        match &self.data {
+            MetricPlotData::Histogram(data) if data.config.trailing_window.is_some() => {
+                data.trailing_stats()
+            }
+            _ => None,
+        }
+    }
+
+    /// The latest plotted values compared with the [ghost](GhostConfig) of
+    /// a counter or gauge plot, as of the last [refresh](Self::refresh).
+    ///
+    /// Returns `None` without a ghost, for histograms, or while the buffer
+    /// doesn't reach [`GhostConfig::seconds`] back.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::FrameMetricsSnapshot;
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, GhostConfig, MetricPlot, MetricPlotConfig};
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use metrics::Key;
+    /// # use std::sync::atomic::Ordering;
+    /// # use std::time::Duration;
+    /// let registry = MetricsRegistry::new();
+    /// let key = MetricKey::new(Key::from_static_name("enemies"), MetricKind::Gauge);
+    /// let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+    ///     smoothing_weight: 0.0,
+    ///     ghost: Some(GhostConfig {
+    ///         seconds: 2.0,
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// });
+    /// let mut plot = MetricPlot::new(&registry, "enemies", key.clone(), None, config);
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// // One sample per second.
+    /// let mut sample = |plot: &mut MetricPlot, value: f64| {
+    ///     let gauge = registry.get_or_create_gauge(&key.key);
+    ///     gauge.store(f64::to_bits(value), Ordering::Relaxed);
+    ///     let time = snapshot.elapsed().map_or(Duration::ZERO, |t| t + Duration::from_secs(1));
+    ///     snapshot.capture(&registry, [&key]);
+    ///     snapshot.set_elapsed(Some(time));
+    ///     plot.collect(&snapshot);
+    ///     plot.refresh();
+    /// };
+    /// sample(&mut plot, 1.0);
+    /// sample(&mut plot, 2.0);
+    /// assert_eq!(plot.ghost_comparison(), None);
+    ///
+    /// sample(&mut plot, 4.0);
+    /// sample(&mut plot, 6.0);
+    /// // Samples 2 and 3 compared with samples 0 and 1.
+    /// let comparison = plot.ghost_comparison().unwrap();
+    /// assert_eq!(comparison.samples, 2);
+    /// assert_eq!(comparison.mean_delta(), 3.5);
+    /// ```
+    pub fn ghost_comparison(&self) -> Option<GhostComparison> {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.cached.ghost_comparison(),
+            MetricPlotData::Gauge(data) if data.config.display == GaugeDisplay::Line => {
+                data.cached.ghost_comparison()
+            }
+            _ => None,
+        }
+    }
+
+    /// The [outliers](HistogramPlotConfig::outliers) of a histogram plot,
+    /// largest first, transformed like [`Self::stats`], and how many frames
+    /// ago each was collected. Empty for other plots.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::FrameMetricsSnapshot;
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::plots::{HistogramPlotConfig, MetricPlot, MetricPlotConfig};
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use metrics::Key;
+    /// let registry = MetricsRegistry::new();
+    /// let key = MetricKey::new(Key::from_static_name("step_ms"), MetricKind::Histogram);
+    /// let config = MetricPlotConfig::Histogram(HistogramPlotConfig {
+    ///     window_size: Some(6),
+    ///     outliers: Some(2),
+    ///     ..Default::default()
+    /// });
+    /// let mut plot = MetricPlot::new(&registry, "step_ms", key.clone(), None, config);
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// for samples in [[9.0, 1.0], [1.0, 7.0], [2.0, 1.0], [1.0, 1.0]] {
+    ///     let bucket = registry.get_or_create_histogram(&key.key);
+    ///     for sample in samples {
+    ///         bucket.push(sample);
+    ///     }
+    ///     snapshot.capture(&registry, [&key]);
+    ///     registry.clear_atomic_buckets();
+    ///     plot.collect(&snapshot);
+    /// }
+    /// // The 9 left the window of 6 samples.
+    /// assert_eq!(plot.outliers(), [(7.0, 2), (2.0, 1)]);
+    /// ```
+    pub fn outliers(&self) -> Vec<(f64, u64)> {
+        let MetricPlotData::Histogram(data) = &self.data else {
+            return Vec::new();
+        };
+        data.outliers
+            .sorted()
+            .into_iter()
+            .map(|outlier| {
+                let value = data.config.transform.apply(outlier.value.0);
+                (value, data.frames_collected - 1 - outlier.frame)
+            })
+            .collect()
+    }
+
+    /// The number of values [`Self::stats`] are computed from.
+    pub fn n_samples(&self) -> usize {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.cached.values().count(),
+            MetricPlotData::Gauge(data) => data.cached.values().count(),
+            MetricPlotData::Histogram(data) => data
+                .ring
+                .as_ref()
+                .map_or(0, |ring| (ring.n_pushed() - ring.first_index()) as usize),
+        }
+    }
+
+    /// When this plot was created.
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    /// Whether the latest sample exceeds the spike detection threshold.
+    ///
+    /// Always false for histograms and plots without spike detection.
+    pub fn is_alerting(&self) -> bool {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.markers.is_firing(),
+            MetricPlotData::Gauge(data) => data.markers.is_firing(),
+            MetricPlotData::Histogram(_) => false,
+        }
+    }
+
+    /// How long ago a counter or gauge plot last received a new value, if
+    /// it's [stale](StalenessConfig).
+    pub fn stale_for(&self) -> Option<Duration> {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.staleness.stale_for(),
+            MetricPlotData::Gauge(data) => data.staleness.stale_for(),
+            MetricPlotData::Histogram(_) => None,
+        }
+    }
+
+    /// Turn staleness detection of a counter or gauge plot on or off. When
+    /// turned off, the stale gaps are drawn as lines again, from the next
+    /// [refresh](Self::refresh) on.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::FrameMetricsSnapshot;
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlot, MetricPlotConfig, StalenessConfig};
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use metrics::Key;
+    /// # use std::sync::atomic::Ordering;
+    /// let registry = MetricsRegistry::new();
+    /// let key = MetricKey::new(Key::from_static_name("enemies"), MetricKind::Gauge);
+    /// let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+    ///     staleness: Some(StalenessConfig { threshold: 2.0 }),
+    ///     ..Default::default()
+    /// });
+    /// let mut plot = MetricPlot::new(&registry, "Enemies", key.clone(), None, config);
+    /// let gauge = registry.get_or_create_gauge(&key.key);
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// for value in [1.0, 2.0, 3.0, 3.0, 3.0, 3.0, 3.0] {
+    ///     gauge.store(f64::to_bits(value), Ordering::Relaxed);
+    ///     snapshot.capture(&registry, [&key]);
+    ///     plot.collect(&snapshot);
+    /// }
+    /// plot.refresh();
+    /// assert!(plot.stale_for().is_some());
+    /// assert!(plot.n_samples() < 7);
+    ///
+    /// plot.set_staleness(None);
+    /// plot.refresh();
+    /// assert_eq!(plot.stale_for(), None);
+    /// assert_eq!(plot.n_samples(), 7);
+    /// ```
+    pub fn set_staleness(&mut self, config: Option<StalenessConfig>) {
+        let (staleness, tracker, gaps) = match &mut self.data {
+            MetricPlotData::Counter(data) => (
+                &mut data.config.staleness,
+                &mut data.staleness,
+                &mut data.gaps,
+            ),
+            MetricPlotData::Gauge(data) => (
+                &mut data.config.staleness,
+                &mut data.staleness,
+                &mut data.gaps,
+            ),
+            MetricPlotData::Histogram(_) => return,
+        };
+        if config.is_none() {
+            tracker.reset(gaps);
+        }
+        *staleness = config;
+    }
+
+    /// Clone this plot's configuration.
+    pub fn clone_config(&self) -> MetricPlotConfig {
+        match &self.data {
+            MetricPlotData::Counter(data) => MetricPlotConfig::Counter(data.config.clone()),
+            MetricPlotData::Gauge(data) => MetricPlotConfig::Gauge(data.config.clone()),
+            MetricPlotData::Histogram(data) => MetricPlotConfig::Histogram(data.config.clone()),
+        }
+    }
+
+    /// Pull metric data from `snapshot` and [`refresh`](Self::refresh) the plot.
+    ///
+    /// This should run in the [`SampleMetricsSet`](crate::SampleMetricsSet),
+    /// after `snapshot` is captured. If this plot was added after the
+    /// capture, no sample is taken.
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        self.collect(snapshot);
+        self.refresh();
+    }
+
+    /// Pull metric data from `snapshot` without refreshing the plot.
+    ///
+    /// This is cheap enough to run every frame. It has the same scheduling
+    /// requirements as [`Self::update`].
+    ///
+    /// If the plot wasn't [drawn](Self::draw) since the last call and its
+    /// config disables `buffer_while_hidden`, no sample is taken, and time
+    /// series show a gap instead.
+    ///
+    /// Plots that sample [per fixed tick](SamplingMode::PerFixedTick) take
+    /// no sample here, only in [`Self::collect_tick`].
+    pub fn collect(&mut self, snapshot: &FrameMetricsSnapshot) {
+        let visible = std::mem::take(&mut self.drawn);
+        if self.sampling_mode() == SamplingMode::PerFixedTick {
+            self.drawn_last_frame = visible;
+            return;
+        }
+        let Some(value) = snapshot.get(&self.key) else {
+            return;
+        };
+        self.revision += 1;
+        let time = self.sample_time(snapshot);
+        match (&mut self.data, value) {
+            (MetricPlotData::Counter(data), SnapshotValue::Counter(value)) => {
+                data.collect(visible, time, *value);
+            }
+            (MetricPlotData::Gauge(data), SnapshotValue::Gauge(value)) => {
+                let envelope = snapshot.gauge_envelope(&self.key.key);
+                data.collect(visible, time, *value, envelope);
+            }
+            (MetricPlotData::Histogram(data), SnapshotValue::Histogram(samples)) => {
+                data.collect(visible, time, samples);
+            }
+            // The key includes the kind, so the value always matches the data.
+            _ => {}
+        }
+        let Some(frame) = snapshot.frame() else {
+            return;
+        };
+        let late = snapshot.frame_samples(&self.key);
+        match &mut self.data {
+            MetricPlotData::Counter(data) => data.attribute(frame, late),
+            MetricPlotData::Gauge(data) => data.attribute(frame, late),
+            MetricPlotData::Histogram(_) => {}
+        }
+    }
+
+    /// Pull one fixed tick's sample from `snapshot`, if this plot samples
+    /// [per fixed tick](SamplingMode::PerFixedTick).
+    ///
+    /// This should run in [`FixedPostUpdate`](bevy::app::FixedPostUpdate),
+    /// after `snapshot` is captured. Whether the plot is hidden is judged by
+    /// the previous frame.
+    pub fn collect_tick(&mut self, snapshot: &FrameMetricsSnapshot) {
+        if self.sampling_mode() != SamplingMode::PerFixedTick {
+            return;
+        }
+        let visible = self.drawn_last_frame;
+        let time = self.sample_time(snapshot);
+        match (&mut self.data, snapshot.get(&self.key)) {
+            (MetricPlotData::Counter(data), Some(SnapshotValue::Counter(value))) => {
+                data.collect(visible, time, *value);
+            }
+            (MetricPlotData::Gauge(data), Some(SnapshotValue::Gauge(value))) => {
+                let envelope = snapshot.gauge_envelope(&self.key.key);
+                data.collect(visible, time, *value, envelope);
+            }
+            _ => return,
+        }
+        self.revision += 1;
+    }
+
+    /// The [time](FrameMetricsSnapshot::elapsed) of `snapshot`, or the time
+    /// since this plot was created for snapshots without one.
+    fn sample_time(&self, snapshot: &FrameMetricsSnapshot) -> Duration {
+        snapshot.elapsed().unwrap_or_else(|| self.created.elapsed())
+    }
+
+    /// How many values [recorded for earlier frames](MetricsRegistry::record_for_frame)
+    /// couldn't be inserted, because their frame was older than the plot's
+    /// window or wasn't sampled, e.g. while the window was paused.
+    pub fn late_samples_dropped(&self) -> u64 {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.frames.dropped,
+            MetricPlotData::Gauge(data) => data.frames.dropped,
+            MetricPlotData::Histogram(_) => 0,
+        }
+    }
+
+    /// Recompute what is drawn from the samples collected so far.
+    ///
+    /// This can run less often than [`Self::collect`], e.g. at the
+    /// [`DashboardConfig::ui_refresh_rate`]. It does nothing if no sample was
+    /// collected and the config didn't change since the last refresh.
+    pub fn refresh(&mut self) {
+        self.on_screen = false;
+        let downsampling = match &self.data {
+            MetricPlotData::Counter(data) => data.cached.downsampling,
+            MetricPlotData::Gauge(data) => data.cached.downsampling,
+            MetricPlotData::Histogram(_) => 1,
+        };
+        let config = self.clone_config();
+        if let Some((revision, refreshed_config, refreshed_downsampling)) = &self.refreshed {
+            if *revision == self.revision
+                && *refreshed_config == config
+                && *refreshed_downsampling == downsampling
+            {
+                return;
+            }
+        }
+        self.refreshed = Some((self.revision, config, downsampling));
+        let counts = self.unit == Some(MetricUnit::Standard(Unit::Count));
+        match &mut self.data {
+            MetricPlotData::Counter(data) => {
+                data.refresh(counts);
+            }
+            MetricPlotData::Gauge(data) => {
+                data.refresh(counts);
+            }
+            MetricPlotData::Histogram(data) => {
+                data.refresh();
+            }
+        }
+    }
+
+    /// Whether any part of the plot was drawn on screen since the last
+    /// [`Self::refresh`], unlike plots that are collapsed or scrolled out of
+    /// view.
+    pub(crate) fn on_screen_since_refresh(&self) -> bool {
+        self.on_screen
+    }
+
+    /// Draw lines with the lowest and highest value of every `chunk`
+    /// samples, or every sample if `chunk` is 1.
+    pub(crate) fn set_downsampling(&mut self, chunk: usize) {
+        match &mut self.data {
+            MetricPlotData::Counter(data) => data.cached.downsampling = chunk,
+            MetricPlotData::Gauge(data) => data.cached.downsampling = chunk,
+            MetricPlotData::Histogram(_) => {}
+        }
+    }
+
+    /// The points of a counter or gauge plot as of the last refresh, split
+    /// where samples are missing. Histograms have one series of the samples
+    /// in their sliding window, if they have one.
+    #[cfg(feature = "report")]
+    pub(crate) fn report_segments(&self) -> Vec<Vec<PlotPoint>> {
+        let cached = match &self.data {
+            MetricPlotData::Counter(data) => &data.cached,
+            MetricPlotData::Gauge(data) => &data.cached,
+            MetricPlotData::Histogram(data) => {
+                let Some(ring) = &data.ring else {
+                    return Vec::new();
+                };
+                let transform = &data.config.transform;
+                let points = (ring.first_index()..)
+                    .zip(ring.iter_chronological())
+                    .map(|(i, &v)| PlotPoint::new(i as f64, transform.apply(v)))
+                    .collect();
+                return vec![points];
+            }
+        };
+        cached
+            .segments
+            .iter()
+            .map(|segment| cached.points[segment.clone()].to_vec())
+            .collect()
+    }
+
+    /// The sample and label of each [annotation](Self::annotate), oldest
+    /// first.
+    pub fn annotation_labels(&self) -> Vec<(u64, String)> {
+        match &self.data {
+            MetricPlotData::Counter(data) => &data.markers,
+            MetricPlotData::Gauge(data) => &data.markers,
+            MetricPlotData::Histogram(_) => return Vec::new(),
+        }
+        .annotations
+        .iter_chronological()
+        .map(|a| (a.sample, a.label.clone()))
+        .collect()
+    }
+
+    /// The spike detection of a counter or gauge plot.
+    #[cfg(feature = "report")]
+    pub(crate) fn spike_detection(&self) -> Option<&SpikeDetectorConfig> {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.config.spike_detection.as_ref(),
+            MetricPlotData::Gauge(data) => data.config.spike_detection.as_ref(),
+            MetricPlotData::Histogram(_) => None,
+        }
+    }
+
+    /// Mark the latest sample with a labeled vertical line.
+    ///
+    /// Histogram plots have no time axis, so they ignore annotations.
+    pub fn annotate(&mut self, label: impl Into<String>, color: Option<Color32>) {
+        let (n_pushed, markers) = match &mut self.data {
+            MetricPlotData::Counter(data) => (data.ring.n_pushed(), &mut data.markers),
+            MetricPlotData::Gauge(data) => (data.ring.n_pushed(), &mut data.markers),
+            MetricPlotData::Histogram(data) => {
+                data.annotate(label.into());
+                return;
+            }
+        };
+        markers.annotate(label.into(), color, n_pushed.saturating_sub(1));
+    }
+
+    /// The lines of a counter or gauge plot as of the last refresh, for
+    /// drawing them in another plot. Histograms have no lines.
+    ///
+    /// This counts as [drawing](Self::draw) the plot.
+    pub(crate) fn overlay_lines(&mut self, name: &str) -> Option<Vec<Line<'static>>> {
+        self.drawn = true;
+        self.on_screen = true;
+        match &self.data {
+            MetricPlotData::Counter(data) => Some(data.cached.lines(name)),
+            MetricPlotData::Gauge(data) => Some(data.cached.lines(name)),
+            MetricPlotData::Histogram(_) => None,
         }
     }
 
-    fn update_bounds_from_input(&mut self) {
-        let Some(new_bounds) = self.config.buckets.get_bounds() else {
-            return;
+    /// The plotted values of a counter or gauge plot as of the last refresh,
+    /// excluding gaps.
+    pub(crate) fn plotted_values(&self) -> Option<Vec<f64>> {
+        let cached = match &self.data {
+            MetricPlotData::Counter(data) => &data.cached,
+            MetricPlotData::Gauge(data) => &data.cached,
+            MetricPlotData::Histogram(_) => return None,
         };
-
-        self.config.buckets.bounds = new_bounds;
-
-        self.bucket_counts
-            .resize(self.config.buckets.bounds.len() + 1, 0);
-        self.bucket_counts.fill(0);
+        Some(
+            cached
+                .segments
+                .iter()
+                .flat_map(|segment| &cached.points[segment.clone()])
+                .map(|point| point.y)
+                .collect(),
+        )
     }
 
-    fn make_bar_chart(&self) -> BarChart {
-        assert_eq!(
-            self.bucket_counts.len(),
-            self.config.buckets.bounds.len() + 1
+    /// Markdown tables of the plot's [statistics](Self::stats), the buckets
+    /// of a histogram, and the `last_samples` latest plotted values, for
+    /// pasting into issue trackers. See the [`markdown`] module.
+    ///
+    /// Numbers are formatted like in the plot, with `decimal_separator`.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::{DashboardConfig, FrameMetricsSnapshot};
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlot, MetricPlotConfig};
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use metrics::{Key, Label};
+    /// # use std::sync::atomic::Ordering;
+    /// let registry = MetricsRegistry::new();
+    /// let labels = vec![Label::new("path", "a|b")];
+    /// let key = MetricKey::new(Key::from_parts("queue", labels), MetricKind::Gauge);
+    /// let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+    ///     smoothing_weight: 0.0,
+    ///     ..Default::default()
+    /// });
+    /// let mut plot = MetricPlot::new(&registry, "queue", key.clone(), None, config);
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// for value in [1.0, 2.0, 3.0] {
+    ///     let gauge = registry.get_or_create_gauge(&key.key);
+    ///     gauge.store(f64::to_bits(value), Ordering::Relaxed);
+    ///     snapshot.capture(&registry, [&key]);
+    ///     plot.collect(&snapshot);
+    /// }
+    /// plot.refresh();
+    ///
+    /// let markdown = plot.to_markdown(&DashboardConfig::default(), 2, '.');
+    /// assert!(markdown.contains("a\\|b"));
+    /// assert!(markdown.contains("| 3 | 1.000 | 2.000 | 3.000 | 3.000 |  |\n"));
+    /// assert!(markdown.ends_with("| 1 | 2.000 |\n| 2 | 3.000 |\n"));
+    /// ```
+    pub fn to_markdown(
+        &self,
+        dash_config: &DashboardConfig,
+        last_samples: usize,
+        decimal_separator: char,
+    ) -> String {
+        let numbers = &self.style(dash_config).numbers;
+        let unit = self.displayed_unit();
+        let mut text = format!(
+            "**{}** ({})\n\n",
+            markdown::escape(&self.series_name(dash_config)),
+            metric_kind_str(self.key.kind)
         );
+        match self.stats() {
+            Some(stats) => text.push_str(&markdown::stats_table(
+                &stats,
+                unit.as_deref(),
+                numbers,
+                decimal_separator,
+            )),
+            None => text.push_str("No buffered data.\n"),
+        }
+        if let MetricPlotData::Histogram(data) = &self.data {
+            text.push('\n');
+            text.push_str(&markdown::bucket_table(
+                &data.config.buckets.bounds,
+                &data.bucket_counts,
+                numbers,
+                decimal_separator,
+            ));
+        }
+        if last_samples == 0 {
+            return text;
+        }
+        let samples: Vec<(u64, f64)> = match &self.data {
+            MetricPlotData::Counter(data) => data.cached.samples().collect(),
+            MetricPlotData::Gauge(data) => data.cached.samples().collect(),
+            MetricPlotData::Histogram(data) => match &data.ring {
+                Some(ring) => (ring.first_index()..)
+                    .zip(ring.iter_chronological())
+                    .map(|(i, &v)| (i, data.config.transform.apply(v)))
+                    .collect(),
+                None => Vec::new(),
+            },
+        };
+        let skip = samples.len().saturating_sub(last_samples);
+        text.push('\n');
+        text.push_str(&markdown::samples_table(
+            &samples[skip..],
+            unit.as_deref(),
+            numbers,
+            decimal_separator,
+        ));
+        text
+    }
 
-        let mut bars: Vec<_> = self
-            .bucket_counts
-            .iter()
-            .map(|&c| Bar::new(0.0, c as f64))
-            .collect();
+    /// Draw the plot using `ui`.
+    ///
+    /// While the plot is scrolled far enough out of view, only empty space of
+    /// the size it took when it was last drawn is allocated, so the scroll
+    /// position stays the same. It still counts as drawn for
+    /// `buffer_while_hidden`.
+    pub fn draw(
+        &mut self,
+        dash_config: &DashboardConfig,
+        window_config: &DashboardWindowConfig,
+        ui: &mut Ui,
+    ) {
+        self.drawn = true;
+        let top = ui.cursor().top();
+        let width = ui.available_width();
+        if let Some(height) = self.culled_height(width, ui) {
+            ui.allocate_space(vec2(width, height));
+            return;
+        }
+
+        draw_plot(dash_config, window_config, self, None, ui);
+        let bottom = ui.cursor().top();
+        let rect = Rect::from_x_y_ranges(ui.max_rect().x_range(), top..=bottom);
+        self.on_screen |= ui.is_rect_visible(rect);
+        let height = bottom - top - ui.spacing().item_spacing.y;
+        self.drawn_size = Some((width, height.max(0.0)));
+    }
 
-        let mut avg_bar_width = 0.0;
-        for (window_i, edges) in self.config.buckets.bounds.windows(2).enumerate() {
-            let start = edges[0];
-            let end = edges[1];
-            let bar_i = window_i + 1;
-            let width = end - start;
-            let center = 0.5 * (start + end);
-            let bar = &mut bars[bar_i];
-            bar.argument = center;
-            bar.bar_width = width;
-            avg_bar_width += width;
+    /// The height of the placeholder to draw instead of the plot, if it's
+    /// out of view and its size is known for the available `width`.
+    ///
+    /// Plots within half a view of the visible area are drawn, so that they
+    /// are built, and refreshed while the dashboard is
+    /// [degraded](crate::Degradation::SkipHiddenPlots), before they scroll
+    /// into view.
+    fn culled_height(&self, width: f32, ui: &Ui) -> Option<f32> {
+        let (drawn_width, height) = self.drawn_size?;
+        if drawn_width != width || ui.is_sizing_pass() {
+            return None;
         }
-        avg_bar_width /= (self.config.buckets.bounds.len() - 1) as f64;
+        let clip = ui.clip_rect();
+        let near = clip.expand2(vec2(0.0, 0.5 * clip.height()));
+        let top = ui.cursor().top();
+        (top > near.bottom() || top + height < near.top()).then_some(height)
+    }
 
-        let start = self.config.buckets.bounds[0];
-        let end = *self.config.buckets.bounds.last().unwrap();
+    /// Draw only the plot and its latest value in `style`, e.g. in the
+    /// [`OverlayPlugin`](crate::OverlayPlugin).
+    pub fn draw_overlay(
+        &mut self,
+        dash_config: &DashboardConfig,
+        window_config: &DashboardWindowConfig,
+        style: &PlotStyle,
+        ui: &mut Ui,
+    ) {
+        self.drawn = true;
+        self.on_screen = true;
+        draw_plot(dash_config, window_config, self, Some(style), ui);
+    }
+}
 
-        let fst_bar = &mut bars[0];
-        fst_bar.argument = start - 0.5 * avg_bar_width;
-        fst_bar.bar_width = avg_bar_width;
-        fst_bar.fill = Color32::BLUE;
-        let last_bar = bars.last_mut().unwrap();
-        last_bar.argument = end + 0.5 * avg_bar_width;
-        last_bar.bar_width = avg_bar_width;
-        last_bar.fill = Color32::BLUE;
+impl DashboardWidget for MetricPlot {
+    fn id(&self) -> &str {
+        "metric_plot"
+    }
 
-        BarChart::new("histogram bar chart", bars)
+    fn title(&self, _ctx: &WidgetContext) -> String {
+        self.name.clone()
     }
 
-    fn update(&mut self) {
-        if let Some(window_size) = self.config.window_size {
-            // We are only counting within a sliding window, so clear
-            // counts first.
-            self.bucket_counts.fill(0);
-            // We only need to take the latest values up to the ring's
-            // capacity. The histogram doesn't actually care about the order
-            // of elements, it just needs to always have the most recent
-            // N elements.
-            let ring = self.ring.get_or_insert_with(|| Ring::new(window_size));
-            let mut taken = 0;
-            self.source.data_with(|block| {
-                let mut block_iter = block.iter().rev().copied();
-                while taken < ring.max_len() {
-                    if let Some(value) = block_iter.next() {
-                        ring.push(value);
-                        taken += 1;
-                    } else {
-                        break;
-                    }
-                }
-            });
-            for &value in ring.iter_chronological() {
-                add_value_to_bucket(&self.config.buckets.bounds, value, &mut self.bucket_counts);
-            }
-        } else {
-            // Keep adding to the existing buckets.
-            self.source.data_with(|block| {
-                for &value in block {
-                    add_value_to_bucket(
-                        &self.config.buckets.bounds,
-                        value,
-                        &mut self.bucket_counts,
-                    );
-                }
-            });
-        }
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext) {
+        self.draw(ctx.dashboard, ctx.window, ui);
     }
-}
 
-impl MetricPlot {
-    /// Create a new plot from the metric identified by `key`.
-    ///
-    /// The metric will be found in `registry`. If it does not exist, it will
-    /// be created.
-    pub fn new(
-        registry: &MetricsRegistry,
-        name: impl Into<String>,
-        key: MetricKey,
-        unit: Option<Unit>,
-        config: MetricPlotConfig,
-    ) -> Self {
-        let data = match config {
-            MetricPlotConfig::Counter(config) => {
-                let source = registry.get_or_create_counter(&key.key);
-                MetricPlotData::Counter(CounterData::new(config, source))
-            }
-            MetricPlotConfig::Gauge(config) => {
-                let source = registry.get_or_create_gauge(&key.key);
-                MetricPlotData::Gauge(GaugeData::new(config, source))
-            }
-            MetricPlotConfig::Histogram(bar_config) => {
-                let source = registry.get_or_create_histogram(&key.key);
-                MetricPlotData::Histogram(HistogramData::new(bar_config, source))
-            }
-        };
+    fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        MetricPlot::update(self, snapshot);
+    }
 
-        Self {
-            name: name.into(),
-            key,
-            unit,
-            data,
-        }
+    fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+        Box::new(std::iter::once(&self.key))
     }
 
-    /// Name of the plot.
-    pub fn name(&self) -> &str {
-        &self.name
+    fn on_close(&mut self, cached_configs: &mut CachedPlotConfigs) {
+        cached_configs.insert(self.key.clone(), self.clone_config());
     }
 
-    /// The key of the metric being plotted.
-    pub fn key(&self) -> &MetricKey {
-        &self.key
+    #[cfg(feature = "layout")]
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        RawValue::from_rust(&PlotLayout::from(self)).map(Some)
     }
 
-    /// Clone this plot's configuration.
-    pub fn clone_config(&self) -> MetricPlotConfig {
-        match &self.data {
-            MetricPlotData::Counter(data) => MetricPlotConfig::Counter(data.config.clone()),
-            MetricPlotData::Gauge(data) => MetricPlotConfig::Gauge(data.config.clone()),
-            MetricPlotData::Histogram(data) => MetricPlotConfig::Histogram(data.config.clone()),
+    /// Keeps the history of the plot unless the key, unit or config change.
+    #[cfg(feature = "layout")]
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        let layout: PlotLayout = config.into_rust()?;
+        if !layout.matches(self) {
+            let name = layout.key.title(None, 0);
+            let (key, unit, config) = (
+                layout.key.clone(),
+                layout.unit.clone(),
+                layout.config.clone(),
+            );
+            *self = Self::from_parts(name, key, unit, config, None);
         }
+        layout.apply_settings(self);
+        Ok(())
     }
 
-    /// Pull metric data from the source.
-    ///
-    /// This should run in the [`Last`](bevy::prelude::Last) schedule **before**
-    /// [`ClearBucketsSystem`](crate::ClearBucketsSystem) to ensure no data
-    /// is missed.
-    pub fn update(&mut self) {
-        match &mut self.data {
-            MetricPlotData::Counter(data) => {
-                data.update();
-            }
-            MetricPlotData::Gauge(data) => {
-                data.update();
-            }
-            MetricPlotData::Histogram(data) => {
-                data.update();
-            }
-        }
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        Some(self)
     }
 
-    /// Draw the plot using `ui`.
-    pub fn draw(&mut self, dash_config: &DashboardConfig, ui: &mut Ui) {
-        let Self {
-            name, unit, data, ..
-        } = self;
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+}
+
+/// Draw one bar per bucket, including the buckets below and above all
+/// bounds. The latter are filled with `overflow_color`.
+fn make_bar_chart(name: &str, bounds: &[f64], counts: &[u32], overflow_color: Color32) -> BarChart {
+    assert_eq!(counts.len(), bounds.len() + 1);
 
-        draw_plot(dash_config, name, *unit, data, ui);
+    let mut bars: Vec<_> = counts.iter().map(|&c| Bar::new(0.0, c as f64)).collect();
+
+    let mut avg_bar_width = 0.0;
+    for (window_i, edges) in bounds.windows(2).enumerate() {
+        let start = edges[0];
+        let end = edges[1];
+        let bar_i = window_i + 1;
+        let width = end - start;
+        let center = 0.5 * (start + end);
+        let bar = &mut bars[bar_i];
+        bar.argument = center;
+        bar.bar_width = width;
+        avg_bar_width += width;
     }
+    avg_bar_width /= (bounds.len() - 1) as f64;
+
+    let start = bounds[0];
+    let end = *bounds.last().unwrap();
+
+    let fst_bar = &mut bars[0];
+    fst_bar.argument = start - 0.5 * avg_bar_width;
+    fst_bar.bar_width = avg_bar_width;
+    fst_bar.fill = overflow_color;
+    let last_bar = bars.last_mut().unwrap();
+    last_bar.argument = end + 0.5 * avg_bar_width;
+    last_bar.bar_width = avg_bar_width;
+    last_bar.fill = overflow_color;
+
+    BarChart::new(name, bars)
 }
 
 fn add_value_to_bucket(bucket_bounds: &[f64], value: f64, bucket_counts: &mut [u32]) {
@@ -541,103 +3730,848 @@ fn add_value_to_bucket(bucket_bounds: &[f64], value: f64, bucket_counts: &mut [u
     bucket_counts[bucket_i] += 1;
 }
 
+/// Draw `plot` with its settings, or only the plot in the `overlay` style.
 fn draw_plot(
     dash_config: &DashboardConfig,
-    name: &str,
-    unit: Option<Unit>,
-    data: &mut MetricPlotData,
+    window_config: &DashboardWindowConfig,
+    plot: &mut MetricPlot,
+    overlay: Option<&PlotStyle>,
     ui: &mut Ui,
 ) {
+    let series = &plot.series_name(dash_config);
+    let settings = overlay.is_none() && !dash_config.read_only.locks_group(plot.group());
+    let palette = ThemePalette::of(ui);
+    let l10n = DashboardLocalization::of(ui);
+    let numbers = &plot.style(dash_config).numbers.clone();
+    let style = &overlay.unwrap_or(plot.style(dash_config)).clone();
+    let MetricPlot {
+        name,
+        unit,
+        data,
+        display_names,
+        style_override,
+        docs_url,
+        note,
+        pause_with_virtual_time,
+        ..
+    } = plot;
+    let transform = match data {
+        MetricPlotData::Counter(data) => &data.config.transform,
+        MetricPlotData::Gauge(data) => &data.config.transform,
+        MetricPlotData::Histogram(data) => &data.config.transform,
+    };
+    let metric_unit = unit.as_ref();
+    let unit = transform.displayed_unit(metric_unit);
+    let unit = unit.as_deref();
+    let separator = l10n.decimal_separator;
     let new_plot = || {
-        Plot::new(name)
+        Plot::new(&*name)
             .allow_scroll(false)
             .view_aspect(2.0)
             .auto_bounds(Vec2b::new(true, true))
     };
+    let new_time_series = || {
+        new_plot()
+            .y_axis_formatter(numbers.axis_formatter(separator))
+            .label_formatter(numbers.hover_formatter(separator))
+    };
 
     match data {
         MetricPlotData::Counter(data) => {
-            if let Some(latest) = data.ring.latest() {
-                ui.label(format!("latest = {latest:.3}"));
+            if let Some(&latest) = data.ring.latest() {
+                let transform = &data.config.transform;
+                let text = if transform.is_identity() {
+                    let integer = NumberFormat {
+                        precision: Precision::Decimals(0),
+                        ..numbers.clone()
+                    };
+                    integer.format(latest as f64, separator)
+                } else {
+                    numbers.format(transform.apply(latest as f64), separator)
+                };
+                latest_label(text, &data.staleness, ui);
             }
 
-            let mut plot_points = data.ring.make_plot_points();
-            if data.config.derivative {
-                derivative(&mut plot_points);
+            let first_sample = data.cached.first_sample;
+            let spike_points = spike_points(&data.markers, first_sample, &data.cached.points);
+            let mut lines = data.cached.lines(series);
+            if let Some(ghost) = &data.config.ghost {
+                lines.extend(ghost_lines(ghost, &data.cached, series, ui));
             }
-            let line = Line::new("counter line", PlotPoints::Owned(plot_points));
-            let mut plot = new_plot().x_axis_label("frame");
+            let x_axis = x_axis_label(data.config.sampling);
+            let mut plot = new_time_series().x_axis_label(l10n.get(x_axis));
             if let Some(unit) = unit {
-                plot = plot.y_axis_label(unit_str(unit));
+                plot = plot.y_axis_label(unit);
+            }
+            style.scope(ui, |ui| {
+                show_time_series(
+                    style.apply(plot),
+                    lines,
+                    Vec::new(),
+                    &data.cached.gaps,
+                    spike_points,
+                    first_sample,
+                    &mut data.markers,
+                    ui,
+                );
+            });
+            if let (Some(ghost), None) = (&data.config.ghost, overlay) {
+                ui.label(ghost_summary(ghost, &data.cached, numbers, &l10n));
+            }
+
+            data.handle_global_config(dash_config, window_config);
+            if settings {
+                ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                    data.configure_ui(window_config.global_window_size.is_none(), metric_unit, ui);
+                    common_settings_ui(
+                        display_names,
+                        style_override,
+                        pause_with_virtual_time,
+                        note,
+                        docs_url.as_deref(),
+                        ui,
+                    );
+                });
+            }
+        }
+        MetricPlotData::Gauge(data) if data.config.display == GaugeDisplay::Dial => {
+            let latest = data.ring.latest().map(|&v| data.config.transform.apply(v));
+            draw_dial(&data.config.dial, latest, unit, numbers, ui);
+
+            data.handle_global_config(dash_config, window_config);
+            if settings {
+                ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                    data.configure_ui(window_config.global_window_size.is_none(), metric_unit, ui);
+                    common_settings_ui(
+                        display_names,
+                        style_override,
+                        pause_with_virtual_time,
+                        note,
+                        docs_url.as_deref(),
+                        ui,
+                    );
+                });
+            }
+        }
+        MetricPlotData::Gauge(data) if data.config.display == GaugeDisplay::Distribution => {
+            if let Some(&latest) = data.ring.latest() {
+                let value = data.config.transform.apply(latest);
+                latest_label(numbers.format(value, separator), &data.staleness, ui);
             }
-            plot.show(ui, |plot_ui| plot_ui.line(line));
 
-            data.handle_global_config(dash_config);
-            ui.collapsing("Settings", |ui| {
-                data.configure_ui(dash_config.global_window_size.is_none(), ui);
+            let Distribution { bounds, counts } = &data.distribution;
+            let mut plot = new_plot()
+                .x_axis_formatter(numbers.axis_formatter(separator))
+                .y_axis_label(l10n.get(UiText::CountAxis));
+            if let Some(unit) = unit {
+                plot = plot.x_axis_label(unit);
+            }
+            style.show(plot, ui, |plot_ui| {
+                if !bounds.is_empty() {
+                    plot_ui.bar_chart(make_bar_chart(series, bounds, counts, palette.accent));
+                }
             });
+
+            data.handle_global_config(dash_config, window_config);
+            if settings {
+                ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                    data.configure_ui(window_config.global_window_size.is_none(), metric_unit, ui);
+                    common_settings_ui(
+                        display_names,
+                        style_override,
+                        pause_with_virtual_time,
+                        note,
+                        docs_url.as_deref(),
+                        ui,
+                    );
+                });
+            }
         }
         MetricPlotData::Gauge(data) => {
-            if let Some(latest) = data.ring.latest() {
-                ui.label(format!("latest = {latest:.3}"));
+            if let Some(&latest) = data.ring.latest() {
+                let value = data.config.transform.apply(latest);
+                latest_label(numbers.format(value, separator), &data.staleness, ui);
             }
 
-            let mut plot_points = data.ring.make_plot_points();
-            if data.config.derivative {
-                derivative(&mut plot_points);
+            let first_sample = data.cached.first_sample;
+            let spike_points = spike_points(&data.markers, first_sample, &data.cached.points);
+            let mut lines = data.cached.lines(series);
+            if let Some(ghost) = &data.config.ghost {
+                lines.extend(ghost_lines(ghost, &data.cached, series, ui));
             }
-            let line = Line::new("gauge line", PlotPoints::Owned(plot_points));
-            let mut plot = new_plot().x_axis_label("frame");
+            let envelope_color = ThemePalette::of(ui).weak.gamma_multiply(0.3);
+            let mut bands = data.envelope_polygons(series, envelope_color);
+            let std_dev_color = ThemePalette::of(ui).accent.gamma_multiply(0.2);
+            bands.extend(data.std_dev_polygons(series, std_dev_color));
+            let x_axis = x_axis_label(data.config.sampling);
+            let mut plot = new_time_series().x_axis_label(l10n.get(x_axis));
             if let Some(unit) = unit {
-                plot = plot.y_axis_label(unit_str(unit));
+                plot = plot.y_axis_label(unit);
+            }
+            style.scope(ui, |ui| {
+                show_time_series(
+                    style.apply(plot),
+                    lines,
+                    bands,
+                    &data.cached.gaps,
+                    spike_points,
+                    first_sample,
+                    &mut data.markers,
+                    ui,
+                );
+            });
+            if let (Some(ghost), None) = (&data.config.ghost, overlay) {
+                ui.label(ghost_summary(ghost, &data.cached, numbers, &l10n));
             }
-            plot.show(ui, |plot_ui| plot_ui.line(line));
 
-            data.handle_global_config(dash_config);
-            ui.collapsing("Settings", |ui| {
-                data.configure_ui(dash_config.global_window_size.is_none(), ui);
+            data.handle_global_config(dash_config, window_config);
+            if settings {
+                ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                    data.configure_ui(window_config.global_window_size.is_none(), metric_unit, ui);
+                    common_settings_ui(
+                        display_names,
+                        style_override,
+                        pause_with_virtual_time,
+                        note,
+                        docs_url.as_deref(),
+                        ui,
+                    );
+                });
+            }
+        }
+        MetricPlotData::Histogram(data) if data.config.display == HistogramDisplay::FanChart => {
+            let (polygons, lines) = data.fan.items(series, &data.config.fan_chart);
+            let mut plot = new_time_series().x_axis_label(l10n.get(UiText::RefreshAxis));
+            if let Some(unit) = unit {
+                plot = plot.y_axis_label(unit);
+            }
+            let pan_to = data.pan_to.take();
+            style.show(plot, ui, |plot_ui| {
+                for polygon in polygons {
+                    plot_ui.polygon(polygon);
+                }
+                for line in lines {
+                    plot_ui.line(line);
+                }
+                if let Some(refresh) = pan_to {
+                    pan_plot_to(plot_ui, refresh as f64);
+                }
             });
+
+            if overlay.is_none() {
+                if let Some(summary) = data.trailing_summary(numbers, &l10n) {
+                    ui.label(summary);
+                }
+                data.outliers_ui(numbers, ui);
+            }
+            if settings {
+                ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                    data.configure_ui(metric_unit, ui);
+                    common_settings_ui(
+                        display_names,
+                        style_override,
+                        pause_with_virtual_time,
+                        note,
+                        docs_url.as_deref(),
+                        ui,
+                    );
+                });
+            }
         }
         MetricPlotData::Histogram(data) => {
-            let chart = data.make_bar_chart();
-            let mut plot = new_plot().y_axis_label("count");
+            let chart = data.make_bar_chart(series, palette.accent);
+            let mut plot = new_plot()
+                .x_axis_formatter(numbers.axis_formatter(separator))
+                .y_axis_label(l10n.get(UiText::CountAxis));
             if let Some(unit) = unit {
-                plot = plot.x_axis_label(unit_str(unit));
+                plot = plot.x_axis_label(unit);
             }
-            plot.show(ui, |plot_ui| plot_ui.bar_chart(chart));
+            style.show(plot, ui, |plot_ui| plot_ui.bar_chart(chart));
 
-            ui.collapsing("Settings", |ui| {
-                data.configure_ui(ui);
-            });
+            if overlay.is_none() {
+                if let Some(summary) = data.trailing_summary(numbers, &l10n) {
+                    ui.label(summary);
+                }
+                data.outliers_ui(numbers, ui);
+            }
+            if settings {
+                ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                    data.configure_ui(metric_unit, ui);
+                    common_settings_ui(
+                        display_names,
+                        style_override,
+                        pause_with_virtual_time,
+                        note,
+                        docs_url.as_deref(),
+                        ui,
+                    );
+                });
+            }
+        }
+    }
+
+    if overlay.is_none() {
+        let markers = match data {
+            MetricPlotData::Counter(data) => Some(&mut data.markers),
+            MetricPlotData::Gauge(data) => Some(&mut data.markers),
+            MetricPlotData::Histogram(_) => None,
+        };
+        notes_ui(note, markers, ui);
+    }
+}
+
+/// The dimmed lines of a plot's ghost.
+fn ghost_lines(
+    ghost: &GhostConfig,
+    cached: &CachedPoints,
+    series: &str,
+    ui: &Ui,
+) -> Vec<Line<'static>> {
+    let l10n = DashboardLocalization::of(ui);
+    let offset = l10n.number(ghost.seconds, 1);
+    let name = l10n.format(
+        UiText::GhostSeries,
+        &[("series", &series), ("offset", &offset)],
+    );
+    let color = ThemePalette::of(ui).weak.gamma_multiply(0.6);
+    cached.ghost_lines(&name, color)
+}
+
+/// How many samples the ghost covers, or that it isn't drawn, and the
+/// difference of means if enabled.
+fn ghost_summary(
+    ghost: &GhostConfig,
+    cached: &CachedPoints,
+    numbers: &NumberFormat,
+    l10n: &DashboardLocalization,
+) -> String {
+    let offset = l10n.number(ghost.seconds, 1);
+    let Some(comparison) = cached.ghost_comparison() else {
+        return l10n.format(UiText::GhostNotEnoughHistory, &[("offset", &offset)]);
+    };
+    let mut text = l10n.format(
+        UiText::GhostSummary,
+        &[("offset", &offset), ("samples", &comparison.samples)],
+    );
+    if ghost.show_mean_delta {
+        let delta = comparison.mean_delta();
+        let sign = if delta > 0.0 { "+" } else { "" };
+        let delta_text = format!("{sign}{}", numbers.format(delta, l10n.decimal_separator));
+        let percent = if comparison.ghost_mean == 0.0 {
+            "-".to_owned()
+        } else {
+            let percent = 100.0 * delta / comparison.ghost_mean.abs();
+            format!("{sign}{}%", l10n.number(percent, 1))
+        };
+        text.push_str(" · ");
+        text.push_str(&l10n.format(
+            UiText::GhostMeanDelta,
+            &[("delta", &delta_text), ("percent", &percent)],
+        ));
+    }
+    text
+}
+
+/// Center the plot horizontally on `x`, keeping its zoom.
+fn pan_plot_to(plot_ui: &mut PlotUi, x: f64) {
+    let bounds = plot_ui.plot_bounds();
+    let half_width = 0.5 * bounds.width();
+    plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+        [x - half_width, bounds.min()[1]],
+        [x + half_width, bounds.max()[1]],
+    ));
+}
+
+/// The note of a plot and the notes of its samples, when there are any.
+fn notes_ui(note: &str, markers: Option<&mut TimeSeriesMarkers>, ui: &mut Ui) {
+    let has_sample_notes = markers.as_ref().is_some_and(|m| !m.notes.is_empty());
+    if note.is_empty() && !has_sample_notes {
+        return;
+    }
+    let l10n = DashboardLocalization::of(ui);
+    // Open the notes when a sample note was just added, to type its text.
+    let open = markers
+        .as_ref()
+        .is_some_and(|m| m.new_note.is_some())
+        .then_some(true);
+    egui::CollapsingHeader::new(l10n.get(UiText::Notes))
+        .id_salt("plot-notes")
+        .default_open(true)
+        .open(open)
+        .show(ui, |ui| {
+            if !note.is_empty() {
+                ui.label(note);
+            }
+            if let Some(markers) = markers {
+                markers.notes_ui(ui);
+            }
+        });
+}
+
+/// Settings shared by all kinds of plots.
+fn common_settings_ui(
+    display_names: &mut DisplayNames,
+    style_override: &mut Option<PlotStyle>,
+    pause_with_virtual_time: &mut Option<bool>,
+    note: &mut String,
+    docs_url: Option<&str>,
+    ui: &mut Ui,
+) {
+    let l10n = DashboardLocalization::of(ui);
+    ui.collapsing(l10n.get(UiText::Note), |ui| {
+        ui.text_edit_multiline(note);
+        ui.weak(l10n.get(UiText::SampleNoteHint));
+    });
+    ui.collapsing(l10n.get(UiText::DisplayNames), |ui| {
+        display_names.configure_ui(ui);
+    });
+    ui.collapsing(l10n.get(UiText::Style), |ui| {
+        let mut overridden = style_override.is_some();
+        if ui
+            .checkbox(&mut overridden, l10n.get(UiText::OverrideDashboardStyle))
+            .changed()
+        {
+            *style_override = overridden.then(default);
+        }
+        if let Some(style) = style_override {
+            style.configure_ui(ui);
+        }
+    });
+    let modes = [
+        (None, UiText::VirtualTimeDefault),
+        (Some(true), UiText::VirtualTimePause),
+        (Some(false), UiText::VirtualTimeIgnore),
+    ];
+    let selected = modes
+        .iter()
+        .find(|(mode, _)| mode == pause_with_virtual_time)
+        .map_or(UiText::VirtualTimeDefault, |&(_, text)| text);
+    egui::ComboBox::from_id_salt("plot-virtual-time")
+        .selected_text(l10n.format(UiText::VirtualTime, &[("mode", &l10n.get(selected))]))
+        .show_ui(ui, |ui| {
+            for (mode, text) in modes {
+                ui.selectable_value(pause_with_virtual_time, mode, l10n.get(text));
+            }
+        })
+        .response
+        .on_hover_text(l10n.get(UiText::PauseWithVirtualTimeHint));
+    if let Some(url) = docs_url {
+        open_link_button(url, LinkKind::Docs, ui);
+    }
+}
+
+/// Find the plotted location of each spike in the visible range.
+fn spike_points(
+    markers: &TimeSeriesMarkers,
+    first_sample: u64,
+    plot_points: &[PlotPoint],
+) -> Vec<PlotPoint> {
+    markers
+        .spikes
+        .iter_chronological()
+        .filter(|s| s.sample >= first_sample)
+        .filter_map(|s| plot_points.get((s.sample - first_sample) as usize).copied())
+        .collect()
+}
+
+/// Show a line plot along with any markers in the visible range.
+///
+/// Gaps between the lines are shaded. Hovering near an annotation or sample
+/// note shows its text, and ctrl-clicking a sample adds a note to it.
+#[allow(clippy::too_many_arguments)]
+fn show_time_series(
+    plot: Plot,
+    lines: Vec<Line>,
+    bands: Vec<Polygon>,
+    gaps: &[Range<f64>],
+    spike_points: Vec<PlotPoint>,
+    first_sample: u64,
+    markers: &mut TimeSeriesMarkers,
+    ui: &mut Ui,
+) {
+    const HOVER_DISTANCE: f32 = 4.0;
+
+    let palette = ThemePalette::of(ui);
+    let pan_to = markers.pan_to.take();
+    let annotations = &markers.annotations;
+    let visible_annotations = || {
+        annotations
+            .iter_chronological()
+            .filter(move |a| a.sample >= first_sample)
+    };
+    let notes = &markers.notes;
+    let visible_notes = || notes.iter().filter(move |n| n.sample >= first_sample);
+    let response = plot.show(ui, |plot_ui| {
+        // VLines don't affect the y bounds, so a wide one shades the gap
+        // without a fixed height.
+        let px_per_sample = plot_ui.transform().dpos_dvalue_x().abs();
+        for gap in gaps {
+            let width = (gap.end - gap.start) * px_per_sample;
+            plot_ui.vline(
+                VLine::new("no data", 0.5 * (gap.start + gap.end))
+                    .width(width as f32)
+                    .color(palette.weak.gamma_multiply(0.25))
+                    .allow_hover(false),
+            );
+        }
+        for line in lines {
+            plot_ui.line(line);
+        }
+        // After the lines, which take their automatic colors first.
+        for band in bands {
+            plot_ui.polygon(band);
+        }
+        if !spike_points.is_empty() {
+            plot_ui.points(
+                Points::new("spikes", PlotPoints::Owned(spike_points))
+                    .shape(MarkerShape::Diamond)
+                    .radius(4.0)
+                    .color(palette.error),
+            );
+        }
+        for annotation in visible_annotations() {
+            let mut vline = VLine::new(&annotation.label, annotation.sample as f64);
+            if let Some(color) = annotation.color {
+                vline = vline.color(color);
+            }
+            plot_ui.vline(vline);
+        }
+        for note in visible_notes() {
+            plot_ui.vline(
+                VLine::new(&note.text, note.sample as f64)
+                    .color(palette.accent)
+                    .style(LineStyle::dashed_dense()),
+            );
+        }
+        if let Some(sample) = pan_to {
+            pan_plot_to(plot_ui, sample as f64);
+        }
+    });
+
+    let Some(hover_pos) = response.response.hover_pos() else {
+        return;
+    };
+    let is_near = |sample: u64| {
+        let x = response.transform.position_from_point_x(sample as f64);
+        (x - hover_pos.x).abs() <= HOVER_DISTANCE
+    };
+    let hovered = visible_annotations()
+        .find(|a| is_near(a.sample))
+        .map(|a| a.label.clone())
+        .or_else(|| {
+            visible_notes()
+                .find(|n| is_near(n.sample) && !n.text.is_empty())
+                .map(|n| n.text.clone())
+        });
+    let clicked = response.response.clicked() && ui.input(|i| i.modifiers.command);
+    let sample = response
+        .transform
+        .value_from_position(hover_pos)
+        .x
+        .round()
+        .max(first_sample as f64) as u64;
+    if let Some(text) = hovered {
+        response.response.on_hover_text(text);
+    }
+    if clicked {
+        markers.add_note(sample);
+    }
+}
+
+/// Show the latest value, greyed out with a note if the metric is stale.
+fn latest_label(value: String, staleness: &StalenessTracker, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let text = l10n.format(UiText::Latest, &[("value", &value)]);
+    match staleness.stale_for() {
+        Some(stale_for) => {
+            let seconds = l10n.number(stale_for.as_secs_f64(), 1);
+            ui.weak(l10n.format(UiText::Stale, &[("text", &text), ("seconds", &seconds)]));
+        }
+        None => {
+            ui.label(text);
+        }
+    }
+}
+
+fn staleness_ui(config: &mut Option<StalenessConfig>, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let mut enabled = config.is_some();
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut enabled, l10n.get(UiText::StaleDetection));
+        if !enabled {
+            *config = None;
+            return;
+        }
+        let config = config.get_or_insert_with(default);
+        ui.add(
+            DragValue::new(&mut config.threshold)
+                .prefix(l10n.get(UiText::IntervalTimesPrefix))
+                .speed(0.05)
+                .range(1.0..=f64::MAX),
+        );
+    });
+}
+
+fn ghost_ui(config: &mut Option<GhostConfig>, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let mut enabled = config.is_some();
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut enabled, l10n.get(UiText::CompareWithEarlier))
+            .on_hover_text(l10n.get(UiText::CompareWithEarlierHint));
+        if !enabled {
+            *config = None;
+            return;
+        }
+        let config = config.get_or_insert_with(default);
+        ui.add(
+            DragValue::new(&mut config.seconds)
+                .prefix(l10n.get(UiText::SecondsEarlierPrefix))
+                .speed(0.1)
+                .range(0.1..=3600.0),
+        );
+        ui.checkbox(&mut config.show_mean_delta, l10n.get(UiText::ShowMeanDelta));
+    });
+}
+
+fn interpolation_ui(interpolation: &mut Option<LineInterpolation>, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    ui.horizontal(|ui| {
+        ui.label(l10n.get(UiText::Interpolation));
+        ui.selectable_value(interpolation, None, l10n.get(UiText::Auto))
+            .on_hover_text(l10n.get(UiText::InterpolationAutoHint));
+        for option in LineInterpolation::ALL {
+            ui.selectable_value(interpolation, Some(option), l10n.get(option.label()));
         }
+    });
+}
+
+fn sampling_mode_ui(sampling: &mut SamplingMode, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let mut per_tick = *sampling == SamplingMode::PerFixedTick;
+    if ui
+        .checkbox(&mut per_tick, l10n.get(UiText::SamplePerFixedTick))
+        .on_hover_text(l10n.get(UiText::SamplePerFixedTickHint))
+        .changed()
+    {
+        *sampling = if per_tick {
+            SamplingMode::PerFixedTick
+        } else {
+            SamplingMode::PerFrame
+        };
+    }
+}
+
+fn x_axis_label(sampling: SamplingMode) -> UiText {
+    match sampling {
+        SamplingMode::PerFrame => UiText::FrameAxis,
+        SamplingMode::PerFixedTick => UiText::FixedTickAxis,
+    }
+}
+
+/// How many samples a counter or gauge plot keeps.
+enum WindowSize {
+    Samples(usize),
+    /// The samples of this many seconds, see [`SampleTimes::window_size`].
+    Seconds(f64),
+}
+
+/// The window size of a plot, in order of precedence: the window's linked
+/// size, the plot's own size, then the dashboard's default history or
+/// window size.
+fn resolve_window_size(
+    dash_config: &DashboardConfig,
+    window_config: &DashboardWindowConfig,
+    plot_window_size: Option<usize>,
+) -> WindowSize {
+    match window_config.global_window_size.or(plot_window_size) {
+        Some(window_size) => WindowSize::Samples(window_size),
+        None => match dash_config.default_history_secs {
+            Some(seconds) => WindowSize::Seconds(seconds),
+            None => WindowSize::Samples(dash_config.default_window_size),
+        },
+    }
+}
+
+fn window_size_override_ui(window_size: &mut Option<usize>, current: usize, ui: &mut Ui) {
+    let l10n = DashboardLocalization::of(ui);
+    let mut override_default = window_size.is_some();
+    ui.checkbox(&mut override_default, l10n.get(UiText::OverrideWindowSize));
+    if override_default {
+        ui.add(window_size_slider(
+            &l10n,
+            window_size.get_or_insert(current),
+        ));
+    } else {
+        *window_size = None;
     }
 }
 
-pub(crate) fn window_size_slider(size: &mut usize) -> Slider {
-    Slider::new(size, 100..=5000).text("Window Size")
+pub(crate) fn window_size_slider<'a>(
+    l10n: &DashboardLocalization,
+    size: &'a mut usize,
+) -> Slider<'a> {
+    Slider::new(size, 100..=5000).text(l10n.get(UiText::WindowSize))
 }
 
-struct Smoother {
+/// Exponential smoothing of a series, as used by gauge plots with a
+/// [`GaugePlotConfig::smoothing_weight`].
+///
+/// Each value moves the smoothed value by `1 - weight` of the way towards
+/// it. The standard deviation is weighted the same way, and updated in
+/// constant time per value, like Welford's algorithm.
+///
+/// ```
+/// # use bevy_metrics_dashboard::plots::Smoother;
+/// let values = [3.0, 5.0, 4.0, 10.0, 2.0, 2.5];
+/// let weight = 0.7;
+/// let mut smoother = Smoother::new(weight);
+/// for value in values {
+///     smoother.add(value);
+/// }
+///
+/// // The first value starts the series, and later ones are weighted by
+/// // `1 - weight`, decaying by `weight` per newer value.
+/// let n = values.len() - 1;
+/// let weights: Vec<f64> = (0..=n)
+///     .map(|i| match i {
+///         0 => weight.powi(n as i32),
+///         i => (1.0 - weight) * weight.powi((n - i) as i32),
+///     })
+///     .collect();
+/// let mean: f64 = values.iter().zip(&weights).map(|(v, w)| v * w).sum();
+/// let variance: f64 = values
+///     .iter()
+///     .zip(&weights)
+///     .map(|(v, w)| w * (v - mean).powi(2))
+///     .sum();
+/// assert!((smoother.smoothed_value().unwrap() - mean).abs() < 1e-12);
+/// assert!((smoother.std_dev() - variance.sqrt()).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Smoother {
     smoothed_value: Option<f64>,
+    variance: f64,
     weight: f64,
 }
 
 impl Smoother {
-    fn new(weight: f64) -> Self {
+    /// `weight` is clamped to `0.0..=1.0`. Zero disables smoothing.
+    pub fn new(weight: f64) -> Self {
         Self {
             smoothed_value: None,
+            variance: 0.0,
             weight: weight.clamp(0.0, 1.0),
         }
     }
 
-    fn add(&mut self, value: f64) {
-        self.smoothed_value =
-            Some((1.0 - self.weight) * value + self.weight * self.smoothed_value.unwrap_or(value));
+    /// Change the weight of the following values.
+    pub fn set_weight(&mut self, weight: f64) {
+        self.weight = weight.clamp(0.0, 1.0);
+    }
+
+    #[allow(missing_docs)]
+    pub fn add(&mut self, value: f64) {
+        let Some(smoothed) = self.smoothed_value else {
+            self.smoothed_value = Some(value);
+            return;
+        };
+        let diff = value - smoothed;
+        let step = (1.0 - self.weight) * diff;
+        self.smoothed_value = Some(smoothed + step);
+        self.variance = self.weight * (self.variance + diff * step);
+    }
+
+    /// `None` until a value is added.
+    pub fn smoothed_value(&self) -> Option<f64> {
+        self.smoothed_value
+    }
+
+    /// The weighted standard deviation of the values around the
+    /// [smoothed value](Self::smoothed_value).
+    pub fn std_dev(&self) -> f64 {
+        self.variance.max(0.0).sqrt()
+    }
+}
+
+/// `[x, lower, upper]` runs of the `bounds` of each sample in the drawn
+/// segments of `cached`, transformed and downsampled like the line. Runs
+/// break at samples without bounds.
+fn band_runs(
+    cached: &CachedPoints,
+    transform: &PlotTransform,
+    bounds: impl Fn(u64) -> Option<(f64, f64)>,
+) -> Vec<Vec<[f64; 3]>> {
+    let mut runs = Vec::new();
+    for segment in &cached.segments {
+        let mut run = Vec::new();
+        for i in segment.clone() {
+            let sample = cached.first_sample + i as u64;
+            let Some((min, max)) = bounds(sample) else {
+                runs.push(std::mem::take(&mut run));
+                continue;
+            };
+            let (a, b) = (transform.apply(min), transform.apply(max));
+            run.push([sample as f64, a.min(b), a.max(b)]);
+        }
+        runs.push(run);
+    }
+    runs.retain(|run: &Vec<_>| run.len() > 1);
+    for run in &mut runs {
+        downsample_band(run, cached.downsampling);
+    }
+    runs
+}
+
+/// Merge every `chunk` points of a band into one spanning their lowest and
+/// highest bounds, keeping the last point.
+fn downsample_band(points: &mut Vec<[f64; 3]>, chunk: usize) {
+    if chunk <= 2 || points.len() <= chunk {
+        return;
     }
+    let last = points[points.len() - 1];
+    let merged: Vec<_> = points
+        .chunks(chunk)
+        .map(|chunk| {
+            chunk[1..]
+                .iter()
+                .fold(chunk[0], |[x, lower, upper], &[_, l, u]| {
+                    [x, lower.min(l), upper.max(u)]
+                })
+        })
+        .collect();
+    *points = merged;
+    if points[points.len() - 1][0] != last[0] {
+        points.push(last);
+    }
+}
 
-    fn smoothed_value(&self) -> f64 {
-        self.smoothed_value.unwrap()
+/// The lowest and highest of every `chunk` points, in order, and the last
+/// point, so spikes and the latest value are still drawn.
+fn downsample(points: &[PlotPoint], chunk: usize) -> Cow<'_, [PlotPoint]> {
+    if chunk <= 2 || points.len() <= chunk {
+        return Cow::Borrowed(points);
+    }
+    let mut downsampled = Vec::with_capacity(2 * points.len() / chunk + 1);
+    for chunk in points.chunks(chunk) {
+        let by_y = |a: &&PlotPoint, b: &&PlotPoint| a.y.total_cmp(&b.y);
+        let (Some(min), Some(max)) = (chunk.iter().min_by(by_y), chunk.iter().max_by(by_y)) else {
+            continue;
+        };
+        let (first, second) = if min.x <= max.x {
+            (min, max)
+        } else {
+            (max, min)
+        };
+        downsampled.push(*first);
+        if second.x != first.x {
+            downsampled.push(*second);
+        }
+    }
+    let last = points[points.len() - 1];
+    if downsampled.last().is_none_or(|p| p.x != last.x) {
+        downsampled.push(last);
     }
+    Cow::Owned(downsampled)
 }
 
 fn derivative(points: &mut Vec<PlotPoint>) {
@@ -0,0 +1,133 @@
+use crate::egui::{Response, Ui};
+use crate::egui_plot::{Line, Plot, PlotPoints};
+use crate::latest_value::LatestValue;
+use crate::localization::{DashboardLocalization, UiText};
+use crate::registry::MetricKey;
+use crate::ring::Ring;
+use crate::snapshot::FrameMetricsSnapshot;
+use bevy::platform::collections::HashMap;
+use metrics_util::MetricKind;
+use std::time::{Duration, Instant};
+
+/// How long a result must be hovered before its preview is shown.
+const HOVER_DELAY: Duration = Duration::from_millis(300);
+
+/// Previews that haven't been hovered for this long are discarded.
+const EVICT_AFTER: Duration = Duration::from_secs(10);
+
+/// How many samples each preview retains.
+const PREVIEW_LEN: usize = 300;
+
+/// Short histories of recently hovered metrics, drawn as sparklines.
+///
+/// Histories are only recorded while a metric is being previewed, so there is
+/// no cost for the rest of the registry. Counters are previewed as their rate
+/// per second, and histograms as the mean of each frame's samples.
+#[derive(Default)]
+pub struct PreviewSampler {
+    previews: HashMap<MetricKey, Preview>,
+    hovered: Option<(MetricKey, Instant)>,
+}
+
+struct Preview {
+    value: LatestValue,
+    ring: Ring<f64>,
+    last_hovered: Instant,
+    /// When the metric was last sampled, to turn counter increments into
+    /// rates.
+    last_sampled: Option<Instant>,
+}
+
+impl PreviewSampler {
+    /// Start or continue previewing the metric of a hovered search result.
+    ///
+    /// After [`HOVER_DELAY`], a tooltip with the sparkline is shown.
+    pub fn on_hover(&mut self, key: &MetricKey, response: &Response) {
+        if !response.hovered() {
+            if self.hovered.as_ref().is_some_and(|(k, _)| k == key) {
+                self.hovered = None;
+            }
+            return;
+        }
+
+        let now = Instant::now();
+        let hover_start = match &self.hovered {
+            Some((k, start)) if k == key => *start,
+            _ => {
+                self.hovered = Some((key.clone(), now));
+                now
+            }
+        };
+        let preview = self.previews.entry(key.clone()).or_insert_with(|| Preview {
+            value: LatestValue::new(key.clone()),
+            ring: Ring::new(PREVIEW_LEN),
+            last_hovered: now,
+            last_sampled: None,
+        });
+        preview.last_hovered = now;
+
+        if now - hover_start >= HOVER_DELAY {
+            response
+                .clone()
+                .on_hover_ui_at_pointer(|ui| preview.draw_sparkline(ui));
+        } else {
+            // Keep repainting until the delay elapses.
+            response.ctx.request_repaint();
+        }
+    }
+
+    /// The metrics being previewed.
+    pub fn keys(&self) -> impl Iterator<Item = &MetricKey> {
+        self.previews.keys()
+    }
+
+    /// Sample all previewed metrics and evict stale previews.
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        self.previews
+            .retain(|_, preview| preview.last_hovered.elapsed() < EVICT_AFTER);
+        let now = Instant::now();
+        for preview in self.previews.values_mut() {
+            preview.value.update(snapshot);
+            let elapsed = preview
+                .last_sampled
+                .replace(now)
+                .map(|last| (now - last).as_secs_f64());
+            let value = match preview.value.key().kind {
+                // The increment since the last frame, divided by its duration.
+                MetricKind::Counter => preview
+                    .value
+                    .latest()
+                    .zip(elapsed.filter(|&secs| secs > 0.0))
+                    .map(|(increment, secs)| increment / secs),
+                MetricKind::Gauge | MetricKind::Histogram => preview.value.latest(),
+            };
+            if let Some(value) = value {
+                preview.ring.push(value);
+            }
+        }
+    }
+}
+
+impl Preview {
+    fn draw_sparkline(&self, ui: &mut Ui) {
+        if self.ring.latest().is_none() {
+            ui.weak(DashboardLocalization::of(ui).get(UiText::CollectingSamples));
+            return;
+        }
+        Plot::new(("metric-preview", &self.value.key().key))
+            .width(200.0)
+            .height(60.0)
+            .show_axes(false)
+            .show_grid(false)
+            .show_x(false)
+            .show_y(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .show(ui, |plot_ui| {
+                let points = PlotPoints::Owned(self.ring.make_plot_points());
+                plot_ui.line(Line::new("preview", points));
+            });
+    }
+}
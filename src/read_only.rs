@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+/// Which plots can't be changed from the dashboard's UI, e.g. in builds
+/// shipped to QA with plots configured by someone else.
+///
+/// See [`DashboardConfig::read_only`](crate::DashboardConfig::read_only).
+/// Locked plots can still be panned, zoomed, hovered and exported, and
+/// windows can still be paused, but their plots can't be removed,
+/// reordered or configured. Plots added by code are not affected.
+///
+/// ```
+/// use bevy_metrics_dashboard::registry::MetricKey;
+/// use bevy_metrics_dashboard::test_utils::TestApp;
+/// use bevy_metrics_dashboard::{metrics_util::MetricKind, DashboardConfig, DashboardWindow};
+/// use bevy_metrics_dashboard::{OpenPlots, ReadOnly};
+///
+/// let mut test = TestApp::new();
+/// let window = test.spawn_window("Metrics");
+/// let open = |group: &str| OpenPlots {
+///     metrics: vec![(MetricKey::new(group.to_owned().into(), MetricKind::Gauge), None)],
+///     group: Some(group.to_owned()),
+/// };
+/// let plots = |test: &TestApp| {
+///     let window = test.app().world().get::<DashboardWindow>(window).unwrap();
+///     let names = window.plots().iter().map(|p| p.key().key.name().to_owned());
+///     names.collect::<Vec<_>>()
+/// };
+/// let set_read_only = |test: &mut TestApp, read_only| {
+///     let world = test.app_mut().world_mut();
+///     world.resource_mut::<DashboardConfig>().read_only = read_only;
+/// };
+///
+/// // Plots can't be added to locked groups, but they can to others.
+/// set_read_only(&mut test, ReadOnly::Groups(vec!["perf".into()]));
+/// test.app_mut().world_mut().send_event(open("perf"));
+/// test.app_mut().world_mut().send_event(open("qa"));
+/// test.update();
+/// assert_eq!(plots(&test), ["qa"]);
+///
+/// // Nothing can be added while the whole dashboard is locked.
+/// set_read_only(&mut test, ReadOnly::All);
+/// test.request_plot("frame_time", MetricKind::Gauge);
+/// test.app_mut().world_mut().send_event(open("qa"));
+/// test.update();
+/// assert_eq!(plots(&test), ["qa"]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Reflect)]
+pub enum ReadOnly {
+    /// Everything can be changed.
+    #[default]
+    Off,
+    /// Nothing can be changed: window settings, the search bar and toolbar
+    /// buttons that add plots or save the layout are hidden too, and
+    /// [`RequestPlot`](crate::RequestPlot),
+    /// [`OpenPlots`](crate::OpenPlots) and `SaveLayout` events are ignored.
+    All,
+    /// Only plots in the [groups](crate::plots::MetricPlot::group) with
+    /// these names are locked. Other plots, including new ones, can be
+    /// changed, and [`OpenPlots`](crate::OpenPlots) events for these
+    /// groups are ignored.
+    Groups(Vec<String>),
+}
+
+impl ReadOnly {
+    /// Whether the whole dashboard is locked.
+    pub fn is_all(&self) -> bool {
+        *self == Self::All
+    }
+
+    /// Whether plots in `group`, or plots without a group, are locked.
+    pub fn locks_group(&self, group: Option<&str>) -> bool {
+        match self {
+            Self::Off => false,
+            Self::All => true,
+            Self::Groups(groups) => group.is_some_and(|group| groups.iter().any(|g| g == group)),
+        }
+    }
+}
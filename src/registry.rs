@@ -1,10 +1,16 @@
 //! The process-global metrics registry.
 
-use crate::egui::{text::LayoutJob, Color32, TextFormat};
+use crate::cached_metrics::{key_from_parts, CachedCounter, CachedGauge, CachedHistogram};
+use crate::egui::{text::LayoutJob, Stroke, TextFormat, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::retention::{MetricEvicted, RetentionPolicy};
+use crate::scoped_recorder::ScopedRecorderGuard;
+use crate::theme::ThemePalette;
 use crate::{metric_kind_str, unit_str};
 use bevy::{
-    platform::collections::HashMap,
-    prelude::{default, Res, Resource},
+    log::warn,
+    platform::collections::{hash_map::Entry, HashMap, HashSet},
+    prelude::{default, App, Local, Res, ResMut, Resource},
 };
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use metrics::{Counter, Gauge, Histogram, KeyName, Metadata, Recorder, SharedString, Unit};
@@ -13,8 +19,10 @@ use metrics_util::{
     storage::AtomicBucket,
     MetricKind,
 };
-use std::sync::atomic::AtomicU64;
-use std::sync::{Arc, RwLock};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, Weak};
+use std::time::{Duration, Instant};
 
 /// Tracks all metrics in the current process.
 ///
@@ -29,14 +37,681 @@ pub struct MetricsRegistry {
 struct Inner {
     registry: Registry<metrics::Key, AtomicStorage>,
     descriptions: RwLock<HashMap<DescriptionKey, MetricDescription>>,
+    manifest: RwLock<ManifestDescriptions>,
+    custom_units: RwLock<HashMap<DescriptionKey, MetricUnit>>,
+    extras: RwLock<HashMap<DescriptionKey, DescriptionExtras>>,
+    aliases: RwLock<HashMap<KeyName, MetricAlias>>,
+    activity: Mutex<HashMap<MetricKey, Activity>>,
+    histogram_subscriptions: Mutex<Vec<Weak<SubscriptionSlot>>>,
+    hot_histogram_config: RwLock<HotHistogramConfig>,
+    envelope_names: RwLock<HashSet<String>>,
+    /// Whether `envelope_names` is non-empty, so registering a gauge doesn't
+    /// take its lock when no envelopes are tracked.
+    tracks_envelopes: AtomicBool,
+    envelopes: Mutex<HashMap<metrics::Key, Arc<GaugeEnvelope>>>,
+    histogram_loads: Mutex<HashMap<metrics::Key, HistogramLoad>>,
+    cardinality_config: RwLock<CardinalityConfig>,
+    cardinality: Mutex<HashMap<DescriptionKey, LabelCardinality>>,
+    retention: RwLock<Option<RetentionPolicy>>,
+    evicted: AtomicU64,
+    generation: AtomicGeneration,
+    frame_samples: Mutex<VecDeque<FrameSample>>,
+    /// The newest frame a gauge's value was recorded for with
+    /// `record_for_frame`, so a value for an older frame doesn't replace it.
+    frame_gauges: Mutex<HashMap<metrics::Key, FrameId>>,
+    unit_suffixes: RwLock<Option<UnitSuffixes>>,
 }
 
+/// The number of the current frame, counted by the
+/// [`RegistryPlugin`](crate::RegistryPlugin) from the start of the app.
+///
+/// Capture it before spawning a task whose values are recorded with
+/// [`MetricsRegistry::record_for_frame`], so the values are attributed to the
+/// frame that spawned the task rather than the one in which it finished.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, Resource)]
+pub struct FrameId(pub u64);
+
+impl FrameId {
+    /// Bevy system that advances the frame number. The
+    /// [`RegistryPlugin`](crate::RegistryPlugin) runs it in [`First`](bevy::prelude::First).
+    pub fn advance(mut frame: ResMut<Self>) {
+        frame.0 += 1;
+    }
+}
+
+/// A value recorded with [`MetricsRegistry::record_for_frame`], waiting to be
+/// captured in a [`FrameMetricsSnapshot`](crate::FrameMetricsSnapshot).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameSample {
+    /// The frame the value belongs to.
+    pub frame: FrameId,
+    #[allow(missing_docs)]
+    pub key: MetricKey,
+    /// The increment of a counter or the value of a gauge.
+    pub value: f64,
+}
+
+/// How many [`FrameSample`]s are kept until they are captured. The oldest
+/// ones are discarded first, e.g. when no dashboard captures them.
+const MAX_PENDING_FRAME_SAMPLES: usize = 100_000;
+
+/// Counts changes to a [`MetricsRegistry`], so consumers can tell whether
+/// anything changed since they last looked without scanning the registry.
+///
+/// Returned by [`MetricsRegistry::generation`]. Each counter only increases,
+/// and does so whenever the corresponding part of the registry changes.
+/// Consumers should only compare generations for equality: a new metric
+/// registered from several threads at once may increase its counter more
+/// than once.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RegistryGeneration {
+    /// Increased when a counter is registered for the first time, or
+    /// removed.
+    pub counters: u64,
+    /// Increased when a gauge is registered for the first time, or removed.
+    pub gauges: u64,
+    /// Increased when a histogram is registered for the first time, or
+    /// removed.
+    pub histograms: u64,
+    /// Increased when a description is added, or a custom unit, description
+    /// extras or an alias is changed.
+    pub descriptions: u64,
+}
+
+impl RegistryGeneration {
+    /// The registration generation of metrics of `kind`, which also changes
+    /// when one is removed.
+    pub fn registrations(&self, kind: MetricKind) -> u64 {
+        match kind {
+            MetricKind::Counter => self.counters,
+            MetricKind::Gauge => self.gauges,
+            MetricKind::Histogram => self.histograms,
+        }
+    }
+}
+
+/// The counters of a [`RegistryGeneration`], updated without locking.
+#[derive(Default)]
+struct AtomicGeneration {
+    counters: AtomicU64,
+    gauges: AtomicU64,
+    histograms: AtomicU64,
+    descriptions: AtomicU64,
+}
+
+impl AtomicGeneration {
+    /// A metric of `kind` was registered or removed.
+    fn changed(&self, kind: MetricKind) {
+        let counter = match kind {
+            MetricKind::Counter => &self.counters,
+            MetricKind::Gauge => &self.gauges,
+            MetricKind::Histogram => &self.histograms,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn described(&self) {
+        self.descriptions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> RegistryGeneration {
+        RegistryGeneration {
+            counters: self.counters.load(Ordering::Relaxed),
+            gauges: self.gauges.load(Ordering::Relaxed),
+            histograms: self.histograms.load(Ordering::Relaxed),
+            descriptions: self.descriptions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Limits for detecting histograms that record so many samples per frame
+/// that buffering them is expensive, e.g. because a system records a value
+/// per entity in a loop.
+///
+/// See [`MetricsRegistry::hot_histograms`].
+#[derive(Clone, Debug)]
+pub struct HotHistogramConfig {
+    /// The number of samples between two clears of the atomic buckets,
+    /// usually one frame, above which a histogram is hot.
+    pub threshold: usize,
+    /// How many consecutive frames a histogram must exceed its threshold
+    /// before it's reported.
+    pub frames: u32,
+    /// Thresholds replacing [`Self::threshold`] for metrics, by name, that
+    /// legitimately record many samples.
+    pub overrides: HashMap<String, usize>,
+}
+
+impl Default for HotHistogramConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 10_000,
+            frames: 5,
+            overrides: default(),
+        }
+    }
+}
+
+impl HotHistogramConfig {
+    /// See [`Self::overrides`].
+    pub fn with_override(mut self, name: impl Into<String>, threshold: usize) -> Self {
+        self.overrides.insert(name.into(), threshold);
+        self
+    }
+
+    fn threshold_for(&self, key: &metrics::Key) -> usize {
+        self.overrides
+            .get(key.name())
+            .copied()
+            .unwrap_or(self.threshold)
+    }
+}
+
+/// A gauge that also tracks the lowest and highest values it was set to
+/// between two [`MetricsRegistry::take_gauge_envelopes`] calls.
+///
+/// See [`MetricsRegistry::track_gauge_envelope`].
+struct GaugeEnvelope {
+    /// Shared with the registry, so reading the gauge is unaffected.
+    value: Arc<AtomicU64>,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl GaugeEnvelope {
+    fn new(value: Arc<AtomicU64>) -> Self {
+        let current = value.load(Ordering::Relaxed);
+        Self {
+            value,
+            min: AtomicU64::new(current),
+            max: AtomicU64::new(current),
+        }
+    }
+
+    fn update(&self, f: impl Fn(f64) -> f64) {
+        let previous = self
+            .value
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |bits| {
+                Some(f(f64::from_bits(bits)).to_bits())
+            })
+            .expect("the closure always returns a value");
+        self.observe(f(f64::from_bits(previous)));
+    }
+
+    fn observe(&self, value: f64) {
+        let _ = self
+            .min
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |bits| {
+                (value < f64::from_bits(bits)).then_some(value.to_bits())
+            });
+        let _ = self
+            .max
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |bits| {
+                (value > f64::from_bits(bits)).then_some(value.to_bits())
+            });
+    }
+
+    /// The lowest and highest values since the last call, and the current
+    /// value, which starts the next envelope.
+    fn take(&self) -> (f64, f64) {
+        let current = self.value.load(Ordering::Acquire);
+        let min = f64::from_bits(self.min.swap(current, Ordering::AcqRel));
+        let max = f64::from_bits(self.max.swap(current, Ordering::AcqRel));
+        let current = f64::from_bits(current);
+        (min.min(current), max.max(current))
+    }
+}
+
+impl metrics::GaugeFn for GaugeEnvelope {
+    fn increment(&self, value: f64) {
+        self.update(|current| current + value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.update(|current| current - value);
+    }
+
+    fn set(&self, value: f64) {
+        self.value.store(value.to_bits(), Ordering::Release);
+        self.observe(value);
+    }
+}
+
+/// Limits for detecting metrics with so many label combinations that they
+/// bloat the registry, e.g. because a label's value is an entity or frame
+/// number.
+///
+/// See [`MetricsRegistry::high_cardinality`].
+#[derive(Clone, Debug)]
+pub struct CardinalityConfig {
+    /// The number of label combinations of one metric name and kind above
+    /// which it's reported.
+    pub limit: usize,
+    /// Limits replacing [`Self::limit`] for metrics, by name, that
+    /// legitimately have many label combinations.
+    pub overrides: HashMap<String, usize>,
+    /// Record new label combinations above the limit into one key per
+    /// metric, labeled `label="<overflow>"`, instead of registering them.
+    ///
+    /// Off by default, since the values recorded with those labels are
+    /// merged.
+    pub overflow: bool,
+}
+
+impl Default for CardinalityConfig {
+    fn default() -> Self {
+        Self {
+            limit: 1_000,
+            overrides: default(),
+            overflow: false,
+        }
+    }
+}
+
+impl CardinalityConfig {
+    /// See [`Self::overrides`].
+    pub fn with_override(mut self, name: impl Into<String>, limit: usize) -> Self {
+        self.overrides.insert(name.into(), limit);
+        self
+    }
+
+    /// See [`Self::overflow`].
+    pub fn with_overflow(mut self, overflow: bool) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    fn limit_for(&self, name: &str) -> usize {
+        self.overrides.get(name).copied().unwrap_or(self.limit)
+    }
+}
+
+/// A metric with more label combinations than its [`CardinalityConfig`]
+/// limit. Returned by [`MetricsRegistry::high_cardinality`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighCardinality {
+    #[allow(missing_docs)]
+    pub key: DescriptionKey,
+    /// The number of label combinations registered, not counting the
+    /// overflow key.
+    pub combinations: usize,
+    #[allow(missing_docs)]
+    pub limit: usize,
+    /// How many times a new label combination was recorded into the
+    /// overflow key instead of being registered. See
+    /// [`CardinalityConfig::overflow`]. Each registration of such a
+    /// combination counts, since it's never registered.
+    pub overflowed: u64,
+    /// The latest new label combinations, most recent last.
+    pub examples: Vec<metrics::Key>,
+}
+
+/// The label combinations of one metric name and kind registered so far.
+#[derive(Default)]
+struct LabelCardinality {
+    combinations: usize,
+    overflowed: u64,
+    examples: VecDeque<metrics::Key>,
+    last_warning: Option<Instant>,
+}
+
+/// How many example keys are kept for each metric with many label
+/// combinations.
+const CARDINALITY_EXAMPLES: usize = 3;
+
+/// How often a warning is logged for each metric with too many label
+/// combinations.
+const CARDINALITY_WARNING_PERIOD: Duration = Duration::from_secs(60);
+
+/// The label of the key that new label combinations are recorded into above
+/// the [`CardinalityConfig`] limit.
+const OVERFLOW_LABEL: (&str, &str) = ("label", "<overflow>");
+
+/// Units inferred from the end of metric names, for metrics without a
+/// described unit, e.g. milliseconds for `asset_load_ms`.
+///
+/// A suffix matches the last word of a name, after a `_`, `.` or `-`. See
+/// [`MetricsRegistry::set_unit_suffixes`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitSuffixes {
+    /// Checked in order, so earlier suffixes win.
+    pub suffixes: Vec<(String, MetricUnit)>,
+}
+
+impl Default for UnitSuffixes {
+    fn default() -> Self {
+        let suffixes = [
+            ("ns", Unit::Nanoseconds),
+            ("us", Unit::Microseconds),
+            ("ms", Unit::Milliseconds),
+            ("secs", Unit::Seconds),
+            ("seconds", Unit::Seconds),
+            ("bytes", Unit::Bytes),
+            ("kib", Unit::Kibibytes),
+            ("mib", Unit::Mebibytes),
+            ("gib", Unit::Gibibytes),
+            ("percent", Unit::Percent),
+            ("pct", Unit::Percent),
+        ];
+        Self {
+            suffixes: suffixes
+                .into_iter()
+                .map(|(suffix, unit)| (suffix.to_owned(), MetricUnit::Standard(unit)))
+                .collect(),
+        }
+    }
+}
+
+impl UnitSuffixes {
+    /// A table without any suffixes, to build up with [`Self::with_suffix`].
+    pub fn empty() -> Self {
+        Self {
+            suffixes: Vec::new(),
+        }
+    }
+
+    /// Infer `unit` for names ending in `suffix`, replacing any unit the
+    /// suffix had.
+    pub fn with_suffix(mut self, suffix: impl Into<String>, unit: impl Into<MetricUnit>) -> Self {
+        let suffix = suffix.into();
+        self.suffixes.retain(|(s, _)| *s != suffix);
+        self.suffixes.push((suffix, unit.into()));
+        self
+    }
+
+    /// Stop inferring a unit from `suffix`.
+    pub fn without_suffix(mut self, suffix: &str) -> Self {
+        self.suffixes.retain(|(s, _)| s != suffix);
+        self
+    }
+
+    /// The unit of the first suffix matching the end of `name`.
+    pub fn infer(&self, name: &str) -> Option<&MetricUnit> {
+        self.suffixes.iter().find_map(|(suffix, unit)| {
+            let rest = name.strip_suffix(suffix.as_str())?;
+            rest.ends_with(['_', '.', '-']).then_some(unit)
+        })
+    }
+}
+
+/// How many samples a histogram recorded in recent frames, kept for
+/// histograms that exceeded their [`HotHistogramConfig`] threshold.
+struct HistogramLoad {
+    /// The number of samples in the latest frame.
+    samples: usize,
+    /// Consecutive frames above the threshold, up to the latest.
+    hot_frames: u32,
+    last_warning: Option<Instant>,
+}
+
+/// How often a warning is logged for each hot histogram.
+const HOT_HISTOGRAM_WARNING_PERIOD: Duration = Duration::from_secs(60);
+
+/// Where [`MetricsRegistry::clear_atomic_buckets`] publishes the samples of
+/// one subscribed histogram.
+struct SubscriptionSlot {
+    key: metrics::Key,
+    samples: RwLock<Arc<[f64]>>,
+}
+
+/// Receives the samples of one histogram each time the atomic buckets are
+/// cleared.
+///
+/// Created by [`MetricsRegistry::subscribe_histogram`]. The registry stops
+/// publishing to the subscription once this handle is dropped.
+pub struct HistogramSubscription {
+    slot: Arc<SubscriptionSlot>,
+}
+
+impl HistogramSubscription {
+    /// The key of the subscribed histogram.
+    pub fn key(&self) -> &metrics::Key {
+        &self.slot.key
+    }
+
+    /// All samples recorded before the atomic buckets were last cleared,
+    /// i.e. usually during the previous frame.
+    ///
+    /// The buffer is shared by all subscriptions to the same histogram.
+    pub fn samples(&self) -> Arc<[f64]> {
+        self.slot.samples.read().unwrap().clone()
+    }
+}
+
+/// What [`MetricsRegistry::sample_activity`] last saw of a metric.
+struct Activity {
+    last_bits: u64,
+    /// When the metric was first sampled, to measure how long it's idle if
+    /// it was never updated after.
+    first_seen: Instant,
+    last_updated: Option<Instant>,
+}
+
+/// How often [`MetricsRegistry::sample_activity_system`] scans the registry.
+const ACTIVITY_SAMPLE_PERIOD: Duration = Duration::from_millis(250);
+
 /// A description of some metric, displayed when searching the registry or plotting.
 #[allow(missing_docs)]
 #[derive(Clone)]
 pub struct MetricDescription {
-    pub unit: Option<Unit>,
+    pub unit: Option<MetricUnit>,
+    /// Whether [`Self::unit`] was inferred from the metric's name, because
+    /// none was described. See [`MetricsRegistry::set_unit_suffixes`].
+    pub unit_inferred: bool,
     pub text: SharedString,
+    /// Whether the text was marked with `[deprecated]`. See [`Self::new`].
+    pub deprecated: bool,
+    /// See [`MetricsRegistry::set_description_extras`].
+    pub extras: DescriptionExtras,
+}
+
+/// Details of a metric that the `describe_*` macros can't carry, like a link
+/// to its documentation.
+///
+/// See [`MetricsRegistry::set_description_extras`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct DescriptionExtras {
+    /// Shown as a link in the context menu and plot settings.
+    #[cfg_attr(feature = "layout", serde(skip_serializing_if = "Option::is_none"))]
+    pub docs_url: Option<String>,
+    /// The person or team responsible for the metric, shown in the
+    /// description.
+    #[cfg_attr(feature = "layout", serde(skip_serializing_if = "Option::is_none"))]
+    pub owner: Option<String>,
+}
+
+impl DescriptionExtras {
+    /// See [`Self::docs_url`].
+    pub fn with_docs_url(mut self, url: impl Into<String>) -> Self {
+        self.docs_url = Some(url.into());
+        self
+    }
+
+    /// See [`Self::owner`].
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.docs_url.is_none() && self.owner.is_none()
+    }
+}
+
+impl MetricDescription {
+    /// Create a description from the arguments of a `describe_*` macro.
+    ///
+    /// If `unit` is `None` and `text` ends with a bracketed unit like
+    /// `"Entities alive [entities]"`, the brackets are parsed out as a
+    /// [`MetricUnit::Custom`]. A `[deprecated]` marker, before or after the
+    /// unit, is parsed out as [`Self::deprecated`] instead of a unit.
+    /// Otherwise `text` is kept as-is.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::{MetricDescription, MetricUnit};
+    /// # use metrics::Unit;
+    /// let description = MetricDescription::new(None, "Bodies [entities] [deprecated]".into());
+    /// assert_eq!(description.unit, Some(MetricUnit::Custom("entities".into())));
+    /// assert!(description.deprecated);
+    /// assert_eq!(&*description.text, "Bodies");
+    ///
+    /// let description = MetricDescription::new(None, "Bodies [Deprecated] [entities]".into());
+    /// assert_eq!(description.unit, Some(MetricUnit::Custom("entities".into())));
+    /// assert!(description.deprecated);
+    ///
+    /// let description = MetricDescription::new(Some(Unit::Seconds), "Step [deprecated]".into());
+    /// assert_eq!(description.unit, Some(MetricUnit::Standard(Unit::Seconds)));
+    /// assert!(description.deprecated);
+    /// assert_eq!(&*description.text, "Step");
+    ///
+    /// let description = MetricDescription::new(None, "Old bodies [deprecated]".into());
+    /// assert_eq!(description.unit, None);
+    /// assert!(description.deprecated);
+    ///
+    /// // Descriptions without brackets are untouched.
+    /// let description = MetricDescription::new(None, "Bodies".into());
+    /// assert_eq!((description.unit, description.deprecated), (None, false));
+    /// assert_eq!(&*description.text, "Bodies");
+    /// ```
+    pub fn new(unit: Option<Unit>, text: SharedString) -> Self {
+        let (mut rest, mut deprecated) = split_deprecated_marker(&text);
+        let mut custom = None;
+        if unit.is_none() {
+            if let Some((stripped, unit)) = split_unit_suffix(rest) {
+                custom = Some(MetricUnit::Custom(unit.into()));
+                let (stripped, marked) = split_deprecated_marker(stripped);
+                rest = stripped;
+                deprecated |= marked;
+            }
+        }
+        let text = if rest.len() == text.len() {
+            text
+        } else {
+            rest.to_owned().into()
+        };
+        Self {
+            unit: custom.or(unit.map(MetricUnit::Standard)),
+            unit_inferred: false,
+            text,
+            deprecated,
+            extras: default(),
+        }
+    }
+}
+
+/// Which description of a metric is shown when both the code, with the
+/// `describe_*` macros, and a manifest describe it.
+///
+/// See [`MetricsRegistry::set_manifest_descriptions`]. The losing
+/// description still fills in a missing unit or docs link.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ManifestPolicy {
+    /// The code's description is shown.
+    #[default]
+    CodeWins,
+    /// The manifest's description is shown.
+    ManifestWins,
+}
+
+#[derive(Default)]
+struct ManifestDescriptions {
+    descriptions: HashMap<DescriptionKey, MetricDescription>,
+    policy: ManifestPolicy,
+}
+
+/// Another name for a metric whose name can't be changed, e.g. because it
+/// is recorded by a third-party crate.
+///
+/// See [`MetricsRegistry::add_alias`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricAlias {
+    /// Shown instead of the original name in search results, the namespace
+    /// tree, and plot titles.
+    pub name: String,
+    /// When `Some`, replaces the unit from the metric's description.
+    pub unit: Option<MetricUnit>,
+    /// When `Some`, replaces the text of the metric's description.
+    pub description: Option<String>,
+}
+
+impl MetricAlias {
+    /// An alias that only changes the name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            unit: None,
+            description: None,
+        }
+    }
+
+    /// See [`Self::unit`].
+    pub fn with_unit(mut self, unit: impl Into<MetricUnit>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// See [`Self::description`].
+    pub fn with_description(mut self, text: impl Into<String>) -> Self {
+        self.description = Some(text.into());
+        self
+    }
+}
+
+/// Split a trailing `[deprecated]` marker, in any case, off `text`.
+fn split_deprecated_marker(text: &str) -> (&str, bool) {
+    let Some((rest, marker)) = split_unit_suffix(text) else {
+        return (text, false);
+    };
+    if marker.eq_ignore_ascii_case("deprecated") {
+        (rest, true)
+    } else {
+        (text, false)
+    }
+}
+
+/// Split `"text [unit]"` into `("text", "unit")`.
+fn split_unit_suffix(text: &str) -> Option<(&str, &str)> {
+    let rest = text.trim_end().strip_suffix(']')?;
+    let open = rest.rfind('[')?;
+    let unit = rest[open + 1..].trim();
+    if unit.is_empty() || unit.contains(['[', ']']) {
+        return None;
+    }
+    Some((rest[..open].trim_end(), unit))
+}
+
+/// The unit of a metric, as displayed in search results and on plot axes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MetricUnit {
+    /// One of the units known to the `metrics` crate.
+    Standard(Unit),
+    /// Any other unit, like "entities" or "voxels/frame".
+    Custom(String),
+}
+
+impl MetricUnit {
+    /// The abbreviated text for this unit.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Standard(unit) => unit_str(*unit),
+            Self::Custom(unit) => unit,
+        }
+    }
+}
+
+impl From<Unit> for MetricUnit {
+    fn from(unit: Unit) -> Self {
+        Self::Standard(unit)
+    }
+}
+
+impl std::fmt::Display for MetricUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl Inner {
@@ -44,114 +719,1348 @@ impl Inner {
         Self {
             registry: Registry::atomic(),
             descriptions: RwLock::new(Default::default()),
+            manifest: RwLock::new(Default::default()),
+            custom_units: RwLock::new(Default::default()),
+            extras: RwLock::new(Default::default()),
+            aliases: RwLock::new(Default::default()),
+            activity: Mutex::new(Default::default()),
+            histogram_subscriptions: Mutex::new(Default::default()),
+            hot_histogram_config: RwLock::new(Default::default()),
+            envelope_names: RwLock::new(Default::default()),
+            tracks_envelopes: AtomicBool::new(false),
+            envelopes: Mutex::new(Default::default()),
+            histogram_loads: Mutex::new(Default::default()),
+            cardinality_config: RwLock::new(Default::default()),
+            cardinality: Mutex::new(Default::default()),
+            retention: RwLock::new(None),
+            evicted: AtomicU64::new(0),
+            generation: Default::default(),
+            frame_samples: Mutex::new(Default::default()),
+            frame_gauges: Mutex::new(Default::default()),
+            unit_suffixes: RwLock::new(None),
+        }
+    }
+
+    fn get_or_create_counter(&self, key: &metrics::Key) -> Arc<AtomicU64> {
+        self.get_or_create(
+            MetricKind::Counter,
+            key,
+            |key| self.registry.get_counter(key),
+            |key| self.registry.get_or_create_counter(key, Arc::clone),
+        )
+    }
+
+    fn get_or_create_gauge(&self, key: &metrics::Key) -> Arc<AtomicU64> {
+        self.get_or_create(
+            MetricKind::Gauge,
+            key,
+            |key| self.registry.get_gauge(key),
+            |key| self.registry.get_or_create_gauge(key, Arc::clone),
+        )
+    }
+
+    fn get_or_create_histogram(&self, key: &metrics::Key) -> Arc<AtomicBucket<f64>> {
+        self.get_or_create(
+            MetricKind::Histogram,
+            key,
+            |key| self.registry.get_histogram(key),
+            |key| self.registry.get_or_create_histogram(key, Arc::clone),
+        )
+    }
+
+    // Looking the key up first keeps the generation and label cardinality
+    // unchanged when an existing metric is registered again, which is the
+    // common case. New keys are stored retained, so the keys visited in the
+    // registry, and everything cloned from them, share their name and
+    // labels.
+    fn get_or_create<T>(
+        &self,
+        kind: MetricKind,
+        key: &metrics::Key,
+        get: impl Fn(&metrics::Key) -> Option<T>,
+        create: impl FnOnce(&metrics::Key) -> T,
+    ) -> T {
+        if let Some(metric) = get(key) {
+            return metric;
+        }
+        let overflow = self.count_label_combination(kind, key);
+        let key = match &overflow {
+            Some(overflow) => {
+                if let Some(metric) = get(overflow) {
+                    return metric;
+                }
+                overflow
+            }
+            None => key,
+        };
+        self.generation.changed(kind);
+        create(&key.to_retained())
+    }
+
+    /// Count a new label combination of `key`'s metric, warning when it
+    /// exceeds the [`CardinalityConfig`] limit. Returns the overflow key to
+    /// register instead, if enabled.
+    ///
+    /// Registering the same key from several threads at once may count it
+    /// more than once.
+    fn count_label_combination(
+        &self,
+        kind: MetricKind,
+        key: &metrics::Key,
+    ) -> Option<metrics::Key> {
+        let config = self.cardinality_config.read().unwrap();
+        let limit = config.limit_for(key.name());
+        let mut cardinality = self.cardinality.lock().unwrap();
+        let description_key = DescriptionKey {
+            name: key.name_shared(),
+            kind,
+        };
+        let metric = cardinality.entry(description_key).or_default();
+        let overflow = config.overflow && metric.combinations >= limit;
+        if overflow {
+            metric.overflowed += 1;
+        } else {
+            metric.combinations += 1;
+        }
+        if metric.combinations <= limit && !overflow {
+            return None;
+        }
+
+        if metric.examples.len() == CARDINALITY_EXAMPLES {
+            metric.examples.pop_front();
+        }
+        metric.examples.push_back(key.clone());
+        let now = Instant::now();
+        let recently_warned = metric
+            .last_warning
+            .is_some_and(|last| now - last < CARDINALITY_WARNING_PERIOD);
+        if !recently_warned {
+            metric.last_warning = Some(now);
+            let examples: Vec<_> = metric
+                .examples
+                .iter()
+                .map(|key| MetricKey::new(key.clone(), kind).name_with_labels())
+                .collect();
+            let action = if overflow {
+                "New combinations are recorded with label=\"<overflow>\""
+            } else {
+                "Avoid labels with unbounded values, or raise its limit with \
+                 CardinalityConfig::with_override"
+            };
+            warn!(
+                "{} {} has more than {limit} label combinations, e.g. {}. {action}",
+                metric_kind_str(kind),
+                key.name(),
+                examples.join(", "),
+            );
+        }
+        overflow.then(|| {
+            let (label, value) = OVERFLOW_LABEL;
+            metrics::Key::from_parts(key.name_shared(), vec![metrics::Label::new(label, value)])
+        })
+    }
+}
+
+impl MetricsRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner::new()),
+        }
+    }
+
+    /// The counter of `key`, registered if it wasn't yet.
+    ///
+    /// Labels given in a different order are the same metric, since
+    /// [`metrics::Key`]s compare and hash equal regardless of their order:
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use std::sync::{atomic::Ordering, Arc};
+    /// use metrics::{Key, Level, Metadata, Recorder};
+    ///
+    /// let registry = MetricsRegistry::new();
+    /// let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    /// let ab = Key::from_parts("hits", &[("a", "1"), ("b", "2")]);
+    /// let ba = Key::from_parts("hits", &[("b", "2"), ("a", "1")]);
+    /// registry.register_counter(&ab, &metadata).increment(1);
+    /// registry.register_counter(&ba, &metadata).increment(2);
+    ///
+    /// let counter = registry.get_or_create_counter(&ba);
+    /// assert_eq!(counter.load(Ordering::Relaxed), 3);
+    /// assert!(Arc::ptr_eq(&counter, &registry.get_or_create_counter(&ab)));
+    /// assert_eq!(registry.fuzzy_search_by_name("hits").len(), 1);
+    /// ```
+    pub fn get_or_create_counter(&self, key: &metrics::Key) -> Arc<AtomicU64> {
+        self.inner.get_or_create_counter(key)
+    }
+    #[allow(missing_docs)]
+    pub fn get_or_create_gauge(&self, key: &metrics::Key) -> Arc<AtomicU64> {
+        self.inner.get_or_create_gauge(key)
+    }
+    #[allow(missing_docs)]
+    pub fn get_or_create_histogram(&self, key: &metrics::Key) -> Arc<AtomicBucket<f64>> {
+        self.inner.get_or_create_histogram(key)
+    }
+
+    /// The current generation of the registry, to compare with one taken
+    /// earlier to find out whether metrics were registered, removed or
+    /// described since.
+    ///
+    /// This only reads a few atomics, so it's cheap enough to call every
+    /// frame. Registering a metric that already exists doesn't change it.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// let registry = MetricsRegistry::new();
+    /// let key = metrics::Key::from_static_name("enemies/spawned");
+    /// registry.get_or_create_counter(&key);
+    /// let seen = registry.generation();
+    ///
+    /// registry.get_or_create_counter(&key);
+    /// assert_eq!(registry.generation(), seen);
+    ///
+    /// registry.get_or_create_counter(&metrics::Key::from_static_name("enemies/despawned"));
+    /// assert!(registry.generation().counters > seen.counters);
+    ///
+    /// // Removing a metric changes it too, so caches drop the metric.
+    /// let queued = metrics::Key::from_static_name("jobs/queued");
+    /// registry.get_or_create_gauge(&queued);
+    /// let seen = registry.generation();
+    /// assert!(registry.remove_gauge(&queued));
+    /// assert!(registry.generation().gauges > seen.gauges);
+    /// ```
+    pub fn generation(&self) -> RegistryGeneration {
+        self.inner.generation.load()
+    }
+
+    /// Record `value` for the metric `key` on behalf of an earlier `frame`,
+    /// e.g. from an async task that measured work done in that frame.
+    ///
+    /// The metric is updated right away, like with the `metrics` macros: a
+    /// counter is incremented by `value`, truncated to an integer, and a gauge
+    /// is set to it, unless a value was already recorded for a later frame.
+    /// Dashboard plots then move the value to the samples of
+    /// `frame`: a counter's increment is counted from `frame` on, and a
+    /// gauge's value replaces the one sampled in `frame`. Values for frames
+    /// that are no longer in a plot's window are counted as late samples
+    /// dropped.
+    ///
+    /// Histograms have no time axis, so their samples are recorded as usual.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::{FrameId, MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use metrics::Key;
+    /// # use std::sync::atomic::Ordering;
+    /// let registry = MetricsRegistry::new();
+    /// let key = MetricKey::new(Key::from_static_name("loaded_chunks"), MetricKind::Gauge);
+    /// registry.record_for_frame(FrameId(8), &key, 30.0);
+    /// // A task for an earlier frame finishes last.
+    /// registry.record_for_frame(FrameId(5), &key, 10.0);
+    ///
+    /// let gauge = registry.get_gauge(&key.key).unwrap();
+    /// assert_eq!(f64::from_bits(gauge.load(Ordering::Relaxed)), 30.0);
+    /// assert_eq!(registry.take_frame_samples().len(), 2);
+    /// ```
+    pub fn record_for_frame(&self, frame: FrameId, key: &MetricKey, value: f64) {
+        match key.kind {
+            MetricKind::Counter => {
+                self.get_or_create_counter(&key.key)
+                    .fetch_add(value as u64, Ordering::Relaxed);
+            }
+            MetricKind::Gauge => {
+                let mut newest = self.inner.frame_gauges.lock().unwrap();
+                let newest = newest.entry(key.key.clone()).or_insert(frame);
+                if frame >= *newest {
+                    *newest = frame;
+                    self.get_or_create_gauge(&key.key)
+                        .store(value.to_bits(), Ordering::Relaxed);
+                }
+            }
+            MetricKind::Histogram => {
+                self.get_or_create_histogram(&key.key).push(value);
+                return;
+            }
+        }
+        let mut samples = self.inner.frame_samples.lock().unwrap();
+        if samples.len() >= MAX_PENDING_FRAME_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(FrameSample {
+            frame,
+            key: key.clone(),
+            value,
+        });
+    }
+
+    /// Take all values recorded with [`Self::record_for_frame`] since the
+    /// last call, in the order they were recorded.
+    pub fn take_frame_samples(&self) -> Vec<FrameSample> {
+        let mut samples = self.inner.frame_samples.lock().unwrap();
+        samples.drain(..).collect()
+    }
+
+    /// Track the lowest and highest values of the gauges named `name`
+    /// within each frame, so that a value that spikes and returns before
+    /// it's sampled still shows up in plots, as the gauge's envelope.
+    ///
+    /// Only gauges registered through the [`Recorder`] API afterwards, e.g.
+    /// with the `metrics` macros, are tracked: their handles update two
+    /// extra atomics with every write. Writes to the atomic returned by
+    /// [`Self::get_or_create_gauge`], e.g. through a [`CachedGauge`], are
+    /// only seen when the envelope is taken.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use metrics::Key;
+    /// let registry = MetricsRegistry::new();
+    /// registry.track_gauge_envelope("queue/len");
+    /// let key = Key::from_static_name("queue/len");
+    /// let queue = metrics::with_local_recorder(&registry, || metrics::gauge!("queue/len"));
+    ///
+    /// queue.increment(10.0);
+    /// queue.decrement(8.0);
+    /// assert_eq!(registry.take_gauge_envelopes(), vec![(key.clone(), (0.0, 10.0))]);
+    /// // Each envelope starts at the value the previous one ended with.
+    /// queue.set(1.0);
+    /// assert_eq!(registry.take_gauge_envelopes(), vec![(key, (1.0, 2.0))]);
+    /// ```
+    pub fn track_gauge_envelope(&self, name: impl Into<String>) {
+        self.inner
+            .envelope_names
+            .write()
+            .unwrap()
+            .insert(name.into());
+        self.inner.tracks_envelopes.store(true, Ordering::Release);
+    }
+
+    /// Whether gauges named `name` are tracked with
+    /// [`Self::track_gauge_envelope`].
+    pub fn tracks_gauge_envelope(&self, name: &str) -> bool {
+        self.inner.envelope_names.read().unwrap().contains(name)
+    }
+
+    /// Take the lowest and highest values of the
+    /// [tracked gauges](Self::track_gauge_envelope) since the last call,
+    /// sorted by key. The [`FrameMetricsSnapshot`](crate::FrameMetricsSnapshot)
+    /// takes them every frame.
+    pub fn take_gauge_envelopes(&self) -> Vec<(metrics::Key, (f64, f64))> {
+        let envelopes = self.inner.envelopes.lock().unwrap();
+        let mut taken: Vec<_> = envelopes
+            .iter()
+            .map(|(key, envelope)| (key.clone(), envelope.take()))
+            .collect();
+        taken.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        taken
+    }
+
+    /// A handle for writing the counter `name` with `labels` without looking
+    /// it up each time. The counter is created if it doesn't exist.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// let registry = MetricsRegistry::new();
+    /// let spawned = registry.cached_counter("enemies/spawned", &[("team", "red")]);
+    /// spawned.increment(1);
+    /// ```
+    pub fn cached_counter(&self, name: &str, labels: &[(&str, &str)]) -> CachedCounter {
+        let key = key_from_parts(name, labels);
+        let atomic = self.get_or_create_counter(&key);
+        CachedCounter::new(key, atomic)
+    }
+    /// Like [`Self::cached_counter`], for a gauge.
+    pub fn cached_gauge(&self, name: &str, labels: &[(&str, &str)]) -> CachedGauge {
+        let key = key_from_parts(name, labels);
+        let atomic = self.get_or_create_gauge(&key);
+        CachedGauge::new(key, atomic)
+    }
+    /// Like [`Self::cached_counter`], for a histogram.
+    pub fn cached_histogram(&self, name: &str, labels: &[(&str, &str)]) -> CachedHistogram {
+        let key = key_from_parts(name, labels);
+        let bucket = self.get_or_create_histogram(&key);
+        CachedHistogram::new(key, bucket)
+    }
+
+    /// Remove a gauge from the registry. Returns false if it didn't exist.
+    ///
+    /// Handles to the gauge, like a [`CachedGauge`], keep working, but
+    /// their writes are no longer seen by the registry.
+    pub fn remove_gauge(&self, key: &metrics::Key) -> bool {
+        self.remove(&MetricKey::new(key.clone(), MetricKind::Gauge))
+    }
+
+    /// Like [`Self::remove_gauge`], for any kind of metric.
+    fn remove(&self, key: &MetricKey) -> bool {
+        let MetricKey { key, kind } = key;
+        let reg = &self.inner.registry;
+        let removed = match kind {
+            MetricKind::Counter => reg.delete_counter(key),
+            MetricKind::Gauge => {
+                self.inner.envelopes.lock().unwrap().remove(key);
+                self.inner.frame_gauges.lock().unwrap().remove(key);
+                reg.delete_gauge(key)
+            }
+            MetricKind::Histogram => {
+                self.inner.histogram_loads.lock().unwrap().remove(key);
+                reg.delete_histogram(key)
+            }
+        };
+        if removed {
+            self.inner.generation.changed(*kind);
+            let description_key = DescriptionKey {
+                name: key.name_shared(),
+                kind: *kind,
+            };
+            let mut cardinality = self.inner.cardinality.lock().unwrap();
+            if let Some(metric) = cardinality.get_mut(&description_key) {
+                metric.combinations = metric.combinations.saturating_sub(1);
+            }
+        }
+        removed
+    }
+
+    /// Evict idle metrics with the [`RetentionPolicy`], if any.
+    pub fn set_retention_policy(&self, policy: Option<RetentionPolicy>) {
+        *self.inner.retention.write().unwrap() = policy;
+    }
+
+    #[allow(missing_docs)]
+    pub fn retention_policy(&self) -> Option<RetentionPolicy> {
+        self.inner.retention.read().unwrap().clone()
+    }
+
+    /// How many metrics were evicted by the [`RetentionPolicy`] since the
+    /// registry was created.
+    pub fn evicted_count(&self) -> u64 {
+        self.inner.evicted.load(Ordering::Relaxed)
+    }
+
+    /// Remove metrics that weren't [updated](Self::last_updated) for the
+    /// [`RetentionPolicy::max_idle`], unless they're protected by the policy
+    /// or `keep` returns true for them. Returns the evicted metrics.
+    ///
+    /// Idle time is measured by [`Self::sample_activity`], so this doesn't
+    /// scan the registry itself. Does nothing without a retention policy.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::retention::RetentionPolicy;
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// use metrics::{counter, Key};
+    /// use std::time::Duration;
+    ///
+    /// let registry = MetricsRegistry::new();
+    /// let policy = RetentionPolicy::new(Duration::from_millis(50)).protect("frame_*");
+    /// registry.set_retention_policy(Some(policy));
+    /// let _guard = registry.install_as_local();
+    /// let key = Key::from_static_name("enemy_42/health");
+    /// counter!("enemy_42/health").increment(1);
+    /// counter!("frame_count").increment(1);
+    /// registry.sample_activity();
+    ///
+    /// // The counter is idle, but not for long enough yet.
+    /// assert!(registry.evict_idle(|_| false).is_empty());
+    ///
+    /// std::thread::sleep(Duration::from_millis(60));
+    /// registry.sample_activity();
+    /// let evicted = registry.evict_idle(|_| false);
+    /// assert_eq!(evicted.len(), 1);
+    /// assert_eq!(evicted[0].key, MetricKey::new(key.clone(), MetricKind::Counter));
+    /// assert_eq!(registry.evicted_count(), 1);
+    /// assert!(registry.get_counter(&key).is_none());
+    ///
+    /// // Recording the metric again registers it from scratch.
+    /// counter!("enemy_42/health").increment(1);
+    /// let counter = registry.get_counter(&key).unwrap();
+    /// assert_eq!(counter.load(std::sync::atomic::Ordering::Relaxed), 1);
+    /// ```
+    pub fn evict_idle(&self, keep: impl Fn(&MetricKey) -> bool) -> Vec<MetricEvicted> {
+        let retention = self.inner.retention.read().unwrap();
+        let Some(policy) = retention.as_ref() else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        let mut activity = self.inner.activity.lock().unwrap();
+        let mut evicted = Vec::new();
+        activity.retain(|key, a| {
+            let idle = now - a.last_updated.unwrap_or(a.first_seen);
+            if idle < policy.max_idle || policy.protects(key.key.name()) || keep(key) {
+                return true;
+            }
+            evicted.push(MetricEvicted {
+                key: key.clone(),
+                idle,
+            });
+            false
+        });
+        drop(activity);
+        evicted.retain(|e| self.remove(&e.key));
+        self.inner
+            .evicted
+            .fetch_add(evicted.len() as u64, Ordering::Relaxed);
+        evicted
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_counter(&self, key: &metrics::Key) -> Option<Arc<AtomicU64>> {
+        self.inner.registry.get_counter(key)
+    }
+    #[allow(missing_docs)]
+    pub fn get_gauge(&self, key: &metrics::Key) -> Option<Arc<AtomicU64>> {
+        self.inner.registry.get_gauge(key)
+    }
+    #[allow(missing_docs)]
+    pub fn get_histogram(&self, key: &metrics::Key) -> Option<Arc<AtomicBucket<f64>>> {
+        self.inner.registry.get_histogram(key)
+    }
+    /// The current value of the metric `key`, without consuming anything:
+    /// a counter's total, a gauge's value, or how many samples a histogram
+    /// holds, i.e. those recorded since its buckets were last cleared.
+    ///
+    /// Only reads atomics, so it's cheap enough to call for each visible row
+    /// of a list every frame.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use metrics::Key;
+    /// let registry = MetricsRegistry::new();
+    /// let key = Key::from_static_name("enemies/damage");
+    /// let histogram = MetricKey::new(key.clone(), MetricKind::Histogram);
+    /// assert_eq!(registry.peek_value(&histogram), None);
+    ///
+    /// let bucket = registry.get_or_create_histogram(&key);
+    /// bucket.push(12.0);
+    /// bucket.push(30.0);
+    /// assert_eq!(registry.peek_value(&histogram), Some(2.0));
+    /// // Peeking doesn't drain the samples.
+    /// assert_eq!(registry.peek_value(&histogram), Some(2.0));
+    /// ```
+    pub fn peek_value(&self, key: &MetricKey) -> Option<f64> {
+        match key.kind {
+            MetricKind::Counter => {
+                let counter = self.get_counter(&key.key)?;
+                Some(counter.load(Ordering::Relaxed) as f64)
+            }
+            MetricKind::Gauge => {
+                let gauge = self.get_gauge(&key.key)?;
+                Some(f64::from_bits(gauge.load(Ordering::Relaxed)))
+            }
+            MetricKind::Histogram => {
+                let histogram = self.get_histogram(&key.key)?;
+                let mut count = 0;
+                histogram.data_with(|block| count += block.len());
+                Some(count as f64)
+            }
         }
     }
-}
 
-impl MetricsRegistry {
-    /// Create an empty registry.
-    pub fn new() -> Self {
-        Self {
-            inner: Arc::new(Inner::new()),
+    #[allow(missing_docs)]
+    pub fn get_description(&self, key: &DescriptionKey) -> Option<MetricDescription> {
+        self.descriptions_ref().merge(key)
+    }
+
+    /// The search result for `key`, whether or not it is registered.
+    pub fn get_search_result(&self, key: MetricKey) -> SearchResult {
+        self.descriptions_ref().search_result(key)
+    }
+
+    fn descriptions_ref(&self) -> DescriptionsRef<'_> {
+        DescriptionsRef {
+            descriptions: self.inner.descriptions.read().unwrap(),
+            manifest: self.inner.manifest.read().unwrap(),
+            custom_units: self.inner.custom_units.read().unwrap(),
+            extras: self.inner.extras.read().unwrap(),
+            aliases: self.inner.aliases.read().unwrap(),
+            unit_suffixes: self.inner.unit_suffixes.read().unwrap(),
         }
     }
 
-    #[allow(missing_docs)]
-    pub fn get_or_create_counter(&self, key: &metrics::Key) -> Arc<AtomicU64> {
-        self.inner.registry.get_or_create_counter(key, Arc::clone)
+    /// Show metrics named `original_name` as `display_name`.
+    ///
+    /// Searches match both names. The metric is still recorded and stored
+    /// under its original name.
+    pub fn add_alias(&self, original_name: impl Into<KeyName>, display_name: impl Into<String>) {
+        self.set_alias(original_name, MetricAlias::new(display_name));
     }
-    #[allow(missing_docs)]
-    pub fn get_or_create_gauge(&self, key: &metrics::Key) -> Arc<AtomicU64> {
-        self.inner.registry.get_or_create_gauge(key, Arc::clone)
+
+    /// Like [`Self::add_alias`], but the alias can also override the unit and
+    /// description.
+    ///
+    /// Replaces any previous alias of `original_name`.
+    pub fn set_alias(&self, original_name: impl Into<KeyName>, alias: MetricAlias) {
+        let mut aliases = self.inner.aliases.write().unwrap();
+        aliases.insert(original_name.into(), alias);
+        self.inner.generation.described();
     }
+
     #[allow(missing_docs)]
-    pub fn get_or_create_histogram(&self, key: &metrics::Key) -> Arc<AtomicBucket<f64>> {
-        self.inner.registry.get_or_create_histogram(key, Arc::clone)
+    pub fn remove_alias(&self, original_name: &str) -> Option<MetricAlias> {
+        let mut aliases = self.inner.aliases.write().unwrap();
+        let removed = aliases.remove(&KeyName::from(original_name.to_owned()));
+        if removed.is_some() {
+            self.inner.generation.described();
+        }
+        removed
     }
-    #[allow(missing_docs)]
-    pub fn get_description(&self, key: &DescriptionKey) -> Option<MetricDescription> {
-        self.inner.descriptions.read().unwrap().get(key).cloned()
+
+    /// The alias of metrics named `original_name`.
+    pub fn get_alias(&self, original_name: &str) -> Option<MetricAlias> {
+        let aliases = self.inner.aliases.read().unwrap();
+        aliases
+            .get(&KeyName::from(original_name.to_owned()))
+            .cloned()
+    }
+
+    /// All aliases by original name, sorted by original name.
+    pub fn aliases(&self) -> Vec<(KeyName, MetricAlias)> {
+        let aliases = self.inner.aliases.read().unwrap();
+        let mut aliases: Vec<_> = aliases
+            .iter()
+            .map(|(k, a)| (k.clone(), a.clone()))
+            .collect();
+        aliases.sort_unstable_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        aliases
+    }
+
+    /// Like [`MetricKey::title`], but uses the metric's alias, if any.
+    pub fn title(&self, key: &MetricKey, n_duplicates: usize) -> String {
+        let alias = self.get_alias(key.key.name());
+        key.title(alias.as_ref().map(|a| a.name.as_str()), n_duplicates)
     }
 
-    /// Search the registry for metrics whose name matches `input`.
+    /// Display `unit` for the metric with this name and kind, taking
+    /// precedence over the unit from its description.
+    pub fn set_custom_unit(
+        &self,
+        key_name: impl Into<KeyName>,
+        kind: MetricKind,
+        unit: impl Into<String>,
+    ) {
+        let key = DescriptionKey {
+            name: key_name.into(),
+            kind,
+        };
+        let mut custom_units = self.inner.custom_units.write().unwrap();
+        custom_units.insert(key, MetricUnit::Custom(unit.into()));
+        self.inner.generation.described();
+    }
+
+    /// Attach `extras` to the description of the metric with this name and
+    /// kind, replacing any previous extras.
+    ///
+    /// This works whether or not the metric is described, or even registered.
+    /// Empty `extras` are removed.
+    pub fn set_description_extras(
+        &self,
+        key_name: impl Into<KeyName>,
+        kind: MetricKind,
+        extras: DescriptionExtras,
+    ) {
+        let key = DescriptionKey {
+            name: key_name.into(),
+            kind,
+        };
+        let mut all_extras = self.inner.extras.write().unwrap();
+        if extras.is_empty() {
+            all_extras.remove(&key);
+        } else {
+            all_extras.insert(key, extras);
+        }
+        self.inner.generation.described();
+    }
+
+    /// All extras set with [`Self::set_description_extras`], sorted by name
+    /// and kind.
+    pub fn description_extras(&self) -> Vec<(DescriptionKey, DescriptionExtras)> {
+        let extras = self.inner.extras.read().unwrap();
+        let mut extras: Vec<_> = extras.iter().map(|(k, e)| (k.clone(), e.clone())).collect();
+        extras.sort_unstable_by(|(a, _), (b, _)| a.cmp_name_kind(b));
+        extras
+    }
+
+    /// Use this registry as the recorder for the current thread until the
+    /// returned guard is dropped, taking precedence over the global recorder.
+    ///
+    /// This lets tests record into their own registry without fighting over
+    /// [`set_global_recorder`](metrics::set_global_recorder). Note that
+    /// metrics recorded from other threads, e.g. by systems on a
+    /// multi-threaded executor, still go to the global recorder.
+    ///
+    /// To run a Bevy system this way, see
+    /// [`with_scoped_recorder`](crate::with_scoped_recorder).
+    pub fn install_as_local(&self) -> ScopedRecorderGuard<'_> {
+        ScopedRecorderGuard::new(self)
+    }
+
+    #[cfg(any(feature = "render_metrics", feature = "otlp", feature = "statsd"))]
+    pub(crate) fn storage(&self) -> &Registry<metrics::Key, AtomicStorage> {
+        &self.inner.registry
+    }
+
+    #[cfg(feature = "render_metrics")]
+    pub(crate) fn descriptions(&self) -> Vec<(DescriptionKey, MetricDescription)> {
+        let descriptions = self.inner.descriptions.read().unwrap();
+        descriptions
+            .iter()
+            .map(|(k, d)| (k.clone(), d.clone()))
+            .collect()
+    }
+
+    /// Search the registry for metrics whose name or alias matches `input`.
     ///
     /// Empty `input` will match everything.
     ///
-    /// Results are not returned in any particular order.
+    /// Results are not returned in any particular order. This collects
+    /// [`Self::search_iter`].
     pub fn fuzzy_search_by_name(&self, input: &str) -> Vec<SearchResult> {
-        let mut results = Vec::new();
+        self.search_iter(input).collect()
+    }
+
+    /// Like [`Self::fuzzy_search_by_name`], but only the keys of the
+    /// matching metrics are collected up front. Each [`SearchResult`], with
+    /// its description, is built when the iterator reaches it, so a UI that
+    /// only shows a few results can stop early.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// let registry = MetricsRegistry::new();
+    /// for i in 0..1000 {
+    ///     registry.get_or_create_counter(&metrics::Key::from_name(format!("enemy_{i}")));
+    /// }
+    /// let results = registry.search_iter("enemy");
+    /// assert_eq!(results.len(), 1000);
+    /// let first: Vec<_> = results.take(20).collect();
+    /// assert_eq!(first.len(), 20);
+    /// ```
+    pub fn search_iter(&self, input: &str) -> SearchIter<'_> {
+        let mut keys = Vec::new();
         let matcher = SkimMatcherV2::default();
         let reg = &self.inner.registry;
-        let descriptions = self.inner.descriptions.read().unwrap();
-        reg.visit_counters(|key, _| {
-            if matcher.fuzzy_match(key.name(), input).is_some() {
-                results.push(make_search_result(MetricKind::Counter, key, &descriptions));
-            }
-        });
-        reg.visit_gauges(|key, _| {
-            if matcher.fuzzy_match(key.name(), input).is_some() {
-                results.push(make_search_result(MetricKind::Gauge, key, &descriptions));
-            }
-        });
-        reg.visit_histograms(|key, _| {
-            if matcher.fuzzy_match(key.name(), input).is_some() {
-                results.push(make_search_result(
-                    MetricKind::Histogram,
-                    key,
-                    &descriptions,
-                ));
+        let aliases = self.inner.aliases.read().unwrap();
+        let is_match = |key: &metrics::Key| {
+            input.is_empty()
+                || matcher.fuzzy_match(key.name(), input).is_some()
+                || aliases
+                    .get(key.name())
+                    .is_some_and(|a| matcher.fuzzy_match(&a.name, input).is_some())
+        };
+        let mut visit = |kind, key: &metrics::Key| {
+            if is_match(key) {
+                keys.push(MetricKey::new(key.clone(), kind));
             }
-        });
-        results
+        };
+        reg.visit_counters(|key, _| visit(MetricKind::Counter, key));
+        reg.visit_gauges(|key, _| visit(MetricKind::Gauge, key));
+        reg.visit_histograms(|key, _| visit(MetricKind::Histogram, key));
+        SearchIter {
+            registry: self,
+            keys: keys.into_iter(),
+        }
     }
 
     /// Get a search result for every registered metric.
     pub fn all_metrics(&self) -> Vec<SearchResult> {
-        let mut results = Vec::new();
+        self.search_iter("").collect()
+    }
+
+    /// The keys of all registered metrics, without their descriptions.
+    pub(crate) fn all_keys(&self) -> Vec<MetricKey> {
+        let mut keys = Vec::new();
+        let reg = &self.inner.registry;
+        reg.visit_counters(|key, _| keys.push(MetricKey::new(key.clone(), MetricKind::Counter)));
+        reg.visit_gauges(|key, _| keys.push(MetricKey::new(key.clone(), MetricKind::Gauge)));
+        reg.visit_histograms(|key, _| {
+            keys.push(MetricKey::new(key.clone(), MetricKind::Histogram))
+        });
+        keys
+    }
+
+    /// Compare the described metrics with the registered metrics.
+    ///
+    /// Only descriptions from the `describe_*` macros,
+    /// [`Self::describe_many`] and [manifests](Self::set_manifest_descriptions)
+    /// count, not aliases or custom units.
+    pub fn audit(&self) -> MetricsAudit {
+        let mut registered: HashMap<DescriptionKey, usize> = HashMap::default();
+        let mut observe = |kind, key: &metrics::Key| {
+            let key = DescriptionKey {
+                name: KeyName::from(key.name().to_owned()),
+                kind,
+            };
+            *registered.entry(key).or_default() += 1;
+        };
+        let reg = &self.inner.registry;
+        reg.visit_counters(|key, _| observe(MetricKind::Counter, key));
+        reg.visit_gauges(|key, _| observe(MetricKind::Gauge, key));
+        reg.visit_histograms(|key, _| observe(MetricKind::Histogram, key));
+
+        let descriptions = self.inner.descriptions.read().unwrap();
+        let manifest = self.inner.manifest.read().unwrap();
+        let described = |key: &DescriptionKey| {
+            descriptions.contains_key(key) || manifest.descriptions.contains_key(key)
+        };
+        let mut unregistered: Vec<_> = descriptions
+            .keys()
+            .chain(manifest.descriptions.keys())
+            .filter(|key| !registered.contains_key(*key))
+            .cloned()
+            .collect();
+        let mut undescribed: Vec<_> = registered
+            .into_iter()
+            .filter(|(key, _)| !described(key))
+            .collect();
+        unregistered.sort_unstable_by(DescriptionKey::cmp_name_kind);
+        unregistered.dedup();
+        undescribed.sort_unstable_by(|(a, _), (b, _)| a.cmp_name_kind(b));
+        MetricsAudit {
+            unregistered,
+            undescribed,
+        }
+    }
+
+    /// Metrics described in code, but without any registered metric of the
+    /// same name and kind. Unlike [`Self::audit`], manifests don't count.
+    pub(crate) fn described_only(&self) -> Vec<DescriptionKey> {
+        let mut registered = HashSet::new();
+        let mut observe = |kind, key: &metrics::Key| {
+            registered.insert(DescriptionKey {
+                name: KeyName::from(key.name().to_owned()),
+                kind,
+            });
+        };
         let reg = &self.inner.registry;
+        reg.visit_counters(|key, _| observe(MetricKind::Counter, key));
+        reg.visit_gauges(|key, _| observe(MetricKind::Gauge, key));
+        reg.visit_histograms(|key, _| observe(MetricKind::Histogram, key));
+
         let descriptions = self.inner.descriptions.read().unwrap();
-        reg.visit_counters(|key, _| {
-            results.push(make_search_result(MetricKind::Counter, key, &descriptions));
+        let mut described_only: Vec<_> = descriptions
+            .keys()
+            .filter(|key| !registered.contains(*key))
+            .cloned()
+            .collect();
+        described_only.sort_unstable_by(DescriptionKey::cmp_name_kind);
+        described_only
+    }
+
+    pub(crate) fn add_description_if_missing(
+        &self,
+        key: DescriptionKey,
+        description: MetricDescription,
+    ) {
+        let mut descriptions = self.inner.descriptions.write().unwrap();
+        if let Entry::Vacant(entry) = descriptions.entry(key) {
+            entry.insert(description);
+            self.inner.generation.described();
+        }
+    }
+
+    /// Describe metrics from a manifest, replacing the previous manifest
+    /// descriptions.
+    ///
+    /// Unlike the `describe_*` macros, these are kept apart from the code's
+    /// descriptions, so `policy` decides between the two regardless of which
+    /// came first. Like all descriptions, they apply to metrics registered
+    /// later too. With the `manifest` feature, `manifest::MetricManifest`
+    /// loads them from a file.
+    pub fn set_manifest_descriptions(
+        &self,
+        descriptions: impl IntoIterator<Item = (DescriptionKey, MetricDescription)>,
+        policy: ManifestPolicy,
+    ) {
+        let mut manifest = self.inner.manifest.write().unwrap();
+        manifest.descriptions = descriptions.into_iter().collect();
+        manifest.policy = policy;
+        self.inner.generation.described();
+    }
+
+    /// Describe a batch of metrics at once.
+    ///
+    /// Each entry is the name, kind, unit and description of a metric, as
+    /// passed to the `describe_*` macros. Metrics that already have a
+    /// description keep it.
+    pub fn describe_many(&self, metrics: &[(KeyName, MetricKind, Option<Unit>, &str)]) {
+        let mut descriptions = self.inner.descriptions.write().unwrap();
+        for (name, kind, unit, text) in metrics {
+            let key = DescriptionKey {
+                name: name.clone(),
+                kind: *kind,
+            };
+            if let Entry::Vacant(entry) = descriptions.entry(key) {
+                entry.insert(MetricDescription::new(*unit, text.to_string().into()));
+                self.inner.generation.described();
+            }
+        }
+    }
+
+    /// When the value of the metric was last seen changing by
+    /// [`Self::sample_activity`].
+    ///
+    /// Returns `None` if it never changed after it was first sampled.
+    pub fn last_updated(&self, key: &MetricKey) -> Option<Instant> {
+        let activity = self.inner.activity.lock().unwrap();
+        activity.get(key).and_then(|a| a.last_updated)
+    }
+
+    /// Check every metric for changes since the last call, to track when it
+    /// was [last updated](Self::last_updated).
+    ///
+    /// Counters and gauges are updated when their value changes, and
+    /// histograms when they have samples. This scans the whole registry, so
+    /// the [`RegistryPlugin`](crate::RegistryPlugin) only runs it a few times
+    /// per second, before histogram buckets are cleared.
+    pub fn sample_activity(&self) {
+        let now = Instant::now();
+        let mut activity = self.inner.activity.lock().unwrap();
+        // Histograms are cleared every frame, so they only need their
+        // `changed` flag. Counters and gauges compare with the last value.
+        let mut observe = |kind, key: &metrics::Key, bits, changed| {
+            let key = MetricKey::new(key.clone(), kind);
+            match activity.get_mut(&key) {
+                Some(a) => {
+                    if changed || a.last_bits != bits {
+                        a.last_bits = bits;
+                        a.last_updated = Some(now);
+                    }
+                }
+                None => {
+                    activity.insert(
+                        key,
+                        Activity {
+                            last_bits: bits,
+                            first_seen: now,
+                            last_updated: changed.then_some(now),
+                        },
+                    );
+                }
+            }
+        };
+        let reg = &self.inner.registry;
+        reg.visit_counters(|key, c| {
+            observe(MetricKind::Counter, key, c.load(Ordering::Relaxed), false);
         });
-        reg.visit_gauges(|key, _| {
-            results.push(make_search_result(MetricKind::Gauge, key, &descriptions));
+        reg.visit_gauges(|key, g| {
+            observe(MetricKind::Gauge, key, g.load(Ordering::Relaxed), false);
         });
-        reg.visit_histograms(|key, _| {
-            results.push(make_search_result(
-                MetricKind::Histogram,
-                key,
-                &descriptions,
-            ));
+        reg.visit_histograms(|key, h| {
+            observe(MetricKind::Histogram, key, 0, !h.is_empty());
         });
-        results
     }
 
-    fn add_description_if_missing(&self, key: DescriptionKey, description: MetricDescription) {
-        let mut descriptions = self.inner.descriptions.write().unwrap();
-        descriptions.entry(key).or_insert(description);
+    pub(crate) fn sample_activity_system(registry: Res<Self>, mut last: Local<Option<Instant>>) {
+        if last.is_some_and(|last| last.elapsed() < ACTIVITY_SAMPLE_PERIOD) {
+            return;
+        }
+        *last = Some(Instant::now());
+        registry.sample_activity();
+    }
+
+    /// Take all samples of a histogram, leaving its bucket empty.
+    ///
+    /// Returns an empty list if the histogram is not registered. The samples
+    /// are taken from every other consumer, including the dashboard, so
+    /// prefer [`Self::subscribe_histogram`] to read samples alongside them.
+    pub fn drain_histogram(&self, key: &metrics::Key) -> Vec<f64> {
+        let mut samples = Vec::new();
+        if let Some(histogram) = self.get_histogram(key) {
+            histogram.clear_with(|block| samples.extend_from_slice(block));
+        }
+        samples
+    }
+
+    /// Receive the samples of the histogram with `key` every time the atomic
+    /// buckets are cleared, i.e. once per frame unless changed on the
+    /// [`RegistryPlugin`](crate::RegistryPlugin).
+    ///
+    /// Buckets are drained once for all subscribers, into a buffer they
+    /// share. Unlike reading the bucket directly, a subscription never
+    /// misses samples because another consumer read or cleared them first.
+    /// The histogram is created if it doesn't exist.
+    pub fn subscribe_histogram(&self, key: metrics::Key) -> HistogramSubscription {
+        self.get_or_create_histogram(&key);
+        let slot = Arc::new(SubscriptionSlot {
+            key,
+            samples: RwLock::new(Arc::new([])),
+        });
+        let mut subscriptions = self.inner.histogram_subscriptions.lock().unwrap();
+        subscriptions.push(Arc::downgrade(&slot));
+        HistogramSubscription { slot }
     }
 
     /// Clear all atomic buckets used for storing histogram data.
+    ///
+    /// The samples of subscribed histograms are published to their
+    /// [`HistogramSubscription`]s first, and each histogram's samples are
+    /// counted to find [hot histograms](Self::hot_histograms).
     pub fn clear_atomic_buckets(&self) {
+        self.track_histogram_loads();
+        self.publish_histogram_subscriptions();
         self.inner.registry.visit_histograms(|_, h| {
             h.clear();
         });
     }
 
-    pub(crate) fn clear_atomic_buckets_system(registry: Res<Self>) {
+    fn publish_histogram_subscriptions(&self) {
+        let mut subscriptions = self.inner.histogram_subscriptions.lock().unwrap();
+        // Forget dropped subscriptions.
+        subscriptions.retain(|slot| slot.strong_count() > 0);
+        let mut drained: HashMap<metrics::Key, Arc<[f64]>> = HashMap::default();
+        for slot in subscriptions.iter().filter_map(Weak::upgrade) {
+            let samples = drained
+                .entry(slot.key.clone())
+                .or_insert_with(|| self.drain_histogram(&slot.key).into())
+                .clone();
+            *slot.samples.write().unwrap() = samples;
+        }
+    }
+
+    /// Infer the units of metrics without a described unit from the end of
+    /// their names, or stop inferring them with `None`, the default.
+    ///
+    /// Inferred units are used wherever a described one would be, and are
+    /// marked as inferred in the [detailed text](SearchResult::detailed_text).
+    /// A unit from a description, alias or [custom
+    /// unit](Self::set_custom_unit) always takes precedence, even if it's
+    /// added later. The `DashboardPlugin` sets this from the
+    /// [`DashboardConfig`](crate::DashboardConfig).
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricUnit, MetricsRegistry, UnitSuffixes};
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// use metrics::{Key, KeyName, Recorder, Unit};
+    ///
+    /// let registry = MetricsRegistry::new();
+    /// registry.set_unit_suffixes(Some(UnitSuffixes::default()));
+    /// let key = MetricKey::new(Key::from_static_name("asset_load_ms"), MetricKind::Gauge);
+    /// let description = registry.get_search_result(key.clone()).description.unwrap();
+    /// assert_eq!(description.unit, Some(MetricUnit::Standard(Unit::Milliseconds)));
+    /// assert!(description.unit_inferred);
+    ///
+    /// // The metric is actually recorded in seconds.
+    /// let name = KeyName::from_const_str("asset_load_ms");
+    /// registry.describe_gauge(name, Some(Unit::Seconds), "Asset load time".into());
+    /// let description = registry.get_search_result(key).description.unwrap();
+    /// assert_eq!(description.unit, Some(MetricUnit::Standard(Unit::Seconds)));
+    /// assert!(!description.unit_inferred);
+    /// ```
+    pub fn set_unit_suffixes(&self, suffixes: Option<UnitSuffixes>) {
+        let mut current = self.inner.unit_suffixes.write().unwrap();
+        if *current != suffixes {
+            *current = suffixes;
+            self.inner.generation.described();
+        }
+    }
+
+    /// See [`Self::set_unit_suffixes`].
+    pub fn unit_suffixes(&self) -> Option<UnitSuffixes> {
+        self.inner.unit_suffixes.read().unwrap().clone()
+    }
+
+    /// Replace the limits for detecting [hot histograms](Self::hot_histograms).
+    pub fn set_hot_histogram_config(&self, config: HotHistogramConfig) {
+        *self.inner.hot_histogram_config.write().unwrap() = config;
+    }
+
+    #[allow(missing_docs)]
+    pub fn hot_histogram_config(&self) -> HotHistogramConfig {
+        self.inner.hot_histogram_config.read().unwrap().clone()
+    }
+
+    /// Histograms that recorded more samples than their
+    /// [`HotHistogramConfig`] threshold for the last
+    /// [`frames`](HotHistogramConfig::frames) times the atomic buckets were
+    /// cleared, with the number of samples in the latest frame, most first.
+    ///
+    /// A warning is logged when a histogram becomes hot, at most once a
+    /// minute per histogram.
+    pub fn hot_histograms(&self) -> Vec<(metrics::Key, usize)> {
+        let frames = self
+            .inner
+            .hot_histogram_config
+            .read()
+            .unwrap()
+            .frames
+            .max(1);
+        let loads = self.inner.histogram_loads.lock().unwrap();
+        let mut hot: Vec<_> = loads
+            .iter()
+            .filter(|(_, load)| load.hot_frames >= frames)
+            .map(|(key, load)| (key.clone(), load.samples))
+            .collect();
+        hot.sort_unstable_by(|(a, n_a), (b, n_b)| n_b.cmp(n_a).then_with(|| a.cmp(b)));
+        hot
+    }
+
+    /// The number of samples recorded in the latest frame by the histogram
+    /// with `key`, if it's one of the [`Self::hot_histograms`].
+    pub fn hot_histogram_samples(&self, key: &metrics::Key) -> Option<usize> {
+        let frames = self
+            .inner
+            .hot_histogram_config
+            .read()
+            .unwrap()
+            .frames
+            .max(1);
+        let loads = self.inner.histogram_loads.lock().unwrap();
+        loads
+            .get(key)
+            .filter(|load| load.hot_frames >= frames)
+            .map(|load| load.samples)
+    }
+
+    /// Replace the limits for detecting metrics with [too many label
+    /// combinations](Self::high_cardinality).
+    pub fn set_cardinality_config(&self, config: CardinalityConfig) {
+        *self.inner.cardinality_config.write().unwrap() = config;
+    }
+
+    #[allow(missing_docs)]
+    pub fn cardinality_config(&self) -> CardinalityConfig {
+        self.inner.cardinality_config.read().unwrap().clone()
+    }
+
+    /// Metrics with more label combinations than their
+    /// [`CardinalityConfig`] limit, most combinations first.
+    ///
+    /// Label combinations are counted when they're first registered, so
+    /// this is cheap to call. A warning with example keys is logged when a
+    /// metric exceeds its limit, and again at most once a minute per metric
+    /// while new combinations are registered. With
+    /// [`CardinalityConfig::overflow`], they're recorded into an overflow
+    /// key instead.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::{CardinalityConfig, MetricsRegistry};
+    /// use metrics::Key;
+    ///
+    /// let registry = MetricsRegistry::new();
+    /// registry.set_cardinality_config(CardinalityConfig::default().with_overflow(true));
+    /// for frame in 0..2000 {
+    ///     let key = Key::from_parts("frame_time", &[("frame", frame.to_string())]);
+    ///     registry.get_or_create_gauge(&key);
+    /// }
+    /// let high = registry.high_cardinality();
+    /// assert_eq!(high[0].combinations, 1000);
+    /// assert_eq!(high[0].overflowed, 1000);
+    /// assert!(registry
+    ///     .get_gauge(&Key::from_parts("frame_time", &[("label", "<overflow>")]))
+    ///     .is_some());
+    /// ```
+    pub fn high_cardinality(&self) -> Vec<HighCardinality> {
+        let config = self.inner.cardinality_config.read().unwrap();
+        let cardinality = self.inner.cardinality.lock().unwrap();
+        let mut high: Vec<_> = cardinality
+            .iter()
+            .filter_map(|(key, metric)| {
+                let limit = config.limit_for(key.name.as_str());
+                (metric.combinations > limit || metric.overflowed > 0).then(|| HighCardinality {
+                    key: key.clone(),
+                    combinations: metric.combinations,
+                    limit,
+                    overflowed: metric.overflowed,
+                    examples: metric.examples.iter().cloned().collect(),
+                })
+            })
+            .collect();
+        high.sort_unstable_by(|a, b| {
+            b.combinations
+                .cmp(&a.combinations)
+                .then_with(|| a.key.cmp_name_kind(&b.key))
+        });
+        high
+    }
+
+    fn track_histogram_loads(&self) {
+        let config = self.inner.hot_histogram_config.read().unwrap();
+        let mut loads = self.inner.histogram_loads.lock().unwrap();
+        let now = Instant::now();
+        self.inner.registry.visit_histograms(|key, h| {
+            let mut samples = 0;
+            h.data_with(|block| samples += block.len());
+            if samples <= config.threshold_for(key) {
+                // Keep the entry, so a histogram that keeps becoming hot
+                // doesn't warn every time.
+                if let Some(load) = loads.get_mut(key) {
+                    load.hot_frames = 0;
+                }
+                return;
+            }
+
+            let load = match loads.get_mut(key) {
+                Some(load) => load,
+                None => loads.entry(key.clone()).or_insert(HistogramLoad {
+                    samples,
+                    hot_frames: 0,
+                    last_warning: None,
+                }),
+            };
+            load.samples = samples;
+            load.hot_frames = load.hot_frames.saturating_add(1);
+            let recently_warned = load
+                .last_warning
+                .is_some_and(|last| now - last < HOT_HISTOGRAM_WARNING_PERIOD);
+            if load.hot_frames == config.frames.max(1) && !recently_warned {
+                load.last_warning = Some(now);
+                let name = MetricKey::new(key.clone(), MetricKind::Histogram).name_with_labels();
+                warn!(
+                    "Histogram {name} recorded {samples} samples per frame for {} frames, \
+                     which is expensive to buffer. Record fewer samples, or raise its \
+                     threshold with HotHistogramConfig::with_override",
+                    config.frames
+                );
+            }
+        });
+    }
+
+    /// Bevy system that calls [`Self::clear_atomic_buckets`].
+    ///
+    /// [`RegistryPlugin`](crate::RegistryPlugin) adds this system unless
+    /// [`RegistryPlugin::manual_bucket_clearing`](crate::RegistryPlugin::manual_bucket_clearing)
+    /// is used.
+    pub fn clear_atomic_buckets_system(registry: Res<Self>) {
         registry.clear_atomic_buckets();
     }
 }
 
-fn make_search_result(
-    kind: MetricKind,
-    key: &metrics::Key,
-    descriptions: &HashMap<DescriptionKey, MetricDescription>,
-) -> SearchResult {
-    let key = MetricKey::new(key.clone(), kind);
-    let desc_key = DescriptionKey::from(&key);
-    let description = descriptions.get(&desc_key).cloned();
-    SearchResult { key, description }
+/// Describe a batch of metrics through the global recorder, like
+/// [`MetricsRegistry::describe_many`].
+///
+/// Unlike that method, this also reaches any recorder the global recorder
+/// sends metrics to, like the other recorders of a
+/// [`FanoutRecorder`](crate::FanoutRecorder), and does nothing if no global
+/// recorder is installed.
+pub fn describe_many(metrics: &[(KeyName, MetricKind, Option<Unit>, &str)]) {
+    metrics::with_recorder(|recorder| {
+        for (name, kind, unit, text) in metrics {
+            let text = SharedString::from(text.to_string());
+            match kind {
+                MetricKind::Counter => recorder.describe_counter(name.clone(), *unit, text),
+                MetricKind::Gauge => recorder.describe_gauge(name.clone(), *unit, text),
+                MetricKind::Histogram => recorder.describe_histogram(name.clone(), *unit, text),
+            }
+        }
+    });
+}
+
+/// Descriptions made by plugins added before the
+/// [`RegistryPlugin`](crate::RegistryPlugin), replayed when it's built.
+#[derive(Default, Resource)]
+pub(crate) struct PendingDescriptions(pub(crate) Vec<(KeyName, MetricKind, Option<Unit>, String)>);
+
+/// Describe a batch of metrics from a [`Plugin::build`](bevy::app::Plugin::build),
+/// like [`describe_many`].
+///
+/// If the [`RegistryPlugin`](crate::RegistryPlugin) isn't added yet, the
+/// descriptions are also kept until it's built, since the global recorder
+/// doesn't exist yet.
+pub(crate) fn describe_in_build(
+    app: &mut App,
+    metrics: &[(KeyName, MetricKind, Option<Unit>, &str)],
+) {
+    describe_many(metrics);
+    if !app.is_plugin_added::<crate::RegistryPlugin>() {
+        let mut pending = app
+            .world_mut()
+            .get_resource_or_init::<PendingDescriptions>();
+        pending.0.extend(
+            metrics
+                .iter()
+                .map(|(name, kind, unit, text)| (name.clone(), *kind, *unit, text.to_string())),
+        );
+    }
+}
+
+/// Read locks on everything that makes up a description.
+struct DescriptionsRef<'a> {
+    descriptions: RwLockReadGuard<'a, HashMap<DescriptionKey, MetricDescription>>,
+    manifest: RwLockReadGuard<'a, ManifestDescriptions>,
+    custom_units: RwLockReadGuard<'a, HashMap<DescriptionKey, MetricUnit>>,
+    extras: RwLockReadGuard<'a, HashMap<DescriptionKey, DescriptionExtras>>,
+    aliases: RwLockReadGuard<'a, HashMap<KeyName, MetricAlias>>,
+    unit_suffixes: RwLockReadGuard<'a, Option<UnitSuffixes>>,
+}
+
+impl DescriptionsRef<'_> {
+    fn search_result(&self, key: MetricKey) -> SearchResult {
+        let desc_key = DescriptionKey::from(&key);
+        let description = self.merge(&desc_key);
+        let alias = self.aliases.get(&desc_key.name).map(|a| a.name.clone());
+        SearchResult {
+            key,
+            description,
+            alias,
+        }
+    }
+
+    /// Look up a description, overriding it with the metric's alias, and its
+    /// unit with any custom unit. Without any unit, one may be inferred from
+    /// the metric's name.
+    fn merge(&self, key: &DescriptionKey) -> Option<MetricDescription> {
+        let code = self.descriptions.get(key);
+        let manifest = self.manifest.descriptions.get(key);
+        let mut description = match self.manifest.policy {
+            ManifestPolicy::CodeWins => layer(code, manifest),
+            ManifestPolicy::ManifestWins => layer(manifest, code),
+        };
+        if let Some(alias) = self.aliases.get(&key.name) {
+            if let Some(unit) = &alias.unit {
+                or_empty(&mut description).unit = Some(unit.clone());
+            }
+            if let Some(text) = &alias.description {
+                or_empty(&mut description).text = text.clone().into();
+            }
+        }
+        if let Some(unit) = self.custom_units.get(key) {
+            or_empty(&mut description).unit = Some(unit.clone());
+        }
+        if let Some(extras) = self.extras.get(key) {
+            or_empty(&mut description).extras = extras.clone();
+        }
+        if description.as_ref().is_none_or(|d| d.unit.is_none()) {
+            let inferred = self
+                .unit_suffixes
+                .as_ref()
+                .and_then(|s| s.infer(key.name.as_str()));
+            if let Some(unit) = inferred {
+                let description = or_empty(&mut description);
+                description.unit = Some(unit.clone());
+                description.unit_inferred = true;
+            }
+        }
+        description
+    }
+}
+
+/// `winner`, with its missing unit and extras taken from `fallback`.
+fn layer(
+    winner: Option<&MetricDescription>,
+    fallback: Option<&MetricDescription>,
+) -> Option<MetricDescription> {
+    let Some(winner) = winner else {
+        return fallback.cloned();
+    };
+    let mut description = winner.clone();
+    if let Some(fallback) = fallback {
+        if description.unit.is_none() {
+            description.unit = fallback.unit.clone();
+        }
+        if description.extras.is_empty() {
+            description.extras = fallback.extras.clone();
+        }
+    }
+    Some(description)
+}
+
+fn or_empty(description: &mut Option<MetricDescription>) -> &mut MetricDescription {
+    description.get_or_insert_with(|| MetricDescription {
+        unit: None,
+        unit_inferred: false,
+        text: "".into(),
+        deprecated: false,
+        extras: default(),
+    })
 }
 
 impl Default for MetricsRegistry {
@@ -162,7 +2071,7 @@ impl Default for MetricsRegistry {
 
 /// Identifies some metric in the registry.
 #[allow(missing_docs)]
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct MetricKey {
     pub key: metrics::Key,
     pub kind: MetricKind,
@@ -187,6 +2096,66 @@ impl MetricKey {
             format!("{} ({})", name, metric_kind_str(self.kind))
         }
     }
+
+    /// A version of this key that is cheap to clone, sharing its name and
+    /// labels. See [`metrics::Key::to_retained`].
+    ///
+    /// Keys of registered metrics, e.g. in [`SearchResult`]s, already are.
+    /// This allocates only if the key was built from owned strings.
+    pub fn to_retained(&self) -> Self {
+        Self::new(self.key.to_retained(), self.kind)
+    }
+
+    /// This key with its labels sorted.
+    ///
+    /// [`metrics::Key`]s compare and hash equal regardless of the order their
+    /// labels were added in, which may change between runs, e.g. when labels
+    /// are collected from a `HashMap`. But they keep that order, so anything
+    /// that lists the labels, like [`Self::name_with_labels`] and saved
+    /// [layouts](crate::layout), uses this instead.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::registry::MetricKey;
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use metrics::{Key, Label};
+    /// let key = |labels: [(&'static str, &'static str); 2]| {
+    ///     let labels = labels.map(|(k, v)| Label::new(k, v)).to_vec();
+    ///     MetricKey::new(Key::from_parts("enemies", labels), MetricKind::Gauge)
+    /// };
+    /// let a = key([("team", "red"), ("zone", "north")]);
+    /// let b = key([("zone", "north"), ("team", "red")]);
+    /// assert_eq!(a, b);
+    /// assert_ne!(a.key.labels().next(), b.key.labels().next());
+    ///
+    /// let (a, b) = (a.normalized(), b.normalized());
+    /// assert!(a.key.labels().eq(b.key.labels()));
+    /// assert_eq!(a.name_with_labels(), "enemies{team=red,zone=north}");
+    /// assert_eq!(b.name_with_labels(), "enemies{team=red,zone=north}");
+    /// ```
+    pub fn normalized(&self) -> Self {
+        if self.key.labels().is_sorted() {
+            return self.clone();
+        }
+        let mut labels: Vec<_> = self.key.labels().cloned().collect();
+        labels.sort();
+        let name = KeyName::from(self.key.name().to_owned());
+        Self::new(metrics::Key::from_parts(name, labels), self.kind)
+    }
+
+    /// The metric name followed by its sorted labels, like `name{k1=v1,k2=v2}`.
+    pub fn name_with_labels(&self) -> String {
+        let labels: Vec<_> = self
+            .normalized()
+            .key
+            .labels()
+            .map(|l| format!("{}={}", l.key(), l.value()))
+            .collect();
+        if labels.is_empty() {
+            self.key.name().to_owned()
+        } else {
+            format!("{}{{{}}}", self.key.name(), labels.join(","))
+        }
+    }
 }
 
 /// Key used for storing metric descriptions.
@@ -197,6 +2166,33 @@ pub struct DescriptionKey {
     pub kind: MetricKind,
 }
 
+impl DescriptionKey {
+    fn cmp_name_kind(&self, other: &Self) -> std::cmp::Ordering {
+        (self.name.as_str(), self.kind).cmp(&(other.name.as_str(), other.kind))
+    }
+}
+
+/// Differences between the described and registered metrics, found by
+/// [`MetricsRegistry::audit`].
+///
+/// Both lists are sorted by name and kind.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsAudit {
+    /// Descriptions without any registered metric of the same name and kind,
+    /// e.g. left over from removed instrumentation.
+    pub unregistered: Vec<DescriptionKey>,
+    /// Registered metrics without a description, with the number of
+    /// registered series, i.e. label sets, of each.
+    pub undescribed: Vec<(DescriptionKey, usize)>,
+}
+
+impl MetricsAudit {
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.unregistered.is_empty() && self.undescribed.is_empty()
+    }
+}
+
 impl From<&MetricKey> for DescriptionKey {
     fn from(value: &MetricKey) -> Self {
         Self {
@@ -206,36 +2202,162 @@ impl From<&MetricKey> for DescriptionKey {
     }
 }
 
+/// The results of [`MetricsRegistry::search_iter`], in no particular order.
+///
+/// Descriptions are read when each result is built, so descriptions added
+/// while iterating may or may not be included.
+pub struct SearchIter<'a> {
+    registry: &'a MetricsRegistry,
+    keys: std::vec::IntoIter<MetricKey>,
+}
+
+impl SearchIter<'_> {
+    /// The keys of the remaining results, e.g. to rank them before building
+    /// only the ones that are shown with
+    /// [`MetricsRegistry::get_search_result`].
+    pub fn keys(&self) -> &[MetricKey] {
+        self.keys.as_slice()
+    }
+}
+
+impl Iterator for SearchIter<'_> {
+    type Item = SearchResult;
+
+    fn next(&mut self) -> Option<SearchResult> {
+        let key = self.keys.next()?;
+        Some(self.registry.get_search_result(key))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl ExactSizeIterator for SearchIter<'_> {}
+
 /// Metadata for a metric.
 #[allow(missing_docs)]
 #[derive(Clone)]
 pub struct SearchResult {
     pub key: MetricKey,
     pub description: Option<MetricDescription>,
+    /// The name of the metric's [alias](MetricAlias::name).
+    pub alias: Option<String>,
 }
 
 impl SearchResult {
+    /// The alias of the metric, or else its name.
+    pub fn name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(self.key.key.name())
+    }
+
     /// Display the complete information of a search result.
     ///
-    /// `display_path` will override the key's name, which is used for removing
-    /// layers of namespacing.
-    pub fn detailed_text(&self, display_path: Option<&str>) -> LayoutJob {
+    /// `display_path` will override the key's name or alias, which is used for
+    /// removing layers of namespacing. The text is colored and translated for
+    /// `ui`, which it should be shown in.
+    pub fn detailed_text(&self, display_path: Option<&str>, ui: &Ui) -> LayoutJob {
+        self.highlighted_text(
+            display_path,
+            &[],
+            &ThemePalette::of(ui),
+            &DashboardLocalization::of(ui),
+        )
+    }
+
+    /// Like [`Self::detailed_text`], but highlights the characters of the
+    /// name at the `highlighted` char indices, e.g. those matched by a search.
+    ///
+    /// Takes the palette and localization of the `Ui` it will be shown in.
+    pub fn highlighted_text(
+        &self,
+        display_path: Option<&str>,
+        highlighted: &[usize],
+        palette: &ThemePalette,
+        l10n: &DashboardLocalization,
+    ) -> LayoutJob {
         let mut job = LayoutJob::default();
-        job.append(
-            &self.key.title(display_path, 0),
-            0.0,
-            TextFormat {
-                // underline: Stroke::new(1.0, Color32::WHITE),
-                color: Color32::WHITE,
-                ..default()
+        let title = self.key.title(display_path.or(self.alias.as_deref()), 0);
+        let format = |highlight| TextFormat {
+            color: if highlight {
+                palette.highlight
+            } else {
+                palette.text
             },
-        );
-        if let Some(unit) = self.description.as_ref().and_then(|d| d.unit) {
+            underline: if highlight {
+                Stroke::new(1.0, palette.highlight)
+            } else {
+                Stroke::NONE
+            },
+            ..default()
+        };
+        // Append runs of characters with the same highlighting.
+        let mut run = String::new();
+        let mut run_highlighted = false;
+        for (i, c) in title.chars().enumerate() {
+            let highlight = highlighted.contains(&i);
+            if highlight != run_highlighted && !run.is_empty() {
+                job.append(&run, 0.0, format(run_highlighted));
+                run.clear();
+            }
+            run_highlighted = highlight;
+            run.push(c);
+        }
+        job.append(&run, 0.0, format(run_highlighted));
+        if let Some(description) = self.description.as_ref() {
+            if let Some(unit) = &description.unit {
+                job.append(
+                    &format!(" [{unit}]"),
+                    0.0,
+                    TextFormat {
+                        color: palette.accent,
+                        ..default()
+                    },
+                );
+            }
+            if description.unit_inferred {
+                job.append(
+                    &format!(" {}", l10n.get(UiText::InferredUnit)),
+                    0.0,
+                    TextFormat {
+                        color: palette.weak,
+                        ..default()
+                    },
+                );
+            }
+            if description.deprecated {
+                job.append(
+                    &format!(" {}", l10n.get(UiText::Deprecated)),
+                    0.0,
+                    TextFormat {
+                        color: palette.error,
+                        ..default()
+                    },
+                );
+            }
+        }
+        if self.alias.is_some() {
+            job.append("\n", 0.0, default());
+            job.append(
+                &l10n.format(UiText::AliasOf, &[("name", &self.key.key.name())]),
+                0.0,
+                TextFormat {
+                    color: palette.weak,
+                    ..default()
+                },
+            );
+        }
+        let owner = self
+            .description
+            .as_ref()
+            .and_then(|d| d.extras.owner.as_deref());
+        if let Some(owner) = owner {
+            job.append("\n", 0.0, default());
             job.append(
-                &format!(" [{}]", unit_str(unit)),
+                &l10n.format(UiText::Owner, &[("owner", &owner)]),
                 0.0,
                 TextFormat {
-                    color: Color32::LIGHT_BLUE,
+                    color: palette.weak,
                     ..default()
                 },
             );
@@ -246,18 +2368,18 @@ impl SearchResult {
                 &format!("{}={}", label.key(), label.value()),
                 0.0,
                 TextFormat {
-                    color: Color32::YELLOW,
+                    color: palette.label,
                     ..default()
                 },
             );
         }
-        if let Some(description) = &self.description {
+        if let Some(description) = self.description.as_ref().filter(|d| !d.text.is_empty()) {
             job.append("\n", 0.0, default());
             job.append(
                 &description.text,
                 0.0,
                 TextFormat {
-                    color: Color32::GRAY,
+                    color: palette.weak,
                     italics: true,
                     ..default()
                 },
@@ -274,10 +2396,7 @@ impl Recorder for MetricsRegistry {
                 name: key_name,
                 kind: MetricKind::Counter,
             },
-            MetricDescription {
-                unit,
-                text: description,
-            },
+            MetricDescription::new(unit, description),
         );
     }
 
@@ -287,10 +2406,7 @@ impl Recorder for MetricsRegistry {
                 name: key_name,
                 kind: MetricKind::Gauge,
             },
-            MetricDescription {
-                unit,
-                text: description,
-            },
+            MetricDescription::new(unit, description),
         );
     }
 
@@ -300,28 +2416,29 @@ impl Recorder for MetricsRegistry {
                 name: key_name,
                 kind: MetricKind::Histogram,
             },
-            MetricDescription {
-                unit,
-                text: description,
-            },
+            MetricDescription::new(unit, description),
         );
     }
 
     fn register_counter(&self, key: &metrics::Key, _metadata: &Metadata<'_>) -> Counter {
-        self.inner
-            .registry
-            .get_or_create_counter(key, |c| c.clone().into())
+        self.inner.get_or_create_counter(key).into()
     }
 
     fn register_gauge(&self, key: &metrics::Key, _metadata: &Metadata<'_>) -> Gauge {
-        self.inner
-            .registry
-            .get_or_create_gauge(key, |c| c.clone().into())
+        let value = self.inner.get_or_create_gauge(key);
+        if !self.inner.tracks_envelopes.load(Ordering::Acquire)
+            || !self.tracks_gauge_envelope(key.name())
+        {
+            return value.into();
+        }
+        let mut envelopes = self.inner.envelopes.lock().unwrap();
+        let envelope = envelopes
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(GaugeEnvelope::new(value)));
+        Gauge::from_arc(Arc::clone(envelope))
     }
 
     fn register_histogram(&self, key: &metrics::Key, _metadata: &Metadata<'_>) -> Histogram {
-        self.inner
-            .registry
-            .get_or_create_histogram(key, |c| c.clone().into())
+        self.inner.get_or_create_histogram(key).into()
     }
 }
@@ -1,23 +1,217 @@
-use crate::registry::MetricsRegistry;
-use bevy::prelude::*;
-use metrics::set_global_recorder;
+use crate::fanout_recorder::FanoutRecorder;
+use crate::metric_kind_str;
+use crate::registry::{
+    describe_many, CardinalityConfig, FrameId, HotHistogramConfig, MetricsRegistry,
+    PendingDescriptions,
+};
+use crate::retention::{evict_idle_metrics, MetricEvicted, RetentionPolicy};
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
+use metrics::{set_global_recorder, Recorder};
+use std::sync::Mutex;
+
+#[cfg(feature = "manifest")]
+use crate::{manifest::MetricManifest, registry::ManifestPolicy};
+#[cfg(feature = "manifest")]
+use std::path::PathBuf;
 
 /// Installs and garbage collects a [`MetricsRegistry`].
 ///
 /// This plugin runs a garbage collector every frame in the
-/// [`ClearBucketsSystem`]. Direct consumers of atomic buckets (e.g. plots)
-/// should read the documentation of [`ClearBucketsSystem`].
-#[derive(Default)]
+/// [`BucketClearSet`], in the [`Last`] schedule unless changed with
+/// [`Self::clear_buckets_in`] or [`Self::manual_bucket_clearing`]. Direct
+/// consumers of atomic buckets (e.g. plots) should read the documentation of
+/// [`BucketClearSet`].
+///
+/// The `metrics` crate only allows one global recorder per process, and it
+/// can never be replaced. To share it with another recorder, like an
+/// exporter, pass that recorder to [`Self::fanout`] instead of installing it
+/// yourself, or use [`FallbackBehavior::Fanout`] to add it later. If some
+/// other global recorder was installed first, the [`FallbackBehavior`]
+/// decides what happens.
+///
+/// The global recorder is installed when this plugin is built. Metrics
+/// recorded before that, e.g. in the [`Plugin::build`] of plugins added
+/// before this one, go to the no-op recorder and are lost. The descriptions
+/// made by this crate's plugins are kept and replayed, but those made by
+/// other plugins are lost too. Add this plugin first, or create it with [`Self::with_eager_install`]
+/// before any other plugin. In debug builds, metrics that are described but
+/// still not recorded at the end of the first frame are logged as likely
+/// lost, see [`Self::check_early_recordings`].
 pub struct RegistryPlugin {
     registry: Option<MetricsRegistry>,
+    install_global: bool,
+    /// The result of installing the global recorder when the plugin was
+    /// created, with [`Self::with_eager_install`].
+    eager_install: Option<Result<Option<FanoutRecorder>, String>>,
+    check_early_recordings: bool,
+    fallback: FallbackBehavior,
+    fanout: Mutex<Vec<Box<dyn Recorder + Send + Sync>>>,
+    clear_buckets_schedule: Option<InternedScheduleLabel>,
+    hot_histograms: Option<HotHistogramConfig>,
+    cardinality: Option<CardinalityConfig>,
+    gauge_envelopes: Vec<String>,
+    retention: Option<RetentionPolicy>,
+    #[cfg(feature = "manifest")]
+    manifest: Option<(PathBuf, ManifestPolicy)>,
+}
+
+/// What [`RegistryPlugin`] does when a global recorder is already installed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FallbackBehavior {
+    /// Panic with an explanation.
+    Panic,
+    /// Log a warning. The dashboard keeps working, but it only shows
+    /// metrics recorded directly through the [`MetricsRegistry`] resource.
+    #[default]
+    WarnAndDisable,
+    /// Do nothing. Use this if the registry was already installed manually.
+    Ignore,
+    /// Install a [`FanoutRecorder`], even without [fanout
+    /// recorders](RegistryPlugin::fanout), so that recorders set up later,
+    /// like an exporter, can be added to it.
+    ///
+    /// A recorder installed before the plugin can't be wrapped, since the
+    /// `metrics` crate never lets a global recorder be read or replaced. In
+    /// that case, a warning explains how to fix the order, and the dashboard
+    /// keeps working like with [`Self::WarnAndDisable`]:
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use bevy_metrics_dashboard::{FallbackBehavior, FanoutRecorder, RegistryPlugin};
+    /// use metrics::{counter, Key, Level, Metadata, Recorder};
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// // Stands in for an exporter's recorder.
+    /// let exporter = MetricsRegistry::new();
+    /// metrics::set_global_recorder(exporter.clone()).unwrap();
+    ///
+    /// let mut app = App::new();
+    /// app.add_plugins(RegistryPlugin::new().with_fallback(FallbackBehavior::Fanout));
+    /// assert!(!app.world().contains_resource::<FanoutRecorder>());
+    ///
+    /// counter!("hits").increment(2);
+    /// let key = Key::from_static_name("hits");
+    /// let registry = app.world().resource::<MetricsRegistry>();
+    /// assert_eq!(exporter.get_counter(&key).unwrap().load(Ordering::Relaxed), 2);
+    /// assert!(registry.get_counter(&key).is_none());
+    ///
+    /// // Metrics recorded through the registry directly are still shown.
+    /// let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    /// registry.register_counter(&key, &metadata).increment(1);
+    /// assert_eq!(registry.get_counter(&key).unwrap().load(Ordering::Relaxed), 1);
+    /// ```
+    Fanout,
 }
 
 /// The [`SystemSet`] from which atomic buckets are cleared.
 ///
-/// Histogram consumer systems should run in the [`Last`] schedule **before**
-/// this set to avoid missing samples.
+/// Histogram consumer systems should run in the same schedule as this set
+/// (by default [`Last`]) and **before** it to avoid missing samples. Samples
+/// recorded after the set runs are seen by consumers in the next frame.
+/// Consumers that can't be ordered this way, or that must not miss samples
+/// read by others, can use
+/// [`MetricsRegistry::subscribe_histogram`] from any system instead.
+///
+/// The dashboard's own consumers, like
+/// [`DashboardWindow::update_plots_on_all_windows`](crate::DashboardWindow::update_plots_on_all_windows),
+/// run in [`Last`] before this set. If [`RegistryPlugin::clear_buckets_in`]
+/// moves the set to a schedule that runs earlier in the frame, histogram
+/// plots only see samples recorded after it. With
+/// [`RegistryPlugin::manual_bucket_clearing`], the set is empty, and buckets
+/// must be cleared after the dashboard has run.
+///
+/// A histogram consumer ordered before the set sees every sample recorded
+/// since the set last ran, and one ordered after it sees none:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::{BucketClearSet, RegistryPlugin};
+/// # use metrics::Key;
+/// #[derive(Default, Resource)]
+/// struct Seen {
+///     before: Vec<f64>,
+///     after: Vec<f64>,
+/// }
+///
+/// fn samples(registry: &MetricsRegistry) -> Vec<f64> {
+///     let histogram = registry.get_or_create_histogram(&Key::from_static_name("load"));
+///     let mut samples = Vec::new();
+///     histogram.data_with(|block| samples.extend_from_slice(block));
+///     samples
+/// }
+///
+/// let mut app = App::new();
+/// app.add_plugins(RegistryPlugin::local(MetricsRegistry::new()))
+///     .init_resource::<Seen>()
+///     .add_systems(Update, |registry: Res<MetricsRegistry>, mut n: Local<u32>| {
+///         *n += 1;
+///         let histogram = registry.get_or_create_histogram(&Key::from_static_name("load"));
+///         histogram.push(f64::from(*n));
+///     })
+///     .add_systems(
+///         Last,
+///         (
+///             (|registry: Res<MetricsRegistry>, mut seen: ResMut<Seen>| {
+///                 seen.before = samples(&registry);
+///             })
+///             .before(BucketClearSet),
+///             (|registry: Res<MetricsRegistry>, mut seen: ResMut<Seen>| {
+///                 seen.after = samples(&registry);
+///             })
+///             .after(BucketClearSet),
+///         ),
+///     );
+///
+/// for frame in 1..=3 {
+///     app.update();
+///     let seen = app.world().resource::<Seen>();
+///     assert_eq!(seen.before, [f64::from(frame)]);
+///     assert!(seen.after.is_empty());
+/// }
+/// ```
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
-pub struct ClearBucketsSystem;
+pub struct BucketClearSet;
+
+#[allow(deprecated)]
+pub use deprecated::ClearBucketsSystem;
+
+// The derived impls use the deprecated name, which can only be allowed for a
+// whole module.
+#[allow(deprecated)]
+mod deprecated {
+    use bevy::prelude::SystemSet;
+
+    /// The former name of [`BucketClearSet`](super::BucketClearSet), which
+    /// still contains the same system.
+    #[deprecated(note = "use `BucketClearSet`")]
+    #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+    pub struct ClearBucketsSystem;
+}
+
+impl Default for RegistryPlugin {
+    fn default() -> Self {
+        Self {
+            registry: None,
+            install_global: true,
+            eager_install: None,
+            check_early_recordings: cfg!(debug_assertions),
+            fallback: default(),
+            fanout: default(),
+            clear_buckets_schedule: Some(Last.intern()),
+            hot_histograms: None,
+            cardinality: None,
+            gauge_envelopes: Vec::new(),
+            retention: None,
+            #[cfg(feature = "manifest")]
+            manifest: None,
+        }
+    }
+}
 
 impl RegistryPlugin {
     /// Create a default plugin.
@@ -29,29 +223,303 @@ impl RegistryPlugin {
     ///
     /// WARNING: Using this constructor will silence errors if it fails to
     /// call [`set_global_recorder`], assuming that the user already did this
-    /// manually with `registry`.
+    /// manually with `registry`. Use [`Self::with_fallback`] to change this.
     pub fn with_registry(registry: MetricsRegistry) -> Self {
         Self {
             registry: Some(registry),
+            fallback: FallbackBehavior::Ignore,
+            ..default()
+        }
+    }
+
+    /// Use `registry` without installing it as the global recorder.
+    ///
+    /// Metrics must be recorded through `registry` directly or by installing it
+    /// as a local recorder with [`MetricsRegistry::install_as_local`].
+    pub fn local(registry: MetricsRegistry) -> Self {
+        Self {
+            registry: Some(registry),
+            install_global: false,
+            ..default()
+        }
+    }
+
+    /// Create a plugin and install its registry as the global recorder
+    /// immediately, instead of when the plugin is built.
+    ///
+    /// Create it before any other plugin, so metrics recorded while they're
+    /// built aren't lost:
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_metrics_dashboard::{metrics::counter, registry::MetricsRegistry, RegistryPlugin};
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// struct LevelPlugin;
+    ///
+    /// impl Plugin for LevelPlugin {
+    ///     fn build(&self, _app: &mut App) {
+    ///         counter!("levels_loaded").increment(1);
+    ///     }
+    /// }
+    ///
+    /// let registry_plugin = RegistryPlugin::with_eager_install();
+    /// let mut app = App::new();
+    /// app.add_plugins((LevelPlugin, registry_plugin));
+    ///
+    /// let registry = app.world().resource::<MetricsRegistry>();
+    /// let key = metrics::Key::from_name("levels_loaded");
+    /// let loaded = registry.get_counter(&key).unwrap();
+    /// assert_eq!(loaded.load(Ordering::Relaxed), 1);
+    /// ```
+    ///
+    /// The [`FallbackBehavior`] still applies when the plugin is built.
+    /// Recorders passed to [`Self::fanout`] afterwards are added to the
+    /// [`FanoutRecorder`] if one was installed, and dropped with a warning
+    /// otherwise.
+    pub fn with_eager_install() -> Self {
+        let mut plugin = Self {
+            registry: Some(default()),
+            ..default()
+        };
+        plugin.eager_install = Some(plugin.install(plugin.registry.as_ref().unwrap()));
+        plugin
+    }
+
+    /// Set what happens when a global recorder is already installed.
+    pub fn with_fallback(mut self, fallback: FallbackBehavior) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Also send all metrics to `recorder`.
+    ///
+    /// The registry and every fanout recorder are installed together as the
+    /// global recorder, wrapped in a [`FanoutRecorder`].
+    pub fn fanout(self, recorder: impl Recorder + Send + Sync + 'static) -> Self {
+        self.fanout.lock().unwrap().push(Box::new(recorder));
+        self
+    }
+
+    /// Clear atomic buckets in `schedule` instead of [`Last`].
+    ///
+    /// The [`BucketClearSet`] set moves along with it, so systems
+    /// ordered against the set must be added to the same schedule.
+    pub fn clear_buckets_in(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.clear_buckets_schedule = Some(schedule.intern());
+        self
+    }
+
+    /// Don't clear atomic buckets automatically.
+    ///
+    /// Histogram buckets grow until they are cleared with
+    /// [`MetricsRegistry::clear_atomic_buckets`] or by adding
+    /// [`MetricsRegistry::clear_atomic_buckets_system`] to a schedule.
+    pub fn manual_bucket_clearing(mut self) -> Self {
+        self.clear_buckets_schedule = None;
+        self
+    }
+
+    /// Detect hot histograms with `config` instead of the default limits.
+    ///
+    /// See [`MetricsRegistry::hot_histograms`].
+    pub fn hot_histograms(mut self, config: HotHistogramConfig) -> Self {
+        self.hot_histograms = Some(config);
+        self
+    }
+
+    /// Detect metrics with too many label combinations with `config`
+    /// instead of the default limits.
+    ///
+    /// See [`MetricsRegistry::high_cardinality`].
+    pub fn cardinality(mut self, config: CardinalityConfig) -> Self {
+        self.cardinality = Some(config);
+        self
+    }
+
+    /// Track the lowest and highest values of the gauges named `name` within
+    /// each frame, and draw them as a band around the gauge's plot.
+    ///
+    /// See [`MetricsRegistry::track_gauge_envelope`].
+    pub fn track_gauge_envelope(mut self, name: impl Into<String>) -> Self {
+        self.gauge_envelopes.push(name.into());
+        self
+    }
+
+    /// Evict metrics that stop being updated with `policy`, sending a
+    /// [`MetricEvicted`] event for each.
+    ///
+    /// See the [`retention`](crate::retention) module.
+    pub fn retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = Some(policy);
+        self
+    }
+
+    /// Whether to log a warning for metrics that are described, but still
+    /// not recorded at the end of the first frame. These likely were
+    /// recorded before the global recorder was installed.
+    ///
+    /// Metrics that are only recorded later, e.g. once a level is loaded,
+    /// are reported too. Defaults to true in debug builds.
+    pub fn check_early_recordings(mut self, check: bool) -> Self {
+        self.check_early_recordings = check;
+        self
+    }
+
+    #[cfg(feature = "manifest")]
+    /// Describe metrics with the [`MetricManifest`] at `path` when the plugin
+    /// is built, resolving conflicts with descriptions from code with
+    /// `policy`.
+    ///
+    /// Errors, including the offending entry, are logged.
+    pub fn manifest(mut self, path: impl Into<PathBuf>, policy: ManifestPolicy) -> Self {
+        self.manifest = Some((path.into(), policy));
+        self
+    }
+
+    /// Install the global recorder, returning the [`FanoutRecorder`] if one
+    /// was installed.
+    fn install(&self, registry: &MetricsRegistry) -> Result<Option<FanoutRecorder>, String> {
+        let fanout = std::mem::take(&mut *self.fanout.lock().unwrap());
+        if fanout.is_empty() && self.fallback != FallbackBehavior::Fanout {
+            return set_global_recorder(registry.clone())
+                .map(|()| None)
+                .map_err(|e| e.to_string());
+        }
+        let recorder = FanoutRecorder::new(registry.clone());
+        for other in fanout {
+            recorder.add_boxed(other);
+        }
+        set_global_recorder(recorder.clone())
+            .map(|()| Some(recorder))
+            .map_err(|e| e.to_string())
+    }
+
+    fn fall_back(&self, e: &str) {
+        match self.fallback {
+            FallbackBehavior::Panic => panic!(
+                "Failed to set global recorder: {e}. Another recorder was installed first; \
+                 pass it to RegistryPlugin::fanout instead"
+            ),
+            FallbackBehavior::WarnAndDisable => warn!(
+                "Failed to set global recorder: {e}. The dashboard will only show metrics \
+                 recorded directly through the MetricsRegistry resource. To share the global \
+                 recorder, pass the other recorder to RegistryPlugin::fanout instead of \
+                 installing it"
+            ),
+            FallbackBehavior::Fanout => warn!(
+                "Failed to set global recorder: {e}. Another recorder was installed before \
+                 RegistryPlugin, and the metrics crate can't wrap it in a fanout. The dashboard \
+                 will only show metrics recorded directly through the MetricsRegistry resource. \
+                 Add RegistryPlugin before installing the other recorder, and add that recorder \
+                 to the FanoutRecorder resource instead"
+            ),
+            FallbackBehavior::Ignore => {}
         }
     }
 }
 
 impl Plugin for RegistryPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        let registry = if let Some(registry) = &self.registry {
-            _ = set_global_recorder(registry.clone());
-            registry.clone()
-        } else {
-            let registry = MetricsRegistry::default();
-            if let Err(e) = set_global_recorder(registry.clone()) {
-                error!("Failed to set global recorder: {e}");
+        let registry = self.registry.clone().unwrap_or_default();
+        let result = match &self.eager_install {
+            Some(result) => {
+                let fanout = std::mem::take(&mut *self.fanout.lock().unwrap());
+                match result {
+                    Ok(Some(recorder)) => fanout.into_iter().for_each(|r| recorder.add_boxed(r)),
+                    _ if !fanout.is_empty() => warn!(
+                        "{} fanout recorders were added after the global recorder was \
+                         installed by RegistryPlugin::with_eager_install, and are dropped. Use \
+                         FallbackBehavior::Fanout to add them later",
+                        fanout.len()
+                    ),
+                    _ => {}
+                }
+                result.clone()
             }
-            registry
+            None if self.install_global => self.install(&registry),
+            None => Ok(None),
         };
-        app.insert_resource(registry).add_systems(
-            Last,
-            MetricsRegistry::clear_atomic_buckets_system.in_set(ClearBucketsSystem),
-        );
+        match result {
+            Ok(Some(recorder)) => {
+                app.insert_resource(recorder);
+            }
+            Ok(None) => {}
+            Err(e) => self.fall_back(&e),
+        }
+        if let Some(config) = &self.hot_histograms {
+            registry.set_hot_histogram_config(config.clone());
+        }
+        if let Some(config) = &self.cardinality {
+            registry.set_cardinality_config(config.clone());
+        }
+        if let Some(policy) = &self.retention {
+            registry.set_retention_policy(Some(policy.clone()));
+        }
+        for name in &self.gauge_envelopes {
+            registry.track_gauge_envelope(name.clone());
+        }
+        #[cfg(feature = "manifest")]
+        if let Some((path, policy)) = &self.manifest {
+            match MetricManifest::load(path) {
+                Ok(manifest) => manifest.apply(&registry, *policy),
+                Err(e) => error!("{}: {e}", path.display()),
+            }
+        }
+        if let Some(pending) = app.world_mut().remove_resource::<PendingDescriptions>() {
+            let metrics: Vec<_> = pending
+                .0
+                .iter()
+                .map(|(name, kind, unit, text)| (name.clone(), *kind, *unit, text.as_str()))
+                .collect();
+            // The registry may not be the global recorder, e.g. when it's local.
+            describe_many(&metrics);
+            registry.describe_many(&metrics);
+        }
+        app.insert_resource(registry)
+            .init_resource::<FrameId>()
+            .add_event::<MetricEvicted>()
+            .add_systems(First, FrameId::advance);
+        let sample_activity = (MetricsRegistry::sample_activity_system, evict_idle_metrics).chain();
+        match self.clear_buckets_schedule {
+            Some(schedule) => {
+                #[allow(deprecated)]
+                let clear_atomic_buckets = MetricsRegistry::clear_atomic_buckets_system
+                    .in_set(BucketClearSet)
+                    .in_set(ClearBucketsSystem);
+                app.configure_sets(schedule, BucketClearSet).add_systems(
+                    schedule,
+                    (sample_activity.before(BucketClearSet), clear_atomic_buckets),
+                );
+            }
+            None => {
+                app.add_systems(Last, sample_activity);
+            }
+        }
+        if self.install_global && self.check_early_recordings {
+            app.add_systems(Last, warn_described_only.run_if(run_once));
+        }
+    }
+}
+
+/// Warn about metrics that are described but weren't recorded in the first
+/// frame, which likely were recorded before the recorder was installed.
+fn warn_described_only(registry: Res<MetricsRegistry>) {
+    let described_only = registry.described_only();
+    if described_only.is_empty() {
+        return;
     }
+    let names: Vec<_> = described_only
+        .iter()
+        .map(|key| format!("{} {}", metric_kind_str(key.kind), key.name.as_str()))
+        .collect();
+    warn!(
+        "{} metrics were described but not recorded in the first frame: {}. If they were \
+         recorded while plugins were built, those values went to the no-op recorder: add \
+         RegistryPlugin before other plugins, or create it with \
+         RegistryPlugin::with_eager_install. Disable this check with \
+         RegistryPlugin::check_early_recordings",
+        names.len(),
+        names.join(", "),
+    );
 }
@@ -0,0 +1,216 @@
+//! Forwarding metrics from the render world into the main world's registry.
+
+use crate::core_metrics::names::RENDER_FRAME_LATENCY;
+use crate::registry::{DescriptionKey, MetricDescription, MetricsRegistry};
+use bevy::{
+    diagnostic::FrameCount,
+    platform::collections::HashMap,
+    prelude::*,
+    render::{Render, RenderApp, RenderSet},
+};
+use metrics::{Key, KeyName, Unit};
+use metrics_util::MetricKind;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::Ordering,
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+};
+
+/// Gives the render world its own [`MetricsRegistry`] and forwards its
+/// metrics into the main world's registry every frame.
+///
+/// Render systems wrapped in [`with_scoped_recorder`](crate::with_scoped_recorder)
+/// record into the render-world registry. At the end of each render frame,
+/// the counter increments, the changed gauges and the new histogram samples
+/// are sent over a channel. The main world applies them in [`First`], so
+/// forwarded histogram samples are visible to plots until the
+/// [`BucketClearSet`](crate::BucketClearSet) of that frame.
+///
+/// Because rendering is pipelined, render data arrives one or two frames
+/// late, and sometimes two render frames finish during one main frame. Each
+/// batch holds the values of exactly one render frame, and at most one batch
+/// is applied per main frame, so histogram samples of different render frames
+/// are not merged (unless the main world falls behind by several frames). The
+/// `render::frame_latency` gauge shows how many frames old the applied batch
+/// is.
+///
+/// Both worlds can record the same metric. Counters are forwarded as their
+/// increments since the last render frame, which are added to the main
+/// world's counter. Gauges are only forwarded when they change, and then
+/// replace the main world's value, like setting a gauge from two systems.
+///
+/// Add this after [`RegistryPlugin`](crate::RegistryPlugin) and the render
+/// plugins. Without a render sub-app, this does nothing.
+pub struct RenderMetricsForwardingPlugin;
+
+enum ForwardedMetric {
+    /// The increment since the last batch.
+    Counter(metrics::Key, u64),
+    Gauge(metrics::Key, u64),
+    Histogram(metrics::Key, Vec<f64>),
+    Description(DescriptionKey, MetricDescription),
+}
+
+/// The metrics of one render frame.
+struct RenderMetricsBatch {
+    /// The main world frame that this render frame extracted.
+    frame: Option<u32>,
+    metrics: Vec<ForwardedMetric>,
+}
+
+#[derive(Resource)]
+struct RenderMetricsSender {
+    sender: Sender<RenderMetricsBatch>,
+    n_descriptions_sent: usize,
+    /// The counter and gauge values that were last sent, by key.
+    sent_counters: HashMap<metrics::Key, u64>,
+    sent_gauges: HashMap<metrics::Key, u64>,
+}
+
+#[derive(Resource)]
+struct RenderMetricsReceiver {
+    receiver: Mutex<Receiver<RenderMetricsBatch>>,
+    pending: VecDeque<RenderMetricsBatch>,
+}
+
+/// Batches older than this are applied immediately, even if that merges them
+/// with newer batches.
+const MAX_PENDING_BATCHES: usize = 2;
+
+impl Plugin for RenderMetricsForwardingPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (sender, receiver) = channel();
+        render_app
+            .insert_resource(MetricsRegistry::new())
+            .insert_resource(RenderMetricsSender {
+                sender,
+                n_descriptions_sent: 0,
+                sent_counters: default(),
+                sent_gauges: default(),
+            })
+            .add_systems(Render, send_render_metrics.in_set(RenderSet::Cleanup));
+        if let Some(registry) = app.world().get_resource::<MetricsRegistry>() {
+            registry.describe_many(&[(
+                KeyName::from_const_str(RENDER_FRAME_LATENCY),
+                MetricKind::Gauge,
+                Some(Unit::Count),
+                "How many frames behind the main world the forwarded render metrics are",
+            )]);
+        }
+        app.insert_resource(RenderMetricsReceiver {
+            receiver: Mutex::new(receiver),
+            pending: default(),
+        })
+        .add_systems(First, receive_render_metrics);
+    }
+}
+
+fn send_render_metrics(
+    registry: Res<MetricsRegistry>,
+    frame_count: Option<Res<FrameCount>>,
+    mut sender: ResMut<RenderMetricsSender>,
+) {
+    let mut batch = Vec::new();
+
+    let descriptions = registry.descriptions();
+    if descriptions.len() != sender.n_descriptions_sent {
+        sender.n_descriptions_sent = descriptions.len();
+        batch.extend(
+            descriptions
+                .into_iter()
+                .map(|(k, d)| ForwardedMetric::Description(k, d)),
+        );
+    }
+
+    let RenderMetricsSender {
+        sent_counters,
+        sent_gauges,
+        ..
+    } = &mut *sender;
+    let storage = registry.storage();
+    storage.visit_counters(|key, counter| {
+        let value = counter.load(Ordering::Relaxed);
+        let sent = sent_counters.entry(key.clone()).or_default();
+        // Counters set to a lower absolute value count from there.
+        let increment = value.saturating_sub(*sent);
+        *sent = value;
+        if increment > 0 {
+            batch.push(ForwardedMetric::Counter(key.clone(), increment));
+        }
+    });
+    storage.visit_gauges(|key, gauge| {
+        let bits = gauge.load(Ordering::Relaxed);
+        if sent_gauges.insert(key.clone(), bits) != Some(bits) {
+            batch.push(ForwardedMetric::Gauge(key.clone(), bits));
+        }
+    });
+    storage.visit_histograms(|key, histogram| {
+        let mut samples = Vec::new();
+        histogram.clear_with(|block| samples.extend_from_slice(block));
+        if !samples.is_empty() {
+            batch.push(ForwardedMetric::Histogram(key.clone(), samples));
+        }
+    });
+
+    // The main world might have been dropped.
+    _ = sender.sender.send(RenderMetricsBatch {
+        frame: frame_count.map(|f| f.0),
+        metrics: batch,
+    });
+}
+
+fn receive_render_metrics(
+    registry: Res<MetricsRegistry>,
+    frame_count: Option<Res<FrameCount>>,
+    mut receiver: ResMut<RenderMetricsReceiver>,
+) {
+    let RenderMetricsReceiver { receiver, pending } = &mut *receiver;
+    pending.extend(receiver.get_mut().unwrap().try_iter());
+
+    while pending.len() > MAX_PENDING_BATCHES {
+        let batch = pending.pop_front().unwrap();
+        apply_batch(&registry, batch.metrics);
+    }
+    let Some(batch) = pending.pop_front() else {
+        return;
+    };
+    if let (Some(frame), Some(frame_count)) = (batch.frame, frame_count) {
+        let latency = frame_count.0.wrapping_sub(frame);
+        registry
+            .get_or_create_gauge(&Key::from_static_name(RENDER_FRAME_LATENCY))
+            .store(f64::from(latency).to_bits(), Ordering::Relaxed);
+    }
+    apply_batch(&registry, batch.metrics);
+}
+
+fn apply_batch(registry: &MetricsRegistry, batch: Vec<ForwardedMetric>) {
+    for metric in batch {
+        match metric {
+            ForwardedMetric::Counter(key, increment) => {
+                registry
+                    .get_or_create_counter(&key)
+                    .fetch_add(increment, Ordering::Relaxed);
+            }
+            ForwardedMetric::Gauge(key, bits) => {
+                registry
+                    .get_or_create_gauge(&key)
+                    .store(bits, Ordering::Relaxed);
+            }
+            ForwardedMetric::Histogram(key, samples) => {
+                let histogram = registry.get_or_create_histogram(&key);
+                for sample in samples {
+                    histogram.push(sample);
+                }
+            }
+            ForwardedMetric::Description(key, description) => {
+                registry.add_description_if_missing(key, description);
+            }
+        }
+    }
+}
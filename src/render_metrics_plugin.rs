@@ -1,27 +1,120 @@
-use bevy::{prelude::*, render::view::VisibleEntities};
-use metrics::{describe_gauge, gauge, Unit};
+use crate::{
+    core_metrics::names::*,
+    registry::{describe_many, MetricsRegistry},
+    RenderMetricsForwardingPlugin,
+};
+use bevy::{
+    prelude::*,
+    render::{view::VisibleEntities, Render, RenderApp, RenderSet},
+};
+use metrics::{gauge, Key, KeyName, Unit};
+use metrics_util::MetricKind;
+use std::time::Instant;
 
 /// Provides rendering metrics like visible entities, etc.
+///
+/// This also times the phases of the render schedule and forwards them from
+/// the render world with [`RenderMetricsForwardingPlugin`], which is added if
+/// it's missing. Add both after `RenderPlugin`, e.g. after `DefaultPlugins`:
+/// if the forwarding plugin was added before the render app existed, the
+/// render phases aren't timed and a warning is logged. All phase timings
+/// are histograms in milliseconds:
+///
+/// - `render::setup_time`: applying extracted commands, preparing assets and managing views
+/// - `render::queue_time`: queueing and sorting render phases
+/// - `render::prepare_time`: preparing GPU resources and bind groups
+/// - `render::render_time`: recording and submitting render graph commands
+///
+/// The extract schedule runs on the main world's thread while the main world
+/// is blocked, so its duration shows up in the main world's frame time
+/// instead.
 pub struct RenderMetricsPlugin;
 
 impl Plugin for RenderMetricsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, describe_render_metrics)
             .add_systems(Update, update_render_metrics);
+
+        if !app.is_plugin_added::<RenderMetricsForwardingPlugin>() {
+            app.add_plugins(RenderMetricsForwardingPlugin);
+        }
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let Some(registry) = render_app.world().get_resource::<MetricsRegistry>() else {
+            warn!(
+                "RenderMetricsPlugin: the render world has no MetricsRegistry, because \
+                 RenderMetricsForwardingPlugin was added before RenderPlugin. Add it after \
+                 RenderPlugin (e.g. after DefaultPlugins) to time the render phases"
+            );
+            return;
+        };
+        describe_render_phases(registry);
+        render_app.init_resource::<RenderPhaseTimer>().add_systems(
+            Render,
+            (
+                start_render_phases.before(RenderSet::ExtractCommands),
+                end_render_phase(RENDER_SETUP_TIME)
+                    .after(RenderSet::ManageViews)
+                    .before(RenderSet::Queue),
+                end_render_phase(RENDER_QUEUE_TIME)
+                    .after(RenderSet::PhaseSort)
+                    .before(RenderSet::Prepare),
+                end_render_phase(RENDER_PREPARE_TIME)
+                    .after(RenderSet::Prepare)
+                    .before(RenderSet::Render),
+                end_render_phase(RENDER_RENDER_TIME)
+                    .after(RenderSet::Render)
+                    .before(RenderSet::Cleanup),
+            ),
+        );
     }
 }
 
 fn describe_render_metrics() {
-    describe_gauge!(
-        "visible_2d_entities",
-        Unit::Count,
-        "The number of entities visible to some 2D camera"
-    );
-    describe_gauge!(
-        "visible_3d_entities",
-        Unit::Count,
-        "The number of entities visible to some 3D camera"
-    );
+    describe_many(&[
+        (
+            KeyName::from_const_str(VISIBLE_2D_ENTITIES),
+            MetricKind::Gauge,
+            Some(Unit::Count),
+            "The number of entities visible to some 2D camera",
+        ),
+        (
+            KeyName::from_const_str(VISIBLE_3D_ENTITIES),
+            MetricKind::Gauge,
+            Some(Unit::Count),
+            "The number of entities visible to some 3D camera",
+        ),
+    ]);
+}
+
+fn describe_render_phases(registry: &MetricsRegistry) {
+    let describe = |name, text| {
+        (
+            KeyName::from_const_str(name),
+            MetricKind::Histogram,
+            Some(Unit::Milliseconds),
+            text,
+        )
+    };
+    registry.describe_many(&[
+        describe(
+            RENDER_SETUP_TIME,
+            "Time spent applying extracted commands, preparing assets and managing views",
+        ),
+        describe(
+            RENDER_QUEUE_TIME,
+            "Time spent queueing and sorting render phases",
+        ),
+        describe(
+            RENDER_PREPARE_TIME,
+            "Time spent preparing GPU resources and bind groups",
+        ),
+        describe(
+            RENDER_RENDER_TIME,
+            "Time spent running the render graph and submitting commands",
+        ),
+    ]);
 }
 
 fn update_render_metrics(
@@ -33,15 +126,42 @@ fn update_render_metrics(
             "entity",
             format!("{}v{}", entity.index(), entity.generation()),
         )];
-        let gauge = gauge!("visible_2d_entities", &labels);
-        gauge.set(visible.len::<()>() as f64);
+        let gauge = gauge!(VISIBLE_2D_ENTITIES, &labels);
+        gauge.set(visible.entities.values().map(Vec::len).sum::<usize>() as f64);
     }
     for (entity, visible) in &cameras_3d {
         let labels = [(
             "entity",
             format!("{}v{}", entity.index(), entity.generation()),
         )];
-        let gauge = gauge!("visible_3d_entities", &labels);
-        gauge.set(visible.len::<()>() as f64);
+        let gauge = gauge!(VISIBLE_3D_ENTITIES, &labels);
+        gauge.set(visible.entities.values().map(Vec::len).sum::<usize>() as f64);
+    }
+}
+
+/// The end of the last timed render phase.
+#[derive(Resource)]
+struct RenderPhaseTimer(Instant);
+
+impl Default for RenderPhaseTimer {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+fn start_render_phases(mut timer: ResMut<RenderPhaseTimer>) {
+    timer.0 = Instant::now();
+}
+
+fn end_render_phase(
+    name: &'static str,
+) -> impl FnMut(Res<MetricsRegistry>, ResMut<RenderPhaseTimer>) {
+    let key = Key::from_static_name(name);
+    move |registry, mut timer| {
+        let now = Instant::now();
+        let elapsed = now - std::mem::replace(&mut timer.0, now);
+        registry
+            .get_or_create_histogram(&key)
+            .push(elapsed.as_secs_f64() * 1000.0);
     }
 }
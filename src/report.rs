@@ -0,0 +1,442 @@
+//! A standalone HTML report of the dashboard, to share after a profiling
+//! session.
+//!
+//! The report has a chart of the buffered data of each open plot, drawn as
+//! SVG, with its statistics, spike threshold and annotations, a summary of
+//! the registry and the session's duration and build info. It has no
+//! scripts or external resources.
+//!
+//! ```
+//! # use bevy_metrics_dashboard::{DashboardConfig, DashboardWindow};
+//! # use bevy_metrics_dashboard::metrics_util::MetricKind;
+//! # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+//! # use bevy_metrics_dashboard::report::DashboardReport;
+//! # use std::time::Duration;
+//! let registry = MetricsRegistry::new();
+//! let key = MetricKey::new(metrics::Key::from_static_name("enemies"), MetricKind::Gauge);
+//! let mut window = DashboardWindow::new("Gameplay");
+//! window.add_plot_with_config(
+//!     &registry,
+//!     key.clone(),
+//!     None,
+//!     bevy_metrics_dashboard::plots::MetricPlotConfig::default_for_kind(key.kind),
+//! );
+//!
+//! let report = DashboardReport::from_windows(
+//!     &registry,
+//!     &DashboardConfig::default(),
+//!     [&window],
+//!     Duration::from_secs(90),
+//!     Some("v1.2 <abc123>"),
+//! );
+//! let html = report.to_html();
+//! assert!(html.starts_with("<!DOCTYPE html>"));
+//! assert!(html.contains("<h2>Gameplay</h2>"));
+//! assert!(html.contains("<h3>enemies"));
+//! assert!(html.contains("1m 30s"));
+//! assert!(html.contains("v1.2 &lt;abc123&gt;"));
+//! ```
+//!
+//! Export it from the dashboard toolbar, or with [`ExportReport`], after
+//! adding a [`ReportFile`] with
+//! [`DashboardPlugin::report_file`](crate::DashboardPlugin::report_file).
+//! The report's text isn't translated.
+
+use crate::dashboard_window::{DashboardConfig, DashboardWindow};
+use crate::egui_plot::PlotPoint;
+use crate::number_format::NumberFormat;
+use crate::plots::{MetricPlot, PlotStats, SpikeDetectorConfig};
+use crate::registry::MetricsRegistry;
+use bevy::prelude::*;
+use metrics_util::MetricKind;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Event used to write a [`DashboardReport`] of all [`DashboardWindow`]s to
+/// the [`ReportFile`].
+#[derive(Clone, Event)]
+pub struct ExportReport;
+
+/// The HTML file [`ExportReport`] writes to, replacing any previous report.
+#[derive(Clone, Resource)]
+pub struct ReportFile {
+    path: PathBuf,
+    build_info: Option<String>,
+}
+
+impl ReportFile {
+    #[allow(missing_docs)]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            build_info: None,
+        }
+    }
+
+    /// Describe the build in the report, e.g. with its version and commit.
+    pub fn build_info(mut self, info: impl Into<String>) -> Self {
+        self.build_info = Some(info.into());
+        self
+    }
+
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bevy system that handles [`ExportReport`] events.
+    pub fn export_on_request(
+        mut requests: EventReader<ExportReport>,
+        registry: Res<MetricsRegistry>,
+        config: Res<DashboardConfig>,
+        time: Res<Time<Real>>,
+        file: Res<Self>,
+        windows: Query<&DashboardWindow>,
+    ) {
+        if requests.read().count() == 0 {
+            return;
+        }
+        let report = DashboardReport::from_windows(
+            &registry,
+            &config,
+            windows,
+            time.elapsed(),
+            file.build_info.as_deref(),
+        );
+        match std::fs::write(&file.path, report.to_html()) {
+            Ok(()) => info!("Exported the dashboard report to {}", file.path.display()),
+            Err(e) => error!("{}: {e}", file.path.display()),
+        }
+    }
+}
+
+/// The contents of a report. See the [module docs](self).
+pub struct DashboardReport {
+    session_duration: Duration,
+    build_info: Option<String>,
+    registry: RegistrySummary,
+    windows: Vec<WindowReport>,
+}
+
+struct RegistrySummary {
+    /// The number of registered series of each kind.
+    series: [(MetricKind, usize); 3],
+    undescribed: usize,
+    unregistered: usize,
+}
+
+struct WindowReport {
+    title: String,
+    plots: Vec<PlotReport>,
+}
+
+struct PlotReport {
+    name: String,
+    unit: Option<String>,
+    segments: Vec<Vec<PlotPoint>>,
+    stats: Option<PlotStats>,
+    latest: Option<f64>,
+    spike_detection: Option<SpikeDetectorConfig>,
+    annotations: Vec<(u64, String)>,
+    numbers: NumberFormat,
+}
+
+impl PlotReport {
+    fn new(plot: &MetricPlot, config: &DashboardConfig) -> Self {
+        Self {
+            name: plot.name().to_owned(),
+            unit: plot.displayed_unit(),
+            segments: plot.report_segments(),
+            stats: plot.stats(),
+            latest: plot.latest_value(),
+            spike_detection: plot.spike_detection().cloned(),
+            annotations: plot.annotation_labels(),
+            numbers: plot.style(config).numbers.clone(),
+        }
+    }
+
+    fn number(&self, value: f64) -> String {
+        self.numbers
+            .format_with_unit(value, self.unit.as_deref(), '.')
+    }
+}
+
+impl DashboardReport {
+    /// Capture the buffered data of the plots in `windows`, as of their last
+    /// refresh.
+    pub fn from_windows<'a>(
+        registry: &MetricsRegistry,
+        config: &DashboardConfig,
+        windows: impl IntoIterator<Item = &'a DashboardWindow>,
+        session_duration: Duration,
+        build_info: Option<&str>,
+    ) -> Self {
+        let metrics = registry.all_metrics();
+        let count = |kind| metrics.iter().filter(|m| m.key.kind == kind).count();
+        let audit = registry.audit();
+        let windows = windows
+            .into_iter()
+            .map(|window| WindowReport {
+                title: window.title().to_owned(),
+                plots: window
+                    .plots()
+                    .iter()
+                    .map(|plot| PlotReport::new(plot, config))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            session_duration,
+            build_info: build_info.map(ToOwned::to_owned),
+            registry: RegistrySummary {
+                series: [
+                    (MetricKind::Counter, count(MetricKind::Counter)),
+                    (MetricKind::Gauge, count(MetricKind::Gauge)),
+                    (MetricKind::Histogram, count(MetricKind::Histogram)),
+                ],
+                undescribed: audit.undescribed.len(),
+                unregistered: audit.unregistered.len(),
+            },
+            windows,
+        }
+    }
+
+    /// The report as a standalone HTML document.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Metrics Dashboard Report</title>\n");
+        html.push_str(STYLE);
+        html.push_str("</head>\n<body>\n<h1>Metrics Dashboard Report</h1>\n<table>\n");
+        table_row(
+            &mut html,
+            "Session duration",
+            &duration(self.session_duration),
+        );
+        if let Some(info) = &self.build_info {
+            table_row(&mut html, "Build", &escape(info));
+        }
+        html.push_str("</table>\n\n<h2>Registry</h2>\n<table>\n");
+        for (kind, n) in self.registry.series {
+            let label = match kind {
+                MetricKind::Counter => "Counters",
+                MetricKind::Gauge => "Gauges",
+                MetricKind::Histogram => "Histograms",
+            };
+            table_row(&mut html, label, &n.to_string());
+        }
+        table_row(
+            &mut html,
+            "Metrics without descriptions",
+            &self.registry.undescribed.to_string(),
+        );
+        table_row(
+            &mut html,
+            "Descriptions without metrics",
+            &self.registry.unregistered.to_string(),
+        );
+        html.push_str("</table>\n");
+
+        for window in &self.windows {
+            let _ = write!(html, "\n<h2>{}</h2>\n", escape(&window.title));
+            if window.plots.is_empty() {
+                html.push_str("<p>No plots.</p>\n");
+            }
+            for plot in &window.plots {
+                plot_html(&mut html, plot);
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+const STYLE: &str = "<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+section { margin-bottom: 2em; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+td.number { text-align: right; font-family: monospace; }
+svg { border: 1px solid #ccc; background: #fafafa; }
+svg .line { fill: none; stroke: #1f77b4; stroke-width: 1.5; }
+svg .threshold { stroke: #d62728; stroke-dasharray: 4 3; }
+svg .annotation { stroke: #888; }
+svg text { font-size: 11px; fill: #555; }
+</style>
+";
+
+fn plot_html(html: &mut String, plot: &PlotReport) {
+    html.push_str("<section>\n");
+    let _ = writeln!(html, "<h3>{}</h3>", escape(&plot.name));
+    let threshold = plot
+        .spike_detection
+        .as_ref()
+        .and_then(|s| s.absolute_threshold);
+    if plot.segments.iter().any(|s| !s.is_empty()) {
+        svg_chart(html, plot, threshold);
+    } else {
+        html.push_str("<p>No buffered data.</p>\n");
+    }
+
+    html.push_str("<table>\n");
+    if let Some(latest) = plot.latest {
+        number_row(html, "Latest", &plot.number(latest));
+    }
+    match &plot.stats {
+        Some(stats) => {
+            number_row(html, "Samples", &stats.n_samples().to_string());
+            number_row(html, "Min", &plot.number(stats.min()));
+            number_row(html, "Mean", &plot.number(stats.mean()));
+            for p in [50.0, 95.0, 99.0] {
+                number_row(html, &format!("p{p}"), &plot.number(stats.percentile(p)));
+            }
+            number_row(html, "Max", &plot.number(stats.max()));
+        }
+        None => table_row(html, "Samples", "0"),
+    }
+    match (&plot.spike_detection, threshold) {
+        (_, Some(threshold)) => number_row(html, "Spike threshold", &plot.number(threshold)),
+        (Some(spikes), None) => table_row(
+            html,
+            "Spike threshold",
+            &format!(
+                "{}× the median of the last {} samples",
+                spikes.median_multiple, spikes.median_window
+            ),
+        ),
+        (None, None) => {}
+    }
+    html.push_str("</table>\n");
+
+    if !plot.annotations.is_empty() {
+        html.push_str("<table>\n<tr><th>Sample</th><th>Annotation</th></tr>\n");
+        for (sample, label) in &plot.annotations {
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"number\">{sample}</td><td>{}</td></tr>",
+                escape(label)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</section>\n");
+}
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 200.0;
+/// Room for the axis labels, in pixels.
+const CHART_MARGIN: f64 = 60.0;
+
+/// Draw the segments of `plot` as polylines, with a dashed line at
+/// `threshold` and a vertical line at each annotation.
+fn svg_chart(html: &mut String, plot: &PlotReport, threshold: Option<f64>) {
+    let points = plot.segments.iter().flatten();
+    let (mut x_min, mut x_max) = (f64::INFINITY, f64::NEG_INFINITY);
+    let (mut y_min, mut y_max) = (f64::INFINITY, f64::NEG_INFINITY);
+    for p in points.filter(|p| p.y.is_finite()) {
+        x_min = x_min.min(p.x);
+        x_max = x_max.max(p.x);
+        y_min = y_min.min(p.y);
+        y_max = y_max.max(p.y);
+    }
+    if let Some(threshold) = threshold {
+        y_min = y_min.min(threshold);
+        y_max = y_max.max(threshold);
+    }
+    if x_max <= x_min {
+        x_max = x_min + 1.0;
+    }
+    if y_max <= y_min {
+        // A constant value, drawn in the middle.
+        y_min -= 1.0;
+        y_max += 1.0;
+    }
+    let left = CHART_MARGIN;
+    let width = CHART_WIDTH - CHART_MARGIN;
+    let x = |x: f64| left + (x - x_min) / (x_max - x_min) * width;
+    let y = |y: f64| (1.0 - (y - y_min) / (y_max - y_min)) * (CHART_HEIGHT - 20.0) + 10.0;
+
+    let _ = writeln!(
+        html,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" \
+         height=\"{CHART_HEIGHT}\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\">"
+    );
+    for (value, anchor) in [(y_max, "hanging"), (y_min, "auto")] {
+        let _ = writeln!(
+            html,
+            "<text x=\"4\" y=\"{:.1}\" dominant-baseline=\"{anchor}\">{}</text>",
+            y(value),
+            escape(&plot.number(value))
+        );
+    }
+    if let Some(threshold) = threshold {
+        let _ = writeln!(
+            html,
+            "<line class=\"threshold\" x1=\"{left}\" x2=\"{CHART_WIDTH}\" y1=\"{0:.1}\" y2=\"{0:.1}\"/>",
+            y(threshold)
+        );
+    }
+    for (sample, label) in &plot.annotations {
+        let sample = *sample as f64;
+        if !(x_min..=x_max).contains(&sample) {
+            continue;
+        }
+        let _ = writeln!(
+            html,
+            "<line class=\"annotation\" x1=\"{0:.1}\" x2=\"{0:.1}\" y1=\"0\" y2=\"{CHART_HEIGHT}\">\
+             <title>{1}</title></line>",
+            x(sample),
+            escape(label)
+        );
+    }
+    for segment in &plot.segments {
+        html.push_str("<polyline class=\"line\" points=\"");
+        for p in segment.iter().filter(|p| p.y.is_finite()) {
+            let _ = write!(html, "{:.1},{:.1} ", x(p.x), y(p.y));
+        }
+        html.push_str("\"/>\n");
+    }
+    html.push_str("</svg>\n");
+}
+
+fn table_row(html: &mut String, header: &str, value: &str) {
+    let _ = writeln!(html, "<tr><th>{header}</th><td>{value}</td></tr>");
+}
+
+fn number_row(html: &mut String, header: &str, value: &str) {
+    let _ = writeln!(
+        html,
+        "<tr><th>{header}</th><td class=\"number\">{}</td></tr>",
+        escape(value)
+    );
+}
+
+/// Like `1h 2m 3s`, leaving out leading zero units.
+fn duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m}m {s}s")
+    } else if m > 0 {
+        format!("{m}m {s}s")
+    } else {
+        format!("{s}s")
+    }
+}
+
+/// `text` with the characters that are special in HTML escaped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,89 @@
+//! Evicting metrics that stopped being updated, e.g. those labeled with the
+//! ID of an entity that was despawned long ago.
+//!
+//! With a [`RetentionPolicy`] set on the [`MetricsRegistry`], or passed to
+//! [`RegistryPlugin::retention`](crate::RegistryPlugin::retention), metrics
+//! that weren't [updated](MetricsRegistry::last_updated) for
+//! [`RetentionPolicy::max_idle`] are removed from the registry, and a
+//! [`MetricEvicted`] event is sent for each. Metrics matching one of the
+//! [protected](RetentionPolicy::protect) patterns, and metrics captured in
+//! the [`FrameMetricsSnapshot`], e.g. because they're plotted, are kept.
+//!
+//! An evicted metric is registered again the next time it's recorded with
+//! the `metrics` macros. Handles kept from before, like a
+//! [`CachedCounter`](crate::CachedCounter), keep working, but their writes
+//! are no longer seen by the registry.
+
+use crate::name_pattern::NamePattern;
+use crate::registry::{MetricKey, MetricsRegistry};
+use crate::snapshot::FrameMetricsSnapshot;
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+
+/// When idle metrics are evicted from a [`MetricsRegistry`]. See the
+/// [module docs](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetentionPolicy {
+    /// How long a metric must go without updates before it's evicted.
+    ///
+    /// Idle time is measured by [`MetricsRegistry::sample_activity`], a few
+    /// times per second, so metrics may be kept a little longer.
+    pub max_idle: Duration,
+    /// Metrics whose names match any of these patterns are never evicted.
+    pub protect: Vec<NamePattern>,
+}
+
+impl RetentionPolicy {
+    /// Evict metrics idle for `max_idle`, without protecting any.
+    pub fn new(max_idle: Duration) -> Self {
+        Self {
+            max_idle,
+            protect: Vec::new(),
+        }
+    }
+
+    /// See [`Self::protect`].
+    pub fn protect(mut self, pattern: impl Into<NamePattern>) -> Self {
+        self.protect.push(pattern.into());
+        self
+    }
+
+    /// Whether the metric named `name` is never evicted.
+    pub fn protects(&self, name: &str) -> bool {
+        self.protect.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+/// Event sent by the [`RegistryPlugin`](crate::RegistryPlugin) when a metric
+/// is evicted by the [`RetentionPolicy`].
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct MetricEvicted {
+    #[allow(missing_docs)]
+    pub key: MetricKey,
+    /// How long the metric went without updates.
+    pub idle: Duration,
+}
+
+/// How often [`evict_idle_metrics`] checks for idle metrics.
+const EVICTION_PERIOD: Duration = Duration::from_secs(1);
+
+/// Bevy system that calls [`MetricsRegistry::evict_idle`], keeping metrics
+/// captured in the [`FrameMetricsSnapshot`], and sends a [`MetricEvicted`]
+/// event for each evicted metric.
+///
+/// The [`RegistryPlugin`](crate::RegistryPlugin) runs it after sampling
+/// activity, about once a second.
+pub fn evict_idle_metrics(
+    registry: Res<MetricsRegistry>,
+    snapshot: Option<Res<FrameMetricsSnapshot>>,
+    mut events: EventWriter<MetricEvicted>,
+    mut last: Local<Option<Instant>>,
+) {
+    if last.is_some_and(|last| last.elapsed() < EVICTION_PERIOD) {
+        return;
+    }
+    *last = Some(Instant::now());
+    let evicted =
+        registry.evict_idle(|key| snapshot.as_ref().is_some_and(|s| s.get(key).is_some()));
+    events.write_batch(evicted);
+}
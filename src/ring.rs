@@ -5,6 +5,7 @@ use std::collections::VecDeque;
 pub struct Ring<T> {
     elements: VecDeque<T>,
     max_len: usize,
+    n_pushed: u64,
 }
 
 impl<T: Clone + Default> Ring<T> {
@@ -12,9 +13,23 @@ impl<T: Clone + Default> Ring<T> {
         Self {
             elements: VecDeque::with_capacity(max_len),
             max_len,
+            n_pushed: 0,
         }
     }
 
+    /// An empty ring whose first element will have `index`, to align it
+    /// with another ring.
+    pub fn starting_at(max_len: usize, index: u64) -> Self {
+        Self {
+            n_pushed: index,
+            ..Self::new(max_len)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
     pub fn max_len(&self) -> usize {
         self.max_len
     }
@@ -31,19 +46,42 @@ impl<T: Clone + Default> Ring<T> {
         }
     }
 
+    /// The total number of elements ever pushed, including overwritten ones.
+    pub fn n_pushed(&self) -> u64 {
+        self.n_pushed
+    }
+
+    /// The index (in push order) of the oldest retained element.
+    pub fn first_index(&self) -> u64 {
+        self.n_pushed - self.elements.len() as u64
+    }
+
     pub fn latest(&self) -> Option<&T> {
         self.elements.front()
     }
 
+    /// The element with this index (in push order), if it's retained.
+    pub fn get(&self, index: u64) -> Option<&T> {
+        let age = self.n_pushed.checked_sub(index + 1)?;
+        self.elements.get(usize::try_from(age).ok()?)
+    }
+
+    /// The element with this index (in push order), if it's retained.
+    pub fn get_mut(&mut self, index: u64) -> Option<&mut T> {
+        let age = self.n_pushed.checked_sub(index + 1)?;
+        self.elements.get_mut(usize::try_from(age).ok()?)
+    }
+
     /// Overwrites the oldest element if capacity limit is reached.
     pub fn push(&mut self, elem: T) {
         while self.elements.len() >= self.max_len {
             self.elements.pop_back();
         }
         self.elements.push_front(elem);
+        self.n_pushed += 1;
     }
 
-    pub fn iter_chronological(&self) -> impl Iterator<Item = &T> {
+    pub fn iter_chronological(&self) -> impl DoubleEndedIterator<Item = &T> {
         self.elements.iter().rev()
     }
 
@@ -51,9 +89,9 @@ impl<T: Clone + Default> Ring<T> {
     where
         T: num_traits::NumCast,
     {
-        (0..)
+        (self.first_index()..)
             .zip(self.iter_chronological().cloned())
-            .map(|(i, y)| [i.into(), num_traits::cast(y).unwrap()].into())
+            .map(|(i, y)| [i as f64, num_traits::cast(y).unwrap()].into())
             .collect()
     }
 }
@@ -0,0 +1,270 @@
+use crate::core_metrics::names::{SAMPLING_LATENCY, SAMPLING_PROBE};
+use crate::registry::{FrameId, MetricKey, MetricsRegistry};
+use crate::snapshot::{FrameMetricsSnapshot, SampleMetricsSet};
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
+use metrics::{Key, KeyName, Unit};
+use metrics_util::MetricKind;
+use std::{fmt, sync::atomic::Ordering};
+
+/// Measures how many frames pass between recording a metric and sampling it
+/// into plots, to find system ordering problems.
+///
+/// Every frame, the number of the frame is written to the
+/// `dashboard/sampling_probe` gauge in the probe schedule, [`Update`] by
+/// default. When the dashboard samples its metrics, the probe's value is
+/// compared with the frame of the [`FrameMetricsSnapshot`], and the
+/// difference is written to the `dashboard/sampling_latency` gauge and the
+/// [`SamplingLatency`] resource. Zero means the value was sampled in the
+/// frame it was recorded. A warning is logged when the latency exceeds
+/// [`Self::max_latency`].
+///
+/// Move the probe to where the app records its own metrics with
+/// [`Self::probe_in`] to check their latency, e.g. in tests with
+/// [`validate_sampling_latency`].
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin), and the
+/// [`DashboardPlugin`](crate::DashboardPlugin) or
+/// [`HeadlessDashboardPlugin`](crate::HeadlessDashboardPlugin).
+pub struct SamplingLatencyPlugin {
+    probe_schedule: InternedScheduleLabel,
+    sample_schedule: InternedScheduleLabel,
+    max_latency: u64,
+}
+
+impl Default for SamplingLatencyPlugin {
+    fn default() -> Self {
+        Self {
+            probe_schedule: Update.intern(),
+            sample_schedule: Last.intern(),
+            max_latency: 1,
+        }
+    }
+}
+
+impl SamplingLatencyPlugin {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the probe in `schedule`.
+    pub fn probe_in(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.probe_schedule = schedule.intern();
+        self
+    }
+
+    /// Check the probe in `schedule`, which must be the one passed to
+    /// [`DashboardPlugin::sample_metrics_in`](crate::DashboardPlugin::sample_metrics_in),
+    /// if any.
+    pub fn sample_metrics_in(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.sample_schedule = schedule.intern();
+        self
+    }
+
+    /// Log a warning when the probe is sampled more than `frames` frames
+    /// after it was written. Defaults to one.
+    pub fn max_latency(mut self, frames: u64) -> Self {
+        self.max_latency = frames;
+        self
+    }
+}
+
+impl Plugin for SamplingLatencyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SamplingLatency {
+            max_allowed: self.max_latency,
+            ..default()
+        })
+        .add_systems(Startup, (describe_sampling_latency, watch_probe))
+        .add_systems(self.probe_schedule, write_probe)
+        .add_systems(
+            self.sample_schedule,
+            check_probe
+                .in_set(SampleMetricsSet)
+                .after(FrameMetricsSnapshot::capture_system),
+        );
+    }
+}
+
+/// The latencies measured by the [`SamplingLatencyPlugin`], in frames.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct SamplingLatency {
+    latest: Option<u64>,
+    max: Option<u64>,
+    max_allowed: u64,
+    /// Whether the latest latency was logged as exceeding the maximum.
+    warned: bool,
+}
+
+impl SamplingLatency {
+    /// The latency of the latest sample of the probe, if it was sampled.
+    pub fn latest(&self) -> Option<u64> {
+        self.latest
+    }
+
+    /// The highest latency measured so far.
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// See [`SamplingLatencyPlugin::max_latency`].
+    pub fn max_allowed(&self) -> u64 {
+        self.max_allowed
+    }
+
+    fn observe(&mut self, latency: u64) {
+        self.latest = Some(latency);
+        self.max = self.max.max(Some(latency));
+        let exceeded = latency > self.max_allowed;
+        if exceeded && !self.warned {
+            warn!(
+                "Metrics are sampled {latency} frames after they're recorded, more than the \
+                 expected {}. Check the ordering of the systems that record metrics and the \
+                 SampleMetricsSet.",
+                self.max_allowed
+            );
+        }
+        self.warned = exceeded;
+    }
+}
+
+/// Why [`validate_sampling_latency`] failed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SamplingLatencyError {
+    /// The app has no [`SamplingLatencyPlugin`].
+    MissingPlugin,
+    /// The probe was never sampled, e.g. because the app has no
+    /// [`FrameMetricsSnapshot`] or [`FrameId`].
+    NotSampled,
+    /// The probe was sampled later than [`SamplingLatencyPlugin::max_latency`].
+    TooLate {
+        #[allow(missing_docs)]
+        latency: u64,
+        #[allow(missing_docs)]
+        max_allowed: u64,
+    },
+}
+
+impl fmt::Display for SamplingLatencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPlugin => write!(f, "the app has no SamplingLatencyPlugin"),
+            Self::NotSampled => write!(f, "the sampling probe was never sampled"),
+            Self::TooLate {
+                latency,
+                max_allowed,
+            } => write!(
+                f,
+                "metrics were sampled {latency} frames after they were recorded, \
+                 more than the allowed {max_allowed}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SamplingLatencyError {}
+
+/// Update `app` for `frames` frames, and return the highest latency its
+/// [`SamplingLatencyPlugin`] measured, or an error if it's too high.
+///
+/// ```
+/// # use bevy::{ecs::schedule::ScheduleLabel, prelude::*, time::TimePlugin};
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::{
+/// #     validate_sampling_latency, HeadlessDashboardPlugin, RegistryPlugin,
+/// #     SamplingLatencyError, SamplingLatencyPlugin,
+/// # };
+/// fn app(record: impl ScheduleLabel, sample: impl ScheduleLabel + Clone) -> App {
+///     let mut app = App::new();
+///     app.add_plugins((TaskPoolPlugin::default(), TimePlugin))
+///         .add_plugins(RegistryPlugin::local(MetricsRegistry::new()))
+///         .add_plugins(HeadlessDashboardPlugin::new().sample_metrics_in(sample.clone()))
+///         .add_plugins(
+///             SamplingLatencyPlugin::new()
+///                 .probe_in(record)
+///                 .sample_metrics_in(sample)
+///                 .max_latency(0),
+///         );
+///     app
+/// }
+///
+/// // Metrics recorded in `Update` are sampled in the same frame.
+/// assert_eq!(validate_sampling_latency(&mut app(Update, Last), 5), Ok(0));
+/// // Metrics recorded after sampling are a frame late.
+/// assert_eq!(
+///     validate_sampling_latency(&mut app(Last, PostUpdate), 5),
+///     Err(SamplingLatencyError::TooLate {
+///         latency: 1,
+///         max_allowed: 0
+///     })
+/// );
+/// ```
+pub fn validate_sampling_latency(
+    app: &mut App,
+    frames: usize,
+) -> Result<u64, SamplingLatencyError> {
+    if !app.world().contains_resource::<SamplingLatency>() {
+        return Err(SamplingLatencyError::MissingPlugin);
+    }
+    for _ in 0..frames {
+        app.update();
+    }
+    let latency = app.world().resource::<SamplingLatency>();
+    let max = latency.max().ok_or(SamplingLatencyError::NotSampled)?;
+    if max > latency.max_allowed {
+        return Err(SamplingLatencyError::TooLate {
+            latency: max,
+            max_allowed: latency.max_allowed,
+        });
+    }
+    Ok(max)
+}
+
+fn probe_key() -> MetricKey {
+    MetricKey::new(Key::from_static_name(SAMPLING_PROBE), MetricKind::Gauge)
+}
+
+fn describe_sampling_latency(registry: Res<MetricsRegistry>) {
+    registry.describe_many(&[
+        (
+            KeyName::from_const_str(SAMPLING_PROBE),
+            MetricKind::Gauge,
+            Some(Unit::Count),
+            "The frame in which the sampling latency probe was last written",
+        ),
+        (
+            KeyName::from_const_str(SAMPLING_LATENCY),
+            MetricKind::Gauge,
+            Some(Unit::Count),
+            "How many frames pass between recording a metric and sampling it",
+        ),
+    ]);
+}
+
+fn watch_probe(mut snapshot: ResMut<FrameMetricsSnapshot>) {
+    snapshot.watch(probe_key());
+}
+
+fn write_probe(registry: Res<MetricsRegistry>, frame: Res<FrameId>) {
+    registry
+        .get_or_create_gauge(&probe_key().key)
+        .store((frame.0 as f64).to_bits(), Ordering::Relaxed);
+}
+
+fn check_probe(
+    registry: Res<MetricsRegistry>,
+    snapshot: Res<FrameMetricsSnapshot>,
+    mut latency: ResMut<SamplingLatency>,
+) {
+    let (Some(frame), Some(probe)) = (snapshot.frame(), snapshot.gauge(&probe_key().key)) else {
+        return;
+    };
+    let frames = frame.0.saturating_sub(probe as u64);
+    latency.observe(frames);
+    registry
+        .get_or_create_gauge(&Key::from_static_name(SAMPLING_LATENCY))
+        .store((frames as f64).to_bits(), Ordering::Relaxed);
+}
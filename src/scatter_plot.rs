@@ -0,0 +1,445 @@
+//! Widget for plotting the latest values of two metrics against each other.
+
+use crate::dashboard_window::CachedPlotConfigs;
+use crate::egui::{DragValue, Ui};
+use crate::egui_plot::{Line, Plot, PlotPoint, PlotPoints, Points};
+use crate::latest_value::LatestValue;
+use crate::localization::{DashboardLocalization, UiText};
+use crate::plot_style::PlotStyle;
+use crate::registry::{MetricKey, MetricsRegistry};
+use crate::search_bar::{MetricSearchBar, SearchSelection};
+use crate::snapshot::FrameMetricsSnapshot;
+use crate::theme::ThemePalette;
+use crate::widget::{DashboardWidget, WidgetContext};
+use bevy::prelude::Reflect;
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Configuration for one [`ScatterPlot`].
+#[derive(Clone, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct ScatterPlotConfig {
+    /// The metric on the horizontal axis.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "layout", serde(with = "crate::layout::optional_metric_key"))]
+    pub x: Option<MetricKey>,
+    /// The metric on the vertical axis.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "layout", serde(with = "crate::layout::optional_metric_key"))]
+    pub y: Option<MetricKey>,
+    /// How many seconds points are kept for.
+    pub retention: f64,
+    /// How many seconds before their removal points start fading out.
+    pub fade: f64,
+    /// If true, draws a least-squares trend line and shows the Pearson
+    /// correlation coefficient.
+    pub trend_line: bool,
+}
+
+impl Default for ScatterPlotConfig {
+    fn default() -> Self {
+        Self {
+            x: None,
+            y: None,
+            retention: 10.0,
+            fade: 5.0,
+            trend_line: true,
+        }
+    }
+}
+
+impl ScatterPlotConfig {
+    /// The key of this config in the [`CachedPlotConfigs`], if both axes are
+    /// chosen.
+    pub fn axes(&self) -> Option<(MetricKey, MetricKey)> {
+        Some((self.x.clone()?, self.y.clone()?))
+    }
+}
+
+/// A scatter plot with one point per frame at the latest values of two
+/// metrics.
+///
+/// Counters contribute their increment since the last update, gauges their
+/// current value, and histograms the mean of their samples this frame. A
+/// frame where either metric is stale adds no point: a counter that didn't
+/// increment, a gauge whose value didn't change, or a histogram without
+/// samples.
+///
+/// ```
+/// # use bevy_metrics_dashboard::FrameMetricsSnapshot;
+/// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+/// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+/// # use bevy_metrics_dashboard::scatter_plot::{ScatterPlot, ScatterPlotConfig};
+/// # use metrics::Key;
+/// # use std::sync::atomic::Ordering;
+/// let registry = MetricsRegistry::new();
+/// let entities = MetricKey::new(Key::from_static_name("entities"), MetricKind::Gauge);
+/// let frame_time = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+/// let mut scatter = ScatterPlot::new(
+///     "Cost of Entities",
+///     ScatterPlotConfig {
+///         x: Some(entities.clone()),
+///         y: Some(frame_time.clone()),
+///         ..Default::default()
+///     },
+/// );
+/// let mut snapshot = FrameMetricsSnapshot::default();
+/// // Frame time is only written every other frame.
+/// for (n, time) in [(100.0, 10.0), (110.0, 10.0), (120.0, 12.0), (130.0, 12.0)] {
+///     for (key, value) in [(&entities, n), (&frame_time, time)] {
+///         let gauge = registry.get_or_create_gauge(&key.key);
+///         gauge.store(f64::to_bits(value), Ordering::Relaxed);
+///     }
+///     snapshot.capture(&registry, [&entities, &frame_time]);
+///     scatter.update(&snapshot);
+/// }
+/// let points: Vec<_> = scatter.points().collect();
+/// assert_eq!(points, [[100.0, 10.0], [120.0, 12.0]]);
+/// ```
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+pub struct ScatterPlot {
+    name: String,
+    config: ScatterPlotConfig,
+    #[reflect(ignore)]
+    x: Option<LatestValue>,
+    #[reflect(ignore)]
+    y: Option<LatestValue>,
+    #[reflect(ignore)]
+    points: VecDeque<(Instant, [f64; 2])>,
+    #[reflect(ignore)]
+    x_search_bar: MetricSearchBar,
+    #[reflect(ignore)]
+    y_search_bar: MetricSearchBar,
+}
+
+/// Points are drawn in this many groups of decreasing opacity.
+const FADE_STEPS: usize = 8;
+
+impl ScatterPlot {
+    /// Create a new scatter plot widget.
+    pub fn new(name: impl Into<String>, config: ScatterPlotConfig) -> Self {
+        Self {
+            name: name.into(),
+            x: config.x.clone().map(LatestValue::new),
+            y: config.y.clone().map(LatestValue::new),
+            config,
+            points: VecDeque::new(),
+            x_search_bar: Default::default(),
+            y_search_bar: Default::default(),
+        }
+    }
+
+    /// Name of the widget.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This widget's configuration.
+    pub fn config(&self) -> &ScatterPlotConfig {
+        &self.config
+    }
+
+    /// Sample the latest value of both metrics and add a point if both were
+    /// updated.
+    ///
+    /// This should run in the [`SampleMetricsSet`](crate::SampleMetricsSet),
+    /// like [`MetricPlot::update`](crate::plots::MetricPlot::update). Both
+    /// values are read from the same `snapshot`.
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        let now = Instant::now();
+        for value in [&mut self.x, &mut self.y].into_iter().flatten() {
+            value.update(snapshot);
+        }
+        let latest = |value: &Option<LatestValue>| {
+            value
+                .as_ref()
+                .filter(|value| value.updated())
+                .and_then(LatestValue::latest)
+        };
+        if let (Some(x), Some(y)) = (latest(&self.x), latest(&self.y)) {
+            self.points.push_back((now, [x, y]));
+        }
+
+        let retention = Duration::try_from_secs_f64(self.config.retention).unwrap_or_default();
+        while self
+            .points
+            .front()
+            .is_some_and(|&(time, _)| now - time > retention)
+        {
+            self.points.pop_front();
+        }
+
+        self.x_search_bar.update(snapshot);
+        self.y_search_bar.update(snapshot);
+    }
+
+    /// The plotted points as `[x, y]`, oldest first.
+    pub fn points(&self) -> impl Iterator<Item = [f64; 2]> + '_ {
+        self.points.iter().map(|&(_, point)| point)
+    }
+
+    /// The metrics sampled by [`Self::update`].
+    pub fn consumed_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        [&self.config.x, &self.config.y]
+            .into_iter()
+            .flatten()
+            .chain(self.x_search_bar.previewed_metrics())
+            .chain(self.y_search_bar.previewed_metrics())
+    }
+
+    /// Draw the widget using `ui`.
+    ///
+    /// When both axes are newly chosen, settings are restored from
+    /// `cached_configs`.
+    pub fn draw(
+        &mut self,
+        registry: &MetricsRegistry,
+        cached_configs: &CachedPlotConfigs,
+        style: &PlotStyle,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        let (Some(x), Some(y)) = (&self.x, &self.y) else {
+            ui.weak(l10n.get(UiText::ChooseAxes));
+            ui.collapsing(l10n.get(UiText::Settings), |ui| {
+                self.configure_ui(registry, cached_configs, ui);
+            });
+            return;
+        };
+        let x_title = registry.title(x.key(), 0);
+        let y_title = registry.title(y.key(), 0);
+
+        let fit = self
+            .config
+            .trend_line
+            .then(|| LinearFit::new(self.points.iter().map(|(_, p)| *p)))
+            .flatten();
+        if let Some(fit) = &fit {
+            ui.label(format!(
+                "r = {:.3}, y = {:.3} x + {:.3}",
+                fit.correlation, fit.slope, fit.intercept
+            ));
+        }
+
+        let groups = self.fade_groups();
+        let x_range =
+            self.points
+                .iter()
+                .map(|(_, [x, _])| *x)
+                .fold(None, |range: Option<(f64, f64)>, x| {
+                    Some(range.map_or((x, x), |(min, max)| (min.min(x), max.max(x))))
+                });
+        let plot = Plot::new(&self.name)
+            .allow_scroll(false)
+            .view_aspect(2.0)
+            .x_axis_label(x_title)
+            .y_axis_label(y_title);
+        let palette = ThemePalette::of(ui);
+        style.show(plot, ui, |plot_ui| {
+            for (i, group) in groups.into_iter().enumerate() {
+                if group.is_empty() {
+                    continue;
+                }
+                let alpha = 1.0 - i as f32 / FADE_STEPS as f32;
+                plot_ui.points(
+                    Points::new("samples", PlotPoints::Owned(group))
+                        .radius(2.0)
+                        .color(palette.series[0].gamma_multiply(alpha)),
+                );
+            }
+            if let (Some(fit), Some((min, max))) = (&fit, x_range) {
+                plot_ui.line(
+                    Line::new("trend", vec![[min, fit.at(min)], [max, fit.at(max)]])
+                        .color(palette.series[1]),
+                );
+            }
+        });
+
+        ui.collapsing(l10n.get(UiText::Settings), |ui| {
+            self.configure_ui(registry, cached_configs, ui);
+        });
+    }
+
+    /// Split the points by how far they are faded out.
+    fn fade_groups(&self) -> Vec<Vec<PlotPoint>> {
+        let now = Instant::now();
+        let fade = self.config.fade.clamp(0.0, self.config.retention);
+        let fade_start = self.config.retention - fade;
+        let mut groups = vec![Vec::new(); FADE_STEPS];
+        for &(time, point) in &self.points {
+            let age = (now - time).as_secs_f64();
+            let faded = if fade > 0.0 {
+                ((age - fade_start) / fade).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let i = ((faded * FADE_STEPS as f64) as usize).min(FADE_STEPS - 1);
+            groups[i].push(point.into());
+        }
+        groups
+    }
+
+    fn configure_ui(
+        &mut self,
+        registry: &MetricsRegistry,
+        cached_configs: &CachedPlotConfigs,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.add(
+            DragValue::new(&mut self.config.retention)
+                .prefix(l10n.get(UiText::RetentionPrefix))
+                .suffix(" s")
+                .speed(0.1)
+                .range(0.1..=300.0),
+        );
+        ui.add(
+            DragValue::new(&mut self.config.fade)
+                .prefix(l10n.get(UiText::FadePrefix))
+                .suffix(" s")
+                .speed(0.1)
+                .range(0.0..=self.config.retention),
+        );
+        ui.checkbox(&mut self.config.trend_line, l10n.get(UiText::TrendLine));
+
+        ui.separator();
+
+        let mut changed = false;
+        for (label, key, value, search_bar) in [
+            ("X", &mut self.config.x, &mut self.x, &mut self.x_search_bar),
+            ("Y", &mut self.config.y, &mut self.y, &mut self.y_search_bar),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(l10n.format(UiText::Axis, &[("axis", &label)]));
+                match key {
+                    Some(key) => ui.label(registry.title(key, 0)),
+                    None => ui.weak(l10n.get(UiText::Nothing)),
+                };
+            });
+            if let Some(selected) = search_bar
+                .show(ui, registry)
+                .map(SearchSelection::into_result)
+            {
+                *value = Some(LatestValue::new(selected.key.clone()));
+                *key = Some(selected.key);
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+        self.points.clear();
+        if let Some(cached) = self
+            .config
+            .axes()
+            .and_then(|axes| cached_configs.scatter_plots.get(&axes))
+        {
+            self.config = cached.clone();
+        }
+    }
+}
+
+impl Default for ScatterPlot {
+    fn default() -> Self {
+        Self::new("Scatter", ScatterPlotConfig::default())
+    }
+}
+
+impl DashboardWidget for ScatterPlot {
+    fn id(&self) -> &str {
+        "scatter_plot"
+    }
+
+    fn title(&self, _ctx: &WidgetContext) -> String {
+        self.name.clone()
+    }
+
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext) {
+        self.draw(ctx.registry, ctx.cached_configs, ctx.style, ui);
+    }
+
+    fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        ScatterPlot::update(self, snapshot);
+    }
+
+    fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+        Box::new(ScatterPlot::consumed_metrics(self))
+    }
+
+    fn on_close(&mut self, cached_configs: &mut CachedPlotConfigs) {
+        if let Some(axes) = self.config.axes() {
+            cached_configs
+                .scatter_plots
+                .insert(axes, self.config.clone());
+        }
+    }
+
+    #[cfg(feature = "layout")]
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        RawValue::from_rust(&self.config).map(Some)
+    }
+
+    #[cfg(feature = "layout")]
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        let name = std::mem::take(&mut self.name);
+        *self = Self::new(name, config.into_rust()?);
+        Ok(())
+    }
+
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+}
+
+/// A least-squares line through a set of points.
+struct LinearFit {
+    slope: f64,
+    intercept: f64,
+    /// The Pearson correlation coefficient.
+    correlation: f64,
+}
+
+impl LinearFit {
+    /// Returns `None` if there are fewer than two distinct x values.
+    fn new(points: impl Iterator<Item = [f64; 2]> + Clone) -> Option<Self> {
+        let (n, sum_x, sum_y) = points.clone().fold((0.0, 0.0, 0.0), |(n, sx, sy), [x, y]| {
+            (n + 1.0, sx + x, sy + y)
+        });
+        if n < 2.0 {
+            return None;
+        }
+        let (mean_x, mean_y) = (sum_x / n, sum_y / n);
+        let (sxx, syy, sxy) = points.fold((0.0, 0.0, 0.0), |(sxx, syy, sxy), [x, y]| {
+            let (dx, dy) = (x - mean_x, y - mean_y);
+            (sxx + dx * dx, syy + dy * dy, sxy + dx * dy)
+        });
+        if sxx <= 0.0 {
+            return None;
+        }
+        let slope = sxy / sxx;
+        let correlation = if syy > 0.0 {
+            sxy / (sxx * syy).sqrt()
+        } else {
+            0.0
+        };
+        Some(Self {
+            slope,
+            intercept: mean_y - slope * mean_x,
+            correlation,
+        })
+    }
+
+    fn at(&self, x: f64) -> f64 {
+        self.slope * x + self.intercept
+    }
+}
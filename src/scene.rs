@@ -0,0 +1,195 @@
+use crate::overlay::OverlayCorner;
+use crate::plot_style::PlotStyle;
+use crate::plots::{MetricPlot, MetricPlotConfig};
+use crate::registry::{MetricKey, MetricsRegistry};
+use crate::{CachedPlotConfigs, DashboardWindow};
+use bevy::prelude::*;
+use metrics::{Key, Label};
+
+/// The plots of the [`DashboardWindow`] on the same entity, in a form that
+/// can be saved in a Bevy [`DynamicScene`](https://docs.rs/bevy/latest/bevy/scene/struct.DynamicScene.html),
+/// e.g. by an editor that saves the dashboard with its project.
+///
+/// This is an alternative to [layout files](crate::layout), for apps that
+/// already save their state as scenes. The window itself, its title and
+/// [`DashboardWindowConfig`](crate::DashboardWindowConfig), is saved by its
+/// own reflected component. Sample buffers and other runtime state aren't
+/// saved.
+///
+/// The state isn't kept up to date while the dashboard is used. Run
+/// [`Self::capture_all`] before building the scene, e.g. with
+/// `world.run_system_cached(DashboardSceneState::capture_all)`. When the
+/// component is added to a window, e.g. by spawning the scene, its plots
+/// replace those of the window, unless it has none. See
+/// [`DashboardSceneAppExt::register_dashboard_scene_types`].
+#[derive(Clone, Component, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct DashboardSceneState {
+    /// Plots in the order they were added.
+    pub plots: Vec<ScenePlot>,
+}
+
+/// The saved state of one [`MetricPlot`] in a [`DashboardSceneState`].
+///
+/// Its unit is looked up in the registry when the plot is restored, and
+/// per-plot [display names](MetricPlot::display_names) aren't saved.
+#[derive(Clone, Reflect)]
+pub struct ScenePlot {
+    /// The metric name.
+    pub name: String,
+    /// The metric labels, as key-value pairs.
+    pub labels: Vec<(String, String)>,
+    /// Also determines the metric kind.
+    pub config: MetricPlotConfig,
+    /// See [`MetricPlot::style_override`].
+    pub style_override: Option<PlotStyle>,
+    /// See [`MetricPlot::overlay`].
+    pub overlay: Option<OverlayCorner>,
+    /// See [`MetricPlot::note`].
+    pub note: String,
+    /// See [`MetricPlot::group`].
+    pub group: Option<String>,
+    /// See [`MetricPlot::pause_with_virtual_time`].
+    pub pause_with_virtual_time: Option<bool>,
+}
+
+impl ScenePlot {
+    #[allow(missing_docs)]
+    pub fn from_plot(plot: &MetricPlot) -> Self {
+        let key = &plot.key().key;
+        Self {
+            name: key.name().to_owned(),
+            labels: key
+                .labels()
+                .map(|label| (label.key().to_owned(), label.value().to_owned()))
+                .collect(),
+            config: plot.clone_config(),
+            style_override: plot.style_override().cloned(),
+            overlay: plot.overlay(),
+            note: plot.note().to_owned(),
+            group: plot.group().map(str::to_owned),
+            pause_with_virtual_time: plot.pause_with_virtual_time(),
+        }
+    }
+
+    /// The key of the plotted metric.
+    pub fn key(&self) -> MetricKey {
+        let labels = self
+            .labels
+            .iter()
+            .map(|(key, value)| Label::new(key.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        MetricKey::new(
+            Key::from_parts(self.name.clone(), labels),
+            self.config.kind(),
+        )
+    }
+}
+
+impl DashboardSceneState {
+    /// Capture the plots of `window`.
+    pub fn from_window(window: &DashboardWindow) -> Self {
+        Self {
+            plots: window.plots().iter().map(ScenePlot::from_plot).collect(),
+        }
+    }
+
+    /// Replace the plots of `window` with those in this state. The configs
+    /// of the replaced plots are kept in `cached_configs`.
+    pub fn apply(
+        &self,
+        registry: &MetricsRegistry,
+        cached_configs: &mut CachedPlotConfigs,
+        window: &mut DashboardWindow,
+    ) {
+        window.clear_plots(cached_configs);
+        for saved in &self.plots {
+            let key = saved.key();
+            let unit = registry
+                .get_search_result(key.clone())
+                .description
+                .and_then(|d| d.unit);
+            window.add_plot_with_config(registry, key, unit, saved.config.clone());
+            if let Some(plot) = window.plots_mut().last_mut() {
+                plot.set_style_override(saved.style_override.clone());
+                plot.set_overlay(saved.overlay);
+                plot.set_note(saved.note.clone());
+                plot.set_group(saved.group.clone());
+                plot.set_pause_with_virtual_time(saved.pause_with_virtual_time);
+            }
+        }
+    }
+
+    /// Bevy system that captures the plots of every [`DashboardWindow`] that
+    /// has a [`DashboardSceneState`].
+    pub fn capture_all(mut windows: Query<(&DashboardWindow, &mut Self)>) {
+        for (window, mut state) in &mut windows {
+            *state = Self::from_window(window);
+        }
+    }
+
+    /// Bevy system that [applies](Self::apply) newly added states with
+    /// plots to their windows.
+    pub fn apply_added(
+        registry: Res<MetricsRegistry>,
+        cached_configs: Option<ResMut<CachedPlotConfigs>>,
+        mut windows: Query<(&mut DashboardWindow, &Self), Added<Self>>,
+    ) {
+        let mut fallback = CachedPlotConfigs::default();
+        let cached_configs = match cached_configs {
+            Some(configs) => configs.into_inner(),
+            None => &mut fallback,
+        };
+        for (mut window, state) in &mut windows {
+            if !state.plots.is_empty() {
+                state.apply(&registry, cached_configs, &mut window);
+            }
+        }
+    }
+}
+
+/// Registers the types needed to save dashboards in Bevy scenes.
+pub trait DashboardSceneAppExt {
+    /// Register the reflected types of [`DashboardWindow`] and
+    /// [`DashboardSceneState`], and apply states when they're added, in
+    /// [`PreUpdate`]. Requires the [`RegistryPlugin`](crate::RegistryPlugin).
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use bevy_metrics_dashboard::{
+    /// #     DashboardSceneAppExt, DashboardSceneState, DashboardWindow, RegistryPlugin,
+    /// # };
+    /// # use bevy_metrics_dashboard::plots::{MetricPlotConfig, GaugePlotConfig};
+    /// # use bevy_metrics_dashboard::registry::MetricKey;
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// let mut app = App::new();
+    /// app.add_plugins(RegistryPlugin::local(MetricsRegistry::new()))
+    ///     .register_dashboard_scene_types();
+    /// let registry = app.world().resource::<MetricsRegistry>().clone();
+    ///
+    /// let mut window = DashboardWindow::new("Metrics");
+    /// let key = MetricKey::new(metrics::Key::from_static_name("enemies"), MetricKind::Gauge);
+    /// let config = MetricPlotConfig::Gauge(GaugePlotConfig::dial());
+    /// window.add_plot_with_config(&registry, key, None, config.clone());
+    /// let state = DashboardSceneState::from_window(&window);
+    ///
+    /// // Like spawning a scene with the window and its state.
+    /// let entity = app
+    ///     .world_mut()
+    ///     .spawn((DashboardWindow::new("Metrics"), state))
+    ///     .id();
+    /// app.update();
+    /// let window = app.world().get::<DashboardWindow>(entity).unwrap();
+    /// assert!(window.find_plot("enemies").unwrap().clone_config() == config);
+    /// ```
+    fn register_dashboard_scene_types(&mut self) -> &mut Self;
+}
+
+impl DashboardSceneAppExt for App {
+    fn register_dashboard_scene_types(&mut self) -> &mut Self {
+        self.register_type::<DashboardWindow>()
+            .register_type::<DashboardSceneState>()
+            .add_systems(PreUpdate, DashboardSceneState::apply_added)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::registry::MetricsRegistry;
+use bevy::prelude::*;
+use metrics::LocalRecorderGuard;
+
+/// Keeps a [`MetricsRegistry`] installed as the current thread's recorder,
+/// using [`metrics::set_default_local_recorder`]. The previous recorder is
+/// restored when this is dropped.
+///
+/// Also returned by [`MetricsRegistry::install_as_local`]. Metrics recorded
+/// from other threads still go to the global recorder, so to run a Bevy
+/// system with a guard, use [`with_scoped_recorder`].
+///
+/// ```
+/// # use bevy_metrics_dashboard::{metrics::counter, ScopedRecorderGuard};
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// let registry = MetricsRegistry::new();
+/// {
+///     let _guard = ScopedRecorderGuard::new(&registry);
+///     counter!("frames").increment(1);
+/// }
+/// counter!("dropped").increment(1);
+///
+/// assert!(!registry.fuzzy_search_by_name("frames").is_empty());
+/// assert!(registry.fuzzy_search_by_name("dropped").is_empty());
+/// ```
+pub struct ScopedRecorderGuard<'a> {
+    _guard: LocalRecorderGuard<'a>,
+}
+
+impl<'a> ScopedRecorderGuard<'a> {
+    /// Install `registry` as the current thread's recorder.
+    pub fn new(registry: &'a MetricsRegistry) -> Self {
+        Self {
+            _guard: metrics::set_default_local_recorder(registry),
+        }
+    }
+}
+
+/// Wrap `system` so that metrics it records go to the world's
+/// [`MetricsRegistry`] resource instead of the global recorder, by holding a
+/// [`ScopedRecorderGuard`] while it runs.
+///
+/// This is useful when one process has several registries, e.g. one per
+/// [`App`] in tests, or a separate registry in a sub-app. The wrapped system
+/// runs exclusively, because the local recorder only applies to the current
+/// thread.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::{metrics::counter, with_scoped_recorder, RegistryPlugin};
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// fn count_frames() {
+///     counter!("frames").increment(1);
+/// }
+///
+/// let registry = MetricsRegistry::new();
+/// let mut app = App::new();
+/// app.add_plugins(RegistryPlugin::local(registry.clone()))
+///     .add_systems(Update, with_scoped_recorder(count_frames));
+/// app.update();
+/// # assert!(!registry.fuzzy_search_by_name("frames").is_empty());
+/// ```
+pub fn with_scoped_recorder<M>(system: impl IntoSystem<(), (), M>) -> impl FnMut(&mut World) {
+    let mut system = IntoSystem::into_system(system);
+    let mut initialized = false;
+    move |world: &mut World| {
+        if !initialized {
+            system.initialize(world);
+            initialized = true;
+        }
+        let registry = world.resource::<MetricsRegistry>().clone();
+        let _guard = ScopedRecorderGuard::new(&registry);
+        system.run((), world);
+    }
+}
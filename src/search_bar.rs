@@ -1,92 +1,527 @@
 //! Widget for search the metrics registry.
 
-use crate::egui::{TextEdit, Ui};
+use crate::egui::{
+    text::{CCursor, CCursorRange, LayoutJob},
+    ComboBox, Key, TextEdit, TextFormat, Ui,
+};
 use crate::{
+    context_menu::metric_context_menu,
+    dashboard_window::DEFAULT_UI_REFRESH_RATE,
     dropdown_list::dropdown_list,
-    registry::{MetricsRegistry, SearchResult},
+    localization::{DashboardLocalization, UiText},
+    preview::PreviewSampler,
+    registry::{MetricKey, MetricUnit, MetricsRegistry, RegistryGeneration, SearchResult},
+    snapshot::FrameMetricsSnapshot,
+    theme::ThemePalette,
+};
+use bevy::{
+    platform::collections::HashMap,
+    prelude::{Reflect, ReflectDefault},
+    tasks::{block_on, AsyncComputeTaskPool, Task},
 };
-use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use metrics_util::MetricKind;
 use std::time::{Duration, Instant};
 
 /// A widget that searches the [`MetricsRegistry`] with fuzzy string matching.
-pub struct SearchBar {
+///
+/// This can be used on its own in any `egui` UI. It owns the query, the
+/// filters, and the latest results. To show previews when hovering results,
+/// call [`Self::update`] every frame and capture
+/// [`Self::previewed_metrics`] in the
+/// [`FrameMetricsSnapshot`](crate::FrameMetricsSnapshot), e.g. with
+/// [`FrameMetricsSnapshot::watch`](crate::FrameMetricsSnapshot::watch).
+///
+/// The query and timings are reflected. A query set through reflection is
+/// searched for on the next draw.
+#[derive(Reflect)]
+#[reflect(Default)]
+pub struct MetricSearchBar {
     search_input: String,
+    #[reflect(ignore)]
     input_dirty: bool,
+    #[reflect(ignore)]
+    last_input_time: Instant,
+    #[reflect(ignore)]
     last_search_time: Instant,
-    search_task: Option<Task<Vec<SearchResult>>>,
+    debounce: Duration,
+    #[reflect(ignore)]
+    search_task: Option<(SearchedQuery, Task<Vec<SearchResult>>)>,
+    #[reflect(ignore)]
     search_results: Vec<SearchResult>,
+    /// What [`Self::search_results`] were searched for.
+    #[reflect(ignore)]
+    results_query: Option<SearchedQuery>,
+    #[reflect(ignore)]
+    previews: PreviewSampler,
+    #[reflect(ignore)]
+    filters: SearchFilters,
+    #[reflect(ignore)]
+    focus_requested: bool,
+    /// The values shown next to the visible results, read since
+    /// [`Self::values_read`].
+    #[reflect(ignore)]
+    values: HashMap<MetricKey, Option<f64>>,
+    #[reflect(ignore)]
+    values_read: Instant,
+    value_refresh: Duration,
+}
+
+/// A query, and the [`RegistryGeneration`] it was searched in.
+#[derive(Clone)]
+struct SearchedQuery {
+    query: String,
+    generation: RegistryGeneration,
+}
+
+#[derive(Default)]
+struct SearchFilters {
+    only_active: bool,
+    kind: Option<MetricKind>,
+    unit: Option<MetricUnit>,
+}
+
+impl SearchFilters {
+    fn passes(&self, registry: &MetricsRegistry, result: &SearchResult) -> bool {
+        let unit = result.description.as_ref().and_then(|d| d.unit.as_ref());
+        (!self.only_active || is_active(age(registry, &result.key)))
+            && self.kind.is_none_or(|kind| result.key.kind == kind)
+            && self.unit.as_ref().is_none_or(|filter| unit == Some(filter))
+    }
+}
+
+/// A search result chosen in a [`MetricSearchBar`].
+#[derive(Clone)]
+pub enum SearchSelection {
+    /// The result was clicked.
+    Clicked(SearchResult),
+    /// Enter was pressed on the highlighted result, which is the first one
+    /// unless moved with the arrow keys.
+    Submitted(SearchResult),
 }
 
-impl Default for SearchBar {
+impl SearchSelection {
+    #[allow(missing_docs)]
+    pub fn result(&self) -> &SearchResult {
+        match self {
+            Self::Clicked(result) | Self::Submitted(result) => result,
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn into_result(self) -> SearchResult {
+        match self {
+            Self::Clicked(result) | Self::Submitted(result) => result,
+        }
+    }
+
+    /// The key of the selected metric.
+    pub fn key(&self) -> &MetricKey {
+        &self.result().key
+    }
+}
+
+/// Metrics updated within this long are shown as active.
+const ACTIVE_AGE: Duration = Duration::from_secs(1);
+
+/// The default [`MetricSearchBar::set_debounce`] and
+/// [`DashboardConfig::search_debounce`](crate::DashboardConfig::search_debounce).
+pub const DEFAULT_SEARCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+impl Default for MetricSearchBar {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl SearchBar {
+impl MetricSearchBar {
     /// Create an empty search bar.
     pub fn new() -> Self {
         Self {
             search_input: Default::default(),
             input_dirty: true,
+            last_input_time: Instant::now(),
             last_search_time: Instant::now(),
+            debounce: DEFAULT_SEARCH_DEBOUNCE,
             search_task: Default::default(),
             search_results: Default::default(),
+            results_query: None,
+            previews: Default::default(),
+            filters: Default::default(),
+            focus_requested: false,
+            values: Default::default(),
+            values_read: Instant::now(),
+            value_refresh: Duration::from_secs_f64(DEFAULT_UI_REFRESH_RATE.recip()),
         }
     }
 
+    /// Move the keyboard focus to the search box the next time it's shown,
+    /// with the cursor after the query. A non-empty query opens the results.
+    pub fn request_focus(&mut self) {
+        self.focus_requested = true;
+    }
+
+    /// The current search query.
+    pub fn query(&self) -> &str {
+        &self.search_input
+    }
+
+    /// Replace the search query.
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.search_input = query.into();
+        self.input_changed();
+    }
+
+    /// Wait until the query hasn't changed for `debounce` before searching,
+    /// instead of [`DEFAULT_SEARCH_DEBOUNCE`]. Results of the previous query
+    /// are shown dimmed in the meantime.
+    ///
+    /// With zero, a search starts as soon as the query changes, at most four
+    /// times per second.
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+
+    /// Re-read the values shown next to the results at most once per
+    /// `period`, instead of at the [`DEFAULT_UI_REFRESH_RATE`].
+    pub fn set_value_refresh(&mut self, period: Duration) {
+        self.value_refresh = period;
+    }
+
+    fn input_changed(&mut self) {
+        self.input_dirty = true;
+        self.last_input_time = Instant::now();
+    }
+
+    /// Only show results of this kind.
+    pub fn set_kind_filter(&mut self, kind: Option<MetricKind>) {
+        self.filters.kind = kind;
+    }
+
+    /// Only show results with this unit.
+    pub fn set_unit_filter(&mut self, unit: Option<MetricUnit>) {
+        self.filters.unit = unit;
+    }
+
+    /// The latest results, before filtering, sorted by name.
+    pub fn results(&self) -> &[SearchResult] {
+        &self.search_results
+    }
+
+    /// The latest results that pass the filters, as they're listed.
+    pub fn filtered_results<'a>(
+        &'a self,
+        registry: &'a MetricsRegistry,
+    ) -> impl Iterator<Item = &'a SearchResult> {
+        self.search_results
+            .iter()
+            .filter(|s| self.filters.passes(registry, s))
+    }
+
+    /// Whether the latest results were searched for the current query,
+    /// rather than an earlier one.
+    pub fn results_are_current(&self) -> bool {
+        self.results_query
+            .as_ref()
+            .is_some_and(|results| results.query == self.search_input)
+    }
+
+    /// Sample the metrics being previewed by hovering over search results.
+    ///
+    /// This should run in the [`SampleMetricsSet`](crate::SampleMetricsSet),
+    /// like [`MetricPlot::update`](crate::plots::MetricPlot::update).
+    pub fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        self.previews.update(snapshot);
+    }
+
+    /// The metrics sampled by [`Self::update`].
+    pub fn previewed_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        self.previews.keys()
+    }
+
     /// Draw the widget and accept user input.
     ///
     /// If the user selects one of the search results, it will be returned.
-    pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) -> Option<SearchResult> {
+    /// Hovering a result shows a preview of its recent values. Each result has
+    /// a dot that is green if the metric was
+    /// [updated](MetricsRegistry::last_updated) in the last second, the
+    /// characters matching the query are highlighted, and its
+    /// [current value](MetricsRegistry::peek_value) is shown on the right.
+    ///
+    /// The results can be browsed with the arrow keys while the search box has
+    /// focus. Enter selects the highlighted result, and ctrl+enter selects it
+    /// while keeping the results open, e.g. to add several plots in a row.
+    /// Escape clears the query and the focus.
+    pub fn show(&mut self, ui: &mut Ui, registry: &MetricsRegistry) -> Option<SearchSelection> {
         // Draw search box.
+        let l10n = DashboardLocalization::of(ui);
         let maybe_selected = ui
             .horizontal(|ui| {
-                ui.label("Search:");
-                let response = TextEdit::singleline(&mut self.search_input)
-                    .hint_text("metric name")
-                    .show(ui)
-                    .response;
+                ui.label(l10n.get(UiText::SearchPrefix));
+                let mut output = TextEdit::singleline(&mut self.search_input)
+                    .hint_text(l10n.get(UiText::MetricNameHint))
+                    .show(ui);
+                let mut response = output.response;
+                if std::mem::take(&mut self.focus_requested) {
+                    response.request_focus();
+                    let end = CCursor::new(self.search_input.chars().count());
+                    output
+                        .state
+                        .cursor
+                        .set_char_range(Some(CCursorRange::one(end)));
+                    output.state.store(ui.ctx(), response.id);
+                    if !self.search_input.is_empty() {
+                        response.mark_changed();
+                    }
+                }
+                if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Escape)) {
+                    self.search_input.clear();
+                    self.search_results.clear();
+                    self.search_task = None;
+                    self.results_query = None;
+                }
                 if response.changed() {
-                    self.input_dirty = true;
+                    self.input_changed();
                 }
+                ui.checkbox(&mut self.filters.only_active, l10n.get(UiText::OnlyActive));
+                self.filters_ui(ui);
+
+                let matcher = SkimMatcherV2::default();
+                let palette = ThemePalette::of(ui);
+                if self.values_read.elapsed() >= self.value_refresh {
+                    self.values.clear();
+                    self.values_read = Instant::now();
+                }
+                let Self {
+                    search_input,
+                    search_results,
+                    results_query,
+                    previews,
+                    filters,
+                    values,
+                    ..
+                } = self;
+                let stale = results_query
+                    .as_ref()
+                    .is_none_or(|results| results.query != *search_input);
                 dropdown_list(
                     response,
                     ui,
                     "metric-search-dropdown",
-                    self.search_results.iter(),
-                    |&s| s.detailed_text(None),
+                    search_results
+                        .iter()
+                        .filter(|s| filters.passes(registry, s)),
+                    |&s| {
+                        let highlighted = matcher
+                            .fuzzy_indices(s.name(), search_input)
+                            .map(|(_, indices)| indices)
+                            .unwrap_or_default();
+                        let text = with_freshness_dot(
+                            s.highlighted_text(None, &highlighted, &palette, &l10n),
+                            age(registry, &s.key),
+                            &palette,
+                        );
+                        if stale {
+                            dimmed(text)
+                        } else {
+                            text
+                        }
+                    },
+                    |&s| {
+                        let value = *values
+                            .entry(s.key.clone())
+                            .or_insert_with(|| registry.peek_value(&s.key));
+                        Some(value_text(&l10n, s, value?))
+                    },
+                    |&s, response| {
+                        response
+                            .clone()
+                            .on_hover_text(age_text(&l10n, age(registry, &s.key)));
+                        previews.on_hover(&s.key, response);
+                        metric_context_menu(response, s);
+                    },
                 )
-                .cloned()
+                .map(|(s, submitted)| match submitted {
+                    true => SearchSelection::Submitted(s.clone()),
+                    false => SearchSelection::Clicked(s.clone()),
+                })
             })
             .inner;
 
         // Check if we have new search results.
-        if let Some(task) = self.search_task.take() {
+        if let Some((searched, task)) = self.search_task.take() {
             if task.is_finished() {
                 self.search_results = block_on(task);
                 self.search_results
                     .sort_by(|r1, r2| r1.key.key.name().cmp(r2.key.key.name()));
+                self.results_query = Some(searched);
             } else {
-                self.search_task = Some(task);
+                self.search_task = Some((searched, task));
             }
         }
 
+        // The query was set without typing, e.g. through reflection.
+        if self.search_task.is_none() && !self.results_are_current() {
+            self.input_dirty = true;
+        }
         if self.input_dirty
             && !self.search_input.is_empty()
+            && self.last_input_time.elapsed() >= self.debounce
             && self.last_search_time.elapsed() > Duration::from_millis(250)
         {
+            self.last_search_time = Instant::now();
+            let searched = SearchedQuery {
+                query: self.search_input.clone(),
+                generation: registry.generation(),
+            };
+            // Every match of a query also matches its prefixes, so while the
+            // registry is unchanged, only the previous results can match.
+            let previous = self
+                .results_query
+                .as_ref()
+                .filter(|results| {
+                    results.generation == searched.generation
+                        && searched.query.starts_with(&results.query)
+                })
+                .map(|_| self.search_results.clone());
             // Spawn task to search the registry, just to avoid long frame times
             // when searching a large registry.
-            self.last_search_time = Instant::now();
-            let search_input = self.search_input.clone();
+            let search_input = searched.query.clone();
             let task_registry = registry.clone();
-            let task = AsyncComputeTaskPool::get()
-                .spawn(async move { task_registry.fuzzy_search_by_name(&search_input) });
-            self.search_task = Some(task);
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                match previous {
+                    Some(results) => refine(results, &search_input),
+                    None => task_registry.fuzzy_search_by_name(&search_input),
+                }
+            });
+            self.search_task = Some((searched, task));
             self.input_dirty = false;
         }
 
         maybe_selected
     }
+
+    fn filters_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ComboBox::from_id_salt("metric-search-kind")
+            .selected_text(l10n.get(self.filters.kind.map_or(UiText::AnyKind, UiText::kind)))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.filters.kind, None, l10n.get(UiText::AnyKind));
+                for kind in [
+                    MetricKind::Counter,
+                    MetricKind::Gauge,
+                    MetricKind::Histogram,
+                ] {
+                    ui.selectable_value(
+                        &mut self.filters.kind,
+                        Some(kind),
+                        l10n.get(UiText::kind(kind)),
+                    );
+                }
+            });
+
+        let mut units: Vec<_> = self
+            .search_results
+            .iter()
+            .filter_map(|s| s.description.as_ref()?.unit.clone())
+            .chain(self.filters.unit.clone())
+            .collect();
+        units.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        units.dedup();
+        ComboBox::from_id_salt("metric-search-unit")
+            .selected_text(
+                self.filters
+                    .unit
+                    .as_ref()
+                    .map_or(l10n.get(UiText::AnyUnit), MetricUnit::as_str),
+            )
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.filters.unit, None, l10n.get(UiText::AnyUnit));
+                for unit in units {
+                    let text = unit.as_str().to_owned();
+                    ui.selectable_value(&mut self.filters.unit, Some(unit), text);
+                }
+            });
+    }
+}
+
+/// The `results` that match `query`, by name or alias, like
+/// [`MetricsRegistry::fuzzy_search_by_name`].
+fn refine(mut results: Vec<SearchResult>, query: &str) -> Vec<SearchResult> {
+    let matcher = SkimMatcherV2::default();
+    results.retain(|result| {
+        matcher.fuzzy_match(result.key.key.name(), query).is_some()
+            || (result.alias.as_ref()).is_some_and(|a| matcher.fuzzy_match(a, query).is_some())
+    });
+    results
+}
+
+/// Time since the metric was last updated.
+fn age(registry: &MetricsRegistry, key: &MetricKey) -> Option<Duration> {
+    registry.last_updated(key).map(|t| t.elapsed())
+}
+
+fn is_active(age: Option<Duration>) -> bool {
+    age.is_some_and(|age| age < ACTIVE_AGE)
+}
+
+fn age_text(l10n: &DashboardLocalization, age: Option<Duration>) -> String {
+    match age {
+        Some(age) => {
+            let seconds = l10n.number(age.as_secs_f64(), 1);
+            l10n.format(UiText::UpdatedAgo, &[("seconds", &seconds)])
+        }
+        None => l10n.get(UiText::NotUpdated).to_owned(),
+    }
+}
+
+/// The current `value` of a result, with its unit. Histograms show how many
+/// samples they hold, since they don't have a single value.
+fn value_text(l10n: &DashboardLocalization, result: &SearchResult, value: f64) -> String {
+    let unit = result.description.as_ref().and_then(|d| d.unit.as_ref());
+    let text = match result.key.kind {
+        MetricKind::Counter => l10n.number(value, 0),
+        MetricKind::Gauge => l10n.number(value, 3),
+        MetricKind::Histogram => {
+            let count = l10n.number(value, 0);
+            return l10n.format(UiText::SampleCount, &[("count", &count)]);
+        }
+    };
+    match unit {
+        Some(unit) => format!("{text} {unit}"),
+        None => text,
+    }
+}
+
+/// `text` with faded colors, for results of an outdated query.
+fn dimmed(mut text: LayoutJob) -> LayoutJob {
+    for section in &mut text.sections {
+        section.format.color = section.format.color.gamma_multiply(0.5);
+    }
+    text
+}
+
+fn with_freshness_dot(text: LayoutJob, age: Option<Duration>, palette: &ThemePalette) -> LayoutJob {
+    let color = if is_active(age) {
+        palette.good
+    } else {
+        palette.weak
+    };
+    let mut job = LayoutJob::default();
+    job.append(
+        "● ",
+        0.0,
+        TextFormat {
+            color,
+            ..Default::default()
+        },
+    );
+    for section in &text.sections {
+        job.append(
+            &text.text[section.byte_range.clone()],
+            section.leading_space,
+            section.format.clone(),
+        );
+    }
+    job
 }
@@ -0,0 +1,482 @@
+use crate::registry::{FrameId, FrameSample, MetricKey, MetricsRegistry};
+use crate::stats::mean;
+use crate::{DashboardPerformance, DashboardWindow};
+use bevy::{platform::collections::HashSet, prelude::*};
+use metrics_util::MetricKind;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+/// The [`SystemSet`] in which dashboard widgets sample metrics.
+///
+/// The [`FrameMetricsSnapshot`] is captured at the start of this set, and
+/// every widget samples from it. By default the set runs in [`Last`] before
+/// the [`BucketClearSet`](crate::BucketClearSet). Use
+/// [`DashboardPlugin::sample_metrics_in`](crate::DashboardPlugin::sample_metrics_in)
+/// to choose another schedule, e.g. [`PostUpdate`] so that values written
+/// by systems in [`Last`] are consistently attributed to the next frame.
+///
+/// Systems that write metrics can be ordered against this set like any
+/// other, e.g. `.before(SampleMetricsSet)`.
+///
+/// Plots that sample [per fixed tick](crate::plots::SamplingMode::PerFixedTick)
+/// take their samples in this set in [`FixedPostUpdate`] too, from their own
+/// snapshot.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub struct SampleMetricsSet;
+
+/// The value of one metric in a [`FrameMetricsSnapshot`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotValue {
+    /// The total count.
+    Counter(u64),
+    #[allow(missing_docs)]
+    Gauge(f64),
+    /// All samples recorded since the buckets were last cleared.
+    Histogram(Vec<f64>),
+}
+
+/// The values of all consumed metrics, loaded once per frame in the
+/// [`SampleMetricsSet`].
+///
+/// Dashboard widgets read metrics from this snapshot instead of the
+/// registry, so every widget sees the same value of a metric within one
+/// frame, even if it is written concurrently, e.g. by async tasks. Metrics
+/// used by several widgets are only loaded once.
+///
+/// Only metrics that are used by some [`DashboardWindow`] or
+/// [watched](Self::watch) are captured. Metrics that are not registered yet
+/// are left out, rather than created. Allocations are reused from frame to
+/// frame.
+#[derive(Default, Resource)]
+pub struct FrameMetricsSnapshot {
+    /// Sorted by key.
+    entries: Vec<(MetricKey, SnapshotValue)>,
+    /// The entries of the previous capture, kept for their allocations.
+    spare: Vec<(MetricKey, SnapshotValue)>,
+    watched: HashSet<MetricKey>,
+    frame: Option<FrameId>,
+    /// Sorted by key, then in the order they were recorded.
+    frame_samples: Vec<FrameSample>,
+    /// Sorted by key.
+    gauge_envelopes: Vec<(metrics::Key, (f64, f64))>,
+    histogram_cap: Option<usize>,
+    elapsed: Option<Duration>,
+}
+
+impl FrameMetricsSnapshot {
+    /// Capture `key` every frame, even if no dashboard widget uses it, e.g.
+    /// for an exporter that reads the snapshot.
+    pub fn watch(&mut self, key: MetricKey) {
+        self.watched.insert(key.to_retained());
+    }
+
+    /// Stop capturing `key` unless a dashboard widget uses it.
+    pub fn unwatch(&mut self, key: &MetricKey) {
+        self.watched.remove(key);
+    }
+
+    /// Capture at most `cap` samples of each histogram, or all of them if
+    /// `None`. Which samples are left out is unspecified.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::metrics_util::MetricKind;
+    /// # use bevy_metrics_dashboard::registry::{MetricKey, MetricsRegistry};
+    /// # use bevy_metrics_dashboard::{FrameMetricsSnapshot, SnapshotValue};
+    /// # use metrics::Key;
+    /// let registry = MetricsRegistry::new();
+    /// let key = MetricKey::new(Key::from_static_name("step_ms"), MetricKind::Histogram);
+    /// let histogram = registry.get_or_create_histogram(&key.key);
+    /// for value in [1.0, 2.0, 3.0, 4.0, 5.0] {
+    ///     histogram.push(value);
+    /// }
+    ///
+    /// let mut snapshot = FrameMetricsSnapshot::default();
+    /// snapshot.set_histogram_cap(Some(3));
+    /// snapshot.capture(&registry, [&key]);
+    /// let Some(SnapshotValue::Histogram(samples)) = snapshot.get(&key) else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(samples.len(), 3);
+    /// ```
+    pub fn set_histogram_cap(&mut self, cap: Option<usize>) {
+        self.histogram_cap = cap;
+    }
+
+    /// Load the current values of the `consumed` and watched metrics from
+    /// `registry`, replacing the previous snapshot.
+    ///
+    /// The values [recorded for earlier frames](MetricsRegistry::record_for_frame)
+    /// and the [gauge envelopes](MetricsRegistry::track_gauge_envelope)
+    /// since the last capture are taken from the registry too. Those of
+    /// metrics that aren't captured are discarded.
+    pub fn capture<'a>(
+        &mut self,
+        registry: &MetricsRegistry,
+        consumed: impl IntoIterator<Item = &'a MetricKey>,
+    ) {
+        self.capture_values(registry, consumed);
+
+        let Self {
+            entries,
+            frame_samples,
+            gauge_envelopes,
+            ..
+        } = self;
+        *frame_samples = registry.take_frame_samples();
+        frame_samples.retain(|sample| {
+            entries
+                .binary_search_by(|(k, _)| k.cmp(&sample.key))
+                .is_ok()
+        });
+        frame_samples.sort_by(|a, b| a.key.cmp(&b.key));
+
+        *gauge_envelopes = registry.take_gauge_envelopes();
+        gauge_envelopes.retain(|(key, _)| {
+            entries
+                .binary_search_by(|(k, _)| (&k.key, k.kind).cmp(&(key, MetricKind::Gauge)))
+                .is_ok()
+        });
+    }
+
+    /// Like [`Self::capture`], but leaves the values recorded for earlier
+    /// frames in the registry, for the snapshot of the next frame.
+    pub(crate) fn capture_values<'a>(
+        &mut self,
+        registry: &MetricsRegistry,
+        consumed: impl IntoIterator<Item = &'a MetricKey>,
+    ) {
+        let Self {
+            entries,
+            spare,
+            watched,
+            histogram_cap,
+            ..
+        } = self;
+        let mut keys: Vec<&MetricKey> = watched.iter().collect();
+        for key in consumed {
+            keys.push(key);
+        }
+        keys.sort_unstable();
+        keys.dedup();
+
+        std::mem::swap(entries, spare);
+        entries.clear();
+        let mut previous_entries = spare.drain(..).peekable();
+        for key in keys {
+            // Both lists are sorted, so skip entries that aren't consumed anymore.
+            while previous_entries.next_if(|(k, _)| k < key).is_some() {}
+            let previous = previous_entries.next_if(|(k, _)| k == key);
+            let (key, previous) = match previous {
+                Some((key, value)) => (key, Some(value)),
+                None => (key.to_retained(), None),
+            };
+            if let Some(value) = load(registry, &key, previous, *histogram_cap) {
+                entries.push((key, value));
+            }
+        }
+    }
+
+    /// Bevy system that calls [`Self::capture`] with the metrics used by all
+    /// [`DashboardWindow`]s, and by all `MetricIndicator`s with the
+    /// `ui_indicator` feature.
+    ///
+    /// The [`FrameId`] is recorded as the [frame](Self::frame) of the
+    /// snapshot, the elapsed [`Time<Real>`] as its [time](Self::elapsed),
+    /// and the time spent counts towards the [`DashboardPerformance`] cost.
+    pub fn capture_system(
+        registry: Res<MetricsRegistry>,
+        frame: Option<Res<FrameId>>,
+        time: Option<Res<Time<Real>>>,
+        windows: Query<&DashboardWindow>,
+        #[cfg(feature = "ui_indicator")] indicators: Query<&crate::MetricIndicator>,
+        mut snapshot: ResMut<Self>,
+        performance: Option<ResMut<DashboardPerformance>>,
+    ) {
+        let start = Instant::now();
+        snapshot.frame = frame.map(|frame| *frame);
+        snapshot.elapsed = time.map(|time| time.elapsed());
+        let consumed = windows.iter().flat_map(DashboardWindow::consumed_metrics);
+        #[cfg(feature = "ui_indicator")]
+        let consumed = consumed.chain(indicators.iter().map(|indicator| &indicator.metric));
+        snapshot.capture(&registry, consumed);
+        if let Some(mut performance) = performance {
+            performance.add_cost(start.elapsed());
+        }
+    }
+
+    /// All captured metrics, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&MetricKey, &SnapshotValue)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    /// The frame in which the snapshot was captured, if there is a [`FrameId`]
+    /// resource.
+    pub fn frame(&self) -> Option<FrameId> {
+        self.frame
+    }
+
+    /// The real time elapsed since startup when the snapshot was captured,
+    /// if there is a [`Time<Real>`] resource. Histogram plots use it for
+    /// their [`TrailingWindow`](crate::plots::TrailingWindow).
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+
+    /// Set the [time](Self::elapsed) of a snapshot that is captured
+    /// manually.
+    pub fn set_elapsed(&mut self, elapsed: Option<Duration>) {
+        self.elapsed = elapsed;
+    }
+
+    /// The values of `key` recorded for earlier frames since the previous
+    /// capture, in the order they were recorded.
+    pub fn frame_samples(&self, key: &MetricKey) -> &[FrameSample] {
+        let start = self.frame_samples.partition_point(|s| &s.key < key);
+        let end = self.frame_samples.partition_point(|s| &s.key <= key);
+        &self.frame_samples[start..end]
+    }
+
+    /// The captured value of `key`.
+    pub fn get(&self, key: &MetricKey) -> Option<&SnapshotValue> {
+        self.find(&key.key, key.kind)
+    }
+
+    /// The captured value of a counter.
+    pub fn counter(&self, key: &metrics::Key) -> Option<u64> {
+        match self.find(key, MetricKind::Counter)? {
+            SnapshotValue::Counter(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The captured value of a gauge.
+    pub fn gauge(&self, key: &metrics::Key) -> Option<f64> {
+        match self.find(key, MetricKind::Gauge)? {
+            SnapshotValue::Gauge(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The lowest and highest values of a gauge since the previous capture,
+    /// if it's [tracked](MetricsRegistry::track_gauge_envelope).
+    pub fn gauge_envelope(&self, key: &metrics::Key) -> Option<(f64, f64)> {
+        let i = self
+            .gauge_envelopes
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .ok()?;
+        Some(self.gauge_envelopes[i].1)
+    }
+
+    /// The captured samples of a histogram.
+    pub fn histogram(&self, key: &metrics::Key) -> Option<&[f64]> {
+        match self.find(key, MetricKind::Histogram)? {
+            SnapshotValue::Histogram(samples) => Some(samples),
+            _ => None,
+        }
+    }
+
+    fn find(&self, key: &metrics::Key, kind: MetricKind) -> Option<&SnapshotValue> {
+        // Same order as the derived `Ord` of `MetricKey`.
+        let i = self
+            .entries
+            .binary_search_by(|(k, _)| (&k.key, k.kind).cmp(&(key, kind)))
+            .ok()?;
+        Some(&self.entries[i].1)
+    }
+}
+
+/// The values of every metric in a [`MetricsRegistry`] at one point in time,
+/// to compare with a later snapshot, e.g. to find a leak. See [`Self::diff`].
+///
+/// Unlike the [`FrameMetricsSnapshot`], this captures all registered
+/// metrics, but histograms keep only the mean of their samples since the
+/// buckets were last cleared, usually those recorded in the current frame.
+#[derive(Clone, Debug)]
+pub struct RegistrySnapshot {
+    /// Sorted by key. `None` for histograms without samples.
+    values: Vec<(MetricKey, Option<f64>)>,
+    taken: Instant,
+}
+
+impl RegistrySnapshot {
+    /// Capture the current value of every metric in `registry`.
+    pub fn capture(registry: &MetricsRegistry) -> Self {
+        let mut values: Vec<_> = registry
+            .all_keys()
+            .into_iter()
+            .filter_map(|key| {
+                let value = match load(registry, &key, None, None)? {
+                    SnapshotValue::Counter(total) => Some(total as f64),
+                    SnapshotValue::Gauge(value) => Some(value),
+                    SnapshotValue::Histogram(samples) => mean(&samples),
+                };
+                Some((key, value))
+            })
+            .collect();
+        values.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        Self {
+            values,
+            taken: Instant::now(),
+        }
+    }
+
+    /// When the snapshot was captured.
+    pub fn taken(&self) -> Instant {
+        self.taken
+    }
+
+    /// How many metrics were captured.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The captured value of `key`: the total of a counter, the value of a
+    /// gauge, or the mean of a histogram's samples.
+    ///
+    /// Returns `None` if the metric wasn't registered, or if it's a
+    /// histogram without samples.
+    pub fn get(&self, key: &MetricKey) -> Option<f64> {
+        let i = self.values.binary_search_by(|(k, _)| k.cmp(key)).ok()?;
+        self.values[i].1
+    }
+
+    /// Compare this snapshot with a later one, `other`, with one
+    /// [`MetricDiff`] per metric in either snapshot, sorted by key.
+    ///
+    /// ```
+    /// # use bevy_metrics_dashboard::{MetricPresence, RegistrySnapshot};
+    /// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+    /// # use metrics::Key;
+    /// # use std::sync::atomic::Ordering;
+    /// let registry = MetricsRegistry::new();
+    /// let spawned = Key::from_static_name("enemies/spawned");
+    /// registry.get_or_create_counter(&spawned).fetch_add(40, Ordering::Relaxed);
+    /// let before = RegistrySnapshot::capture(&registry);
+    ///
+    /// registry.get_or_create_counter(&spawned).fetch_add(10, Ordering::Relaxed);
+    /// registry.get_or_create_gauge(&Key::from_static_name("enemies/alive"));
+    /// let after = RegistrySnapshot::capture(&registry);
+    ///
+    /// let diff = before.diff(&after);
+    /// assert_eq!(diff.len(), 2);
+    /// assert_eq!(diff[0].key.key.name(), "enemies/alive");
+    /// assert_eq!(diff[0].presence, MetricPresence::OnlyLater);
+    /// assert_eq!(diff[1].delta(), Some(10.0));
+    /// assert_eq!(diff[1].percent(), Some(25.0));
+    /// ```
+    pub fn diff(&self, other: &Self) -> Vec<MetricDiff> {
+        let mut diffs = Vec::with_capacity(self.values.len().max(other.values.len()));
+        let mut earlier = self.values.iter().peekable();
+        let mut later = other.values.iter().peekable();
+        loop {
+            let (key, before, after, presence) = match (earlier.peek(), later.peek()) {
+                (None, None) => break,
+                (Some((a, _)), Some((b, _))) if a == b => {
+                    let (key, before) = earlier.next().unwrap();
+                    let (_, after) = later.next().unwrap();
+                    (key, *before, *after, MetricPresence::Both)
+                }
+                (Some((a, _)), Some((b, _))) if b < a => {
+                    let (key, after) = later.next().unwrap();
+                    (key, None, *after, MetricPresence::OnlyLater)
+                }
+                (Some(_), _) => {
+                    let (key, before) = earlier.next().unwrap();
+                    (key, *before, None, MetricPresence::OnlyEarlier)
+                }
+                (None, Some(_)) => {
+                    let (key, after) = later.next().unwrap();
+                    (key, None, *after, MetricPresence::OnlyLater)
+                }
+            };
+            diffs.push(MetricDiff {
+                key: key.clone(),
+                before,
+                after,
+                presence,
+            });
+        }
+        diffs
+    }
+}
+
+/// Which of two [`RegistrySnapshot`]s a metric was captured in.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MetricPresence {
+    #[allow(missing_docs)]
+    Both,
+    /// The metric was removed, e.g. [evicted](crate::retention), between
+    /// the snapshots.
+    OnlyEarlier,
+    /// The metric was registered between the snapshots.
+    OnlyLater,
+}
+
+/// How a metric changed between two [`RegistrySnapshot`]s. See
+/// [`RegistrySnapshot::diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricDiff {
+    #[allow(missing_docs)]
+    pub key: MetricKey,
+    /// The value in the earlier snapshot. See [`RegistrySnapshot::get`].
+    pub before: Option<f64>,
+    /// The value in the later snapshot.
+    pub after: Option<f64>,
+    #[allow(missing_docs)]
+    pub presence: MetricPresence,
+}
+
+impl MetricDiff {
+    /// How much the value increased, if both snapshots have one.
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.after? - self.before?)
+    }
+
+    /// The [delta](Self::delta) as a percentage of the earlier value, or
+    /// `None` if that is zero.
+    pub fn percent(&self) -> Option<f64> {
+        let before = self.before?;
+        let delta = self.delta()?;
+        (before != 0.0).then(|| 100.0 * delta / before.abs())
+    }
+}
+
+/// Load the value of `key`, reusing the allocation of its `previous` value.
+fn load(
+    registry: &MetricsRegistry,
+    key: &MetricKey,
+    previous: Option<SnapshotValue>,
+    histogram_cap: Option<usize>,
+) -> Option<SnapshotValue> {
+    match key.kind {
+        MetricKind::Counter => {
+            let counter = registry.get_counter(&key.key)?;
+            Some(SnapshotValue::Counter(counter.load(Ordering::Relaxed)))
+        }
+        MetricKind::Gauge => {
+            let gauge = registry.get_gauge(&key.key)?;
+            Some(SnapshotValue::Gauge(f64::from_bits(
+                gauge.load(Ordering::Relaxed),
+            )))
+        }
+        MetricKind::Histogram => {
+            let histogram = registry.get_histogram(&key.key)?;
+            let mut samples = match previous {
+                Some(SnapshotValue::Histogram(samples)) => samples,
+                _ => Vec::new(),
+            };
+            samples.clear();
+            let cap = histogram_cap.unwrap_or(usize::MAX);
+            histogram.data_with(|block| {
+                let room = cap - samples.len();
+                samples.extend_from_slice(&block[..block.len().min(room)]);
+            });
+            Some(SnapshotValue::Histogram(samples))
+        }
+    }
+}
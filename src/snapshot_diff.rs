@@ -0,0 +1,351 @@
+//! Widget comparing every metric between two [`RegistrySnapshot`]s, e.g. to
+//! find what grew between entering and leaving a level.
+
+use crate::egui::{Grid, ScrollArea, TextEdit, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::number_format::NumberFormat;
+use crate::registry::MetricsRegistry;
+use crate::snapshot::{MetricDiff, MetricPresence, RegistrySnapshot};
+use crate::widget::{DashboardWidget, WidgetContext};
+use bevy::prelude::Reflect;
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
+use std::cmp::Ordering;
+
+/// A column of the [`SnapshotDiff`] table.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum SnapshotDiffColumn {
+    /// The metric name with its labels.
+    #[default]
+    Name,
+    /// The value in snapshot A.
+    Earlier,
+    /// The value in snapshot B.
+    Later,
+    /// See [`MetricDiff::delta`].
+    Delta,
+    /// See [`MetricDiff::percent`].
+    Percent,
+}
+
+impl SnapshotDiffColumn {
+    #[allow(missing_docs)]
+    pub const ALL: [Self; 5] = [
+        Self::Name,
+        Self::Earlier,
+        Self::Later,
+        Self::Delta,
+        Self::Percent,
+    ];
+
+    fn label(self) -> UiText {
+        match self {
+            Self::Name => UiText::ColumnName,
+            Self::Earlier => UiText::SnapshotA,
+            Self::Later => UiText::SnapshotB,
+            Self::Delta => UiText::ColumnDelta,
+            Self::Percent => UiText::ColumnPercent,
+        }
+    }
+
+    fn value(self, diff: &MetricDiff) -> Option<f64> {
+        match self {
+            Self::Name => None,
+            Self::Earlier => diff.before,
+            Self::Later => diff.after,
+            Self::Delta => diff.delta(),
+            Self::Percent => diff.percent(),
+        }
+    }
+
+    /// Orders `a` and `b` by this column. Rows without a value come last in
+    /// either direction.
+    fn compare(self, a: &MetricDiff, b: &MetricDiff, descending: bool) -> Ordering {
+        if self == Self::Name {
+            let order = a.key.name_with_labels().cmp(&b.key.name_with_labels());
+            return if descending { order.reverse() } else { order };
+        }
+        match (self.value(a), self.value(b)) {
+            (Some(a), Some(b)) if descending => b.total_cmp(&a),
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+/// Configuration for one [`SnapshotDiff`].
+#[derive(Clone, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct SnapshotDiffConfig {
+    /// Only metrics whose names contain this are listed.
+    pub filter: String,
+    /// The column the table is sorted by.
+    pub sort: SnapshotDiffColumn,
+    #[allow(missing_docs)]
+    pub descending: bool,
+}
+
+impl SnapshotDiffConfig {
+    /// Whether the row of `diff` is listed.
+    pub fn matches(&self, diff: &MetricDiff) -> bool {
+        diff.key.key.name().contains(&self.filter)
+    }
+}
+
+/// Lists every metric with its values in two [`RegistrySnapshot`]s, "A" and
+/// "B", captured with the buttons of the widget, and how much it changed.
+///
+/// The table can be filtered by name and sorted by any column. Metrics only
+/// registered in one of the snapshots are flagged. See
+/// [`RegistrySnapshot::diff`] to compare snapshots without a dashboard.
+///
+/// ```
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::snapshot_diff::{
+/// #     SnapshotDiff, SnapshotDiffColumn, SnapshotDiffConfig,
+/// # };
+/// # use metrics::Key;
+/// # use std::sync::atomic::Ordering;
+/// let registry = MetricsRegistry::new();
+/// let mut widget = SnapshotDiff::new(
+///     "Leaks",
+///     SnapshotDiffConfig {
+///         filter: "entities".into(),
+///         sort: SnapshotDiffColumn::Delta,
+///         descending: true,
+///     },
+/// );
+/// let mut set = |name: &'static str, value: f64| {
+///     let gauge = registry.get_or_create_gauge(&Key::from_static_name(name));
+///     gauge.store(value.to_bits(), Ordering::Relaxed);
+/// };
+/// set("entities/meshes", 100.0);
+/// set("entities/lights", 10.0);
+/// widget.capture_earlier(&registry);
+///
+/// set("entities/meshes", 150.0);
+/// set("entities/lights", 30.0);
+/// set("frame_time", 16.0);
+/// widget.capture_later(&registry);
+///
+/// let rows: Vec<_> = widget
+///     .rows()
+///     .map(|diff| (diff.key.key.name().to_owned(), diff.delta()))
+///     .collect();
+/// assert_eq!(
+///     rows,
+///     [
+///         ("entities/meshes".into(), Some(50.0)),
+///         ("entities/lights".into(), Some(20.0)),
+///     ]
+/// );
+/// ```
+#[derive(Reflect)]
+#[reflect(from_reflect = false)]
+pub struct SnapshotDiff {
+    name: String,
+    config: SnapshotDiffConfig,
+    #[reflect(ignore)]
+    earlier: Option<RegistrySnapshot>,
+    #[reflect(ignore)]
+    later: Option<RegistrySnapshot>,
+    /// Sorted by the configured column, or empty without both snapshots.
+    #[reflect(ignore)]
+    diffs: Vec<MetricDiff>,
+    /// The sort order of `diffs`, to sort again when it changes.
+    #[reflect(ignore)]
+    sorted_by: Option<(SnapshotDiffColumn, bool)>,
+}
+
+impl SnapshotDiff {
+    /// Create a new snapshot diff widget, without snapshots.
+    pub fn new(name: impl Into<String>, config: SnapshotDiffConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            earlier: None,
+            later: None,
+            diffs: Vec::new(),
+            sorted_by: None,
+        }
+    }
+
+    /// Name of the widget.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This widget's configuration.
+    pub fn config(&self) -> &SnapshotDiffConfig {
+        &self.config
+    }
+
+    /// Capture snapshot A.
+    pub fn capture_earlier(&mut self, registry: &MetricsRegistry) {
+        self.earlier = Some(RegistrySnapshot::capture(registry));
+        self.compute();
+    }
+
+    /// Capture snapshot B.
+    pub fn capture_later(&mut self, registry: &MetricsRegistry) {
+        self.later = Some(RegistrySnapshot::capture(registry));
+        self.compute();
+    }
+
+    fn compute(&mut self) {
+        self.diffs = match (&self.earlier, &self.later) {
+            (Some(earlier), Some(later)) => earlier.diff(later),
+            _ => Vec::new(),
+        };
+        self.sorted_by = None;
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        let order = (self.config.sort, self.config.descending);
+        if self.sorted_by == Some(order) {
+            return;
+        }
+        self.sorted_by = Some(order);
+        let (column, descending) = order;
+        self.diffs.sort_by(|a, b| column.compare(a, b, descending));
+    }
+
+    /// The rows of the table: the [matching](SnapshotDiffConfig::matches)
+    /// differences between snapshots A and B, in the configured order.
+    ///
+    /// Empty until both snapshots are captured.
+    pub fn rows(&mut self) -> impl Iterator<Item = &MetricDiff> {
+        self.sort();
+        self.diffs.iter().filter(|diff| self.config.matches(diff))
+    }
+
+    /// Draw the widget using `ui`.
+    pub fn draw(&mut self, registry: &MetricsRegistry, numbers: &NumberFormat, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.horizontal(|ui| {
+            if ui.button(l10n.get(UiText::CaptureA)).clicked() {
+                self.capture_earlier(registry);
+            }
+            if ui.button(l10n.get(UiText::CaptureB)).clicked() {
+                self.capture_later(registry);
+            }
+            ui.label(l10n.get(UiText::FilterPrefix));
+            ui.add(
+                TextEdit::singleline(&mut self.config.filter)
+                    .hint_text(l10n.get(UiText::MetricNameHint)),
+            );
+        });
+        if self.earlier.is_none() || self.later.is_none() {
+            ui.weak(l10n.get(UiText::CaptureBothSnapshots));
+            return;
+        }
+
+        let format = |value: Option<f64>| match value {
+            Some(value) => numbers.format(value, l10n.decimal_separator),
+            None => "-".to_owned(),
+        };
+        let mut clicked_column = None;
+        let (sort, descending) = (self.config.sort, self.config.descending);
+        let name = self.name.clone();
+        let rows: Vec<_> = self.rows().collect();
+        if rows.is_empty() {
+            ui.weak(l10n.get(UiText::NoMatchingRows));
+        }
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new(name).num_columns(6).striped(true).show(ui, |ui| {
+                for column in SnapshotDiffColumn::ALL {
+                    let mut text = l10n.get(column.label()).to_owned();
+                    if column == sort {
+                        text.push_str(if descending { " ⏷" } else { " ⏶" });
+                    }
+                    if ui
+                        .selectable_label(column == sort, text)
+                        .on_hover_text(l10n.get(UiText::ClickToSort))
+                        .clicked()
+                    {
+                        clicked_column = Some(column);
+                    }
+                }
+                ui.end_row();
+
+                for diff in rows {
+                    ui.label(diff.key.name_with_labels());
+                    ui.label(format(diff.before));
+                    ui.label(format(diff.after));
+                    ui.label(format(diff.delta()));
+                    match diff.percent() {
+                        Some(percent) => ui.label(format!(
+                            "{}%",
+                            numbers.format(percent, l10n.decimal_separator)
+                        )),
+                        None => ui.label("-"),
+                    };
+                    match diff.presence {
+                        MetricPresence::Both => ui.label(""),
+                        MetricPresence::OnlyEarlier => {
+                            ui.colored_label(ui.visuals().warn_fg_color, l10n.get(UiText::OnlyInA))
+                        }
+                        MetricPresence::OnlyLater => {
+                            ui.colored_label(ui.visuals().warn_fg_color, l10n.get(UiText::OnlyInB))
+                        }
+                    };
+                    ui.end_row();
+                }
+            });
+        });
+
+        if let Some(column) = clicked_column {
+            if column == self.config.sort {
+                self.config.descending = !self.config.descending;
+            } else {
+                self.config.sort = column;
+                self.config.descending = column != SnapshotDiffColumn::Name;
+            }
+        }
+    }
+}
+
+impl Default for SnapshotDiff {
+    fn default() -> Self {
+        Self::new("Snapshot Diff", SnapshotDiffConfig::default())
+    }
+}
+
+impl DashboardWidget for SnapshotDiff {
+    fn id(&self) -> &str {
+        "snapshot_diff"
+    }
+
+    fn title(&self, _ctx: &WidgetContext) -> String {
+        self.name.clone()
+    }
+
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext) {
+        self.draw(ctx.registry, &ctx.style.numbers, ui);
+    }
+
+    #[cfg(feature = "layout")]
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        RawValue::from_rust(&self.config).map(Some)
+    }
+
+    #[cfg(feature = "layout")]
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        self.config = config.into_rust()?;
+        self.sorted_by = None;
+        Ok(())
+    }
+
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+}
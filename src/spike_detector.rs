@@ -0,0 +1,106 @@
+use crate::plots::SpikeDetectorConfig;
+use float_ord::FloatOrd;
+use std::collections::VecDeque;
+
+/// Detects samples that exceed a multiple of the rolling median, or an
+/// absolute threshold.
+///
+/// After firing, the detector stays disarmed until a sample falls back below
+/// the threshold, so one sustained elevation only produces one spike.
+pub struct SpikeDetector {
+    config: SpikeDetectorConfig,
+    window: VecDeque<f64>,
+    sorted: Vec<f64>,
+    armed: bool,
+}
+
+impl SpikeDetector {
+    pub fn new(config: SpikeDetectorConfig) -> Self {
+        let window_len = config.median_window.max(1);
+        Self {
+            config,
+            window: VecDeque::with_capacity(window_len),
+            sorted: Vec::with_capacity(window_len),
+            armed: true,
+        }
+    }
+
+    /// Replace the configuration, discarding the rolling window if its length
+    /// changed.
+    pub fn set_config(&mut self, config: SpikeDetectorConfig) {
+        if config.median_window != self.config.median_window {
+            self.window.clear();
+            self.sorted.clear();
+        }
+        self.config = config;
+    }
+
+    fn median(&self) -> Option<f64> {
+        if self.sorted.is_empty() {
+            return None;
+        }
+        let mid = self.sorted.len() / 2;
+        if self.sorted.len().is_multiple_of(2) {
+            Some(0.5 * (self.sorted[mid - 1] + self.sorted[mid]))
+        } else {
+            Some(self.sorted[mid])
+        }
+    }
+
+    /// The current threshold, if enough samples have been seen to compute it.
+    fn threshold(&self) -> Option<f64> {
+        if let Some(threshold) = self.config.absolute_threshold {
+            return Some(threshold);
+        }
+        if self.window.len() < self.config.median_window.max(1) {
+            return None;
+        }
+        self.median().map(|m| m * self.config.median_multiple)
+    }
+
+    /// Whether the latest sample exceeded the threshold.
+    pub fn is_firing(&self) -> bool {
+        !self.armed
+    }
+
+    /// Add a sample to the rolling window.
+    ///
+    /// Returns `true` if this sample is a new spike.
+    pub fn add(&mut self, value: f64) -> bool {
+        // Compare against the threshold from previous samples, so the spike
+        // itself doesn't inflate the median.
+        let is_spike = match self.threshold() {
+            Some(threshold) if value > threshold => {
+                let fire = self.armed;
+                self.armed = false;
+                fire
+            }
+            _ => {
+                self.armed = true;
+                false
+            }
+        };
+
+        let window_len = self.config.median_window.max(1);
+        while self.window.len() >= window_len {
+            let oldest = self.window.pop_front().unwrap();
+            if let Ok(i) = self
+                .sorted
+                .binary_search_by_key(&FloatOrd(oldest), |&v| FloatOrd(v))
+            {
+                self.sorted.remove(i);
+            }
+        }
+        self.window.push_back(value);
+        let i = match self
+            .sorted
+            .binary_search_by_key(&FloatOrd(value), |&v| FloatOrd(v))
+        {
+            Ok(i) => i,
+            Err(i) => i,
+        };
+        self.sorted.insert(i, value);
+
+        is_spike
+    }
+}
@@ -0,0 +1,144 @@
+use crate::registry::describe_in_build;
+use crate::type_name::short_type_name;
+use bevy::{
+    platform::collections::HashSet,
+    prelude::*,
+    state::state::{StateTransitionEvent, States},
+};
+use metrics::{counter, gauge, histogram, KeyName, Unit};
+use metrics_util::MetricKind;
+use std::{fmt::Debug, marker::PhantomData, time::Instant};
+
+/// Provides metrics for transitions of the state `S`.
+///
+/// - `state/S/transitions`: counter with `from` and `to` labels
+/// - `state/S/time_in_state`: gauge of the time spent in the current state
+/// - `state/S/dwell_time`: histogram with a `state` label, recorded when a
+///   state is exited
+///
+/// Label values are the [`Debug`] representations of the states, or `None`
+/// when the state doesn't exist. `S` is the type name without its module path,
+/// unless another state type with the same short name was registered first.
+///
+/// Adding this plugin twice for the same `S` logs a warning and does nothing.
+pub struct StateMetricsPlugin<S> {
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<S> Default for StateMetricsPlugin<S> {
+    fn default() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+/// The metric name prefixes of all registered state types.
+#[derive(Default, Resource)]
+struct StateMetricsPrefixes(HashSet<String>);
+
+#[derive(Resource)]
+struct StateMetrics<S> {
+    prefix: String,
+    current: Option<(String, Instant)>,
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<S: States> Plugin for StateMetricsPlugin<S> {
+    fn build(&self, app: &mut App) {
+        if app.world().contains_resource::<StateMetrics<S>>() {
+            warn!(
+                "StateMetricsPlugin::<{}> was already added",
+                std::any::type_name::<S>()
+            );
+            return;
+        }
+
+        let mut prefixes = app
+            .world_mut()
+            .get_resource_or_insert_with(StateMetricsPrefixes::default);
+        let mut prefix = format!("state/{}", short_type_name(std::any::type_name::<S>()));
+        if prefixes.0.contains(&prefix) {
+            prefix = format!("state/{}", std::any::type_name::<S>());
+        }
+        prefixes.0.insert(prefix.clone());
+
+        describe_state_metrics(app, &prefix);
+        app.insert_resource(StateMetrics::<S> {
+            prefix,
+            current: None,
+            marker: PhantomData,
+        })
+        // Lets the plugin be added before the state is initialized.
+        .add_event::<StateTransitionEvent<S>>()
+        .add_systems(Update, update_state_metrics::<S>);
+    }
+
+    fn is_unique(&self) -> bool {
+        // Handled in build with a warning instead of a panic.
+        false
+    }
+}
+
+fn describe_state_metrics(app: &mut App, prefix: &str) {
+    describe_in_build(
+        app,
+        &[
+            (
+                KeyName::from(format!("{prefix}/transitions")),
+                MetricKind::Counter,
+                Some(Unit::Count),
+                "State transitions, labeled by the exited and entered states",
+            ),
+            (
+                KeyName::from(format!("{prefix}/time_in_state")),
+                MetricKind::Gauge,
+                Some(Unit::Seconds),
+                "Time spent in the current state",
+            ),
+            (
+                KeyName::from(format!("{prefix}/dwell_time")),
+                MetricKind::Histogram,
+                Some(Unit::Seconds),
+                "Time spent in a state before exiting it",
+            ),
+        ],
+    );
+}
+
+fn update_state_metrics<S: States>(
+    mut metrics: ResMut<StateMetrics<S>>,
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+) {
+    let now = Instant::now();
+    let StateMetrics {
+        prefix, current, ..
+    } = &mut *metrics;
+
+    for transition in transitions.read() {
+        let from = state_label(&transition.exited);
+        let to = state_label(&transition.entered);
+        counter!(format!("{prefix}/transitions"), "from" => from.clone(), "to" => to.clone())
+            .increment(1);
+
+        if let Some((state, entered_at)) = current.take() {
+            histogram!(format!("{prefix}/dwell_time"), "state" => state)
+                .record((now - entered_at).as_secs_f64());
+        }
+        if transition.entered.is_some() {
+            *current = Some((to, now));
+        }
+    }
+
+    let time_in_state = current
+        .as_ref()
+        .map_or(0.0, |(_, entered_at)| (now - *entered_at).as_secs_f64());
+    gauge!(format!("{prefix}/time_in_state")).set(time_in_state);
+}
+
+fn state_label<S: Debug>(state: &Option<S>) -> String {
+    match state {
+        Some(state) => format!("{state:?}"),
+        None => "None".into(),
+    }
+}
@@ -0,0 +1,29 @@
+use float_ord::FloatOrd;
+
+/// The arithmetic mean of `values`, or `None` if empty.
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// The `p`th percentile of `values` by the nearest-rank method.
+///
+/// `values` is sorted in place.
+pub fn percentile(values: &mut [f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by_key(|&v| FloatOrd(v));
+    percentile_of_sorted(values, p)
+}
+
+/// Like [`percentile`], for `values` that are already sorted.
+pub fn percentile_of_sorted(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.saturating_sub(1)])
+}
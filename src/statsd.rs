@@ -0,0 +1,453 @@
+//! Push metrics to a StatsD or DogStatsD server over UDP.
+//!
+//! See [`StatsdExporterPlugin`].
+
+use crate::registry::{HistogramSubscription, MetricsRegistry};
+use crate::BucketClearSet;
+use bevy::{platform::collections::HashMap, prelude::*};
+use std::fmt::Write as _;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A datagram size that fits in the MTU of most networks.
+pub const DEFAULT_MAX_DATAGRAM_SIZE: usize = 1432;
+
+/// Sends every metric in the [`MetricsRegistry`] to a StatsD server as UDP
+/// datagrams, once per [`StatsdConfig::flush_interval`].
+///
+/// - counters are sent as the increment since the last flush (`|c`), and
+///   not at all if they didn't change
+/// - gauges are sent as their current value (`|g`)
+/// - histograms send every sample as a timing (`|ms`), or a fraction of
+///   them with [`Self::histogram_sample_rate`]
+///
+/// Labels become DogStatsD tags like `|#team:red`, unless
+/// [`Self::dogstatsd_tags`] is disabled. In metric names, `/` becomes `.`,
+/// and characters reserved by the protocol become `_`.
+///
+/// Sending never blocks: datagrams that can't be sent right away are
+/// dropped. If the socket can't be created, an error is logged and nothing
+/// is exported. See [`StatsdEncoder`] for the lines that are sent.
+///
+/// Requires the [`RegistryPlugin`](crate::RegistryPlugin).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::statsd::StatsdExporterPlugin;
+/// # let mut app = App::new();
+/// app.add_plugins(
+///     StatsdExporterPlugin::new("127.0.0.1:8125")
+///         .prefix("my_game")
+///         .histogram_sample_rate(0.1),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct StatsdExporterPlugin {
+    config: StatsdConfig,
+}
+
+/// Configuration of the [`StatsdExporterPlugin`].
+#[derive(Clone, Debug)]
+pub struct StatsdConfig {
+    /// The `host:port` of the server.
+    pub address: String,
+    /// Prepended to every metric name, followed by a `.`.
+    pub prefix: Option<String>,
+    /// How often metrics are sent.
+    pub flush_interval: Duration,
+    /// Lines are batched into datagrams of at most this many bytes. Longer
+    /// lines are sent on their own.
+    pub max_datagram_size: usize,
+    /// The fraction of histogram samples that are sent, from 0 to 1.
+    pub histogram_sample_rate: f64,
+    /// Send labels as DogStatsD tags. Plain StatsD servers don't support
+    /// tags.
+    pub dogstatsd_tags: bool,
+    /// Tags added to every metric, e.g. the build or platform.
+    pub global_tags: Vec<(String, String)>,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8125".into(),
+            prefix: None,
+            flush_interval: Duration::from_secs(1),
+            max_datagram_size: DEFAULT_MAX_DATAGRAM_SIZE,
+            histogram_sample_rate: 1.0,
+            dogstatsd_tags: true,
+            global_tags: Vec::new(),
+        }
+    }
+}
+
+impl StatsdExporterPlugin {
+    /// Send to the server at `address`, like `127.0.0.1:8125`.
+    pub fn new(address: impl Into<String>) -> Self {
+        Self::with_config(StatsdConfig {
+            address: address.into(),
+            ..default()
+        })
+    }
+
+    #[allow(missing_docs)]
+    pub fn with_config(config: StatsdConfig) -> Self {
+        Self { config }
+    }
+
+    /// See [`StatsdConfig::prefix`].
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.config.prefix = Some(prefix.into());
+        self
+    }
+
+    /// See [`StatsdConfig::flush_interval`].
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.config.flush_interval = interval;
+        self
+    }
+
+    /// See [`StatsdConfig::max_datagram_size`].
+    pub fn max_datagram_size(mut self, size: usize) -> Self {
+        self.config.max_datagram_size = size;
+        self
+    }
+
+    /// See [`StatsdConfig::histogram_sample_rate`].
+    pub fn histogram_sample_rate(mut self, rate: f64) -> Self {
+        self.config.histogram_sample_rate = rate;
+        self
+    }
+
+    /// See [`StatsdConfig::dogstatsd_tags`].
+    pub fn dogstatsd_tags(mut self, enabled: bool) -> Self {
+        self.config.dogstatsd_tags = enabled;
+        self
+    }
+
+    /// Add a tag to every metric. See [`StatsdConfig::global_tags`].
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.global_tags.push((key.into(), value.into()));
+        self
+    }
+}
+
+impl Plugin for StatsdExporterPlugin {
+    fn build(&self, app: &mut App) {
+        let socket = match connect(&self.config.address) {
+            Ok(socket) => socket,
+            Err(e) => {
+                error!(
+                    "StatsD export disabled: can't send to {:?}: {e}",
+                    self.config.address
+                );
+                return;
+            }
+        };
+        app.insert_resource(StatsdExporter::new(self.config.clone(), socket))
+            .add_systems(Last, StatsdExporter::export_system.after(BucketClearSet));
+    }
+}
+
+fn connect(address: &str) -> io::Result<UdpSocket> {
+    let addr = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other("address has no socket address"))?;
+    let local: SocketAddr = if addr.is_ipv4() {
+        "0.0.0.0:0".parse().unwrap()
+    } else {
+        "[::]:0".parse().unwrap()
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(addr)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// The state of the [`StatsdExporterPlugin`].
+#[derive(Resource)]
+pub struct StatsdExporter {
+    encoder: StatsdEncoder,
+    socket: UdpSocket,
+    last_flush: Option<Instant>,
+}
+
+/// Formats the metrics of a [`MetricsRegistry`] as StatsD lines, like the
+/// [`StatsdExporterPlugin`] sends, keeping the counter values of the last
+/// flush to send their increments.
+///
+/// ```
+/// # use bevy_metrics_dashboard::registry::MetricsRegistry;
+/// # use bevy_metrics_dashboard::statsd::{StatsdConfig, StatsdEncoder};
+/// use metrics::Key;
+/// use std::sync::atomic::Ordering;
+///
+/// let registry = MetricsRegistry::new();
+/// let spawned = registry.get_or_create_counter(&Key::from_parts(
+///     "enemies/spawned",
+///     &[("team", "red")],
+/// ));
+/// let queued = registry.get_or_create_gauge(&Key::from_static_name("jobs_queued"));
+/// let mut encoder = StatsdEncoder::new(StatsdConfig {
+///     prefix: Some("my_game".into()),
+///     ..Default::default()
+/// });
+///
+/// spawned.fetch_add(3, Ordering::Relaxed);
+/// queued.store(2.5f64.to_bits(), Ordering::Relaxed);
+/// assert_eq!(
+///     encoder.flush_lines(&registry),
+///     ["my_game.enemies.spawned:3|c|#team:red", "my_game.jobs_queued:2.5|g"]
+/// );
+///
+/// // Counters send their increment since the last flush, and nothing if
+/// // they didn't change.
+/// spawned.fetch_add(2, Ordering::Relaxed);
+/// assert_eq!(
+///     encoder.flush_lines(&registry),
+///     ["my_game.enemies.spawned:2|c|#team:red", "my_game.jobs_queued:2.5|g"]
+/// );
+/// assert_eq!(encoder.flush_lines(&registry), ["my_game.jobs_queued:2.5|g"]);
+/// ```
+pub struct StatsdEncoder {
+    config: StatsdConfig,
+    /// Counter values at the last flush.
+    counters: HashMap<metrics::Key, u64>,
+    histograms: HashMap<metrics::Key, HistogramState>,
+    /// Accumulates the sample rate, and a sample is kept each time it
+    /// reaches one.
+    sample_credit: f64,
+}
+
+struct HistogramState {
+    subscription: HistogramSubscription,
+    /// The samples that were read last, so they aren't sent twice.
+    last: Option<Arc<[f64]>>,
+    /// Samples kept since the last flush.
+    pending: Vec<f64>,
+}
+
+impl StatsdExporter {
+    fn new(config: StatsdConfig, socket: UdpSocket) -> Self {
+        Self {
+            encoder: StatsdEncoder::new(config),
+            socket,
+            last_flush: None,
+        }
+    }
+
+    /// Bevy system that collects the histogram samples of this frame, and
+    /// sends all metrics every [`StatsdConfig::flush_interval`].
+    ///
+    /// This runs in [`Last`] after the [`BucketClearSet`], when the
+    /// samples of subscribed histograms are published.
+    pub fn export_system(registry: Res<MetricsRegistry>, mut exporter: ResMut<Self>) {
+        exporter.encoder.collect_histograms(&registry);
+
+        let now = Instant::now();
+        let config = &exporter.encoder.config;
+        if exporter
+            .last_flush
+            .is_some_and(|last| now - last < config.flush_interval)
+        {
+            return;
+        }
+        exporter.last_flush = Some(now);
+        let lines = exporter.encoder.flush_lines(&registry);
+        let max_size = exporter.encoder.config.max_datagram_size;
+        for datagram in batch_lines(&lines, max_size) {
+            match exporter.socket.send(datagram.as_bytes()) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    debug!("Dropped a StatsD datagram, the socket is busy");
+                }
+                Err(e) => warn_once!("Failed to send StatsD datagram: {e}"),
+            }
+        }
+    }
+}
+
+impl StatsdEncoder {
+    #[allow(missing_docs)]
+    pub fn new(config: StatsdConfig) -> Self {
+        Self {
+            config,
+            counters: HashMap::default(),
+            histograms: HashMap::default(),
+            sample_credit: 0.0,
+        }
+    }
+
+    /// Subscribe to new histograms, and keep a
+    /// [fraction](StatsdConfig::histogram_sample_rate) of the samples
+    /// published since the last call, to send with the next flush.
+    ///
+    /// Call this after the atomic buckets are cleared, every time.
+    pub fn collect_histograms(&mut self, registry: &MetricsRegistry) {
+        registry.storage().visit_histograms(|key, _| {
+            if !self.histograms.contains_key(key) {
+                self.histograms.insert(
+                    key.clone(),
+                    HistogramState {
+                        subscription: registry.subscribe_histogram(key.clone()),
+                        last: None,
+                        pending: Vec::new(),
+                    },
+                );
+            }
+        });
+        let rate = self.config.histogram_sample_rate.clamp(0.0, 1.0);
+        for state in self.histograms.values_mut() {
+            let samples = state.subscription.samples();
+            if state
+                .last
+                .as_ref()
+                .is_some_and(|last| Arc::ptr_eq(last, &samples))
+            {
+                continue;
+            }
+            for &value in samples.iter().filter(|v| v.is_finite()) {
+                self.sample_credit += rate;
+                if self.sample_credit >= 1.0 {
+                    self.sample_credit -= 1.0;
+                    state.pending.push(value);
+                }
+            }
+            state.last = Some(samples);
+        }
+    }
+
+    /// One line per counter that changed since the last flush, per gauge,
+    /// and per kept histogram sample.
+    pub fn flush_lines(&mut self, registry: &MetricsRegistry) -> Vec<String> {
+        let Self {
+            config,
+            counters,
+            histograms,
+            ..
+        } = self;
+        let mut lines = Vec::new();
+        let storage = registry.storage();
+        storage.visit_counters(|key, counter| {
+            let value = counter.load(Ordering::Relaxed);
+            let last = counters.insert(key.clone(), value).unwrap_or(0);
+            let delta = counter_delta(last, value);
+            if delta > 0 {
+                lines.push(format_line(config, key, &delta.to_string(), "c", None));
+            }
+        });
+        storage.visit_gauges(|key, gauge| {
+            let value = f64::from_bits(gauge.load(Ordering::Relaxed));
+            if value.is_finite() {
+                lines.push(format_line(config, key, &value.to_string(), "g", None));
+            }
+        });
+        let rate = config.histogram_sample_rate.clamp(0.0, 1.0);
+        let rate = (rate < 1.0).then_some(rate);
+        for (key, state) in histograms.iter_mut() {
+            for value in state.pending.drain(..) {
+                lines.push(format_line(config, key, &value.to_string(), "ms", rate));
+            }
+        }
+        lines
+    }
+}
+
+/// The increment of a counter from `last` to `value`. A counter that went
+/// down was reset, so its whole value is new.
+fn counter_delta(last: u64, value: u64) -> u64 {
+    if value >= last {
+        value - last
+    } else {
+        value
+    }
+}
+
+/// A line like `prefix.name:value|kind|@rate|#tag:value`.
+fn format_line(
+    config: &StatsdConfig,
+    key: &metrics::Key,
+    value: &str,
+    kind: &str,
+    rate: Option<f64>,
+) -> String {
+    let mut line = String::new();
+    if let Some(prefix) = &config.prefix {
+        line.push_str(&sanitize_name(prefix));
+        line.push('.');
+    }
+    line.push_str(&sanitize_name(key.name()));
+    let _ = write!(line, ":{value}|{kind}");
+    if let Some(rate) = rate {
+        let _ = write!(line, "|@{rate}");
+    }
+    if config.dogstatsd_tags {
+        let tags: Vec<_> = config
+            .global_tags
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .chain(key.labels().map(|l| (l.key(), l.value())))
+            .map(|(k, v)| format!("{}:{}", sanitize_tag(k), sanitize_tag(v)))
+            .collect();
+        if !tags.is_empty() {
+            let _ = write!(line, "|#{}", tags.join(","));
+        }
+    }
+    line
+}
+
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' => '.',
+            ':' | '|' | '@' | '#' | ',' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn sanitize_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| match c {
+            ':' | '|' | '@' | '#' | ',' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Join `lines` with newlines into datagrams of at most `max_size` bytes,
+/// e.g. [`DEFAULT_MAX_DATAGRAM_SIZE`]. Lines longer than that get a datagram
+/// of their own.
+///
+/// ```
+/// # use bevy_metrics_dashboard::statsd::batch_lines;
+/// let lines = ["a:1|c", "b:2|c", "c:3|c", "a_much_longer_name:4|g"].map(String::from);
+/// assert_eq!(
+///     batch_lines(&lines, 11),
+///     ["a:1|c\nb:2|c", "c:3|c", "a_much_longer_name:4|g"]
+/// );
+/// assert_eq!(batch_lines(&lines, 1432), [lines.join("\n")]);
+/// ```
+pub fn batch_lines(lines: &[String], max_size: usize) -> Vec<String> {
+    let mut datagrams = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > max_size {
+            datagrams.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+    datagrams
+}
@@ -0,0 +1,343 @@
+//! Utilities for testing apps that use this crate.
+//!
+//! [`TestApp`] runs the dashboard's update systems (without drawing) against
+//! its own [`MetricsRegistry`] and a clock that advances by a fixed amount
+//! every frame. Each test gets an independent registry, so tests can run in
+//! parallel without installing a global recorder.
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_metrics_dashboard::test_utils::{
+//!     assert_plot_exists, latest_plot_value, plot_stats, TestApp,
+//! };
+//! use bevy_metrics_dashboard::{metrics::counter, metrics_util::MetricKind};
+//!
+//! fn count_frames() {
+//!     counter!("frames").increment(1);
+//! }
+//!
+//! let mut test = TestApp::new();
+//! test.app_mut().add_systems(Update, count_frames);
+//! test.spawn_window("Metrics");
+//! test.update();
+//! test.request_plot("frames", MetricKind::Counter);
+//! test.update_n(3);
+//!
+//! assert_plot_exists(test.app(), "frames");
+//! assert_eq!(latest_plot_value(test.app(), "frames"), Some(4.0));
+//!
+//! assert!(test.update_until_samples("frames", 10, 100));
+//! let stats = plot_stats(test.app(), "frames").unwrap();
+//! assert!(stats.percentile(95.0) <= stats.max());
+//! ```
+
+use crate::{
+    dashboard_window::RequestPlot,
+    plots::{MetricPlot, PlotStats},
+    registry::{MetricKey, MetricsRegistry},
+    DashboardWindow, HeadlessDashboardPlugin, RegistryPlugin,
+};
+use bevy::{
+    ecs::schedule::{ExecutorKind, Schedules},
+    prelude::*,
+    time::{TimePlugin, TimeUpdateStrategy},
+};
+use metrics_util::MetricKind;
+use std::time::Duration;
+
+/// The time that passes in each [`TestApp::update`] by default.
+pub const DEFAULT_FRAME_TIME: Duration = Duration::from_millis(16);
+
+/// An [`App`] with a local [`MetricsRegistry`] and a deterministic clock.
+///
+/// All schedules use the single-threaded executor, so metrics recorded by
+/// systems go to this app's registry.
+pub struct TestApp {
+    app: App,
+    registry: MetricsRegistry,
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestApp {
+    /// Create an app whose clock advances by [`DEFAULT_FRAME_TIME`] each frame.
+    pub fn new() -> Self {
+        Self::with_frame_time(DEFAULT_FRAME_TIME)
+    }
+
+    /// Create an app whose clock advances by `frame_time` each frame.
+    pub fn with_frame_time(frame_time: Duration) -> Self {
+        let registry = MetricsRegistry::new();
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), TimePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time))
+            .add_plugins(RegistryPlugin::local(registry.clone()))
+            .add_plugins(HeadlessDashboardPlugin::new());
+        Self { app, registry }
+    }
+
+    #[allow(missing_docs)]
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    #[allow(missing_docs)]
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// The registry that all metrics recorded during [`Self::update`] go to.
+    pub fn registry(&self) -> &MetricsRegistry {
+        &self.registry
+    }
+
+    /// Run one frame with this app's registry as the local recorder.
+    pub fn update(&mut self) {
+        let mut schedules = self.app.world_mut().resource_mut::<Schedules>();
+        for (_, schedule) in schedules.iter_mut() {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        }
+
+        let registry = self.registry.clone();
+        let _guard = registry.install_as_local();
+        self.app.update();
+    }
+
+    /// Run `n` frames.
+    pub fn update_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.update();
+        }
+    }
+
+    /// Run frames until `done` returns true, for at most `max_frames`.
+    ///
+    /// Returns false if `done` never returned true.
+    pub fn update_until(&mut self, max_frames: usize, mut done: impl FnMut(&App) -> bool) -> bool {
+        for _ in 0..max_frames {
+            if done(&self.app) {
+                return true;
+            }
+            self.update();
+        }
+        done(&self.app)
+    }
+
+    /// Run frames until the first plot of the metric named `name` has at
+    /// least `n` samples, for at most `max_frames`.
+    ///
+    /// Returns false if it didn't. See [`MetricPlot::n_samples`].
+    pub fn update_until_samples(&mut self, name: &str, n: usize, max_frames: usize) -> bool {
+        self.update_until(max_frames, |app| {
+            find_plot(app, name).is_some_and(|plot| plot.n_samples() >= n)
+        })
+    }
+
+    /// Spawn an empty [`DashboardWindow`].
+    pub fn spawn_window(&mut self, title: impl Into<String>) -> Entity {
+        self.app.world_mut().spawn(DashboardWindow::new(title)).id()
+    }
+
+    /// Pause or resume [`Time<Virtual>`], from the next frame on.
+    ///
+    /// Plots that [pause with virtual time](crate::DashboardWindowConfig::pause_with_virtual_time)
+    /// don't sample while it's paused, but other plots do.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_metrics_dashboard::test_utils::TestApp;
+    /// use bevy_metrics_dashboard::{metrics::gauge, metrics_util::MetricKind};
+    /// use bevy_metrics_dashboard::DashboardWindow;
+    ///
+    /// let mut test = TestApp::new();
+    /// test.app_mut()
+    ///     .add_systems(Update, || gauge!("speed").set(1.0));
+    /// let real = test.spawn_window("Real");
+    /// let mut window = DashboardWindow::new("Virtual");
+    /// window.set_pause_with_virtual_time(true);
+    /// let virt = test.app_mut().world_mut().spawn(window).id();
+    /// test.update();
+    /// test.request_plot("speed", MetricKind::Gauge);
+    /// test.update_n(5);
+    ///
+    /// // Plots only refresh at the UI refresh rate, so refresh them first.
+    /// let n_samples = |test: &mut TestApp, window| {
+    ///     let world = test.app_mut().world_mut();
+    ///     let mut window = world.get_mut::<DashboardWindow>(window).unwrap();
+    ///     window.refresh_plots();
+    ///     window.find_plot("speed").unwrap().n_samples()
+    /// };
+    /// let real_before = n_samples(&mut test, real);
+    /// let virt_before = n_samples(&mut test, virt);
+    /// assert!(virt_before > 0);
+    ///
+    /// test.set_virtual_time_paused(true);
+    /// test.update_n(5);
+    /// assert_eq!(n_samples(&mut test, real), real_before + 5);
+    /// assert_eq!(n_samples(&mut test, virt), virt_before);
+    ///
+    /// test.set_virtual_time_paused(false);
+    /// test.update_n(5);
+    /// assert_eq!(n_samples(&mut test, virt), virt_before + 5);
+    /// ```
+    pub fn set_virtual_time_paused(&mut self, paused: bool) {
+        let mut time = self.app.world_mut().resource_mut::<Time<Virtual>>();
+        if paused {
+            time.pause();
+        } else {
+            time.unpause();
+        }
+    }
+
+    /// Run the fixed timestep every `timestep` of virtual time.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_metrics_dashboard::plots::{CounterPlotConfig, MetricPlotConfig, SamplingMode};
+    /// use bevy_metrics_dashboard::registry::MetricKey;
+    /// use bevy_metrics_dashboard::test_utils::TestApp;
+    /// use bevy_metrics_dashboard::{metrics::counter, metrics_util::MetricKind};
+    /// use bevy_metrics_dashboard::{DashboardWindow, RequestPlot};
+    /// use std::time::Duration;
+    ///
+    /// // Five fixed ticks per frame.
+    /// let mut test = TestApp::with_frame_time(Duration::from_millis(50));
+    /// test.set_fixed_timestep(Duration::from_millis(10));
+    /// test.app_mut()
+    ///     .add_systems(FixedUpdate, || counter!("steps").increment(1));
+    /// let window = test.spawn_window("Physics");
+    /// test.update();
+    ///
+    /// let config = CounterPlotConfig {
+    ///     sampling: SamplingMode::PerFixedTick,
+    ///     ..default()
+    /// };
+    /// let key = MetricKey::new("steps".into(), MetricKind::Counter);
+    /// test.app_mut()
+    ///     .world_mut()
+    ///     .send_event(RequestPlot::new(key, None).with_config(MetricPlotConfig::Counter(config)));
+    /// test.update_n(4);
+    ///
+    /// let world = test.app_mut().world_mut();
+    /// let mut window = world.get_mut::<DashboardWindow>(window).unwrap();
+    /// window.refresh_plots();
+    /// // The plot was added after the ticks of the first of those frames.
+    /// assert_eq!(window.find_plot("steps").unwrap().n_samples(), 3 * 5);
+    /// ```
+    pub fn set_fixed_timestep(&mut self, timestep: Duration) {
+        self.app
+            .world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .set_timestep(timestep);
+    }
+
+    /// Send a [`RequestPlot`] for the metric with this name and no labels.
+    ///
+    /// The plot is added to every window on the next update.
+    pub fn request_plot(&mut self, name: impl Into<String>, kind: MetricKind) {
+        let key = MetricKey::new(metrics::Key::from_name(name.into()), kind);
+        self.app.world_mut().send_event(RequestPlot::new(key, None));
+    }
+}
+
+/// Panics unless some [`DashboardWindow`] in `app` plots a metric named `name`.
+#[track_caller]
+pub fn assert_plot_exists(app: &App, name: &str) {
+    assert!(
+        find_plot_value(app, |key| key.key.name() == name).is_some(),
+        "no plot of metric {name:?}"
+    );
+}
+
+/// The latest value of the first plot of the metric named `name`.
+///
+/// Returns `None` if there is no such plot, or it has no value yet. See
+/// [`MetricPlot::latest_value`](crate::plots::MetricPlot::latest_value).
+pub fn latest_plot_value(app: &App, name: &str) -> Option<f64> {
+    find_plot_value(app, |key| key.key.name() == name).flatten()
+}
+
+/// The latest value of the first plot of `key`.
+pub fn latest_plot_value_by_key(app: &App, key: &MetricKey) -> Option<f64> {
+    find_plot_value(app, |k| k == key).flatten()
+}
+
+/// The statistics of the first plot of the metric named `name`.
+///
+/// Returns `None` if there is no such plot, or it has no samples yet. See
+/// [`MetricPlot::stats`].
+pub fn plot_stats(app: &App, name: &str) -> Option<PlotStats> {
+    find_plot(app, name)?.stats()
+}
+
+/// The statistics of the trailing window of the first plot of the metric
+/// named `name`, e.g. for checking the p99 of the last seconds against a
+/// budget.
+///
+/// Returns `None` if there is no such plot, or it has no samples in its
+/// window. See [`MetricPlot::trailing_stats`].
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_metrics_dashboard::plots::{HistogramPlotConfig, MetricPlotConfig, TrailingWindow};
+/// use bevy_metrics_dashboard::registry::MetricKey;
+/// use bevy_metrics_dashboard::test_utils::{plot_trailing_stats, TestApp};
+/// use bevy_metrics_dashboard::{metrics::histogram, metrics_util::MetricKind, RequestPlot};
+///
+/// // 1000 steps per frame, of which 1% take 9 ms.
+/// fn step() {
+///     for i in 0..1000 {
+///         histogram!("step_ms").record(if i % 100 == 0 { 9.0 } else { 1.0 });
+///     }
+/// }
+///
+/// let mut test = TestApp::new();
+/// test.app_mut().add_systems(Update, step);
+/// test.spawn_window("Metrics");
+/// test.update();
+/// // The last 32 frames, at 16 ms per frame.
+/// let window = TrailingWindow {
+///     seconds: 0.5,
+///     max_samples_per_frame: 100,
+/// };
+/// let key = MetricKey::new(metrics::Key::from_name("step_ms"), MetricKind::Histogram);
+/// let config = MetricPlotConfig::Histogram(HistogramPlotConfig {
+///     trailing_window: Some(window),
+///     ..default()
+/// });
+/// test.app_mut()
+///     .world_mut()
+///     .send_event(RequestPlot::new(key, None).with_config(config));
+/// test.update_n(40);
+///
+/// let stats = plot_trailing_stats(test.app(), "step_ms").unwrap();
+/// assert_eq!(stats.n_samples(), 32 * 1000);
+/// assert_eq!(stats.percentile(98.0), 1.0);
+/// assert_eq!(stats.percentile(99.5), 9.0);
+/// ```
+pub fn plot_trailing_stats(app: &App, name: &str) -> Option<PlotStats> {
+    find_plot(app, name)?.trailing_stats()
+}
+
+/// The first plot of the metric named `name` in any [`DashboardWindow`].
+pub fn find_plot<'a>(app: &'a App, name: &str) -> Option<&'a MetricPlot> {
+    let world = app.world();
+    let mut windows = world.try_query::<&DashboardWindow>()?;
+    windows
+        .iter(world)
+        .find_map(|window| window.find_plot(name))
+}
+
+fn find_plot_value(app: &App, matches: impl Fn(&MetricKey) -> bool) -> Option<Option<f64>> {
+    let world = app.world();
+    let mut windows = world.try_query::<&DashboardWindow>()?;
+    windows
+        .iter(world)
+        .flat_map(DashboardWindow::plots)
+        .find(|plot| matches(plot.key()))
+        .map(|plot| plot.latest_value())
+}
@@ -0,0 +1,296 @@
+//! Color presets for the dashboard's own windows and areas.
+//!
+//! See [`DashboardConfig::theme`](crate::DashboardConfig::theme).
+
+use crate::egui::{self, Color32, ComboBox, Context, Stroke, Style, Ui, Visuals};
+use crate::localization::{DashboardLocalization, UiText};
+use bevy::prelude::Reflect;
+
+/// The look of all dashboard windows, the overlay and the widgets drawn in
+/// them.
+///
+/// The theme is applied with [`Self::scope`] while the dashboard draws, so
+/// the rest of the app keeps its own egui style.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum DashboardTheme {
+    /// Use the style of the egui context, with colors matching its
+    /// [dark mode](Visuals::dark_mode).
+    #[default]
+    Inherit,
+    #[allow(missing_docs)]
+    Dark,
+    #[allow(missing_docs)]
+    Light,
+    /// Dark, with black backgrounds, white text and saturated colors.
+    HighContrast,
+}
+
+impl DashboardTheme {
+    #[allow(missing_docs)]
+    pub const ALL: [Self; 4] = [Self::Inherit, Self::Dark, Self::Light, Self::HighContrast];
+
+    #[allow(missing_docs)]
+    pub fn label(self) -> UiText {
+        match self {
+            Self::Inherit => UiText::ThemeInherit,
+            Self::Dark => UiText::ThemeDark,
+            Self::Light => UiText::ThemeLight,
+            Self::HighContrast => UiText::ThemeHighContrast,
+        }
+    }
+
+    /// The visuals of this preset, or `None` to keep those of the context.
+    pub fn visuals(self) -> Option<Visuals> {
+        match self {
+            Self::Inherit => None,
+            Self::Dark => Some(Visuals::dark()),
+            Self::Light => Some(Visuals::light()),
+            Self::HighContrast => {
+                let mut visuals = Visuals::dark();
+                visuals.override_text_color = Some(Color32::WHITE);
+                visuals.panel_fill = Color32::BLACK;
+                visuals.window_fill = Color32::BLACK;
+                visuals.extreme_bg_color = Color32::BLACK;
+                visuals.faint_bg_color = Color32::from_gray(30);
+                visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.hyperlink_color = Color32::from_rgb(0, 200, 255);
+                visuals.warn_fg_color = Color32::YELLOW;
+                visuals.error_fg_color = Color32::from_rgb(255, 60, 60);
+                for widget in [
+                    &mut visuals.widgets.noninteractive,
+                    &mut visuals.widgets.inactive,
+                    &mut visuals.widgets.hovered,
+                    &mut visuals.widgets.active,
+                    &mut visuals.widgets.open,
+                ] {
+                    widget.fg_stroke.color = Color32::WHITE;
+                    widget.bg_stroke = Stroke::new(1.0, Color32::from_gray(200));
+                }
+                Some(visuals)
+            }
+        }
+    }
+
+    /// The colors of this preset, or of `visuals` if it inherits them.
+    pub fn palette(self, visuals: &Visuals) -> ThemePalette {
+        match self {
+            Self::Inherit => ThemePalette::from_visuals(visuals),
+            Self::Dark => ThemePalette::dark(),
+            Self::Light => ThemePalette::light(),
+            Self::HighContrast => ThemePalette::high_contrast(),
+        }
+    }
+
+    /// Draw the windows and areas in `add_contents` with this theme.
+    ///
+    /// The style of `ctx` is replaced for the duration of `add_contents`, so
+    /// window frames and title bars are themed too, and restored afterwards.
+    /// Widgets find the colors of the theme with [`ThemePalette::of`].
+    pub fn scope<R>(self, ctx: &Context, add_contents: impl FnOnce(&Context) -> R) -> R {
+        let style = ctx.style();
+        if let Some(visuals) = self.visuals() {
+            ctx.set_style(Style {
+                visuals,
+                ..(*style).clone()
+            });
+        }
+        let palette = self.palette(&ctx.style().visuals);
+        let outer_palette = ctx.data_mut(|d| {
+            let outer = d.get_temp::<ThemePalette>(palette_id());
+            d.insert_temp(palette_id(), palette);
+            outer
+        });
+
+        let inner = add_contents(ctx);
+
+        ctx.data_mut(|d| match outer_palette {
+            Some(outer) => d.insert_temp(palette_id(), outer),
+            None => d.remove::<ThemePalette>(palette_id()),
+        });
+        ctx.set_style(style);
+        inner
+    }
+
+    /// Draw a selector for the theme. Returns true if it changed.
+    pub fn selector_ui(&mut self, ui: &mut Ui) -> bool {
+        let l10n = DashboardLocalization::of(ui);
+        let mut changed = false;
+        ComboBox::from_id_salt("dashboard-theme")
+            .selected_text(l10n.format(UiText::Theme, &[("theme", &l10n.get(self.label()))]))
+            .show_ui(ui, |ui| {
+                for theme in Self::ALL {
+                    changed |= ui
+                        .selectable_value(self, theme, l10n.get(theme.label()))
+                        .changed();
+                }
+            });
+        changed
+    }
+}
+
+/// The colors the dashboard uses for its own drawing, beyond those of the egui
+/// [`Visuals`].
+///
+/// Colors that are part of a plot's or widget's configuration, like budget
+/// bar colors or dial zones, aren't affected by the theme.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThemePalette {
+    /// Regular text in metric descriptions.
+    pub text: Color32,
+    /// Secondary text, like descriptions and aliases, and inactive markers.
+    pub weak: Color32,
+    /// Characters matched by a search.
+    pub highlight: Color32,
+    /// Units, and the overflow buckets of histograms.
+    pub accent: Color32,
+    /// Metric labels.
+    pub label: Color32,
+    /// Active markers, like the freshness of search results.
+    pub good: Color32,
+    /// Errors and detected spikes.
+    pub error: Color32,
+    /// Lines drawn on top of plots, like dial needles and budget lines.
+    pub foreground: Color32,
+    /// Outlines separating shapes drawn next to each other.
+    pub outline: Color32,
+    /// The background of dials.
+    pub track: Color32,
+    /// Colors for drawing several series in one plot, in order.
+    pub series: [Color32; 3],
+}
+
+impl Default for ThemePalette {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl ThemePalette {
+    #[allow(missing_docs)]
+    pub fn dark() -> Self {
+        Self {
+            text: Color32::WHITE,
+            weak: Color32::GRAY,
+            highlight: Color32::GOLD,
+            accent: Color32::LIGHT_BLUE,
+            label: Color32::YELLOW,
+            good: Color32::GREEN,
+            error: Color32::RED,
+            foreground: Color32::WHITE,
+            outline: Color32::BLACK,
+            track: Color32::DARK_GRAY,
+            series: [Color32::LIGHT_BLUE, Color32::ORANGE, Color32::LIGHT_GREEN],
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn light() -> Self {
+        Self {
+            text: Color32::from_gray(20),
+            weak: Color32::from_gray(100),
+            highlight: Color32::from_rgb(190, 110, 0),
+            accent: Color32::from_rgb(0, 80, 190),
+            label: Color32::from_rgb(140, 90, 0),
+            good: Color32::from_rgb(0, 140, 0),
+            error: Color32::from_rgb(200, 0, 0),
+            foreground: Color32::BLACK,
+            outline: Color32::WHITE,
+            track: Color32::LIGHT_GRAY,
+            series: [
+                Color32::from_rgb(0, 90, 200),
+                Color32::from_rgb(220, 100, 0),
+                Color32::from_rgb(0, 140, 60),
+            ],
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Color32::WHITE,
+            weak: Color32::LIGHT_GRAY,
+            highlight: Color32::YELLOW,
+            accent: Color32::from_rgb(0, 200, 255),
+            label: Color32::from_rgb(255, 130, 255),
+            good: Color32::from_rgb(0, 255, 0),
+            error: Color32::from_rgb(255, 60, 60),
+            foreground: Color32::WHITE,
+            outline: Color32::BLACK,
+            track: Color32::GRAY,
+            series: [
+                Color32::from_rgb(0, 200, 255),
+                Color32::YELLOW,
+                Color32::from_rgb(255, 130, 255),
+            ],
+        }
+    }
+
+    /// The dark or light palette, matching `visuals`.
+    pub fn from_visuals(visuals: &Visuals) -> Self {
+        if visuals.dark_mode {
+            Self::dark()
+        } else {
+            Self::light()
+        }
+    }
+
+    /// The palette of the [`DashboardTheme`] that `ui` is drawn with, or the
+    /// one matching its visuals outside of [`DashboardTheme::scope`].
+    pub fn of(ui: &Ui) -> Self {
+        ui.ctx()
+            .data(|d| d.get_temp(palette_id()))
+            .unwrap_or_else(|| Self::from_visuals(ui.visuals()))
+    }
+}
+
+fn palette_id() -> egui::Id {
+    egui::Id::new("bevy_metrics_dashboard_theme_palette")
+}
+
+/// A color in a plot's or widget's configuration.
+///
+/// Unlike [`Color32`], this can be [reflected](Reflect), e.g. to edit it in
+/// an inspector. It's stored unmultiplied, and saved in
+/// [layouts](crate::layout) the same way as a [`Color32`].
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(from = "Color32", into = "Color32"))]
+pub struct DashboardColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl DashboardColor {
+    /// An opaque color.
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Shows a button that opens a color picker for this color.
+    pub fn edit_ui(&mut self, ui: &mut Ui) -> egui::Response {
+        let before = Color32::from(*self);
+        let mut color = before;
+        let response = ui.color_edit_button_srgba(&mut color);
+        if color != before {
+            *self = color.into();
+        }
+        response
+    }
+}
+
+impl From<Color32> for DashboardColor {
+    fn from(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        Self { r, g, b, a }
+    }
+}
+
+impl From<DashboardColor> for Color32 {
+    fn from(DashboardColor { r, g, b, a }: DashboardColor) -> Self {
+        Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    core_metrics::names,
+    latest_value::LatestValue,
+    localization::{DashboardLocalization, UiText},
+    registry::MetricKey,
+    snapshot::FrameMetricsSnapshot,
+    stats::percentile,
+};
+use bevy::prelude::{default, Reflect};
+use metrics_util::MetricKind;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Which core metrics are summarized in a
+/// [`DashboardWindow`](crate::DashboardWindow)'s title.
+///
+/// Metrics that aren't registered are left out.
+#[derive(Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct TitleSummaryConfig {
+    /// Show the current frames per second.
+    pub fps: bool,
+    /// Show a percentile of the recent frame times.
+    pub frame_time: bool,
+    /// The frame time percentile, in `0.0..=100.0`.
+    pub percentile: f64,
+    /// How many seconds of frame times the percentile is computed over.
+    pub percentile_window: f64,
+    /// Show the number of entities.
+    pub entities: bool,
+}
+
+impl Default for TitleSummaryConfig {
+    fn default() -> Self {
+        Self {
+            fps: true,
+            frame_time: true,
+            percentile: 95.0,
+            percentile_window: 5.0,
+            entities: true,
+        }
+    }
+}
+
+/// Samples the core metrics shown in a window title.
+pub struct TitleSummary {
+    fps: LatestValue,
+    frame_time: LatestValue,
+    entities: LatestValue,
+    frame_times: VecDeque<(Instant, f64)>,
+    text: String,
+}
+
+impl Default for TitleSummary {
+    fn default() -> Self {
+        let gauge = |name| {
+            LatestValue::new(MetricKey::new(
+                metrics::Key::from_static_name(name),
+                MetricKind::Gauge,
+            ))
+        };
+        Self {
+            fps: gauge(names::FRAMES_PER_SECOND),
+            frame_time: gauge(names::FRAME_TIME),
+            entities: gauge(names::ENTITIES),
+            frame_times: default(),
+            text: default(),
+        }
+    }
+}
+
+impl TitleSummary {
+    /// The summary as of the last [`Self::refresh`], or empty.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The metrics sampled by [`Self::collect`].
+    pub fn keys(&self) -> impl Iterator<Item = &MetricKey> {
+        [&self.fps, &self.frame_time, &self.entities]
+            .into_iter()
+            .map(LatestValue::key)
+    }
+
+    /// Sample the metrics. This should run every frame.
+    pub fn collect(&mut self, snapshot: &FrameMetricsSnapshot, config: &TitleSummaryConfig) {
+        let now = Instant::now();
+        for value in [&mut self.fps, &mut self.frame_time, &mut self.entities] {
+            value.update(snapshot);
+        }
+
+        if !config.frame_time {
+            self.frame_times.clear();
+            return;
+        }
+        if let Some(frame_time) = self.frame_time.latest() {
+            self.frame_times.push_back((now, frame_time));
+        }
+        let window = Duration::try_from_secs_f64(config.percentile_window).unwrap_or_default();
+        while self
+            .frame_times
+            .front()
+            .is_some_and(|&(time, _)| now - time > window)
+        {
+            self.frame_times.pop_front();
+        }
+    }
+
+    /// Recompute the text, translated by `l10n`.
+    pub fn refresh(&mut self, config: &TitleSummaryConfig, l10n: &DashboardLocalization) {
+        let mut parts = Vec::new();
+        if let (true, Some(fps)) = (config.fps, self.fps.latest()) {
+            parts.push(l10n.format(UiText::FpsSummary, &[("fps", &l10n.number(fps, 0))]));
+        }
+        if config.frame_time {
+            let mut frame_times: Vec<_> = self.frame_times.iter().map(|&(_, t)| t).collect();
+            if let Some(p) = percentile(&mut frame_times, config.percentile) {
+                parts.push(l10n.format(
+                    UiText::FrameTimeSummary,
+                    &[
+                        ("percentile", &config.percentile),
+                        ("frame_time", &l10n.number(p, 1)),
+                    ],
+                ));
+            }
+        }
+        if let (true, Some(entities)) = (config.entities, self.entities.latest()) {
+            parts.push(l10n.format(
+                UiText::EntitiesSummary,
+                &[("entities", &l10n.number(entities, 0))],
+            ));
+        }
+        self.text = parts.join(" | ");
+    }
+}
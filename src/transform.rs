@@ -0,0 +1,358 @@
+//! Transforms applied to a plot's values before they are drawn.
+//!
+//! See [`PlotTransform`].
+
+use crate::egui::{ComboBox, DragValue, Ui};
+use crate::localization::{DashboardLocalization, UiText};
+use crate::registry::MetricUnit;
+use crate::unit_str;
+use bevy::prelude::Reflect;
+use metrics::Unit;
+
+/// A chain of [`TransformStep`]s from the metric's values to the plotted ones,
+/// e.g. to convert radians to degrees or flip the sign of a gauge.
+///
+/// Plots keep the raw samples and transform them when they are
+/// [refreshed](crate::plots::MetricPlot::refresh), so editing the chain
+/// applies to the whole buffer. The transformed values are used everywhere
+/// the plot shows or reports values: lines, dials, bar charts, the latest
+/// value, [stats](crate::plots::MetricPlot::stats) and spike detection
+/// thresholds.
+///
+/// Values entered in the plot's settings, like spike thresholds, dial ranges
+/// and bucket bounds, are in transformed units too, so they are entered in
+/// the displayed unit.
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "layout", serde(default))]
+pub struct PlotTransform {
+    /// Converts values to another unit of the metric's unit family before
+    /// the [steps](Self::steps), e.g. to show seconds as milliseconds.
+    #[reflect(ignore)]
+    pub conversion: Option<UnitConversion>,
+    /// Applied in order.
+    pub steps: Vec<TransformStep>,
+    /// Replaces the unit of the metric on axes and dials, since transforms
+    /// usually change it.
+    pub unit: Option<String>,
+}
+
+/// One step of a [`PlotTransform`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub enum TransformStep {
+    /// Multiply by a constant.
+    Scale(f64),
+    /// Add a constant.
+    Offset(f64),
+    #[allow(missing_docs)]
+    Abs,
+    /// Restrict to `min..=max`.
+    #[allow(missing_docs)]
+    Clamp { min: f64, max: f64 },
+    /// Replace `x` with `1 / x`. Zero becomes infinite.
+    Reciprocal,
+}
+
+impl TransformStep {
+    /// One step of each kind, with neutral parameters where possible.
+    pub const ALL: [Self; 5] = [
+        Self::Scale(1.0),
+        Self::Offset(0.0),
+        Self::Abs,
+        Self::Clamp {
+            min: 0.0,
+            max: 100.0,
+        },
+        Self::Reciprocal,
+    ];
+
+    #[allow(missing_docs)]
+    pub fn apply(self, value: f64) -> f64 {
+        match self {
+            Self::Scale(factor) => value * factor,
+            Self::Offset(offset) => value + offset,
+            Self::Abs => value.abs(),
+            Self::Clamp { min, max } => value.clamp(min, max),
+            Self::Reciprocal => value.recip(),
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn label(self) -> UiText {
+        match self {
+            Self::Scale(_) => UiText::Scale,
+            Self::Offset(_) => UiText::Offset,
+            Self::Abs => UiText::AbsoluteValue,
+            Self::Clamp { .. } => UiText::Clamp,
+            Self::Reciprocal => UiText::Reciprocal,
+        }
+    }
+
+    fn configure_ui(&mut self, ui: &mut Ui) {
+        let l10n = DashboardLocalization::of(ui);
+        ui.label(l10n.get(self.label()));
+        match self {
+            Self::Scale(value) | Self::Offset(value) => {
+                ui.add(DragValue::new(value).speed(0.01));
+            }
+            Self::Clamp { min, max } => {
+                ui.add(
+                    DragValue::new(min)
+                        .prefix(l10n.get(UiText::MinPrefix))
+                        .speed(0.1),
+                );
+                *max = max.max(*min);
+                ui.add(
+                    DragValue::new(max)
+                        .prefix(l10n.get(UiText::MaxPrefix))
+                        .speed(0.1),
+                );
+                *min = min.min(*max);
+            }
+            Self::Abs | Self::Reciprocal => {}
+        }
+    }
+}
+
+/// A conversion between two units of the same family, like milliseconds
+/// and seconds, or bytes and mebibytes. See [`PlotTransform::conversion`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "layout", derive(serde::Deserialize, serde::Serialize))]
+pub struct UnitConversion {
+    /// The unit the metric is recorded in.
+    #[cfg_attr(feature = "layout", serde(with = "unit_name"))]
+    pub from: Unit,
+    /// The unit values are displayed in.
+    #[cfg_attr(feature = "layout", serde(with = "unit_name"))]
+    pub to: Unit,
+}
+
+/// Units that convert into each other, with their size in the first unit.
+const UNIT_FAMILIES: [&[(Unit, f64)]; 3] = [
+    &[
+        (Unit::Seconds, 1.0),
+        (Unit::Milliseconds, 1e-3),
+        (Unit::Microseconds, 1e-6),
+        (Unit::Nanoseconds, 1e-9),
+    ],
+    &[
+        (Unit::Bytes, 1.0),
+        (Unit::Kibibytes, 1024.0),
+        (Unit::Mebibytes, 1024.0 * 1024.0),
+        (Unit::Gibibytes, 1024.0 * 1024.0 * 1024.0),
+        (Unit::Tebibytes, 1024.0 * 1024.0 * 1024.0 * 1024.0),
+    ],
+    &[
+        (Unit::BitsPerSecond, 1.0),
+        (Unit::KilobitsPerSecond, 1e3),
+        (Unit::MegabitsPerSecond, 1e6),
+        (Unit::GigabitsPerSecond, 1e9),
+        (Unit::TerabitsPerSecond, 1e12),
+    ],
+];
+
+impl UnitConversion {
+    /// Convert from `from` to `to`, or `None` if they aren't in the same
+    /// family.
+    pub fn new(from: Unit, to: Unit) -> Option<Self> {
+        Self::units_like(from)
+            .contains(&to)
+            .then_some(Self { from, to })
+    }
+
+    /// The units that `unit` converts to, including itself. Empty if it
+    /// doesn't convert to any other unit, like [`Unit::Count`].
+    pub fn units_like(unit: Unit) -> Vec<Unit> {
+        family(unit)
+            .map(|family| family.iter().map(|&(unit, _)| unit).collect())
+            .unwrap_or_default()
+    }
+
+    /// The number of [`Self::to`] in one [`Self::from`].
+    pub fn factor(self) -> f64 {
+        match (size(self.from), size(self.to)) {
+            (Some(from), Some(to)) if family(self.from) == family(self.to) => from / to,
+            _ => 1.0,
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn apply(self, value: f64) -> f64 {
+        value * self.factor()
+    }
+}
+
+fn family(unit: Unit) -> Option<&'static [(Unit, f64)]> {
+    UNIT_FAMILIES
+        .into_iter()
+        .find(|family| family.iter().any(|&(u, _)| u == unit))
+}
+
+fn size(unit: Unit) -> Option<f64> {
+    family(unit)?
+        .iter()
+        .find_map(|&(u, size)| (u == unit).then_some(size))
+}
+
+impl PlotTransform {
+    /// Convert radians to degrees, and label the values as degrees.
+    pub fn radians_to_degrees() -> Self {
+        Self {
+            steps: vec![TransformStep::Scale(180.0 / std::f64::consts::PI)],
+            unit: Some("°".into()),
+            ..Default::default()
+        }
+    }
+
+    /// Display values recorded in `from` in `to` instead, e.g. seconds as
+    /// milliseconds. Units of different families are left unconverted.
+    ///
+    /// ```
+    /// use bevy_metrics_dashboard::{metrics::Unit, transform::PlotTransform};
+    ///
+    /// let transform = PlotTransform::default().with_conversion(Unit::Seconds, Unit::Milliseconds);
+    /// assert_eq!(transform.apply(0.0166), 16.6);
+    /// assert_eq!(transform.displayed_unit(None).as_deref(), Some("ms"));
+    ///
+    /// let ignored = PlotTransform::default().with_conversion(Unit::Seconds, Unit::Bytes);
+    /// assert!(ignored.is_identity());
+    /// ```
+    pub fn with_conversion(mut self, from: Unit, to: Unit) -> Self {
+        self.conversion = UnitConversion::new(from, to);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn with_step(mut self, step: TransformStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// See [`Self::unit`].
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Whether values are plotted unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.conversion.is_none() && self.steps.is_empty()
+    }
+
+    /// Apply the conversion and all steps to `value`, in order.
+    pub fn apply(&self, value: f64) -> f64 {
+        let value = self.conversion.map_or(value, |c| c.apply(value));
+        self.steps
+            .iter()
+            .fold(value, |value, step| step.apply(value))
+    }
+
+    /// The unit that transformed values are in, given the `unit` of the
+    /// metric: the overridden [unit](Self::unit), or the one converted to.
+    pub fn displayed_unit(&self, unit: Option<&MetricUnit>) -> Option<String> {
+        self.unit
+            .clone()
+            .or_else(|| self.conversion.map(|c| unit_str(c.to).to_owned()))
+            .or_else(|| unit.map(|u| u.as_str().to_owned()))
+    }
+
+    /// Edit the conversion, steps and unit, previewing the transform of
+    /// `latest`, the latest raw value of the plot. Only units of the same
+    /// family as `unit`, the unit of the metric, can be converted to.
+    pub(crate) fn configure_ui(
+        &mut self,
+        unit: Option<&MetricUnit>,
+        latest: Option<f64>,
+        ui: &mut Ui,
+    ) {
+        let l10n = DashboardLocalization::of(ui);
+        if let Some(&MetricUnit::Standard(from)) = unit {
+            self.conversion_ui(from, ui);
+        }
+        let mut remove = None;
+        let mut move_up = None;
+        for (i, step) in self.steps.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                step.configure_ui(ui);
+                if i > 0 && ui.small_button(l10n.get(UiText::MoveUp)).clicked() {
+                    move_up = Some(i);
+                }
+                if ui.small_button(l10n.get(UiText::RemoveRule)).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = move_up {
+            self.steps.swap(i - 1, i);
+        }
+        if let Some(i) = remove {
+            self.steps.remove(i);
+        }
+        ui.menu_button(l10n.get(UiText::AddStep), |ui| {
+            for step in TransformStep::ALL {
+                if ui.button(l10n.get(step.label())).clicked() {
+                    self.steps.push(step);
+                    ui.close_menu();
+                }
+            }
+        });
+
+        let mut override_unit = self.unit.is_some();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut override_unit, l10n.get(UiText::OverrideUnit));
+            if override_unit {
+                ui.text_edit_singleline(self.unit.get_or_insert_with(String::new));
+            } else {
+                self.unit = None;
+            }
+        });
+
+        if let (Some(latest), false) = (latest, self.is_identity()) {
+            ui.weak(l10n.format(
+                UiText::TransformPreview,
+                &[
+                    ("raw", &l10n.number(latest, 3)),
+                    ("value", &l10n.number(self.apply(latest), 3)),
+                ],
+            ));
+        }
+    }
+    /// Choose the unit that values recorded in `from` are displayed in.
+    fn conversion_ui(&mut self, from: Unit, ui: &mut Ui) {
+        let units = UnitConversion::units_like(from);
+        if units.is_empty() {
+            return;
+        }
+        let l10n = DashboardLocalization::of(ui);
+        // The metric's unit may have changed since the conversion was chosen.
+        if self.conversion.is_some_and(|c| c.from != from) {
+            self.conversion = None;
+        }
+        let mut to = self.conversion.map_or(from, |c| c.to);
+        ComboBox::from_id_salt("display-unit")
+            .selected_text(l10n.format(UiText::DisplayUnit, &[("unit", &unit_str(to))]))
+            .show_ui(ui, |ui| {
+                for unit in units {
+                    ui.selectable_value(&mut to, unit, unit_str(unit));
+                }
+            });
+        self.conversion = (to != from).then_some(UnitConversion { from, to });
+    }
+}
+
+/// Units are written with their `metrics` name, like `"milliseconds"`.
+#[cfg(feature = "layout")]
+mod unit_name {
+    use metrics::Unit;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(unit: &Unit, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(unit.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Unit, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Unit::from_string(&name).ok_or_else(|| D::Error::custom(format!("unknown unit {name:?}")))
+    }
+}
@@ -0,0 +1,19 @@
+/// Strip module paths from a type name, including those of generic
+/// arguments, e.g. `Option<my_game::Item>` becomes `Option<Item>`.
+pub(crate) fn short_type_name(full_name: &str) -> String {
+    let mut short = String::with_capacity(full_name.len());
+    let mut segment_start = 0;
+    for (i, c) in full_name.char_indices() {
+        if matches!(c, '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | '&' | ';') {
+            short.push_str(last_path_segment(&full_name[segment_start..i]));
+            short.push(c);
+            segment_start = i + c.len_utf8();
+        }
+    }
+    short.push_str(last_path_segment(&full_name[segment_start..]));
+    short
+}
+
+fn last_path_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
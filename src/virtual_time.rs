@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+/// Whether [`Time<Virtual>`] advanced in the current frame, for plots that
+/// [pause with virtual time](crate::DashboardWindowConfig::pause_with_virtual_time).
+///
+/// This only decides whether a sample is taken. Samples aren't timestamped
+/// with virtual time: the x axis counts samples and rates are per sample,
+/// so plots aren't stretched or compressed when [`Time<Virtual>`] is
+/// scaled.
+///
+/// Updated in the [`SampleMetricsSet`](crate::SampleMetricsSet), before the
+/// plots are sampled. The default is `true`.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct VirtualTimeAdvanced(pub bool);
+
+impl Default for VirtualTimeAdvanced {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+impl VirtualTimeAdvanced {
+    /// Whether a plot takes a sample in this frame, given whether it
+    /// pauses with virtual time.
+    pub fn samples(&self, pause_with_virtual_time: bool) -> bool {
+        self.0 || !pause_with_virtual_time
+    }
+
+    /// Bevy system that records whether virtual time advanced in this frame.
+    pub(crate) fn update_system(mut advanced: ResMut<Self>, time: Res<Time<Virtual>>) {
+        advanced.set_if_neq(Self(!time.delta().is_zero()));
+    }
+}
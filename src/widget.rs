@@ -0,0 +1,252 @@
+//! Custom widgets drawn in [`DashboardWindow`](crate::DashboardWindow)s next
+//! to the built-in plots.
+//!
+//! The built-in plots, budget bars and other widgets implement it too.
+//!
+//! Implement [`DashboardWidget`] and register the type with
+//! `DashboardPlugin::widget` to add it from the "Add Widget" menu of every
+//! dashboard window. Widgets can be closed and reordered like the built-in
+//! ones, and with the `layout` feature, they're saved and restored with the
+//! [`DashboardLayout`](crate::layout::DashboardLayout), tagged with their
+//! [`id`](DashboardWidget::id). Widgets that return their state from
+//! [`DashboardWidget::reflect_state`] are reflected as part of the
+//! [`DashboardWindow`](crate::DashboardWindow), e.g. for inspectors.
+//!
+//! ```
+//! # use bevy_metrics_dashboard::{egui::Ui, metrics_util::MetricKind};
+//! # use bevy_metrics_dashboard::registry::MetricKey;
+//! # use bevy_metrics_dashboard::widget::{DashboardWidget, WidgetContext};
+//! # use bevy_metrics_dashboard::{metrics::Key, FrameMetricsSnapshot, SnapshotValue};
+//! /// Shows the highest value of the `health` gauge so far.
+//! struct PeakHealth {
+//!     key: MetricKey,
+//!     peak: f64,
+//! }
+//!
+//! impl Default for PeakHealth {
+//!     fn default() -> Self {
+//!         Self {
+//!             key: MetricKey::new(Key::from_name("health"), MetricKind::Gauge),
+//!             peak: 0.0,
+//!         }
+//!     }
+//! }
+//!
+//! impl DashboardWidget for PeakHealth {
+//!     fn title(&self, _ctx: &WidgetContext) -> String {
+//!         "Peak Health".into()
+//!     }
+//!
+//!     fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+//!         if let Some(SnapshotValue::Gauge(value)) = snapshot.get(&self.key) {
+//!             self.peak = self.peak.max(*value);
+//!         }
+//!     }
+//!
+//!     fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+//!         Box::new(std::iter::once(&self.key))
+//!     }
+//!
+//!     fn ui(&mut self, ui: &mut Ui, _ctx: &WidgetContext) {
+//!         ui.label(format!("{:.1}", self.peak));
+//!     }
+//! }
+//! ```
+
+use crate::budget_bar::BudgetBar;
+use crate::dashboard_window::{CachedPlotConfigs, DashboardConfig, DashboardWindowConfig};
+use crate::difference_plot::DifferencePlot;
+use crate::egui::Ui;
+use crate::leaderboard::Leaderboard;
+use crate::plot_style::PlotStyle;
+use crate::registry::{MetricKey, MetricUnit, MetricsRegistry};
+use crate::scatter_plot::ScatterPlot;
+use crate::snapshot::FrameMetricsSnapshot;
+use crate::snapshot_diff::SnapshotDiff;
+use bevy::prelude::Reflect;
+use std::{cell::RefCell, sync::Arc};
+
+#[cfg(feature = "layout")]
+use ron::value::RawValue;
+
+/// What a [`DashboardWidget`] can read while it's drawn.
+pub struct WidgetContext<'a> {
+    #[allow(missing_docs)]
+    pub registry: &'a MetricsRegistry,
+    /// The metrics captured this frame, including those returned by
+    /// [`DashboardWidget::consumed_metrics`].
+    pub snapshot: &'a FrameMetricsSnapshot,
+    /// See [`DashboardConfig::plot_style`].
+    pub style: &'a PlotStyle,
+    #[allow(missing_docs)]
+    pub dashboard: &'a DashboardConfig,
+    /// The config of the window the widget is drawn in.
+    pub window: &'a DashboardWindowConfig,
+    /// Settings of closed plots, for restoring them when the same metrics
+    /// are chosen again.
+    pub cached_configs: &'a CachedPlotConfigs,
+    pub(crate) requested_plots: RefCell<Vec<(MetricKey, Option<MetricUnit>)>>,
+}
+
+impl WidgetContext<'_> {
+    /// Plot the metric `key` in the widget's window, once all widgets are
+    /// drawn. Ignored while the whole dashboard is
+    /// [read-only](crate::ReadOnly::All).
+    pub fn request_plot(&self, key: MetricKey, unit: Option<MetricUnit>) {
+        self.requested_plots.borrow_mut().push((key, unit));
+    }
+}
+
+/// A widget drawn in a collapsible section of a
+/// [`DashboardWindow`](crate::DashboardWindow).
+pub trait DashboardWidget: Send + Sync + 'static {
+    /// Identifies the widget's type in saved layouts and the
+    /// [`DashboardWidgetTypes`].
+    ///
+    /// Defaults to the type name, which can change when the type is moved or
+    /// renamed. Override it to keep saved layouts loading.
+    fn id(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// The header of the widget's section.
+    fn title(&self, ctx: &WidgetContext) -> String;
+
+    /// Draw the widget below its header.
+    fn ui(&mut self, ui: &mut Ui, ctx: &WidgetContext);
+
+    /// Sample metrics from the frame's `snapshot`. Runs in the
+    /// [`SampleMetricsSet`](crate::SampleMetricsSet) unless the window is
+    /// paused.
+    fn update(&mut self, snapshot: &FrameMetricsSnapshot) {
+        let _ = snapshot;
+    }
+
+    /// The metrics that need to be in the [`FrameMetricsSnapshot`].
+    fn consumed_metrics(&self) -> Box<dyn Iterator<Item = &MetricKey> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    /// Called when the widget is closed, e.g. to remember its settings in
+    /// `cached_configs`.
+    fn on_close(&mut self, cached_configs: &mut CachedPlotConfigs) {
+        let _ = cached_configs;
+    }
+
+    /// The widget's state to [reflect](Reflect) as the `widget` field of its
+    /// window's widget list, or `None` to reflect no fields. Usually `self`,
+    /// for widgets that derive [`Reflect`].
+    fn reflect_state(&self) -> Option<&dyn Reflect> {
+        None
+    }
+
+    /// Mutable access to the [state](Self::reflect_state).
+    fn reflect_state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    #[cfg(feature = "layout")]
+    /// The widget's settings for saving in a layout, or `None` to save only
+    /// its [`id`](Self::id).
+    fn save_config(&self) -> Result<Option<Box<RawValue>>, ron::Error> {
+        Ok(None)
+    }
+
+    #[cfg(feature = "layout")]
+    /// Restore settings saved by [`Self::save_config`].
+    fn load_config(&mut self, config: &RawValue) -> Result<(), ron::error::SpannedError> {
+        let _ = config;
+        Ok(())
+    }
+}
+
+/// A type of [`DashboardWidget`] that windows can create.
+#[derive(Clone)]
+pub struct WidgetType {
+    id: String,
+    label: String,
+    built_in: bool,
+    create: Arc<dyn Fn() -> Box<dyn DashboardWidget> + Send + Sync>,
+}
+
+impl WidgetType {
+    fn of<W: DashboardWidget + Default>(label: String, built_in: bool) -> Self {
+        Self {
+            id: W::default().id().to_owned(),
+            label,
+            built_in,
+            create: Arc::new(|| Box::new(W::default())),
+        }
+    }
+
+    /// The [`DashboardWidget::id`] of widgets of this type.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The entry of this type in the "Add Widget" menu.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Create a widget of this type with default settings.
+    pub fn create(&self) -> Box<dyn DashboardWidget> {
+        (self.create)()
+    }
+}
+
+/// The widget types that [dashboard windows](crate::DashboardWindow) can
+/// create, either from their "Add Widget" menu or when applying a layout.
+///
+/// See [`DashboardConfig::widget_types`](crate::DashboardConfig::widget_types).
+/// The default includes the [`BudgetBar`], [`ScatterPlot`],
+/// [`DifferencePlot`], [`Leaderboard`] and [`SnapshotDiff`], which have
+/// their own toolbar buttons instead of menu entries.
+/// [`MetricPlot`](crate::plots::MetricPlot)s are created from the search
+/// bar, so they aren't listed.
+#[derive(Clone)]
+pub struct DashboardWidgetTypes {
+    types: Vec<WidgetType>,
+}
+
+impl Default for DashboardWidgetTypes {
+    fn default() -> Self {
+        let mut types = Self { types: Vec::new() };
+        types.register_built_in::<BudgetBar>();
+        types.register_built_in::<ScatterPlot>();
+        types.register_built_in::<DifferencePlot>();
+        types.register_built_in::<Leaderboard>();
+        types.register_built_in::<SnapshotDiff>();
+        types
+    }
+}
+
+impl DashboardWidgetTypes {
+    /// Add `W` to the "Add Widget" menu as `label`, replacing any type with
+    /// the same [`DashboardWidget::id`].
+    pub fn register<W: DashboardWidget + Default>(&mut self, label: impl Into<String>) {
+        self.insert(WidgetType::of::<W>(label.into(), false));
+    }
+
+    fn register_built_in<W: DashboardWidget + Default>(&mut self) {
+        self.insert(WidgetType::of::<W>(String::new(), true));
+    }
+
+    fn insert(&mut self, widget_type: WidgetType) {
+        match self.types.iter_mut().find(|t| t.id == widget_type.id) {
+            Some(existing) => *existing = widget_type,
+            None => self.types.push(widget_type),
+        }
+    }
+
+    /// The type with this [`DashboardWidget::id`].
+    pub fn get(&self, id: &str) -> Option<&WidgetType> {
+        self.types.iter().find(|t| t.id == id)
+    }
+
+    /// The types listed in the "Add Widget" menu, in the order they were
+    /// registered.
+    pub fn menu_entries(&self) -> impl Iterator<Item = &WidgetType> {
+        self.types.iter().filter(|t| !t.built_in)
+    }
+}